@@ -67,7 +67,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let updated_project = db.update_project(
         project2.id,
         format!("API v2.1 Test Updated {}", timestamp),
-        Some("Enhanced API with new endpoints and improved performance".to_string())
+        Some("Enhanced API with new endpoints and improved performance".to_string()),
+        None,
+        None
     ).await?;
 
     println!("   ✅ Updated project: {} -> {}", project2.name, updated_project.name);
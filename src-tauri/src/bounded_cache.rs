@@ -0,0 +1,286 @@
+// Size-bounded LRU shared by the in-memory caches (PR list, files changed, deployments). Each cache is
+// capped by entry count and by an estimate of its size; the least recently used entries go
+// first once either bound is passed. Bounds come from the cache_limits setting and apply
+// process-wide, like the rate-limit budgets; a cache trims to new bounds on its next insert.
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::{LazyLock, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+pub const CACHE_LIMITS_KEY: &str = "cache_limits";
+
+const MIB: usize = 1024 * 1024;
+// Anything smaller couldn't hold a single PR list
+const MIN_MAX_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheName {
+    PrList,
+    PrFiles,
+    Deployments,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheBounds {
+    pub max_entries: usize,
+    pub max_bytes: usize, // estimated from the serialized size
+}
+
+/// Setting value, e.g. {"pr_list": {"max_entries": 1, "max_bytes": 33554432}, "pr_files": {...}}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheLimits {
+    pub pr_list: CacheBounds,  // only ever holds the default-sorted list, so one entry
+    pub pr_files: CacheBounds,
+    pub deployments: CacheBounds,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        CacheLimits {
+            pr_list: CacheBounds { max_entries: 1, max_bytes: 32 * MIB },
+            pr_files: CacheBounds { max_entries: 200, max_bytes: 16 * MIB },
+            deployments: CacheBounds { max_entries: 200, max_bytes: MIB },
+        }
+    }
+}
+
+impl CacheLimits {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let limits: CacheLimits = serde_json::from_str(value).map_err(|e| format!("Invalid cache limits: {}", e))?;
+        for bounds in [limits.pr_list, limits.pr_files, limits.deployments] {
+            if bounds.max_entries == 0 {
+                return Err("Cache limits need at least one entry".to_string());
+            }
+            if bounds.max_bytes < MIN_MAX_BYTES {
+                return Err(format!("Cache limits need at least {} KB", MIN_MAX_BYTES / 1024));
+            }
+        }
+        Ok(limits)
+    }
+
+    pub fn bounds(&self, cache: CacheName) -> CacheBounds {
+        match cache {
+            CacheName::PrList => self.pr_list,
+            CacheName::PrFiles => self.pr_files,
+            CacheName::Deployments => self.deployments,
+        }
+    }
+}
+
+static LIMITS: LazyLock<Mutex<CacheLimits>> = LazyLock::new(|| Mutex::new(CacheLimits::default()));
+
+pub fn set_limits(limits: CacheLimits) {
+    *LIMITS.lock().unwrap() = limits;
+}
+
+pub fn limits() -> CacheLimits {
+    *LIMITS.lock().unwrap()
+}
+
+/// Rough in-memory size of a cached value
+pub trait EstimateSize {
+    fn estimated_bytes(&self) -> usize;
+}
+
+/// Serialized length, a fair stand-in for values that are mostly strings
+pub fn json_size<T: Serialize>(value: &T) -> usize {
+    serde_json::to_vec(value).map(|bytes| bytes.len()).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CacheStats {
+    pub cache: CacheName,
+    pub entries: usize,
+    pub estimated_bytes: usize,
+    pub max_entries: usize,
+    pub max_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: Option<f64>, // None until the first lookup
+    pub evictions: u64,
+}
+
+struct Entry<V> {
+    value: V,
+    bytes: usize,
+    used: u64, // key into `recency`
+}
+
+pub struct BoundedCache<K, V> {
+    name: CacheName,
+    entries: HashMap<K, Entry<V>>,
+    recency: BTreeMap<u64, K>, // oldest use first
+    clock: u64,
+    bytes: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<K: Eq + Hash + Clone, V: EstimateSize> BoundedCache<K, V> {
+    pub fn new(name: CacheName) -> Self {
+        BoundedCache {
+            name,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            bytes: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Look up an entry that is still usable; one that fails `valid` is dropped and counts as a miss
+    pub fn get_if(&mut self, key: &K, valid: impl FnOnce(&V) -> bool) -> Option<&V> {
+        let usable = self.entries.get(key).map(|entry| valid(&entry.value));
+        match usable {
+            Some(true) => {
+                self.hits += 1;
+                self.clock += 1;
+                let entry = self.entries.get_mut(key)?;
+                self.recency.remove(&entry.used);
+                entry.used = self.clock;
+                self.recency.insert(self.clock, key.clone());
+                Some(&entry.value)
+            }
+            Some(false) => {
+                self.misses += 1;
+                self.remove(key);
+                None
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_if(key, |_| true)
+    }
+
+    /// Store a value, then evict the least recently used entries until the cache fits its
+    /// bounds again. A value larger than the byte bound on its own isn't kept.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.insert_within(key, value, limits().bounds(self.name));
+    }
+
+    fn insert_within(&mut self, key: K, value: V, bounds: CacheBounds) {
+        self.remove(&key);
+        self.clock += 1;
+        let bytes = value.estimated_bytes();
+        self.bytes += bytes;
+        self.recency.insert(self.clock, key.clone());
+        self.entries.insert(key, Entry { value, bytes, used: self.clock });
+
+        while self.entries.len() > bounds.max_entries || self.bytes > bounds.max_bytes {
+            let Some((_, oldest)) = self.recency.pop_first() else { break };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.bytes -= entry.bytes;
+                self.evictions += 1;
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.used);
+            self.bytes -= entry.bytes;
+        }
+    }
+
+    /// Drop every entry; hit and eviction counts are kept for the session
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.bytes = 0;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let bounds = limits().bounds(self.name);
+        let lookups = self.hits + self.misses;
+        CacheStats {
+            cache: self.name,
+            entries: self.entries.len(),
+            estimated_bytes: self.bytes,
+            max_entries: bounds.max_entries,
+            max_bytes: bounds.max_bytes,
+            hits: self.hits,
+            misses: self.misses,
+            hit_rate: (lookups > 0).then(|| self.hits as f64 / lookups as f64),
+            evictions: self.evictions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl EstimateSize for String {
+        fn estimated_bytes(&self) -> usize {
+            self.len()
+        }
+    }
+
+    #[test]
+    fn test_lru_eviction_at_the_bounds() {
+        let mut cache: BoundedCache<i64, String> = BoundedCache::new(CacheName::PrFiles);
+        let bounds = CacheBounds { max_entries: 3, max_bytes: 100 };
+        for id in 1..=3 {
+            cache.insert_within(id, "x".repeat(10), bounds);
+        }
+        // 1 is used, so 2 is the least recently used when 4 arrives
+        assert!(cache.get(&1).is_some());
+        cache.insert_within(4, "x".repeat(10), bounds);
+        assert!(cache.get(&2).is_none());
+        assert_eq!(cache.entries.len(), 3);
+        assert_eq!(cache.evictions, 1);
+
+        // Byte bound: 85 bytes pushes out the two oldest (3 for the count, then 1 for the size)
+        cache.insert_within(5, "x".repeat(85), bounds);
+        assert_eq!(cache.bytes, 95);
+        assert!(cache.get(&3).is_none() && cache.get(&1).is_none());
+        assert!(cache.get(&4).is_some() && cache.get(&5).is_some());
+        assert_eq!(cache.evictions, 3);
+
+        // Replacing a key doesn't count its old size twice; too large on its own isn't kept
+        cache.insert_within(5, "x".repeat(20), bounds);
+        assert_eq!(cache.bytes, 30);
+        cache.insert_within(6, "x".repeat(101), bounds);
+        assert!(cache.get(&6).is_none());
+        assert_eq!(cache.bytes, 0);
+    }
+
+    #[test]
+    fn test_hit_rate_accounting() {
+        let mut cache: BoundedCache<&str, String> = BoundedCache::new(CacheName::PrList);
+        assert_eq!(cache.stats().hit_rate, None);
+        cache.insert("list", "cached".to_string());
+        assert!(cache.get(&"list").is_some());
+        assert!(cache.get(&"list").is_some());
+        assert!(cache.get(&"other").is_none());
+        // A stale entry is a miss and is dropped
+        assert!(cache.get_if(&"list", |value| value == "fresh").is_none());
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 0));
+        assert_eq!(stats.hit_rate, Some(0.5));
+
+        cache.insert("list", "cached".to_string());
+        cache.clear();
+        assert_eq!((cache.stats().entries, cache.stats().estimated_bytes, cache.stats().hits), (0, 0, 2));
+    }
+
+    #[test]
+    fn test_limits_parse() {
+        assert_eq!(CacheLimits::parse("{}").unwrap(), CacheLimits::default());
+        let limits = CacheLimits::parse(r#"{"pr_files": {"max_entries": 10, "max_bytes": 1048576}}"#).unwrap();
+        assert_eq!(limits.pr_files, CacheBounds { max_entries: 10, max_bytes: MIB });
+        assert!(CacheLimits::parse(r#"{"pr_files": {"max_entries": 0, "max_bytes": 1048576}}"#).is_err());
+        assert!(CacheLimits::parse(r#"{"pr_list": {"max_entries": 1, "max_bytes": 10}}"#).is_err());
+    }
+}
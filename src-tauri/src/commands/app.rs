@@ -0,0 +1,341 @@
+use crate::database::transfer::{ConflictResolution, DataImportResult};
+use serde::Serialize;
+use std::path::Path;
+use tauri::Emitter;
+
+use crate::bounded_cache::CacheStats;
+use crate::database::export_bundle::{self, ExportManifest};
+use crate::database::migrations::{latest_schema_version, MigrationError, MigrationLogEntry};
+use crate::database::sync_runs::SyncRun;
+use crate::database::{Database, MissingReferenceFix};
+use crate::dev_env::{self, DevEnvironment};
+use crate::github::keychain_access::TokenAccessDenied;
+use crate::github::GitHubTokenManager;
+use crate::instance_lock::{self, InstanceStatus};
+use crate::services;
+use crate::services::avatars::{AvatarPrefetchReport, AVATAR_PREFETCH_PROGRESS_EVENT};
+use crate::services::onboarding::OnboardingState;
+use crate::services::power::PowerState;
+use crate::services::deployments::DeploymentsCache;
+use crate::services::pr_files::PrFilesCache;
+use crate::services::pr_list_cache::PrListCache;
+use crate::services::privacy::PrivacyState;
+use crate::DbState;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InitDatabaseError {
+    MigrationFailed { version: i64, name: String, error: String, message: String },
+    Failed { message: String },
+}
+
+impl From<anyhow::Error> for InitDatabaseError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast_ref::<MigrationError>() {
+            Some(failure) => InitDatabaseError::MigrationFailed {
+                version: failure.version,
+                name: failure.name.clone(),
+                error: failure.error.clone(),
+                message: failure.to_string(),
+            },
+            None => InitDatabaseError::Failed { message: error.to_string() },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppInfo {
+    pub version: String,
+    pub schema_version: i64,
+    pub latest_schema_version: i64,
+    pub migrations: Vec<MigrationLogEntry>,
+    pub instance: InstanceStatus, // secondary when another instance holds the lock
+    pub last_sync: Option<SyncRun>,
+}
+
+// Initialize database connection
+#[tauri::command]
+pub async fn init_database(
+    state: tauri::State<'_, DbState>,
+    cache: tauri::State<'_, PrListCache>
+) -> Result<(), InitDatabaseError> {
+    // The startup task may already have opened it
+    let mut db_state = state.lock().await;
+    if db_state.is_some() {
+        println!("✅ Database already initialized");
+        return Ok(());
+    }
+
+    let db = Database::new().await?;
+    println!("✅ Database initialized successfully (without sample data)");
+
+    *db_state = Some(db);
+    cache.lock().await.clear();
+
+    Ok(())
+}
+
+// Clear all data from database (for clean start)
+#[tauri::command]
+pub async fn clear_all_data(state: tauri::State<'_, DbState>) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    println!("🧹 Clearing all data from database...");
+
+    // Clear in order of dependencies
+    sqlx::query("DELETE FROM import_conflicts").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM sync_conflicts").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM pr_profile_state").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM pr_reviewers").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM daily_stats").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM daily_stats_runs").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM pr_links_external").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM scheduled_tasks").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM retry_queue").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM repositories").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM pull_requests").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM review_history").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM team_members").execute(&db.pool).await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM projects").execute(&db.pool).await.map_err(|e| e.to_string())?;
+
+    println!("✅ All data cleared from database");
+    Ok(())
+}
+
+// Dev builds only: wipe PRTracker-dev, reseed it and switch the app over to the fresh database.
+// If the reset fails the previous database is reopened, so the app isn't left without one.
+#[tauri::command]
+pub async fn reset_dev_environment(
+    state: tauri::State<'_, DbState>,
+    cache: tauri::State<'_, PrListCache>
+) -> Result<DevEnvironment, String> {
+    let (app_dir, production_dir) = dev_env::reset_target().map_err(|e| e.to_string())?;
+    dev_env::check_wipe_target(&app_dir, &production_dir).map_err(|e| e.to_string())?;
+
+    let mut db_state = state.lock().await;
+    let was_open = match db_state.take() {
+        Some(db) => {
+            db.pool.close().await;
+            true
+        }
+        None => false,
+    };
+    cache.lock().await.clear();
+    match dev_env::reset_in(&app_dir, &production_dir).await {
+        Ok((db, env)) => {
+            *db_state = Some(db);
+            Ok(env)
+        }
+        Err(e) if was_open => {
+            let reopened = Database::new().await.map_err(|reopen| format!("{}; reopening the database also failed: {}", e, reopen))?;
+            *db_state = Some(reopened);
+            Err(e.to_string())
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Clear project ids and relink authors that point at rows deleted outside the app
+#[tauri::command]
+pub async fn fix_missing_references(state: tauri::State<'_, DbState>) -> Result<MissingReferenceFix, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.fix_missing_references().await.map_err(|e| e.to_string())
+}
+
+// Export projects and PRs as JSON for merging into another database
+#[tauri::command]
+pub async fn export_data_json(
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<String, String> {
+    privacy.ensure_export_allowed()?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.export_data_json().await.map_err(|e| e.to_string())
+}
+
+// Merge an export: new rows import now, differing status/score/priority come back as conflicts
+#[tauri::command]
+pub async fn import_data_json(
+    json: String,
+    state: tauri::State<'_, DbState>
+) -> Result<DataImportResult, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.import_data_json(&json).await.map_err(|e| e.to_string())
+}
+
+// Write the export to a file plus <file>.manifest.json (format version, row counts, SHA-256)
+#[tauri::command]
+pub async fn export_data_file(
+    path: String,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<ExportManifest, String> {
+    privacy.ensure_export_allowed()?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.export_data_file(Path::new(&path)).await.map_err(|e| e.to_string())
+}
+
+// Import a file written by export_data_file; refused unless it matches its manifest
+#[tauri::command]
+pub async fn import_data_file(
+    path: String,
+    state: tauri::State<'_, DbState>
+) -> Result<DataImportResult, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.import_data_file(Path::new(&path)).await.map_err(|e| e.to_string())
+}
+
+// Check an export file against its manifest without importing it; needs no database
+#[tauri::command]
+pub async fn verify_export(path: String) -> Result<ExportManifest, String> {
+    export_bundle::verify_export(Path::new(&path)).map_err(|e| e.to_string())
+}
+
+// Apply "mine"/"theirs" choices for an import session's conflicts in one transaction
+#[tauri::command]
+pub async fn apply_import_conflict_resolutions(
+    session_id: String,
+    resolutions: Vec<ConflictResolution>,
+    state: tauri::State<'_, DbState>
+) -> Result<u32, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.apply_import_conflict_resolutions(&session_id, &resolutions).await.map_err(|e| e.to_string())
+}
+
+// Applied (and failed) schema migrations, oldest first
+#[tauri::command]
+pub async fn get_migration_history(state: tauri::State<'_, DbState>) -> Result<Vec<MigrationLogEntry>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_migration_history().await.map_err(|e| e.to_string())
+}
+
+// App version plus schema version and migration history, for support reports
+#[tauri::command]
+pub async fn get_app_info(state: tauri::State<'_, DbState>, privacy: tauri::State<'_, PrivacyState>) -> Result<AppInfo, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    Ok(AppInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: db.get_schema_version().await.map_err(|e| e.to_string())?,
+        latest_schema_version: latest_schema_version(),
+        migrations: db.get_migration_history().await.map_err(|e| e.to_string())?,
+        instance: instance_lock::status(),
+        last_sync: privacy.redact(db.get_last_sync_run().await.map_err(|e| e.to_string())?),
+    })
+}
+
+// What the first-run screen shows. Local only: the keychain, a few counts, and the
+// outcome of the last token check (no GitHub call).
+#[tauri::command]
+pub async fn get_onboarding_state(state: tauri::State<'_, DbState>) -> Result<OnboardingState, String> {
+    let manager = GitHubTokenManager::new().map_err(|e| e.to_string())?;
+    let (has_token, keychain_access_denied) = match manager.get_token() {
+        Ok(token) => (token.is_some(), false),
+        Err(e) if e.is::<TokenAccessDenied>() => (false, true),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+    services::onboarding::get_onboarding_state(db, has_token, keychain_access_denied).await
+}
+
+// The user finished or skipped onboarding; the first-run screen stays away from then on
+#[tauri::command]
+pub async fn mark_onboarding_complete(state: tauri::State<'_, DbState>) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.mark_onboarding_complete(chrono::Utc::now().timestamp()).await.map_err(|e| e.to_string())
+}
+
+// Download avatars for members that have a URL but no local copy (4 at a time, 1 MB cap),
+// emitting avatar-prefetch-progress after each one. Failures back off like the retry queue.
+#[tauri::command]
+pub async fn prefetch_missing_avatars<R: tauri::Runtime>(app: tauri::AppHandle<R>, state: tauri::State<'_, DbState>) -> Result<AvatarPrefetchReport, String> {
+    prefetch_avatars_with_events(&app, &state).await
+}
+
+/// Prefetch with progress sent to the UI as avatar-prefetch-progress events
+pub(crate) async fn prefetch_avatars_with_events<R: tauri::Runtime>(app: &tauri::AppHandle<R>, state: &DbState) -> Result<AvatarPrefetchReport, String> {
+    services::avatars::prefetch_from_cdn(state, |progress| {
+        let _ = app.emit(AVATAR_PREFETCH_PROGRESS_EVENT, progress.clone());
+    }).await
+}
+
+// Power hint from the window (Battery Status / Network Information APIs), used by the sync
+// constraints where the OS can't be asked directly. Reports expire after 15 minutes.
+#[tauri::command]
+pub async fn report_power_state(on_battery: bool, metered: bool) -> Result<PowerState, String> {
+    Ok(services::power::report_power_state(on_battery, metered, chrono::Utc::now().timestamp()))
+}
+
+// Entries, estimated size, hit rate and evictions for each in-memory cache
+#[tauri::command]
+pub async fn get_cache_stats(
+    pr_list_cache: tauri::State<'_, PrListCache>,
+    pr_files_cache: tauri::State<'_, PrFilesCache>,
+    deployments_cache: tauri::State<'_, DeploymentsCache>
+) -> Result<Vec<CacheStats>, String> {
+    Ok(vec![
+        pr_list_cache.lock().await.stats(),
+        pr_files_cache.lock().await.stats(),
+        deployments_cache.lock().await.stats(),
+    ])
+}
+
+// Empty every in-memory cache; the next reads go to SQLite or GitHub again
+#[tauri::command]
+pub async fn clear_caches(
+    pr_list_cache: tauri::State<'_, PrListCache>,
+    pr_files_cache: tauri::State<'_, PrFilesCache>,
+    deployments_cache: tauri::State<'_, DeploymentsCache>
+) -> Result<(), String> {
+    pr_list_cache.lock().await.clear();
+    pr_files_cache.lock().await.clear();
+    deployments_cache.lock().await.clear();
+    Ok(())
+}
+
+// Hide identities in read commands (pseudonyms, no avatars, owners as "org") for screenshots.
+// Nothing stored changes; exports refuse to run while it's on.
+#[tauri::command]
+pub async fn set_privacy_mode(enabled: bool, privacy: tauri::State<'_, PrivacyState>) -> Result<(), String> {
+    privacy.set_enabled(enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_privacy_mode(privacy: tauri::State<'_, PrivacyState>) -> Result<bool, String> {
+    Ok(privacy.is_enabled())
+}
+
+/// Test command to verify Tauri invoke is working
+#[tauri::command]
+pub async fn test_invoke(message: String) -> Result<String, String> {
+    println!("🧪 Test invoke called with message: {}", message);
+    Ok(format!("Test successful: {}", message))
+}
+
+// Keep original greet command for testing
+#[tauri::command]
+pub fn greet(name: &str) -> String {
+    format!("Hello, {}! You've been greeted from Rust!", name)
+}
+
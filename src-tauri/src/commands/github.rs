@@ -0,0 +1,539 @@
+use crate::database::reimport::ReimportSummary;
+use crate::database::sync_runs::{SyncChange, SyncRun};
+use crate::database::{Database, PullRequest, RepoRepairSummary, RetryEntry};
+use crate::github::api_usage::{ApiFeature, ApiUsageReport};
+use crate::github::rate_limit::{self, RequestCategory};
+use crate::github::url::{parse_pr_reference, PrUrlError, ValidatedPrUrl};
+use crate::github::keychain_access;
+use crate::github::token_grants::{self, TokenGrant};
+use crate::github::token_permissions::RequiredTokenPermissions;
+use crate::github::{token_or_stored, GitHubTokenInfo, GitHubTokenManager};
+use crate::services;
+use crate::services::deployments::{DeploymentsCache, GitHubDeploymentsClient, PrDeployments};
+use crate::services::issues::{GitHubIssueClient, IssueRefreshResult};
+use crate::services::pr_files::{GitHubPrFilesClient, PrFilesCache, PrFilesSummary};
+use crate::services::privacy::PrivacyState;
+use crate::services::pull_requests::{AddPrResult, BranchAddResult, BranchLookupError, BranchPrCandidate, GitHubPrDataClient, QuickAddError, QuickAddResult};
+use crate::services::rate_limit::RateLimitStatus;
+use crate::services::sync_all::{RefreshAllSummary, SyncAllSummary, SYNC_PROGRESS_EVENT};
+use crate::services::reimport::GitHubRepoPullsClient;
+use crate::services::releases::{GitHubReleaseClient, ReleaseBatchResult, ShippedRelease};
+use crate::services::repositories::{GitHubRepoAccessChecker, RepoAccessReport};
+use crate::services::retry::GitHubEnrichmentClient;
+use crate::services::review_comments::{CommentStatsSyncResult, GitHubReviewCommentClient};
+use crate::services::token_expiry::TokenExpiryStatus;
+use crate::services::token_permissions::{GitHubPermissionProbe, TokenDiagnosis};
+use crate::DbState;
+use tauri::Emitter;
+
+/// Save GitHub token to macOS Keychain
+#[tauri::command]
+pub async fn save_github_token(token: String) -> Result<(), String> {
+    let manager = GitHubTokenManager::new().map_err(|e| e.to_string())?;
+    manager.save_token(&token).map_err(|e| e.to_string())?;
+    // A new token has its own expiry; the next API call reports it
+    token_grants::forget_expiry();
+    Ok(())
+}
+
+/// Retrieve GitHub token from macOS Keychain
+#[tauri::command]
+pub async fn get_github_token() -> Result<Option<String>, String> {
+    let manager = GitHubTokenManager::new().map_err(|e| e.to_string())?;
+    manager.get_token().map_err(|e| e.to_string())
+}
+
+/// Delete GitHub token from macOS Keychain
+#[tauri::command]
+pub async fn delete_github_token() -> Result<(), String> {
+    let manager = GitHubTokenManager::new().map_err(|e| e.to_string())?;
+    manager.delete_token().map_err(|e| e.to_string())?;
+    token_grants::forget_expiry();
+    Ok(())
+}
+
+/// Read the keychain again after the user granted access; returns whether a token is stored
+#[tauri::command]
+pub async fn retry_keychain_access() -> Result<bool, String> {
+    keychain_access::clear();
+    let manager = GitHubTokenManager::new().map_err(|e| e.to_string())?;
+    Ok(manager.get_token().map_err(|e| e.to_string())?.is_some())
+}
+
+/// Verify GitHub token and get user info
+#[tauri::command]
+pub async fn verify_github_token(
+    token: String,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<GitHubTokenInfo, String> {
+    let manager = GitHubTokenManager::new().map_err(|e| e.to_string())?;
+    let info = manager.verify_token(&token).await.map_err(|e| e.to_string())?;
+    record_token_check(state.lock().await.as_ref(), &info).await;
+    Ok(privacy.redact(info))
+}
+
+// Remembered for the first-run screen, which shows token validity without calling GitHub
+async fn record_token_check(db: Option<&Database>, info: &GitHubTokenInfo) {
+    let Some(db) = db else { return };
+    let login = info.user.as_ref().map(|user| user.login.as_str());
+    if let Err(e) = db.record_token_check(info.valid, login, chrono::Utc::now().timestamp()).await {
+        println!("⚠️ Could not save the token check: {}", e);
+    }
+}
+
+/// Test connection with stored GitHub token
+#[tauri::command]
+pub async fn test_github_connection(
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<GitHubTokenInfo, String> {
+    let manager = GitHubTokenManager::new().map_err(|e| e.to_string())?;
+    let mut info = manager.test_stored_token().await.map_err(|e| e.to_string())?;
+
+    let db_state = state.lock().await;
+    // A denied keychain means nothing was checked
+    if !info.keychain_access_denied {
+        record_token_check(db_state.as_ref(), &info).await;
+    }
+    info.rate_limit_pause = services::rate_limit::current_status(db_state.as_ref()).await.message;
+    info.token_expiry_warning = services::token_expiry::current_status(db_state.as_ref()).await.message;
+    info.last_sync = services::sync_runs::last_sync_message(db_state.as_ref()).await;
+    Ok(privacy.redact(info))
+}
+
+/// When the stored token expires, with a warning once it's within a week
+#[tauri::command]
+pub async fn get_token_expiry(state: tauri::State<'_, DbState>) -> Result<TokenExpiryStatus, String> {
+    let db_state = state.lock().await;
+    Ok(services::token_expiry::current_status(db_state.as_ref()).await)
+}
+
+/// Report the token's kind, scopes and expiry, with where to review or revoke it.
+/// The app can't revoke tokens itself: that needs the issuing OAuth app's client secret.
+#[tauri::command]
+pub async fn list_token_grants(token: Option<String>) -> Result<TokenGrant, String> {
+    let token = token_or_stored(token)?;
+    crate::github::fetch_token_grant(ApiFeature::TokenCheck, &token).await
+}
+
+/// Fine-grained permissions (and the classic scope) the features in use need; no GitHub call
+#[tauri::command]
+pub async fn get_required_token_permissions(state: tauri::State<'_, DbState>) -> Result<RequiredTokenPermissions, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    services::token_permissions::get_required_token_permissions(db).await
+}
+
+/// Test each needed permission on a tracked repository and report the missing ones
+#[tauri::command]
+pub async fn diagnose_token_access(token: Option<String>, state: tauri::State<'_, DbState>) -> Result<TokenDiagnosis, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let probe = GitHubPermissionProbe { token, feature: ApiFeature::TokenCheck };
+    services::token_permissions::diagnose_token_access(db, &probe).await
+}
+
+/// Last rate-limit state GitHub reported, including any pause carried over from the previous session
+#[tauri::command]
+pub async fn get_rate_limit(state: tauri::State<'_, DbState>) -> Result<RateLimitStatus, String> {
+    let db_state = state.lock().await;
+    Ok(services::rate_limit::current_status(db_state.as_ref()).await)
+}
+
+/// GitHub calls per feature over the last `hours` hours (at most a week), busiest first
+#[tauri::command]
+pub async fn get_api_usage(hours: i64, state: tauri::State<'_, DbState>) -> Result<ApiUsageReport, String> {
+    let db_state = state.lock().await;
+    services::api_usage::get_api_usage(db_state.as_ref(), hours, chrono::Utc::now().timestamp()).await
+}
+
+/// Drop the saved rate-limit state and any pause (e.g. after fixing a wrong system clock)
+#[tauri::command]
+pub async fn reset_rate_limit_state(state: tauri::State<'_, DbState>) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    services::rate_limit::reset_rate_limit_state(db).await
+}
+
+
+/// Validate a PR URL or owner/repo#123 reference as the user types - no network or database access.
+/// A bare #123 resolves against `default_repo` ("owner/repo").
+#[tauri::command]
+pub fn validate_pr_url(url: String, default_repo: Option<String>) -> Result<ValidatedPrUrl, PrUrlError> {
+    parse_pr_reference(&url, default_repo.as_deref()).map(ValidatedPrUrl::from)
+}
+
+/// Add PR from GitHub URL - fetches data and correlates with database
+#[tauri::command]
+pub async fn add_pr_from_github_url(
+    pr_url: String,
+    project_id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<AddPrResult, String> {
+    let token = token_or_stored(token)?;
+    println!("🚀 Starting add_pr_from_github_url with URL: {}", pr_url);
+    println!("📝 Function parameters: project_id={}, token_length={}", project_id, token.len());
+
+    println!("🔒 Acquiring database lock...");
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+    println!("✅ Database lock acquired successfully");
+
+    services::pull_requests::add_pr_from_github_url(db, &pr_url, project_id, &token).await.map(|result| privacy.redact(result))
+}
+
+/// Add a PR from a URL, picking the project from its repository or the default project
+#[tauri::command]
+pub async fn quick_add_from_url(
+    url: String,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<QuickAddResult, QuickAddError> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or_else(|| QuickAddError::from("Database not initialized".to_string()))?;
+
+    services::pull_requests::quick_add_from_url(db, &url, &token).await.map(|result| privacy.redact(result))
+}
+
+/// PRs opened from a branch (open, closed and merged), for when there's a branch name but no URL
+#[tauri::command]
+pub async fn find_pr_for_branch(
+    owner: String,
+    repo: String,
+    branch: String,
+    token: Option<String>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<BranchPrCandidate>, BranchLookupError> {
+    let token = token_or_stored(token)?;
+    services::pull_requests::find_pr_for_branch(&token, &owner, &repo, &branch).await.map(|candidates| privacy.redact(candidates))
+}
+
+/// Add the branch's PR, or return the candidates to choose from when it's ambiguous
+#[tauri::command]
+pub async fn add_pr_for_branch(
+    owner: String,
+    repo: String,
+    branch: String,
+    project_id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<BranchAddResult, BranchLookupError> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or_else(|| BranchLookupError::from("Database not initialized".to_string()))?;
+
+    services::pull_requests::add_pr_for_branch(db, &owner, &repo, &branch, project_id, &token).await.map(|result| privacy.redact(result))
+}
+
+/// Re-fetch one tracked PR's title and branch; merged or closed on GitHub moves its status
+#[tauri::command]
+pub async fn refresh_pr_from_github(
+    pr_id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<PullRequest, String> {
+    let token = token_or_stored(token)?;
+    let client = GitHubPrDataClient { token, feature: ApiFeature::PrRefresh };
+    services::pull_requests::refresh_pr(&state, &client, pr_id).await.map(|pr| privacy.redact(pr))
+}
+
+/// Refresh every tracked PR; sync_all_prs without progress events, summed up as {updated, failed, errors}
+#[tauri::command]
+pub async fn refresh_all_prs(
+    token: Option<String>,
+    state: tauri::State<'_, DbState>
+) -> Result<RefreshAllSummary, String> {
+    let token = token_or_stored(token)?;
+    let client = GitHubPrDataClient { token, feature: ApiFeature::PrRefresh };
+    rate_limit::in_category(
+        RequestCategory::BackgroundSync,
+        services::sync_all::sync_all_prs(&state, &client, |_| {}),
+    ).await.map(RefreshAllSummary::from)
+}
+
+/// Sync every tracked PR with repository fields, four at a time, reporting sync-progress events;
+/// stops early with the reset time once rate limited. The database stays usable meanwhile.
+#[tauri::command]
+pub async fn sync_all_prs<R: tauri::Runtime>(
+    token: Option<String>,
+    app: tauri::AppHandle<R>,
+    state: tauri::State<'_, DbState>
+) -> Result<SyncAllSummary, String> {
+    let token = token_or_stored(token)?;
+    let client = GitHubPrDataClient { token, feature: ApiFeature::PrRefresh };
+    rate_limit::in_category(
+        RequestCategory::BackgroundSync,
+        services::sync_all::sync_all_prs(&state, &client, |progress| {
+            let _ = app.emit(SYNC_PROGRESS_EVENT, progress.clone());
+        }),
+    ).await
+}
+
+/// Backfill missing repository owner/name on legacy PRs so they can be synced again
+#[tauri::command]
+pub async fn repair_missing_repo_fields(
+    token: Option<String>,
+    state: tauri::State<'_, DbState>
+) -> Result<RepoRepairSummary, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    rate_limit::in_category(RequestCategory::Import, services::pull_requests::repair_missing_repo_fields(db, &token)).await
+}
+
+/// Move PRs merged on GitHub to the Merged column (pinned PRs are skipped)
+#[tauri::command]
+pub async fn close_merged_prs(
+    token: Option<String>,
+    state: tauri::State<'_, DbState>
+) -> Result<Vec<i64>, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    rate_limit::in_category(RequestCategory::BackgroundSync, services::pull_requests::close_merged_prs(db, &token)).await
+}
+
+/// Check which tracked repositories the token can still read
+#[tauri::command]
+pub async fn audit_repo_access(
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<RepoAccessReport, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let checker = GitHubRepoAccessChecker { token, feature: ApiFeature::RepoAudit };
+    rate_limit::in_category(
+        RequestCategory::BackgroundSync,
+        services::repositories::audit_repo_access(db, &checker, chrono::Utc::now().timestamp()),
+    ).await.map(|report| privacy.redact(report))
+}
+
+/// Recount review comments per team member over the last `window_days` days (opt-in;
+/// only authors and timestamps are read)
+#[tauri::command]
+pub async fn sync_review_comment_stats(
+    token: Option<String>,
+    window_days: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<CommentStatsSyncResult, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubReviewCommentClient { token, feature: ApiFeature::ReviewCommentStats };
+    rate_limit::in_category(
+        RequestCategory::Import,
+        services::review_comments::sync_review_comment_stats(db, &client, window_days, chrono::Utc::now().timestamp()),
+    ).await
+}
+
+/// Background sync runs (merge sync, scheduled refreshes), newest first
+#[tauri::command]
+pub async fn get_recent_sync_runs(
+    limit: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<SyncRun>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    services::sync_runs::get_recent_sync_runs(db, limit).await.map(|runs| privacy.redact(runs))
+}
+
+/// PRs the most recent sync run changed; empty before the first run
+#[tauri::command]
+pub async fn get_last_sync_changes(
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<SyncChange>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let run = db.get_last_sync_run().await.map_err(|e| e.to_string())?;
+    Ok(privacy.redact(run.map(|run| run.changes).unwrap_or_default()))
+}
+
+/// Operations that exhausted their retries and were parked
+#[tauri::command]
+pub async fn get_failed_operations(state: tauri::State<'_, DbState>) -> Result<Vec<RetryEntry>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_failed_operations().await.map_err(|e| e.to_string())
+}
+
+/// Retry a queued operation immediately; returns the entry if it failed again
+#[tauri::command]
+pub async fn retry_now(
+    id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>
+) -> Result<Option<RetryEntry>, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubEnrichmentClient { token, feature: ApiFeature::RetryQueue };
+    services::retry::retry_now(db, &client, id, chrono::Utc::now().timestamp()).await
+}
+
+/// Files changed in a PR with a per-directory churn rollup (cached until the head SHA changes)
+#[tauri::command]
+pub async fn get_pr_files(
+    pr_id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    cache: tauri::State<'_, PrFilesCache>
+) -> Result<PrFilesSummary, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubPrFilesClient { token, feature: ApiFeature::PrFiles };
+    services::pr_files::get_pr_files(db, &client, &cache, pr_id).await
+}
+
+/// Latest deployment status per environment for the PR's head commit (cached for a minute per SHA)
+#[tauri::command]
+pub async fn get_pr_deployments(
+    pr_id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    cache: tauri::State<'_, DeploymentsCache>
+) -> Result<PrDeployments, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubDeploymentsClient { token, feature: ApiFeature::Deployments };
+    services::deployments::get_pr_deployments(db, &client, &cache, pr_id, chrono::Utc::now().timestamp()).await
+}
+
+/// Which release shipped a merged PR (compare API per candidate release; stored once found)
+#[tauri::command]
+pub async fn resolve_shipped_release(
+    pr_id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>
+) -> Result<ShippedRelease, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubReleaseClient { token, feature: ApiFeature::Releases };
+    services::releases::resolve_shipped_release(db, &client, pr_id).await
+}
+
+/// Resolve releases for up to `limit` merged PRs of one repository that don't have one yet
+#[tauri::command]
+pub async fn resolve_shipped_releases(
+    owner: String,
+    repo: String,
+    limit: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>
+) -> Result<ReleaseBatchResult, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubReleaseClient { token, feature: ApiFeature::Releases };
+    rate_limit::in_category(
+        RequestCategory::Import,
+        services::releases::resolve_shipped_releases_for_repo(db, &client, &owner, &repo, limit),
+    ).await
+}
+
+/// Delete and reimport one repository's tracked PRs (open plus recently closed), optionally
+/// carrying local fields and history over by PR number. Needs a "reimport_repository" nonce.
+#[tauri::command]
+pub async fn reimport_repository<R: tauri::Runtime>(
+    owner: String,
+    repo: String,
+    preserve_local_fields: bool,
+    confirm_nonce: String,
+    token: Option<String>,
+    app: tauri::AppHandle<R>,
+    state: tauri::State<'_, DbState>
+) -> Result<ReimportSummary, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubRepoPullsClient { token, feature: ApiFeature::Reimport };
+    let summary = rate_limit::in_category(
+        RequestCategory::Import,
+        services::reimport::reimport_repository(
+            db,
+            &client,
+            &owner,
+            &repo,
+            preserve_local_fields,
+            &confirm_nonce,
+            chrono::Utc::now().timestamp(),
+        ),
+    ).await?;
+
+    // New authors' avatars download in the background, reporting avatar-prefetch-progress
+    drop(db_state);
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        match super::app::prefetch_avatars_with_events(&app, &state).await {
+            Ok(report) if report.failed > 0 => println!("⚠️ {} avatar download(s) failed: {:?}", report.failed, report.errors),
+            Ok(_) => {}
+            Err(e) => println!("❌ Avatar prefetch failed: {}", e),
+        }
+    });
+    Ok(summary)
+}
+
+/// Track an issue before any PR exists for it
+#[tauri::command]
+pub async fn add_issue(
+    owner: String,
+    repo: String,
+    issue_number: i64,
+    project_id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<PullRequest, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubIssueClient { token, feature: ApiFeature::Issues };
+    services::issues::add_issue(db, &client, &owner, &repo, issue_number, project_id).await.map(|pr| privacy.redact(pr))
+}
+
+/// Re-check a tracked issue and upgrade it to its PR once one is linked
+#[tauri::command]
+pub async fn refresh_issue(
+    pr_id: i64,
+    token: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<IssueRefreshResult, String> {
+    let token = token_or_stored(token)?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let client = GitHubIssueClient { token, feature: ApiFeature::Issues };
+    services::issues::refresh_issue(db, &client, pr_id).await.map(|result| privacy.redact(result))
+}
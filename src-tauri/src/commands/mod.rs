@@ -0,0 +1,7 @@
+// Tauri command wrappers, grouped by feature area
+pub mod app;
+pub mod github;
+pub mod projects;
+pub mod pull_requests;
+pub mod settings;
+pub mod team;
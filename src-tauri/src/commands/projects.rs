@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+use crate::database::{InactiveProject, MisfiledPr, Project, ProjectDeleteSummary, ProjectDeleteTarget, ReviewCoverage, TeamMember};
+use crate::services;
+use crate::services::privacy::PrivacyState;
+use crate::services::sla::ProjectSlaReport;
+use crate::DbState;
+
+// Get all projects
+#[tauri::command]
+pub async fn get_projects(state: tauri::State<'_, DbState>) -> Result<Vec<Project>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_projects().await.map_err(|e| e.to_string())
+}
+
+// Add a new project
+#[tauri::command]
+pub async fn add_project(
+    name: String,
+    description: Option<String>,
+    state: tauri::State<'_, DbState>
+) -> Result<Project, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.add_project(name, description).await.map_err(|e| e.to_string())
+}
+
+// Onboarding: create a project and optionally start tracking owner/repo in one call.
+// The first project becomes the import default when none is set.
+#[tauri::command]
+pub async fn create_starter_project(
+    name: String,
+    repository_owner: Option<String>,
+    repository_name: Option<String>,
+    state: tauri::State<'_, DbState>
+) -> Result<Project, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let repository = match (repository_owner.as_deref(), repository_name.as_deref()) {
+        (None, None) => None,
+        (owner, repo) => Some((owner.unwrap_or_default(), repo.unwrap_or_default())),
+    };
+    db.create_starter_project(&name, repository, chrono::Utc::now().timestamp())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Update an existing project
+#[tauri::command]
+pub async fn update_project(
+    id: i64,
+    name: String,
+    description: Option<String>,
+    sla_hours: Option<i64>,
+    target_score: Option<i32>,
+    state: tauri::State<'_, DbState>
+) -> Result<Project, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.update_project(id, name, description, sla_hours, target_score).await.map_err(|e| e.to_string())
+}
+
+// Delete a project
+#[tauri::command]
+pub async fn delete_project(
+    id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.delete_project(id).await.map_err(|e| e.to_string())
+}
+
+// Delete a project after moving its PRs to another project or leaving them unassigned, in one transaction
+#[tauri::command]
+pub async fn delete_project_with_reassign(
+    id: i64,
+    target: ProjectDeleteTarget,
+    state: tauri::State<'_, DbState>
+) -> Result<ProjectDeleteSummary, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.delete_project_with_reassign(id, target).await.map_err(|e| e.to_string())
+}
+
+// Delete one project's PRs and their history, comments, reviewers and links, optionally the project too
+// (requires a "clear_project" nonce; backs up first). Returns rows deleted per table.
+#[tauri::command]
+pub async fn clear_project_data(
+    project_id: i64,
+    delete_project: bool,
+    confirm_nonce: String,
+    state: tauri::State<'_, DbState>
+) -> Result<BTreeMap<String, u64>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.clear_project_data(project_id, delete_project, &confirm_nonce).await.map_err(|e| e.to_string())
+}
+
+// Get project by ID
+#[tauri::command]
+pub async fn get_project_by_id(
+    id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<Option<Project>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_project_by_id(id).await.map_err(|e| e.to_string())
+}
+
+// Get projects with no PR updates in the last N days
+#[tauri::command]
+pub async fn get_inactive_projects(
+    days: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<Vec<InactiveProject>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_inactive_projects(days).await.map_err(|e| e.to_string())
+}
+
+// Project most PRs from a repository are filed under
+#[tauri::command]
+pub async fn suggest_project_for_repo(
+    owner: String,
+    repo: String,
+    state: tauri::State<'_, DbState>
+) -> Result<Option<Project>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.suggest_project_for_repo(&owner, &repo).await.map_err(|e| e.to_string())
+}
+
+// Map a repository to the project its PRs belong in (None clears it); adding one elsewhere warns
+#[tauri::command]
+pub async fn set_repository_project(
+    owner: String,
+    repo: String,
+    project_id: Option<i64>,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.set_repository_project(&owner, &repo, project_id).await.map_err(|e| e.to_string())
+}
+
+// PRs filed under a different project than most PRs from their repository (report only)
+#[tauri::command]
+pub async fn find_misfiled_prs(
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<MisfiledPr>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.find_misfiled_prs().await.map(|prs| privacy.redact(prs)).map_err(|e| e.to_string())
+}
+
+// SLA compliance report for a project over the last N days
+#[tauri::command]
+pub async fn get_project_sla_report(
+    project_id: i64,
+    window_days: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<ProjectSlaReport, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    services::sla::get_project_sla_report(db, project_id, window_days, chrono::Utc::now().timestamp())
+        .await
+        .map(|report| privacy.redact(report))
+}
+
+// Share of a repository's merged PRs that got a local score
+#[tauri::command]
+pub async fn get_review_coverage(
+    owner: String,
+    repo: String,
+    state: tauri::State<'_, DbState>
+) -> Result<ReviewCoverage, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_review_coverage(Some((&owner, &repo)), None).await.map_err(|e| e.to_string())
+}
+
+// Share of a project's merged PRs that got a local score
+#[tauri::command]
+pub async fn get_project_review_coverage(
+    project_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<ReviewCoverage, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_review_coverage(None, Some(project_id)).await.map_err(|e| e.to_string())
+}
+
+// Share of all merged PRs that got a local score
+#[tauri::command]
+pub async fn get_global_review_coverage(state: tauri::State<'_, DbState>) -> Result<ReviewCoverage, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_review_coverage(None, None).await.map_err(|e| e.to_string())
+}
+
+// Put a team member on a project's roster (their PRs are unaffected)
+#[tauri::command]
+pub async fn add_project_member(
+    project_id: i64,
+    team_member_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.add_project_member(project_id, team_member_id).await.map_err(|e| e.to_string())
+}
+
+// Take a team member off a project's roster; their PRs, comments and reviews stay as they are
+#[tauri::command]
+pub async fn remove_project_member(
+    project_id: i64,
+    team_member_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.remove_project_member(project_id, team_member_id).await.map_err(|e| e.to_string())
+}
+
+// A project's explicit roster (empty when none was set up)
+#[tauri::command]
+pub async fn get_project_members(
+    project_id: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<TeamMember>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_project_members(project_id).await.map(|members| privacy.redact(members)).map_err(|e| e.to_string())
+}
@@ -0,0 +1,709 @@
+use crate::database::query::{BulkAction, FilterSpec};
+use crate::database::scheduled_tasks::{ScheduledTask, TASK_REFRESH_PR};
+use crate::database::cumulative_flow::CumulativeFlow;
+use crate::database::daily_stats::StatsTimeseries;
+use crate::database::author_backlog::AuthorBacklogReport;
+use crate::database::lifecycle::HistoryEntry;
+use crate::database::search::SearchHit;
+use crate::database::sync_conflicts::{ConflictChoice, SyncConflict, SyncField};
+use crate::database::pr_links::{PrExternalLink, StatusUpdateResult};
+use crate::database::transitions::BulkStatusUpdateResult;
+use crate::database::tray::TraySummary;
+use crate::database::waiting_on::WaitingOn;
+use crate::database::{AuthorScorecard, BoardData, BulkOperationResult, BulkPreview, BurndownPoint, PrComment, PrDetail, PrPriority, PullRequest, PullRequestWithAge, ReviewHistory, VelocityPoint};
+use crate::services::pr_list_cache::PrListCache;
+use crate::services::pr_summary::PrSummaryOptions;
+use crate::services::privacy::PrivacyState;
+use crate::display_time::local_date;
+use crate::DbState;
+
+// Get all pull requests with author and project names, optionally sorted ("updated", "reactions", "priority").
+// The default order is served from the startup cache while no data has changed.
+#[tauri::command]
+pub async fn get_pull_requests(
+    sort_by: Option<String>,
+    state: tauri::State<'_, DbState>,
+    cache: tauri::State<'_, PrListCache>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<PullRequest>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    crate::services::pr_list_cache::get_pull_requests(db, &cache, sort_by.as_deref()).await.map(|prs| privacy.redact(prs))
+}
+
+// Pull requests narrowed by project, status, author and whose move it is (each optional), in the default order
+#[tauri::command]
+pub async fn get_pull_requests_filtered(
+    project_id: Option<i64>,
+    status: Option<String>,
+    author_id: Option<i64>,
+    waiting_on: Option<WaitingOn>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<PullRequest>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_pull_requests_filtered(project_id, status.as_deref(), author_id, waiting_on)
+        .await
+        .map(|prs| privacy.redact(prs))
+        .map_err(|e| e.to_string())
+}
+
+// Pull requests in one status, matched exactly ("Waiting", "Reviewing", ...)
+#[tauri::command]
+pub async fn get_pull_requests_by_status(
+    status: String,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<PullRequest>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_pull_requests_by_status(&status).await.map(|prs| privacy.redact(prs)).map_err(|e| e.to_string())
+}
+
+// Full-text search over titles, branches and local notes, then authors and title fragments,
+// best matches first (at most 50)
+#[tauri::command]
+pub async fn search_pull_requests(
+    query: String,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<SearchHit>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.search_pull_requests(&query).await.map(|hits| privacy.redact(hits)).map_err(|e| e.to_string())
+}
+
+// Get all pull requests with server-computed age_seconds and time_since_update_seconds
+#[tauri::command]
+pub async fn get_pull_requests_with_age(
+    sort_by: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<PullRequestWithAge>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_pull_requests_with_age(sort_by.as_deref()).await.map(|prs| privacy.redact(prs)).map_err(|e| e.to_string())
+}
+
+// Open PR count per "day" or "week" bucket between two unix timestamps
+#[tauri::command]
+pub async fn get_open_pr_burndown(
+    from_ts: i64,
+    to_ts: i64,
+    bucket: String,
+    state: tauri::State<'_, DbState>
+) -> Result<Vec<BurndownPoint>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_open_pr_burndown(from_ts, to_ts, &bucket).await.map_err(|e| e.to_string())
+}
+
+// Merged PRs per week for the last `weeks` weeks (weeks start Monday in the display time zone),
+// optionally for one project. Weeks without merges are included with a zero count.
+#[tauri::command]
+pub async fn get_velocity(
+    weeks: i64,
+    project_id: Option<i64>,
+    state: tauri::State<'_, DbState>
+) -> Result<Vec<VelocityPoint>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let tz = db.get_display_timezone().await.map_err(|e| e.to_string())?;
+    db.get_velocity(weeks, project_id, chrono::Utc::now().timestamp(), tz).await.map_err(|e| e.to_string())
+}
+
+// One contributor's PRs started in [from_ts, to_ts): volume, merge rate, average score,
+// average time to first review, plus their current open count
+#[tauri::command]
+pub async fn get_author_scorecard(
+    github_username: String,
+    from_ts: i64,
+    to_ts: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<AuthorScorecard, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_author_scorecard(&github_username, from_ts, to_ts).await.map(|card| privacy.redact(card)).map_err(|e| e.to_string())
+}
+
+// Authors with more open PRs than the threshold (default: author_backlog_threshold), each
+// with their open PRs oldest first. Most open PRs first.
+#[tauri::command]
+pub async fn get_author_backlog_alerts(
+    threshold: Option<i64>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<AuthorBacklogReport, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let mut report = db.get_author_backlog_alerts(threshold, chrono::Utc::now().timestamp()).await.map_err(|e| e.to_string())?;
+    report.alerts = privacy.redact(report.alerts);
+    Ok(report)
+}
+
+// Tray numbers: waiting, overdue (no activity for stale_threshold_days), needing my review,
+// and the most urgent PR's display key. Cheap enough to poll.
+#[tauri::command]
+pub async fn get_tray_summary(state: tauri::State<'_, DbState>) -> Result<TraySummary, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_tray_summary(chrono::Utc::now().timestamp()).await.map_err(|e| e.to_string())
+}
+
+// Daily snapshot series for trend charts ("open_count", "avg_age_seconds", "avg_score", "status:<name>"),
+// ending today in the display time zone. Days without a snapshot are gaps, never interpolated.
+#[tauri::command]
+pub async fn get_stats_timeseries(
+    project_id: Option<i64>,
+    metric: String,
+    days: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<StatsTimeseries, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let tz = db.get_display_timezone().await.map_err(|e| e.to_string())?;
+    let today = local_date(chrono::Utc::now().timestamp(), tz);
+    db.get_stats_timeseries(project_id, &metric, days, today).await.map_err(|e| e.to_string())
+}
+
+// PRs per status at the end of each of the last `days` days (display time zone), rebuilt
+// from review history; works for days the app wasn't running, unlike get_stats_timeseries
+#[tauri::command]
+pub async fn get_cumulative_flow(
+    project_id: Option<i64>,
+    days: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<CumulativeFlow, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let tz = db.get_display_timezone().await.map_err(|e| e.to_string())?;
+    db.get_cumulative_flow(project_id, days, chrono::Utc::now().timestamp(), tz).await.map_err(|e| e.to_string())
+}
+
+// Get PRs whose title parsed to the given commit type (e.g. "feat")
+#[tauri::command]
+pub async fn get_prs_by_commit_type(
+    commit_type: String,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<PullRequest>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_prs_by_commit_type(&commit_type).await.map(|prs| privacy.redact(prs)).map_err(|e| e.to_string())
+}
+
+// Get merged PRs reviewed by at most one person
+#[tauri::command]
+pub async fn get_single_reviewer_prs(
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<PullRequest>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_single_reviewer_prs().await.map(|prs| privacy.redact(prs)).map_err(|e| e.to_string())
+}
+
+// Re-parse commit type, scope and ticket key from every title with the current title rules
+#[tauri::command]
+pub async fn apply_title_rules(state: tauri::State<'_, DbState>) -> Result<u64, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.apply_title_rules().await.map_err(|e| e.to_string())
+}
+
+// Get PRs with changes since they were last viewed
+#[tauri::command]
+pub async fn get_unread_pull_requests(
+    project_id: Option<i64>,
+    sort_by: Option<String>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<PullRequest>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_unread_pull_requests(project_id, sort_by.as_deref()).await.map(|prs| privacy.redact(prs)).map_err(|e| e.to_string())
+}
+
+// Mark a PR as viewed (called when the detail pane opens)
+#[tauri::command]
+pub async fn mark_pr_viewed(
+    pr_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.mark_pr_viewed(pr_id).await.map_err(|e| e.to_string())
+}
+
+// Mark all PRs (or all PRs in a project) as viewed
+#[tauri::command]
+pub async fn mark_all_viewed(
+    project_id: Option<i64>,
+    state: tauri::State<'_, DbState>
+) -> Result<u64, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.mark_all_viewed(project_id).await.map_err(|e| e.to_string())
+}
+
+// Get the compact kanban board payload, optionally scoped to a project
+#[tauri::command]
+pub async fn get_board_data(
+    project_id: Option<i64>,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<BoardData, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_board_data(project_id).await.map(|board| privacy.redact(board)).map_err(|e| e.to_string())
+}
+
+// Update PR status. Moving to Merged with unresolved blocking links fails listing them,
+// or succeeds and returns them when blocking_links_enforcement is "warn"
+#[tauri::command]
+pub async fn update_pr_status(
+    pr_id: i64,
+    status: String,
+    state: tauri::State<'_, DbState>
+) -> Result<StatusUpdateResult, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.update_pr_status(&ctx, pr_id, status).await.map_err(|e| e.to_string())
+}
+
+// Move several PRs to one status; strict-mode and blocking-link rejections are reported per PR
+#[tauri::command]
+pub async fn bulk_update_pr_status(
+    pr_ids: Vec<i64>,
+    status: String,
+    state: tauri::State<'_, DbState>
+) -> Result<BulkStatusUpdateResult, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.bulk_update_pr_status(&ctx, &pr_ids, &status).await.map_err(|e| e.to_string())
+}
+
+// Pin a PR's status so automatic updates (e.g. close_merged_prs) leave it alone
+#[tauri::command]
+pub async fn set_pr_status_pinned(
+    pr_id: i64,
+    pinned: bool,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.set_pr_status_pinned(&ctx, pr_id, pinned).await.map_err(|e| e.to_string())
+}
+
+// Update PR score
+#[tauri::command]
+pub async fn update_pr_score(
+    pr_id: i64,
+    score: i32,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.update_pr_score(&ctx, pr_id, score).await.map_err(|e| e.to_string())
+}
+
+// Clear a PR's score back to unscored
+#[tauri::command]
+pub async fn clear_pr_score(
+    pr_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.clear_pr_score(&ctx, pr_id).await.map_err(|e| e.to_string())
+}
+
+// Correct a PR's title locally; refreshes then follow the title sync policy
+#[tauri::command]
+pub async fn update_pr_title(
+    pr_id: i64,
+    title: String,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.set_pr_field_locally(&ctx, pr_id, SyncField::Title, &title).await.map_err(|e| e.to_string())
+}
+
+// Correct a PR's branch locally; refreshes then follow the branch sync policy
+#[tauri::command]
+pub async fn update_pr_branch(
+    pr_id: i64,
+    branch: String,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.set_pr_field_locally(&ctx, pr_id, SyncField::Branch, &branch).await.map_err(|e| e.to_string())
+}
+
+// GitHub values held back under the "prompt" sync policy, oldest first
+#[tauri::command]
+pub async fn get_sync_conflicts(
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<SyncConflict>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_sync_conflicts().await.map(|conflicts| privacy.redact(conflicts)).map_err(|e| e.to_string())
+}
+
+// Keep the local edit ("local") or take GitHub's value ("github") for one conflict
+#[tauri::command]
+pub async fn resolve_sync_conflict(
+    id: i64,
+    choose: ConflictChoice,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.resolve_sync_conflict(id, choose).await.map_err(|e| e.to_string())
+}
+
+// Set PR priority ("High" | "Medium" | "Low")
+#[tauri::command]
+pub async fn set_pr_priority(
+    pr_id: i64,
+    priority: PrPriority,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.set_pr_priority(&ctx, pr_id, priority).await.map_err(|e| e.to_string())
+}
+
+// Remove a PR added by mistake along with its history; returns the number of history rows deleted
+#[tauri::command]
+pub async fn delete_pull_request(
+    pr_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<u64, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.delete_pull_request(&ctx, pr_id).await.map_err(|e| e.to_string())
+}
+
+// Update PR project assignment
+#[tauri::command]
+pub async fn update_pr_project(
+    pr_id: i64,
+    project_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.update_pr_project(&ctx, pr_id, project_id).await.map_err(|e| e.to_string())
+}
+
+// Add a local reviewer comment to a PR
+#[tauri::command]
+pub async fn add_pr_comment(
+    pr_id: i64,
+    author_member_id: i64,
+    body: String,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<PrComment, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.add_pr_comment(&ctx, pr_id, author_member_id, body).await.map(|comment| privacy.redact(comment)).map_err(|e| e.to_string())
+}
+
+// Get the local comment thread for a PR, oldest first
+#[tauri::command]
+pub async fn get_pr_comments(
+    pr_id: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<PrComment>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_pr_comments(pr_id).await.map(|comments| privacy.redact(comments)).map_err(|e| e.to_string())
+}
+
+// Get one PR with its external links
+#[tauri::command]
+pub async fn get_pr_detail(
+    pr_id: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<PrDetail, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_pr_detail(pr_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|detail| privacy.redact(detail))
+        .ok_or_else(|| format!("Pull request {} not found", pr_id))
+}
+
+// Compact Markdown card about one PR for meeting notes; include_local_notes = false for sharing outside the team
+#[tauri::command]
+pub async fn generate_pr_summary_markdown(
+    pr_id: i64,
+    include_local_notes: bool,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<String, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let options = PrSummaryOptions { include_local_notes };
+    crate::services::pr_summary::generate_pr_summary_markdown(db, &privacy, pr_id, options, chrono::Utc::now().timestamp()).await
+}
+
+// Attach an http(s) link (Jira ticket, design doc, staging URL, ...) to the end of a PR's list
+#[tauri::command]
+pub async fn add_pr_external_link(
+    pr_id: i64,
+    label: String,
+    url: String,
+    state: tauri::State<'_, DbState>
+) -> Result<PrExternalLink, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.add_pr_external_link(pr_id, &label, &url).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_pr_external_link(
+    link_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.remove_pr_external_link(link_id).await.map_err(|e| e.to_string())
+}
+
+// Mark a link as a dependency the PR can't be done without (or clear that)
+#[tauri::command]
+pub async fn set_link_blocking(
+    link_id: i64,
+    blocking: bool,
+    state: tauri::State<'_, DbState>
+) -> Result<PrExternalLink, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.set_link_blocking(link_id, blocking).await.map_err(|e| e.to_string())
+}
+
+// Record that the linked ticket is closed (or reopened)
+#[tauri::command]
+pub async fn set_link_resolved(
+    link_id: i64,
+    resolved: bool,
+    state: tauri::State<'_, DbState>
+) -> Result<PrExternalLink, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.set_link_resolved(link_id, resolved).await.map_err(|e| e.to_string())
+}
+
+// Move a link right after another one (None = to the top); returns the reordered list
+#[tauri::command]
+pub async fn move_pr_external_link(
+    link_id: i64,
+    after_link_id: Option<i64>,
+    state: tauri::State<'_, DbState>
+) -> Result<Vec<PrExternalLink>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.move_pr_external_link(link_id, after_link_id).await.map_err(|e| e.to_string())
+}
+
+/// Check if a PR with the given GitHub ID already exists
+#[tauri::command]
+pub async fn check_pr_exists_by_github_id(
+    github_id: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Option<PullRequest>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_pull_request_by_github_id(github_id).await.map(|pr| privacy.redact(pr)).map_err(|e| e.to_string())
+}
+
+// Archive every PR matching the filter
+#[tauri::command]
+pub async fn bulk_archive_by_filter(
+    filter: FilterSpec,
+    state: tauri::State<'_, DbState>
+) -> Result<BulkOperationResult, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.bulk_archive_by_filter(&ctx, &filter).await.map_err(|e| e.to_string())
+}
+
+// What a bulk operation would touch, without running it: total count plus the first 500 PRs
+#[tauri::command]
+pub async fn preview_bulk_by_filter(
+    filter: FilterSpec,
+    action: BulkAction,
+    state: tauri::State<'_, DbState>
+) -> Result<BulkPreview, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.preview_bulk_by_filter(&filter, action).await.map_err(|e| e.to_string())
+}
+
+// Move an archived PR back to the status it was archived from
+#[tauri::command]
+pub async fn restore_archived_pr(
+    pr_id: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<PullRequest, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    db.restore_archived_pr(&ctx, pr_id).await.map(|pr| privacy.redact(pr)).map_err(|e| e.to_string())
+}
+
+// Review actions and lifecycle events for one PR, oldest first
+#[tauri::command]
+pub async fn get_pr_history(
+    pr_id: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<HistoryEntry>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_pr_history(pr_id).await.map(|history| privacy.redact(history)).map_err(|e| e.to_string())
+}
+
+// review_history rows for one PR, newest first
+#[tauri::command]
+pub async fn get_review_history(
+    pr_id: i64,
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<ReviewHistory>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_review_history(pr_id).await.map(|history| privacy.redact(history)).map_err(|e| e.to_string())
+}
+
+// Issue a single-use nonce confirming a destructive action (e.g. "bulk_delete")
+#[tauri::command]
+pub async fn request_confirmation_nonce(
+    action: String,
+    state: tauri::State<'_, DbState>
+) -> Result<String, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.issue_confirmation_nonce(&action).await.map_err(|e| e.to_string())
+}
+
+// Delete every PR matching the filter (requires a "bulk_delete" nonce; backs up first)
+#[tauri::command]
+pub async fn bulk_delete_by_filter(
+    filter: FilterSpec,
+    confirm_nonce: String,
+    state: tauri::State<'_, DbState>
+) -> Result<BulkOperationResult, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.bulk_delete_by_filter(&filter, &confirm_nonce).await.map_err(|e| e.to_string())
+}
+
+// Schedule a one-off GitHub refresh of a PR at `at_ts` (unix seconds); returns the task id
+#[tauri::command]
+pub async fn schedule_pr_refresh(
+    pr_id: i64,
+    at_ts: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<i64, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.schedule_task(pr_id, TASK_REFRESH_PR, at_ts, chrono::Utc::now().timestamp())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Cancel a scheduled refresh that hasn't run yet
+#[tauri::command]
+pub async fn cancel_scheduled_refresh(
+    task_id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.cancel_scheduled_task(task_id).await.map_err(|e| e.to_string())
+}
+
+// Get pending scheduled refreshes, soonest first
+#[tauri::command]
+pub async fn get_scheduled_refreshes(state: tauri::State<'_, DbState>) -> Result<Vec<ScheduledTask>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_pending_scheduled_tasks().await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,204 @@
+use crate::database::settings_registry::SettingsDiff;
+use crate::database::sync_constraints::SyncConstraints;
+use crate::database::transitions::{StatusTransitions, TransitionGraph};
+use crate::database::{LocalProfile, SettingsImportResult};
+use crate::services;
+use crate::services::privacy::PrivacyState;
+use crate::DbState;
+use std::collections::BTreeMap;
+
+// Get a single setting value
+#[tauri::command]
+pub async fn get_setting(
+    key: String,
+    state: tauri::State<'_, DbState>
+) -> Result<Option<String>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_setting(&key).await.map_err(|e| e.to_string())
+}
+
+// Set a setting value (only known keys are accepted)
+#[tauri::command]
+pub async fn set_setting(
+    key: String,
+    value: String,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.set_setting(&key, &value).await.map_err(|e| e.to_string())?;
+    services::rate_limit::reload_budgets(db).await;
+    services::pr_list_cache::reload_cache_limits(db).await;
+    Ok(())
+}
+
+// Get all stored settings
+#[tauri::command]
+pub async fn get_all_settings(state: tauri::State<'_, DbState>) -> Result<BTreeMap<String, String>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_all_settings().await.map_err(|e| e.to_string())
+}
+
+// Settings that differ from their defaults, grouped by feature area (for support questions)
+#[tauri::command]
+pub async fn get_settings_diff(state: tauri::State<'_, DbState>) -> Result<SettingsDiff, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_settings_diff().await.map_err(|e| e.to_string())
+}
+
+// Go back to the default for one setting
+#[tauri::command]
+pub async fn reset_setting(
+    key: String,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.reset_setting(&key).await.map_err(|e| e.to_string())?;
+    services::rate_limit::reload_budgets(db).await;
+    services::pr_list_cache::reload_cache_limits(db).await;
+    Ok(())
+}
+
+// Go back to the defaults for every user setting; needs a "reset_all_settings" nonce
+#[tauri::command]
+pub async fn reset_all_settings(
+    confirm_nonce: String,
+    state: tauri::State<'_, DbState>
+) -> Result<Vec<String>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let reset = db.reset_all_settings(&confirm_nonce).await.map_err(|e| e.to_string())?;
+    services::rate_limit::reload_budgets(db).await;
+    services::pr_list_cache::reload_cache_limits(db).await;
+    Ok(reset)
+}
+
+// Export only the settings (no projects/PRs) as JSON
+#[tauri::command]
+pub async fn export_settings_json(
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<String, String> {
+    privacy.ensure_export_allowed()?;
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.export_settings_json().await.map_err(|e| e.to_string())
+}
+
+// Restore settings from an export, skipping unknown keys
+#[tauri::command]
+pub async fn import_settings_json(
+    json: String,
+    state: tauri::State<'_, DbState>
+) -> Result<SettingsImportResult, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    let result = db.import_settings_json(&json).await.map_err(|e| e.to_string())?;
+    services::rate_limit::reload_budgets(db).await;
+    services::pr_list_cache::reload_cache_limits(db).await;
+    Ok(result)
+}
+
+// Effective IANA zone for human-facing timestamps (display_timezone setting, else the system zone)
+#[tauri::command]
+pub async fn get_display_timezone(state: tauri::State<'_, DbState>) -> Result<String, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_display_timezone().await.map(|tz| tz.name().to_string()).map_err(|e| e.to_string())
+}
+
+// Current SQLite busy timeout in milliseconds
+#[tauri::command]
+pub async fn get_busy_timeout(state: tauri::State<'_, DbState>) -> Result<u64, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    Ok(db.get_busy_timeout())
+}
+
+// Raise (or lower) how long queries wait on a locked database before failing
+#[tauri::command]
+pub async fn set_busy_timeout(
+    ms: u64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.set_busy_timeout(ms).await.map_err(|e| e.to_string())
+}
+
+// Transition graph and whether strict mode enforces it
+#[tauri::command]
+pub async fn get_status_transitions(state: tauri::State<'_, DbState>) -> Result<StatusTransitions, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_status_transitions().await.map_err(|e| e.to_string())
+}
+
+// Store the status -> allowed next statuses graph (statuses must be known, every column reachable)
+#[tauri::command]
+pub async fn set_status_transitions(
+    graph: TransitionGraph,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.set_status_transitions(&graph).await.map_err(|e| e.to_string())
+}
+
+// When background syncs hold back: on battery, on a metered connection, or at most every N minutes on battery
+#[tauri::command]
+pub async fn get_sync_constraints(state: tauri::State<'_, DbState>) -> Result<SyncConstraints, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_sync_constraints().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_sync_constraints(
+    constraints: SyncConstraints,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.set_sync_constraints(&constraints).await.map_err(|e| e.to_string())
+}
+
+// List local profiles (people sharing this machine)
+#[tauri::command]
+pub async fn get_local_profiles(state: tauri::State<'_, DbState>) -> Result<Vec<LocalProfile>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_local_profiles().await.map_err(|e| e.to_string())
+}
+
+// Switch to a local profile, creating it on first use; viewed/pinned state follows the profile
+#[tauri::command]
+pub async fn switch_local_profile(
+    name: String,
+    state: tauri::State<'_, DbState>
+) -> Result<LocalProfile, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.switch_local_profile(&name).await.map_err(|e| e.to_string())
+}
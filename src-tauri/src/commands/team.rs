@@ -0,0 +1,27 @@
+use crate::database::TeamMember;
+use crate::services::privacy::PrivacyState;
+use crate::DbState;
+
+// Get all team members, ordered by GitHub username
+#[tauri::command]
+pub async fn get_team_members(
+    state: tauri::State<'_, DbState>,
+    privacy: tauri::State<'_, PrivacyState>
+) -> Result<Vec<TeamMember>, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.get_team_members().await.map(|members| privacy.redact(members)).map_err(|e| e.to_string())
+}
+
+// Delete a team member; refused while any PR, comment or review still references them
+#[tauri::command]
+pub async fn delete_team_member(
+    id: i64,
+    state: tauri::State<'_, DbState>
+) -> Result<(), String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+
+    db.delete_team_member(id).await.map_err(|e| e.to_string())
+}
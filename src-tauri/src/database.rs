@@ -1,9 +1,70 @@
+pub mod actor;
+pub mod api_usage;
+pub mod author_backlog;
+pub mod avatars;
+pub mod cumulative_flow;
+pub mod daily_stats;
+pub mod display_key;
+pub mod export_bundle;
+pub mod github_reviews;
+pub mod import_defaults;
+pub mod lifecycle;
+pub mod migrations;
+pub mod onboarding;
+pub mod ordering;
+pub mod pr_links;
+pub mod project_members;
+pub mod query;
+pub mod reimport;
+pub mod search;
+pub mod releases;
+pub mod repo_mappings;
+pub mod review_comments;
+pub mod scheduled_tasks;
+pub mod settings_registry;
+pub mod status;
+pub mod sync_conflicts;
+pub mod sync_constraints;
+pub mod sync_runs;
+pub mod transfer;
+pub mod transitions;
+pub mod tray;
+pub mod waiting_on;
+
 use anyhow::Result;
 use chrono::Utc;
 use dirs::data_dir;
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, SqlitePool};
-use std::path::PathBuf;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+use sqlx::{QueryBuilder, Row, Sqlite, SqliteConnection, SqlitePool};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actor::{validate_local_identity, WriteContext, LOCAL_IDENTITY_KEY};
+use author_backlog::{parse_author_backlog_threshold, AUTHOR_BACKLOG_THRESHOLD_KEY};
+use display_key::{assign_display_keys, display_key, display_keys};
+use import_defaults::{validate_import_open_status, MergedImportScoring, IMPORT_OPEN_STATUS_KEY, MERGED_IMPORT_SCORING_KEY};
+use lifecycle::{record_lifecycle_event, record_status_move, LifecycleEvent};
+use pr_links::{blocking_links_for_move, BlockedByLinks, BlockingLinksEnforcement, StatusUpdateResult, BLOCKING_LINKS_KEY};
+use query::{BulkAction, FilterSpec, PrQuery};
+use repo_mappings::STRICT_REPO_MAPPING_KEY;
+use transitions::{check_status_transition, parse_strict_flag, TransitionGraph, STATUS_TRANSITIONS_KEY, STRICT_STATUS_TRANSITIONS_KEY};
+use sync_conflicts::{SyncPolicies, SYNC_POLICIES_KEY};
+use sync_constraints::{SyncConstraints, SYNC_CONSTRAINTS_KEY};
+use settings_registry::{is_user_setting, DEFAULT_BUSY_TIMEOUT_MS, DEFAULT_PROJECT_KEY};
+use status::PrStatus;
+use waiting_on::{follow_status, WaitingOn};
+
+use crate::bounded_cache::{CacheLimits, CACHE_LIMITS_KEY};
+use crate::display_time::{self, parse_timezone, system_timezone, DISPLAY_TIMEZONE_KEY};
+use crate::github::rate_limit::{RateBudgets, RateLimitState, RATE_LIMIT_BUDGETS_KEY, RATE_LIMIT_STATE_KEY};
+use crate::github::token_grants::TOKEN_EXPIRY_KEY;
+use crate::github::token_permissions::FeatureUsage;
+use crate::title_rules::{self, ParsedTitle, TITLE_RULES_KEY};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TeamMember {
@@ -12,6 +73,7 @@ pub struct TeamMember {
     pub avatar_url: Option<String>,
     pub display_name: Option<String>,
     pub created_at: i64,
+    pub avatar_path: Option<String>, // downloaded copy of avatar_url, see services/avatars.rs
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +82,119 @@ pub struct Project {
     pub name: String,
     pub description: Option<String>,
     pub created_at: i64,
+    pub sla_hours: Option<i64>,    // business hours allowed until first review; None = no SLA
+    pub target_score: Option<i32>, // percentage of SLA-met PRs to aim for, 0..=100
+}
+
+// Start and first-review times of a project's PR, input for SLA reporting
+#[derive(Debug, Clone)]
+pub struct PrReviewTiming {
+    pub pr_id: i64,
+    pub pr_number: i64,
+    pub display_key: String,
+    pub title: Option<String>,
+    pub started_at: i64,
+    pub first_reviewed_at: Option<i64>,
+    pub waiting_on: WaitingOn,
+}
+
+// One contributor's PRs started within [from_ts, to_ts); open_count is current, not windowed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuthorScorecard {
+    pub github_username: String,
+    pub from_ts: i64,
+    pub to_ts: i64,
+    pub authored_count: i64,
+    pub merged_count: i64,
+    pub merge_rate: Option<f64>, // None when nothing was authored in the window
+    pub avg_score: Option<f64>,
+    pub scored_count: i64,
+    pub avg_time_to_first_review_seconds: Option<f64>, // over PRs that got a review
+    pub open_count: i64,
+    pub review_comment_count: i64, // whole weeks overlapping the window; 0 until sync_review_comment_stats runs
+}
+
+// Open PR count at the end of one burndown bucket
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BurndownPoint {
+    pub bucket_start: i64,
+    pub bucket_end: i64,
+    pub open_count: i64,
+}
+
+const MAX_BURNDOWN_BUCKETS: i64 = 1_000;
+
+// Merged PRs in one week (Monday 00:00 in the display time zone)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VelocityPoint {
+    pub week_start: i64,
+    pub merged_count: i64,
+}
+
+const MAX_VELOCITY_WEEKS: i64 = 104;
+
+// How many merged PRs got a local score
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReviewCoverage {
+    pub merged_count: i64,
+    pub scored_count: i64,
+    pub coverage_ratio: Option<f64>, // None when nothing was merged
+}
+
+// Statuses and review_history actions that mean a PR is no longer open
+const CLOSED_STATUSES: [&str; 2] = ["Merged", "archived"];
+const CLOSING_ACTIONS: [&str; 3] = ["merged", "closed", "archived"];
+
+// Scores are out of 10, matching the UI's 1-10 picker
+const SCORE_RANGE: std::ops::RangeInclusive<i32> = 1..=10;
+
+fn check_score(score: i32) -> Result<()> {
+    if !SCORE_RANGE.contains(&score) {
+        return Err(anyhow::anyhow!(
+            "Score must be between {} and {}, got {}", SCORE_RANGE.start(), SCORE_RANGE.end(), score
+        ));
+    }
+    Ok(())
+}
+
+async fn current_score(conn: &mut SqliteConnection, pr_id: i64) -> Result<Option<i32>> {
+    let current: Option<Option<i32>> = sqlx::query_scalar("SELECT score FROM pull_requests WHERE id = ?")
+        .bind(pr_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    current.ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))
+}
+
+// "score_changed: <from> -> <to>", with "none" for unscored
+async fn record_score_change(conn: &mut SqliteConnection, ctx: &WriteContext, pr_id: i64, from: Option<i32>, to: Option<i32>) -> Result<()> {
+    let show = |score: Option<i32>| score.map_or("none".to_string(), |score| score.to_string());
+    sqlx::query("INSERT INTO review_history (pr_id, action, performed_at, actor) VALUES (?, ?, ?, ?)")
+        .bind(pr_id)
+        .bind(format!("score_changed: {} -> {}", show(from), show(to)))
+        .bind(chrono::Utc::now().timestamp())
+        .bind(&ctx.actor)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+fn sql_string_list(values: &[&str]) -> String {
+    values.iter().map(|v| format!("'{}'", v)).collect::<Vec<_>>().join(", ")
+}
+
+// review_history actions logged for submitted GitHub reviews (database/github_reviews.rs);
+// the earliest is a PR's first review for the SLA and the author scorecard
+const REVIEW_ACTIONS: [&str; 3] = ["approved", "changes_requested", "commented"];
+
+// Project with no PR activity within a threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InactiveProject {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: i64,
+    pub pr_count: i64,
+    pub last_activity_at: Option<i64>, // None when the project has no PRs
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,29 +211,671 @@ pub struct PullRequest {
     pub author_avatar: Option<String>,
     pub author_display_name: Option<String>,
     pub project_name: Option<String>,
-    pub status: String,
+    pub status: PrStatus,
+    pub waiting_on: WaitingOn, // whose move it is; follows the status, see database/waiting_on.rs
     pub branch: Option<String>,
     pub score: Option<i32>,
+    pub exclude_from_scoring: bool, // merged before it was tracked; left out of score coverage
+    pub repository_owner: Option<String>,
+    pub repository_name: Option<String>,
+    pub github_created_at: Option<i64>,
+    pub github_updated_at: Option<i64>,
+    pub last_viewed_at: Option<i64>,
+    pub has_unread_changes: bool,
+    pub reactions_total: Option<i64>,
+    pub reactions_plus_one: Option<i64>,
+    pub priority: PrPriority,
+    pub status_pinned: bool, // user pinned the column; automatic status changes leave it alone
+    // Parsed from the title by the title rules; the raw title is kept as is
+    pub commit_type: Option<String>,
+    pub commit_scope: Option<String>,
+    pub ticket_key: Option<String>,
+    pub is_issue: bool, // tracked issue not yet linked to a PR; github_id is the negated issue id
+    pub added_by: Option<String>, // actor who added it here; None on older rows and reimports
+    pub merge_commit_sha: Option<String>,   // recorded when GitHub reports the PR merged
+    pub shipped_in_release: Option<String>, // tag of the earliest release containing the merge commit
+    pub reviewer_count: i64, // distinct people in pr_reviewers
+    // Lines added/removed and files touched, as GitHub last reported; None on older rows
+    pub additions: Option<i64>,
+    pub deletions: Option<i64>,
+    pub changed_files: Option<i64>,
+    pub project_missing: bool, // project_id points at a row that no longer exists
+    pub author_missing: bool,  // same for author_id; fix_missing_references repairs both
+    // "repo#512", or "owner/repo#512" when the repo name is ambiguous within the returned list
+    pub display_key: String,
+}
+
+// Triage priority, stored as an integer so it sorts naturally (High first when DESC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrPriority {
+    Low,
+    Medium,
+    High,
+}
+
+impl PrPriority {
+    pub fn as_i64(self) -> i64 {
+        match self {
+            PrPriority::Low => 1,
+            PrPriority::Medium => 2,
+            PrPriority::High => 3,
+        }
+    }
+
+    // Unknown values fall back to the column default
+    pub fn from_i64(value: i64) -> Self {
+        match value {
+            1 => PrPriority::Low,
+            3 => PrPriority::High,
+            _ => PrPriority::Medium,
+        }
+    }
+}
+
+impl std::fmt::Display for PrPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PrPriority::Low => "Low",
+            PrPriority::Medium => "Medium",
+            PrPriority::High => "High",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Local profile for people sharing one machine. User-owned PR state (viewed, pinned)
+// is kept per profile in pr_profile_state; everything else on a PR is shared.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LocalProfile {
+    pub id: i64,
+    pub name: String,
+    pub is_active: bool,
+    pub created_at: i64,
+}
+
+pub const DEFAULT_PROFILE_NAME: &str = "default";
+
+// The profile whose overlays PR queries resolve
+const ACTIVE_PROFILE_ID: &str = "(SELECT id FROM local_profiles WHERE is_active = 1)";
+
+// Tables holding per-PR rows that go away with the PR
+const PR_CHILD_TABLES: [&str; 9] = [
+    "review_history", "pr_comments", "retry_queue", "import_conflicts", "pr_profile_state", "pr_reviewers", "scheduled_tasks",
+    "pr_links_external", "sync_conflicts",
+];
+
+// Merged PRs stay merged in reports after being archived
+const PR_WAS_MERGED: &str = "(pr.status = 'Merged' OR EXISTS (SELECT 1 FROM review_history rh WHERE rh.pr_id = pr.id AND rh.action = 'merged'))";
+
+// Repository referenced by tracked PRs, with the result of the last access audit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrackedRepository {
+    pub id: i64,
+    pub owner: String,
+    pub name: String,
+    pub accessible: Option<bool>, // None until audited
+    pub last_checked_at: Option<i64>,
+    pub project_id: Option<i64>, // project PRs from this repository belong in (repo_mappings.rs)
+}
+
+// PR filed under a different project than most PRs from its repository
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MisfiledPr {
+    pub pr_id: i64,
+    pub pr_number: i64,
+    pub title: Option<String>,
+    pub repository_owner: String,
+    pub repository_name: String,
+    pub current_project_id: i64,
+    pub current_project_name: String,
+    pub current_project_count: i64, // PRs from this repo in the current project
+    pub suggested_project_id: i64,
+    pub suggested_project_name: String,
+    pub suggested_project_count: i64,
+}
+
+// Where a deleted project's PRs go: {"project": id} or "unassigned"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectDeleteTarget {
+    Project(i64),
+    Unassigned,
+}
+
+// Outcome of delete_project_with_reassign
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProjectDeleteSummary {
+    pub deleted_project_id: i64,
+    pub target_project_id: Option<i64>, // None when the PRs were unassigned
+    pub moved_prs: u64,
+}
+
+// Outcome of fix_missing_references
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MissingReferenceFix {
+    pub projects_cleared: u64,
+    pub authors_relinked: u64,
+}
+
+// Team member that PRs with a deleted author are relinked to (GitHub's own name for deleted accounts)
+pub const PLACEHOLDER_MEMBER_USERNAME: &str = "ghost";
+
+// Outcome of backfilling repository_owner/repository_name on legacy rows
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoRepairSummary {
+    pub repaired: u32,
+    pub unrepairable: u32,
+    pub errors: Vec<String>,
+}
+
+// Fields for inserting a new pull request row
+#[derive(Debug, Clone, Default)]
+pub struct NewPullRequest {
+    pub github_id: i64,
+    pub pr_number: i64,
+    pub title: Option<String>,
+    pub author_id: i64,
+    pub project_id: Option<i64>,
+    pub branch: Option<String>,
+    pub status: PrStatus,
     pub repository_owner: Option<String>,
     pub repository_name: Option<String>,
+    pub github_created_at: Option<i64>,
+    pub github_updated_at: Option<i64>,
+    pub reactions_total: Option<i64>,
+    pub reactions_plus_one: Option<i64>,
+    pub additions: Option<i64>,
+    pub deletions: Option<i64>,
+    pub changed_files: Option<i64>,
+    pub is_issue: bool,
+}
+
+// PullRequest plus ages computed by SQLite at query time, so every client agrees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestWithAge {
+    #[serde(flatten)]
+    pub pull_request: PullRequest,
+    pub age_seconds: Option<i64>, // None for legacy rows without github_created_at
+    pub time_since_update_seconds: i64,
+}
+
+// A single PR with what hangs off it, for the detail view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrDetail {
+    #[serde(flatten)]
+    pub pull_request: PullRequest,
+    pub external_links: Vec<pr_links::PrExternalLink>,
+    // App versions that inserted and last wrote the row; None for rows older than the tracking
+    pub created_by_version: Option<String>,
+    pub updated_by_version: Option<String>,
 }
 
+// A team member who reviewed a PR (pr_reviewers, filled from GitHub reviews on add and refresh)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrReviewer {
+    pub team_member_id: i64,
+    pub github_username: Option<String>, // None when the member row is missing
+    pub display_name: Option<String>,
+    pub reviewed_at: i64,
+}
+
+// Failed background operation waiting for another attempt. Parked entries have
+// exhausted RETRY_MAX_ATTEMPTS and only run again via retry_now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryEntry {
+    pub id: i64,
+    pub pr_id: i64,
+    pub operation: String,
+    pub attempts: i64,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub parked: bool,
+    pub created_at: i64,
+}
+
+pub const RETRY_MAX_ATTEMPTS: i64 = 5;
+const RETRY_BASE_DELAY_SECS: i64 = 60;
+const RETRY_MAX_DELAY_SECS: i64 = 6 * 60 * 60;
+
+// Exponential backoff after the given number of failed attempts: 1m, 2m, 4m, ... capped at 6h
+pub fn retry_backoff_secs(attempts: i64) -> i64 {
+    let exponent = attempts.clamp(1, 20) - 1;
+    (RETRY_BASE_DELAY_SECS << exponent).min(RETRY_MAX_DELAY_SECS)
+}
+
+// One row of the confirmation preview returned by bulk operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkPrSummary {
+    pub id: i64,
+    pub pr_number: i64,
+    pub title: Option<String>,
+    pub status: PrStatus,
+    pub project_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkOperationResult {
+    pub affected: u64,
+    pub sample: Vec<BulkPrSummary>, // first BULK_SAMPLE_SIZE affected PRs
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkPreview {
+    pub action: BulkAction,
+    pub total: u64,
+    pub prs: Vec<BulkPrSummary>, // first BULK_PREVIEW_LIMIT matches, oldest id first
+}
+
+// Bulk operations run in transactions of this many rows
+const BULK_CHUNK_SIZE: usize = 500;
+const BULK_SAMPLE_SIZE: usize = 20;
+pub const BULK_PREVIEW_LIMIT: i64 = 500;
+
+// Actions that require a nonce from issue_confirmation_nonce
+pub const CONFIRM_BULK_DELETE: &str = "bulk_delete";
+pub const CONFIRM_CLEAR_PROJECT: &str = "clear_project";
+pub const CONFIRM_REIMPORT_REPOSITORY: &str = "reimport_repository";
+pub const CONFIRM_RESET_SETTINGS: &str = "reset_all_settings";
+const CONFIRMATION_NONCE_TTL_SECS: i64 = 5 * 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReviewHistory {
     pub id: i64,
     pub pr_id: i64,
     pub action: String,
     pub performed_at: i64,
+    pub actor: Option<String>, // who made the change locally; None for GitHub's changes and older rows
+}
+
+// Local reviewer comment on a PR, with the author's display info joined in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrComment {
+    pub id: i64,
+    pub pr_id: i64,
+    pub author_member_id: i64,
+    pub body: String,
+    pub created_at: i64,
+    pub author_name: Option<String>,
+    pub author_display_name: Option<String>,
+    pub author_avatar: Option<String>,
+    pub added_by: Option<String>, // who wrote it locally (WriteContext); None for older comments
+}
+
+// Result of restoring a settings snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsImportResult {
+    pub imported: Vec<String>,
+    pub skipped_unknown: Vec<String>,
+}
+
+// Upper bound for the busy timeout (default in settings_registry)
+const MAX_BUSY_TIMEOUT_MS: u64 = 600_000;
+const BUSY_TIMEOUT_KEY: &str = "busy_timeout_ms";
+
+fn parse_busy_timeout(value: &str) -> Result<u64> {
+    match value.trim().parse::<u64>() {
+        Ok(ms) if ms <= MAX_BUSY_TIMEOUT_MS => Ok(ms),
+        _ => Err(anyhow::anyhow!("Busy timeout must be between 0 and {} ms", MAX_BUSY_TIMEOUT_MS)),
+    }
+}
+
+// Per-key value checks applied on set and import
+fn validate_setting_value(key: &str, value: &str) -> Result<()> {
+    match key {
+        DISPLAY_TIMEZONE_KEY => {
+            parse_timezone(value).map_err(|e| anyhow::anyhow!(e))?;
+        }
+        TITLE_RULES_KEY => {
+            title_rules::parse_rules(value).map_err(|e| anyhow::anyhow!(e))?;
+        }
+        BUSY_TIMEOUT_KEY => {
+            parse_busy_timeout(value)?;
+        }
+        IMPORT_OPEN_STATUS_KEY => {
+            validate_import_open_status(value)?;
+        }
+        MERGED_IMPORT_SCORING_KEY => {
+            MergedImportScoring::parse(value)?;
+        }
+        LOCAL_IDENTITY_KEY => {
+            validate_local_identity(value)?;
+        }
+        BLOCKING_LINKS_KEY => {
+            BlockingLinksEnforcement::parse(value)?;
+        }
+        RATE_LIMIT_BUDGETS_KEY => {
+            RateBudgets::parse(value).map_err(|e| anyhow::anyhow!(e))?;
+        }
+        STATUS_TRANSITIONS_KEY => {
+            TransitionGraph::parse(value)?;
+        }
+        STRICT_STATUS_TRANSITIONS_KEY | STRICT_REPO_MAPPING_KEY => {
+            parse_strict_flag(key, value)?;
+        }
+        SYNC_CONSTRAINTS_KEY => {
+            SyncConstraints::parse(value)?;
+        }
+        SYNC_POLICIES_KEY => {
+            SyncPolicies::parse(value)?;
+        }
+        CACHE_LIMITS_KEY => {
+            CacheLimits::parse(value).map_err(|e| anyhow::anyhow!(e))?;
+        }
+        AUTHOR_BACKLOG_THRESHOLD_KEY => {
+            parse_author_backlog_threshold(value)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Version of the JSON produced by export_settings_json
+const SETTINGS_EXPORT_VERSION: i64 = 1;
+
+// Compact card shown on the kanban board - only the fields the board renders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardCard {
+    pub id: i64,
+    pub pr_number: i64,
+    pub title: Option<String>,
+    pub author_avatar: Option<String>,
+    pub status: PrStatus,
+    pub score: Option<i32>,
+    pub priority: PrPriority,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumn {
+    pub status: String,
+    pub count: usize,
+    pub cards: Vec<BoardCard>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardData {
+    pub columns: Vec<BoardColumn>,
+    pub total: usize,
+}
+
+// Board columns that are always present, in display order
+const BOARD_COLUMNS: [&str; 4] = ["Waiting", "Reviewing", "Action", "Approved"];
+
+// Shared SELECT for PullRequest rows with joined author/project names and the
+// active profile's overlay (ps). A PR is unread until viewed, or when GitHub (or,
+// lacking that, the newest history entry) reports a change after the last view.
+const PR_SELECT: &str = r#"
+    SELECT
+        pr.id, pr.github_id, pr.pr_number, pr.title, pr.author_id,
+        pr.project_id, pr.last_updated_at, pr.status, pr.waiting_on, pr.branch, pr.score, pr.exclude_from_scoring,
+        pr.repository_owner, pr.repository_name,
+        pr.github_created_at, pr.github_updated_at, ps.last_viewed_at,
+        pr.reactions_total, pr.reactions_plus_one, pr.priority,
+        COALESCE(ps.status_pinned, 0) as status_pinned,
+        pr.commit_type, pr.commit_scope, pr.ticket_key, pr.is_issue,
+        pr.merge_commit_sha, pr.shipped_in_release, pr.added_by,
+        pr.additions, pr.deletions, pr.changed_files,
+        COALESCE(rc.reviewer_count, 0) as reviewer_count,
+        CASE
+            WHEN ps.last_viewed_at IS NULL THEN 1
+            WHEN COALESCE(
+                pr.github_updated_at,
+                (SELECT MAX(rh.performed_at) FROM review_history rh WHERE rh.pr_id = pr.id)
+            ) > ps.last_viewed_at THEN 1
+            ELSE 0
+        END as has_unread_changes,
+        tm.github_username as author_name,
+        tm.avatar_url as author_avatar,
+        tm.display_name as author_display_name,
+        p.name as project_name
+    FROM pull_requests pr
+    LEFT JOIN team_members tm ON pr.author_id = tm.id
+    LEFT JOIN projects p ON pr.project_id = p.id
+    LEFT JOIN pr_profile_state ps ON ps.pr_id = pr.id
+        AND ps.profile_id = (SELECT id FROM local_profiles WHERE is_active = 1)
+    LEFT JOIN (
+        SELECT pr_id, COUNT(DISTINCT team_member_id) as reviewer_count
+        FROM pr_reviewers GROUP BY pr_id
+    ) rc ON rc.pr_id = pr.id
+"#;
+
+// A non-null id with a null joined name means the referenced row was deleted behind our back
+fn pull_request_from_row(row: &SqliteRow) -> PullRequest {
+    let project_id: Option<i64> = row.get("project_id");
+    let project_name: Option<String> = row.get("project_name");
+    let author_name: Option<String> = row.get("author_name");
+    let repository_owner: Option<String> = row.get("repository_owner");
+    let repository_name: Option<String> = row.get("repository_name");
+    let pr_number: i64 = row.get("pr_number");
+    PullRequest {
+        id: row.get("id"),
+        github_id: row.get("github_id"),
+        title: row.get("title"),
+        author_id: row.get("author_id"),
+        project_missing: project_id.is_some() && project_name.is_none(),
+        author_missing: author_name.is_none(),
+        project_id,
+        last_updated_at: row.get("last_updated_at"),
+        author_name,
+        author_avatar: row.get("author_avatar"),
+        author_display_name: row.get("author_display_name"),
+        project_name,
+        status: row.get::<String, _>("status").into(),
+        waiting_on: WaitingOn::from_db(row.get("waiting_on")),
+        branch: row.get("branch"),
+        score: row.get("score"),
+        exclude_from_scoring: row.get("exclude_from_scoring"),
+        github_created_at: row.get("github_created_at"),
+        github_updated_at: row.get("github_updated_at"),
+        last_viewed_at: row.get("last_viewed_at"),
+        has_unread_changes: row.get("has_unread_changes"),
+        reactions_total: row.get("reactions_total"),
+        reactions_plus_one: row.get("reactions_plus_one"),
+        priority: PrPriority::from_i64(row.get("priority")),
+        status_pinned: row.get("status_pinned"),
+        commit_type: row.get("commit_type"),
+        commit_scope: row.get("commit_scope"),
+        ticket_key: row.get("ticket_key"),
+        is_issue: row.get("is_issue"),
+        added_by: row.get("added_by"),
+        merge_commit_sha: row.get("merge_commit_sha"),
+        shipped_in_release: row.get("shipped_in_release"),
+        reviewer_count: row.get("reviewer_count"),
+        additions: row.get("additions"),
+        deletions: row.get("deletions"),
+        changed_files: row.get("changed_files"),
+        display_key: display_key(repository_owner.as_deref(), repository_name.as_deref(), pr_number, false),
+        repository_owner,
+        repository_name,
+        pr_number,
+    }
+}
+
+// Shared by add_pull_request and repository reimports, which insert inside their own transaction
+async fn insert_pull_request(conn: &mut SqliteConnection, new_pr: &NewPullRequest, added_by: Option<&str>, rules: &[regex::Regex], now: i64) -> Result<i64> {
+    let parsed = new_pr.title.as_deref()
+        .map(|title| title_rules::parse_title(rules, title))
+        .unwrap_or_default();
+    let status = &new_pr.status;
+
+    Ok(sqlx::query(
+        r#"
+        INSERT INTO pull_requests (
+            github_id, pr_number, title, author_id, project_id, branch, status,
+            repository_owner, repository_name, github_created_at, github_updated_at,
+            reactions_total, reactions_plus_one, additions, deletions, changed_files, last_updated_at,
+            commit_type, commit_scope, ticket_key, is_issue, added_by, waiting_on
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#
+    )
+    .bind(new_pr.github_id)
+    .bind(new_pr.pr_number)
+    .bind(&new_pr.title)
+    .bind(new_pr.author_id)
+    .bind(new_pr.project_id)
+    .bind(&new_pr.branch)
+    .bind(status.as_str())
+    .bind(&new_pr.repository_owner)
+    .bind(&new_pr.repository_name)
+    .bind(new_pr.github_created_at)
+    .bind(new_pr.github_updated_at)
+    .bind(new_pr.reactions_total)
+    .bind(new_pr.reactions_plus_one)
+    .bind(new_pr.additions)
+    .bind(new_pr.deletions)
+    .bind(new_pr.changed_files)
+    .bind(now)
+    .bind(&parsed.commit_type)
+    .bind(&parsed.commit_scope)
+    .bind(&parsed.ticket_key)
+    .bind(new_pr.is_issue)
+    .bind(added_by)
+    .bind(WaitingOn::for_status(status).unwrap_or_default().as_str())
+    .execute(conn)
+    .await?
+    .last_insert_rowid())
+}
+
+// The project and its roster; false when there was no such project
+async fn delete_project_row(conn: &mut SqliteConnection, id: i64) -> Result<bool> {
+    sqlx::query("DELETE FROM project_members WHERE project_id = ?")
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    let result = sqlx::query("DELETE FROM projects WHERE id = ?")
+        .bind(id)
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// A readable error instead of SQLite's bare "FOREIGN KEY constraint failed"
+async fn ensure_project_exists(conn: &mut SqliteConnection, project_id: i64) -> Result<()> {
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM projects WHERE id = ?")
+        .bind(project_id)
+        .fetch_optional(conn)
+        .await?;
+    match exists {
+        Some(_) => Ok(()),
+        None => Err(anyhow::anyhow!("Project {} not found", project_id)),
+    }
+}
+
+// List results get their display keys disambiguated against each other
+fn pull_requests_from_rows(rows: &[SqliteRow]) -> Vec<PullRequest> {
+    let mut prs: Vec<PullRequest> = rows.iter().map(pull_request_from_row).collect();
+    assign_display_keys(&mut prs);
+    prs
+}
+
+fn team_member_from_row(row: &SqliteRow) -> TeamMember {
+    TeamMember {
+        id: row.get("id"),
+        github_username: row.get("github_username"),
+        avatar_url: row.get("avatar_url"),
+        display_name: row.get("display_name"),
+        created_at: row.get("created_at"),
+        avatar_path: row.get("avatar_path"),
+    }
+}
+
+fn project_from_row(row: &SqliteRow) -> Project {
+    Project {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        created_at: row.get("created_at"),
+        sla_hours: row.get("sla_hours"),
+        target_score: row.get("target_score"),
+    }
+}
+
+fn local_profile_from_row(row: &SqliteRow) -> LocalProfile {
+    LocalProfile {
+        id: row.get("id"),
+        name: row.get("name"),
+        is_active: row.get("is_active"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn tracked_repository_from_row(row: &SqliteRow) -> TrackedRepository {
+    TrackedRepository {
+        id: row.get("id"),
+        owner: row.get("owner"),
+        name: row.get("name"),
+        accessible: row.get("accessible"),
+        last_checked_at: row.get("last_checked_at"),
+        project_id: row.get("project_id"),
+    }
+}
+
+fn retry_entry_from_row(row: &SqliteRow) -> RetryEntry {
+    RetryEntry {
+        id: row.get("id"),
+        pr_id: row.get("pr_id"),
+        operation: row.get("operation"),
+        attempts: row.get("attempts"),
+        next_attempt_at: row.get("next_attempt_at"),
+        last_error: row.get("last_error"),
+        parked: row.get("parked"),
+        created_at: row.get("created_at"),
+    }
+}
+
+// Sort keys accepted by the list commands. The ORDER BY terms use PR_SELECT's
+// output column names so they also work when PR_SELECT is wrapped in a subquery.
+const PR_SORT_WHITELIST: [(&str, &str); 3] = [
+    ("updated", "last_updated_at DESC"),
+    ("reactions", "COALESCE(reactions_total, 0) DESC, COALESCE(reactions_plus_one, 0) DESC, last_updated_at DESC"),
+    ("priority", "priority DESC, last_updated_at DESC"),
+];
+
+fn pr_order_by(sort_by: Option<&str>) -> Result<&'static str> {
+    let key = sort_by.unwrap_or("updated");
+    PR_SORT_WHITELIST.iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, clause)| *clause)
+        .ok_or_else(|| anyhow::anyhow!(
+            "Unknown sort key '{}'. Valid keys: {}",
+            key,
+            PR_SORT_WHITELIST.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+        ))
 }
 
 pub struct Database {
     pub pool: SqlitePool, // Make public for testing
+    pub backup_dir: Option<PathBuf>, // None disables safety backups (in-memory databases)
+    busy_timeout_ms: Arc<AtomicU64>, // read by the pool hooks whenever a connection is handed out
+}
+
+// Pool whose connections pick up the current busy timeout on connect and on every acquire
+fn pool_options(busy_timeout_ms: Arc<AtomicU64>) -> SqlitePoolOptions {
+    let on_acquire = busy_timeout_ms.clone();
+    SqlitePoolOptions::new()
+        .after_connect(move |conn, _meta| {
+            let ms = busy_timeout_ms.load(Ordering::Relaxed);
+            Box::pin(async move {
+                sqlx::query(&format!("PRAGMA busy_timeout = {}", ms)).execute(conn).await?;
+                Ok(())
+            })
+        })
+        .before_acquire(move |conn, _meta| {
+            let ms = on_acquire.load(Ordering::Relaxed);
+            Box::pin(async move {
+                sqlx::query(&format!("PRAGMA busy_timeout = {}", ms)).execute(conn).await?;
+                Ok(true)
+            })
+        })
 }
 
 impl Database {
     pub async fn new() -> Result<Self> {
-        let db_path = get_database_path()?;
+        Self::open(&get_database_path()?).await
+    }
 
+    /// Open (creating if needed) the database file at `db_path`
+    pub async fn open(db_path: &Path) -> Result<Self> {
         // Ensure the directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
@@ -66,21 +883,68 @@ impl Database {
 
         // Create the file if it doesn't exist
         if !db_path.exists() {
-            std::fs::File::create(&db_path)?;
+            std::fs::File::create(db_path)?;
             println!("Created database file: {:?}", db_path);
         }
 
         let database_url = format!("sqlite:{}?mode=rwc", db_path.to_string_lossy());
         println!("Connecting to database: {}", database_url);
 
-        let pool = SqlitePool::connect(&database_url).await?;
+        let busy_timeout_ms = Arc::new(AtomicU64::new(DEFAULT_BUSY_TIMEOUT_MS));
+        // sqlx's default, spelled out: a PR pointing at a deleted project is refused by SQLite too
+        let options = SqliteConnectOptions::from_str(&database_url)?
+            .foreign_keys(true)
+            .busy_timeout(Duration::from_millis(DEFAULT_BUSY_TIMEOUT_MS));
+        let pool = pool_options(busy_timeout_ms.clone()).connect_with(options).await?;
 
-        let db = Database { pool };
+        let backup_dir = db_path.parent().map(|dir| dir.join("backups"));
+        let db = Database { pool, backup_dir, busy_timeout_ms };
         db.initialize_tables().await?;
+        db.load_busy_timeout().await?;
 
         Ok(db)
     }
 
+    /// Open a private in-memory database (used by tests)
+    pub async fn new_in_memory() -> Result<Self> {
+        // A single connection, otherwise every pooled connection gets its own empty database
+        let busy_timeout_ms = Arc::new(AtomicU64::new(DEFAULT_BUSY_TIMEOUT_MS));
+        let options = SqliteConnectOptions::from_str("sqlite::memory:")?.foreign_keys(true);
+        let pool = pool_options(busy_timeout_ms.clone())
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+
+        let db = Database { pool, backup_dir: None, busy_timeout_ms };
+        db.initialize_tables().await?;
+        db.load_busy_timeout().await?;
+
+        Ok(db)
+    }
+
+    // Pick up a busy timeout saved by set_busy_timeout in an earlier session
+    async fn load_busy_timeout(&self) -> Result<()> {
+        if let Some(value) = self.get_setting(BUSY_TIMEOUT_KEY).await? {
+            match parse_busy_timeout(&value) {
+                Ok(ms) => self.busy_timeout_ms.store(ms, Ordering::Relaxed),
+                Err(e) => println!("⚠️ Ignoring stored busy timeout '{}': {}", value, e),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_busy_timeout(&self) -> u64 {
+        self.busy_timeout_ms.load(Ordering::Relaxed)
+    }
+
+    /// Persist a new busy timeout; pooled connections apply it the next time they are used
+    pub async fn set_busy_timeout(&self, ms: u64) -> Result<()> {
+        self.set_setting(BUSY_TIMEOUT_KEY, &ms.to_string()).await?;
+        self.busy_timeout_ms.store(ms, Ordering::Relaxed);
+        println!("⏱️ SQLite busy timeout set to {} ms", ms);
+        Ok(())
+    }
+
     async fn migrate_database(&self) -> Result<()> {
         // Check if we need to add avatar_url and display_name columns to team_members
         let columns_exist = sqlx::query(
@@ -109,6 +973,32 @@ impl Database {
             }
         }
 
+        // Check if we need to add new columns to projects table
+        let project_columns_exist = sqlx::query(
+            "SELECT sql FROM sqlite_master WHERE type='table' AND name='projects'"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(row) = project_columns_exist {
+            let table_sql: String = row.get("sql");
+
+            // Add SLA columns if they don't exist
+            if !table_sql.contains("sla_hours") {
+                sqlx::query("ALTER TABLE projects ADD COLUMN sla_hours INTEGER")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added sla_hours column to projects table");
+            }
+
+            if !table_sql.contains("target_score") {
+                sqlx::query("ALTER TABLE projects ADD COLUMN target_score INTEGER")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added target_score column to projects table");
+            }
+        }
+
         // Check if we need to add new columns to pull_requests table
         let pr_columns_exist = sqlx::query(
             "SELECT sql FROM sqlite_master WHERE type='table' AND name='pull_requests'"
@@ -158,15 +1048,78 @@ impl Database {
                     .await?;
                 println!("✅ Added repository_name column to pull_requests table");
             }
-        }
 
-        Ok(())
-    }
+            // Add github_created_at column if it doesn't exist
+            if !table_sql.contains("github_created_at") {
+                sqlx::query("ALTER TABLE pull_requests ADD COLUMN github_created_at INTEGER")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added github_created_at column to pull_requests table");
+            }
 
-    async fn initialize_tables(&self) -> Result<()> {
-        // Run migrations first
-        self.migrate_database().await?;
-        // Team members table
+            // Add github_updated_at column if it doesn't exist
+            if !table_sql.contains("github_updated_at") {
+                sqlx::query("ALTER TABLE pull_requests ADD COLUMN github_updated_at INTEGER")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added github_updated_at column to pull_requests table");
+            }
+
+            // Add last_viewed_at column if it doesn't exist (NULL means never viewed)
+            if !table_sql.contains("last_viewed_at") {
+                sqlx::query("ALTER TABLE pull_requests ADD COLUMN last_viewed_at INTEGER")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added last_viewed_at column to pull_requests table");
+            }
+
+            // Add reaction count columns if they don't exist
+            if !table_sql.contains("reactions_total") {
+                sqlx::query("ALTER TABLE pull_requests ADD COLUMN reactions_total INTEGER")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added reactions_total column to pull_requests table");
+            }
+
+            if !table_sql.contains("reactions_plus_one") {
+                sqlx::query("ALTER TABLE pull_requests ADD COLUMN reactions_plus_one INTEGER")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added reactions_plus_one column to pull_requests table");
+            }
+
+            // Add priority column if it doesn't exist (2 = Medium)
+            if !table_sql.contains("priority") {
+                sqlx::query("ALTER TABLE pull_requests ADD COLUMN priority INTEGER NOT NULL DEFAULT 2")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added priority column to pull_requests table");
+            }
+
+            // Add status_pinned column if it doesn't exist
+            if !table_sql.contains("status_pinned") {
+                sqlx::query("ALTER TABLE pull_requests ADD COLUMN status_pinned INTEGER NOT NULL DEFAULT 0")
+                    .execute(&self.pool)
+                    .await?;
+                println!("✅ Added status_pinned column to pull_requests table");
+            }
+
+            // Add title-derived columns if they don't exist
+            for column in ["commit_type", "commit_scope", "ticket_key"] {
+                if !table_sql.contains(column) {
+                    sqlx::query(&format!("ALTER TABLE pull_requests ADD COLUMN {} TEXT", column))
+                        .execute(&self.pool)
+                        .await?;
+                    println!("✅ Added {} column to pull_requests table", column);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn initialize_tables(&self) -> Result<()> {
+        // Team members table
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS team_members (
@@ -232,23 +1185,243 @@ impl Database {
         .execute(&self.pool)
         .await?;
 
+        // Local reviewer comments (a thread of timestamped notes per PR)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS pr_comments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                pr_id INTEGER NOT NULL,
+                author_member_id INTEGER NOT NULL,
+                body TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY (pr_id) REFERENCES pull_requests(id),
+                FOREIGN KEY (author_member_id) REFERENCES team_members(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Failed refresh/enrichment operations awaiting retry (one row per PR and operation)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS retry_queue (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                pr_id INTEGER NOT NULL,
+                operation TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at INTEGER NOT NULL,
+                last_error TEXT,
+                parked INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                UNIQUE (pr_id, operation),
+                FOREIGN KEY (pr_id) REFERENCES pull_requests(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Repositories seen on tracked PRs and whether the stored token can read them
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS repositories (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                owner TEXT NOT NULL,
+                name TEXT NOT NULL,
+                accessible INTEGER,
+                last_checked_at INTEGER,
+                UNIQUE (owner, name)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Single-use nonces guarding destructive operations
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS confirmation_nonces (
+                nonce TEXT PRIMARY KEY,
+                action TEXT NOT NULL,
+                expires_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // "Theirs" values from a merge import, waiting for the user to resolve them
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS import_conflicts (
+                session_id TEXT NOT NULL,
+                pr_id INTEGER NOT NULL,
+                field TEXT NOT NULL,
+                theirs TEXT,
+                expires_at INTEGER NOT NULL,
+                PRIMARY KEY (session_id, pr_id, field),
+                FOREIGN KEY (pr_id) REFERENCES pull_requests(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Local profiles; exactly one is active
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS local_profiles (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                is_active INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Per-profile overlay of user-owned PR state
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS pr_profile_state (
+                pr_id INTEGER NOT NULL,
+                profile_id INTEGER NOT NULL,
+                last_viewed_at INTEGER,
+                status_pinned INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (pr_id, profile_id),
+                FOREIGN KEY (pr_id) REFERENCES pull_requests(id),
+                FOREIGN KEY (profile_id) REFERENCES local_profiles(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Key/value application settings
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // One row per migration attempt, including failures
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS migration_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                version INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                applied_at INTEGER NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                rows_affected INTEGER,
+                status TEXT NOT NULL,
+                error TEXT
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Run migrations once the tables exist so fresh databases get the added columns too
+        self.run_migrations().await?;
+        self.record_app_version(env!("CARGO_PKG_VERSION")).await?;
+
         Ok(())
     }
 
-    // Project operations
-    pub async fn get_projects(&self) -> Result<Vec<Project>> {
-        let rows = sqlx::query("SELECT id, name, description, created_at FROM projects ORDER BY name")
+    // First run with profiles: create "default" and move the single-user viewed/pinned state into it.
+    // Returns how many PRs had state migrated.
+    async fn ensure_default_profile(&self) -> Result<u64> {
+        let profiles: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM local_profiles")
+            .fetch_one(&self.pool)
+            .await?;
+        if profiles > 0 {
+            return Ok(0);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let profile_id: i64 = sqlx::query_scalar(
+            "INSERT INTO local_profiles (name, is_active, created_at) VALUES (?, 1, ?) RETURNING id"
+        )
+        .bind(DEFAULT_PROFILE_NAME)
+        .bind(chrono::Utc::now().timestamp())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let migrated = sqlx::query(
+            r#"
+            INSERT INTO pr_profile_state (pr_id, profile_id, last_viewed_at, status_pinned)
+            SELECT id, ?, last_viewed_at, status_pinned FROM pull_requests
+            WHERE last_viewed_at IS NOT NULL OR status_pinned != 0
+            "#
+        )
+        .bind(profile_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        tx.commit().await?;
+
+        println!("✅ Created default local profile (migrated state for {} PRs)", migrated);
+        Ok(migrated)
+    }
+
+    // Local profile operations
+    pub async fn get_local_profiles(&self) -> Result<Vec<LocalProfile>> {
+        let rows = sqlx::query("SELECT id, name, is_active, created_at FROM local_profiles ORDER BY name")
             .fetch_all(&self.pool)
             .await?;
 
-        let projects = rows.into_iter().map(|row| Project {
-            id: row.get("id"),
-            name: row.get("name"),
-            description: row.get("description"),
-            created_at: row.get("created_at"),
-        }).collect();
+        Ok(rows.iter().map(local_profile_from_row).collect())
+    }
 
-        Ok(projects)
+    pub async fn get_active_profile(&self) -> Result<LocalProfile> {
+        let row = sqlx::query("SELECT id, name, is_active, created_at FROM local_profiles WHERE is_active = 1")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(local_profile_from_row(&row))
+    }
+
+    /// Make `name` the active profile, creating it on first use
+    pub async fn switch_local_profile(&self, name: &str) -> Result<LocalProfile> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Profile name cannot be empty"));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("INSERT INTO local_profiles (name, created_at) VALUES (?, ?) ON CONFLICT(name) DO NOTHING")
+            .bind(name)
+            .bind(chrono::Utc::now().timestamp())
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE local_profiles SET is_active = (name = ?)")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        println!("👤 Switched to local profile '{}'", name);
+        self.get_active_profile().await
+    }
+
+    // Project operations
+    pub async fn get_projects(&self) -> Result<Vec<Project>> {
+        let rows = sqlx::query(
+            "SELECT id, name, description, created_at, sla_hours, target_score FROM projects ORDER BY name"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(project_from_row).collect())
     }
 
     pub async fn add_project(&self, name: String, description: Option<String>) -> Result<Project> {
@@ -270,33 +1443,46 @@ impl Database {
             name,
             description,
             created_at: current_time,
+            sla_hours: None,
+            target_score: None,
         })
     }
 
-    pub async fn update_project(&self, id: i64, name: String, description: Option<String>) -> Result<Project> {
+    pub async fn update_project(
+        &self,
+        id: i64,
+        name: String,
+        description: Option<String>,
+        sla_hours: Option<i64>,
+        target_score: Option<i32>,
+    ) -> Result<Project> {
+        if sla_hours.is_some_and(|h| h <= 0) {
+            return Err(anyhow::anyhow!("SLA hours must be positive"));
+        }
+        if target_score.is_some_and(|t| !(0..=100).contains(&t)) {
+            return Err(anyhow::anyhow!("Target score must be between 0 and 100"));
+        }
+
         sqlx::query(
-            "UPDATE projects SET name = ?, description = ? WHERE id = ?"
+            "UPDATE projects SET name = ?, description = ?, sla_hours = ?, target_score = ? WHERE id = ?"
         )
         .bind(&name)
         .bind(&description)
+        .bind(sla_hours)
+        .bind(target_score)
         .bind(id)
         .execute(&self.pool)
         .await?;
 
         // Return updated project
         let row = sqlx::query(
-            "SELECT id, name, description, created_at FROM projects WHERE id = ?"
+            "SELECT id, name, description, created_at, sla_hours, target_score FROM projects WHERE id = ?"
         )
         .bind(id)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(Project {
-            id: row.get("id"),
-            name: row.get("name"),
-            description: row.get("description"),
-            created_at: row.get("created_at"),
-        })
+        Ok(project_from_row(&row))
     }
 
     pub async fn delete_project(&self, id: i64) -> Result<()> {
@@ -316,424 +1502,3303 @@ impl Database {
             ));
         }
 
-        // Safe to delete - no PRs are assigned to this project
-        let result = sqlx::query("DELETE FROM projects WHERE id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
+        // Safe to delete - no PRs are assigned to this project; its roster goes with it
+        let mut tx = self.pool.begin().await?;
+        let deleted = delete_project_row(&mut tx, id).await?;
+        tx.commit().await?;
 
-        if result.rows_affected() == 0 {
+        if !deleted {
             return Err(anyhow::anyhow!("Project not found"));
         }
 
         Ok(())
     }
 
+    /// Move the project's PRs (tracked issues included) to `target`, then delete it, all in one
+    /// transaction so a failure leaves every PR where it was
+    pub async fn delete_project_with_reassign(&self, id: i64, target: ProjectDeleteTarget) -> Result<ProjectDeleteSummary> {
+        let mut tx = self.pool.begin().await?;
+        ensure_project_exists(&mut tx, id).await?;
+        let target_project_id = match target {
+            ProjectDeleteTarget::Project(target_id) if target_id == id => {
+                return Err(anyhow::anyhow!("Cannot reassign pull requests to project {}: it is the project being deleted", id));
+            }
+            ProjectDeleteTarget::Project(target_id) => {
+                ensure_project_exists(&mut tx, target_id).await?;
+                Some(target_id)
+            }
+            ProjectDeleteTarget::Unassigned => None,
+        };
+
+        let moved_prs = sqlx::query("UPDATE pull_requests SET project_id = ? WHERE project_id = ?")
+            .bind(target_project_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        delete_project_row(&mut tx, id).await?;
+        tx.commit().await?;
+
+        println!("🗂️ Deleted project {}, moved {} PRs to {}", id, moved_prs,
+            target_project_id.map_or("no project".to_string(), |target_id| format!("project {}", target_id)));
+        Ok(ProjectDeleteSummary { deleted_project_id: id, target_project_id, moved_prs })
+    }
+
     pub async fn get_project_by_id(&self, id: i64) -> Result<Option<Project>> {
         let row = sqlx::query(
-            "SELECT id, name, description, created_at FROM projects WHERE id = ?"
+            "SELECT id, name, description, created_at, sla_hours, target_score FROM projects WHERE id = ?"
         )
         .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(Project {
-                id: row.get("id"),
-                name: row.get("name"),
-                description: row.get("description"),
-                created_at: row.get("created_at"),
-            }))
-        } else {
-            Ok(None)
-        }
+        Ok(row.as_ref().map(project_from_row))
     }
 
-    // Team member operations
-    pub async fn get_or_create_team_member(&self, github_username: String) -> Result<TeamMember> {
-        // Try to get existing member
-        if let Ok(row) = sqlx::query(
-            "SELECT id, github_username, avatar_url, display_name, created_at FROM team_members WHERE github_username = ?"
-        )
-        .bind(&github_username)
-        .fetch_one(&self.pool)
-        .await {
-            return Ok(TeamMember {
-                id: row.get("id"),
-                github_username: row.get("github_username"),
-                avatar_url: row.get("avatar_url"),
-                display_name: row.get("display_name"),
-                created_at: row.get("created_at"),
-            });
-        }
-
-        // Create new member
-        let current_time = chrono::Utc::now().timestamp();
-        let result = sqlx::query(
-            "INSERT INTO team_members (github_username, created_at) VALUES (?, ?) RETURNING id"
+    /// Project most PRs from this repository are filed under (ties go to the most recently used)
+    pub async fn suggest_project_for_repo(&self, owner: &str, repo: &str) -> Result<Option<Project>> {
+        let row = sqlx::query(
+            r#"
+            SELECT p.id, p.name, p.description, p.created_at, p.sla_hours, p.target_score
+            FROM pull_requests pr
+            JOIN projects p ON pr.project_id = p.id
+            WHERE pr.repository_owner = ? COLLATE NOCASE AND pr.repository_name = ? COLLATE NOCASE
+            GROUP BY p.id
+            ORDER BY COUNT(*) DESC, MAX(pr.last_updated_at) DESC
+            LIMIT 1
+            "#
         )
-        .bind(&github_username)
-        .bind(current_time)
-        .fetch_one(&self.pool)
+        .bind(owner)
+        .bind(repo)
+        .fetch_optional(&self.pool)
         .await?;
 
-        let id: i64 = result.get("id");
-
-        Ok(TeamMember {
-            id,
-            github_username,
-            avatar_url: None,
-            display_name: None,
-            created_at: current_time,
-        })
+        Ok(row.as_ref().map(project_from_row))
     }
 
-    // Update team member with GitHub data (avatar, display name)
-
-    // Pull request operations
-    pub async fn get_pull_requests(&self) -> Result<Vec<PullRequest>> {
+    /// Report-only: PRs whose project differs from their repository's most common project
+    /// (same ranking as suggest_project_for_repo). Ties are not reported, and unassigned PRs are skipped.
+    pub async fn find_misfiled_prs(&self) -> Result<Vec<MisfiledPr>> {
         let rows = sqlx::query(
             r#"
-            SELECT
-                pr.id, pr.github_id, pr.pr_number, pr.title, pr.author_id,
-                pr.project_id, pr.last_updated_at, pr.status, pr.branch, pr.score,
-                pr.repository_owner, pr.repository_name,
-                tm.github_username as author_name,
-                tm.avatar_url as author_avatar,
-                tm.display_name as author_display_name,
-                p.name as project_name
+            WITH counts AS (
+                SELECT LOWER(pr.repository_owner) as repo_owner, LOWER(pr.repository_name) as repo_name,
+                       pr.project_id, COUNT(*) as pr_count, MAX(pr.last_updated_at) as latest
+                FROM pull_requests pr
+                JOIN projects p ON pr.project_id = p.id
+                WHERE pr.repository_owner IS NOT NULL AND pr.repository_name IS NOT NULL
+                GROUP BY repo_owner, repo_name, pr.project_id
+            ),
+            ranked AS (
+                SELECT *, ROW_NUMBER() OVER (
+                    PARTITION BY repo_owner, repo_name ORDER BY pr_count DESC, latest DESC
+                ) as rank
+                FROM counts
+            )
+            SELECT pr.id, pr.pr_number, pr.title, pr.repository_owner, pr.repository_name,
+                   cur.id as current_project_id, cur.name as current_project_name, mine.pr_count as current_project_count,
+                   sug.id as suggested_project_id, sug.name as suggested_project_name, top.pr_count as suggested_project_count
             FROM pull_requests pr
-            LEFT JOIN team_members tm ON pr.author_id = tm.id
-            LEFT JOIN projects p ON pr.project_id = p.id
-            ORDER BY pr.last_updated_at DESC
+            JOIN counts mine ON mine.repo_owner = LOWER(pr.repository_owner)
+                AND mine.repo_name = LOWER(pr.repository_name)
+                AND mine.project_id = pr.project_id
+            JOIN ranked top ON top.repo_owner = mine.repo_owner AND top.repo_name = mine.repo_name AND top.rank = 1
+            JOIN projects cur ON cur.id = pr.project_id
+            JOIN projects sug ON sug.id = top.project_id
+            WHERE top.project_id != pr.project_id AND top.pr_count > mine.pr_count
+            ORDER BY LOWER(pr.repository_owner), LOWER(pr.repository_name), pr.id
             "#
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let prs = rows.into_iter().map(|row| PullRequest {
-            id: row.get("id"),
-            github_id: row.get("github_id"),
+        Ok(rows.iter().map(|row| MisfiledPr {
+            pr_id: row.get("id"),
             pr_number: row.get("pr_number"),
             title: row.get("title"),
-            author_id: row.get("author_id"),
-            project_id: row.get("project_id"),
-            last_updated_at: row.get("last_updated_at"),
-            author_name: row.get("author_name"),
-            author_avatar: row.get("author_avatar"),
-            author_display_name: row.get("author_display_name"),
-            project_name: row.get("project_name"),
-            status: row.get("status"),
-            branch: row.get("branch"),
-            score: row.get("score"),
             repository_owner: row.get("repository_owner"),
             repository_name: row.get("repository_name"),
-        }).collect();
+            current_project_id: row.get("current_project_id"),
+            current_project_name: row.get("current_project_name"),
+            current_project_count: row.get("current_project_count"),
+            suggested_project_id: row.get("suggested_project_id"),
+            suggested_project_name: row.get("suggested_project_name"),
+            suggested_project_count: row.get("suggested_project_count"),
+        }).collect())
+    }
 
-        Ok(prs)
+    /// Share of merged PRs with a score, optionally limited to one repository and/or project.
+    /// A PR counts as merged while in Merged, or once history records it merged (e.g. later archived).
+    /// PRs flagged exclude_from_scoring are left out of both counts.
+    pub async fn get_review_coverage(&self, repository: Option<(&str, &str)>, project_id: Option<i64>) -> Result<ReviewCoverage> {
+        let (owner, repo) = repository.unzip();
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT COUNT(*) as merged_count, COUNT(pr.score) as scored_count
+            FROM pull_requests pr
+            WHERE {} AND pr.exclude_from_scoring = 0
+              AND (? IS NULL OR pr.repository_owner = ? COLLATE NOCASE)
+              AND (? IS NULL OR pr.repository_name = ? COLLATE NOCASE)
+              AND (? IS NULL OR pr.project_id = ?)
+            "#,
+            PR_WAS_MERGED
+        ))
+        .bind(owner)
+        .bind(owner)
+        .bind(repo)
+        .bind(repo)
+        .bind(project_id)
+        .bind(project_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let merged_count: i64 = row.get("merged_count");
+        let scored_count: i64 = row.get("scored_count");
+        Ok(ReviewCoverage {
+            merged_count,
+            scored_count,
+            coverage_ratio: (merged_count > 0).then(|| scored_count as f64 / merged_count as f64),
+        })
     }
 
-    pub async fn update_pr_status(&self, pr_id: i64, status: String) -> Result<()> {
-        sqlx::query("UPDATE pull_requests SET status = ? WHERE id = ?")
-            .bind(status)
-            .bind(pr_id)
-            .execute(&self.pool)
-            .await?;
+    /// Zone used for human-facing timestamps: the display_timezone setting, else the system zone
+    pub async fn get_display_timezone(&self) -> Result<chrono_tz::Tz> {
+        match self.get_setting(DISPLAY_TIMEZONE_KEY).await? {
+            Some(name) => parse_timezone(&name).map_err(|e| anyhow::anyhow!(e)),
+            None => Ok(system_timezone()),
+        }
+    }
 
-        Ok(())
+    /// Rate-limit state saved by the previous session; unreadable JSON is treated as none
+    pub async fn get_rate_limit_state(&self) -> Result<Option<RateLimitState>> {
+        Ok(self.get_setting(RATE_LIMIT_STATE_KEY).await?
+            .and_then(|json| serde_json::from_str(&json).ok()))
     }
 
-    pub async fn update_pr_score(&self, pr_id: i64, score: i32) -> Result<()> {
-        sqlx::query("UPDATE pull_requests SET score = ? WHERE id = ?")
-            .bind(score)
-            .bind(pr_id)
-            .execute(&self.pool)
-            .await?;
+    pub async fn save_rate_limit_state(&self, state: &RateLimitState) -> Result<()> {
+        self.write_setting(RATE_LIMIT_STATE_KEY, &serde_json::to_string(state)?).await
+    }
 
-        Ok(())
+    /// Per-category rate-limit budgets (rate_limit_budgets, default 50/30/20)
+    pub async fn get_rate_budgets(&self) -> Result<RateBudgets> {
+        match self.get_setting(RATE_LIMIT_BUDGETS_KEY).await? {
+            Some(value) => RateBudgets::parse(&value).map_err(|e| anyhow::anyhow!(e)),
+            None => Ok(RateBudgets::default()),
+        }
     }
 
-    pub async fn update_pr_project(&self, pr_id: i64, project_id: i64) -> Result<()> {
-        sqlx::query("UPDATE pull_requests SET project_id = ? WHERE id = ?")
-            .bind(project_id)
-            .bind(pr_id)
+    /// Bounds for the in-memory caches (cache_limits); an unreadable value falls back to the defaults
+    pub async fn get_cache_limits(&self) -> Result<CacheLimits> {
+        Ok(self.get_setting(CACHE_LIMITS_KEY).await?
+            .and_then(|value| CacheLimits::parse(&value).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn clear_rate_limit_state(&self) -> Result<()> {
+        sqlx::query("DELETE FROM settings WHERE key = ?")
+            .bind(RATE_LIMIT_STATE_KEY)
             .execute(&self.pool)
             .await?;
-
         Ok(())
     }
 
-    // Add sample data for testing
-    pub async fn add_sample_data(&self) -> Result<()> {
-        // Add sample projects if none exist
-        let project_count: i64 = sqlx::query("SELECT COUNT(*) as count FROM projects")
-            .fetch_one(&self.pool)
-            .await?
-            .get("count");
-
-        if project_count == 0 {
-            let projects = vec![
-                ("Frontend Core", Some("Main React application")),
-                ("Backend API", Some("REST API and services")),
-                ("Mobile App", Some("iOS and Android applications")),
-                ("DevOps Tools", Some("CI/CD and deployment tools")),
-            ];
+    /// Expiry of the stored token as last reported by GitHub
+    pub async fn get_saved_token_expiry(&self) -> Result<Option<i64>> {
+        Ok(self.get_setting(TOKEN_EXPIRY_KEY).await?.and_then(|value| value.parse().ok()))
+    }
 
-            for (name, desc) in projects {
-                self.add_project(name.to_string(), desc.map(|s| s.to_string())).await?;
+    /// Save the expiry, or forget it when the token doesn't expire
+    pub async fn save_token_expiry(&self, expires_at: Option<i64>) -> Result<()> {
+        match expires_at {
+            Some(at) => self.write_setting(TOKEN_EXPIRY_KEY, &at.to_string()).await,
+            None => {
+                sqlx::query("DELETE FROM settings WHERE key = ?")
+                    .bind(TOKEN_EXPIRY_KEY)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
             }
         }
+    }
 
-        // Add sample team members and PRs
-        let member_count: i64 = sqlx::query("SELECT COUNT(*) as count FROM team_members")
-            .fetch_one(&self.pool)
-            .await?
-            .get("count");
+    /// The project named by the default_project_id setting, if set and still present
+    pub async fn get_default_project(&self) -> Result<Option<Project>> {
+        let Some(value) = self.get_setting(DEFAULT_PROJECT_KEY).await? else {
+            return Ok(None);
+        };
+        match value.trim().parse::<i64>() {
+            Ok(id) => self.get_project_by_id(id).await,
+            Err(_) => Ok(None),
+        }
+    }
 
-        if member_count == 0 {
-            let members = vec!["Alex Chen", "Sarah Liao", "Michael Wu"];
+    /// Start time (GitHub creation, else when tracked) and first review of each project PR started since `since`
+    pub async fn get_pr_review_timings(&self, project_id: i64, since: i64) -> Result<Vec<PrReviewTiming>> {
+        let review_actions = sql_string_list(&REVIEW_ACTIONS);
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT pr.id, pr.pr_number, pr.title, pr.repository_owner, pr.repository_name, pr.waiting_on,
+                   COALESCE(pr.github_created_at, pr.last_updated_at) as started_at,
+                   (SELECT MIN(rh.performed_at) FROM review_history rh
+                    WHERE rh.pr_id = pr.id AND rh.action IN ({})) as first_reviewed_at
+            FROM pull_requests pr
+            WHERE pr.project_id = ? AND COALESCE(pr.github_created_at, pr.last_updated_at) >= ?
+            ORDER BY started_at
+            "#,
+            review_actions
+        ))
+        .bind(project_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
 
-            for member in members {
-                let team_member = self.get_or_create_team_member(member.to_string()).await?;
+        let keys = display_keys(&rows.iter()
+            .map(|row| (row.get("repository_owner"), row.get("repository_name"), row.get("pr_number")))
+            .collect::<Vec<_>>());
+        Ok(rows.iter().zip(keys).map(|(row, display_key)| PrReviewTiming {
+            pr_id: row.get("id"),
+            pr_number: row.get("pr_number"),
+            display_key,
+            title: row.get("title"),
+            started_at: row.get("started_at"),
+            first_reviewed_at: row.get("first_reviewed_at"),
+            waiting_on: WaitingOn::from_db(row.get("waiting_on")),
+        }).collect())
+    }
 
-                // Add sample PRs for each member
-                let current_time = chrono::Utc::now().timestamp();
-                sqlx::query(
-                    r#"
-                    INSERT INTO pull_requests
-                    (github_id, pr_number, title, author_id, project_id, last_updated_at, status, branch, score)
-                    VALUES (?, ?, ?, ?, 1, ?, ?, ?, ?)
-                    "#
-                )
-                .bind(1000 + team_member.id)
-                .bind(1000 + team_member.id)
-                .bind(format!("Sample PR from {}", member))
-                .bind(team_member.id)
-                .bind(current_time)
-                .bind("Reviewing")
-                .bind(format!("feature/sample-{}", team_member.id))
-                .bind(if team_member.id % 2 == 0 { Some(9) } else { None::<i32> })
-                .execute(&self.pool)
-                .await?;
-            }
+    /// Volume, merge rate, score and review speed for one author's PRs started in the window
+    pub async fn get_author_scorecard(&self, github_username: &str, from_ts: i64, to_ts: i64) -> Result<AuthorScorecard> {
+        if to_ts <= from_ts {
+            return Err(anyhow::anyhow!("to_ts must be after from_ts"));
         }
+        let member = self.get_team_member_by_username(github_username).await?
+            .ok_or_else(|| anyhow::anyhow!("Team member '{}' not found", github_username))?;
 
-        Ok(())
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT COUNT(*) as authored_count,
+                   COALESCE(SUM(t.merged), 0) as merged_count,
+                   AVG(t.score) as avg_score,
+                   COUNT(t.score) as scored_count,
+                   AVG(t.first_reviewed_at - t.started_at) as avg_time_to_first_review
+            FROM (
+                SELECT pr.score,
+                       CASE WHEN {} THEN 1 ELSE 0 END as merged,
+                       COALESCE(pr.github_created_at, pr.last_updated_at) as started_at,
+                       (SELECT MIN(rh.performed_at) FROM review_history rh
+                        WHERE rh.pr_id = pr.id AND rh.action IN ({})) as first_reviewed_at
+                FROM pull_requests pr
+                WHERE pr.author_id = ? AND pr.is_issue = 0
+                  AND COALESCE(pr.github_created_at, pr.last_updated_at) >= ?
+                  AND COALESCE(pr.github_created_at, pr.last_updated_at) < ?
+            ) t
+            "#,
+            PR_WAS_MERGED,
+            sql_string_list(&REVIEW_ACTIONS)
+        ))
+        .bind(member.id)
+        .bind(from_ts)
+        .bind(to_ts)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let open_count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pull_requests WHERE author_id = ? AND is_issue = 0 AND status NOT IN ({})",
+            sql_string_list(&CLOSED_STATUSES)
+        ))
+        .bind(member.id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let review_comment_count = self.get_review_comment_count(member.id, from_ts, to_ts).await?;
+
+        let authored_count: i64 = row.get("authored_count");
+        let merged_count: i64 = row.get("merged_count");
+        Ok(AuthorScorecard {
+            github_username: member.github_username,
+            from_ts,
+            to_ts,
+            authored_count,
+            merged_count,
+            merge_rate: (authored_count > 0).then(|| merged_count as f64 / authored_count as f64),
+            avg_score: row.get("avg_score"),
+            scored_count: row.get("scored_count"),
+            avg_time_to_first_review_seconds: row.get("avg_time_to_first_review"),
+            open_count,
+            review_comment_count,
+        })
     }
 
-    // Team member management methods
-    pub async fn get_team_member_by_username(&self, username: &str) -> Result<Option<TeamMember>> {
-        let row = sqlx::query(
-            "SELECT id, github_username, avatar_url, display_name, created_at
-             FROM team_members WHERE github_username = ?"
-        )
-        .bind(username)
-        .fetch_optional(&self.pool)
+    /// Number of open PRs at the end of each "day" or "week" bucket between from_ts and to_ts.
+    /// A PR opens at github_created_at (else when it was tracked) and closes at its first
+    /// merged/archived history entry; PRs that are still open count through the end of the range.
+    pub async fn get_open_pr_burndown(&self, from_ts: i64, to_ts: i64, bucket: &str) -> Result<Vec<BurndownPoint>> {
+        let step = match bucket {
+            "day" => 86_400,
+            "week" => 7 * 86_400,
+            other => return Err(anyhow::anyhow!("Unknown bucket '{}'. Valid buckets: day, week", other)),
+        };
+        if to_ts <= from_ts {
+            return Err(anyhow::anyhow!("to_ts must be after from_ts"));
+        }
+        if (to_ts - from_ts) / step >= MAX_BURNDOWN_BUCKETS {
+            return Err(anyhow::anyhow!("Range too large: at most {} buckets", MAX_BURNDOWN_BUCKETS));
+        }
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT COALESCE(pr.github_created_at, pr.last_updated_at) as opened_at,
+                   CASE WHEN pr.status IN ({}) THEN COALESCE(
+                       (SELECT MIN(rh.performed_at) FROM review_history rh
+                        WHERE rh.pr_id = pr.id AND rh.action IN ({})),
+                       pr.github_updated_at,
+                       pr.last_updated_at
+                   ) END as closed_at
+            FROM pull_requests pr
+            WHERE COALESCE(pr.github_created_at, pr.last_updated_at) <= ?
+            "#,
+            sql_string_list(&CLOSED_STATUSES),
+            sql_string_list(&CLOSING_ACTIONS)
+        ))
+        .bind(to_ts)
+        .fetch_all(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(TeamMember {
-                id: row.get("id"),
-                github_username: row.get("github_username"),
-                avatar_url: row.get("avatar_url"),
-                display_name: row.get("display_name"),
-                created_at: row.get("created_at"),
-            }))
-        } else {
-            Ok(None)
+        let spans: Vec<(i64, Option<i64>)> = rows.iter()
+            .map(|row| (row.get("opened_at"), row.get("closed_at")))
+            .collect();
+
+        let mut points = Vec::new();
+        let mut bucket_start = from_ts;
+        while bucket_start < to_ts {
+            let bucket_end = (bucket_start + step).min(to_ts);
+            let open_count = spans.iter()
+                .filter(|(opened_at, closed_at)| *opened_at <= bucket_end && closed_at.is_none_or(|c| c > bucket_end))
+                .count() as i64;
+            points.push(BurndownPoint { bucket_start, bucket_end, open_count });
+            bucket_start = bucket_end;
         }
+
+        Ok(points)
     }
 
-    pub async fn add_team_member(
-        &self,
-        github_username: &str,
-        avatar_url: Option<&str>,
-        display_name: Option<&str>
-    ) -> Result<TeamMember> {
-        let now = chrono::Utc::now().timestamp();
+    /// Merged PRs per week for the `weeks` weeks ending with the current one, oldest first.
+    /// A PR counts in the week of its first merge in review_history, else its last GitHub update.
+    pub async fn get_velocity(&self, weeks: i64, project_id: Option<i64>, now: i64, tz: chrono_tz::Tz) -> Result<Vec<VelocityPoint>> {
+        if !(1..=MAX_VELOCITY_WEEKS).contains(&weeks) {
+            return Err(anyhow::anyhow!("weeks must be between 1 and {}", MAX_VELOCITY_WEEKS));
+        }
 
-        let id = sqlx::query(
+        let merged_at: Vec<i64> = sqlx::query_scalar(&format!(
             r#"
-            INSERT INTO team_members (github_username, avatar_url, display_name, created_at)
-            VALUES (?, ?, ?, ?)
+            SELECT COALESCE(
+                (SELECT MIN(rh.performed_at) FROM review_history rh WHERE rh.pr_id = pr.id AND rh.action = 'merged'),
+                pr.github_updated_at,
+                pr.last_updated_at
+            )
+            FROM pull_requests pr
+            WHERE {} AND pr.is_issue = 0 AND (? IS NULL OR pr.project_id = ?)
+            "#,
+            PR_WAS_MERGED
+        ))
+        .bind(project_id)
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Week boundaries are computed per date so DST changes don't shift them
+        let this_week = display_time::week_start_date(display_time::local_date(now, tz));
+        let boundaries: Vec<i64> = (0..=weeks)
+            .rev()
+            .map(|i| this_week + chrono::Days::new(7) - chrono::Days::new(7 * i as u64))
+            .map(|monday| display_time::local_midnight(monday, tz))
+            .collect();
+
+        Ok(boundaries.windows(2)
+            .map(|week| VelocityPoint {
+                week_start: week[0],
+                merged_count: merged_at.iter().filter(|at| (week[0]..week[1]).contains(*at)).count() as i64,
+            })
+            .collect())
+    }
+
+    /// Projects whose most recent PR update is older than `days` (or that have no PRs), stalest first
+    pub async fn get_inactive_projects(&self, days: i64) -> Result<Vec<InactiveProject>> {
+        if days < 0 {
+            return Err(anyhow::anyhow!("days must not be negative"));
+        }
+        let cutoff = chrono::Utc::now().timestamp() - days * 86_400;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT p.id, p.name, p.description, p.created_at,
+                   COUNT(pr.id) as pr_count,
+                   MAX(COALESCE(pr.github_updated_at, pr.last_updated_at)) as last_activity_at
+            FROM projects p
+            LEFT JOIN pull_requests pr ON pr.project_id = p.id
+            GROUP BY p.id
+            HAVING last_activity_at IS NULL OR last_activity_at < ?
+            ORDER BY last_activity_at IS NOT NULL, last_activity_at ASC, p.name
             "#
         )
-        .bind(github_username)
-        .bind(avatar_url)
-        .bind(display_name)
-        .bind(now)
-        .execute(&self.pool)
-        .await?
-        .last_insert_rowid();
+        .bind(cutoff)
+        .fetch_all(&self.pool)
+        .await?;
 
-        Ok(TeamMember {
-            id,
-            github_username: github_username.to_string(),
-            avatar_url: avatar_url.map(|s| s.to_string()),
-            display_name: display_name.map(|s| s.to_string()),
-            created_at: now,
-        })
+        let projects = rows.into_iter().map(|row| InactiveProject {
+            id: row.get("id"),
+            name: row.get("name"),
+            description: row.get("description"),
+            created_at: row.get("created_at"),
+            pr_count: row.get("pr_count"),
+            last_activity_at: row.get("last_activity_at"),
+        }).collect();
+
+        Ok(projects)
     }
 
-    pub async fn update_team_member_info(
+    // Team member operations
+    pub async fn get_or_create_team_member(&self, github_username: String) -> Result<TeamMember> {
+        if let Some(member) = self.get_team_member_by_username(&github_username).await? {
+            return Ok(member);
+        }
+        self.add_team_member(&github_username, None, None).await
+    }
+
+    // Update team member with GitHub data (avatar, display name)
+
+    // Pull request operations
+    pub async fn get_pull_requests(&self) -> Result<Vec<PullRequest>> {
+        self.get_pull_requests_sorted(None).await
+    }
+
+    /// All PRs ordered by a whitelisted sort key (defaults to most recently updated)
+    pub async fn get_pull_requests_sorted(&self, sort_by: Option<&str>) -> Result<Vec<PullRequest>> {
+        let rows = sqlx::query(&format!("{} ORDER BY {}", PR_SELECT, pr_order_by(sort_by)?))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(pull_requests_from_rows(&rows))
+    }
+
+    /// PRs narrowed by any of project, status, author and whose move it is, in the default
+    /// order; with nothing set it's the same list as get_pull_requests
+    pub async fn get_pull_requests_filtered(
         &self,
-        member_id: i64,
-        avatar_url: &str,
-        display_name: Option<&str>
-    ) -> Result<()> {
-        sqlx::query(
-            "UPDATE team_members SET avatar_url = ?, display_name = ? WHERE id = ?"
-        )
-        .bind(avatar_url)
-        .bind(display_name)
-        .bind(member_id)
+        project_id: Option<i64>,
+        status: Option<&str>,
+        author_id: Option<i64>,
+        waiting_on: Option<WaitingOn>,
+    ) -> Result<Vec<PullRequest>> {
+        let filter = FilterSpec {
+            project_id,
+            statuses: status.map(str::to_string).into_iter().collect(),
+            author_id,
+            waiting_on,
+            ..Default::default()
+        };
+        let mut qb = QueryBuilder::new(format!("{} WHERE 1 = 1", PR_SELECT));
+        PrQuery::new(&filter, Utc::now().timestamp()).push_conditions(&mut qb);
+        qb.push(" ORDER BY ").push(pr_order_by(None)?);
+        let rows = qb.build().fetch_all(&self.pool).await?;
+
+        Ok(pull_requests_from_rows(&rows))
+    }
+
+    /// PRs in exactly `status` (case-sensitive, "Waiting" is not "waiting")
+    pub async fn get_pull_requests_by_status(&self, status: &str) -> Result<Vec<PullRequest>> {
+        self.get_pull_requests_filtered(None, Some(status), None, None).await
+    }
+
+    /// Pull requests with age and time since last update computed in SQL
+    pub async fn get_pull_requests_with_age(&self, sort_by: Option<&str>) -> Result<Vec<PullRequestWithAge>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT base.*,
+                   CAST(strftime('%s', 'now') AS INTEGER) - base.github_created_at as age_seconds,
+                   CAST(strftime('%s', 'now') AS INTEGER)
+                       - COALESCE(base.github_updated_at, base.last_updated_at) as time_since_update_seconds
+            FROM ({}) base
+            ORDER BY {}
+            "#,
+            PR_SELECT,
+            pr_order_by(sort_by)?
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut prs: Vec<PullRequestWithAge> = rows.iter().map(|row| PullRequestWithAge {
+            pull_request: pull_request_from_row(row),
+            age_seconds: row.get("age_seconds"),
+            time_since_update_seconds: row.get("time_since_update_seconds"),
+        }).collect();
+        assign_display_keys(prs.iter_mut().map(|pr| &mut pr.pull_request));
+        Ok(prs)
+    }
+
+    pub async fn get_unread_pull_requests(&self, project_id: Option<i64>, sort_by: Option<&str>) -> Result<Vec<PullRequest>> {
+        let rows = sqlx::query(&format!(
+            "SELECT * FROM ({}) WHERE has_unread_changes = 1 AND (? IS NULL OR project_id = ?) ORDER BY {}",
+            PR_SELECT,
+            pr_order_by(sort_by)?
+        ))
+        .bind(project_id)
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pull_requests_from_rows(&rows))
+    }
+
+    pub async fn mark_pr_viewed(&self, pr_id: i64) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let result = sqlx::query(&format!(
+            r#"
+            INSERT INTO pr_profile_state (pr_id, profile_id, last_viewed_at)
+            SELECT id, {}, ? FROM pull_requests WHERE id = ?
+            ON CONFLICT(pr_id, profile_id) DO UPDATE SET last_viewed_at = excluded.last_viewed_at
+            "#,
+            ACTIVE_PROFILE_ID
+        ))
+        .bind(now)
+        .bind(pr_id)
         .execute(&self.pool)
         .await?;
 
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Pull request not found"));
+        }
+
         Ok(())
     }
 
-    /// Check if a PR with the given GitHub ID already exists
-    pub async fn get_pull_request_by_github_id(&self, github_id: i64) -> Result<Option<PullRequest>> {
-        let row = sqlx::query(
+    /// Mark every PR (or every PR in a project) as viewed by the active profile, returning how many were updated
+    pub async fn mark_all_viewed(&self, project_id: Option<i64>) -> Result<u64> {
+        let now = chrono::Utc::now().timestamp();
+        let result = sqlx::query(&format!(
             r#"
-            SELECT
-                pr.id, pr.github_id, pr.pr_number, pr.title, pr.author_id, pr.project_id,
-                pr.last_updated_at, pr.status, pr.branch, pr.score, pr.repository_owner, pr.repository_name,
-                tm.github_username as author_name,
-                tm.avatar_url as author_avatar,
-                tm.display_name as author_display_name,
-                p.name as project_name
+            INSERT INTO pr_profile_state (pr_id, profile_id, last_viewed_at)
+            SELECT id, {}, ? FROM pull_requests WHERE ? IS NULL OR project_id = ?
+            ON CONFLICT(pr_id, profile_id) DO UPDATE SET last_viewed_at = excluded.last_viewed_at
+            "#,
+            ACTIVE_PROFILE_ID
+        ))
+        .bind(now)
+        .bind(project_id)
+        .bind(project_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Compact board payload grouped by status column (archived PRs are not on the board)
+    pub async fn get_board_data(&self, project_id: Option<i64>) -> Result<BoardData> {
+        let rows = sqlx::query(
+            r#"
+            SELECT pr.id, pr.pr_number, pr.title, pr.status, pr.score, pr.priority,
+                   tm.avatar_url as author_avatar
             FROM pull_requests pr
             LEFT JOIN team_members tm ON pr.author_id = tm.id
-            LEFT JOIN projects p ON pr.project_id = p.id
-            WHERE pr.github_id = ?
+            WHERE pr.status != 'archived' AND (? IS NULL OR pr.project_id = ?)
+            ORDER BY pr.last_updated_at DESC
             "#
         )
-        .bind(github_id)
-        .fetch_optional(&self.pool)
+        .bind(project_id)
+        .bind(project_id)
+        .fetch_all(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(PullRequest {
+        let mut columns: Vec<BoardColumn> = BOARD_COLUMNS.iter().map(|status| BoardColumn {
+            status: status.to_string(),
+            count: 0,
+            cards: Vec::new(),
+        }).collect();
+
+        for row in rows {
+            let card = BoardCard {
                 id: row.get("id"),
-                github_id: row.get("github_id"),
                 pr_number: row.get("pr_number"),
                 title: row.get("title"),
-                author_id: row.get("author_id"),
-                project_id: row.get("project_id"),
-                last_updated_at: row.get("last_updated_at"),
-                author_name: row.get("author_name"),
                 author_avatar: row.get("author_avatar"),
-                author_display_name: row.get("author_display_name"),
-                project_name: row.get("project_name"),
-                status: row.get("status"),
-                branch: row.get("branch"),
+                status: row.get::<String, _>("status").into(),
                 score: row.get("score"),
-                repository_owner: row.get("repository_owner"),
-                repository_name: row.get("repository_name"),
-            }))
-        } else {
-            Ok(None)
+                priority: PrPriority::from_i64(row.get("priority")),
+            };
+
+            // Statuses outside the fixed columns get their own column at the end
+            match columns.iter_mut().find(|c| card.status == c.status.as_str()) {
+                Some(column) => column.cards.push(card),
+                None => columns.push(BoardColumn {
+                    status: card.status.to_string(),
+                    count: 0,
+                    cards: vec![card],
+                }),
+            }
+        }
+
+        for column in &mut columns {
+            column.count = column.cards.len();
         }
+        let total = columns.iter().map(|c| c.count).sum();
+
+        Ok(BoardData { columns, total })
     }
 
-    pub async fn add_pull_request(
-        &self,
-        github_id: i64,
-        pr_number: i64,
-        title: Option<String>,
-        author_id: i64,
-        project_id: Option<i64>,
-        branch: Option<String>,
-        status: String,
-        repository_owner: Option<String>,
-        repository_name: Option<String>
-    ) -> Result<PullRequest> {
+    /// User move between columns; unknown statuses are rejected, others stored in canonical form.
+    /// Checked against the transition graph in strict mode and recorded in review_history in the
+    /// same transaction: as "status_changed:<from>-><to>", or a merged/archived lifecycle event
+    /// when the move closes the PR
+    pub async fn update_pr_status(&self, ctx: &WriteContext, pr_id: i64, status: String) -> Result<StatusUpdateResult> {
+        let status = String::from(PrStatus::parse_input(&status)?);
+        let graph = self.enforced_transitions().await?;
+        let enforcement = self.get_blocking_links_enforcement().await?;
+        let mut tx = self.pool.begin().await?;
+
+        let current: Option<String> = sqlx::query_scalar("SELECT status FROM pull_requests WHERE id = ?")
+            .bind(pr_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let current = current.ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))?;
+
+        if current == status {
+            return Ok(StatusUpdateResult::default());
+        }
+        check_status_transition(graph.as_ref(), &current, &status)?;
+
+        let unresolved = blocking_links_for_move(&mut tx, pr_id, &status).await?;
+        if !unresolved.is_empty() && enforcement == BlockingLinksEnforcement::Block {
+            return Err(BlockedByLinks { pr_id, links: unresolved }.into());
+        }
+
+        sqlx::query("UPDATE pull_requests SET status = ? WHERE id = ?")
+            .bind(&status)
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+
         let now = chrono::Utc::now().timestamp();
+        record_status_move(&mut tx, pr_id, &current, &status, &ctx.actor, now).await?;
+        follow_status(&mut tx, pr_id, &format!("status changed to {}", status), Some(&ctx.actor), now).await?;
 
-        let id = sqlx::query(
-            r#"
-            INSERT INTO pull_requests (github_id, pr_number, title, author_id, project_id, branch, status, repository_owner, repository_name, last_updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#
-        )
-        .bind(github_id)
-        .bind(pr_number)
-        .bind(&title)
-        .bind(author_id)
-        .bind(project_id)
-        .bind(&branch)
-        .bind(&status)
-        .bind(&repository_owner)
-        .bind(&repository_name)
-        .bind(now)
-        .execute(&self.pool)
-        .await?
-        .last_insert_rowid();
+        tx.commit().await?;
+        Ok(StatusUpdateResult { unresolved_blocking_links: unresolved })
+    }
 
-        // Fetch the complete PR with joined data
-        let row = sqlx::query(
-            r#"
-            SELECT
-                pr.id,
-                pr.github_id,
-                pr.pr_number,
-                pr.title,
-                pr.author_id,
-                pr.project_id,
-                pr.last_updated_at,
-                tm.github_username as author_name,
-                tm.avatar_url as author_avatar,
-                tm.display_name as author_display_name,
-                p.name as project_name,
-                pr.status,
-                pr.branch,
-                pr.score,
-                pr.repository_owner,
-                pr.repository_name
-            FROM pull_requests pr
-            LEFT JOIN team_members tm ON pr.author_id = tm.id
-            LEFT JOIN projects p ON pr.project_id = p.id
-            WHERE pr.id = ?
-            "#
+    /// A PR's review_history rows, newest first (get_pr_history also has source and details)
+    pub async fn get_review_history(&self, pr_id: i64) -> Result<Vec<ReviewHistory>> {
+        let rows = sqlx::query(
+            "SELECT id, pr_id, action, performed_at, actor FROM review_history WHERE pr_id = ? ORDER BY performed_at DESC, id DESC"
         )
-        .bind(id)
-        .fetch_one(&self.pool)
+        .bind(pr_id)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(PullRequest {
+        Ok(rows.iter().map(|row| ReviewHistory {
             id: row.get("id"),
-            github_id: row.get("github_id"),
-            pr_number: row.get("pr_number"),
-            title: row.get("title"),
-            author_id: row.get("author_id"),
-            project_id: row.get("project_id"),
-            last_updated_at: row.get("last_updated_at"),
-            author_name: row.get("author_name"),
-            author_avatar: row.get("author_avatar"),
-            author_display_name: row.get("author_display_name"),
-            project_name: row.get("project_name"),
-            status: row.get("status"),
-            branch: row.get("branch"),
-            score: row.get("score"),
-            repository_owner: row.get("repository_owner"),
-            repository_name: row.get("repository_name"),
-        })
+            pr_id: row.get("pr_id"),
+            action: row.get("action"),
+            performed_at: row.get("performed_at"),
+            actor: row.get("actor"),
+        }).collect())
+    }
+
+    /// Pin or unpin a PR's status for the active profile so automatic reconciles don't move it;
+    /// a change is recorded in review_history as status_pinned/status_unpinned
+    pub async fn set_pr_status_pinned(&self, ctx: &WriteContext, pr_id: i64, pinned: bool) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let current: Option<bool> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT COALESCE(ps.status_pinned, 0) FROM pull_requests pr
+            LEFT JOIN pr_profile_state ps ON ps.pr_id = pr.id AND ps.profile_id = {}
+            WHERE pr.id = ?
+            "#,
+            ACTIVE_PROFILE_ID
+        ))
+        .bind(pr_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let current = current.ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))?;
+
+        if current == pinned {
+            return Ok(());
+        }
+
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO pr_profile_state (pr_id, profile_id, status_pinned)
+            VALUES (?, {}, ?)
+            ON CONFLICT(pr_id, profile_id) DO UPDATE SET status_pinned = excluded.status_pinned
+            "#,
+            ACTIVE_PROFILE_ID
+        ))
+        .bind(pr_id)
+        .bind(pinned)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO review_history (pr_id, action, performed_at, actor) VALUES (?, ?, ?, ?)")
+            .bind(pr_id)
+            .bind(if pinned { "status_pinned" } else { "status_unpinned" })
+            .bind(chrono::Utc::now().timestamp())
+            .bind(&ctx.actor)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Open, unpinned PRs with enough repository info to check their merge state on GitHub
+    pub async fn get_merge_candidates(&self) -> Result<Vec<PullRequest>> {
+        let rows = sqlx::query(&format!(
+            r#"{} WHERE pr.status NOT IN ('Merged', 'archived') AND COALESCE(ps.status_pinned, 0) = 0 AND pr.is_issue = 0
+                AND pr.repository_owner IS NOT NULL AND pr.repository_owner != ''
+                AND pr.repository_name IS NOT NULL AND pr.repository_name != ''
+               ORDER BY pr.id"#,
+            PR_SELECT
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pull_requests_from_rows(&rows))
+    }
+
+    /// Turn a tracked issue row into the PR that implements it, keeping project, status and local state
+    pub async fn convert_issue_to_pr(&self, id: i64, pr: &NewPullRequest) -> Result<PullRequest> {
+        let now = chrono::Utc::now().timestamp();
+        let rules = self.get_title_rules().await?;
+        let parsed = pr.title.as_deref()
+            .map(|title| title_rules::parse_title(&rules, title))
+            .unwrap_or_default();
+
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            r#"
+            UPDATE pull_requests SET
+                github_id = ?, pr_number = ?, title = ?, author_id = ?, branch = ?,
+                repository_owner = ?, repository_name = ?, github_created_at = ?, github_updated_at = ?,
+                commit_type = ?, commit_scope = ?, ticket_key = ?, is_issue = 0, locally_edited = NULL, last_updated_at = ?
+            WHERE id = ? AND is_issue = 1
+            "#
+        )
+        .bind(pr.github_id)
+        .bind(pr.pr_number)
+        .bind(&pr.title)
+        .bind(pr.author_id)
+        .bind(&pr.branch)
+        .bind(&pr.repository_owner)
+        .bind(&pr.repository_name)
+        .bind(pr.github_created_at)
+        .bind(pr.github_updated_at)
+        .bind(&parsed.commit_type)
+        .bind(&parsed.commit_scope)
+        .bind(&parsed.ticket_key)
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Item {} is not a tracked issue", id));
+        }
+
+        let converted_to = format!(
+            "{}/{}#{}",
+            pr.repository_owner.as_deref().unwrap_or_default(),
+            pr.repository_name.as_deref().unwrap_or_default(),
+            pr.pr_number
+        );
+        record_lifecycle_event(&mut tx, id, LifecycleEvent::ConvertedToPr, Some(&converted_to), None, now).await?;
+        tx.commit().await?;
+
+        self.get_pull_request_by_id(id).await?
+            .ok_or_else(|| anyhow::anyhow!("Pull request {} not found", id))
+    }
+
+    /// Move a PR to Merged and record it in review_history
+    pub async fn mark_pr_merged(&self, pr_id: i64, merged_at: Option<i64>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let now = chrono::Utc::now().timestamp();
+
+        sqlx::query("UPDATE pull_requests SET status = 'Merged' WHERE id = ?")
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_lifecycle_event(&mut tx, pr_id, LifecycleEvent::Merged, None, None, merged_at.unwrap_or(now)).await?;
+        follow_status(&mut tx, pr_id, "merged on GitHub", None, merged_at.unwrap_or(now)).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Archive a PR GitHub closed without merging, recording the status it had
+    pub async fn mark_pr_closed(&self, pr_id: i64, closed_at: Option<i64>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let status: String = sqlx::query_scalar("SELECT status FROM pull_requests WHERE id = ?")
+            .bind(pr_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))?;
+
+        sqlx::query("UPDATE pull_requests SET status = 'archived' WHERE id = ?")
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let performed_at = closed_at.unwrap_or_else(|| chrono::Utc::now().timestamp());
+        record_lifecycle_event(&mut tx, pr_id, LifecycleEvent::Closed, Some(&status), None, performed_at).await?;
+        follow_status(&mut tx, pr_id, "closed on GitHub", None, performed_at).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Repair PRs whose project or author row was deleted outside the app: dangling project ids
+    /// are cleared and dangling authors are relinked to the placeholder member.
+    pub async fn fix_missing_references(&self) -> Result<MissingReferenceFix> {
+        let dangling_authors: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pull_requests WHERE author_id NOT IN (SELECT id FROM team_members)"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let placeholder = if dangling_authors > 0 {
+            Some(self.get_or_create_team_member(PLACEHOLDER_MEMBER_USERNAME.to_string()).await?)
+        } else {
+            None
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let projects_cleared = sqlx::query(
+            "UPDATE pull_requests SET project_id = NULL WHERE project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects)"
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let authors_relinked = match &placeholder {
+            Some(member) => sqlx::query("UPDATE pull_requests SET author_id = ? WHERE author_id NOT IN (SELECT id FROM team_members)")
+                .bind(member.id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected(),
+            None => 0,
+        };
+        tx.commit().await?;
+
+        println!("🔧 Fixed missing references: {} projects cleared, {} authors relinked", projects_cleared, authors_relinked);
+        Ok(MissingReferenceFix { projects_cleared, authors_relinked })
+    }
+
+    /// Record that a team member reviewed a PR; repeat reviews keep the latest time
+    pub async fn add_pr_reviewer(&self, pr_id: i64, team_member_id: i64, reviewed_at: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO pr_reviewers (pr_id, team_member_id, reviewed_at) VALUES (?, ?, ?)
+            ON CONFLICT(pr_id, team_member_id) DO UPDATE SET reviewed_at = MAX(reviewed_at, excluded.reviewed_at)
+            "#
+        )
+        .bind(pr_id)
+        .bind(team_member_id)
+        .bind(reviewed_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Who reviewed a PR, most recent review first
+    pub async fn get_pr_reviewers(&self, pr_id: i64) -> Result<Vec<PrReviewer>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT r.team_member_id, r.reviewed_at, tm.github_username, tm.display_name
+            FROM pr_reviewers r LEFT JOIN team_members tm ON tm.id = r.team_member_id
+            WHERE r.pr_id = ?
+            ORDER BY r.reviewed_at DESC, r.team_member_id
+            "#
+        )
+        .bind(pr_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|row| PrReviewer {
+            team_member_id: row.get("team_member_id"),
+            github_username: row.get("github_username"),
+            display_name: row.get("display_name"),
+            reviewed_at: row.get("reviewed_at"),
+        }).collect())
+    }
+
+    /// Merged PRs that one person or nobody reviewed, newest first
+    pub async fn get_single_reviewer_prs(&self) -> Result<Vec<PullRequest>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE {} AND COALESCE(rc.reviewer_count, 0) <= 1 ORDER BY pr.last_updated_at DESC",
+            PR_SELECT,
+            PR_WAS_MERGED
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pull_requests_from_rows(&rows))
+    }
+
+    /// Scoring a PR by hand also brings it back into score coverage. A new score is recorded
+    /// in review_history
+    pub async fn update_pr_score(&self, ctx: &WriteContext, pr_id: i64, score: i32) -> Result<()> {
+        check_score(score)?;
+        let mut tx = self.pool.begin().await?;
+        let current = current_score(&mut tx, pr_id).await?;
+
+        sqlx::query("UPDATE pull_requests SET score = ?, exclude_from_scoring = 0 WHERE id = ?")
+            .bind(score)
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+        if current != Some(score) {
+            record_score_change(&mut tx, ctx, pr_id, current, Some(score)).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Back to unscored; exclude_from_scoring is left as it is
+    pub async fn clear_pr_score(&self, ctx: &WriteContext, pr_id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let current = current_score(&mut tx, pr_id).await?;
+
+        if current.is_none() {
+            return Ok(());
+        }
+
+        sqlx::query("UPDATE pull_requests SET score = NULL WHERE id = ?")
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+        record_score_change(&mut tx, ctx, pr_id, current, None).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Set a PR's priority, recording the change in review_history
+    pub async fn set_pr_priority(&self, ctx: &WriteContext, pr_id: i64, priority: PrPriority) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let current: Option<i64> = sqlx::query_scalar("SELECT priority FROM pull_requests WHERE id = ?")
+            .bind(pr_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let current = current
+            .map(PrPriority::from_i64)
+            .ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))?;
+
+        if current == priority {
+            return Ok(());
+        }
+
+        sqlx::query("UPDATE pull_requests SET priority = ? WHERE id = ?")
+            .bind(priority.as_i64())
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("INSERT INTO review_history (pr_id, action, performed_at, actor) VALUES (?, ?, ?, ?)")
+            .bind(pr_id)
+            .bind(format!("priority_changed: {} -> {}", current, priority))
+            .bind(chrono::Utc::now().timestamp())
+            .bind(&ctx.actor)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Move a PR to another project, recording the change in review_history by project name
+    pub async fn update_pr_project(&self, ctx: &WriteContext, pr_id: i64, project_id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        ensure_project_exists(&mut tx, project_id).await?;
+
+        let current: Option<(Option<i64>, Option<String>)> = sqlx::query_as(
+            "SELECT pr.project_id, p.name FROM pull_requests pr LEFT JOIN projects p ON p.id = pr.project_id WHERE pr.id = ?"
+        )
+        .bind(pr_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (current_id, current_name) = current.ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))?;
+
+        if current_id == Some(project_id) {
+            return Ok(());
+        }
+
+        sqlx::query("UPDATE pull_requests SET project_id = ? WHERE id = ?")
+            .bind(project_id)
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let new_name: String = sqlx::query_scalar("SELECT name FROM projects WHERE id = ?")
+            .bind(project_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO review_history (pr_id, action, performed_at, actor) VALUES (?, ?, ?, ?)")
+            .bind(pr_id)
+            .bind(format!("project_changed: {} -> {}", current_name.as_deref().unwrap_or("none"), new_name))
+            .bind(chrono::Utc::now().timestamp())
+            .bind(&ctx.actor)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // PR comment operations
+    pub async fn add_pr_comment(&self, ctx: &WriteContext, pr_id: i64, author_member_id: i64, body: String) -> Result<PrComment> {
+        let body = body.trim().to_string();
+        if body.is_empty() {
+            return Err(anyhow::anyhow!("Comment body cannot be empty"));
+        }
+
+        let pr_exists: i64 = sqlx::query("SELECT COUNT(*) as count FROM pull_requests WHERE id = ?")
+            .bind(pr_id)
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+        if pr_exists == 0 {
+            return Err(anyhow::anyhow!("Pull request not found"));
+        }
+
+        let member_exists: i64 = sqlx::query("SELECT COUNT(*) as count FROM team_members WHERE id = ?")
+            .bind(author_member_id)
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+        if member_exists == 0 {
+            return Err(anyhow::anyhow!("Team member not found"));
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let id = sqlx::query(
+            "INSERT INTO pr_comments (pr_id, author_member_id, body, created_at, added_by) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(pr_id)
+        .bind(author_member_id)
+        .bind(&body)
+        .bind(now)
+        .bind(&ctx.actor)
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid();
+
+        let comments = self.get_pr_comments(pr_id).await?;
+        comments.into_iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Comment not found after insert"))
+    }
+
+    /// Comments for a PR, oldest first
+    pub async fn get_pr_comments(&self, pr_id: i64) -> Result<Vec<PrComment>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                c.id, c.pr_id, c.author_member_id, c.body, c.created_at, c.added_by,
+                tm.github_username as author_name,
+                tm.display_name as author_display_name,
+                tm.avatar_url as author_avatar
+            FROM pr_comments c
+            LEFT JOIN team_members tm ON c.author_member_id = tm.id
+            WHERE c.pr_id = ?
+            ORDER BY c.created_at ASC, c.id ASC
+            "#
+        )
+        .bind(pr_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let comments = rows.into_iter().map(|row| PrComment {
+            id: row.get("id"),
+            pr_id: row.get("pr_id"),
+            author_member_id: row.get("author_member_id"),
+            body: row.get("body"),
+            created_at: row.get("created_at"),
+            author_name: row.get("author_name"),
+            author_display_name: row.get("author_display_name"),
+            author_avatar: row.get("author_avatar"),
+            added_by: row.get("added_by"),
+        }).collect();
+
+        Ok(comments)
+    }
+
+    // Settings operations
+    pub async fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let row = sqlx::query("SELECT value FROM settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|row| row.get("value")))
+    }
+
+    pub async fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        if !is_user_setting(key) {
+            return Err(anyhow::anyhow!("Unknown setting: {}", key));
+        }
+        validate_setting_value(key, value)?;
+        self.write_setting(key, value).await
+    }
+
+    // Upsert without the known-key check, for state the app keeps in settings itself
+    async fn write_setting(&self, key: &str, value: &str) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        sqlx::query(
+            r#"
+            INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+            "#
+        )
+        .bind(key)
+        .bind(value)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_all_settings(&self) -> Result<BTreeMap<String, String>> {
+        let rows = sqlx::query("SELECT key, value FROM settings ORDER BY key")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| (row.get("key"), row.get("value"))).collect())
+    }
+
+    /// Snapshot every settings row as JSON, without any project/PR data
+    pub async fn export_settings_json(&self) -> Result<String> {
+        let snapshot = serde_json::json!({
+            "version": SETTINGS_EXPORT_VERSION,
+            "settings": self.get_all_settings().await?,
+        });
+
+        Ok(serde_json::to_string_pretty(&snapshot)?)
+    }
+
+    /// Restore settings from export_settings_json output; unknown keys are skipped with a warning
+    pub async fn import_settings_json(&self, json: &str) -> Result<SettingsImportResult> {
+        let snapshot: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| anyhow::anyhow!("Invalid settings JSON: {}", e))?;
+
+        let version = snapshot.get("version").and_then(|v| v.as_i64()).unwrap_or(0);
+        if version != SETTINGS_EXPORT_VERSION {
+            return Err(anyhow::anyhow!("Unsupported settings export version: {}", version));
+        }
+
+        let settings = snapshot.get("settings")
+            .and_then(|s| s.as_object())
+            .ok_or_else(|| anyhow::anyhow!("Settings JSON is missing the 'settings' object"))?;
+
+        let mut result = SettingsImportResult { imported: Vec::new(), skipped_unknown: Vec::new() };
+        let mut tx = self.pool.begin().await?;
+        let now = chrono::Utc::now().timestamp();
+
+        for (key, value) in settings {
+            if !is_user_setting(key) {
+                println!("⚠️ Skipping unknown setting during import: {}", key);
+                result.skipped_unknown.push(key.clone());
+                continue;
+            }
+
+            // Values are stored as text; accept plain JSON scalars for hand-edited files
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            validate_setting_value(key, &value)
+                .map_err(|e| anyhow::anyhow!("Invalid value for '{}': {}", key, e))?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)
+                ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+                "#
+            )
+            .bind(key)
+            .bind(&value)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            result.imported.push(key.clone());
+        }
+
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    /// Compiled title rules from the setting, or the built-in defaults when unset
+    pub async fn get_title_rules(&self) -> Result<Vec<regex::Regex>> {
+        match self.get_setting(TITLE_RULES_KEY).await? {
+            Some(json) => title_rules::parse_rules(&json).map_err(|e| anyhow::anyhow!(e)),
+            None => Ok(title_rules::default_rules()),
+        }
+    }
+
+    /// Re-parse every PR title with the current rules; returns how many PRs changed
+    pub async fn apply_title_rules(&self) -> Result<u64> {
+        let rules = self.get_title_rules().await?;
+        let rows = sqlx::query("SELECT id, title, commit_type, commit_scope, ticket_key FROM pull_requests")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut changed = 0;
+        for row in &rows {
+            let title: Option<String> = row.get("title");
+            let parsed = title.as_deref().map(|t| title_rules::parse_title(&rules, t)).unwrap_or_default();
+            let current = ParsedTitle {
+                commit_type: row.get("commit_type"),
+                commit_scope: row.get("commit_scope"),
+                ticket_key: row.get("ticket_key"),
+            };
+            if parsed == current {
+                continue;
+            }
+
+            sqlx::query("UPDATE pull_requests SET commit_type = ?, commit_scope = ?, ticket_key = ? WHERE id = ?")
+                .bind(&parsed.commit_type)
+                .bind(&parsed.commit_scope)
+                .bind(&parsed.ticket_key)
+                .bind(row.get::<i64, _>("id"))
+                .execute(&mut *tx)
+                .await?;
+            changed += 1;
+        }
+        tx.commit().await?;
+
+        println!("🏷️ Title rules updated {} PRs", changed);
+        Ok(changed)
+    }
+
+    /// PRs whose parsed commit type matches (case-insensitive), newest first
+    pub async fn get_prs_by_commit_type(&self, commit_type: &str) -> Result<Vec<PullRequest>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE pr.commit_type = ? COLLATE NOCASE ORDER BY pr.last_updated_at DESC",
+            PR_SELECT
+        ))
+        .bind(commit_type.trim())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pull_requests_from_rows(&rows))
+    }
+
+    /// PRs that predate the repository columns (or lost them) and can't be refreshed
+    pub async fn get_prs_missing_repo_fields(&self) -> Result<Vec<PullRequest>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE pr.repository_owner IS NULL OR pr.repository_owner = '' OR pr.repository_name IS NULL OR pr.repository_name = '' ORDER BY pr.id",
+            PR_SELECT
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pull_requests_from_rows(&rows))
+    }
+
+    pub async fn get_pull_request_by_id(&self, pr_id: i64) -> Result<Option<PullRequest>> {
+        let row = sqlx::query(&format!("{} WHERE pr.id = ?", PR_SELECT))
+            .bind(pr_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(pull_request_from_row))
+    }
+
+    pub async fn get_pr_detail(&self, pr_id: i64) -> Result<Option<PrDetail>> {
+        let Some(pull_request) = self.get_pull_request_by_id(pr_id).await? else {
+            return Ok(None);
+        };
+        let external_links = self.get_pr_external_links(pr_id).await?;
+        let (created_by_version, updated_by_version) = self.get_pr_row_versions(pr_id).await?;
+        Ok(Some(PrDetail { pull_request, external_links, created_by_version, updated_by_version }))
+    }
+
+    /// (created_by_version, updated_by_version) of one PR row
+    pub async fn get_pr_row_versions(&self, pr_id: i64) -> Result<(Option<String>, Option<String>)> {
+        let row = sqlx::query("SELECT created_by_version, updated_by_version FROM pull_requests WHERE id = ?")
+            .bind(pr_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| (row.get("created_by_version"), row.get("updated_by_version"))).unwrap_or_default())
+    }
+
+    /// Store the size GitHub reported; a response without it leaves the stored counts alone
+    pub async fn update_pr_size(&self, pr_id: i64, additions: Option<i64>, deletions: Option<i64>, changed_files: Option<i64>) -> Result<()> {
+        sqlx::query(
+            "UPDATE pull_requests SET additions = COALESCE(?, additions), deletions = COALESCE(?, deletions), changed_files = COALESCE(?, changed_files) WHERE id = ?"
+        )
+        .bind(additions)
+        .bind(deletions)
+        .bind(changed_files)
+        .bind(pr_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_pr_reactions(&self, pr_id: i64, total: Option<i64>, plus_one: Option<i64>) -> Result<()> {
+        sqlx::query("UPDATE pull_requests SET reactions_total = ?, reactions_plus_one = ? WHERE id = ?")
+            .bind(total)
+            .bind(plus_one)
+            .bind(pr_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Destructive operation safeguards
+    /// Issue a single-use nonce the client must echo back to confirm `action`
+    pub async fn issue_confirmation_nonce(&self, action: &str) -> Result<String> {
+        let now = chrono::Utc::now().timestamp();
+        sqlx::query("DELETE FROM confirmation_nonces WHERE expires_at < ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        let nonce: String = sqlx::query_scalar(
+            "INSERT INTO confirmation_nonces (nonce, action, expires_at) VALUES (lower(hex(randomblob(16))), ?, ?) RETURNING nonce"
+        )
+        .bind(action)
+        .bind(now + CONFIRMATION_NONCE_TTL_SECS)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(nonce)
+    }
+
+    /// Consume a nonce; fails if it is unknown, expired or was issued for another action
+    pub async fn consume_confirmation_nonce(&self, nonce: &str, action: &str) -> Result<()> {
+        let row = sqlx::query("DELETE FROM confirmation_nonces WHERE nonce = ? RETURNING action, expires_at")
+            .bind(nonce)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Invalid or already used confirmation"))?;
+
+        let issued_for: String = row.get("action");
+        let expires_at: i64 = row.get("expires_at");
+        if issued_for != action {
+            return Err(anyhow::anyhow!("Confirmation was issued for '{}', not '{}'", issued_for, action));
+        }
+        if expires_at < chrono::Utc::now().timestamp() {
+            return Err(anyhow::anyhow!("Confirmation expired, please confirm again"));
+        }
+
+        Ok(())
+    }
+
+    /// Copy the database file into backup_dir before a destructive change.
+    /// Returns None when backups are disabled.
+    pub async fn create_safety_backup(&self, reason: &str) -> Result<Option<PathBuf>> {
+        let Some(dir) = &self.backup_dir else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(dir)?;
+
+        let path = dir.join(format!("{}-{}.sqlite", reason, chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await?;
+
+        println!("💾 Safety backup written to {:?}", path);
+        Ok(Some(path))
+    }
+
+    // Bulk operations
+    async fn select_pr_ids(&self, query: &PrQuery<'_>) -> Result<Vec<i64>> {
+        Ok(query.select_ids().build_query_scalar().fetch_all(&self.pool).await?)
+    }
+
+    async fn get_bulk_sample(&self, ids: &[i64]) -> Result<Vec<BulkPrSummary>> {
+        let sample_ids = &ids[..ids.len().min(BULK_SAMPLE_SIZE)];
+        if sample_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT pr.id, pr.pr_number, pr.title, pr.status, p.name as project_name \
+             FROM pull_requests pr LEFT JOIN projects p ON pr.project_id = p.id WHERE pr.id IN ("
+        );
+        let mut list = qb.separated(", ");
+        for id in sample_ids {
+            list.push_bind(*id);
+        }
+        qb.push(") ORDER BY pr.id");
+
+        let rows = qb.build().fetch_all(&self.pool).await?;
+        Ok(rows.iter().map(|row| BulkPrSummary {
+            id: row.get("id"),
+            pr_number: row.get("pr_number"),
+            title: row.get("title"),
+            status: row.get::<String, _>("status").into(),
+            project_name: row.get("project_name"),
+        }).collect())
+    }
+
+    /// Archive every PR matching the filter, writing an 'archived' history entry (with the previous status) for each
+    pub async fn bulk_archive_by_filter(&self, ctx: &WriteContext, filter: &FilterSpec) -> Result<BulkOperationResult> {
+        if filter.is_empty() {
+            return Err(anyhow::anyhow!("Refusing to archive without a filter"));
+        }
+        let now = chrono::Utc::now().timestamp();
+        let ids = self.select_pr_ids(&BulkAction::Archive.query(filter, now)).await?;
+        let sample = self.get_bulk_sample(&ids).await?;
+
+        for chunk in ids.chunks(BULK_CHUNK_SIZE) {
+            let mut tx = self.pool.begin().await?;
+
+            let mut select: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT id, status FROM pull_requests WHERE id IN (");
+            let mut list = select.separated(", ");
+            for id in chunk {
+                list.push_bind(*id);
+            }
+            select.push(")");
+            let previous: Vec<(i64, String)> = select.build_query_as().fetch_all(&mut *tx).await?;
+
+            let mut update: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE pull_requests SET status = 'archived' WHERE id IN (");
+            let mut list = update.separated(", ");
+            for id in chunk {
+                list.push_bind(*id);
+            }
+            update.push(")");
+            update.build().execute(&mut *tx).await?;
+
+            for (id, status) in &previous {
+                record_lifecycle_event(&mut tx, *id, LifecycleEvent::Archived, Some(status), Some(&ctx.actor), now).await?;
+                follow_status(&mut tx, *id, "archived", Some(&ctx.actor), now).await?;
+            }
+
+            tx.commit().await?;
+        }
+
+        println!("📦 Archived {} PRs", ids.len());
+        Ok(BulkOperationResult { affected: ids.len() as u64, sample })
+    }
+
+    /// The non-archived PRs a bulk operation would touch, without touching them: the total plus
+    /// the first BULK_PREVIEW_LIMIT. Selects through the same query as the operation itself;
+    /// archived matches are left out, though a delete removes them too.
+    pub async fn preview_bulk_by_filter(&self, filter: &FilterSpec, action: BulkAction) -> Result<BulkPreview> {
+        if filter.is_empty() {
+            return Err(anyhow::anyhow!("Refusing to preview without a filter"));
+        }
+        let query = action.query(filter, chrono::Utc::now().timestamp()).excluding_status("archived");
+        let total: i64 = query.select_count().build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT pr.id, pr.pr_number, pr.title, pr.status, p.name as project_name \
+             FROM pull_requests pr LEFT JOIN projects p ON pr.project_id = p.id WHERE 1 = 1"
+        );
+        query.push_conditions(&mut qb);
+        qb.push(" ORDER BY pr.id LIMIT ").push_bind(BULK_PREVIEW_LIMIT);
+        let rows = qb.build().fetch_all(&self.pool).await?;
+
+        Ok(BulkPreview {
+            action,
+            total: total as u64,
+            prs: rows.iter().map(|row| BulkPrSummary {
+                id: row.get("id"),
+                pr_number: row.get("pr_number"),
+                title: row.get("title"),
+                status: row.get::<String, _>("status").into(),
+                project_name: row.get("project_name"),
+            }).collect(),
+        })
+    }
+
+    /// Delete every PR matching the filter along with its history, comments and queued retries.
+    /// Requires a CONFIRM_BULK_DELETE nonce and takes a safety backup first.
+    pub async fn bulk_delete_by_filter(&self, filter: &FilterSpec, confirm_nonce: &str) -> Result<BulkOperationResult> {
+        if filter.is_empty() {
+            return Err(anyhow::anyhow!("Refusing to delete without a filter"));
+        }
+        self.consume_confirmation_nonce(confirm_nonce, CONFIRM_BULK_DELETE).await?;
+
+        let ids = self.select_pr_ids(&BulkAction::Delete.query(filter, chrono::Utc::now().timestamp())).await?;
+        let sample = self.get_bulk_sample(&ids).await?;
+        if ids.is_empty() {
+            return Ok(BulkOperationResult { affected: 0, sample });
+        }
+
+        self.create_safety_backup("pre-bulk-delete").await?;
+
+        for chunk in ids.chunks(BULK_CHUNK_SIZE) {
+            let mut tx = self.pool.begin().await?;
+            for table in PR_CHILD_TABLES {
+                let mut delete: QueryBuilder<Sqlite> = QueryBuilder::new(format!("DELETE FROM {} WHERE pr_id IN (", table));
+                let mut list = delete.separated(", ");
+                for id in chunk {
+                    list.push_bind(*id);
+                }
+                delete.push(")");
+                delete.build().execute(&mut *tx).await?;
+            }
+
+            let mut delete: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM pull_requests WHERE id IN (");
+            let mut list = delete.separated(", ");
+            for id in chunk {
+                list.push_bind(*id);
+            }
+            delete.push(")");
+            delete.build().execute(&mut *tx).await?;
+
+            tx.commit().await?;
+        }
+
+        println!("🗑️ Deleted {} PRs", ids.len());
+        Ok(BulkOperationResult { affected: ids.len() as u64, sample })
+    }
+
+    /// Delete one PR with its history, comments and everything else attached to it, in one
+    /// transaction. Returns how many review_history rows went with it.
+    pub async fn delete_pull_request(&self, ctx: &WriteContext, pr_id: i64) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut history_deleted = 0;
+        for table in PR_CHILD_TABLES {
+            let rows = sqlx::query(&format!("DELETE FROM {} WHERE pr_id = ?", table))
+                .bind(pr_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if table == "review_history" {
+                history_deleted = rows;
+            }
+        }
+        let deleted = sqlx::query("DELETE FROM pull_requests WHERE id = ?")
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            // Dropping the transaction rolls back; there was nothing to delete anyway
+            return Err(anyhow::anyhow!("Pull request {} not found", pr_id));
+        }
+        tx.commit().await?;
+
+        println!("🗑️ {} deleted PR {} and {} history entries", ctx.actor, pr_id, history_deleted);
+        Ok(history_deleted)
+    }
+
+    /// Delete one project's PRs and everything attached to them, and optionally the project itself,
+    /// in a single transaction. Requires a CONFIRM_CLEAR_PROJECT nonce and takes a safety backup first.
+    /// Team members are kept even when they no longer author any PR. Returns rows deleted per table.
+    pub async fn clear_project_data(&self, project_id: i64, delete_project: bool, confirm_nonce: &str) -> Result<BTreeMap<String, u64>> {
+        if self.get_project_by_id(project_id).await?.is_none() {
+            return Err(anyhow::anyhow!("Project {} not found", project_id));
+        }
+        self.consume_confirmation_nonce(confirm_nonce, CONFIRM_CLEAR_PROJECT).await?;
+        self.create_safety_backup("pre-clear-project").await?;
+
+        let mut deleted = BTreeMap::new();
+        let mut tx = self.pool.begin().await?;
+        for table in PR_CHILD_TABLES {
+            let rows = sqlx::query(&format!(
+                "DELETE FROM {} WHERE pr_id IN (SELECT id FROM pull_requests WHERE project_id = ?)",
+                table
+            ))
+            .bind(project_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            deleted.insert(table.to_string(), rows);
+        }
+
+        let mut project_tables = vec!["pull_requests"];
+        if delete_project {
+            project_tables.extend(["daily_stats", "project_members", "projects"]);
+        }
+        for table in project_tables {
+            let column = if table == "projects" { "id" } else { "project_id" };
+            let rows = sqlx::query(&format!("DELETE FROM {} WHERE {} = ?", table, column))
+                .bind(project_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            deleted.insert(table.to_string(), rows);
+        }
+        tx.commit().await?;
+
+        println!("🧹 Cleared project {}: {:?}", project_id, deleted);
+        Ok(deleted)
+    }
+
+    // Retry queue operations
+    /// Record a failed attempt, scheduling the next one with backoff or parking the entry
+    pub async fn record_retry_failure(&self, pr_id: i64, operation: &str, error: &str, now: i64) -> Result<RetryEntry> {
+        let attempts: i64 = sqlx::query_scalar("SELECT attempts FROM retry_queue WHERE pr_id = ? AND operation = ?")
+            .bind(pr_id)
+            .bind(operation)
+            .fetch_optional(&self.pool)
+            .await?
+            .unwrap_or(0)
+            + 1;
+        let parked = attempts >= RETRY_MAX_ATTEMPTS;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO retry_queue (pr_id, operation, attempts, next_attempt_at, last_error, parked, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(pr_id, operation) DO UPDATE SET
+                attempts = excluded.attempts,
+                next_attempt_at = excluded.next_attempt_at,
+                last_error = excluded.last_error,
+                parked = excluded.parked
+            RETURNING id, pr_id, operation, attempts, next_attempt_at, last_error, parked, created_at
+            "#
+        )
+        .bind(pr_id)
+        .bind(operation)
+        .bind(attempts)
+        .bind(now + retry_backoff_secs(attempts))
+        .bind(error)
+        .bind(parked)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if parked {
+            println!("🅿️ Parked {} for PR {} after {} attempts: {}", operation, pr_id, attempts, error);
+        }
+
+        Ok(retry_entry_from_row(&row))
+    }
+
+    /// Entries whose next attempt is due (parked entries are excluded)
+    pub async fn get_due_retries(&self, now: i64) -> Result<Vec<RetryEntry>> {
+        let rows = sqlx::query(
+            "SELECT * FROM retry_queue WHERE parked = 0 AND next_attempt_at <= ? ORDER BY next_attempt_at"
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(retry_entry_from_row).collect())
+    }
+
+    /// Parked entries that gave up after RETRY_MAX_ATTEMPTS
+    pub async fn get_failed_operations(&self) -> Result<Vec<RetryEntry>> {
+        let rows = sqlx::query("SELECT * FROM retry_queue WHERE parked = 1 ORDER BY next_attempt_at DESC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(retry_entry_from_row).collect())
+    }
+
+    pub async fn get_retry_entry(&self, id: i64) -> Result<Option<RetryEntry>> {
+        let row = sqlx::query("SELECT * FROM retry_queue WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(retry_entry_from_row))
+    }
+
+    /// Unpark an entry and make it due immediately. Attempts are kept, so another
+    /// failure parks it again straight away.
+    pub async fn reschedule_retry_now(&self, id: i64, now: i64) -> Result<()> {
+        let result = sqlx::query("UPDATE retry_queue SET parked = 0, next_attempt_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Retry entry {} not found", id));
+        }
+
+        Ok(())
+    }
+
+    /// Remove an entry once its operation succeeds
+    pub async fn complete_retry(&self, pr_id: i64, operation: &str) -> Result<()> {
+        sqlx::query("DELETE FROM retry_queue WHERE pr_id = ? AND operation = ?")
+            .bind(pr_id)
+            .bind(operation)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Repository operations
+    /// Register every repository referenced by a PR, then list them all
+    pub async fn get_tracked_repositories(&self) -> Result<Vec<TrackedRepository>> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO repositories (owner, name)
+            SELECT DISTINCT repository_owner, repository_name FROM pull_requests
+            WHERE repository_owner IS NOT NULL AND repository_owner != ''
+              AND repository_name IS NOT NULL AND repository_name != ''
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let rows = sqlx::query("SELECT * FROM repositories ORDER BY owner, name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(tracked_repository_from_row).collect())
+    }
+
+    /// Which conditional features are in use, for the token permission report
+    pub async fn get_feature_usage(&self) -> Result<FeatureUsage> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                EXISTS(SELECT 1 FROM pull_requests WHERE is_issue = 1) AS tracks_issues,
+                EXISTS(SELECT 1 FROM pull_requests WHERE shipped_in_release IS NOT NULL) AS resolves_releases
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(FeatureUsage {
+            tracks_issues: row.get("tracks_issues"),
+            resolves_releases: row.get("resolves_releases"),
+        })
+    }
+
+    pub async fn get_inaccessible_repositories(&self) -> Result<Vec<TrackedRepository>> {
+        let rows = sqlx::query("SELECT * FROM repositories WHERE accessible = 0 ORDER BY owner, name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(tracked_repository_from_row).collect())
+    }
+
+    pub async fn set_repository_access(&self, id: i64, accessible: bool, checked_at: i64) -> Result<()> {
+        sqlx::query("UPDATE repositories SET accessible = ?, last_checked_at = ? WHERE id = ?")
+            .bind(accessible)
+            .bind(checked_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_pr_repo_fields(&self, pr_id: i64, owner: &str, name: &str) -> Result<()> {
+        sqlx::query("UPDATE pull_requests SET repository_owner = ?, repository_name = ? WHERE id = ?")
+            .bind(owner)
+            .bind(name)
+            .bind(pr_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Add sample data for testing
+    pub async fn add_sample_data(&self) -> Result<()> {
+        // Add sample projects if none exist
+        let project_count: i64 = sqlx::query("SELECT COUNT(*) as count FROM projects")
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+
+        if project_count == 0 {
+            let projects = vec![
+                ("Frontend Core", Some("Main React application")),
+                ("Backend API", Some("REST API and services")),
+                ("Mobile App", Some("iOS and Android applications")),
+                ("DevOps Tools", Some("CI/CD and deployment tools")),
+            ];
+
+            for (name, desc) in projects {
+                self.add_project(name.to_string(), desc.map(|s| s.to_string())).await?;
+            }
+        }
+
+        // Add sample team members and PRs
+        let member_count: i64 = sqlx::query("SELECT COUNT(*) as count FROM team_members")
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+
+        if member_count == 0 {
+            let members = vec!["Alex Chen", "Sarah Liao", "Michael Wu"];
+
+            for member in members {
+                let team_member = self.get_or_create_team_member(member.to_string()).await?;
+
+                // Add sample PRs for each member
+                let current_time = chrono::Utc::now().timestamp();
+                sqlx::query(
+                    r#"
+                    INSERT INTO pull_requests
+                    (github_id, pr_number, title, author_id, project_id, last_updated_at, status, branch, score)
+                    VALUES (?, ?, ?, ?, 1, ?, ?, ?, ?)
+                    "#
+                )
+                .bind(1000 + team_member.id)
+                .bind(1000 + team_member.id)
+                .bind(format!("Sample PR from {}", member))
+                .bind(team_member.id)
+                .bind(current_time)
+                .bind("Reviewing")
+                .bind(format!("feature/sample-{}", team_member.id))
+                .bind(if team_member.id % 2 == 0 { Some(9) } else { None::<i32> })
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Team member management methods
+    pub async fn get_team_member_by_username(&self, username: &str) -> Result<Option<TeamMember>> {
+        let row = sqlx::query(
+            "SELECT id, github_username, avatar_url, display_name, created_at, avatar_path
+             FROM team_members WHERE github_username = ?"
+        )
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(team_member_from_row))
+    }
+
+    pub async fn get_team_members(&self) -> Result<Vec<TeamMember>> {
+        let rows = sqlx::query(
+            "SELECT id, github_username, avatar_url, display_name, created_at, avatar_path
+             FROM team_members ORDER BY github_username"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(team_member_from_row).collect())
+    }
+
+    pub async fn delete_team_member(&self, id: i64) -> Result<()> {
+        // Authored PRs block the delete, like PRs assigned to a project; comments and
+        // reviews are refused too rather than left pointing at nobody
+        let row = sqlx::query(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM pull_requests WHERE author_id = ?) as prs,
+                (SELECT COUNT(*) FROM pr_comments WHERE author_member_id = ?) as comments,
+                (SELECT COUNT(*) FROM pr_reviewers WHERE team_member_id = ?) as reviews
+            "#
+        )
+        .bind(id)
+        .bind(id)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+        let (pr_count, comment_count, review_count): (i64, i64, i64) = (row.get("prs"), row.get("comments"), row.get("reviews"));
+
+        if pr_count > 0 {
+            return Err(anyhow::anyhow!(
+                "Cannot delete team member: {} pull requests are authored by this member. Please delete them first.",
+                pr_count
+            ));
+        }
+        if comment_count + review_count > 0 {
+            return Err(anyhow::anyhow!(
+                "Cannot delete team member: they wrote {} comments and reviewed {} pull requests.",
+                comment_count,
+                review_count
+            ));
+        }
+
+        // Project rosters aren't history, and review comment counts are re-tallied from GitHub
+        // on the next sync, so neither blocks the delete
+        let mut tx = self.pool.begin().await?;
+        for table in ["project_members", "reviewer_comment_stats"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE team_member_id = ?", table))
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let result = sqlx::query("DELETE FROM team_members WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Team member not found"));
+        }
+
+        Ok(())
+    }
+
+    /// Insert the member, or update the existing one in the same statement, so concurrent
+    /// imports of the same author can't race into a UNIQUE violation. Values given replace the
+    /// stored ones (a new avatar URL resets the downloaded copy, as in update_team_member_info);
+    /// None keeps what's there.
+    pub async fn add_team_member(
+        &self,
+        github_username: &str,
+        avatar_url: Option<&str>,
+        display_name: Option<&str>
+    ) -> Result<TeamMember> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO team_members (github_username, avatar_url, display_name, created_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(github_username) DO UPDATE SET
+                avatar_path = CASE WHEN excluded.avatar_url IS NULL OR avatar_url IS excluded.avatar_url THEN avatar_path END,
+                avatar_failures = CASE WHEN excluded.avatar_url IS NULL OR avatar_url IS excluded.avatar_url THEN avatar_failures ELSE 0 END,
+                avatar_retry_at = CASE WHEN excluded.avatar_url IS NULL OR avatar_url IS excluded.avatar_url THEN avatar_retry_at END,
+                avatar_url = COALESCE(excluded.avatar_url, avatar_url),
+                display_name = COALESCE(excluded.display_name, display_name)
+            RETURNING id, github_username, avatar_url, display_name, created_at, avatar_path
+            "#
+        )
+        .bind(github_username)
+        .bind(avatar_url)
+        .bind(display_name)
+        .bind(chrono::Utc::now().timestamp())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(team_member_from_row(&row))
+    }
+
+    pub async fn update_team_member_info(
+        &self,
+        member_id: i64,
+        avatar_url: &str,
+        display_name: Option<&str>
+    ) -> Result<()> {
+        // A new avatar URL makes the downloaded copy stale and earns the download a fresh start
+        sqlx::query(
+            r#"
+            UPDATE team_members SET
+                avatar_path = CASE WHEN avatar_url IS ? THEN avatar_path END,
+                avatar_failures = CASE WHEN avatar_url IS ? THEN avatar_failures ELSE 0 END,
+                avatar_retry_at = CASE WHEN avatar_url IS ? THEN avatar_retry_at END,
+                avatar_url = ?,
+                display_name = ?
+            WHERE id = ?
+            "#
+        )
+        .bind(avatar_url)
+        .bind(avatar_url)
+        .bind(avatar_url)
+        .bind(avatar_url)
+        .bind(display_name)
+        .bind(member_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Check if a PR with the given GitHub ID already exists
+    pub async fn get_pull_request_by_github_id(&self, github_id: i64) -> Result<Option<PullRequest>> {
+        let row = sqlx::query(&format!("{} WHERE pr.github_id = ?", PR_SELECT))
+            .bind(github_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(pull_request_from_row))
+    }
+
+    pub async fn add_pull_request(&self, ctx: &WriteContext, new_pr: NewPullRequest) -> Result<PullRequest> {
+        self.add_pull_request_with_note(ctx, new_pr, None).await
+    }
+
+    /// add_pull_request with details on the "created" history entry (e.g. a project override)
+    pub async fn add_pull_request_with_note(&self, ctx: &WriteContext, new_pr: NewPullRequest, note: Option<&str>) -> Result<PullRequest> {
+        let rules = self.get_title_rules().await?;
+        let now = chrono::Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+        if let Some(project_id) = new_pr.project_id {
+            ensure_project_exists(&mut tx, project_id).await?;
+        }
+        let id = insert_pull_request(&mut tx, &new_pr, Some(&ctx.actor), &rules, now).await?;
+        // Starts the PR's timeline; the status changes that follow are logged by update_pr_status
+        record_lifecycle_event(&mut tx, id, LifecycleEvent::Created, note, Some(&ctx.actor), now).await?;
+        tx.commit().await?;
+
+        // Fetch the complete PR with joined data
+        let row = sqlx::query(&format!("{} WHERE pr.id = ?", PR_SELECT))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(pull_request_from_row(&row))
     }
 }
 
-fn get_database_path() -> Result<PathBuf> {
+pub const APP_DIR_NAME: &str = "PRTracker";
+// Builds with the dev feature keep their data here instead (dev_env.rs)
+pub const DEV_APP_DIR_NAME: &str = "PRTracker-dev";
+pub const DATABASE_FILE_NAME: &str = "database.sqlite";
+
+/// The app directory under `data_dir`, for a dev build or the real app
+pub fn app_dir_in(data_dir: &Path, dev: bool) -> PathBuf {
+    data_dir.join(if dev { DEV_APP_DIR_NAME } else { APP_DIR_NAME })
+}
+
+/// The app's data directory (created if missing); holds the database and instance lock
+pub fn get_app_dir() -> Result<PathBuf> {
     let data_dir = data_dir().ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?;
-    let app_dir = data_dir.join("PRTracker");
+    let app_dir = app_dir_in(&data_dir, cfg!(feature = "dev"));
 
     // Ensure the directory exists
     if !app_dir.exists() {
         std::fs::create_dir_all(&app_dir)?;
         println!("Created database directory: {:?}", app_dir);
     }
+    Ok(app_dir)
+}
 
-    let db_path = app_dir.join("database.sqlite");
+fn get_database_path() -> Result<PathBuf> {
+    let db_path = get_app_dir()?.join(DATABASE_FILE_NAME);
     println!("Database path: {:?}", db_path);
     Ok(db_path)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seed_prs(db: &Database, count: i64) -> Result<()> {
+        let project = db.add_project("Board".to_string(), None).await?;
+        let author = db.add_team_member(
+            "octocat",
+            Some("https://avatars.githubusercontent.com/u/583231?v=4"),
+            Some("The Octocat"),
+        ).await?;
+        let statuses = ["Waiting", "Reviewing", "Action", "Approved", "archived"];
+
+        for i in 0..count {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: 10_000 + i,
+                pr_number: i,
+                title: Some(format!("Refactor the widget pipeline to support streaming batch {}", i)),
+                author_id: author.id,
+                project_id: Some(project.id),
+                branch: Some(format!("feature/widget-streaming-{}", i)),
+                status: statuses[(i % 5) as usize].into(),
+                repository_owner: Some("octo-org".to_string()),
+                repository_name: Some("widget-service".to_string()),
+                ..Default::default()
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_board_data_groups_by_status() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 10).await.unwrap();
+
+        let board = db.get_board_data(None).await.unwrap();
+        let statuses: Vec<&str> = board.columns.iter().map(|c| c.status.as_str()).collect();
+        assert_eq!(statuses, BOARD_COLUMNS.to_vec());
+        assert!(board.columns.iter().all(|c| c.count == 2 && c.cards.len() == 2));
+        // Archived PRs are not on the board
+        assert_eq!(board.total, 8);
+
+        let other_project = db.get_board_data(Some(9999)).await.unwrap();
+        assert_eq!(other_project.total, 0);
+        assert_eq!(other_project.columns.len(), BOARD_COLUMNS.len());
+    }
+
+    #[tokio::test]
+    async fn test_board_payload_smaller_than_full_rows() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 1000).await.unwrap();
+
+        let full = serde_json::to_vec(&db.get_pull_requests().await.unwrap()).unwrap();
+        let board = serde_json::to_vec(&db.get_board_data(None).await.unwrap()).unwrap();
+        assert!(board.len() * 2 < full.len());
+    }
+
+    #[tokio::test]
+    async fn test_pr_comments_thread() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 1).await.unwrap();
+        let pr = db.get_pull_requests().await.unwrap().remove(0);
+        let reviewer = db.add_team_member("reviewer", None, Some("Rita Reviewer")).await.unwrap();
+
+        let first = db.add_pr_comment(&WriteContext::default(), pr.id, pr.author_id, "Pushed a fix".to_string()).await.unwrap();
+        let second = db.add_pr_comment(&WriteContext::new("ana"), pr.id, reviewer.id, "  Looks good now  ".to_string()).await.unwrap();
+        assert_eq!(second.body, "Looks good now");
+        assert_eq!(second.added_by.as_deref(), Some("ana"));
+        assert_eq!(second.author_display_name.as_deref(), Some("Rita Reviewer"));
+
+        let thread = db.get_pr_comments(pr.id).await.unwrap();
+        let ids: Vec<i64> = thread.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![first.id, second.id]);
+        assert_eq!(thread[0].author_name.as_deref(), Some("octocat"));
+
+        assert!(db.add_pr_comment(&WriteContext::default(), pr.id, reviewer.id, "   ".to_string()).await.is_err());
+        assert!(db.add_pr_comment(&WriteContext::default(), 9999, reviewer.id, "Orphan".to_string()).await.is_err());
+        assert!(db.add_pr_comment(&WriteContext::default(), pr.id, 9999, "Ghost".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unread_tracking() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 3).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+
+        // Newly added PRs start unread (NULL last_viewed_at)
+        assert!(prs.iter().all(|p| p.has_unread_changes && p.last_viewed_at.is_none()));
+
+        db.mark_pr_viewed(prs[0].id).await.unwrap();
+        let unread = db.get_unread_pull_requests(None, None).await.unwrap();
+        assert_eq!(unread.len(), 2);
+        assert!(unread.iter().all(|p| p.id != prs[0].id));
+
+        // A GitHub update after the last view makes it unread again
+        sqlx::query("UPDATE pr_profile_state SET last_viewed_at = 100 WHERE pr_id = ?")
+            .bind(prs[0].id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE review_history SET performed_at = 50 WHERE pr_id = ? AND action = 'created'")
+            .bind(prs[0].id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE pull_requests SET github_updated_at = 200 WHERE id = ?")
+            .bind(prs[0].id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(db.get_unread_pull_requests(None, None).await.unwrap().len(), 3);
+
+        // Without a GitHub timestamp the newest history entry is used instead
+        sqlx::query("UPDATE pull_requests SET github_updated_at = NULL WHERE id = ?")
+            .bind(prs[0].id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(db.get_unread_pull_requests(None, None).await.unwrap().len(), 2);
+        sqlx::query("INSERT INTO review_history (pr_id, action, performed_at) VALUES (?, 'commented', 150)")
+            .bind(prs[0].id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(db.get_unread_pull_requests(None, None).await.unwrap().len(), 3);
+
+        let project_id = prs[0].project_id;
+        assert_eq!(db.mark_all_viewed(project_id).await.unwrap(), 3);
+        assert!(db.get_unread_pull_requests(project_id, None).await.unwrap().is_empty());
+        assert!(db.mark_pr_viewed(9999).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_settings_export_import_round_trip() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 2).await.unwrap();
+        db.set_setting("sync_interval_minutes", "15").await.unwrap();
+        db.set_setting("status_order", "Waiting,Reviewing,Action,Approved").await.unwrap();
+        assert!(db.set_setting("not_a_setting", "1").await.is_err());
+
+        let json = db.export_settings_json().await.unwrap();
+
+        let other = Database::new_in_memory().await.unwrap();
+        let tampered = json.replace("\"settings\": {", "\"settings\": {\n    \"mystery_key\": \"x\",");
+        let result = other.import_settings_json(&tampered).await.unwrap();
+        assert_eq!(result.imported.len(), 2);
+        assert_eq!(result.skipped_unknown, vec!["mystery_key".to_string()]);
+
+        assert_eq!(other.get_all_settings().await.unwrap(), db.get_all_settings().await.unwrap());
+        // Only configuration moves across, never projects or PRs
+        assert!(other.get_projects().await.unwrap().is_empty());
+        assert!(other.get_pull_requests().await.unwrap().is_empty());
+
+        assert!(other.import_settings_json("{\"version\": 99, \"settings\": {}}").await.is_err());
+        assert!(other.import_settings_json("not json").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_pull_requests_filtered_combinations() {
+        let db = Database::new_in_memory().await.unwrap();
+        let alice = db.add_team_member("alice", None, None).await.unwrap();
+        let bob = db.add_team_member("bob", None, None).await.unwrap();
+        let core = db.add_project("Core".to_string(), None).await.unwrap();
+        let web = db.add_project("Web".to_string(), None).await.unwrap();
+        for (n, (author, project, status)) in [
+            (alice.id, core.id, "Waiting"),
+            (alice.id, core.id, "Approved"),
+            (bob.id, core.id, "Waiting"),
+            (bob.id, web.id, "Waiting"),
+        ].into_iter().enumerate() {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: n as i64 + 1,
+                pr_number: n as i64 + 1,
+                author_id: author,
+                project_id: Some(project),
+                status: status.into(),
+                ..Default::default()
+            }).await.unwrap();
+        }
+        let numbers = |prs: Vec<PullRequest>| {
+            let mut numbers: Vec<i64> = prs.iter().map(|pr| pr.pr_number).collect();
+            numbers.sort();
+            numbers
+        };
+
+        let all = db.get_pull_requests_filtered(None, None, None, None).await.unwrap();
+        assert_eq!(all.iter().map(|pr| pr.id).collect::<Vec<_>>(), db.get_pull_requests().await.unwrap().iter().map(|pr| pr.id).collect::<Vec<_>>());
+        assert_eq!(numbers(db.get_pull_requests_filtered(Some(core.id), None, None, None).await.unwrap()), vec![1, 2, 3]);
+        assert_eq!(numbers(db.get_pull_requests_filtered(Some(core.id), Some("Waiting"), None, None).await.unwrap()), vec![1, 3]);
+        assert_eq!(numbers(db.get_pull_requests_filtered(None, Some("Waiting"), Some(bob.id), None).await.unwrap()), vec![3, 4]);
+        let one = db.get_pull_requests_filtered(Some(core.id), Some("Waiting"), Some(alice.id), None).await.unwrap();
+        assert_eq!(numbers(one.clone()), vec![1]);
+        assert_eq!((one[0].author_name.as_deref(), one[0].project_name.as_deref()), (Some("alice"), Some("Core")));
+        assert!(db.get_pull_requests_filtered(Some(web.id), Some("Approved"), None, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_pull_requests_by_status() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.add_team_member("octocat", None, None).await.unwrap();
+        for (n, status) in ["Waiting", "Reviewing", "Waiting", "Merged"].into_iter().enumerate() {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: n as i64 + 1,
+                pr_number: n as i64 + 1,
+                author_id: author.id,
+                status: status.into(),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let mut waiting: Vec<i64> = db.get_pull_requests_by_status("Waiting").await.unwrap().iter().map(|pr| pr.pr_number).collect();
+        waiting.sort();
+        assert_eq!(waiting, vec![1, 3]);
+        let reviewing = db.get_pull_requests_by_status("Reviewing").await.unwrap();
+        assert_eq!((reviewing.len(), reviewing[0].status.as_str()), (1, "Reviewing"));
+        assert!(db.get_pull_requests_by_status("waiting").await.unwrap().is_empty());
+        assert!(db.get_pull_requests_by_status("Action").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_team_members_listing_and_delete_guard() {
+        let db = Database::new_in_memory().await.unwrap();
+        let zed = db.add_team_member("zed", None, None).await.unwrap();
+        let amy = db.add_team_member("amy", None, Some("Amy")).await.unwrap();
+        let names: Vec<String> = db.get_team_members().await.unwrap().into_iter().map(|m| m.github_username).collect();
+        assert_eq!(names, vec!["amy", "zed"]);
+
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: amy.id,
+            ..Default::default()
+        }).await.unwrap();
+        let err = db.delete_team_member(amy.id).await.unwrap_err().to_string();
+        assert!(err.contains("1 pull requests are authored"), "{}", err);
+
+        db.add_pr_reviewer(pr.id, zed.id, 100).await.unwrap();
+        assert!(db.delete_team_member(zed.id).await.unwrap_err().to_string().contains("reviewed 1 pull requests"));
+
+        let new = db.add_team_member("new", None, None).await.unwrap();
+        let counts = BTreeMap::from([((new.id, 0), 3)]);
+        db.replace_review_comment_stats("acme", "api", 0, &counts, 100).await.unwrap();
+        db.delete_team_member(new.id).await.unwrap();
+        assert_eq!(db.get_review_comment_count(new.id, 0, 7 * 86_400).await.unwrap(), 0);
+        assert!(db.delete_team_member(new.id).await.unwrap_err().to_string().contains("not found"));
+        assert_eq!(db.get_team_members().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_inactive_projects() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 2).await.unwrap(); // "Board" project, active now
+        let empty = db.add_project("Empty".to_string(), None).await.unwrap();
+        let dormant = db.add_project("Dormant".to_string(), None).await.unwrap();
+        let older = db.add_project("Older".to_string(), None).await.unwrap();
+        let author_id = db.get_pull_requests().await.unwrap()[0].author_id;
+
+        let now = chrono::Utc::now().timestamp();
+        for (github_id, project_id, updated_at) in [(1, dormant.id, now - 40 * 86_400), (2, older.id, now - 90 * 86_400)] {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id,
+                pr_number: github_id,
+                author_id,
+                project_id: Some(project_id),
+                status: PrStatus::Waiting,
+                github_updated_at: Some(updated_at),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let inactive = db.get_inactive_projects(30).await.unwrap();
+        let names: Vec<&str> = inactive.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Empty", "Older", "Dormant"]);
+        assert_eq!(inactive[0].id, empty.id);
+        assert_eq!(inactive[0].last_activity_at, None);
+        assert_eq!(inactive[1].last_activity_at, Some(now - 90 * 86_400));
+
+        let very_inactive = db.get_inactive_projects(60).await.unwrap();
+        assert_eq!(very_inactive.len(), 2);
+        assert!(db.get_inactive_projects(-1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pull_requests_with_age() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            github_created_at: Some(now - 3 * 86_400),
+            github_updated_at: Some(now - 3_600),
+            ..Default::default()
+        }).await.unwrap();
+        // Legacy row: no GitHub timestamps, falls back to last_updated_at
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 2,
+            pr_number: 2,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap();
+
+        let prs = db.get_pull_requests_with_age(None).await.unwrap();
+        let tracked = prs.iter().find(|p| p.pull_request.github_id == 1).unwrap();
+        let age = tracked.age_seconds.unwrap();
+        assert!((3 * 86_400..3 * 86_400 + 5).contains(&age));
+        assert!((3_600..3_605).contains(&tracked.time_since_update_seconds));
+
+        let legacy = prs.iter().find(|p| p.pull_request.github_id == 2).unwrap();
+        assert_eq!(legacy.age_seconds, None);
+        assert!((0..5).contains(&legacy.time_since_update_seconds));
+
+        // Flattened so the client sees one object per PR
+        let json = serde_json::to_value(tracked).unwrap();
+        assert_eq!(json["pr_number"], 1);
+        assert!(json["age_seconds"].is_i64());
+    }
+
+    #[tokio::test]
+    async fn test_sort_by_reactions() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 3).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+
+        for (pr, (total, plus_one)) in prs.iter().zip([(2, 1), (9, 7), (2, 2)]) {
+            sqlx::query("UPDATE pull_requests SET reactions_total = ?, reactions_plus_one = ? WHERE id = ?")
+                .bind(total)
+                .bind(plus_one)
+                .bind(pr.id)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+
+        let sorted = db.get_pull_requests_sorted(Some("reactions")).await.unwrap();
+        let totals: Vec<(Option<i64>, Option<i64>)> = sorted.iter()
+            .map(|p| (p.reactions_total, p.reactions_plus_one))
+            .collect();
+        assert_eq!(totals, vec![(Some(9), Some(7)), (Some(2), Some(2)), (Some(2), Some(1))]);
+
+        let unread = db.get_unread_pull_requests(None, Some("reactions")).await.unwrap();
+        assert_eq!(unread[0].reactions_total, Some(9));
+
+        assert!(db.get_pull_requests_sorted(Some("title; DROP TABLE pull_requests")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_pr_priority() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 3).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+        assert!(prs.iter().all(|p| p.priority == PrPriority::Medium));
+
+        db.set_pr_priority(&WriteContext::default(), prs[2].id, PrPriority::High).await.unwrap();
+        db.set_pr_priority(&WriteContext::default(), prs[0].id, PrPriority::Low).await.unwrap();
+        // Setting the same value again is a no-op and adds no history
+        db.set_pr_priority(&WriteContext::default(), prs[2].id, PrPriority::High).await.unwrap();
+
+        let sorted = db.get_pull_requests_sorted(Some("priority")).await.unwrap();
+        let order: Vec<(i64, PrPriority)> = sorted.iter().map(|p| (p.id, p.priority)).collect();
+        assert_eq!(order, vec![
+            (prs[2].id, PrPriority::High),
+            (prs[1].id, PrPriority::Medium),
+            (prs[0].id, PrPriority::Low),
+        ]);
+
+        let actions: Vec<String> = sqlx::query_scalar("SELECT action FROM review_history WHERE pr_id = ?")
+            .bind(prs[2].id)
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(actions, vec!["created".to_string(), "priority_changed: Medium -> High".to_string()]);
+
+        assert!(db.set_pr_priority(&WriteContext::default(), 9999, PrPriority::High).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_score_project_and_pin_changes_are_recorded_with_the_actor() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 1).await.unwrap();
+        let pr_id = db.get_pull_requests().await.unwrap()[0].id;
+        let project = db.add_project("Platform".to_string(), None).await.unwrap();
+        let ana = WriteContext::new("ana");
+
+        db.update_pr_score(&ana, pr_id, 7).await.unwrap();
+        db.update_pr_score(&ana, pr_id, 7).await.unwrap();
+        db.clear_pr_score(&ana, pr_id).await.unwrap();
+        db.clear_pr_score(&ana, pr_id).await.unwrap();
+        db.update_pr_project(&ana, pr_id, project.id).await.unwrap();
+        db.update_pr_project(&ana, pr_id, project.id).await.unwrap();
+        db.set_pr_status_pinned(&ana, pr_id, true).await.unwrap();
+        db.set_pr_status_pinned(&ana, pr_id, true).await.unwrap();
+        db.set_pr_status_pinned(&ana, pr_id, false).await.unwrap();
+
+        // Repeats change nothing and add no history
+        let history: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT action, actor FROM review_history WHERE pr_id = ? AND action != 'created' ORDER BY id"
+        )
+        .bind(pr_id)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+        let ana = Some("ana".to_string());
+        assert_eq!(history, vec![
+            ("score_changed: none -> 7".to_string(), ana.clone()),
+            ("score_changed: 7 -> none".to_string(), ana.clone()),
+            ("project_changed: Board -> Platform".to_string(), ana.clone()),
+            ("status_pinned".to_string(), ana.clone()),
+            ("status_unpinned".to_string(), ana),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_status_changes_are_recorded_in_review_history() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 1).await.unwrap();
+        let pr = db.get_pull_requests_by_status("Waiting").await.unwrap().remove(0);
+
+        db.update_pr_status(&WriteContext::default(), pr.id, "Reviewing".to_string()).await.unwrap();
+        db.update_pr_status(&WriteContext::default(), pr.id, "Approved".to_string()).await.unwrap();
+        // Same status again changes nothing, so nothing is logged
+        db.update_pr_status(&WriteContext::default(), pr.id, "Approved".to_string()).await.unwrap();
+
+        let history = db.get_review_history(pr.id).await.unwrap();
+        let actions: Vec<&str> = history.iter().map(|h| h.action.as_str()).collect();
+        assert_eq!(actions, vec![
+            "waiting_on_changed:reviewer->nobody",
+            "status_changed:Reviewing->Approved",
+            "status_changed:Waiting->Reviewing",
+            "created",
+        ]);
+        assert!(history.iter().all(|h| h.pr_id == pr.id && h.performed_at > 0));
+
+        assert!(db.update_pr_status(&WriteContext::default(), 9999, "Reviewing".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_status_typos_are_rejected_and_legacy_rows_still_read() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 2).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+
+        let err = db.update_pr_status(&WriteContext::default(), prs[0].id, "Reviwing".to_string()).await.unwrap_err();
+        assert!(err.to_string().starts_with("Unknown status 'Reviwing'. Valid statuses: Waiting, Reviewing"), "{}", err);
+        assert!(db.bulk_update_pr_status(&WriteContext::default(), &[prs[0].id], "Reviwing").await.is_err());
+        assert_eq!(db.get_pull_request_by_id(prs[0].id).await.unwrap().unwrap().status, PrStatus::Waiting);
+
+        // Stored in canonical form whatever the casing sent
+        db.update_pr_status(&WriteContext::default(), prs[0].id, "reviewing".to_string()).await.unwrap();
+        let stored: String = sqlx::query_scalar("SELECT status FROM pull_requests WHERE id = ?")
+            .bind(prs[0].id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, "Reviewing");
+        let added = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 900,
+            pr_number: 900,
+            author_id: prs[0].author_id,
+            status: PrStatus::Unknown("approved".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!((added.status.as_str(), added.waiting_on), ("Approved", WaitingOn::Nobody));
+
+        // A status written before validation existed comes back as Unknown, unchanged
+        sqlx::query("UPDATE pull_requests SET status = 'QA' WHERE id = ?").bind(prs[1].id).execute(&db.pool).await.unwrap();
+        let legacy = db.get_pull_request_by_id(prs[1].id).await.unwrap().unwrap();
+        assert_eq!(legacy.status, PrStatus::Unknown("QA".to_string()));
+        assert_eq!(serde_json::to_value(&legacy).unwrap()["status"], "QA");
+    }
+
+    #[tokio::test]
+    async fn test_bulk_archive_by_filter() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 1_250).await.unwrap(); // 250 of each status
+        let other = db.add_project("Other".to_string(), None).await.unwrap();
+        let old = chrono::Utc::now().timestamp() - 90 * 86_400;
+        sqlx::query("UPDATE pull_requests SET github_updated_at = ? WHERE pr_number < 1000")
+            .bind(old)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        assert!(db.bulk_archive_by_filter(&WriteContext::default(), &FilterSpec::default()).await.is_err());
+        let none = db.bulk_archive_by_filter(&WriteContext::default(), &FilterSpec { project_id: Some(other.id), ..Default::default() }).await.unwrap();
+        assert_eq!(none.affected, 0);
+
+        // Approved and older than 60 days: every 5th PR among the first 1000
+        let filter = FilterSpec {
+            statuses: vec!["Approved".to_string(), "archived".to_string()],
+            older_than_days: Some(60),
+            ..Default::default()
+        };
+        let result = db.bulk_archive_by_filter(&WriteContext::default(), &filter).await.unwrap();
+        assert_eq!(result.affected, 200);
+        assert_eq!(result.sample.len(), 20);
+        assert!(result.sample.iter().all(|p| p.status == "Approved"));
+
+        let approved: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pull_requests WHERE status = 'Approved'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(approved, 50);
+        let history: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM review_history WHERE action = 'archived'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(history, 200);
+
+        // Chunking: archive everything still open in the project (750 + 50 rows, two chunks)
+        let board = db.get_pull_requests().await.unwrap()[0].project_id;
+        let result = db.bulk_archive_by_filter(&WriteContext::default(), &FilterSpec { project_id: board, ..Default::default() }).await.unwrap();
+        assert_eq!(result.affected, 800);
+    }
+
+    #[tokio::test]
+    async fn test_delete_pull_request() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 2).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+        let (pr, other) = (&prs[0], &prs[1]);
+        db.set_pr_priority(&WriteContext::default(), pr.id, PrPriority::High).await.unwrap();
+        db.set_pr_priority(&WriteContext::default(), pr.id, PrPriority::Low).await.unwrap();
+        db.set_pr_priority(&WriteContext::default(), other.id, PrPriority::High).await.unwrap();
+        db.add_pr_comment(&WriteContext::default(), pr.id, pr.author_id, "wrong PR".to_string()).await.unwrap();
+        db.add_pr_reviewer(pr.id, pr.author_id, 100).await.unwrap();
+
+        assert_eq!(db.delete_pull_request(&WriteContext::default(), pr.id).await.unwrap(), 3);
+        assert!(db.get_pull_request_by_id(pr.id).await.unwrap().is_none());
+        assert!(db.get_pr_comments(pr.id).await.unwrap().is_empty());
+        assert_eq!(db.get_pr_history(other.id).await.unwrap().len(), 2);
+
+        let error = db.delete_pull_request(&WriteContext::default(), pr.id).await.unwrap_err();
+        assert_eq!(error.to_string(), format!("Pull request {} not found", pr.id));
+    }
+
+    #[tokio::test]
+    async fn test_preview_matches_the_bulk_operation() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 1_250).await.unwrap(); // 250 of each status
+        let filter = FilterSpec { statuses: vec!["Approved".to_string(), "archived".to_string()], ..Default::default() };
+        assert!(db.preview_bulk_by_filter(&FilterSpec::default(), BulkAction::Archive).await.is_err());
+
+        // Only non-archived PRs are evaluated, whatever the action
+        let delete = db.preview_bulk_by_filter(&filter, BulkAction::Delete).await.unwrap();
+        assert_eq!((delete.total, delete.prs.len()), (250, 250));
+        assert!(delete.prs.iter().all(|p| p.status == PrStatus::Approved));
+        let preview = db.preview_bulk_by_filter(&filter, BulkAction::Archive).await.unwrap();
+        assert_eq!((preview.total, preview.prs.len()), (250, 250));
+        assert!(preview.prs.iter().all(|p| p.status == "Approved" && p.project_name.as_deref() == Some("Board")));
+
+        // Nothing was applied, and the operation hits exactly the previewed PRs
+        let project = db.get_pull_requests().await.unwrap()[0].project_id;
+        let wide = db.preview_bulk_by_filter(&FilterSpec { project_id: project, ..Default::default() }, BulkAction::Archive).await.unwrap();
+        assert_eq!((wide.total, wide.prs.len() as i64), (1_000, BULK_PREVIEW_LIMIT));
+        let result = db.bulk_archive_by_filter(&WriteContext::default(), &filter).await.unwrap();
+        assert_eq!(result.affected, preview.total);
+        assert_eq!(
+            result.sample.iter().map(|p| p.id).collect::<Vec<_>>(),
+            preview.prs.iter().take(20).map(|p| p.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bulk_delete_requires_nonce_and_backs_up() {
+        // Backups need a file-backed database
+        let dir = std::env::temp_dir().join(format!("pr-tracker-bulk-delete-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let db = Database::open(&dir.join("database.sqlite")).await.unwrap();
+        let backup_dir = db.backup_dir.clone().unwrap();
+        seed_prs(&db, 10).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+        let waiting = prs.iter().find(|p| p.status == "Waiting").unwrap();
+        db.add_pr_comment(&WriteContext::default(), waiting.id, waiting.author_id, "note".to_string()).await.unwrap();
+
+        let filter = FilterSpec { statuses: vec!["Waiting".to_string()], ..Default::default() };
+        assert!(db.bulk_delete_by_filter(&filter, "not-a-nonce").await.is_err());
+
+        let wrong_action = db.issue_confirmation_nonce("something_else").await.unwrap();
+        assert!(db.bulk_delete_by_filter(&filter, &wrong_action).await.is_err());
+
+        let nonce = db.issue_confirmation_nonce(CONFIRM_BULK_DELETE).await.unwrap();
+        assert_eq!(nonce.len(), 32);
+        let result = db.bulk_delete_by_filter(&filter, &nonce).await.unwrap();
+        assert_eq!(result.affected, 2);
+        assert_eq!(result.sample.iter().map(|p| p.pr_number).collect::<Vec<_>>(), vec![0, 5]);
+        assert_eq!(db.get_pull_requests().await.unwrap().len(), 8);
+        assert!(db.get_pr_comments(waiting.id).await.unwrap().is_empty());
+
+        // Nonces are single use
+        assert!(db.bulk_delete_by_filter(&filter, &nonce).await.is_err());
+
+        let backups: Vec<_> = std::fs::read_dir(&backup_dir).unwrap().collect();
+        assert_eq!(backups.len(), 1);
+        db.pool.close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clear_project_data_keeps_other_projects_and_members() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let doomed = db.add_project("Experiment".to_string(), None).await.unwrap();
+        let kept = db.add_project("Real".to_string(), None).await.unwrap();
+        let mut ids = Vec::new();
+        for (i, project_id) in [doomed.id, doomed.id, kept.id].iter().enumerate() {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64,
+                author_id: author.id,
+                project_id: Some(*project_id),
+                status: PrStatus::Waiting,
+                ..Default::default()
+            }).await.unwrap();
+            db.mark_pr_merged(pr.id, Some(100)).await.unwrap();
+            ids.push(pr.id);
+        }
+        db.add_pr_reviewer(ids[0], author.id, 100).await.unwrap();
+
+        // Unknown projects fail before the nonce is spent
+        let nonce = db.issue_confirmation_nonce(CONFIRM_CLEAR_PROJECT).await.unwrap();
+        assert!(db.clear_project_data(9999, true, &nonce).await.is_err());
+        let wrong_action = db.issue_confirmation_nonce(CONFIRM_BULK_DELETE).await.unwrap();
+        assert!(db.clear_project_data(doomed.id, false, &wrong_action).await.is_err());
+
+        let deleted = db.clear_project_data(doomed.id, false, &nonce).await.unwrap();
+        assert_eq!(deleted["pull_requests"], 2);
+        assert_eq!(deleted["pr_reviewers"], 1);
+        assert_eq!(deleted["review_history"], 6); // created, merged and waiting_on_changed for each
+        assert!(!deleted.contains_key("projects"));
+        assert!(db.get_project_by_id(doomed.id).await.unwrap().is_some());
+
+        let remaining: Vec<i64> = db.get_pull_requests().await.unwrap().iter().map(|p| p.id).collect();
+        assert_eq!(remaining, vec![ids[2]]);
+        let history: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM review_history WHERE pr_id = ?")
+            .bind(ids[2])
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(history, 3);
+
+        let nonce = db.issue_confirmation_nonce(CONFIRM_CLEAR_PROJECT).await.unwrap();
+        let deleted = db.clear_project_data(doomed.id, true, &nonce).await.unwrap();
+        assert_eq!((deleted["pull_requests"], deleted["projects"]), (0, 1));
+        assert!(db.get_project_by_id(doomed.id).await.unwrap().is_none());
+        assert!(db.get_team_member_by_username("octocat").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_author_scorecard() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let other = db.get_or_create_team_member("hubot".to_string()).await.unwrap();
+        let start = 1_704_067_200; // 2024-01-01
+        let hour = 3_600;
+
+        // (author, created): three in the window for octocat, one before it, one by someone else
+        let cases = [(author.id, start), (author.id, start + 10 * hour), (author.id, start + 20 * hour), (author.id, start - hour), (other.id, start)];
+        let mut ids = Vec::new();
+        for (i, (author_id, created)) in cases.iter().enumerate() {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64,
+                author_id: *author_id,
+                status: PrStatus::Waiting,
+                github_created_at: Some(*created),
+                ..Default::default()
+            }).await.unwrap();
+            ids.push(pr.id);
+        }
+        db.mark_pr_merged(ids[0], Some(start + 5 * hour)).await.unwrap();
+        db.update_pr_score(&WriteContext::default(), ids[0], 9).await.unwrap();
+        db.update_pr_score(&WriteContext::default(), ids[1], 7).await.unwrap();
+        // Reviews as a refresh records them; octocat answering on their own PR isn't a review
+        let review = |review_id: i64, reviewer: &str, state: &str, submitted_at: i64| github_reviews::SubmittedReview {
+            review_id,
+            reviewer: reviewer.to_string(),
+            state: state.to_string(),
+            submitted_at,
+            commit_id: None,
+        };
+        db.record_github_reviews(ids[0], &[review(1, "octocat", "COMMENTED", start + hour), review(2, "hubot", "APPROVED", start + 2 * hour)]).await.unwrap();
+        db.record_github_reviews(ids[1], &[review(3, "hubot", "CHANGES_REQUESTED", start + 14 * hour), review(4, "hubot", "APPROVED", start + 30 * hour)]).await.unwrap();
+
+        let card = db.get_author_scorecard("octocat", start, start + 86_400).await.unwrap();
+        assert_eq!((card.authored_count, card.merged_count, card.scored_count), (3, 1, 2));
+        assert_eq!(card.merge_rate, Some(1.0 / 3.0));
+        assert_eq!(card.avg_score, Some(8.0));
+        // First reviews after 2h and 4h; the third PR was never reviewed
+        assert_eq!(card.avg_time_to_first_review_seconds, Some(3.0 * hour as f64));
+        assert_eq!(card.open_count, 3);
+
+        let empty = db.get_author_scorecard("octocat", start + 86_400, start + 2 * 86_400).await.unwrap();
+        assert_eq!((empty.authored_count, empty.merge_rate, empty.avg_score), (0, None, None));
+        assert!(db.get_author_scorecard("nobody", start, start + 1).await.is_err());
+        assert!(db.get_author_scorecard("octocat", start, start).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_pr_burndown() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let day = 86_400;
+        let start = 1_704_067_200; // 2024-01-01
+
+        // (created, merged): open through the range, merged on day 2, opened on day 3, archived before the range
+        let cases = [(start - day, None), (start, Some(start + 2 * day)), (start + 3 * day + 3_600, None), (start - 10 * day, Some(start - 5 * day))];
+        for (i, (created, merged)) in cases.iter().enumerate() {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64,
+                author_id: author.id,
+                status: PrStatus::Waiting,
+                github_created_at: Some(*created),
+                ..Default::default()
+            }).await.unwrap();
+            if let Some(merged_at) = merged {
+                db.mark_pr_merged(pr.id, Some(*merged_at)).await.unwrap();
+            }
+        }
+
+        let points = db.get_open_pr_burndown(start, start + 5 * day, "day").await.unwrap();
+        let counts: Vec<i64> = points.iter().map(|p| p.open_count).collect();
+        assert_eq!(counts, vec![2, 1, 1, 2, 2]);
+        assert_eq!(points[0].bucket_end, start + day);
+
+        // Partial final bucket ends at to_ts
+        let weeks = db.get_open_pr_burndown(start, start + 10 * day, "week").await.unwrap();
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[1].bucket_end, start + 10 * day);
+        assert_eq!(weeks[1].open_count, 2);
+
+        assert!(db.get_open_pr_burndown(start, start + day, "month").await.is_err());
+        assert!(db.get_open_pr_burndown(start, start, "day").await.is_err());
+        assert!(db.get_open_pr_burndown(0, start, "day").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pr_project_must_exist() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let new_pr = |project_id| NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            project_id,
+            status: PrStatus::Waiting,
+            ..Default::default()
+        };
+
+        let err = db.add_pull_request(&WriteContext::default(), new_pr(Some(404))).await.unwrap_err();
+        assert_eq!(err.to_string(), "Project 404 not found");
+        assert!(db.get_pull_requests().await.unwrap().is_empty());
+
+        let pr = db.add_pull_request(&WriteContext::default(), new_pr(None)).await.unwrap();
+        let err = db.update_pr_project(&WriteContext::default(), pr.id, 404).await.unwrap_err();
+        assert_eq!(err.to_string(), "Project 404 not found");
+        assert_eq!(db.get_pull_request_by_id(pr.id).await.unwrap().unwrap().project_id, None);
+
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        assert_eq!(db.update_pr_project(&WriteContext::default(), pr.id + 1, project.id).await.unwrap_err().to_string(), format!("Pull request {} not found", pr.id + 1));
+        db.update_pr_project(&WriteContext::default(), pr.id, project.id).await.unwrap();
+        assert_eq!(db.get_pull_request_by_id(pr.id).await.unwrap().unwrap().project_name.as_deref(), Some("Board"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_project_with_reassign() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let old = db.add_project("Old".to_string(), None).await.unwrap();
+        let new = db.add_project("New".to_string(), None).await.unwrap();
+        db.add_project_member(old.id, author.id).await.unwrap();
+        for (i, project_id) in [old.id, old.id, old.id, new.id].into_iter().enumerate() {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64,
+                author_id: author.id,
+                project_id: Some(project_id),
+                status: PrStatus::Waiting,
+                ..Default::default()
+            }).await.unwrap();
+        }
+        let pulls = &db;
+        let in_project = move |project_id: i64| async move {
+            pulls.get_pull_requests_filtered(Some(project_id), None, None, None).await.unwrap().len()
+        };
+
+        let err = db.delete_project_with_reassign(old.id, ProjectDeleteTarget::Project(old.id)).await.unwrap_err();
+        assert!(err.to_string().contains("the project being deleted"), "{}", err);
+        let err = db.delete_project_with_reassign(old.id, ProjectDeleteTarget::Project(404)).await.unwrap_err();
+        assert_eq!(err.to_string(), "Project 404 not found");
+        assert!(db.delete_project_with_reassign(404, ProjectDeleteTarget::Unassigned).await.is_err());
+        // Nothing moved and nothing deleted
+        assert_eq!(in_project(old.id).await, 3);
+        assert!(db.get_project_by_id(old.id).await.unwrap().is_some());
+
+        let summary = db.delete_project_with_reassign(old.id, ProjectDeleteTarget::Project(new.id)).await.unwrap();
+        assert_eq!(summary, ProjectDeleteSummary { deleted_project_id: old.id, target_project_id: Some(new.id), moved_prs: 3 });
+        assert!(db.get_project_by_id(old.id).await.unwrap().is_none());
+        assert_eq!(in_project(new.id).await, 4);
+
+        let target: ProjectDeleteTarget = serde_json::from_str("\"unassigned\"").unwrap();
+        let summary = db.delete_project_with_reassign(new.id, target).await.unwrap();
+        assert_eq!((summary.target_project_id, summary.moved_prs), (None, 4));
+        assert!(db.get_pull_requests().await.unwrap().iter().all(|pr| pr.project_id.is_none()));
+        assert_eq!(serde_json::from_str::<ProjectDeleteTarget>(r#"{"project": 7}"#).unwrap(), ProjectDeleteTarget::Project(7));
+    }
+
+    #[tokio::test]
+    async fn test_foreign_keys_enforced_on_every_connection() {
+        let dir = std::env::temp_dir().join(format!("pr-tracker-foreign-keys-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let on_disk = Database::open(&dir.join("database.sqlite")).await.unwrap();
+        let in_memory = Database::new_in_memory().await.unwrap();
+
+        for db in [&on_disk, &in_memory] {
+            let enabled: i64 = sqlx::query_scalar("PRAGMA foreign_keys").fetch_one(&db.pool).await.unwrap();
+            assert_eq!(enabled, 1);
+
+            let err = sqlx::query(
+                "INSERT INTO pull_requests (github_id, pr_number, author_id, status, last_updated_at, repository_owner, repository_name)
+                 VALUES (1, 1, 9999, 'Waiting', 0, 'octo', 'repo')"
+            ).execute(&db.pool).await.unwrap_err();
+            assert!(err.to_string().contains("FOREIGN KEY constraint failed"), "{}", err);
+
+            assert!(db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: 1,
+                pr_number: 1,
+                author_id: 9999,
+                status: PrStatus::Waiting,
+                ..Default::default()
+            }).await.is_err());
+            assert!(db.get_pull_requests().await.unwrap().is_empty());
+        }
+
+        drop(on_disk);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dangling_references_are_flagged_and_fixed() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            project_id: Some(project.id),
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap();
+        assert!(!pr.project_missing && !pr.author_missing);
+        assert_eq!(db.fix_missing_references().await.unwrap(), MissingReferenceFix::default());
+
+        // What a manual edit in the sqlite shell does
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&db.pool).await.unwrap();
+        sqlx::query("DELETE FROM projects WHERE id = ?").bind(project.id).execute(&db.pool).await.unwrap();
+        sqlx::query("DELETE FROM team_members WHERE id = ?").bind(author.id).execute(&db.pool).await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = ON").execute(&db.pool).await.unwrap();
+
+        let listed = db.get_pull_requests().await.unwrap().remove(0);
+        assert!(listed.project_missing && listed.author_missing);
+
+        let fixed = db.fix_missing_references().await.unwrap();
+        assert_eq!(fixed, MissingReferenceFix { projects_cleared: 1, authors_relinked: 1 });
+        let pr = db.get_pull_request_by_id(pr.id).await.unwrap().unwrap();
+        assert_eq!((pr.project_id, pr.author_name.as_deref()), (None, Some(PLACEHOLDER_MEMBER_USERNAME)));
+        assert!(!pr.project_missing && !pr.author_missing);
+    }
+
+    #[tokio::test]
+    async fn test_velocity_buckets_by_week() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        let day = 86_400;
+        let monday = 1_704_067_200; // 2024-01-01, a Monday
+        let now = monday + 15 * day; // Tuesday of the third week
+
+        // Merged in week 1 (history), week 3 (history, other project), week 3 (status only, via github_updated_at)
+        let cases = [(Some(monday + day), Some(project.id), "Waiting"), (Some(monday + 14 * day), None, "Waiting"), (None, Some(project.id), "Merged")];
+        for (i, (merged_at, project_id, status)) in cases.iter().enumerate() {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64,
+                author_id: author.id,
+                project_id: *project_id,
+                status: (*status).into(),
+                github_updated_at: Some(monday + 14 * day + 3_600),
+                ..Default::default()
+            }).await.unwrap();
+            if merged_at.is_some() {
+                db.mark_pr_merged(pr.id, *merged_at).await.unwrap();
+            }
+        }
+
+        let points = db.get_velocity(4, None, now, chrono_tz::Tz::UTC).await.unwrap();
+        let series: Vec<(i64, i64)> = points.iter().map(|p| (p.week_start, p.merged_count)).collect();
+        assert_eq!(series, vec![(monday - 7 * day, 0), (monday, 1), (monday + 7 * day, 0), (monday + 14 * day, 2)]);
+
+        let filtered = db.get_velocity(3, Some(project.id), now, chrono_tz::Tz::UTC).await.unwrap();
+        assert_eq!(filtered.iter().map(|p| p.merged_count).collect::<Vec<_>>(), vec![1, 0, 1]);
+
+        // Weeks start at local midnight on Monday
+        let tokyo: chrono_tz::Tz = "Asia/Tokyo".parse().unwrap();
+        assert_eq!(db.get_velocity(1, None, now, tokyo).await.unwrap()[0].week_start, monday + 14 * day - 9 * 3_600);
+        assert!(db.get_velocity(0, None, now, chrono_tz::Tz::UTC).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_suggest_project_for_repo() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let api = db.add_project("API".to_string(), None).await.unwrap();
+        let web = db.add_project("Web".to_string(), None).await.unwrap();
+
+        for (i, (repo, project_id)) in [("api", api.id), ("api", api.id), ("api", web.id), ("web", web.id)].iter().enumerate() {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64,
+                author_id: author.id,
+                project_id: Some(*project_id),
+                status: PrStatus::Waiting,
+                repository_owner: Some("Acme".to_string()),
+                repository_name: Some(repo.to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let suggested = db.suggest_project_for_repo("acme", "API").await.unwrap().unwrap();
+        assert_eq!(suggested.id, api.id);
+        assert_eq!(db.suggest_project_for_repo("acme", "web").await.unwrap().unwrap().id, web.id);
+        assert!(db.suggest_project_for_repo("acme", "unknown").await.unwrap().is_none());
+
+        assert!(db.get_default_project().await.unwrap().is_none());
+        db.set_setting("default_project_id", &web.id.to_string()).await.unwrap();
+        assert_eq!(db.get_default_project().await.unwrap().unwrap().id, web.id);
+    }
+
+    #[tokio::test]
+    async fn test_title_rules_populate_parsed_columns() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        for (i, title) in ["feat(ui): PROJ-1 dark mode", "fix: crash", "[OPS-9] Feat rotate keys"].iter().enumerate() {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64 + 1,
+                title: Some(title.to_string()),
+                author_id: author.id,
+                status: PrStatus::Waiting,
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let feats = db.get_prs_by_commit_type("FEAT").await.unwrap();
+        assert_eq!(feats.len(), 1);
+        assert_eq!(feats[0].title.as_deref(), Some("feat(ui): PROJ-1 dark mode"));
+        assert_eq!((feats[0].commit_scope.as_deref(), feats[0].ticket_key.as_deref()), (Some("ui"), Some("PROJ-1")));
+
+        assert!(db.set_setting("title_rules", r#"[{"pattern": "(?P<type>feat"}]"#).await.is_err());
+        db.set_setting("title_rules", r#"[{"pattern": "^\\[(?P<ticket>[A-Z]+-\\d+)\\] (?P<type>\\w+)"}]"#).await.unwrap();
+        assert_eq!(db.apply_title_rules().await.unwrap(), 3);
+
+        let feats = db.get_prs_by_commit_type("feat").await.unwrap();
+        assert_eq!(feats.len(), 1);
+        assert_eq!(feats[0].ticket_key.as_deref(), Some("OPS-9"));
+        assert_eq!(feats[0].title.as_deref(), Some("[OPS-9] Feat rotate keys"));
+        assert!(db.get_prs_by_commit_type("fix").await.unwrap().is_empty());
+        assert_eq!(db.apply_title_rules().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_review_coverage() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 5).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+
+        let empty = db.get_review_coverage(Some(("octo-org", "widget-service")), None).await.unwrap();
+        assert_eq!((empty.merged_count, empty.coverage_ratio), (0, None));
+
+        db.mark_pr_merged(prs[0].id, None).await.unwrap();
+        db.mark_pr_merged(prs[1].id, None).await.unwrap();
+        db.update_pr_score(&WriteContext::default(), prs[1].id, 9).await.unwrap();
+        // Merged then archived still counts
+        db.mark_pr_merged(prs[2].id, None).await.unwrap();
+        db.update_pr_status(&WriteContext::default(), prs[2].id, "archived".to_string()).await.unwrap();
+        db.update_pr_score(&WriteContext::default(), prs[2].id, 6).await.unwrap();
+        // Scored but never merged does not
+        db.update_pr_score(&WriteContext::default(), prs[3].id, 5).await.unwrap();
+
+        let coverage = db.get_review_coverage(Some(("Octo-Org", "widget-service")), None).await.unwrap();
+        assert_eq!((coverage.merged_count, coverage.scored_count), (3, 2));
+        assert!((coverage.coverage_ratio.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+
+        let project_id = prs[0].project_id.unwrap();
+        assert_eq!(db.get_review_coverage(None, Some(project_id)).await.unwrap(), coverage);
+        assert_eq!(db.get_review_coverage(None, None).await.unwrap(), coverage);
+        assert_eq!(db.get_review_coverage(None, Some(project_id + 1)).await.unwrap().merged_count, 0);
+        assert_eq!(db.get_review_coverage(Some(("octo-org", "other")), None).await.unwrap().merged_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pr_score_range_and_clear() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 1).await.unwrap();
+        let pr_id = db.get_pull_requests().await.unwrap()[0].id;
+
+        for valid in [1, 10] {
+            db.update_pr_score(&WriteContext::default(), pr_id, valid).await.unwrap();
+            assert_eq!(db.get_pull_request_by_id(pr_id).await.unwrap().unwrap().score, Some(valid));
+        }
+        for invalid in [0, 11, -1, 500] {
+            let err = db.update_pr_score(&WriteContext::default(), pr_id, invalid).await.unwrap_err();
+            assert_eq!(err.to_string(), format!("Score must be between 1 and 10, got {}", invalid));
+        }
+        assert_eq!(db.get_pull_request_by_id(pr_id).await.unwrap().unwrap().score, Some(10));
+
+        db.clear_pr_score(&WriteContext::default(), pr_id).await.unwrap();
+        assert_eq!(db.get_pull_request_by_id(pr_id).await.unwrap().unwrap().score, None);
+        // Clearing an unscored PR is fine; a missing PR is not
+        db.clear_pr_score(&WriteContext::default(), pr_id).await.unwrap();
+        assert!(db.clear_pr_score(&WriteContext::default(), pr_id + 1).await.is_err());
+        assert!(db.update_pr_score(&WriteContext::default(), pr_id + 1, 5).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_single_reviewer_prs() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 4).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+        let alice = db.add_team_member("alice", None, None).await.unwrap();
+        let bob = db.add_team_member("bob", None, None).await.unwrap();
+
+        // Two reviewers (one reviewed twice), one reviewer, none, and an open PR with one
+        db.add_pr_reviewer(prs[0].id, alice.id, 100).await.unwrap();
+        db.add_pr_reviewer(prs[0].id, alice.id, 200).await.unwrap();
+        db.add_pr_reviewer(prs[0].id, bob.id, 150).await.unwrap();
+        db.add_pr_reviewer(prs[1].id, bob.id, 100).await.unwrap();
+        db.add_pr_reviewer(prs[3].id, alice.id, 100).await.unwrap();
+        for pr in &prs[..3] {
+            db.mark_pr_merged(pr.id, None).await.unwrap();
+        }
+
+        let counts: Vec<(i64, i64)> = db.get_pull_requests().await.unwrap().iter()
+            .map(|pr| (pr.id, pr.reviewer_count))
+            .collect();
+        assert!(counts.contains(&(prs[0].id, 2)));
+        assert!(counts.contains(&(prs[2].id, 0)));
+
+        let mut flagged: Vec<i64> = db.get_single_reviewer_prs().await.unwrap().iter().map(|pr| pr.id).collect();
+        flagged.sort();
+        let mut expected = vec![prs[1].id, prs[2].id];
+        expected.sort();
+        assert_eq!(flagged, expected);
+    }
+
+    #[tokio::test]
+    async fn test_find_misfiled_prs() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let api = db.add_project("API".to_string(), None).await.unwrap();
+        let web = db.add_project("Web".to_string(), None).await.unwrap();
+
+        // api: 2 in API, 1 in Web (misfiled); web: 1 each (tie, not reported); infra: unassigned
+        let cases = [
+            ("api", Some(api.id)), ("API", Some(api.id)), ("api", Some(web.id)),
+            ("web", Some(web.id)), ("web", Some(api.id)), ("infra", None),
+        ];
+        for (i, (repo, project_id)) in cases.iter().enumerate() {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64 + 1,
+                author_id: author.id,
+                project_id: *project_id,
+                status: PrStatus::Waiting,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some(repo.to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let misfiled = db.find_misfiled_prs().await.unwrap();
+        assert_eq!(misfiled.len(), 1);
+        let entry = &misfiled[0];
+        assert_eq!(entry.pr_number, 3);
+        assert_eq!((entry.current_project_id, entry.current_project_count), (web.id, 1));
+        assert_eq!((entry.suggested_project_id, entry.suggested_project_count), (api.id, 2));
+        assert_eq!(entry.suggested_project_name, "API");
+
+        // Nothing was reassigned
+        let pr = db.get_pull_request_by_id(entry.pr_id).await.unwrap().unwrap();
+        assert_eq!(pr.project_id, Some(web.id));
+    }
+
+    #[tokio::test]
+    async fn test_local_profiles_keep_separate_overlays() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 2).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+        assert_eq!(db.get_active_profile().await.unwrap().name, DEFAULT_PROFILE_NAME);
+
+        db.mark_pr_viewed(prs[0].id).await.unwrap();
+        db.set_pr_status_pinned(&WriteContext::default(), prs[1].id, true).await.unwrap();
+        db.update_pr_score(&WriteContext::default(), prs[0].id, 7).await.unwrap();
+
+        let alice = db.switch_local_profile("alice").await.unwrap();
+        assert!(alice.is_active);
+        let seen_by_alice = db.get_pull_requests().await.unwrap();
+        assert!(seen_by_alice.iter().all(|p| p.has_unread_changes && !p.status_pinned));
+        // Shared facts are the same for everyone
+        assert_eq!(seen_by_alice.iter().find(|p| p.id == prs[0].id).unwrap().score, Some(7));
+        assert_eq!(db.get_merge_candidates().await.unwrap().len(), 2);
+
+        db.switch_local_profile(DEFAULT_PROFILE_NAME).await.unwrap();
+        assert_eq!(db.get_unread_pull_requests(None, None).await.unwrap().len(), 1);
+        assert!(db.get_pull_request_by_id(prs[1].id).await.unwrap().unwrap().status_pinned);
+
+        let profiles = db.get_local_profiles().await.unwrap();
+        assert_eq!(profiles.iter().filter(|p| p.is_active).count(), 1);
+        assert_eq!(profiles.len(), 2);
+        assert!(db.switch_local_profile("  ").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_default_profile_migrates_legacy_state() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 2).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+
+        // Simulate a database from before profiles existed
+        sqlx::query("DELETE FROM pr_profile_state").execute(&db.pool).await.unwrap();
+        sqlx::query("DELETE FROM local_profiles").execute(&db.pool).await.unwrap();
+        sqlx::query("UPDATE pull_requests SET last_viewed_at = 4102444800, status_pinned = 1 WHERE id = ?")
+            .bind(prs[0].id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        db.ensure_default_profile().await.unwrap();
+        let pr = db.get_pull_request_by_id(prs[0].id).await.unwrap().unwrap();
+        assert_eq!(pr.last_viewed_at, Some(4102444800));
+        assert!(pr.status_pinned && !pr.has_unread_changes);
+    }
+
+    #[tokio::test]
+    async fn test_busy_timeout_applies_to_connections() {
+        let db = Database::new_in_memory().await.unwrap();
+        let current = || async { sqlx::query_scalar::<_, i64>("PRAGMA busy_timeout").fetch_one(&db.pool).await.unwrap() };
+        assert_eq!(db.get_busy_timeout(), DEFAULT_BUSY_TIMEOUT_MS);
+        assert_eq!(current().await, DEFAULT_BUSY_TIMEOUT_MS as i64);
+
+        db.set_busy_timeout(20_000).await.unwrap();
+        assert_eq!(current().await, 20_000);
+        assert_eq!(db.get_setting("busy_timeout_ms").await.unwrap().as_deref(), Some("20000"));
+
+        assert!(db.set_busy_timeout(MAX_BUSY_TIMEOUT_MS + 1).await.is_err());
+        assert!(db.set_setting("busy_timeout_ms", "soon").await.is_err());
+        assert_eq!(db.get_busy_timeout(), 20_000);
+    }
+
+    #[tokio::test]
+    async fn test_display_timezone_setting() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_display_timezone().await.unwrap(), system_timezone());
+
+        assert!(db.set_setting("display_timezone", "Tokyo").await.is_err());
+        db.set_setting("display_timezone", "Asia/Tokyo").await.unwrap();
+        assert_eq!(db.get_display_timezone().await.unwrap(), chrono_tz::Asia::Tokyo);
+
+        let bad = r#"{"version": 1, "settings": {"display_timezone": "Nowhere/Special"}}"#;
+        assert!(db.import_settings_json(bad).await.is_err());
+        assert_eq!(db.get_display_timezone().await.unwrap(), chrono_tz::Asia::Tokyo);
+    }
+
+    #[tokio::test]
+    async fn test_merge_candidates_skip_pinned_and_closed() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 4).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+        for pr in &prs {
+            db.update_pr_repo_fields(pr.id, "owner", "repo").await.unwrap();
+        }
+
+        db.set_pr_status_pinned(&WriteContext::default(), prs[0].id, true).await.unwrap();
+        db.mark_pr_merged(prs[1].id, Some(500)).await.unwrap();
+        db.update_pr_status(&WriteContext::default(), prs[2].id, "archived".to_string()).await.unwrap();
+
+        let candidates: Vec<i64> = db.get_merge_candidates().await.unwrap().iter().map(|p| p.id).collect();
+        assert_eq!(candidates, vec![prs[3].id]);
+
+        let merged = db.get_pull_request_by_id(prs[1].id).await.unwrap().unwrap();
+        assert_eq!(merged.status, "Merged");
+        let history: Vec<(String, i64)> = sqlx::query_as("SELECT action, performed_at FROM review_history WHERE pr_id = ? AND action != 'created'")
+            .bind(prs[1].id)
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(history, vec![("merged".to_string(), 500), ("waiting_on_changed:reviewer->nobody".to_string(), 500)]);
+
+        assert!(db.get_pull_request_by_id(prs[0].id).await.unwrap().unwrap().status_pinned);
+        assert!(db.set_pr_status_pinned(&WriteContext::default(), 9999, true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prs_missing_repo_fields() {
+        let db = Database::new_in_memory().await.unwrap();
+        seed_prs(&db, 3).await.unwrap();
+        let prs = db.get_pull_requests().await.unwrap();
+        sqlx::query("UPDATE pull_requests SET repository_owner = NULL WHERE id = ?")
+            .bind(prs[0].id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE pull_requests SET repository_name = '' WHERE id = ?")
+            .bind(prs[1].id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let missing = db.get_prs_missing_repo_fields().await.unwrap();
+        assert_eq!(missing.len(), 2);
+
+        db.update_pr_repo_fields(prs[0].id, "octo-org", "widget-service").await.unwrap();
+        db.update_pr_repo_fields(prs[1].id, "octo-org", "widget-service").await.unwrap();
+        assert!(db.get_prs_missing_repo_fields().await.unwrap().is_empty());
+    }
+}
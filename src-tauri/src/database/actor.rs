@@ -0,0 +1,111 @@
+// Who made a local change, for databases more than one person writes to. Write paths that a
+// person triggers (adding a PR, moving it, archiving and restoring) take a WriteContext and
+// store its actor on pull_requests.added_by and review_history.actor. Changes GitHub reports
+// have no actor. Commands build the context with write_context: the local_identity setting,
+// else the login of the last valid token check, else "local", so a single-user database
+// needs no setup.
+use anyhow::Result;
+use serde::Serialize;
+
+use super::Database;
+
+pub const LOCAL_IDENTITY_KEY: &str = "local_identity";
+pub const DEFAULT_ACTOR: &str = "local";
+pub const MAX_LOCAL_IDENTITY_LEN: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WriteContext {
+    pub actor: String,
+}
+
+impl WriteContext {
+    pub fn new(actor: impl Into<String>) -> Self {
+        WriteContext { actor: actor.into() }
+    }
+}
+
+impl Default for WriteContext {
+    fn default() -> Self {
+        WriteContext::new(DEFAULT_ACTOR)
+    }
+}
+
+pub fn validate_local_identity(value: &str) -> Result<()> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(anyhow::anyhow!("Local identity cannot be empty"));
+    }
+    if value.chars().count() > MAX_LOCAL_IDENTITY_LEN {
+        return Err(anyhow::anyhow!("Local identity is limited to {} characters", MAX_LOCAL_IDENTITY_LEN));
+    }
+    Ok(())
+}
+
+impl Database {
+    pub async fn write_context(&self) -> Result<WriteContext> {
+        if let Some(identity) = self.get_setting(LOCAL_IDENTITY_KEY).await? {
+            return Ok(WriteContext::new(identity.trim()));
+        }
+        let login = self.get_last_token_check().await?
+            .filter(|check| check.valid)
+            .and_then(|check| check.login);
+        Ok(login.map(WriteContext::new).unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::lifecycle::HistoryEntry;
+    use crate::database::query::FilterSpec;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+
+    fn actors(history: &[HistoryEntry]) -> Vec<(&str, Option<&str>)> {
+        history.iter().map(|entry| (entry.action.as_str(), entry.actor.as_deref())).collect()
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_attributed_to_the_context_actor() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.write_context().await.unwrap(), WriteContext::default());
+        db.record_token_check(false, Some("stranger"), 100).await.unwrap();
+        assert_eq!(db.write_context().await.unwrap().actor, DEFAULT_ACTOR);
+        db.record_token_check(true, Some("mihir"), 200).await.unwrap();
+        assert_eq!(db.write_context().await.unwrap().actor, "mihir");
+        assert!(db.set_setting(LOCAL_IDENTITY_KEY, "  ").await.is_err());
+        db.set_setting(LOCAL_IDENTITY_KEY, "mihir-laptop").await.unwrap();
+        let mihir = db.write_context().await.unwrap();
+        assert_eq!(mihir.actor, "mihir-laptop");
+
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&mihir, NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(pr.added_by.as_deref(), Some("mihir-laptop"));
+
+        let ana = WriteContext::new("ana");
+        db.update_pr_status(&ana, pr.id, "Reviewing".to_string()).await.unwrap();
+        db.mark_pr_merged(pr.id, Some(300)).await.unwrap();
+        db.bulk_archive_by_filter(&ana, &FilterSpec { statuses: vec!["Merged".to_string()], ..Default::default() }).await.unwrap();
+        db.restore_archived_pr(&mihir, pr.id).await.unwrap();
+
+        // GitHub's merge has no actor; the PR keeps who added it
+        let history = db.get_pr_history(pr.id).await.unwrap();
+        let mut attributed = actors(&history);
+        attributed.sort();
+        assert_eq!(attributed, vec![
+            ("archived", Some("ana")),
+            ("created", Some("mihir-laptop")),
+            ("merged", None),
+            ("restored", Some("mihir-laptop")),
+            ("status_changed:Waiting->Reviewing", Some("ana")),
+            ("waiting_on_changed:reviewer->nobody", None),
+        ]);
+        assert_eq!(db.get_pull_request_by_id(pr.id).await.unwrap().unwrap().added_by.as_deref(), Some("mihir-laptop"));
+    }
+}
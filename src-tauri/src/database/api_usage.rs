@@ -0,0 +1,70 @@
+// Saved per-feature GitHub call counts, flushed from the in-memory counters in
+// github/api_usage.rs. Rows are hourly, so repeated flushes add to the same row.
+use anyhow::Result;
+use sqlx::Row;
+
+use super::Database;
+use crate::github::api_usage::{hour_start, ApiUsageEntry};
+
+impl Database {
+    /// Add flushed counts to their hourly rows
+    pub async fn add_api_usage(&self, entries: &[ApiUsageEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for entry in entries {
+            sqlx::query(
+                "INSERT INTO api_usage (hour_start, feature, calls) VALUES (?, ?, ?)
+                 ON CONFLICT(hour_start, feature) DO UPDATE SET calls = calls + excluded.calls"
+            )
+            .bind(entry.hour_start)
+            .bind(&entry.feature)
+            .bind(entry.calls)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Saved counts from the hour containing `since` onwards, oldest first
+    pub async fn get_api_usage_since(&self, since: i64) -> Result<Vec<ApiUsageEntry>> {
+        let rows = sqlx::query("SELECT hour_start, feature, calls FROM api_usage WHERE hour_start >= ? ORDER BY hour_start, feature")
+            .bind(hour_start(since))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(|row| ApiUsageEntry {
+            hour_start: row.get("hour_start"),
+            feature: row.get("feature"),
+            calls: row.get("calls"),
+        }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::api_usage::usage_report;
+
+    fn entry(hour_start: i64, feature: &str, calls: i64) -> ApiUsageEntry {
+        ApiUsageEntry { hour_start, feature: feature.to_string(), calls }
+    }
+
+    #[tokio::test]
+    async fn test_saved_and_unsaved_usage_are_combined() {
+        let db = Database::new_in_memory().await.unwrap();
+        db.add_api_usage(&[entry(0, "repo_repair", 40), entry(3600, "add_pr", 2)]).await.unwrap();
+        db.add_api_usage(&[entry(3600, "add_pr", 3), entry(7200, "retry_queue", 5)]).await.unwrap();
+
+        // Two hours back from 03:00 starts at the 01:00 row; the 00:00 row is left out
+        let now = 10_800;
+        let saved = db.get_api_usage_since(now - 2 * 3600).await.unwrap();
+        assert_eq!(saved, vec![entry(3600, "add_pr", 5), entry(7200, "retry_queue", 5)]);
+
+        let report = usage_report(2, now - 2 * 3600, saved, vec![entry(7200, "repo_repair", 9), entry(7200, "add_pr", 1)]);
+        assert_eq!(report.total_calls, 20);
+        let totals: Vec<(&str, i64)> = report.features.iter().map(|f| (f.feature.as_str(), f.calls)).collect();
+        assert_eq!(totals, vec![("repo_repair", 9), ("add_pr", 6), ("retry_queue", 5)]);
+        assert_eq!(report.hourly.len(), 4);
+        assert_eq!(report.hourly[0], entry(3600, "add_pr", 5));
+    }
+}
@@ -0,0 +1,164 @@
+// Authors with more open PRs than the backlog threshold: one person with six PRs sitting
+// in Waiting means reviewers are the bottleneck for them in particular. There is no digest
+// or notification sender in the tree yet, so this is only reported on request.
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::Row;
+
+use super::settings_registry::DEFAULT_AUTHOR_BACKLOG_THRESHOLD;
+use super::{display_key, sql_string_list, Database, CLOSED_STATUSES};
+
+pub const AUTHOR_BACKLOG_THRESHOLD_KEY: &str = "author_backlog_threshold";
+
+// Past this the alert would never fire for any realistic team
+const MAX_AUTHOR_BACKLOG_THRESHOLD: i64 = 1_000;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BacklogPr {
+    pub pr_id: i64,
+    pub display_key: String,
+    pub title: Option<String>,
+    pub status: String,
+    pub opened_at: i64, // GitHub creation time, else the last local update
+    pub age_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AuthorBacklogAlert {
+    pub author_id: i64,
+    pub github_username: String,
+    pub display_name: Option<String>,
+    pub open_count: usize,
+    pub prs: Vec<BacklogPr>, // oldest first
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AuthorBacklogReport {
+    pub threshold: i64,
+    pub alerts: Vec<AuthorBacklogAlert>, // most open PRs first
+}
+
+pub fn parse_author_backlog_threshold(value: &str) -> Result<i64> {
+    let threshold: i64 = value.trim().parse()
+        .map_err(|_| anyhow::anyhow!("Author backlog threshold must be a whole number"))?;
+    if !(1..=MAX_AUTHOR_BACKLOG_THRESHOLD).contains(&threshold) {
+        anyhow::bail!("Author backlog threshold must be between 1 and {}", MAX_AUTHOR_BACKLOG_THRESHOLD);
+    }
+    Ok(threshold)
+}
+
+impl Database {
+    pub async fn get_author_backlog_threshold(&self) -> Result<i64> {
+        Ok(self.get_setting(AUTHOR_BACKLOG_THRESHOLD_KEY).await?
+            .and_then(|value| parse_author_backlog_threshold(&value).ok())
+            .unwrap_or(DEFAULT_AUTHOR_BACKLOG_THRESHOLD))
+    }
+
+    /// Authors with more open PRs (not merged or archived) than `threshold`, or the
+    /// author_backlog_threshold setting when none is given
+    pub async fn get_author_backlog_alerts(&self, threshold: Option<i64>, now: i64) -> Result<AuthorBacklogReport> {
+        let threshold = match threshold {
+            Some(threshold) => parse_author_backlog_threshold(&threshold.to_string())?,
+            None => self.get_author_backlog_threshold().await?,
+        };
+        let open = format!("pr.is_issue = 0 AND pr.status NOT IN ({})", sql_string_list(&CLOSED_STATUSES));
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT pr.id, pr.pr_number, pr.title, pr.status, pr.repository_owner, pr.repository_name,
+                   COALESCE(pr.github_created_at, pr.last_updated_at) as opened_at,
+                   tm.id as author_id, tm.github_username, tm.display_name
+            FROM pull_requests pr
+            JOIN team_members tm ON tm.id = pr.author_id
+            WHERE {open} AND pr.author_id IN (
+                SELECT pr.author_id FROM pull_requests pr WHERE {open}
+                GROUP BY pr.author_id HAVING COUNT(*) > ?
+            )
+            ORDER BY tm.id, opened_at, pr.id
+            "#
+        ))
+        .bind(threshold)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut alerts: Vec<AuthorBacklogAlert> = Vec::new();
+        for row in rows {
+            let author_id: i64 = row.get("author_id");
+            if alerts.last().is_none_or(|alert| alert.author_id != author_id) {
+                alerts.push(AuthorBacklogAlert {
+                    author_id,
+                    github_username: row.get("github_username"),
+                    display_name: row.get("display_name"),
+                    open_count: 0,
+                    prs: Vec::new(),
+                });
+            }
+            let owner: Option<String> = row.get("repository_owner");
+            let repo: Option<String> = row.get("repository_name");
+            let opened_at: i64 = row.get("opened_at");
+            let alert = alerts.last_mut().expect("pushed above");
+            alert.open_count += 1;
+            alert.prs.push(BacklogPr {
+                pr_id: row.get("id"),
+                display_key: display_key(owner.as_deref(), repo.as_deref(), row.get("pr_number"), false),
+                title: row.get("title"),
+                status: row.get("status"),
+                opened_at,
+                age_days: (now - opened_at).max(0) / 86_400,
+            });
+        }
+        alerts.sort_by(|a, b| b.open_count.cmp(&a.open_count).then_with(|| a.github_username.cmp(&b.github_username)));
+
+        Ok(AuthorBacklogReport { threshold, alerts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    #[tokio::test]
+    async fn test_alerts_authors_over_the_threshold() {
+        let db = Database::new_in_memory().await.unwrap();
+        let busy = db.get_or_create_team_member("busy".to_string()).await.unwrap();
+        let calm = db.get_or_create_team_member("calm".to_string()).await.unwrap();
+        for (i, (author, status, created_days_ago)) in [
+            (busy.id, "Waiting", 3),
+            (busy.id, "Waiting", 10),
+            (busy.id, "Reviewing", 1),
+            (busy.id, "Merged", 30),
+            (calm.id, "Waiting", 2),
+            (calm.id, "archived", 5),
+        ].into_iter().enumerate() {
+            let github_id = i as i64 + 1;
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id,
+                pr_number: github_id,
+                author_id: author,
+                status: status.into(),
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some("api".to_string()),
+                github_created_at: Some(100 * 86_400 - created_days_ago * 86_400),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let report = db.get_author_backlog_alerts(Some(2), 100 * 86_400).await.unwrap();
+        assert_eq!(report.threshold, 2);
+        assert_eq!(report.alerts.len(), 1);
+        let alert = &report.alerts[0];
+        assert_eq!((alert.github_username.as_str(), alert.open_count), ("busy", 3));
+        let ages: Vec<(&str, i64)> = alert.prs.iter().map(|pr| (pr.display_key.as_str(), pr.age_days)).collect();
+        assert_eq!(ages, vec![("api#2", 10), ("api#1", 3), ("api#3", 1)]);
+
+        // Exactly at the threshold isn't over it; the setting is the fallback
+        assert!(db.get_author_backlog_alerts(Some(3), 100 * 86_400).await.unwrap().alerts.is_empty());
+        db.set_setting(AUTHOR_BACKLOG_THRESHOLD_KEY, "1").await.unwrap();
+        let report = db.get_author_backlog_alerts(None, 100 * 86_400).await.unwrap();
+        assert_eq!(report.alerts.iter().map(|a| a.open_count).collect::<Vec<_>>(), vec![3]);
+        assert!(db.get_author_backlog_alerts(Some(0), 0).await.is_err());
+        assert!(db.set_setting(AUTHOR_BACKLOG_THRESHOLD_KEY, "zero").await.is_err());
+    }
+}
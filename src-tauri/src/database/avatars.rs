@@ -0,0 +1,62 @@
+// Bookkeeping for downloaded avatars. Failed downloads back off like the retry queue
+// (retry_backoff_secs) and stop after RETRY_MAX_ATTEMPTS until the avatar URL changes.
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::Row;
+
+use super::{retry_backoff_secs, Database, RETRY_MAX_ATTEMPTS};
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AvatarCandidate {
+    pub member_id: i64,
+    pub avatar_url: String,
+}
+
+impl Database {
+    /// Members with a remote avatar but no local copy, skipping those still backing off
+    pub async fn get_members_missing_avatars(&self, now: i64) -> Result<Vec<AvatarCandidate>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, avatar_url FROM team_members
+            WHERE avatar_url IS NOT NULL AND avatar_url != '' AND avatar_path IS NULL
+              AND avatar_failures < ? AND (avatar_retry_at IS NULL OR avatar_retry_at <= ?)
+            ORDER BY id
+            "#
+        )
+        .bind(RETRY_MAX_ATTEMPTS)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|row| AvatarCandidate { member_id: row.get("id"), avatar_url: row.get("avatar_url") }).collect())
+    }
+
+    /// Store the local copy, unless the URL changed while it was downloading
+    pub async fn set_avatar_path(&self, member_id: i64, avatar_url: &str, path: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE team_members SET avatar_path = ?, avatar_failures = 0, avatar_retry_at = NULL WHERE id = ? AND avatar_url = ?"
+        )
+        .bind(path)
+        .bind(member_id)
+        .bind(avatar_url)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Count a failed download and schedule the next try; returns the failures so far
+    pub async fn record_avatar_failure(&self, member_id: i64, now: i64) -> Result<i64> {
+        let failures: i64 = sqlx::query_scalar(
+            "UPDATE team_members SET avatar_failures = avatar_failures + 1 WHERE id = ? RETURNING avatar_failures"
+        )
+        .bind(member_id)
+        .fetch_one(&self.pool)
+        .await?;
+        sqlx::query("UPDATE team_members SET avatar_retry_at = ? WHERE id = ?")
+            .bind(now + retry_backoff_secs(failures))
+            .bind(member_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(failures)
+    }
+}
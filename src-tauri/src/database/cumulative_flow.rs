@@ -0,0 +1,335 @@
+// Cumulative flow (PRs per status at the end of each day) for kanban charts. Unlike
+// daily_stats, which only knows the days the app ran, this is rebuilt from review_history:
+// each PR starts from its current status and the status-changing rows are walked backwards.
+// PRs count from their "created" row (or GitHub creation time for PRs added before history
+// was kept) and stay counted once merged or archived. Moves made before every status change
+// wrote history (bulk updates didn't at first) show up on the day of the PR's next recorded
+// change instead.
+//
+// Past days are memoized per (project, time zone, day) in cumulative_flow_days, stamped
+// with data_generation; any write to pull_requests or review_history bumps the generation
+// and invalidates them.
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::daily_stats::{day_key, MAX_STATS_DAYS};
+use super::{Database, BOARD_COLUMNS, CLOSED_STATUSES};
+use crate::display_time::local_midnight;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CumulativeFlow {
+    pub project_id: Option<i64>, // None = all projects
+    pub days: Vec<String>,       // YYYY-MM-DD in the display time zone, oldest first
+    pub statuses: Vec<String>,   // board columns, then other statuses seen, then Merged and archived
+    pub counts: Vec<Vec<i64>>,   // counts[day][status], at the end of that day (now for today)
+    pub memoized_days: usize,    // past days served from cumulative_flow_days
+}
+
+/// Statuses written with different casing or spacing over time (e.g. "waiting" before the
+/// columns were capitalized) count as the known status; anything else is kept as written
+pub fn canonical_status(status: &str) -> String {
+    let trimmed = status.trim();
+    BOARD_COLUMNS.iter()
+        .chain(CLOSED_STATUSES.iter())
+        .find(|known| known.eq_ignore_ascii_case(trimmed))
+        .map(|known| known.to_string())
+        .unwrap_or_else(|| trimmed.to_string())
+}
+
+// (from, to) for history rows that move a PR between statuses; from is None when the row
+// doesn't say (merged)
+fn status_move(action: &str, details: Option<&str>) -> Option<(Option<String>, String)> {
+    let details = details.map(canonical_status);
+    match action {
+        "merged" => Some((None, "Merged".to_string())),
+        "closed" | "archived" => Some((details, "archived".to_string())),
+        "restored" => Some((Some("archived".to_string()), details.unwrap_or_else(|| "Waiting".to_string()))),
+        other => {
+            let (from, to) = other.strip_prefix("status_changed:")?.split_once("->")?;
+            Some((Some(canonical_status(from)), canonical_status(to)))
+        }
+    }
+}
+
+// One PR's reconstructed timeline
+struct PrTimeline {
+    created_at: Option<i64>,     // None = existed before any day we can tell
+    initial: String,             // status before the first recorded move
+    changes: Vec<(i64, String)>, // (performed_at, status from then on), oldest first
+}
+
+impl PrTimeline {
+    fn status_at(&self, at: i64) -> Option<&str> {
+        if self.created_at.is_some_and(|created| created > at) {
+            return None;
+        }
+        let index = self.changes.partition_point(|(performed_at, _)| *performed_at <= at);
+        Some(match index {
+            0 => &self.initial,
+            i => &self.changes[i - 1].1,
+        })
+    }
+}
+
+// Walk a PR's moves (oldest first) backwards from its current status
+fn build_timeline(current: &str, created_at: Option<i64>, moves: &[(i64, Option<String>, String)]) -> PrTimeline {
+    // A move that doesn't name its from-status left the status the previous move set, or
+    // the column new PRs start in when it's the first move
+    let mut filled = Vec::with_capacity(moves.len());
+    let mut previous_to: Option<&String> = None;
+    for (performed_at, from, to) in moves {
+        let from = from.clone()
+            .or_else(|| previous_to.cloned())
+            .unwrap_or_else(|| BOARD_COLUMNS[0].to_string());
+        filled.push((*performed_at, from));
+        previous_to = Some(to);
+    }
+
+    let mut state = canonical_status(current);
+    let mut changes = Vec::with_capacity(filled.len());
+    for (performed_at, from) in filled.into_iter().rev() {
+        changes.push((performed_at, std::mem::replace(&mut state, from)));
+    }
+    changes.reverse();
+    PrTimeline { created_at, initial: state, changes }
+}
+
+// Column order: board columns, custom statuses alphabetically, closed statuses last
+fn ordered_statuses(seen: &BTreeSet<String>) -> Vec<String> {
+    let known = |status: &str| BOARD_COLUMNS.contains(&status) || CLOSED_STATUSES.contains(&status);
+    BOARD_COLUMNS.iter().map(|s| s.to_string())
+        .chain(seen.iter().filter(|s| !known(s)).cloned())
+        .chain(CLOSED_STATUSES.iter().map(|s| s.to_string()))
+        .collect()
+}
+
+impl Database {
+    /// Per-day, per-status PR counts for the `days` days ending at `now`'s day in `tz`
+    pub async fn get_cumulative_flow(&self, project_id: Option<i64>, days: i64, now: i64, tz: Tz) -> Result<CumulativeFlow> {
+        if !(1..=MAX_STATS_DAYS).contains(&days) {
+            return Err(anyhow::anyhow!("days must be between 1 and {}", MAX_STATS_DAYS));
+        }
+        let today = crate::display_time::local_date(now, tz);
+        let first = today.checked_sub_days(Days::new(days as u64 - 1)).unwrap_or(today);
+        let day_list: Vec<NaiveDate> = first.iter_days().take(days as usize).collect();
+        let past_keys: Vec<String> = day_list[..day_list.len() - 1].iter().map(|day| day_key(*day)).collect();
+
+        let generation = self.get_data_generation().await?;
+        let project_key = project_id.unwrap_or(0);
+        let memo_rows = sqlx::query(
+            "SELECT day, counts FROM cumulative_flow_days WHERE project_key = ? AND tz = ? AND generation = ? AND day BETWEEN ? AND ?"
+        )
+        .bind(project_key)
+        .bind(tz.name())
+        .bind(generation)
+        .bind(day_key(first))
+        .bind(day_key(today))
+        .fetch_all(&self.pool)
+        .await?;
+        let mut memo: HashMap<String, BTreeMap<String, i64>> = HashMap::new();
+        for row in &memo_rows {
+            let counts: String = row.get("counts");
+            memo.insert(row.get("day"), serde_json::from_str(&counts)?);
+        }
+
+        let prs = sqlx::query(
+            r#"
+            SELECT p.id, p.status, p.github_created_at,
+                   (SELECT MIN(h.performed_at) FROM review_history h WHERE h.pr_id = p.id AND h.action = 'created') as created_at
+            FROM pull_requests p
+            WHERE p.is_issue = 0 AND (? IS NULL OR p.project_id = ?)
+            "#
+        )
+        .bind(project_id)
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Today is the current state; past days need the history unless all of them are memoized
+        let mut by_day: Vec<BTreeMap<String, i64>> = vec![BTreeMap::new(); day_list.len()];
+        for row in &prs {
+            let status: Option<String> = row.get("status");
+            *by_day[day_list.len() - 1].entry(canonical_status(status.as_deref().unwrap_or("Waiting"))).or_default() += 1;
+        }
+        let memoized_days = past_keys.iter().filter(|key| memo.contains_key(*key)).count();
+
+        if memoized_days == past_keys.len() {
+            for (i, key) in past_keys.iter().enumerate() {
+                by_day[i] = memo.remove(key).unwrap_or_default();
+            }
+        } else {
+            let history = sqlx::query(
+                r#"
+                SELECT h.pr_id, h.action, h.details, h.performed_at
+                FROM review_history h
+                JOIN pull_requests p ON p.id = h.pr_id
+                WHERE p.is_issue = 0 AND (? IS NULL OR p.project_id = ?)
+                ORDER BY h.performed_at, h.id
+                "#
+            )
+            .bind(project_id)
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+            let mut moves: HashMap<i64, Vec<(i64, Option<String>, String)>> = HashMap::new();
+            for row in &history {
+                let action: String = row.get("action");
+                let details: Option<String> = row.get("details");
+                if let Some((from, to)) = status_move(&action, details.as_deref()) {
+                    moves.entry(row.get("pr_id")).or_default().push((row.get("performed_at"), from, to));
+                }
+            }
+
+            let timelines: Vec<PrTimeline> = prs.iter().map(|row| {
+                let id: i64 = row.get("id");
+                let status: Option<String> = row.get("status");
+                let created_at: Option<i64> = row.get::<Option<i64>, _>("created_at").or(row.get("github_created_at"));
+                build_timeline(status.as_deref().unwrap_or("Waiting"), created_at, moves.get(&id).map(Vec::as_slice).unwrap_or(&[]))
+            }).collect();
+
+            let mut tx = self.pool.begin().await?;
+            // Older generations can never be served again
+            sqlx::query("DELETE FROM cumulative_flow_days WHERE project_key = ? AND generation != ?")
+                .bind(project_key)
+                .bind(generation)
+                .execute(&mut *tx)
+                .await?;
+            for (i, day) in day_list[..day_list.len() - 1].iter().enumerate() {
+                let end_of_day = local_midnight(day.succ_opt().unwrap_or(*day), tz) - 1;
+                for timeline in &timelines {
+                    if let Some(status) = timeline.status_at(end_of_day) {
+                        *by_day[i].entry(status.to_string()).or_default() += 1;
+                    }
+                }
+                sqlx::query("INSERT OR REPLACE INTO cumulative_flow_days (project_key, tz, day, generation, counts) VALUES (?, ?, ?, ?, ?)")
+                    .bind(project_key)
+                    .bind(tz.name())
+                    .bind(day_key(*day))
+                    .bind(generation)
+                    .bind(serde_json::to_string(&by_day[i])?)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+        }
+
+        let seen: BTreeSet<String> = by_day.iter().flat_map(|counts| counts.keys().cloned()).collect();
+        let statuses = ordered_statuses(&seen);
+        let counts = by_day.iter()
+            .map(|day| statuses.iter().map(|status| day.get(status).copied().unwrap_or(0)).collect())
+            .collect();
+
+        Ok(CumulativeFlow {
+            project_id,
+            days: day_list.iter().map(|day| day_key(*day)).collect(),
+            statuses,
+            counts,
+            memoized_days: if memoized_days == past_keys.len() { memoized_days } else { 0 },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    const DAY: i64 = 86_400;
+    const D1: i64 = 1_709_251_200; // 2024-03-01 00:00 UTC
+
+    async fn history(db: &Database, pr_id: i64, action: &str, details: Option<&str>, performed_at: i64) {
+        sqlx::query("INSERT INTO review_history (pr_id, action, details, performed_at) VALUES (?, ?, ?, ?)")
+            .bind(pr_id)
+            .bind(action)
+            .bind(details)
+            .bind(performed_at)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_canonical_status() {
+        assert_eq!(canonical_status(" waiting "), "Waiting");
+        assert_eq!(canonical_status("ARCHIVED"), "archived");
+        assert_eq!(canonical_status("QA"), "QA");
+    }
+
+    #[tokio::test]
+    async fn test_five_pr_scenario() {
+        let db = Database::new_in_memory().await.unwrap();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        let other = db.add_project("Other".to_string(), None).await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+
+        // (status now, project, is_issue, created)
+        let setup = [
+            ("Approved", project.id, false, D1 - DAY),     // A
+            ("Merged", project.id, false, D1 + 12 * 3600), // B
+            ("archived", project.id, false, D1 - DAY),     // C
+            ("Reviewing", project.id, false, D1 + 2 * DAY + 11 * 3600), // D
+            ("Waiting", project.id, false, D1 - DAY),      // E
+            ("Waiting", other.id, false, D1 - DAY),        // other project
+            ("Waiting", project.id, true, D1 - DAY),       // issue
+        ];
+        let mut ids = Vec::new();
+        for (n, (status, project_id, is_issue, created)) in setup.iter().enumerate() {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: n as i64 + 1,
+                pr_number: n as i64 + 1,
+                author_id: author.id,
+                project_id: Some(*project_id),
+                status: (*status).into(),
+                is_issue: *is_issue,
+                ..Default::default()
+            }).await.unwrap();
+            sqlx::query("UPDATE review_history SET performed_at = ? WHERE pr_id = ? AND action = 'created'")
+                .bind(created)
+                .bind(pr.id)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+            ids.push(pr.id);
+        }
+        let (a, b, c, d, e) = (ids[0], ids[1], ids[2], ids[3], ids[4]);
+        history(&db, a, "status_changed:Waiting->Reviewing", None, D1 + DAY + 10 * 3600).await;
+        history(&db, a, "approved", None, D1 + 3 * DAY + 8 * 3600).await;
+        history(&db, a, "status_changed:Reviewing->Approved", None, D1 + 3 * DAY + 9 * 3600).await;
+        history(&db, b, "merged", None, D1 + 2 * DAY + 15 * 3600).await;
+        history(&db, c, "status_changed:Waiting->Action", None, D1 + 9 * 3600).await;
+        history(&db, c, "archived", Some("Action"), D1 + DAY + 8 * 3600).await;
+        // Written before the columns were capitalized
+        history(&db, d, "status_changed:waiting->reviewing", None, D1 + 3 * DAY + 12 * 3600).await;
+        history(&db, e, "closed", Some("Reviewing"), D1 + 3 * DAY + 18 * 3600).await;
+        history(&db, e, "restored", Some("Waiting"), D1 + 4 * DAY + 8 * 3600).await;
+
+        let now = D1 + 4 * DAY + 12 * 3600;
+        let flow = db.get_cumulative_flow(Some(project.id), 5, now, Tz::UTC).await.unwrap();
+        assert_eq!(flow.days, vec!["2024-03-01", "2024-03-02", "2024-03-03", "2024-03-04", "2024-03-05"]);
+        assert_eq!(flow.statuses, vec!["Waiting", "Reviewing", "Action", "Approved", "Merged", "archived"]);
+        assert_eq!(flow.counts, vec![
+            vec![2, 1, 1, 0, 0, 0],
+            vec![1, 2, 0, 0, 0, 1],
+            vec![1, 2, 0, 0, 1, 1],
+            vec![0, 1, 0, 1, 1, 2],
+            vec![1, 1, 0, 1, 1, 1],
+        ]);
+        assert_eq!(flow.memoized_days, 0);
+
+        // Served from the memo until the data changes
+        let again = db.get_cumulative_flow(Some(project.id), 5, now, Tz::UTC).await.unwrap();
+        assert_eq!((again.counts.clone(), again.memoized_days), (flow.counts.clone(), 4));
+        db.update_pr_score(&WriteContext::default(), a, 7).await.unwrap();
+        let rebuilt = db.get_cumulative_flow(Some(project.id), 5, now, Tz::UTC).await.unwrap();
+        assert_eq!((rebuilt.counts, rebuilt.memoized_days), (flow.counts, 0));
+
+        let all = db.get_cumulative_flow(None, 1, now, Tz::UTC).await.unwrap();
+        assert_eq!(all.counts, vec![vec![2, 1, 0, 1, 1, 1]]);
+        assert!(db.get_cumulative_flow(None, 0, now, Tz::UTC).await.is_err());
+    }
+}
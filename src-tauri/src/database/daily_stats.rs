@@ -0,0 +1,210 @@
+// Once-a-day snapshots of per-project PR counts for trend charts. History can't be
+// rebuilt from current state, so days the app didn't run stay missing for good.
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::{sql_string_list, Database, CLOSED_STATUSES};
+
+pub const MAX_STATS_DAYS: i64 = 366;
+
+// Metrics get_stats_timeseries accepts besides "status:<name>" (count in one status)
+pub const STATS_METRICS: [&str; 3] = ["open_count", "avg_age_seconds", "avg_score"];
+
+const BACKFILL_NOTE: &str = "Snapshots are taken once a day while the app is running; days without one are gaps and cannot be backfilled.";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatsPoint {
+    pub day: String,        // YYYY-MM-DD in the display time zone
+    pub value: Option<f64>, // None when no snapshot exists for that day (or nothing to average)
+    pub recorded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatsTimeseries {
+    pub project_id: Option<i64>, // None = all projects
+    pub metric: String,
+    pub points: Vec<StatsPoint>, // oldest first, one per day including gaps
+    pub backfill_available: bool,
+    pub note: String,
+}
+
+pub(super) fn day_key(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+impl Database {
+    /// Record today's per-project, per-status counts; false when `day` already has a snapshot
+    pub async fn snapshot_daily_stats(&self, day: NaiveDate, now: i64) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let inserted = sqlx::query("INSERT OR IGNORE INTO daily_stats_runs (day, captured_at) VALUES (?, ?)")
+            .bind(day_key(day))
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            INSERT INTO daily_stats (day, project_id, status, pr_count, avg_age_seconds, avg_score, scored_count)
+            SELECT ?, project_id, status, COUNT(*),
+                   AVG(? - COALESCE(github_created_at, last_updated_at)),
+                   AVG(score), COUNT(score)
+            FROM pull_requests
+            WHERE is_issue = 0
+            GROUP BY project_id, status
+            "#
+        )
+        .bind(day_key(day))
+        .bind(now)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        println!("📈 Recorded daily stats for {} ({} rows)", day_key(day), rows);
+        Ok(true)
+    }
+
+    /// One point per day for the `days` days ending at `today`; unrecorded days are gaps
+    pub async fn get_stats_timeseries(&self, project_id: Option<i64>, metric: &str, days: i64, today: NaiveDate) -> Result<StatsTimeseries> {
+        if !(1..=MAX_STATS_DAYS).contains(&days) {
+            return Err(anyhow::anyhow!("days must be between 1 and {}", MAX_STATS_DAYS));
+        }
+        let closed = sql_string_list(&CLOSED_STATUSES);
+        let (value_sql, status) = match metric {
+            "open_count" => (format!("SUM(CASE WHEN s.status NOT IN ({}) THEN s.pr_count ELSE 0 END)", closed), None),
+            "avg_age_seconds" => (format!(
+                "SUM(CASE WHEN s.status NOT IN ({0}) THEN s.avg_age_seconds * s.pr_count END) / SUM(CASE WHEN s.status NOT IN ({0}) THEN s.pr_count END)",
+                closed
+            ), None),
+            "avg_score" => ("SUM(s.avg_score * s.scored_count) / NULLIF(SUM(s.scored_count), 0)".to_string(), None),
+            other => match other.strip_prefix("status:") {
+                Some(status) if !status.is_empty() => (
+                    "SUM(CASE WHEN s.status = ? THEN s.pr_count ELSE 0 END)".to_string(),
+                    Some(status.to_string()),
+                ),
+                _ => return Err(anyhow::anyhow!(
+                    "Unknown metric '{}'. Valid metrics: {}, status:<name>", other, STATS_METRICS.join(", ")
+                )),
+            },
+        };
+
+        let first = today.checked_sub_days(Days::new(days as u64 - 1)).unwrap_or(today);
+        // LEFT JOIN from the runs so a recorded day with no matching PRs reads as 0, not a gap
+        let sql = format!(
+            r#"
+            SELECT r.day, CAST({} AS REAL) as value
+            FROM daily_stats_runs r
+            LEFT JOIN daily_stats s ON s.day = r.day AND (? IS NULL OR s.project_id = ?)
+            WHERE r.day BETWEEN ? AND ?
+            GROUP BY r.day
+            "#,
+            value_sql
+        );
+        let mut query = sqlx::query(&sql);
+        if let Some(status) = &status {
+            query = query.bind(status.clone());
+        }
+        let rows = query
+            .bind(project_id)
+            .bind(project_id)
+            .bind(day_key(first))
+            .bind(day_key(today))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let recorded: std::collections::HashMap<String, Option<f64>> = rows.iter()
+            .map(|row| (row.get("day"), row.get("value")))
+            .collect();
+        let points = first.iter_days()
+            .take(days as usize)
+            .map(|day| {
+                let key = day_key(day);
+                match recorded.get(&key) {
+                    // Counts over a recorded day with no rows come back NULL; that's a real zero
+                    Some(value) => StatsPoint {
+                        value: value.or((metric == "open_count" || status.is_some()).then_some(0.0)),
+                        day: key,
+                        recorded: true,
+                    },
+                    None => StatsPoint { day: key, value: None, recorded: false },
+                }
+            })
+            .collect();
+
+        Ok(StatsTimeseries {
+            project_id,
+            metric: metric.to_string(),
+            points,
+            backfill_available: false,
+            note: BACKFILL_NOTE.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_and_gaps() {
+        let db = Database::new_in_memory().await.unwrap();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let mut ids = Vec::new();
+        for (n, status) in ["Waiting", "Waiting", "Merged"].iter().enumerate() {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: n as i64 + 1,
+                pr_number: n as i64 + 1,
+                author_id: author.id,
+                project_id: Some(project.id),
+                status: (*status).into(),
+                github_created_at: Some(0),
+                ..Default::default()
+            }).await.unwrap();
+            ids.push(pr.id);
+        }
+        db.update_pr_score(&WriteContext::default(), ids[0], 8).await.unwrap();
+
+        assert!(db.snapshot_daily_stats(date("2024-03-01"), 86_400).await.unwrap());
+        assert!(!db.snapshot_daily_stats(date("2024-03-01"), 90_000).await.unwrap());
+        db.update_pr_status(&WriteContext::default(), ids[1], "Merged".to_string()).await.unwrap();
+        // 2024-03-02 missed: the app wasn't running
+        assert!(db.snapshot_daily_stats(date("2024-03-03"), 3 * 86_400).await.unwrap());
+
+        let series = db.get_stats_timeseries(Some(project.id), "open_count", 4, date("2024-03-03")).await.unwrap();
+        let values: Vec<(&str, Option<f64>)> = series.points.iter().map(|p| (p.day.as_str(), p.value)).collect();
+        assert_eq!(values, vec![
+            ("2024-02-29", None),
+            ("2024-03-01", Some(2.0)),
+            ("2024-03-02", None),
+            ("2024-03-03", Some(1.0)),
+        ]);
+        assert!(!series.backfill_available);
+
+        let age = db.get_stats_timeseries(None, "avg_age_seconds", 1, date("2024-03-03")).await.unwrap();
+        assert_eq!(age.points[0].value, Some(3.0 * 86_400.0));
+        let score = db.get_stats_timeseries(None, "avg_score", 3, date("2024-03-03")).await.unwrap();
+        assert_eq!(score.points[0].value, Some(8.0));
+        let merged = db.get_stats_timeseries(Some(project.id), "status:Merged", 1, date("2024-03-03")).await.unwrap();
+        assert_eq!(merged.points[0].value, Some(2.0));
+
+        // Another project existed that day but had no PRs: zero, not a gap
+        let empty = db.get_stats_timeseries(Some(project.id + 1), "open_count", 1, date("2024-03-03")).await.unwrap();
+        assert_eq!((empty.points[0].value, empty.points[0].recorded), (Some(0.0), true));
+
+        assert!(db.get_stats_timeseries(None, "velocity", 7, date("2024-03-03")).await.is_err());
+        assert!(db.get_stats_timeseries(None, "open_count", 0, date("2024-03-03")).await.is_err());
+    }
+}
@@ -0,0 +1,95 @@
+// Short "repo#512" identifiers for PRs. pr_number alone is ambiguous once several repos
+// are tracked, and the owner is only added when the same repo name shows up under more
+// than one owner in the result set being displayed.
+use std::collections::{HashMap, HashSet};
+
+use super::PullRequest;
+
+/// Key for a single PR, optionally qualified with the owner
+pub fn display_key(owner: Option<&str>, repo: Option<&str>, number: i64, with_owner: bool) -> String {
+    match (owner, repo) {
+        (Some(owner), Some(repo)) if with_owner => format!("{}/{}#{}", owner, repo, number),
+        (_, Some(repo)) => format!("{}#{}", repo, number),
+        _ => format!("#{}", number),
+    }
+}
+
+/// Keys for (owner, repo, number) triples, in order. GitHub names are case-insensitive,
+/// so "Acme/api" and "acme/api" count as the same repo.
+pub fn display_keys(prs: &[(Option<&str>, Option<&str>, i64)]) -> Vec<String> {
+    let mut owners_by_repo: HashMap<String, HashSet<Option<String>>> = HashMap::new();
+    for (owner, repo, _) in prs {
+        if let Some(repo) = repo {
+            owners_by_repo.entry(repo.to_lowercase())
+                .or_default()
+                .insert(owner.map(str::to_lowercase));
+        }
+    }
+
+    prs.iter()
+        .map(|(owner, repo, number)| {
+            let ambiguous = repo.is_some_and(|repo| owners_by_repo[&repo.to_lowercase()].len() > 1);
+            display_key(*owner, *repo, *number, ambiguous)
+        })
+        .collect()
+}
+
+/// Recompute display_key across a result set
+pub(crate) fn assign_display_keys<'a>(prs: impl IntoIterator<Item = &'a mut PullRequest>) {
+    let mut prs: Vec<&mut PullRequest> = prs.into_iter().collect();
+    let keys = display_keys(&prs.iter()
+        .map(|pr| (pr.repository_owner.as_deref(), pr.repository_name.as_deref(), pr.pr_number))
+        .collect::<Vec<_>>());
+    for (pr, key) in prs.iter_mut().zip(keys) {
+        pr.display_key = key;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::{Database, NewPullRequest};
+
+    #[test]
+    fn test_owner_added_only_when_repo_name_is_ambiguous() {
+        let keys = display_keys(&[
+            (Some("acme"), Some("api"), 512),
+            (Some("globex"), Some("api"), 512),
+            (Some("acme"), Some("web"), 7),
+            (Some("ACME"), Some("Web"), 8),
+            (None, None, 3),
+        ]);
+        assert_eq!(keys, vec!["acme/api#512", "globex/api#512", "web#7", "Web#8", "#3"]);
+
+        // The same PR reads unqualified when the other owner's repo isn't in the set
+        assert_eq!(display_keys(&[(Some("acme"), Some("api"), 512)]), vec!["api#512"]);
+        // A missing owner still differs from a known one
+        assert_eq!(display_keys(&[(Some("acme"), Some("api"), 1), (None, Some("api"), 2)]), vec!["acme/api#1", "api#2"]);
+    }
+
+    #[tokio::test]
+    async fn test_pr_lists_disambiguate_same_name_repos() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        for (github_id, owner, repo) in [(1, "acme", "api"), (2, "globex", "api"), (3, "acme", "web")] {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id,
+                pr_number: 512,
+                author_id: author.id,
+                status: PrStatus::Waiting,
+                repository_owner: Some(owner.to_string()),
+                repository_name: Some(repo.to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let mut keys: Vec<String> = db.get_pull_requests().await.unwrap().into_iter().map(|pr| pr.display_key).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["acme/api#512", "globex/api#512", "web#512"]);
+        // On its own a PR has nothing to be confused with
+        let single = db.get_pull_request_by_github_id(2).await.unwrap().unwrap();
+        assert_eq!(single.display_key, "api#512");
+    }
+}
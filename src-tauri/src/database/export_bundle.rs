@@ -0,0 +1,231 @@
+// Data exports as files with a companion manifest, for copying between machines. Next to
+// <file> goes <file>.manifest.json with the format version, row counts per table and the
+// SHA-256 of the data file. verify_export checks all three without touching the database,
+// and import_data_file refuses a bundle that doesn't verify before reading anything in.
+// The checksum is computed over the file in fixed-size chunks, so large exports are never
+// held in memory just to be hashed.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::transfer::{DataExport, DataImportResult, DATA_EXPORT_VERSION};
+use super::Database;
+
+pub const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+const HASH_CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportManifest {
+    pub format_version: i64,
+    pub data_file: String, // file name only, so the pair can be moved together
+    pub exported_at: i64,
+    pub bytes: u64,
+    pub sha256: String, // lowercase hex
+    pub row_counts: BTreeMap<String, u64>,
+}
+
+/// `<data file>.manifest.json`
+pub fn manifest_path(data_path: &Path) -> PathBuf {
+    let mut name = data_path.file_name().unwrap_or_default().to_os_string();
+    name.push(MANIFEST_SUFFIX);
+    data_path.with_file_name(name)
+}
+
+/// SHA-256 (lowercase hex) and size of a file, read a chunk at a time
+pub fn sha256_file(path: &Path) -> Result<(String, u64)> {
+    let mut file = File::open(path).map_err(|e| anyhow::anyhow!("Cannot read {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_CHUNK_BYTES];
+    let mut bytes = 0u64;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        bytes += read as u64;
+    }
+    let hex = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok((hex, bytes))
+}
+
+// Keyed by the table each exported row comes from
+fn row_counts(export: &DataExport) -> BTreeMap<String, u64> {
+    BTreeMap::from([
+        ("projects".to_string(), export.projects.len() as u64),
+        ("project_members".to_string(), export.projects.iter().map(|p| p.members.len() as u64).sum()),
+        ("pull_requests".to_string(), export.pull_requests.len() as u64),
+        ("pr_links_external".to_string(), export.pull_requests.iter().map(|pr| pr.external_links.len() as u64).sum()),
+    ])
+}
+
+fn read_manifest(data_path: &Path) -> Result<ExportManifest> {
+    let path = manifest_path(data_path);
+    let file = File::open(&path)
+        .map_err(|e| anyhow::anyhow!("No manifest for {}: cannot read {} ({})", data_path.display(), path.display(), e))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| anyhow::anyhow!("Invalid manifest {}: {}", path.display(), e))
+}
+
+// Checksum first, so a damaged file is reported as such rather than as bad JSON
+fn verify_bundle(data_path: &Path) -> Result<(ExportManifest, DataExport)> {
+    let manifest = read_manifest(data_path)?;
+    if manifest.format_version != DATA_EXPORT_VERSION {
+        return Err(anyhow::anyhow!(
+            "Unsupported export format version: expected {}, actual {}", DATA_EXPORT_VERSION, manifest.format_version
+        ));
+    }
+
+    let (sha256, bytes) = sha256_file(data_path)?;
+    if bytes != manifest.bytes {
+        return Err(anyhow::anyhow!(
+            "Size mismatch for {}: expected {} bytes, actual {}", data_path.display(), manifest.bytes, bytes
+        ));
+    }
+    if sha256 != manifest.sha256 {
+        return Err(anyhow::anyhow!(
+            "Checksum mismatch for {}: expected {}, actual {}", data_path.display(), manifest.sha256, sha256
+        ));
+    }
+
+    let export: DataExport = serde_json::from_reader(BufReader::new(File::open(data_path)?))
+        .map_err(|e| anyhow::anyhow!("Invalid data export JSON in {}: {}", data_path.display(), e))?;
+    let actual = row_counts(&export);
+    for table in manifest.row_counts.keys().chain(actual.keys()) {
+        let (expected, found) = (manifest.row_counts.get(table).copied().unwrap_or(0), actual.get(table).copied().unwrap_or(0));
+        if expected != found {
+            return Err(anyhow::anyhow!("Row count mismatch for {}: expected {}, actual {}", table, expected, found));
+        }
+    }
+    Ok((manifest, export))
+}
+
+/// Check a data file against its manifest without importing it
+pub fn verify_export(data_path: &Path) -> Result<ExportManifest> {
+    verify_bundle(data_path).map(|(manifest, _)| manifest)
+}
+
+impl Database {
+    /// Write the data export to `path` and its manifest next to it
+    pub async fn export_data_file(&self, path: &Path) -> Result<ExportManifest> {
+        let export = self.build_data_export().await?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, &export)?;
+        writer.flush()?;
+        drop(writer);
+
+        // Hashed from disk, so the manifest describes the bytes that were actually written
+        let (sha256, bytes) = sha256_file(path)?;
+        let manifest = ExportManifest {
+            format_version: export.version,
+            data_file: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            exported_at: export.exported_at,
+            bytes,
+            sha256,
+            row_counts: row_counts(&export),
+        };
+        std::fs::write(manifest_path(path), serde_json::to_string_pretty(&manifest)?)?;
+
+        println!("📦 Exported {} PRs to {} ({} bytes)", export.pull_requests.len(), path.display(), bytes);
+        Ok(manifest)
+    }
+
+    /// Import a data file once it verifies against its manifest; on a mismatch nothing is read in
+    pub async fn import_data_file(&self, path: &Path) -> Result<DataImportResult> {
+        let (_, export) = verify_bundle(path)?;
+        self.import_data_export(export).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+
+    async fn seeded() -> Database {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        db.add_project_member(project.id, author.id).await.unwrap();
+        for number in 1..=3 {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                author_id: author.id,
+                project_id: Some(project.id),
+                status: PrStatus::Waiting,
+                ..Default::default()
+            }).await.unwrap();
+            db.add_pr_external_link(pr.id, "Staging", "https://staging.example.com").await.unwrap();
+        }
+        db
+    }
+
+    #[tokio::test]
+    async fn test_export_bundle_round_trip_and_mismatches() {
+        let dir = std::env::temp_dir().join(format!("pr-tracker-export-bundle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("export.json");
+
+        let manifest = seeded().await.export_data_file(&path).await.unwrap();
+        assert_eq!(manifest_path(&path), dir.join("export.json.manifest.json"));
+        assert_eq!(manifest.data_file, "export.json");
+        assert_eq!(manifest.row_counts, BTreeMap::from([
+            ("pr_links_external".to_string(), 3),
+            ("project_members".to_string(), 1),
+            ("projects".to_string(), 1),
+            ("pull_requests".to_string(), 3),
+        ]));
+        assert_eq!(verify_export(&path).unwrap(), manifest);
+        let imported = Database::new_in_memory().await.unwrap().import_data_file(&path).await.unwrap();
+        assert_eq!(imported.prs_imported, 3);
+
+        // One flipped byte: the checksum says so, and the import refuses before reading anything in
+        let original = std::fs::read(&path).unwrap();
+        let mut damaged = original.clone();
+        let at = damaged.iter().position(|b| *b == b'W').unwrap(); // "Waiting"
+        damaged[at] = b'w';
+        std::fs::write(&path, &damaged).unwrap();
+        let (actual, _) = sha256_file(&path).unwrap();
+        let err = verify_export(&path).unwrap_err();
+        assert_eq!(err.to_string(), format!("Checksum mismatch for {}: expected {}, actual {}", path.display(), manifest.sha256, actual));
+        let target = Database::new_in_memory().await.unwrap();
+        assert!(target.import_data_file(&path).await.is_err());
+        assert!(target.get_pull_requests().await.unwrap().is_empty());
+
+        // A file rewritten with a matching checksum but missing a PR still fails on the counts
+        let mut export: DataExport = serde_json::from_slice(&original).unwrap();
+        export.pull_requests.pop();
+        std::fs::write(&path, serde_json::to_vec_pretty(&export).unwrap()).unwrap();
+        let (sha256, bytes) = sha256_file(&path).unwrap();
+        let forged = ExportManifest { sha256, bytes, ..manifest.clone() };
+        std::fs::write(manifest_path(&path), serde_json::to_string(&forged).unwrap()).unwrap();
+        assert_eq!(verify_export(&path).unwrap_err().to_string(), "Row count mismatch for pr_links_external: expected 3, actual 2");
+
+        std::fs::remove_file(manifest_path(&path)).unwrap();
+        assert!(verify_export(&path).unwrap_err().to_string().starts_with("No manifest for"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sha256_streams_across_chunks() {
+        let path = std::env::temp_dir().join(format!("pr-tracker-sha256-{}", std::process::id()));
+        std::fs::write(&path, "abc").unwrap();
+        assert_eq!(sha256_file(&path).unwrap(), ("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string(), 3));
+
+        // Larger than one chunk: the same digest as hashing it in one go
+        let data: Vec<u8> = (0..HASH_CHUNK_BYTES * 2 + 17).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        let whole: String = Sha256::digest(&data).iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(sha256_file(&path).unwrap(), (whole, data.len() as u64));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+// Submitted GitHub reviews, as fetched on add and refresh. Each one is logged to
+// review_history once (keyed by GitHub's review id), which is what first-review times and the
+// review SLA read. Reviews by tracked team members also land in pr_reviewers; reviewers who
+// aren't team members are left out there, as they are from reviewer_comment_stats.
+// A newly seen CHANGES_REQUESTED review that is the PR's latest verdict and was made against
+// its current head commit makes it the author's move (see waiting_on.rs).
+use anyhow::Result;
+
+use super::waiting_on::{self, WaitingOn};
+use super::Database;
+
+// One submitted review (pending and dismissed ones are filtered out by the caller)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmittedReview {
+    pub review_id: i64, // GitHub's review id
+    pub reviewer: String, // login
+    pub state: String,    // APPROVED | CHANGES_REQUESTED | COMMENTED
+    pub submitted_at: i64,
+    pub commit_id: Option<String>,
+}
+
+// review_history action for a review state (one of REVIEW_ACTIONS)
+fn review_action(state: &str) -> Option<&'static str> {
+    match state {
+        "APPROVED" => Some("approved"),
+        "CHANGES_REQUESTED" => Some("changes_requested"),
+        "COMMENTED" => Some("commented"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordedReviews {
+    pub logged: usize,                 // reviews not seen before
+    pub waiting_on: Option<WaitingOn>, // set when a change request flipped it
+}
+
+impl Database {
+    /// Record a PR's submitted reviews (oldest first, as GitHub lists them); the author's own
+    /// reviews are skipped. Call after record_pr_head_sha so the head commit is current.
+    pub async fn record_github_reviews(&self, pr_id: i64, reviews: &[SubmittedReview]) -> Result<RecordedReviews> {
+        let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT tm.github_username, pr.head_sha FROM pull_requests pr LEFT JOIN team_members tm ON tm.id = pr.author_id WHERE pr.id = ?"
+        )
+        .bind(pr_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let (author, head_sha) = row.ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))?;
+        let members = self.team_member_ids_by_login().await?;
+
+        let mut recorded = RecordedReviews::default();
+        // Latest approval or change request, and whether it is new
+        let mut verdict: Option<(&SubmittedReview, bool)> = None;
+        let mut tx = self.pool.begin().await?;
+        for review in reviews {
+            if author.as_deref().is_some_and(|login| login.eq_ignore_ascii_case(&review.reviewer)) {
+                continue;
+            }
+            let Some(action) = review_action(&review.state) else { continue };
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO review_history (pr_id, action, performed_at, source, details, github_review_id)
+                 VALUES (?, ?, ?, 'github', ?, ?)"
+            )
+            .bind(pr_id)
+            .bind(action)
+            .bind(review.submitted_at)
+            .bind(&review.reviewer)
+            .bind(review.review_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() > 0;
+            if inserted {
+                recorded.logged += 1;
+            }
+            if action != "commented" && verdict.is_none_or(|(latest, _)| latest.submitted_at <= review.submitted_at) {
+                verdict = Some((review, inserted));
+            }
+
+            let Some(member_id) = members.get(&review.reviewer.to_lowercase()) else { continue };
+            sqlx::query(
+                "INSERT INTO pr_reviewers (pr_id, team_member_id, reviewed_at) VALUES (?, ?, ?)
+                 ON CONFLICT(pr_id, team_member_id) DO UPDATE SET reviewed_at = MAX(reviewed_at, excluded.reviewed_at)"
+            )
+            .bind(pr_id)
+            .bind(member_id)
+            .bind(review.submitted_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Only a new request counts: an old one was either acted on already or overridden by
+        // hand, and one against an older commit was answered by the push since
+        if let Some((review, true)) = verdict {
+            let current_head = review.commit_id.is_none() || review.commit_id == head_sha;
+            if review.state == "CHANGES_REQUESTED" && current_head {
+                recorded.waiting_on = waiting_on::changes_requested(&mut tx, pr_id, &review.reviewer, review.submitted_at).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(recorded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+
+    fn review(review_id: i64, reviewer: &str, state: &str, submitted_at: i64) -> SubmittedReview {
+        SubmittedReview {
+            review_id,
+            reviewer: reviewer.to_string(),
+            state: state.to_string(),
+            submitted_at,
+            commit_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_github_reviews_logs_each_review_once() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let alice = db.get_or_create_team_member("Alice".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            status: PrStatus::Reviewing,
+            ..Default::default()
+        }).await.unwrap();
+
+        let reviews = [
+            review(1, "alice", "COMMENTED", 100),
+            review(2, "outsider", "APPROVED", 150),
+            review(3, "octocat", "COMMENTED", 160),
+            review(4, "alice", "APPROVED", 200),
+        ];
+        assert_eq!(db.record_github_reviews(pr.id, &reviews).await.unwrap().logged, 3);
+        // A later refresh sees the same reviews again and logs nothing new
+        assert_eq!(db.record_github_reviews(pr.id, &reviews).await.unwrap().logged, 0);
+
+        let reviewers = db.get_pr_reviewers(pr.id).await.unwrap();
+        assert_eq!(reviewers.len(), 1);
+        assert_eq!((reviewers[0].team_member_id, reviewers[0].reviewed_at), (alice.id, 200));
+
+        // Outside reviewers still count as reviews
+        let logged: Vec<(String, i64, Option<String>, Option<String>)> = db.get_pr_history(pr.id).await.unwrap().into_iter()
+            .filter(|entry| entry.action != "created")
+            .map(|entry| (entry.action, entry.performed_at, entry.source, entry.details))
+            .collect();
+        assert_eq!(logged, vec![
+            ("commented".to_string(), 100, Some("github".to_string()), Some("alice".to_string())),
+            ("approved".to_string(), 150, Some("github".to_string()), Some("outsider".to_string())),
+            ("approved".to_string(), 200, Some("github".to_string()), Some("alice".to_string())),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_new_change_request_on_the_head_commit_is_the_authors_move() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            status: PrStatus::Reviewing,
+            ..Default::default()
+        }).await.unwrap();
+        db.record_pr_head_sha(pr.id, "aaaaaaa").await.unwrap();
+        let on_commit = |review_id: i64, state: &str, submitted_at: i64, sha: &str| SubmittedReview {
+            commit_id: Some(sha.to_string()),
+            ..review(review_id, "alice", state, submitted_at)
+        };
+
+        // A change request later approved doesn't count
+        let approved = [on_commit(1, "CHANGES_REQUESTED", 100, "aaaaaaa"), on_commit(2, "APPROVED", 200, "aaaaaaa")];
+        assert_eq!(db.record_github_reviews(pr.id, &approved).await.unwrap().waiting_on, None);
+
+        // Nor does one against a commit the author has pushed over since
+        let stale = [on_commit(3, "CHANGES_REQUESTED", 300, "0000000")];
+        assert_eq!(db.record_github_reviews(pr.id, &stale).await.unwrap().waiting_on, None);
+
+        let requested = [on_commit(4, "CHANGES_REQUESTED", 400, "aaaaaaa"), on_commit(5, "COMMENTED", 500, "aaaaaaa")];
+        assert_eq!(db.record_github_reviews(pr.id, &requested).await.unwrap().waiting_on, Some(WaitingOn::Author));
+        let history = db.get_pr_history(pr.id).await.unwrap();
+        let flip = history.iter().find(|entry| entry.action == "waiting_on_changed:reviewer->author").unwrap();
+        assert_eq!((flip.details.as_deref(), flip.performed_at), (Some("changes requested by alice"), 400));
+
+        // The author pushes; seeing the same request again doesn't take it back
+        db.record_pr_head_sha(pr.id, "bbbbbbb").await.unwrap();
+        assert_eq!(db.record_github_reviews(pr.id, &requested).await.unwrap().waiting_on, None);
+        let pr_now = db.get_pull_request_by_id(pr.id).await.unwrap().unwrap();
+        assert_eq!((pr_now.status.as_str(), pr_now.waiting_on), ("Reviewing", WaitingOn::Reviewer));
+    }
+}
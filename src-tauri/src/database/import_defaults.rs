@@ -0,0 +1,179 @@
+// How PRs entering the tracker are set up: the column open PRs start in, and what happens
+// to the score of PRs that were already merged when added or imported. Those were never
+// reviewed here, so leaving them unscored would drag score coverage down.
+use std::fmt;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::settings_registry::DEFAULT_IMPORT_OPEN_STATUS;
+use super::status::PrStatus;
+use super::{Database, SCORE_RANGE};
+
+pub const IMPORT_OPEN_STATUS_KEY: &str = "import_open_status";
+pub const MERGED_IMPORT_SCORING_KEY: &str = "merged_import_scoring";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum MergedImportScoring {
+    #[default]
+    Unscored,                // score stays empty and counts against coverage
+    Exclude,                 // exclude_from_scoring is set, so coverage ignores the PR
+    Sentinel { score: i32 }, // a fixed score is filled in
+}
+
+impl MergedImportScoring {
+    /// Setting value: "unscored", "exclude" or "sentinel:<1-10>"
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim() {
+            "unscored" => Ok(MergedImportScoring::Unscored),
+            "exclude" => Ok(MergedImportScoring::Exclude),
+            other => {
+                let score = other.strip_prefix("sentinel:")
+                    .and_then(|score| score.trim().parse::<i32>().ok())
+                    .filter(|score| SCORE_RANGE.contains(score))
+                    .ok_or_else(|| anyhow::anyhow!(
+                        "Merged import scoring must be 'unscored', 'exclude' or 'sentinel:<1-10>', got '{}'", other
+                    ))?;
+                Ok(MergedImportScoring::Sentinel { score })
+            }
+        }
+    }
+}
+
+impl fmt::Display for MergedImportScoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergedImportScoring::Unscored => write!(f, "unscored"),
+            MergedImportScoring::Exclude => write!(f, "exclude"),
+            MergedImportScoring::Sentinel { score } => write!(f, "sentinel:{}", score),
+        }
+    }
+}
+
+/// New open PRs can start in any known column except a closed one
+pub fn validate_import_open_status(value: &str) -> Result<PrStatus> {
+    match PrStatus::parse_input(value)? {
+        status @ (PrStatus::Merged | PrStatus::Archived) => {
+            Err(anyhow::anyhow!("Open PRs cannot be imported as '{}'", status))
+        }
+        status => Ok(status),
+    }
+}
+
+impl Database {
+    /// Column newly added open PRs start in (import_open_status, default Waiting)
+    pub async fn get_import_open_status(&self) -> Result<PrStatus> {
+        Ok(self.get_setting(IMPORT_OPEN_STATUS_KEY).await?
+            .and_then(|status| validate_import_open_status(&status).ok())
+            .unwrap_or_else(|| PrStatus::from(DEFAULT_IMPORT_OPEN_STATUS)))
+    }
+
+    pub async fn get_merged_import_scoring(&self) -> Result<MergedImportScoring> {
+        match self.get_setting(MERGED_IMPORT_SCORING_KEY).await? {
+            Some(value) => MergedImportScoring::parse(&value),
+            None => Ok(MergedImportScoring::Unscored),
+        }
+    }
+
+    /// Apply the configured policy to a PR that was already merged when it arrived.
+    /// A score that is already set is left alone.
+    pub async fn apply_merged_import_scoring(&self, pr_id: i64) -> Result<MergedImportScoring> {
+        let policy = self.get_merged_import_scoring().await?;
+        match policy {
+            MergedImportScoring::Unscored => {}
+            MergedImportScoring::Exclude => {
+                sqlx::query("UPDATE pull_requests SET exclude_from_scoring = 1 WHERE id = ? AND score IS NULL")
+                    .bind(pr_id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            MergedImportScoring::Sentinel { score } => {
+                sqlx::query("UPDATE pull_requests SET score = ? WHERE id = ? AND score IS NULL")
+                    .bind(score)
+                    .bind(pr_id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    #[test]
+    fn test_parse_merged_import_scoring() {
+        assert_eq!(MergedImportScoring::parse("unscored").unwrap(), MergedImportScoring::Unscored);
+        assert_eq!(MergedImportScoring::parse(" exclude ").unwrap(), MergedImportScoring::Exclude);
+        assert_eq!(MergedImportScoring::parse("sentinel:5").unwrap(), MergedImportScoring::Sentinel { score: 5 });
+        for bad in ["sentinel:0", "sentinel:11", "sentinel:", "skip", ""] {
+            assert!(MergedImportScoring::parse(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(MergedImportScoring::Sentinel { score: 7 }.to_string(), "sentinel:7");
+        for bad in ["Merged", "merged", "ARCHIVED", "Reviwing", "  "] {
+            assert!(validate_import_open_status(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(validate_import_open_status(" reviewing ").unwrap(), PrStatus::Reviewing);
+    }
+
+    // Every policy against coverage: two merged-on-arrival PRs (one already scored) plus one reviewed here
+    #[tokio::test]
+    async fn test_policies_and_score_coverage() {
+        let cases = [
+            ("unscored", (3, 2), None),
+            ("exclude", (2, 2), None),
+            ("sentinel:6", (3, 3), Some(6)),
+        ];
+        for (setting, (merged, scored), backfilled_score) in cases {
+            let db = Database::new_in_memory().await.unwrap();
+            db.set_setting(MERGED_IMPORT_SCORING_KEY, setting).await.unwrap();
+            let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+            let mut ids = Vec::new();
+            for n in 1..=3 {
+                let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                    github_id: n,
+                    pr_number: n,
+                    author_id: author.id,
+                    status: PrStatus::Merged,
+                    ..Default::default()
+                }).await.unwrap();
+                ids.push(pr.id);
+            }
+            db.update_pr_score(&WriteContext::default(), ids[1], 9).await.unwrap();
+            db.update_pr_score(&WriteContext::default(), ids[2], 8).await.unwrap();
+            db.apply_merged_import_scoring(ids[0]).await.unwrap();
+            db.apply_merged_import_scoring(ids[1]).await.unwrap();
+
+            let coverage = db.get_review_coverage(None, None).await.unwrap();
+            assert_eq!((coverage.merged_count, coverage.scored_count), (merged, scored), "{}", setting);
+            let backfilled = db.get_pull_request_by_id(ids[0]).await.unwrap().unwrap();
+            assert_eq!(backfilled.score, backfilled_score, "{}", setting);
+            assert_eq!(backfilled.exclude_from_scoring, setting == "exclude");
+            // The already-scored PR keeps its score and stays counted under every policy
+            let scored_pr = db.get_pull_request_by_id(ids[1]).await.unwrap().unwrap();
+            assert_eq!((scored_pr.score, scored_pr.exclude_from_scoring), (Some(9), false));
+
+            // Scoring an excluded PR by hand brings it back into coverage
+            db.update_pr_score(&WriteContext::default(), ids[0], 4).await.unwrap();
+            let coverage = db.get_review_coverage(None, None).await.unwrap();
+            assert_eq!((coverage.merged_count, coverage.scored_count), (3, 3), "{}", setting);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_open_status_setting() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_import_open_status().await.unwrap(), PrStatus::Waiting);
+        db.set_setting(IMPORT_OPEN_STATUS_KEY, "reviewing").await.unwrap();
+        assert_eq!(db.get_import_open_status().await.unwrap(), PrStatus::Reviewing);
+        for bad in ["archived", "merged", "Reviwing"] {
+            assert!(db.set_setting(IMPORT_OPEN_STATUS_KEY, bad).await.is_err(), "{}", bad);
+        }
+        assert!(db.set_setting(MERGED_IMPORT_SCORING_KEY, "sentinel:50").await.is_err());
+    }
+}
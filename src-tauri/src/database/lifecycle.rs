@@ -0,0 +1,276 @@
+// PR lifecycle events in review_history. Every mutation that moves a PR into, out of or
+// back into the working set records one row through record_lifecycle_event, so the
+// activity feed has no gaps. Purges (bulk_delete_by_filter, clear_project_data) are the
+// exception: they remove the PR's history along with the PR. A PR closed without merging
+// is archived, so restore_archived_pr brings it back like a bulk-archived one. Moving a PR
+// to Merged or archived by hand (update_pr_status, bulk_update_pr_status) is logged as the
+// lifecycle event too, with source "user", rather than as a status_changed row.
+//
+// Vocabulary (action / source / details):
+//   created          user           how it was filed, when that needs saying (a project override, a reimport)
+//   converted_to_pr  github         "owner/repo#N" the tracked issue turned into
+//   merged           github | user  none
+//   closed           github         status the PR had when GitHub reported it closed unmerged
+//   archived         bulk | user    status the PR had before it was archived
+//   restored         user           status the PR was restored to
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqliteConnection};
+
+use super::actor::WriteContext;
+use super::status::PrStatus;
+use super::waiting_on::follow_status;
+use super::{Database, PullRequest};
+
+// Restoring a PR whose archive entry predates details falls back to this column
+const DEFAULT_RESTORE_STATUS: &str = "Waiting";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    Created,
+    ConvertedToPr,
+    Merged,
+    Closed,
+    Archived,
+    Restored,
+}
+
+impl LifecycleEvent {
+    pub fn action(self) -> &'static str {
+        match self {
+            LifecycleEvent::Created => "created",
+            LifecycleEvent::ConvertedToPr => "converted_to_pr",
+            LifecycleEvent::Merged => "merged",
+            LifecycleEvent::Closed => "closed",
+            LifecycleEvent::Archived => "archived",
+            LifecycleEvent::Restored => "restored",
+        }
+    }
+
+    /// Where the change comes from, unless it was a manual status move (see record_status_move)
+    pub fn source(self) -> &'static str {
+        match self {
+            LifecycleEvent::ConvertedToPr | LifecycleEvent::Merged | LifecycleEvent::Closed => "github",
+            LifecycleEvent::Archived => "bulk",
+            LifecycleEvent::Created | LifecycleEvent::Restored => "user",
+        }
+    }
+}
+
+// One review_history row; source/details are NULL on review actions and older rows
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub pr_id: i64,
+    pub action: String,
+    pub performed_at: i64,
+    pub source: Option<String>,
+    pub details: Option<String>,
+    pub actor: Option<String>, // who did it locally (see actor.rs); None for GitHub's changes
+}
+
+async fn insert_history(
+    conn: &mut SqliteConnection,
+    pr_id: i64,
+    action: &str,
+    source: Option<&str>,
+    details: Option<&str>,
+    actor: Option<&str>,
+    performed_at: i64,
+) -> Result<()> {
+    sqlx::query("INSERT INTO review_history (pr_id, action, performed_at, source, details, actor) VALUES (?, ?, ?, ?, ?, ?)")
+        .bind(pr_id)
+        .bind(action)
+        .bind(performed_at)
+        .bind(source)
+        .bind(details)
+        .bind(actor)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Write a lifecycle event inside the caller's transaction
+pub(crate) async fn record_lifecycle_event(
+    conn: &mut SqliteConnection,
+    pr_id: i64,
+    event: LifecycleEvent,
+    details: Option<&str>,
+    actor: Option<&str>,
+    performed_at: i64,
+) -> Result<()> {
+    insert_history(conn, pr_id, event.action(), Some(event.source()), details, actor, performed_at).await
+}
+
+/// Log a manual move from `from` to `to` inside the caller's transaction: a merged or
+/// archived event when it closes the PR, so restore_archived_pr and merge counts see it,
+/// otherwise status_changed:<from>-><to>
+pub(crate) async fn record_status_move(
+    conn: &mut SqliteConnection,
+    pr_id: i64,
+    from: &str,
+    to: &str,
+    actor: &str,
+    performed_at: i64,
+) -> Result<()> {
+    let (action, source, details) = match PrStatus::from(to) {
+        PrStatus::Merged => (LifecycleEvent::Merged.action().to_string(), Some("user"), None),
+        PrStatus::Archived => (LifecycleEvent::Archived.action().to_string(), Some("user"), Some(from)),
+        _ => (format!("status_changed:{}->{}", from, to), None, None),
+    };
+    insert_history(conn, pr_id, &action, source, details, Some(actor), performed_at).await
+}
+
+impl Database {
+    /// A PR's history, oldest first
+    pub async fn get_pr_history(&self, pr_id: i64) -> Result<Vec<HistoryEntry>> {
+        let rows = sqlx::query(
+            "SELECT id, pr_id, action, performed_at, source, details, actor FROM review_history WHERE pr_id = ? ORDER BY performed_at, id"
+        )
+        .bind(pr_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|row| HistoryEntry {
+            id: row.get("id"),
+            pr_id: row.get("pr_id"),
+            action: row.get("action"),
+            performed_at: row.get("performed_at"),
+            source: row.get("source"),
+            details: row.get("details"),
+            actor: row.get("actor"),
+        }).collect())
+    }
+
+    /// Put an archived PR back in the column it was archived from
+    pub async fn restore_archived_pr(&self, ctx: &WriteContext, pr_id: i64) -> Result<PullRequest> {
+        let mut tx = self.pool.begin().await?;
+        let status: Option<String> = sqlx::query_scalar("SELECT status FROM pull_requests WHERE id = ?")
+            .bind(pr_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        match status.as_deref() {
+            None => return Err(anyhow::anyhow!("Pull request {} not found", pr_id)),
+            Some("archived") => {}
+            Some(other) => return Err(anyhow::anyhow!("Pull request {} is not archived (status: {})", pr_id, other)),
+        }
+
+        let previous: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT details FROM review_history WHERE pr_id = ? AND action IN ('archived', 'closed') ORDER BY performed_at DESC, id DESC LIMIT 1"
+        )
+        .bind(pr_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let restored = previous.flatten()
+            .filter(|status| status != "archived")
+            .unwrap_or_else(|| DEFAULT_RESTORE_STATUS.to_string());
+
+        sqlx::query("UPDATE pull_requests SET status = ? WHERE id = ?")
+            .bind(&restored)
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+        let now = chrono::Utc::now().timestamp();
+        record_lifecycle_event(&mut tx, pr_id, LifecycleEvent::Restored, Some(&restored), Some(&ctx.actor), now).await?;
+        follow_status(&mut tx, pr_id, &format!("restored to {}", restored), Some(&ctx.actor), now).await?;
+        tx.commit().await?;
+
+        self.get_pull_request_by_id(pr_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::query::FilterSpec;
+    use crate::database::{NewPullRequest, CONFIRM_BULK_DELETE};
+
+    fn rows(history: &[HistoryEntry]) -> Vec<(&str, Option<&str>, Option<&str>)> {
+        history.iter()
+            .map(|entry| (entry.action.as_str(), entry.source.as_deref(), entry.details.as_deref()))
+            .collect()
+    }
+
+    // Each lifecycle command on one fixture PR writes exactly its own row, in order
+    #[tokio::test]
+    async fn test_lifecycle_history_is_consistent() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let issue = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: -7,
+            pr_number: 7,
+            author_id: author.id,
+            status: PrStatus::Reviewing,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            is_issue: true,
+            ..Default::default()
+        }).await.unwrap();
+
+        let pr = db.convert_issue_to_pr(issue.id, &NewPullRequest {
+            github_id: 70,
+            pr_number: 12,
+            author_id: author.id,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        assert!(db.restore_archived_pr(&WriteContext::default(), pr.id).await.is_err());
+
+        let filter = FilterSpec { statuses: vec!["Reviewing".to_string()], ..Default::default() };
+        db.bulk_archive_by_filter(&WriteContext::default(), &filter).await.unwrap();
+        let restored = db.restore_archived_pr(&WriteContext::default(), pr.id).await.unwrap();
+        assert_eq!(restored.status, "Reviewing");
+        db.mark_pr_merged(pr.id, Some(4_000_000_000)).await.unwrap();
+
+        assert_eq!(rows(&db.get_pr_history(pr.id).await.unwrap()), vec![
+            ("created", Some("user"), None),
+            ("converted_to_pr", Some("github"), Some("acme/api#12")),
+            ("archived", Some("bulk"), Some("Reviewing")),
+            ("waiting_on_changed:reviewer->nobody", None, Some("archived")),
+            ("restored", Some("user"), Some("Reviewing")),
+            ("waiting_on_changed:nobody->reviewer", None, Some("restored to Reviewing")),
+            ("merged", Some("github"), None),
+            ("waiting_on_changed:reviewer->nobody", None, Some("merged on GitHub")),
+        ]);
+
+        // Purging is the one lifecycle step that removes history instead of adding to it
+        let nonce = db.issue_confirmation_nonce(CONFIRM_BULK_DELETE).await.unwrap();
+        let filter = FilterSpec { statuses: vec!["Merged".to_string()], ..Default::default() };
+        db.bulk_delete_by_filter(&filter, &nonce).await.unwrap();
+        assert!(db.get_pr_history(pr.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_manual_moves_into_closed_statuses_are_lifecycle_events() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let mut ids = Vec::new();
+        for number in [1, 2] {
+            ids.push(db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                author_id: author.id,
+                status: PrStatus::Approved,
+                ..Default::default()
+            }).await.unwrap().id);
+        }
+
+        // Archived from the board, then restored to the column it left
+        db.update_pr_status(&WriteContext::new("ana"), ids[0], "archived".to_string()).await.unwrap();
+        assert_eq!(db.restore_archived_pr(&WriteContext::new("ana"), ids[0]).await.unwrap().status, "Approved");
+        db.bulk_update_pr_status(&WriteContext::new("ana"), &[ids[1]], "Merged").await.unwrap();
+
+        for (pr_id, expected) in [
+            (ids[0], vec![("archived", Some("user"), Some("Approved")), ("restored", Some("user"), Some("Approved"))]),
+            (ids[1], vec![("merged", Some("user"), None)]),
+        ] {
+            let history: Vec<HistoryEntry> = db.get_pr_history(pr_id).await.unwrap().into_iter()
+                .filter(|entry| entry.source.is_some() && entry.action != "created")
+                .collect();
+            assert_eq!(rows(&history), expected);
+            assert!(history.iter().all(|entry| entry.actor.as_deref() == Some("ana")));
+        }
+    }
+}
@@ -0,0 +1,806 @@
+// Versioned schema migrations, tracked with PRAGMA user_version and logged to migration_log
+use std::time::Instant;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::Database;
+
+// (version, name) in the order they run. Never renumber; add new steps at the end.
+const MIGRATIONS: [(i64, &str); 29] = [
+    (1, "baseline_columns"),     // the column checks databases used to run on every start
+    (2, "default_local_profile"),
+    (3, "pull_requests_is_issue"),
+    (4, "data_generation_triggers"),
+    (5, "pr_reviewers"),
+    (6, "scheduled_tasks"),
+    (7, "pr_links_external"),
+    (8, "daily_stats"),
+    (9, "pull_requests_exclude_from_scoring"),
+    (10, "review_history_source_details"),
+    (11, "api_usage"),
+    (12, "reviewer_comment_stats"),
+    (13, "sync_runs"),
+    (14, "pull_requests_release_tracking"),
+    (15, "team_members_avatar_cache"),
+    (16, "pull_requests_row_versions"),
+    (17, "sync_runs_skipped_reason"),
+    (18, "sync_conflicts"),
+    (19, "pr_search_index"),
+    (20, "pr_links_blocking"),
+    (21, "project_members"),
+    (22, "actor_attribution"),
+    (23, "repository_project_mapping"),
+    (24, "cumulative_flow_days"),
+    (25, "pull_requests_size"),
+    (26, "pull_requests_waiting_on"),
+    (27, "review_history_generation_triggers"),
+    (28, "review_history_github_review_id"),
+    (29, "pr_comments_added_by"),
+];
+
+// Tables whose rows show up in the PR list; any write to them bumps data_generation.
+// Tables added later (pr_reviewers) get their triggers in their own step, as does
+// review_history, which the list's time-in-status and cumulative flow are read from.
+const PR_LIST_TABLES: [&str; 5] = ["pull_requests", "team_members", "projects", "pr_profile_state", "local_profiles"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MigrationLogEntry {
+    pub id: i64,
+    pub version: i64,
+    pub name: String,
+    pub applied_at: i64,
+    pub duration_ms: i64,
+    pub rows_affected: Option<i64>, // None when the step can't tell
+    pub status: String,             // "applied" | "failed"
+    pub error: Option<String>,
+}
+
+/// A migration step failed; returned from startup so users can report exactly where it broke
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MigrationError {
+    pub version: i64,
+    pub name: String,
+    pub error: String,
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Migration v{} ({}) failed: {}", self.version, self.name, self.error)
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+pub fn latest_schema_version() -> i64 {
+    MIGRATIONS.last().map(|(version, _)| *version).unwrap_or(0)
+}
+
+impl Database {
+    pub async fn get_schema_version(&self) -> Result<i64> {
+        Ok(sqlx::query_scalar("PRAGMA user_version").fetch_one(&self.pool).await?)
+    }
+
+    // Steps may be re-run after a failure, so column additions check first
+    async fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let exists: bool = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?",
+            table
+        ))
+        .bind(column)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !exists {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                .execute(&self.pool)
+                .await?;
+            println!("✅ Added {} column to {} table", column, table);
+        }
+        Ok(())
+    }
+
+    // A counter maintained by SQLite itself, so caches notice writes from any code path
+    async fn create_data_generation_triggers(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS data_generation (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                generation INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("INSERT OR IGNORE INTO data_generation (id, generation) VALUES (1, 0)")
+            .execute(&self.pool)
+            .await?;
+
+        for table in PR_LIST_TABLES {
+            self.create_generation_triggers(table).await?;
+        }
+        Ok(())
+    }
+
+    async fn create_generation_triggers(&self, table: &str) -> Result<()> {
+        for operation in ["INSERT", "UPDATE", "DELETE"] {
+            sqlx::query(&format!(
+                r#"
+                CREATE TRIGGER IF NOT EXISTS bump_generation_{table}_{op}
+                AFTER {operation} ON {table}
+                BEGIN
+                    UPDATE data_generation SET generation = generation + 1 WHERE id = 1;
+                END
+                "#,
+                table = table,
+                op = operation.to_lowercase(),
+                operation = operation,
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    // Who reviewed each PR; reviewer_count in the PR list comes from here
+    async fn create_pr_reviewers(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS pr_reviewers (
+                pr_id INTEGER NOT NULL,
+                team_member_id INTEGER NOT NULL,
+                reviewed_at INTEGER NOT NULL,
+                PRIMARY KEY (pr_id, team_member_id),
+                FOREIGN KEY (pr_id) REFERENCES pull_requests(id),
+                FOREIGN KEY (team_member_id) REFERENCES team_members(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        self.create_generation_triggers("pr_reviewers").await
+    }
+
+    async fn create_scheduled_tasks(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS scheduled_tasks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                pr_id INTEGER NOT NULL,
+                task_type TEXT NOT NULL,
+                run_at INTEGER NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                created_at INTEGER NOT NULL,
+                completed_at INTEGER,
+                last_error TEXT,
+                FOREIGN KEY (pr_id) REFERENCES pull_requests(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_scheduled_tasks_due ON scheduled_tasks(status, run_at)")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn create_pr_links_external(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS pr_links_external (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                pr_id INTEGER NOT NULL,
+                label TEXT NOT NULL,
+                url TEXT NOT NULL,
+                position REAL NOT NULL,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY (pr_id) REFERENCES pull_requests(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_pr_links_external_pr ON pr_links_external(pr_id, position)")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn add_link_blocking_columns(&self) -> Result<()> {
+        self.add_column_if_missing("pr_links_external", "blocking", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("pr_links_external", "resolved", "INTEGER NOT NULL DEFAULT 0").await
+    }
+
+    // daily_stats_runs marks which days have a snapshot, so a day with no PRs still reads as zero
+    async fn create_daily_stats(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS daily_stats_runs (
+                day TEXT PRIMARY KEY,
+                captured_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS daily_stats (
+                day TEXT NOT NULL,
+                project_id INTEGER,
+                status TEXT NOT NULL,
+                pr_count INTEGER NOT NULL,
+                avg_age_seconds REAL,
+                avg_score REAL,
+                scored_count INTEGER NOT NULL,
+                FOREIGN KEY (day) REFERENCES daily_stats_runs(day)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_daily_stats_day ON daily_stats(day, project_id)")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // Lifecycle events (see lifecycle.rs) say where they came from and carry a detail value
+    async fn add_review_history_source_details(&self) -> Result<()> {
+        self.add_column_if_missing("review_history", "source", "TEXT").await?;
+        self.add_column_if_missing("review_history", "details", "TEXT").await
+    }
+
+    // GitHub calls per feature per hour (see github/api_usage.rs)
+    async fn create_api_usage(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS api_usage (
+                hour_start INTEGER NOT NULL,
+                feature TEXT NOT NULL,
+                calls INTEGER NOT NULL,
+                PRIMARY KEY (hour_start, feature)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Review comment counts per member, week and repository. Aggregate-only by design:
+    // there is deliberately no column for comment text, URLs or ids (see review_comments.rs).
+    async fn create_reviewer_comment_stats(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS reviewer_comment_stats (
+                team_member_id INTEGER NOT NULL,
+                week_start INTEGER NOT NULL,
+                repository_owner TEXT NOT NULL,
+                repository_name TEXT NOT NULL,
+                comment_count INTEGER NOT NULL CHECK (comment_count > 0),
+                synced_at INTEGER NOT NULL,
+                PRIMARY KEY (team_member_id, week_start, repository_owner, repository_name),
+                FOREIGN KEY (team_member_id) REFERENCES team_members(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // One row per background sync run (see sync_runs.rs); errors and changes are JSON arrays
+    async fn create_sync_runs(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sync_runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                finished_at INTEGER NOT NULL,
+                refreshed INTEGER NOT NULL DEFAULT 0,
+                changed INTEGER NOT NULL DEFAULT 0,
+                merged INTEGER NOT NULL DEFAULT 0,
+                failed INTEGER NOT NULL DEFAULT 0,
+                errors TEXT NOT NULL DEFAULT '[]',
+                changes TEXT NOT NULL DEFAULT '[]'
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_sync_runs_finished_at ON sync_runs(finished_at)")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // Which release shipped a merged PR (see releases.rs)
+    async fn add_release_tracking_columns(&self) -> Result<()> {
+        self.add_column_if_missing("pull_requests", "merge_commit_sha", "TEXT").await?;
+        self.add_column_if_missing("pull_requests", "shipped_in_release", "TEXT").await
+    }
+
+    // Left NULL on existing rows until the next refresh reports them
+    async fn add_pr_size_columns(&self) -> Result<()> {
+        self.add_column_if_missing("pull_requests", "additions", "INTEGER").await?;
+        self.add_column_if_missing("pull_requests", "deletions", "INTEGER").await?;
+        self.add_column_if_missing("pull_requests", "changed_files", "INTEGER").await
+    }
+
+    // Whose move each PR is (database/waiting_on.rs), backfilled from the status. head_sha is
+    // the commit the last refresh saw; new commits are noticed from the next refresh on.
+    async fn add_waiting_on_columns(&self) -> Result<u64> {
+        self.add_column_if_missing("pull_requests", "waiting_on", "TEXT NOT NULL DEFAULT 'reviewer'").await?;
+        self.add_column_if_missing("pull_requests", "head_sha", "TEXT").await?;
+        let result = sqlx::query(
+            r#"
+            UPDATE pull_requests
+            SET waiting_on = CASE WHEN lower(status) = 'action' THEN 'author' ELSE 'nobody' END
+            WHERE lower(status) IN ('action', 'approved', 'merged', 'archived')
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    // GitHub review behind an approved/changes_requested/commented row, so seeing the same
+    // review on every refresh logs it once (database/github_reviews.rs)
+    async fn add_review_history_github_review_id(&self) -> Result<()> {
+        self.add_column_if_missing("review_history", "github_review_id", "INTEGER").await?;
+        sqlx::query(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_review_history_github_review
+             ON review_history(pr_id, github_review_id) WHERE github_review_id IS NOT NULL"
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Local copy of the avatar, plus failed downloads and when the next try is allowed
+    async fn add_avatar_cache_columns(&self) -> Result<()> {
+        self.add_column_if_missing("team_members", "avatar_path", "TEXT").await?;
+        self.add_column_if_missing("team_members", "avatar_failures", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("team_members", "avatar_retry_at", "INTEGER").await
+    }
+
+    // Which app version inserted and last wrote each PR. Triggers stamp the version held in
+    // app_version (set on every open), so no write path can skip it; existing rows stay NULL.
+    async fn create_row_version_tracking(&self) -> Result<()> {
+        self.add_column_if_missing("pull_requests", "created_by_version", "TEXT").await?;
+        self.add_column_if_missing("pull_requests", "updated_by_version", "TEXT").await?;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS app_version (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                version TEXT NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS stamp_version_pull_requests_insert
+            AFTER INSERT ON pull_requests
+            BEGIN
+                UPDATE pull_requests SET
+                    created_by_version = (SELECT version FROM app_version WHERE id = 1),
+                    updated_by_version = (SELECT version FROM app_version WHERE id = 1)
+                WHERE id = NEW.id;
+            END
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        // Only fires while the stamp is out of date, so its own update doesn't loop
+        sqlx::query(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS stamp_version_pull_requests_update
+            AFTER UPDATE ON pull_requests
+            WHEN NEW.updated_by_version IS NOT (SELECT version FROM app_version WHERE id = 1)
+            BEGIN
+                UPDATE pull_requests SET updated_by_version = (SELECT version FROM app_version WHERE id = 1)
+                WHERE id = NEW.id;
+            END
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Fields a human edited (JSON array, NULL when none) and, under the "prompt" sync policy,
+    // GitHub values held back because they disagree with such an edit (see sync_conflicts.rs)
+    async fn create_sync_conflicts(&self) -> Result<()> {
+        self.add_column_if_missing("pull_requests", "locally_edited", "TEXT").await?;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sync_conflicts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                pr_id INTEGER NOT NULL,
+                field TEXT NOT NULL,
+                local_value TEXT,
+                github_value TEXT NOT NULL,
+                detected_at INTEGER NOT NULL,
+                UNIQUE(pr_id, field),
+                FOREIGN KEY (pr_id) REFERENCES pull_requests(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Who added a PR and who made each history change locally (database/actor.rs)
+    async fn add_actor_columns(&self) -> Result<()> {
+        self.add_column_if_missing("pull_requests", "added_by", "TEXT").await?;
+        self.add_column_if_missing("review_history", "actor", "TEXT").await
+    }
+
+    // Per-project rosters (database/project_members.rs)
+    async fn create_project_members(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS project_members (
+                project_id INTEGER NOT NULL,
+                team_member_id INTEGER NOT NULL,
+                added_at INTEGER NOT NULL,
+                PRIMARY KEY (project_id, team_member_id),
+                FOREIGN KEY (project_id) REFERENCES projects(id),
+                FOREIGN KEY (team_member_id) REFERENCES team_members(id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Memoized cumulative flow days (database/cumulative_flow.rs); project_key 0 = all projects
+    async fn create_cumulative_flow_days(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS cumulative_flow_days (
+                project_key INTEGER NOT NULL,
+                tz TEXT NOT NULL,
+                day TEXT NOT NULL,
+                generation INTEGER NOT NULL,
+                counts TEXT NOT NULL,
+                PRIMARY KEY (project_key, tz, day)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // FTS5 index behind search_pull_requests (database/search.rs), one row per PR keyed by
+    // its id. Triggers keep it in step with titles, branches and notes; the step backfills
+    // existing PRs and returns how many it indexed.
+    async fn create_pr_search_index(&self) -> Result<u64> {
+        sqlx::query("CREATE VIRTUAL TABLE IF NOT EXISTS pr_search USING fts5(title, branch, notes, tokenize = 'porter unicode61')")
+            .execute(&self.pool)
+            .await?;
+
+        let notes = |pr_id: &str| format!("(SELECT group_concat(body, char(10)) FROM pr_comments WHERE pr_id = {})", pr_id);
+        let triggers = [
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS pr_search_pull_requests_insert AFTER INSERT ON pull_requests BEGIN
+                    INSERT INTO pr_search (rowid, title, branch, notes) VALUES (NEW.id, NEW.title, NEW.branch, {});
+                END",
+                notes("NEW.id"),
+            ),
+            "CREATE TRIGGER IF NOT EXISTS pr_search_pull_requests_update AFTER UPDATE OF title, branch ON pull_requests BEGIN
+                UPDATE pr_search SET title = NEW.title, branch = NEW.branch WHERE rowid = NEW.id;
+            END".to_string(),
+            "CREATE TRIGGER IF NOT EXISTS pr_search_pull_requests_delete AFTER DELETE ON pull_requests BEGIN
+                DELETE FROM pr_search WHERE rowid = OLD.id;
+            END".to_string(),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS pr_search_pr_comments_insert AFTER INSERT ON pr_comments BEGIN
+                    UPDATE pr_search SET notes = {} WHERE rowid = NEW.pr_id;
+                END",
+                notes("NEW.pr_id"),
+            ),
+            // Reimports move comments to the new row, so both PRs are reindexed
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS pr_search_pr_comments_update AFTER UPDATE ON pr_comments BEGIN
+                    UPDATE pr_search SET notes = {} WHERE rowid = OLD.pr_id;
+                    UPDATE pr_search SET notes = {} WHERE rowid = NEW.pr_id;
+                END",
+                notes("OLD.pr_id"),
+                notes("NEW.pr_id"),
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS pr_search_pr_comments_delete AFTER DELETE ON pr_comments BEGIN
+                    UPDATE pr_search SET notes = {} WHERE rowid = OLD.pr_id;
+                END",
+                notes("OLD.pr_id"),
+            ),
+        ];
+        for trigger in &triggers {
+            sqlx::query(trigger).execute(&self.pool).await?;
+        }
+
+        sqlx::query("DELETE FROM pr_search").execute(&self.pool).await?;
+        let backfilled = sqlx::query(&format!(
+            "INSERT INTO pr_search (rowid, title, branch, notes) SELECT pr.id, pr.title, pr.branch, {} FROM pull_requests pr",
+            notes("pr.id"),
+        ))
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(backfilled)
+    }
+
+    /// The version the stamping triggers write; set to CARGO_PKG_VERSION whenever the database opens
+    pub async fn record_app_version(&self, version: &str) -> Result<()> {
+        sqlx::query("INSERT INTO app_version (id, version) VALUES (1, ?) ON CONFLICT(id) DO UPDATE SET version = excluded.version")
+            .bind(version)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Changes whenever a row behind the PR list is written (see PR_LIST_TABLES)
+    pub async fn get_data_generation(&self) -> Result<i64> {
+        Ok(sqlx::query_scalar("SELECT generation FROM data_generation WHERE id = 1")
+            .fetch_one(&self.pool)
+            .await?)
+    }
+
+    // Run one step, returning the rows it changed when known
+    async fn apply_migration(&self, version: i64) -> Result<Option<u64>> {
+        match version {
+            1 => self.migrate_database().await.map(|_| None),
+            2 => self.ensure_default_profile().await.map(Some),
+            3 => self.add_column_if_missing("pull_requests", "is_issue", "INTEGER NOT NULL DEFAULT 0").await.map(|_| None),
+            4 => self.create_data_generation_triggers().await.map(|_| None),
+            5 => self.create_pr_reviewers().await.map(|_| None),
+            6 => self.create_scheduled_tasks().await.map(|_| None),
+            7 => self.create_pr_links_external().await.map(|_| None),
+            8 => self.create_daily_stats().await.map(|_| None),
+            9 => self.add_column_if_missing("pull_requests", "exclude_from_scoring", "INTEGER NOT NULL DEFAULT 0").await.map(|_| None),
+            10 => self.add_review_history_source_details().await.map(|_| None),
+            11 => self.create_api_usage().await.map(|_| None),
+            12 => self.create_reviewer_comment_stats().await.map(|_| None),
+            13 => self.create_sync_runs().await.map(|_| None),
+            14 => self.add_release_tracking_columns().await.map(|_| None),
+            15 => self.add_avatar_cache_columns().await.map(|_| None),
+            16 => self.create_row_version_tracking().await.map(|_| None),
+            17 => self.add_column_if_missing("sync_runs", "skipped_reason", "TEXT").await.map(|_| None),
+            18 => self.create_sync_conflicts().await.map(|_| None),
+            19 => self.create_pr_search_index().await.map(Some),
+            20 => self.add_link_blocking_columns().await.map(|_| None),
+            21 => self.create_project_members().await.map(|_| None),
+            22 => self.add_actor_columns().await.map(|_| None),
+            // Deleting a project drops the mappings to it (database/repo_mappings.rs)
+            23 => self.add_column_if_missing("repositories", "project_id", "INTEGER REFERENCES projects(id) ON DELETE SET NULL").await.map(|_| None),
+            24 => self.create_cumulative_flow_days().await.map(|_| None),
+            25 => self.add_pr_size_columns().await.map(|_| None),
+            26 => self.add_waiting_on_columns().await.map(Some),
+            27 => self.create_generation_triggers("review_history").await.map(|_| None),
+            28 => self.add_review_history_github_review_id().await.map(|_| None),
+            // Who wrote a local comment, like pull_requests.added_by (database/actor.rs)
+            29 => self.add_column_if_missing("pr_comments", "added_by", "TEXT").await.map(|_| None),
+            other => Err(anyhow::anyhow!("No migration registered for v{}", other)),
+        }
+    }
+
+    /// Apply every step newer than the database's schema version, logging each one.
+    /// Stops at the first failure with a MigrationError (also written to the log).
+    pub(super) async fn run_migrations(&self) -> Result<()> {
+        let current = self.get_schema_version().await?;
+
+        for (version, name) in MIGRATIONS.iter().filter(|(version, _)| *version > current) {
+            let started = Instant::now();
+            let outcome = self.apply_migration(*version).await;
+            let duration_ms = started.elapsed().as_millis() as i64;
+
+            let (rows_affected, error) = match &outcome {
+                Ok(rows) => (rows.map(|r| r as i64), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            sqlx::query(
+                r#"
+                INSERT INTO migration_log (version, name, applied_at, duration_ms, rows_affected, status, error)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(version)
+            .bind(name)
+            .bind(chrono::Utc::now().timestamp())
+            .bind(duration_ms)
+            .bind(rows_affected)
+            .bind(if error.is_some() { "failed" } else { "applied" })
+            .bind(&error)
+            .execute(&self.pool)
+            .await?;
+
+            if let Some(error) = error {
+                println!("❌ Migration v{} ({}) failed: {}", version, name, error);
+                return Err(MigrationError { version: *version, name: name.to_string(), error }.into());
+            }
+
+            // PRAGMA values can't be bound
+            sqlx::query(&format!("PRAGMA user_version = {}", version))
+                .execute(&self.pool)
+                .await?;
+            println!("✅ Applied migration v{} ({}) in {} ms", version, name, duration_ms);
+        }
+
+        Ok(())
+    }
+
+    /// Every migration attempt, oldest first
+    pub async fn get_migration_history(&self) -> Result<Vec<MigrationLogEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, version, name, applied_at, duration_ms, rows_affected, status, error
+            FROM migration_log ORDER BY id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|row| MigrationLogEntry {
+            id: row.get("id"),
+            version: row.get("version"),
+            name: row.get("name"),
+            applied_at: row.get("applied_at"),
+            duration_ms: row.get("duration_ms"),
+            rows_affected: row.get("rows_affected"),
+            status: row.get("status"),
+            error: row.get("error"),
+        }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+
+    #[tokio::test]
+    async fn test_fresh_database_logs_every_migration_once() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_schema_version().await.unwrap(), latest_schema_version());
+
+        let history = db.get_migration_history().await.unwrap();
+        let applied: Vec<(i64, &str, &str)> = history.iter()
+            .map(|e| (e.version, e.name.as_str(), e.status.as_str()))
+            .collect();
+        assert_eq!(applied, vec![
+            (1, "baseline_columns", "applied"),
+            (2, "default_local_profile", "applied"),
+            (3, "pull_requests_is_issue", "applied"),
+            (4, "data_generation_triggers", "applied"),
+            (5, "pr_reviewers", "applied"),
+            (6, "scheduled_tasks", "applied"),
+            (7, "pr_links_external", "applied"),
+            (8, "daily_stats", "applied"),
+            (9, "pull_requests_exclude_from_scoring", "applied"),
+            (10, "review_history_source_details", "applied"),
+            (11, "api_usage", "applied"),
+            (12, "reviewer_comment_stats", "applied"),
+            (13, "sync_runs", "applied"),
+            (14, "pull_requests_release_tracking", "applied"),
+            (15, "team_members_avatar_cache", "applied"),
+            (16, "pull_requests_row_versions", "applied"),
+            (17, "sync_runs_skipped_reason", "applied"),
+            (18, "sync_conflicts", "applied"),
+            (19, "pr_search_index", "applied"),
+            (20, "pr_links_blocking", "applied"),
+            (21, "project_members", "applied"),
+            (22, "actor_attribution", "applied"),
+            (23, "repository_project_mapping", "applied"),
+            (24, "cumulative_flow_days", "applied"),
+            (25, "pull_requests_size", "applied"),
+            (26, "pull_requests_waiting_on", "applied"),
+            (27, "review_history_generation_triggers", "applied"),
+            (28, "review_history_github_review_id", "applied"),
+            (29, "pr_comments_added_by", "applied"),
+        ]);
+        assert_eq!(history[0].rows_affected, None);
+        assert_eq!(history[1].rows_affected, Some(0));
+
+        // Nothing left to run on the next start
+        db.run_migrations().await.unwrap();
+        assert_eq!(db.get_migration_history().await.unwrap().len(), MIGRATIONS.len());
+    }
+
+    #[tokio::test]
+    async fn test_failed_migration_is_logged_and_reported() {
+        let db = Database::new_in_memory().await.unwrap();
+
+        // Re-run v2 against a broken schema
+        sqlx::query("PRAGMA user_version = 1").execute(&db.pool).await.unwrap();
+        sqlx::query("DELETE FROM local_profiles").execute(&db.pool).await.unwrap();
+        sqlx::query("DROP TABLE pr_profile_state").execute(&db.pool).await.unwrap();
+
+        let error = db.run_migrations().await.unwrap_err();
+        let failure = error.downcast_ref::<MigrationError>().expect("structured migration error");
+        assert_eq!((failure.version, failure.name.as_str()), (2, "default_local_profile"));
+        assert!(error.to_string().starts_with("Migration v2 (default_local_profile) failed:"));
+        assert_eq!(db.get_schema_version().await.unwrap(), 1);
+
+        let last = db.get_migration_history().await.unwrap().pop().unwrap();
+        assert_eq!((last.version, last.status.as_str()), (2, "failed"));
+        assert!(last.error.unwrap().contains("pr_profile_state"));
+    }
+
+    #[tokio::test]
+    async fn test_status_update_stamps_updated_by_version_only() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr_id = db.add_pull_request(&WriteContext::default(), crate::database::NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            ..Default::default()
+        }).await.unwrap().id;
+        let current = Some(env!("CARGO_PKG_VERSION").to_string());
+        assert_eq!(db.get_pr_row_versions(pr_id).await.unwrap(), (current.clone(), current.clone()));
+
+        // The next release opens the same database
+        db.record_app_version("99.0.0").await.unwrap();
+        db.update_pr_status(&WriteContext::default(), pr_id, "Reviewing".to_string()).await.unwrap();
+        assert_eq!(db.get_pr_row_versions(pr_id).await.unwrap(), (current, Some("99.0.0".to_string())));
+
+        let detail = db.get_pr_detail(pr_id).await.unwrap().unwrap();
+        assert_eq!(detail.updated_by_version.as_deref(), Some("99.0.0"));
+    }
+
+    #[tokio::test]
+    async fn test_history_only_writes_bump_data_generation() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr_id = db.add_pull_request(&WriteContext::default(), crate::database::NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            ..Default::default()
+        }).await.unwrap().id;
+
+        let before = db.get_data_generation().await.unwrap();
+        sqlx::query("INSERT INTO review_history (pr_id, action, performed_at) VALUES (?, 'status_changed:Waiting->Reviewing', 0)")
+            .bind(pr_id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(db.get_data_generation().await.unwrap(), before + 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_index_backfills_existing_prs() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr_id = db.add_pull_request(&WriteContext::default(), crate::database::NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            title: Some("Tidy the retry queue".to_string()),
+            author_id: author.id,
+            ..Default::default()
+        }).await.unwrap().id;
+        db.add_pr_comment(&WriteContext::default(), pr_id, author.id, "backoff looks off by one".to_string()).await.unwrap();
+
+        // A database from before v19
+        sqlx::query("DROP TABLE pr_search").execute(&db.pool).await.unwrap();
+        sqlx::query("PRAGMA user_version = 18").execute(&db.pool).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let history = db.get_migration_history().await.unwrap();
+        let search_index = history.iter().rfind(|entry| entry.version == 19).unwrap();
+        assert_eq!(search_index.rows_affected, Some(1));
+        for query in ["retry", "backoff"] {
+            assert_eq!(db.search_pull_requests(query).await.unwrap().len(), 1, "{}", query);
+        }
+    }
+}
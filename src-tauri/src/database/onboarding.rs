@@ -0,0 +1,165 @@
+// What the first-run screen needs: a few counts, whether the user dismissed onboarding, and
+// the outcome of the last token check. Everything here is read locally, so the screen can
+// ask on every launch without touching GitHub.
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::settings_registry::DEFAULT_PROJECT_KEY;
+use super::{Database, Project};
+
+// Internal keys, written directly rather than through set_setting
+pub const ONBOARDING_COMPLETE_KEY: &str = "onboarding_completed_at";
+pub const LAST_TOKEN_CHECK_KEY: &str = "last_token_check";
+
+/// Outcome of the latest verify_github_token / test_github_connection call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TokenCheck {
+    pub valid: bool,
+    pub login: Option<String>,
+    pub checked_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct OnboardingCounts {
+    pub project_count: i64,
+    pub pr_count: i64,           // tracked PRs, not issues
+    pub tracked_repo_count: i64, // registered repositories plus any only known from PRs
+}
+
+impl Database {
+    pub async fn record_token_check(&self, valid: bool, login: Option<&str>, checked_at: i64) -> Result<()> {
+        let check = TokenCheck { valid, login: login.map(str::to_string), checked_at };
+        self.write_setting(LAST_TOKEN_CHECK_KEY, &serde_json::to_string(&check)?).await
+    }
+
+    /// None until a token has been checked; an unreadable value counts as never checked
+    pub async fn get_last_token_check(&self) -> Result<Option<TokenCheck>> {
+        Ok(self.get_setting(LAST_TOKEN_CHECK_KEY).await?
+            .and_then(|value| serde_json::from_str(&value).ok()))
+    }
+
+    pub async fn is_onboarding_complete(&self) -> Result<bool> {
+        Ok(self.get_setting(ONBOARDING_COMPLETE_KEY).await?.is_some())
+    }
+
+    pub async fn mark_onboarding_complete(&self, now: i64) -> Result<()> {
+        self.write_setting(ONBOARDING_COMPLETE_KEY, &now.to_string()).await
+    }
+
+    /// One read-only pass; unlike get_tracked_repositories it doesn't register repositories
+    pub async fn get_onboarding_counts(&self) -> Result<OnboardingCounts> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM projects) AS project_count,
+                (SELECT COUNT(*) FROM pull_requests WHERE is_issue = 0) AS pr_count,
+                (SELECT COUNT(*) FROM (
+                    SELECT owner, name FROM repositories
+                    UNION
+                    SELECT repository_owner, repository_name FROM pull_requests
+                    WHERE repository_owner IS NOT NULL AND repository_owner != ''
+                      AND repository_name IS NOT NULL AND repository_name != ''
+                )) AS tracked_repo_count
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(OnboardingCounts {
+            project_count: row.get("project_count"),
+            pr_count: row.get("pr_count"),
+            tracked_repo_count: row.get("tracked_repo_count"),
+        })
+    }
+
+    /// Create a project and, when given, register owner/repo as tracked, in one transaction.
+    /// The first project also becomes the default for imports when none is set.
+    pub async fn create_starter_project(&self, name: &str, repository: Option<(&str, &str)>, now: i64) -> Result<Project> {
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("Project name is required");
+        }
+        let repository = repository.map(|(owner, repo)| (owner.trim(), repo.trim()));
+        if repository.is_some_and(|(owner, repo)| owner.is_empty() || repo.is_empty()) {
+            bail!("Owner and repository are both required to track a repository");
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let id: i64 = sqlx::query_scalar("INSERT INTO projects (name, description, created_at) VALUES (?, NULL, ?) RETURNING id")
+            .bind(name)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+        if let Some((owner, repo)) = repository {
+            sqlx::query("INSERT OR IGNORE INTO repositories (owner, name) VALUES (?, ?)")
+                .bind(owner)
+                .bind(repo)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES (?, ?, ?)")
+            .bind(DEFAULT_PROJECT_KEY)
+            .bind(id.to_string())
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(Project {
+            id,
+            name: name.to_string(),
+            description: None,
+            created_at: now,
+            sla_hours: None,
+            target_score: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    #[tokio::test]
+    async fn test_starter_project_and_counts() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_onboarding_counts().await.unwrap(), OnboardingCounts::default());
+        assert!(!db.is_onboarding_complete().await.unwrap());
+        assert_eq!(db.get_last_token_check().await.unwrap(), None);
+
+        assert!(db.create_starter_project("  ", None, 100).await.is_err());
+        assert!(db.create_starter_project("API", Some(("acme", "")), 100).await.is_err());
+        let project = db.create_starter_project(" API ", Some(("acme", "api")), 100).await.unwrap();
+        assert_eq!(project.name, "API");
+        assert_eq!(db.get_default_project().await.unwrap().map(|p| p.id), Some(project.id));
+
+        // The second project doesn't take over as default
+        db.create_starter_project("Web", None, 200).await.unwrap();
+        assert_eq!(db.get_default_project().await.unwrap().map(|p| p.id), Some(project.id));
+
+        // acme/api is both registered and in a PR; acme/web is only in a PR
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        for (number, repo, is_issue) in [(1, "api", false), (2, "web", false), (3, "web", true)] {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                author_id: author.id,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some(repo.to_string()),
+                is_issue,
+                ..Default::default()
+            }).await.unwrap();
+        }
+        let counts = db.get_onboarding_counts().await.unwrap();
+        assert_eq!(counts, OnboardingCounts { project_count: 2, pr_count: 2, tracked_repo_count: 2 });
+
+        db.record_token_check(true, Some("octocat"), 300).await.unwrap();
+        let check = db.get_last_token_check().await.unwrap().unwrap();
+        assert_eq!((check.valid, check.login.as_deref(), check.checked_at), (true, Some("octocat"), 300));
+        db.mark_onboarding_complete(400).await.unwrap();
+        assert!(db.is_onboarding_complete().await.unwrap());
+    }
+}
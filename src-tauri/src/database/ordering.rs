@@ -0,0 +1,47 @@
+// Fractional positions for user-ordered lists: moving an item only rewrites that
+// item's position (midway between its new neighbours) instead of renumbering the list.
+
+/// Gap between items when a list is appended to or renumbered
+pub const POSITION_STEP: f64 = 1024.0;
+
+/// Position for an item placed between `before` and `after` (None = that end of the list).
+/// Returns None once the neighbours are too close to split; renumber and try again.
+pub fn position_between(before: Option<f64>, after: Option<f64>) -> Option<f64> {
+    match (before, after) {
+        (None, None) => Some(POSITION_STEP),
+        (Some(before), None) => Some(before + POSITION_STEP),
+        (None, Some(after)) => Some(after - POSITION_STEP),
+        (Some(before), Some(after)) => {
+            let mid = before + (after - before) / 2.0;
+            (mid > before && mid < after).then_some(mid)
+        }
+    }
+}
+
+/// Evenly spaced positions for a list of `count` items, in order
+pub fn renumbered_positions(count: usize) -> impl Iterator<Item = f64> {
+    (1..=count).map(|i| i as f64 * POSITION_STEP)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_between_until_exhausted() {
+        assert_eq!(position_between(None, None), Some(1024.0));
+        assert_eq!(position_between(Some(1024.0), None), Some(2048.0));
+        assert_eq!(position_between(None, Some(1024.0)), Some(0.0));
+        assert_eq!(position_between(Some(1024.0), Some(2048.0)), Some(1536.0));
+
+        // Repeatedly inserting right after the first item eventually runs out of room
+        let (before, mut after) = (1024.0, 2048.0);
+        let mut splits = 0;
+        while let Some(mid) = position_between(Some(before), Some(after)) {
+            after = mid;
+            splits += 1;
+        }
+        assert!(splits > 40);
+        assert_eq!(renumbered_positions(3).collect::<Vec<_>>(), vec![1024.0, 2048.0, 3072.0]);
+    }
+}
@@ -0,0 +1,359 @@
+// External links on a PR (Jira ticket, design doc, staging URL, ...), in user order.
+// A link can be marked blocking: the ticket has to be resolved before the PR is done.
+// Nothing talks to Jira, so resolution is flipped by hand with set_link_resolved. Moving a
+// PR to Merged (there's no "Done" column; Merged is where finished PRs go) with blocking
+// links still open fails, or only warns, per blocking_links_enforcement. Merges reported
+// by GitHub always go through, as with strict status transitions.
+use std::fmt;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqliteConnection};
+
+use super::ordering::{position_between, renumbered_positions};
+use super::Database;
+
+pub const MAX_EXTERNAL_LINKS_PER_PR: i64 = 20;
+pub const MAX_EXTERNAL_LINK_URL_LEN: usize = 2048;
+pub const MAX_EXTERNAL_LINK_LABEL_LEN: usize = 100;
+
+pub const BLOCKING_LINKS_KEY: &str = "blocking_links_enforcement";
+pub const DONE_STATUS: &str = "Merged";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockingLinksEnforcement {
+    #[default]
+    Block, // the move fails, listing the unresolved links
+    Warn,  // the move happens and the unresolved links come back with it
+}
+
+impl BlockingLinksEnforcement {
+    /// Setting value: "block" or "warn"
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim() {
+            "block" => Ok(BlockingLinksEnforcement::Block),
+            "warn" => Ok(BlockingLinksEnforcement::Warn),
+            other => Err(anyhow::anyhow!("Blocking links enforcement must be 'block' or 'warn', got '{}'", other)),
+        }
+    }
+}
+
+impl fmt::Display for BlockingLinksEnforcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockingLinksEnforcement::Block => write!(f, "block"),
+            BlockingLinksEnforcement::Warn => write!(f, "warn"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrExternalLink {
+    pub id: i64,
+    pub pr_id: i64,
+    pub label: String,
+    pub url: String,
+    pub position: f64,
+    pub created_at: i64,
+    pub blocking: bool,
+    pub resolved: bool,
+}
+
+/// A move to DONE_STATUS held up by blocking links that aren't resolved yet
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockedByLinks {
+    pub pr_id: i64,
+    pub links: Vec<PrExternalLink>,
+}
+
+impl fmt::Display for BlockedByLinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let links: Vec<String> = self.links.iter().map(|link| format!("{} ({})", link.label, link.url)).collect();
+        write!(f, "Pull request {} has unresolved blocking links: {}", self.pr_id, links.join(", "))
+    }
+}
+
+impl std::error::Error for BlockedByLinks {}
+
+/// What update_pr_status let through: unresolved blocking links in warn mode, else nothing
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StatusUpdateResult {
+    pub unresolved_blocking_links: Vec<PrExternalLink>,
+}
+
+fn external_link_from_row(row: &SqliteRow) -> PrExternalLink {
+    PrExternalLink {
+        id: row.get("id"),
+        pr_id: row.get("pr_id"),
+        label: row.get("label"),
+        url: row.get("url"),
+        position: row.get("position"),
+        created_at: row.get("created_at"),
+        blocking: row.get("blocking"),
+        resolved: row.get("resolved"),
+    }
+}
+
+/// Blocking links still unresolved when a PR moves to `status`; empty unless that's DONE_STATUS
+pub(crate) async fn blocking_links_for_move(conn: &mut SqliteConnection, pr_id: i64, status: &str) -> Result<Vec<PrExternalLink>> {
+    if status != DONE_STATUS {
+        return Ok(Vec::new());
+    }
+    let rows = sqlx::query("SELECT * FROM pr_links_external WHERE pr_id = ? AND blocking = 1 AND resolved = 0 ORDER BY position, id")
+        .bind(pr_id)
+        .fetch_all(conn)
+        .await?;
+    Ok(rows.iter().map(external_link_from_row).collect())
+}
+
+/// Labels must be non-blank; URLs must be absolute http(s) with a host
+pub fn validate_external_link(label: &str, url: &str) -> Result<()> {
+    if label.trim().is_empty() {
+        return Err(anyhow::anyhow!("Link label cannot be empty"));
+    }
+    if label.chars().count() > MAX_EXTERNAL_LINK_LABEL_LEN {
+        return Err(anyhow::anyhow!("Link label is longer than {} characters", MAX_EXTERNAL_LINK_LABEL_LEN));
+    }
+    if url.len() > MAX_EXTERNAL_LINK_URL_LEN {
+        return Err(anyhow::anyhow!("Link URL is longer than {} characters", MAX_EXTERNAL_LINK_URL_LEN));
+    }
+
+    let parsed = reqwest::Url::parse(url).map_err(|e| anyhow::anyhow!("Invalid link URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(anyhow::anyhow!("Link URL must start with http:// or https://"));
+    }
+    Ok(())
+}
+
+impl Database {
+    /// A PR's external links in display order
+    pub async fn get_pr_external_links(&self, pr_id: i64) -> Result<Vec<PrExternalLink>> {
+        let rows = sqlx::query("SELECT * FROM pr_links_external WHERE pr_id = ? ORDER BY position, id")
+            .bind(pr_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(external_link_from_row).collect())
+    }
+
+    async fn get_pr_external_link(&self, id: i64) -> Result<PrExternalLink> {
+        let row = sqlx::query("SELECT * FROM pr_links_external WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Link {} not found", id))?;
+
+        Ok(external_link_from_row(&row))
+    }
+
+    /// Append a link to the end of a PR's list
+    pub async fn add_pr_external_link(&self, pr_id: i64, label: &str, url: &str) -> Result<PrExternalLink> {
+        validate_external_link(label, url)?;
+        if self.get_pull_request_by_id(pr_id).await?.is_none() {
+            return Err(anyhow::anyhow!("Pull request {} not found", pr_id));
+        }
+
+        let links = self.get_pr_external_links(pr_id).await?;
+        if links.len() as i64 >= MAX_EXTERNAL_LINKS_PER_PR {
+            return Err(anyhow::anyhow!("A PR can have at most {} links", MAX_EXTERNAL_LINKS_PER_PR));
+        }
+        let last = links.last().map(|link| link.position);
+        let position = position_between(last, None).unwrap_or_default();
+
+        let id = sqlx::query(
+            "INSERT INTO pr_links_external (pr_id, label, url, position, created_at) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(pr_id)
+        .bind(label.trim())
+        .bind(url)
+        .bind(position)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid();
+
+        self.get_pr_external_link(id).await
+    }
+
+    pub async fn get_blocking_links_enforcement(&self) -> Result<BlockingLinksEnforcement> {
+        Ok(self.get_setting(BLOCKING_LINKS_KEY).await?
+            .and_then(|value| BlockingLinksEnforcement::parse(&value).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn set_link_blocking(&self, id: i64, blocking: bool) -> Result<PrExternalLink> {
+        self.set_link_flag(id, "blocking", blocking).await
+    }
+
+    pub async fn set_link_resolved(&self, id: i64, resolved: bool) -> Result<PrExternalLink> {
+        self.set_link_flag(id, "resolved", resolved).await
+    }
+
+    async fn set_link_flag(&self, id: i64, column: &str, value: bool) -> Result<PrExternalLink> {
+        let result = sqlx::query(&format!("UPDATE pr_links_external SET {} = ? WHERE id = ?", column))
+            .bind(value)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Link {} not found", id));
+        }
+        self.get_pr_external_link(id).await
+    }
+
+    pub async fn remove_pr_external_link(&self, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM pr_links_external WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Link {} not found", id));
+        }
+        Ok(())
+    }
+
+    /// Move a link right after `after_id` (None = to the top); returns the PR's links in their new order
+    pub async fn move_pr_external_link(&self, id: i64, after_id: Option<i64>) -> Result<Vec<PrExternalLink>> {
+        let link = self.get_pr_external_link(id).await?;
+        let pr_id = link.pr_id;
+        let mut others: Vec<PrExternalLink> = self.get_pr_external_links(pr_id).await?
+            .into_iter()
+            .filter(|other| other.id != id)
+            .collect();
+
+        let index = match after_id {
+            Some(after_id) => others.iter()
+                .position(|other| other.id == after_id)
+                .ok_or_else(|| anyhow::anyhow!("Link {} is not on the same PR", after_id))? + 1,
+            None => 0,
+        };
+        let before = index.checked_sub(1).map(|i| others[i].position);
+        let after = others.get(index).map(|other| other.position);
+
+        if let Some(position) = position_between(before, after) {
+            sqlx::query("UPDATE pr_links_external SET position = ? WHERE id = ?")
+                .bind(position)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        } else {
+            // Neighbours too close to split: renumber the whole list in its new order
+            others.insert(index, link);
+            let mut tx = self.pool.begin().await?;
+            for (other, position) in others.iter().zip(renumbered_positions(others.len())) {
+                sqlx::query("UPDATE pr_links_external SET position = ? WHERE id = ?")
+                    .bind(position)
+                    .bind(other.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+        }
+
+        self.get_pr_external_links(pr_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+
+    fn labels(links: &[PrExternalLink]) -> Vec<&str> {
+        links.iter().map(|link| link.label.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_add_validate_reorder_and_cap() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap();
+
+        assert!(db.add_pr_external_link(pr.id, "Jira", "javascript:alert(1)").await.is_err());
+        assert!(db.add_pr_external_link(pr.id, "Jira", "ftp://example.com/x").await.is_err());
+        assert!(db.add_pr_external_link(pr.id, "  ", "https://example.com").await.is_err());
+        let long_url = format!("https://example.com/{}", "a".repeat(MAX_EXTERNAL_LINK_URL_LEN));
+        assert!(db.add_pr_external_link(pr.id, "Doc", &long_url).await.is_err());
+        assert!(db.add_pr_external_link(9999, "Doc", "https://example.com").await.is_err());
+
+        let jira = db.add_pr_external_link(pr.id, "Jira", "https://acme.atlassian.net/browse/WEB-1").await.unwrap();
+        let design = db.add_pr_external_link(pr.id, "Design", "https://docs.example.com/d/1").await.unwrap();
+        let staging = db.add_pr_external_link(pr.id, "Staging", "http://staging.example.com").await.unwrap();
+
+        let moved = db.move_pr_external_link(staging.id, None).await.unwrap();
+        assert_eq!(labels(&moved), vec!["Staging", "Jira", "Design"]);
+        let moved = db.move_pr_external_link(jira.id, Some(design.id)).await.unwrap();
+        assert_eq!(labels(&moved), vec!["Staging", "Design", "Jira"]);
+
+        // Keep splitting the same gap until it has to renumber; order stays right throughout
+        for _ in 0..60 {
+            db.move_pr_external_link(jira.id, Some(staging.id)).await.unwrap();
+            let moved = db.move_pr_external_link(design.id, Some(staging.id)).await.unwrap();
+            assert_eq!(labels(&moved), vec!["Staging", "Design", "Jira"]);
+        }
+
+        db.remove_pr_external_link(design.id).await.unwrap();
+        assert!(db.remove_pr_external_link(design.id).await.is_err());
+        for i in 2..MAX_EXTERNAL_LINKS_PER_PR {
+            db.add_pr_external_link(pr.id, &format!("Link {}", i), "https://example.com").await.unwrap();
+        }
+        assert!(db.add_pr_external_link(pr.id, "One too many", "https://example.com").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_blocking_links_hold_up_merged() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let mut ids = Vec::new();
+        for number in 1..=3 {
+            ids.push(db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                author_id: author.id,
+                status: PrStatus::Approved,
+                ..Default::default()
+            }).await.unwrap().id);
+        }
+        let ticket = db.add_pr_external_link(ids[0], "WEB-1", "https://acme.atlassian.net/browse/WEB-1").await.unwrap();
+        db.add_pr_external_link(ids[0], "Design", "https://docs.example.com/d/1").await.unwrap();
+        assert!(db.set_link_blocking(9999, true).await.is_err());
+        assert!(db.set_link_blocking(ticket.id, true).await.unwrap().blocking);
+
+        // Only Merged is guarded, and the error names the open ticket but not the plain link
+        db.update_pr_status(&WriteContext::default(), ids[0], "Reviewing".to_string()).await.unwrap();
+        let err = db.update_pr_status(&WriteContext::default(), ids[0], "Merged".to_string()).await.unwrap_err();
+        assert_eq!(err.to_string(), "Pull request 1 has unresolved blocking links: WEB-1 (https://acme.atlassian.net/browse/WEB-1)");
+        assert!(err.downcast_ref::<BlockedByLinks>().is_some());
+
+        // Bulk moves skip the blocked PR and carry on with the rest
+        let result = db.bulk_update_pr_status(&WriteContext::default(), &[ids[0], ids[1]], "Merged").await.unwrap();
+        assert_eq!(result.updated, vec![ids[1]]);
+        assert_eq!(result.rejected.len(), 1);
+        assert_eq!(result.rejected[0].blocking_links.iter().map(|link| link.id).collect::<Vec<_>>(), vec![ticket.id]);
+
+        // Warn mode lets the move through and reports what was still open
+        let other = db.add_pr_external_link(ids[2], "WEB-2", "https://acme.atlassian.net/browse/WEB-2").await.unwrap();
+        db.set_link_blocking(other.id, true).await.unwrap();
+        db.set_setting(BLOCKING_LINKS_KEY, "warn").await.unwrap();
+        let result = db.bulk_update_pr_status(&WriteContext::default(), &[ids[2]], "Merged").await.unwrap();
+        assert_eq!(result.updated, vec![ids[2]]);
+        assert_eq!(result.warned.iter().map(|blocked| blocked.pr_id).collect::<Vec<_>>(), vec![ids[2]]);
+        db.set_setting(BLOCKING_LINKS_KEY, "block").await.unwrap();
+
+        // Once the ticket is resolved the move goes through with nothing to report
+        assert!(db.set_link_resolved(ticket.id, true).await.unwrap().resolved);
+        let result = db.update_pr_status(&WriteContext::default(), ids[0], "Merged".to_string()).await.unwrap();
+        assert!(result.unresolved_blocking_links.is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+// Which team members belong to which project, for squads that only review their own
+// project's PRs. Membership is a roster, not history: adding or removing someone leaves
+// their PRs, comments and reviews alone. A project nobody was added to has no explicit
+// roster, and project_reviewers falls back to the whole team for it.
+use anyhow::Result;
+
+use super::{team_member_from_row, Database, TeamMember};
+
+impl Database {
+    /// Adding someone already on the project is a no-op
+    pub async fn add_project_member(&self, project_id: i64, team_member_id: i64) -> Result<()> {
+        if self.get_project_by_id(project_id).await?.is_none() {
+            return Err(anyhow::anyhow!("Project {} not found", project_id));
+        }
+        let member_exists: bool = sqlx::query_scalar("SELECT COUNT(*) > 0 FROM team_members WHERE id = ?")
+            .bind(team_member_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !member_exists {
+            return Err(anyhow::anyhow!("Team member {} not found", team_member_id));
+        }
+        sqlx::query("INSERT OR IGNORE INTO project_members (project_id, team_member_id, added_at) VALUES (?, ?, ?)")
+            .bind(project_id)
+            .bind(team_member_id)
+            .bind(chrono::Utc::now().timestamp())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_project_member(&self, project_id: i64, team_member_id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM project_members WHERE project_id = ? AND team_member_id = ?")
+            .bind(project_id)
+            .bind(team_member_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Team member {} is not a member of project {}", team_member_id, project_id));
+        }
+        Ok(())
+    }
+
+    /// The explicit roster, by username; empty when the project has none
+    pub async fn get_project_members(&self, project_id: i64) -> Result<Vec<TeamMember>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT tm.id, tm.github_username, tm.avatar_url, tm.display_name, tm.created_at, tm.avatar_path
+            FROM project_members pm
+            JOIN team_members tm ON tm.id = pm.team_member_id
+            WHERE pm.project_id = ?
+            ORDER BY tm.github_username
+            "#
+        )
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(team_member_from_row).collect())
+    }
+
+    /// Who counts as a reviewer for a project: its roster, or every team member while it has none.
+    /// Without a project scope, everyone.
+    pub async fn project_reviewers(&self, project_id: Option<i64>) -> Result<Vec<TeamMember>> {
+        if let Some(project_id) = project_id {
+            let members = self.get_project_members(project_id).await?;
+            if !members.is_empty() {
+                return Ok(members);
+            }
+        }
+        self.get_team_members().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    fn usernames(members: &[TeamMember]) -> Vec<&str> {
+        members.iter().map(|member| member.github_username.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_roster_scopes_reviewers_and_leaves_prs_alone() {
+        let db = Database::new_in_memory().await.unwrap();
+        let payments = db.add_project("Payments".to_string(), None).await.unwrap();
+        let search = db.add_project("Search".to_string(), None).await.unwrap();
+        let alice = db.get_or_create_team_member("alice".to_string()).await.unwrap();
+        let bob = db.get_or_create_team_member("bob".to_string()).await.unwrap();
+        db.get_or_create_team_member("carol".to_string()).await.unwrap();
+
+        db.add_project_member(payments.id, bob.id).await.unwrap();
+        db.add_project_member(payments.id, alice.id).await.unwrap();
+        db.add_project_member(payments.id, alice.id).await.unwrap();
+        assert!(db.add_project_member(payments.id, 9999).await.is_err());
+        assert!(db.add_project_member(9999, alice.id).await.is_err());
+
+        assert_eq!(usernames(&db.project_reviewers(Some(payments.id)).await.unwrap()), vec!["alice", "bob"]);
+        // No roster yet: everyone
+        assert_eq!(usernames(&db.project_reviewers(Some(search.id)).await.unwrap()), vec!["alice", "bob", "carol"]);
+        assert_eq!(db.project_reviewers(None).await.unwrap().len(), 3);
+
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: bob.id,
+            project_id: Some(payments.id),
+            ..Default::default()
+        }).await.unwrap();
+        db.remove_project_member(payments.id, bob.id).await.unwrap();
+        assert!(db.remove_project_member(payments.id, bob.id).await.is_err());
+        assert_eq!(usernames(&db.get_project_members(payments.id).await.unwrap()), vec!["alice"]);
+        let kept = db.get_pull_request_by_id(pr.id).await.unwrap().unwrap();
+        assert_eq!((kept.author_id, kept.project_id), (bob.id, Some(payments.id)));
+
+        // Deleting the project or the member takes the roster rows with it
+        db.add_project_member(search.id, alice.id).await.unwrap();
+        db.delete_project(search.id).await.unwrap();
+        db.delete_team_member(alice.id).await.unwrap();
+        assert!(db.get_project_members(payments.id).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+// Filter-driven PR selection shared by set-based operations
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Sqlite};
+
+use super::waiting_on::WaitingOn;
+
+/// Criteria for selecting pull requests. Every set field narrows the selection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterSpec {
+    pub project_id: Option<i64>,
+    #[serde(default)]
+    pub statuses: Vec<String>,
+    pub author_id: Option<i64>,
+    pub older_than_days: Option<i64>, // by GitHub's updated_at, else last_updated_at
+    pub waiting_on: Option<WaitingOn>,
+}
+
+impl FilterSpec {
+    pub fn is_empty(&self) -> bool {
+        self.project_id.is_none()
+            && self.statuses.is_empty()
+            && self.author_id.is_none()
+            && self.older_than_days.is_none()
+            && self.waiting_on.is_none()
+    }
+}
+
+/// What a set-based operation does to each PR it selects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkAction {
+    Archive,
+    Delete,
+}
+
+impl BulkAction {
+    /// The PRs the operation runs on; previews select through the same query
+    pub fn query<'a>(self, filter: &'a FilterSpec, now: i64) -> PrQuery<'a> {
+        match self {
+            BulkAction::Archive => PrQuery::new(filter, now).excluding_status("archived"),
+            BulkAction::Delete => PrQuery::new(filter, now),
+        }
+    }
+}
+
+/// Builds `WHERE` clauses over `pull_requests pr` from a FilterSpec, binding every value
+pub struct PrQuery<'a> {
+    filter: &'a FilterSpec,
+    now: i64,
+    exclude_statuses: Vec<&'a str>,
+}
+
+impl<'a> PrQuery<'a> {
+    pub fn new(filter: &'a FilterSpec, now: i64) -> Self {
+        PrQuery { filter, now, exclude_statuses: Vec::new() }
+    }
+
+    /// Additionally skip PRs already in the given status
+    pub fn excluding_status(mut self, status: &'a str) -> Self {
+        self.exclude_statuses.push(status);
+        self
+    }
+
+    /// Append ` AND ...` conditions for the filter to a query that already has a WHERE clause
+    pub fn push_conditions(&self, qb: &mut QueryBuilder<'a, Sqlite>) {
+        if let Some(project_id) = self.filter.project_id {
+            qb.push(" AND pr.project_id = ").push_bind(project_id);
+        }
+        if let Some(author_id) = self.filter.author_id {
+            qb.push(" AND pr.author_id = ").push_bind(author_id);
+        }
+        if !self.filter.statuses.is_empty() {
+            qb.push(" AND pr.status IN (");
+            let mut list = qb.separated(", ");
+            for status in &self.filter.statuses {
+                list.push_bind(status.as_str());
+            }
+            qb.push(")");
+        }
+        if let Some(waiting_on) = self.filter.waiting_on {
+            qb.push(" AND pr.waiting_on = ").push_bind(waiting_on.as_str());
+        }
+        for status in &self.exclude_statuses {
+            qb.push(" AND pr.status != ").push_bind(*status);
+        }
+        if let Some(days) = self.filter.older_than_days {
+            qb.push(" AND COALESCE(pr.github_updated_at, pr.last_updated_at) < ")
+                .push_bind(self.now - days * 86_400);
+        }
+    }
+
+    /// `SELECT pr.id ...` for every matching PR, oldest id first
+    pub fn select_ids(&self) -> QueryBuilder<'a, Sqlite> {
+        let mut qb = QueryBuilder::new("SELECT pr.id FROM pull_requests pr WHERE 1 = 1");
+        self.push_conditions(&mut qb);
+        qb.push(" ORDER BY pr.id");
+        qb
+    }
+
+    /// `SELECT COUNT(*) ...` over the matching PRs
+    pub fn select_count(&self) -> QueryBuilder<'a, Sqlite> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(*) FROM pull_requests pr WHERE 1 = 1");
+        self.push_conditions(&mut qb);
+        qb
+    }
+}
@@ -0,0 +1,318 @@
+// Rebuilding one repository's tracked PRs from GitHub's current list. The swap is a single
+// transaction: the old rows are staged by pr_number, deleted, and the upstream list is
+// inserted. With preserve_local_fields, each new row picks up its staged namesake's local
+// state (status, score, priority, project, scoring exclusion, known release) and everything
+// hanging off the old row (history, comments, reviewers, links, per-profile state such as
+// the pin, queued work) is re-pointed at it. Old rows with no upstream namesake are deleted
+// with their children. Tracked issues in the repository are left alone.
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::Row;
+
+use super::lifecycle::{record_lifecycle_event, LifecycleEvent};
+use super::{insert_pull_request, Database, NewPullRequest, CONFIRM_REIMPORT_REPOSITORY, PR_CHILD_TABLES};
+
+/// A PR as GitHub lists it now, ready to insert
+#[derive(Debug, Clone, Default)]
+pub struct UpstreamPr {
+    pub pr: NewPullRequest,
+    pub merged_at: Option<i64>, // Some once merged
+    pub merge_commit_sha: Option<String>,
+}
+
+/// PR numbers by what happened to them
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ReimportSummary {
+    pub reimported: usize,      // rows inserted from the upstream list
+    pub carried_over: Vec<i64>, // local fields and history moved to the new row
+    pub added: Vec<i64>,        // not tracked before
+    pub removed: Vec<i64>,      // tracked before but not in the upstream list; deleted
+}
+
+// Local state of an old row, kept across its deletion
+struct StagedPr {
+    id: i64,
+    status: String,
+    waiting_on: String,
+    head_sha: Option<String>,
+    score: Option<i32>,
+    priority: i64,
+    project_id: Option<i64>,
+    exclude_from_scoring: bool,
+    shipped_in_release: Option<String>,
+}
+
+impl Database {
+    /// Replace owner/repo's tracked PRs with `upstream`. Requires a CONFIRM_REIMPORT_REPOSITORY
+    /// nonce and takes a safety backup first.
+    pub async fn replace_repository_prs(
+        &self,
+        owner: &str,
+        repo: &str,
+        upstream: &[UpstreamPr],
+        preserve_local_fields: bool,
+        confirm_nonce: &str,
+    ) -> Result<ReimportSummary> {
+        self.consume_confirmation_nonce(confirm_nonce, CONFIRM_REIMPORT_REPOSITORY).await?;
+        self.create_safety_backup("pre-reimport").await?;
+
+        let rules = self.get_title_rules().await?;
+        let now = chrono::Utc::now().timestamp();
+        let upstream_numbers: HashSet<i64> = upstream.iter().map(|up| up.pr.pr_number).collect();
+        let mut summary = ReimportSummary::default();
+        let mut tx = self.pool.begin().await?;
+        // Carried-over children point at a deleted row until they're re-pointed below;
+        // foreign keys are checked at commit instead (the pragma ends with the transaction)
+        sqlx::query("PRAGMA defer_foreign_keys = ON").execute(&mut *tx).await?;
+
+        // Stage
+        let rows = sqlx::query(
+            r#"
+            SELECT id, pr_number, status, waiting_on, head_sha, score, priority, project_id, exclude_from_scoring, shipped_in_release
+            FROM pull_requests
+            WHERE repository_owner = ? AND repository_name = ? AND is_issue = 0
+            ORDER BY id
+            "#
+        )
+        .bind(owner)
+        .bind(repo)
+        .fetch_all(&mut *tx)
+        .await?;
+        let old_ids: Vec<(i64, i64)> = rows.iter().map(|row| (row.get("id"), row.get("pr_number"))).collect();
+        // A duplicate number keeps its newest row; the others go like vanished PRs
+        let mut staged: BTreeMap<i64, StagedPr> = rows.iter()
+            .map(|row| (row.get("pr_number"), StagedPr {
+                id: row.get("id"),
+                status: row.get("status"),
+                waiting_on: row.get("waiting_on"),
+                head_sha: row.get("head_sha"),
+                score: row.get("score"),
+                priority: row.get("priority"),
+                project_id: row.get("project_id"),
+                exclude_from_scoring: row.get("exclude_from_scoring"),
+                shipped_in_release: row.get("shipped_in_release"),
+            }))
+            .collect();
+        summary.removed = staged.keys().copied().filter(|number| !upstream_numbers.contains(number)).collect();
+
+        // Delete; children of rows about to be carried over stay behind for re-pointing
+        for (id, number) in &old_ids {
+            let carried = preserve_local_fields
+                && upstream_numbers.contains(number)
+                && staged.get(number).is_some_and(|old| old.id == *id);
+            if !carried {
+                for table in PR_CHILD_TABLES {
+                    sqlx::query(&format!("DELETE FROM {} WHERE pr_id = ?", table))
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+            sqlx::query("DELETE FROM pull_requests WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Insert, then carry over
+        for up in upstream {
+            let id = insert_pull_request(&mut tx, &up.pr, None, &rules, now).await?;
+            summary.reimported += 1;
+            if let Some(sha) = &up.merge_commit_sha {
+                sqlx::query("UPDATE pull_requests SET merge_commit_sha = ? WHERE id = ?")
+                    .bind(sha)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            let previous = staged.remove(&up.pr.pr_number);
+            if previous.is_none() {
+                summary.added.push(up.pr.pr_number);
+            }
+            // A carried-over row keeps its old timeline; every other row starts one here
+            let carried = previous.as_ref().is_some_and(|_| preserve_local_fields);
+            if !carried {
+                record_lifecycle_event(&mut tx, id, LifecycleEvent::Created, Some("reimport"), None, now).await?;
+            }
+            if let Some(old) = previous.filter(|_| preserve_local_fields) {
+                sqlx::query(
+                    r#"
+                    UPDATE pull_requests
+                    SET status = ?, waiting_on = ?, head_sha = ?, score = ?, priority = ?, project_id = ?,
+                        exclude_from_scoring = ?, shipped_in_release = ?
+                    WHERE id = ?
+                    "#
+                )
+                .bind(&old.status)
+                .bind(&old.waiting_on)
+                .bind(&old.head_sha)
+                .bind(old.score)
+                .bind(old.priority)
+                .bind(old.project_id)
+                .bind(old.exclude_from_scoring)
+                .bind(&old.shipped_in_release)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+                for table in PR_CHILD_TABLES {
+                    sqlx::query(&format!("UPDATE {} SET pr_id = ? WHERE pr_id = ?", table))
+                        .bind(id)
+                        .bind(old.id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                summary.carried_over.push(up.pr.pr_number);
+            }
+
+            // Reports count merges from history; carried-over history may already have it
+            if let Some(merged_at) = up.merged_at {
+                let recorded: bool = sqlx::query_scalar(
+                    "SELECT EXISTS (SELECT 1 FROM review_history WHERE pr_id = ? AND action = 'merged')"
+                )
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+                if !recorded {
+                    record_lifecycle_event(&mut tx, id, LifecycleEvent::Merged, None, None, merged_at).await?;
+                }
+            }
+        }
+
+        tx.commit().await?;
+        println!(
+            "♻️ Reimported {}/{}: {} PRs, {} carried over, {} new, {} removed",
+            owner, repo, summary.reimported, summary.carried_over.len(), summary.added.len(), summary.removed.len()
+        );
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::{PrPriority, PullRequest};
+
+    fn upstream(number: i64, author_id: i64, status: &str, merged_at: Option<i64>) -> UpstreamPr {
+        UpstreamPr {
+            pr: NewPullRequest {
+                github_id: 1000 + number,
+                pr_number: number,
+                title: Some(format!("fix: upstream #{}", number)),
+                author_id,
+                status: status.into(),
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some("api".to_string()),
+                ..Default::default()
+            },
+            merged_at,
+            merge_commit_sha: merged_at.map(|_| format!("sha{}", number)),
+        }
+    }
+
+    async fn by_number(db: &Database, repo: &str, number: i64) -> Option<PullRequest> {
+        db.get_pull_requests().await.unwrap().into_iter()
+            .find(|pr| pr.repository_name.as_deref() == Some(repo) && pr.pr_number == number && !pr.is_issue)
+    }
+
+    async fn reimport(db: &Database, upstream: &[UpstreamPr], preserve: bool) -> ReimportSummary {
+        let nonce = db.issue_confirmation_nonce(CONFIRM_REIMPORT_REPOSITORY).await.unwrap();
+        db.replace_repository_prs("acme", "api", upstream, preserve, &nonce).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reimport_carries_local_fields_by_pr_number() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let reviewer = db.get_or_create_team_member("hubot".to_string()).await.unwrap();
+        let project = db.add_project("API".to_string(), None).await.unwrap();
+
+        // #1 survives upstream, #2 vanished upstream, #3 is new; web#1 and issue #9 are elsewhere
+        let mut ids = BTreeMap::new();
+        for (repo, number) in [("api", 1), ("api", 2), ("web", 1)] {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: if repo == "api" { 1000 + number } else { 2000 + number },
+                pr_number: number,
+                title: Some("old title".to_string()),
+                author_id: author.id,
+                project_id: Some(project.id),
+                status: PrStatus::Waiting,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some(repo.to_string()),
+                ..Default::default()
+            }).await.unwrap();
+            ids.insert((repo, number), pr.id);
+        }
+        let issue = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: -9,
+            pr_number: 9,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            is_issue: true,
+            ..Default::default()
+        }).await.unwrap();
+        for id in [ids[&("api", 1)], ids[&("api", 2)]] {
+            db.update_pr_status(&WriteContext::default(), id, "Reviewing".to_string()).await.unwrap();
+            db.update_pr_score(&WriteContext::default(), id, 4).await.unwrap();
+            db.set_pr_priority(&WriteContext::default(), id, PrPriority::High).await.unwrap();
+            db.set_pr_status_pinned(&WriteContext::default(), id, true).await.unwrap();
+            db.add_pr_reviewer(id, reviewer.id, 100).await.unwrap();
+            db.add_pr_comment(&WriteContext::default(), id, reviewer.id, "check the retry path".to_string()).await.unwrap();
+        }
+        let old_history = db.get_pr_history(ids[&("api", 1)]).await.unwrap().len();
+
+        let summary = reimport(&db, &[
+            upstream(1, author.id, "Waiting", None),
+            upstream(3, author.id, "Merged", Some(5_000)),
+        ], true).await;
+        assert_eq!(summary, ReimportSummary { reimported: 2, carried_over: vec![1], added: vec![3], removed: vec![2] });
+
+        let first = by_number(&db, "api", 1).await.unwrap();
+        assert_ne!(first.id, ids[&("api", 1)]);
+        assert_eq!(first.title.as_deref(), Some("fix: upstream #1"));
+        assert_eq!((first.status.as_str(), first.score, first.priority), ("Reviewing", Some(4), PrPriority::High));
+        assert_eq!(first.project_id, Some(project.id));
+        assert!(first.status_pinned);
+        assert_eq!(first.reviewer_count, 1);
+        assert_eq!(db.get_pr_comments(first.id).await.unwrap()[0].body, "check the retry path");
+        assert_eq!(db.get_pr_history(first.id).await.unwrap().len(), old_history);
+
+        // Vanished: gone with everything attached
+        assert!(by_number(&db, "api", 2).await.is_none());
+        let leftover: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pr_comments WHERE pr_id = ?")
+            .bind(ids[&("api", 2)])
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(leftover, 0);
+
+        // New: as GitHub has it, merge recorded
+        let third = by_number(&db, "api", 3).await.unwrap();
+        assert_eq!((third.status.as_str(), third.merge_commit_sha.as_deref()), ("Merged", Some("sha3")));
+        let actions: Vec<String> = db.get_pr_history(third.id).await.unwrap().into_iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, vec!["merged", "created"]);
+
+        // Other repos and tracked issues are untouched
+        assert_eq!(by_number(&db, "web", 1).await.unwrap().id, ids[&("web", 1)]);
+        assert!(db.get_pull_request_by_id(issue.id).await.unwrap().is_some());
+
+        // Without preserving, everything local starts over
+        let summary = reimport(&db, &[upstream(1, author.id, "Waiting", None)], false).await;
+        assert_eq!(summary, ReimportSummary { reimported: 1, carried_over: vec![], added: vec![], removed: vec![3] });
+        let fresh = by_number(&db, "api", 1).await.unwrap();
+        assert_eq!((fresh.status.as_str(), fresh.score, fresh.status_pinned, fresh.reviewer_count), ("Waiting", None, false, 0));
+        assert!(db.get_pr_comments(fresh.id).await.unwrap().is_empty());
+        let history = db.get_pr_history(fresh.id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!((history[0].action.as_str(), history[0].details.as_deref()), ("created", Some("reimport")));
+
+        // The nonce is single use
+        assert!(db.replace_repository_prs("acme", "api", &[], true, "bogus").await.is_err());
+    }
+}
@@ -0,0 +1,54 @@
+// Which release shipped a merged PR. The merge commit SHA is stored when GitHub reports
+// the PR merged; shipped_in_release is filled on demand (services/releases.rs) and never
+// cleared, since a tag that contains the commit keeps containing it.
+use anyhow::Result;
+
+use super::{pull_requests_from_rows, Database, PullRequest, PR_SELECT};
+
+impl Database {
+    pub async fn set_merge_commit_sha(&self, pr_id: i64, sha: &str) -> Result<()> {
+        sqlx::query("UPDATE pull_requests SET merge_commit_sha = ? WHERE id = ?")
+            .bind(sha)
+            .bind(pr_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_shipped_in_release(&self, pr_id: i64, tag: &str) -> Result<()> {
+        sqlx::query("UPDATE pull_requests SET shipped_in_release = ? WHERE id = ?")
+            .bind(tag)
+            .bind(pr_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// When the PR was merged, from its merged lifecycle event
+    pub async fn get_pr_merged_at(&self, pr_id: i64) -> Result<Option<i64>> {
+        Ok(sqlx::query_scalar("SELECT MAX(performed_at) FROM review_history WHERE pr_id = ? AND action = 'merged'")
+            .bind(pr_id)
+            .fetch_one(&self.pool)
+            .await?)
+    }
+
+    /// Merged PRs in owner/repo with no release found yet, oldest first. A PR archived
+    /// after merging still counts once its merge commit is known.
+    pub async fn get_unreleased_merged_prs(&self, owner: &str, repo: &str, limit: i64) -> Result<Vec<PullRequest>> {
+        let rows = sqlx::query(&format!(
+            r#"{}
+            WHERE pr.repository_owner = ? AND pr.repository_name = ? AND pr.is_issue = 0
+              AND pr.shipped_in_release IS NULL
+              AND (pr.status = 'Merged' OR pr.merge_commit_sha IS NOT NULL)
+            ORDER BY pr.id
+            LIMIT ?"#,
+            PR_SELECT
+        ))
+        .bind(owner)
+        .bind(repo)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(pull_requests_from_rows(&rows))
+    }
+}
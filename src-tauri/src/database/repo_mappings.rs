@@ -0,0 +1,146 @@
+// Repository -> project mappings, set by hand on the tracked repository. Adding a PR from a
+// mapped repository to another project still works but comes back with a warning, and the
+// PR's "created" history entry notes the override; with strict_repo_mapping on it's refused.
+// Quick add files PRs from a mapped repository under its project without asking.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::transitions::parse_strict_flag;
+use super::{project_from_row, Database, Project};
+
+pub const STRICT_REPO_MAPPING_KEY: &str = "strict_repo_mapping";
+pub const DEFAULT_STRICT_REPO_MAPPING: bool = false;
+
+/// An explicit project that differs from the one the repository is mapped to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectMappingConflict {
+    pub owner: String,
+    pub repo: String,
+    pub mapped_project_id: i64,
+    pub mapped_project_name: String,
+    pub requested_project_id: i64,
+    pub message: String, // "repo org/payments-service is mapped to project 'Payments'"
+}
+
+impl fmt::Display for ProjectMappingConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Database {
+    /// Map a repository to a project, or clear the mapping with None; registers the repository if needed
+    pub async fn set_repository_project(&self, owner: &str, repo: &str, project_id: Option<i64>) -> Result<()> {
+        let (owner, repo) = (owner.trim(), repo.trim());
+        if owner.is_empty() || repo.is_empty() {
+            return Err(anyhow::anyhow!("Repository owner and name are required"));
+        }
+        if let Some(project_id) = project_id {
+            if self.get_project_by_id(project_id).await?.is_none() {
+                return Err(anyhow::anyhow!("Project {} not found", project_id));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let existing: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM repositories WHERE owner = ? COLLATE NOCASE AND name = ? COLLATE NOCASE"
+        )
+        .bind(owner)
+        .bind(repo)
+        .fetch_optional(&mut *tx)
+        .await?;
+        match existing {
+            Some(id) => {
+                sqlx::query("UPDATE repositories SET project_id = ? WHERE id = ?")
+                    .bind(project_id)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            None => {
+                sqlx::query("INSERT INTO repositories (owner, name, project_id) VALUES (?, ?, ?)")
+                    .bind(owner)
+                    .bind(repo)
+                    .bind(project_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The project a repository is mapped to, if any
+    pub async fn get_repository_project(&self, owner: &str, repo: &str) -> Result<Option<Project>> {
+        let row = sqlx::query(
+            r#"
+            SELECT p.id, p.name, p.description, p.created_at, p.sla_hours, p.target_score
+            FROM repositories r
+            JOIN projects p ON p.id = r.project_id
+            WHERE r.owner = ? COLLATE NOCASE AND r.name = ? COLLATE NOCASE
+            "#
+        )
+        .bind(owner)
+        .bind(repo)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(project_from_row))
+    }
+
+    /// Some when the repository is mapped to a project other than `project_id`
+    pub async fn project_mapping_conflict(&self, owner: &str, repo: &str, project_id: i64) -> Result<Option<ProjectMappingConflict>> {
+        Ok(self.get_repository_project(owner, repo).await?
+            .filter(|mapped| mapped.id != project_id)
+            .map(|mapped| ProjectMappingConflict {
+                message: format!("repo {}/{} is mapped to project '{}'", owner, repo, mapped.name),
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                mapped_project_id: mapped.id,
+                mapped_project_name: mapped.name,
+                requested_project_id: project_id,
+            }))
+    }
+
+    pub async fn get_strict_repo_mapping(&self) -> Result<bool> {
+        Ok(match self.get_setting(STRICT_REPO_MAPPING_KEY).await? {
+            Some(value) => parse_strict_flag(STRICT_REPO_MAPPING_KEY, &value).unwrap_or(DEFAULT_STRICT_REPO_MAPPING),
+            None => DEFAULT_STRICT_REPO_MAPPING,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mapping_conflicts() {
+        let db = Database::new_in_memory().await.unwrap();
+        let payments = db.add_project("Payments".to_string(), None).await.unwrap();
+        let mobile = db.add_project("Mobile".to_string(), None).await.unwrap();
+
+        assert!(db.project_mapping_conflict("org", "payments-service", mobile.id).await.unwrap().is_none());
+        assert!(db.set_repository_project("org", "payments-service", Some(9999)).await.is_err());
+
+        db.set_repository_project("org", "payments-service", Some(payments.id)).await.unwrap();
+        assert!(db.project_mapping_conflict("org", "payments-service", payments.id).await.unwrap().is_none());
+        let conflict = db.project_mapping_conflict("Org", "Payments-Service", mobile.id).await.unwrap().unwrap();
+        assert_eq!(conflict.mapped_project_id, payments.id);
+        assert_eq!(conflict.to_string(), "repo Org/Payments-Service is mapped to project 'Payments'");
+
+        // The mapping lives on the tracked repository, which keeps one row
+        let repos = db.get_tracked_repositories().await.unwrap();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].project_id, Some(payments.id));
+
+        // Deleting the project drops the mapping with it
+        db.delete_project(payments.id).await.unwrap();
+        assert_eq!(db.get_tracked_repositories().await.unwrap()[0].project_id, None);
+
+        db.set_repository_project("org", "payments-service", Some(mobile.id)).await.unwrap();
+        db.set_repository_project("org", "payments-service", None).await.unwrap();
+        assert!(db.get_repository_project("org", "payments-service").await.unwrap().is_none());
+    }
+}
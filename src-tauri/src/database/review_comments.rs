@@ -0,0 +1,108 @@
+// Weekly review comment counts per team member, filled by sync_review_comment_stats.
+// Aggregate-only: the table holds who, which week, which repository and how many, never
+// what was said. Don't add columns for comment text, URLs or ids.
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use sqlx::Row;
+
+use super::Database;
+
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// Monday 00:00 UTC of the week containing `ts`
+pub fn week_start(ts: i64) -> i64 {
+    let day = ts.div_euclid(DAY_SECS);
+    // 1970-01-01 was a Thursday, three days after a Monday
+    (day - (day + 3).rem_euclid(7)) * DAY_SECS
+}
+
+impl Database {
+    /// Team member ids by lowercased GitHub login (logins are case-insensitive)
+    pub async fn team_member_ids_by_login(&self) -> Result<HashMap<String, i64>> {
+        let rows = sqlx::query("SELECT id, github_username FROM team_members")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter()
+            .map(|row| (row.get::<String, _>("github_username").to_lowercase(), row.get("id")))
+            .collect())
+    }
+
+    /// Replace one repository's counts from `from_week` onwards with a fresh tally
+    /// keyed by (team_member_id, week_start)
+    pub async fn replace_review_comment_stats(
+        &self,
+        owner: &str,
+        repo: &str,
+        from_week: i64,
+        counts: &BTreeMap<(i64, i64), i64>,
+        now: i64,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM reviewer_comment_stats WHERE repository_owner = ? AND repository_name = ? AND week_start >= ?"
+        )
+        .bind(owner)
+        .bind(repo)
+        .bind(from_week)
+        .execute(&mut *tx)
+        .await?;
+
+        for ((member_id, week), count) in counts {
+            sqlx::query(
+                "INSERT INTO reviewer_comment_stats (team_member_id, week_start, repository_owner, repository_name, comment_count, synced_at)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )
+            .bind(member_id)
+            .bind(week)
+            .bind(owner)
+            .bind(repo)
+            .bind(count)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Review comments a member left in the weeks overlapping [from_ts, to_ts)
+    pub async fn get_review_comment_count(&self, team_member_id: i64, from_ts: i64, to_ts: i64) -> Result<i64> {
+        Ok(sqlx::query_scalar(
+            "SELECT COALESCE(SUM(comment_count), 0) FROM reviewer_comment_stats
+             WHERE team_member_id = ? AND week_start >= ? AND week_start < ?"
+        )
+        .bind(team_member_id)
+        .bind(week_start(from_ts))
+        .bind(to_ts)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_week_start_is_monday() {
+        // 2024-03-06 (Wednesday) 15:00 UTC -> 2024-03-04 00:00 UTC
+        assert_eq!(week_start(1_709_737_200), 1_709_510_400);
+        assert_eq!(week_start(1_709_510_400), 1_709_510_400);
+        assert_eq!(week_start(1_709_510_399), 1_709_510_400 - 7 * DAY_SECS);
+    }
+
+    // The privacy promise lives in the schema: nothing but counts and keys
+    #[tokio::test]
+    async fn test_schema_is_aggregate_only() {
+        let db = Database::new_in_memory().await.unwrap();
+        let columns: Vec<String> = sqlx::query("PRAGMA table_info(reviewer_comment_stats)")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get("name"))
+            .collect();
+        assert_eq!(columns, vec!["team_member_id", "week_start", "repository_owner", "repository_name", "comment_count", "synced_at"]);
+    }
+}
@@ -0,0 +1,154 @@
+// One-off tasks to run at a given time (e.g. refresh a PR right after CI finishes).
+// Stored in scheduled_tasks so they survive restarts; the scheduler loop runs due ones.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+
+use super::Database;
+
+pub const TASK_REFRESH_PR: &str = "refresh_pr";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledTask {
+    pub id: i64,
+    pub pr_id: i64,
+    pub task_type: String,
+    pub run_at: i64,
+    pub status: String, // "pending" | "done" | "failed" | "cancelled"
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+fn scheduled_task_from_row(row: &SqliteRow) -> ScheduledTask {
+    ScheduledTask {
+        id: row.get("id"),
+        pr_id: row.get("pr_id"),
+        task_type: row.get("task_type"),
+        run_at: row.get("run_at"),
+        status: row.get("status"),
+        created_at: row.get("created_at"),
+        completed_at: row.get("completed_at"),
+        last_error: row.get("last_error"),
+    }
+}
+
+impl Database {
+    /// Queue a task for a PR; returns the task id
+    pub async fn schedule_task(&self, pr_id: i64, task_type: &str, run_at: i64, now: i64) -> Result<i64> {
+        if run_at < now {
+            return Err(anyhow::anyhow!("Scheduled time is in the past"));
+        }
+        if self.get_pull_request_by_id(pr_id).await?.is_none() {
+            return Err(anyhow::anyhow!("Pull request {} not found", pr_id));
+        }
+
+        let id = sqlx::query(
+            "INSERT INTO scheduled_tasks (pr_id, task_type, run_at, status, created_at) VALUES (?, ?, ?, 'pending', ?)"
+        )
+        .bind(pr_id)
+        .bind(task_type)
+        .bind(run_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid();
+
+        println!("⏰ Scheduled {} for PR {} at {}", task_type, pr_id, run_at);
+        Ok(id)
+    }
+
+    /// Cancel a task that hasn't run yet
+    pub async fn cancel_scheduled_task(&self, id: i64) -> Result<()> {
+        let result = sqlx::query("UPDATE scheduled_tasks SET status = 'cancelled' WHERE id = ? AND status = 'pending'")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("No pending scheduled task {}", id));
+        }
+        Ok(())
+    }
+
+    pub async fn get_scheduled_task(&self, id: i64) -> Result<Option<ScheduledTask>> {
+        let row = sqlx::query("SELECT * FROM scheduled_tasks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(scheduled_task_from_row))
+    }
+
+    /// Pending tasks, soonest first
+    pub async fn get_pending_scheduled_tasks(&self) -> Result<Vec<ScheduledTask>> {
+        let rows = sqlx::query("SELECT * FROM scheduled_tasks WHERE status = 'pending' ORDER BY run_at, id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(scheduled_task_from_row).collect())
+    }
+
+    pub async fn get_due_scheduled_tasks(&self, now: i64) -> Result<Vec<ScheduledTask>> {
+        let rows = sqlx::query("SELECT * FROM scheduled_tasks WHERE status = 'pending' AND run_at <= ? ORDER BY run_at, id")
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(scheduled_task_from_row).collect())
+    }
+
+    /// Mark a task done, or failed with the error; tasks run once either way
+    pub async fn complete_scheduled_task(&self, id: i64, error: Option<&str>, now: i64) -> Result<()> {
+        sqlx::query("UPDATE scheduled_tasks SET status = ?, completed_at = ?, last_error = ? WHERE id = ?")
+            .bind(if error.is_some() { "failed" } else { "done" })
+            .bind(now)
+            .bind(error)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+
+    #[tokio::test]
+    async fn test_schedule_cancel_and_due() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 3,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap();
+
+        let now = 1_000;
+        assert!(db.schedule_task(pr.id, TASK_REFRESH_PR, now - 1, now).await.is_err());
+        assert!(db.schedule_task(9999, TASK_REFRESH_PR, now + 60, now).await.is_err());
+
+        let later = db.schedule_task(pr.id, TASK_REFRESH_PR, now + 600, now).await.unwrap();
+        let soon = db.schedule_task(pr.id, TASK_REFRESH_PR, now + 60, now).await.unwrap();
+        let cancelled = db.schedule_task(pr.id, TASK_REFRESH_PR, now + 30, now).await.unwrap();
+        db.cancel_scheduled_task(cancelled).await.unwrap();
+        assert!(db.cancel_scheduled_task(cancelled).await.is_err());
+
+        let pending: Vec<i64> = db.get_pending_scheduled_tasks().await.unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(pending, vec![soon, later]);
+        let due: Vec<i64> = db.get_due_scheduled_tasks(now + 60).await.unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(due, vec![soon]);
+
+        db.complete_scheduled_task(soon, Some("boom"), now + 61).await.unwrap();
+        let task = db.get_scheduled_task(soon).await.unwrap().unwrap();
+        assert_eq!((task.status.as_str(), task.last_error.as_deref()), ("failed", Some("boom")));
+        assert!(db.get_due_scheduled_tasks(now + 600).await.unwrap().iter().all(|t| t.id == later));
+    }
+}
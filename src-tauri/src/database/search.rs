@@ -0,0 +1,263 @@
+// Full-text search over PR titles, branches and local notes (pr_comments bodies), backed by
+// the pr_search FTS5 table from migration v19. Triggers on pull_requests and pr_comments keep
+// it current, so write paths don't maintain it themselves. PR descriptions aren't stored
+// locally, so they aren't searchable.
+//
+// Authors aren't in the index. A plain LIKE over the title and the author's login and
+// display name adds PRs the index missed (an author, or a fragment inside a word), ranked
+// after every indexed match.
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::Row;
+
+use super::{pull_request_from_row, Database, PullRequest, PR_SELECT};
+
+const MAX_SEARCH_RESULTS: i64 = 50;
+
+// bm25 column weights for (title, branch, notes): a word in the title says more about the
+// PR than the same word somewhere in a long note
+const SEARCH_WEIGHTS: &str = "10.0, 5.0, 1.0";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub pull_request: PullRequest,
+    pub snippet: String, // best-matching column, matches wrapped in ** and long text cut with …
+    pub score: f64,      // negated bm25; higher is more relevant
+}
+
+/// The query as FTS5 syntax: each word becomes a quoted phrase (so quotes, operators and
+/// column filters typed by the user are just text), all of them required, and the last
+/// one a prefix so results show up while typing. None when nothing searchable is left.
+pub fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    let last = terms.len().checked_sub(1)?;
+    Some(terms.iter().enumerate()
+        .map(|(i, term)| if i == last { format!("{}*", term) } else { term.clone() })
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// The query as a LIKE pattern matching it anywhere, with %, _ and the escape character
+/// itself taken literally (use with ESCAPE '\'). None for a blank query.
+pub fn like_pattern(query: &str) -> Option<String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return None;
+    }
+    let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    Some(format!("%{}%", escaped))
+}
+
+// The title with the first case-insensitive occurrence of `query` wrapped in **, like FTS snippets
+fn title_snippet(title: &str, query: &str) -> String {
+    let query = query.trim();
+    match title.to_lowercase().find(&query.to_lowercase()) {
+        Some(start) if title.is_char_boundary(start) && title.is_char_boundary(start + query.len()) => {
+            let end = start + query.len();
+            format!("{}**{}**{}", &title[..start], &title[start..end], &title[end..])
+        }
+        _ => title.to_string(),
+    }
+}
+
+impl Database {
+    /// Best matches first, at most 50
+    pub async fn search_pull_requests(&self, query: &str) -> Result<Vec<SearchHit>> {
+        let mut hits = match fts_query(query) {
+            Some(fts) => self.search_index(&fts).await?,
+            None => Vec::new(),
+        };
+        let Some(pattern) = like_pattern(query) else {
+            return Ok(hits);
+        };
+
+        // Case-insensitive for ASCII, as SQLite's LIKE is
+        let remaining = MAX_SEARCH_RESULTS - hits.len() as i64;
+        if remaining > 0 {
+            let found: Vec<i64> = hits.iter().map(|hit| hit.pull_request.id).collect();
+            let rows = sqlx::query(&format!(
+                r#"
+                {} WHERE (pr.title LIKE ?1 ESCAPE '\' OR tm.github_username LIKE ?1 ESCAPE '\' OR tm.display_name LIKE ?1 ESCAPE '\')
+                  AND pr.id NOT IN (SELECT value FROM json_each(?2))
+                ORDER BY COALESCE(pr.github_updated_at, pr.last_updated_at) DESC, pr.id
+                LIMIT ?3
+                "#,
+                PR_SELECT
+            ))
+            .bind(&pattern)
+            .bind(serde_json::to_string(&found)?)
+            .bind(remaining)
+            .fetch_all(&self.pool)
+            .await?;
+            hits.extend(rows.iter().map(pull_request_from_row).map(|pr| SearchHit {
+                snippet: title_snippet(pr.title.as_deref().unwrap_or_default(), query),
+                pull_request: pr,
+                score: 0.0,
+            }));
+        }
+        Ok(hits)
+    }
+
+    // Matches in the pr_search index, best first
+    async fn search_index(&self, fts: &str) -> Result<Vec<SearchHit>> {
+        let hits = sqlx::query(&format!(
+            r#"
+            SELECT rowid as pr_id, bm25(pr_search, {weights}) as rank,
+                   snippet(pr_search, -1, '**', '**', '…', 12) as snippet
+            FROM pr_search
+            WHERE pr_search MATCH ?
+            ORDER BY rank, rowid
+            LIMIT ?
+            "#,
+            weights = SEARCH_WEIGHTS,
+        ))
+        .bind(fts)
+        .bind(MAX_SEARCH_RESULTS)
+        .fetch_all(&self.pool)
+        .await?;
+        if hits.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<String> = hits.iter().map(|hit| hit.get::<i64, _>("pr_id").to_string()).collect();
+        let rows = sqlx::query(&format!("{} WHERE pr.id IN ({})", PR_SELECT, ids.join(", ")))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut prs: HashMap<i64, PullRequest> = rows.iter()
+            .map(pull_request_from_row)
+            .map(|pr| (pr.id, pr))
+            .collect();
+
+        Ok(hits.iter()
+            .filter_map(|hit| {
+                Some(SearchHit {
+                    pull_request: prs.remove(&hit.get::<i64, _>("pr_id"))?,
+                    snippet: hit.get("snippet"),
+                    score: -hit.get::<f64, _>("rank"),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    #[test]
+    fn test_fts_query_quotes_user_input() {
+        assert_eq!(fts_query("cache bug").as_deref(), Some("\"cache\" \"bug\"*"));
+        assert_eq!(fts_query("title:\"x OR -(y").as_deref(), Some("\"title:\"\"x\" \"OR\" \"-(y\"*"));
+        assert_eq!(fts_query("  - ( ) "), None);
+    }
+
+    #[test]
+    fn test_like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern(" Octo ").as_deref(), Some("%Octo%"));
+        assert_eq!(like_pattern("100%_done\\").as_deref(), Some("%100\\%\\_done\\\\%"));
+        assert_eq!(like_pattern("   "), None);
+        assert_eq!(title_snippet("Raise limit to 100%", "100%"), "Raise limit to **100%**");
+    }
+
+    #[tokio::test]
+    async fn test_search_matches_authors_and_literal_wildcards() {
+        let db = Database::new_in_memory().await.unwrap();
+        let octocat = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let hubot = db.get_or_create_team_member("hubot".to_string()).await.unwrap();
+        db.update_team_member_info(hubot.id, "", Some("Mona Lisa")).await.unwrap();
+        for (number, title, author) in [
+            (1, "Raise rate limit to 100%", octocat.id),
+            (2, "Raise rate limit to 1000", octocat.id),
+            (3, "Rename user_id column", hubot.id),
+            (4, "Rename userXid helper", octocat.id),
+        ] {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                title: Some(title.to_string()),
+                author_id: author,
+                ..Default::default()
+            }).await.unwrap();
+        }
+        let numbers = |hits: Vec<SearchHit>| {
+            let mut numbers: Vec<i64> = hits.iter().map(|hit| hit.pull_request.pr_number).collect();
+            numbers.sort();
+            numbers
+        };
+
+        // Logins and display names, partially and in any case
+        assert_eq!(numbers(db.search_pull_requests("OCTO").await.unwrap()), vec![1, 2, 4]);
+        assert_eq!(numbers(db.search_pull_requests("lisa").await.unwrap()), vec![3]);
+
+        // % and _ are literal characters, not wildcards
+        assert_eq!(numbers(db.search_pull_requests("0%").await.unwrap()), vec![1]);
+        assert_eq!(numbers(db.search_pull_requests("user_id").await.unwrap()), vec![3]);
+        let hits = db.search_pull_requests("r_i").await.unwrap();
+        assert_eq!(numbers(hits.clone()), vec![3]);
+        assert_eq!((hits[0].snippet.as_str(), hits[0].score), ("Rename use**r_i**d column", 0.0));
+
+        // Indexed matches rank first and aren't repeated
+        let hits = db.search_pull_requests("rename").await.unwrap();
+        assert_eq!(numbers(hits.clone()), vec![3, 4]);
+        assert!(hits.iter().all(|hit| hit.score > 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_title_matches_first() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let mut prs = Vec::new();
+        for (number, title, branch) in [
+            (1, "Refactor request logging", "chore/logging"),
+            (2, "Fix cache invalidation in the session store", "fix/session-cache"),
+            (3, "Add billing page", "feat/billing"),
+            (4, "Bump dependencies", "caching-deps"),
+        ] {
+            prs.push(db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                title: Some(title.to_string()),
+                branch: Some(branch.to_string()),
+                author_id: author.id,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some("api".to_string()),
+                ..Default::default()
+            }).await.unwrap());
+        }
+        db.add_pr_comment(&WriteContext::default(), prs[0].id, author.id, "The cache warmup in this path looks slow, worth a second look before merging".to_string())
+            .await.unwrap();
+
+        // Title and branch beat a mention in a note; porter stemming matches "caching"
+        let hits = db.search_pull_requests("cache").await.unwrap();
+        let numbers: Vec<i64> = hits.iter().map(|hit| hit.pull_request.pr_number).collect();
+        assert_eq!(numbers, vec![2, 4, 1]);
+        assert!(hits.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(hits[2].snippet.contains("**cache**"), "{}", hits[2].snippet);
+
+        // Every word must match, the last one as a prefix
+        let numbers: Vec<i64> = db.search_pull_requests("session sto").await.unwrap()
+            .iter().map(|hit| hit.pull_request.pr_number).collect();
+        assert_eq!(numbers, vec![2]);
+
+        // FTS syntax is searched as text rather than failing the query
+        assert!(db.search_pull_requests("billing\" (").await.unwrap().iter().any(|hit| hit.pull_request.pr_number == 3));
+        assert!(db.search_pull_requests("NEAR(").await.unwrap().is_empty());
+        assert!(db.search_pull_requests("  ").await.unwrap().is_empty());
+
+        // Edits and deletes reach the index through the triggers
+        db.set_pr_field_locally(&WriteContext::default(), prs[2].id, crate::database::sync_conflicts::SyncField::Title, "Add invoice page").await.unwrap();
+        let hits = db.search_pull_requests("invoice").await.unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.pull_request.pr_number).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(hits[0].snippet, "Add **invoice** page");
+        db.delete_pull_request(&WriteContext::default(), prs[0].id).await.unwrap();
+        assert!(db.search_pull_requests("warmup").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,296 @@
+// Every key kept in the settings table: its feature area, whether users may set it, and the
+// value in effect while no row is stored. Getters take their fallbacks from here, and
+// get_settings_diff compares against the same values, so a default lives in one place.
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::actor::LOCAL_IDENTITY_KEY;
+use super::author_backlog::AUTHOR_BACKLOG_THRESHOLD_KEY;
+use super::import_defaults::{MergedImportScoring, IMPORT_OPEN_STATUS_KEY, MERGED_IMPORT_SCORING_KEY};
+use super::onboarding::{LAST_TOKEN_CHECK_KEY, ONBOARDING_COMPLETE_KEY};
+use super::pr_links::{BlockingLinksEnforcement, BLOCKING_LINKS_KEY};
+use super::repo_mappings::{DEFAULT_STRICT_REPO_MAPPING, STRICT_REPO_MAPPING_KEY};
+use super::sync_conflicts::{SyncPolicies, SYNC_POLICIES_KEY};
+use super::sync_constraints::{SyncConstraints, SYNC_CONSTRAINTS_KEY};
+use super::transitions::{STATUS_TRANSITIONS_KEY, STRICT_STATUS_TRANSITIONS_KEY};
+use super::tray::STALE_THRESHOLD_KEY;
+use super::{Database, BUSY_TIMEOUT_KEY, CONFIRM_RESET_SETTINGS};
+use crate::bounded_cache::{CacheLimits, CACHE_LIMITS_KEY};
+use crate::display_time::{system_timezone, DISPLAY_TIMEZONE_KEY};
+use crate::github::rate_limit::{RateBudgets, RATE_LIMIT_BUDGETS_KEY, RATE_LIMIT_STATE_KEY};
+use crate::github::token_grants::TOKEN_EXPIRY_KEY;
+use crate::title_rules::{self, TITLE_RULES_KEY};
+
+pub const DEFAULT_PROJECT_KEY: &str = "default_project_id";
+pub const SYNC_INTERVAL_KEY: &str = "sync_interval_minutes";
+pub const STATUS_ORDER_KEY: &str = "status_order";
+
+pub const DEFAULT_STALE_THRESHOLD_DAYS: i64 = 7;
+// More open PRs than this for one author raises a backlog alert
+pub const DEFAULT_AUTHOR_BACKLOG_THRESHOLD: i64 = 5;
+// How long a connection waits on a locked database before failing with "database is locked".
+// Higher values mean fewer lock errors during VACUUM or on slow disks, but slower failures.
+pub const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5_000;
+pub const DEFAULT_IMPORT_OPEN_STATUS: &str = "Waiting";
+pub const DEFAULT_STRICT_STATUS_TRANSITIONS: bool = false;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingArea {
+    General,
+    Display,
+    Import,
+    Workflow,
+    RateLimits,
+    Database,
+    Internal, // state the app keeps for itself; never shown in the diff or reset
+}
+
+pub struct SettingSpec {
+    pub key: &'static str,
+    pub area: SettingArea,
+    pub user_editable: bool,
+    default: fn() -> Option<String>, // None: the feature is off or has nothing to fall back to
+}
+
+impl SettingSpec {
+    pub fn default_value(&self) -> Option<String> {
+        (self.default)()
+    }
+}
+
+const fn user(key: &'static str, area: SettingArea, default: fn() -> Option<String>) -> SettingSpec {
+    SettingSpec { key, area, user_editable: true, default }
+}
+
+const fn internal(key: &'static str) -> SettingSpec {
+    SettingSpec { key, area: SettingArea::Internal, user_editable: false, default: || None }
+}
+
+pub const SETTINGS: [SettingSpec; 23] = [
+    user(DEFAULT_PROJECT_KEY, SettingArea::General, || None),
+    user(SYNC_INTERVAL_KEY, SettingArea::General, || None),
+    user(LOCAL_IDENTITY_KEY, SettingArea::General, || None),
+    user(SYNC_CONSTRAINTS_KEY, SettingArea::General, || serde_json::to_string(&SyncConstraints::default()).ok()),
+    user(SYNC_POLICIES_KEY, SettingArea::General, || serde_json::to_string(&SyncPolicies::default()).ok()),
+    user(STATUS_ORDER_KEY, SettingArea::Display, || None),
+    user(DISPLAY_TIMEZONE_KEY, SettingArea::Display, || Some(system_timezone().name().to_string())),
+    user(TITLE_RULES_KEY, SettingArea::Import, || Some(title_rules::default_rules_json())),
+    user(IMPORT_OPEN_STATUS_KEY, SettingArea::Import, || Some(DEFAULT_IMPORT_OPEN_STATUS.to_string())),
+    user(MERGED_IMPORT_SCORING_KEY, SettingArea::Import, || Some(MergedImportScoring::Unscored.to_string())),
+    user(STALE_THRESHOLD_KEY, SettingArea::Workflow, || Some(DEFAULT_STALE_THRESHOLD_DAYS.to_string())),
+    user(AUTHOR_BACKLOG_THRESHOLD_KEY, SettingArea::Workflow, || Some(DEFAULT_AUTHOR_BACKLOG_THRESHOLD.to_string())),
+    user(STATUS_TRANSITIONS_KEY, SettingArea::Workflow, || None),
+    user(STRICT_STATUS_TRANSITIONS_KEY, SettingArea::Workflow, || Some(DEFAULT_STRICT_STATUS_TRANSITIONS.to_string())),
+    user(BLOCKING_LINKS_KEY, SettingArea::Workflow, || Some(BlockingLinksEnforcement::default().to_string())),
+    user(STRICT_REPO_MAPPING_KEY, SettingArea::Workflow, || Some(DEFAULT_STRICT_REPO_MAPPING.to_string())),
+    user(RATE_LIMIT_BUDGETS_KEY, SettingArea::RateLimits, || serde_json::to_string(&RateBudgets::default()).ok()),
+    user(BUSY_TIMEOUT_KEY, SettingArea::Database, || Some(DEFAULT_BUSY_TIMEOUT_MS.to_string())),
+    user(CACHE_LIMITS_KEY, SettingArea::General, || serde_json::to_string(&CacheLimits::default()).ok()),
+    internal(RATE_LIMIT_STATE_KEY),
+    internal(TOKEN_EXPIRY_KEY),
+    internal(LAST_TOKEN_CHECK_KEY),
+    internal(ONBOARDING_COMPLETE_KEY),
+];
+
+pub fn lookup(key: &str) -> Option<&'static SettingSpec> {
+    SETTINGS.iter().find(|spec| spec.key == key)
+}
+
+/// Keys set_setting, import and reset accept
+pub fn is_user_setting(key: &str) -> bool {
+    lookup(key).is_some_and(|spec| spec.user_editable)
+}
+
+// JSON values match when they parse to the same thing ({"a": 1} vs {"a":1})
+fn same_value(stored: &str, default: &str) -> bool {
+    if stored.trim() == default.trim() {
+        return true;
+    }
+    match (serde_json::from_str::<serde_json::Value>(stored), serde_json::from_str::<serde_json::Value>(default)) {
+        (Ok(stored), Ok(default)) => stored == default,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SettingDiff {
+    pub key: String,
+    pub current: String,
+    pub default: Option<String>, // None when unset means "off"
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SettingsDiffGroup {
+    pub area: SettingArea,
+    pub settings: Vec<SettingDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SettingsDiff {
+    pub groups: Vec<SettingsDiffGroup>, // only areas with changes, in SettingArea order
+    pub unregistered: Vec<String>,      // stored keys this version doesn't know (left by older versions)
+}
+
+impl Database {
+    /// Every user setting whose stored value differs from its default, grouped by area
+    pub async fn get_settings_diff(&self) -> Result<SettingsDiff> {
+        let mut groups: BTreeMap<SettingArea, Vec<SettingDiff>> = BTreeMap::new();
+        let mut unregistered = Vec::new();
+        for (key, current) in self.get_all_settings().await? {
+            let Some(spec) = lookup(&key) else {
+                unregistered.push(key);
+                continue;
+            };
+            if !spec.user_editable {
+                continue;
+            }
+            let default = spec.default_value();
+            if default.as_deref().is_some_and(|default| same_value(&current, default)) {
+                continue;
+            }
+            groups.entry(spec.area).or_default().push(SettingDiff { key, current, default });
+        }
+
+        Ok(SettingsDiff {
+            groups: groups.into_iter().map(|(area, settings)| SettingsDiffGroup { area, settings }).collect(),
+            unregistered,
+        })
+    }
+
+    /// Drop one user setting so its default applies again
+    pub async fn reset_setting(&self, key: &str) -> Result<()> {
+        if !is_user_setting(key) {
+            return Err(anyhow::anyhow!("Unknown setting: {}", key));
+        }
+        sqlx::query("DELETE FROM settings WHERE key = ?")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        self.apply_setting_reset(key);
+        Ok(())
+    }
+
+    /// Drop every user setting (internal state is kept); returns the keys that were set.
+    /// Requires a CONFIRM_RESET_SETTINGS nonce and takes a safety backup first.
+    pub async fn reset_all_settings(&self, confirm_nonce: &str) -> Result<Vec<String>> {
+        self.consume_confirmation_nonce(confirm_nonce, CONFIRM_RESET_SETTINGS).await?;
+        self.create_safety_backup("reset-settings").await?;
+
+        let mut reset = Vec::new();
+        let mut tx = self.pool.begin().await?;
+        for spec in SETTINGS.iter().filter(|spec| spec.user_editable) {
+            let deleted = sqlx::query("DELETE FROM settings WHERE key = ?")
+                .bind(spec.key)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if deleted > 0 {
+                reset.push(spec.key.to_string());
+            }
+        }
+        tx.commit().await?;
+
+        for key in &reset {
+            self.apply_setting_reset(key);
+        }
+        println!("↩️ Reset settings to defaults: {:?}", reset);
+        Ok(reset)
+    }
+
+    // Settings cached in memory pick up their default straight away
+    fn apply_setting_reset(&self, key: &str) {
+        if key == BUSY_TIMEOUT_KEY {
+            self.busy_timeout_ms.store(DEFAULT_BUSY_TIMEOUT_MS, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    fn rust_sources(dir: &Path, out: &mut Vec<(String, String)>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                rust_sources(&path, out);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                out.push((path.display().to_string(), std::fs::read_to_string(&path).unwrap()));
+            }
+        }
+    }
+
+    // Reads the source tree: a key passed to get_setting/write_setting as a literal or a
+    // &str constant has to be registered. Variables (the settings commands) are skipped.
+    #[test]
+    fn test_every_setting_call_site_uses_a_registered_key() {
+        // src/, found from this file (src/database/settings_registry.rs)
+        let this_file = Path::new(env!("CARGO_MANIFEST_DIR")).join(file!());
+        let mut sources = Vec::new();
+        rust_sources(this_file.parent().and_then(Path::parent).unwrap(), &mut sources);
+
+        let constant = regex::Regex::new(r#"const ([A-Z_]+): &str = "([^"]+)";"#).unwrap();
+        let constants: HashMap<String, String> = sources.iter()
+            .flat_map(|(_, source)| constant.captures_iter(source).map(|c| (c[1].to_string(), c[2].to_string())).collect::<Vec<_>>())
+            .collect();
+
+        let call = regex::Regex::new(r"\b(?:get_setting|write_setting)\(\s*([^,)]+)").unwrap();
+        let mut checked = 0;
+        for (file, source) in &sources {
+            for captures in call.captures_iter(source) {
+                let argument = captures[1].trim().trim_start_matches('&');
+                let key = if let Some(literal) = argument.strip_prefix('"') {
+                    literal.trim_end_matches('"').to_string()
+                } else if argument.chars().all(|c| c.is_ascii_uppercase() || c == '_') {
+                    constants.get(argument).unwrap_or_else(|| panic!("{}: can't resolve {}", file, argument)).clone()
+                } else {
+                    continue;
+                };
+                assert!(lookup(&key).is_some(), "{} reads unregistered setting '{}'; add it to SETTINGS", file, key);
+                checked += 1;
+            }
+        }
+        assert!(checked > 10, "only {} call sites found; is the pattern still right?", checked);
+    }
+
+    #[tokio::test]
+    async fn test_settings_diff_and_reset() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_settings_diff().await.unwrap(), SettingsDiff::default());
+
+        // Setting a default explicitly (or the same JSON spelled differently) isn't a change
+        db.set_setting(STALE_THRESHOLD_KEY, "7").await.unwrap();
+        db.set_setting(RATE_LIMIT_BUDGETS_KEY, r#"{"import": 20, "interactive": 50, "background_sync": 30}"#).await.unwrap();
+        db.set_setting(IMPORT_OPEN_STATUS_KEY, "Reviewing").await.unwrap();
+        db.set_setting(DEFAULT_PROJECT_KEY, "3").await.unwrap();
+        db.set_busy_timeout(20_000).await.unwrap();
+        db.save_token_expiry(Some(1_700_000_000)).await.unwrap();
+
+        let diff = db.get_settings_diff().await.unwrap();
+        let areas: Vec<(SettingArea, Vec<&str>)> = diff.groups.iter()
+            .map(|group| (group.area, group.settings.iter().map(|s| s.key.as_str()).collect()))
+            .collect();
+        assert_eq!(areas, vec![
+            (SettingArea::General, vec![DEFAULT_PROJECT_KEY]),
+            (SettingArea::Import, vec![IMPORT_OPEN_STATUS_KEY]),
+            (SettingArea::Database, vec![BUSY_TIMEOUT_KEY]),
+        ]);
+        assert_eq!(diff.groups[1].settings[0].default.as_deref(), Some(DEFAULT_IMPORT_OPEN_STATUS));
+
+        db.reset_setting(BUSY_TIMEOUT_KEY).await.unwrap();
+        assert_eq!(db.get_busy_timeout(), DEFAULT_BUSY_TIMEOUT_MS);
+        assert!(db.reset_setting(TOKEN_EXPIRY_KEY).await.is_err());
+
+        assert!(db.reset_all_settings("bogus").await.is_err());
+        let nonce = db.issue_confirmation_nonce(CONFIRM_RESET_SETTINGS).await.unwrap();
+        let reset = db.reset_all_settings(&nonce).await.unwrap();
+        assert_eq!(reset.len(), 4);
+        assert_eq!(db.get_settings_diff().await.unwrap(), SettingsDiff::default());
+        assert_eq!(db.get_saved_token_expiry().await.unwrap(), Some(1_700_000_000));
+    }
+}
@@ -0,0 +1,153 @@
+// PR statuses as a type. The board columns plus the two closed statuses are the only ones a
+// move may target; update_pr_status rejects anything else, so a typo can't start a column
+// nobody filters on. Action is the "changes requested" column, and a PR closed without
+// merging is archived. The canonical string is what SQLite stores and the UI receives.
+// Rows written before this check may hold other strings. Those read as Unknown and are
+// passed through unchanged, never dropped. Writes go through the enum as well, so a known
+// status is always stored in its canonical spelling.
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum PrStatus {
+    #[default]
+    Waiting,
+    Reviewing,
+    Action,
+    Approved,
+    Merged,
+    Archived,
+    Unknown(String),
+}
+
+impl PrStatus {
+    pub const KNOWN: [PrStatus; 6] = [
+        PrStatus::Waiting,
+        PrStatus::Reviewing,
+        PrStatus::Action,
+        PrStatus::Approved,
+        PrStatus::Merged,
+        PrStatus::Archived,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            PrStatus::Waiting => "Waiting",
+            PrStatus::Reviewing => "Reviewing",
+            PrStatus::Action => "Action",
+            PrStatus::Approved => "Approved",
+            PrStatus::Merged => "Merged",
+            PrStatus::Archived => "archived",
+            PrStatus::Unknown(other) => other,
+        }
+    }
+
+    /// A status typed by a user or sent by the UI; case and surrounding spaces don't matter,
+    /// anything that isn't a known status is an error listing the valid ones
+    pub fn parse_input(input: &str) -> Result<PrStatus> {
+        match PrStatus::from(input.trim()) {
+            PrStatus::Unknown(_) => Err(anyhow::anyhow!(
+                "Unknown status '{}'. Valid statuses: {}",
+                input,
+                PrStatus::KNOWN.iter().map(PrStatus::as_str).collect::<Vec<_>>().join(", ")
+            )),
+            status => Ok(status),
+        }
+    }
+}
+
+// Reading never fails: legacy spellings ("waiting", "Archived") map to the known status
+impl From<&str> for PrStatus {
+    fn from(value: &str) -> Self {
+        PrStatus::KNOWN.into_iter()
+            .find(|known| known.as_str().eq_ignore_ascii_case(value))
+            .unwrap_or_else(|| PrStatus::Unknown(value.to_string()))
+    }
+}
+
+impl From<String> for PrStatus {
+    fn from(value: String) -> Self {
+        PrStatus::from(value.as_str())
+    }
+}
+
+impl From<PrStatus> for String {
+    fn from(status: PrStatus) -> Self {
+        match status {
+            PrStatus::Unknown(other) => other,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+// `"Reviewing".parse::<PrStatus>()`: the strict parse, same as parse_input
+impl FromStr for PrStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        PrStatus::parse_input(input)
+    }
+}
+
+impl fmt::Display for PrStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for PrStatus {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for PrStatus {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_round_trip() {
+        for status in PrStatus::KNOWN {
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(serde_json::from_str::<PrStatus>(&json).unwrap(), status);
+        }
+        assert_eq!(serde_json::to_string(&PrStatus::Archived).unwrap(), "\"archived\"");
+        assert_eq!(serde_json::to_string(&PrStatus::Action).unwrap(), "\"Action\"");
+    }
+
+    #[test]
+    fn test_legacy_strings_read_without_failing() {
+        let read = |json: &str| serde_json::from_str::<PrStatus>(json).unwrap();
+        assert_eq!(read("\"waiting\""), PrStatus::Waiting);
+        assert_eq!(read("\"Archived\""), PrStatus::Archived);
+        assert_eq!(read("\"Reviwing\""), PrStatus::Unknown("Reviwing".to_string()));
+        // Unknown strings go back out exactly as stored
+        assert_eq!(serde_json::to_string(&read("\"QA\"")).unwrap(), "\"QA\"");
+    }
+
+    #[test]
+    fn test_parse_input_rejects_unknown_statuses() {
+        assert_eq!(PrStatus::parse_input(" reviewing ").unwrap(), PrStatus::Reviewing);
+        let err = PrStatus::parse_input("Reviwing").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown status 'Reviwing'. Valid statuses: Waiting, Reviewing, Action, Approved, Merged, archived"
+        );
+        assert!(PrStatus::parse_input("").is_err());
+        assert_eq!("ACTION".parse::<PrStatus>().unwrap(), PrStatus::Action);
+        assert!("Reviewng".parse::<PrStatus>().is_err());
+        for status in PrStatus::KNOWN {
+            assert_eq!(status.to_string().parse::<PrStatus>().unwrap(), status);
+        }
+    }
+}
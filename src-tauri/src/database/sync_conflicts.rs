@@ -0,0 +1,353 @@
+// What a refresh does when GitHub's title or branch disagrees with a local edit. Manual
+// edits mark the field in pull_requests.locally_edited; only marked fields consult the
+// per-field policy in sync_conflict_policies:
+//   github-wins (default)  GitHub's value replaces the edit and the mark is dropped
+//   local-wins             the edit stays
+//   prompt                 the edit stays and the GitHub value waits in sync_conflicts
+//                          until resolve_sync_conflict picks one
+// Unmarked fields always take GitHub's value. Reimports and issue-to-PR conversions write
+// a fresh row from GitHub and start with nothing marked.
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqliteConnection};
+
+use super::actor::WriteContext;
+use super::{display_key, Database};
+use crate::title_rules;
+
+pub const SYNC_POLICIES_KEY: &str = "sync_conflict_policies";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncField {
+    Title,
+    Branch,
+}
+
+impl SyncField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SyncField::Title => "title",
+            SyncField::Branch => "branch",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "title" => Some(SyncField::Title),
+            "branch" => Some(SyncField::Branch),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncPolicy {
+    #[default]
+    GithubWins,
+    LocalWins,
+    Prompt,
+}
+
+/// Setting value, e.g. {"title": "prompt", "branch": "github-wins"}
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncPolicies {
+    pub title: SyncPolicy,
+    pub branch: SyncPolicy,
+}
+
+impl SyncPolicies {
+    pub fn parse(value: &str) -> Result<Self> {
+        serde_json::from_str(value).map_err(|e| anyhow::anyhow!("Invalid sync conflict policies: {}", e))
+    }
+
+    pub fn for_field(&self, field: SyncField) -> SyncPolicy {
+        match field {
+            SyncField::Title => self.title,
+            SyncField::Branch => self.branch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SyncConflict {
+    pub id: i64,
+    pub pr_id: i64,
+    pub display_key: String,
+    pub field: SyncField,
+    pub local_value: Option<String>,
+    pub github_value: String,
+    pub detected_at: i64, // when GitHub first reported this value
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictChoice {
+    Local,
+    Github,
+}
+
+fn edited_fields(value: Option<&str>) -> BTreeSet<SyncField> {
+    value
+        .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|field| SyncField::parse(field))
+        .collect()
+}
+
+fn edited_json(fields: &BTreeSet<SyncField>) -> Option<String> {
+    let names: Vec<&str> = fields.iter().map(|field| field.as_str()).collect();
+    (!names.is_empty()).then(|| serde_json::to_string(&names).unwrap_or_default())
+}
+
+// The values a PR row ends up with after a sync decision, written in one statement
+struct SyncedFields {
+    title: Option<String>,
+    branch: Option<String>,
+    edited: BTreeSet<SyncField>,
+}
+
+impl SyncedFields {
+    fn get(&self, field: SyncField) -> Option<&str> {
+        match field {
+            SyncField::Title => self.title.as_deref(),
+            SyncField::Branch => self.branch.as_deref(),
+        }
+    }
+
+    fn set(&mut self, field: SyncField, value: &str) {
+        let value = Some(value.to_string());
+        match field {
+            SyncField::Title => self.title = value,
+            SyncField::Branch => self.branch = value,
+        }
+    }
+}
+
+async fn read_synced_fields(conn: &mut SqliteConnection, pr_id: i64) -> Result<SyncedFields> {
+    let row = sqlx::query("SELECT title, branch, locally_edited FROM pull_requests WHERE id = ?")
+        .bind(pr_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))?;
+    Ok(SyncedFields {
+        title: row.get("title"),
+        branch: row.get("branch"),
+        edited: edited_fields(row.get::<Option<String>, _>("locally_edited").as_deref()),
+    })
+}
+
+async fn delete_conflict(conn: &mut SqliteConnection, pr_id: i64, field: SyncField) -> Result<()> {
+    sqlx::query("DELETE FROM sync_conflicts WHERE pr_id = ? AND field = ?")
+        .bind(pr_id)
+        .bind(field.as_str())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// Title and branch plus what's derived from the title, in one UPDATE
+async fn write_synced_fields(
+    conn: &mut SqliteConnection,
+    pr_id: i64,
+    fields: &SyncedFields,
+    rules: &[regex::Regex],
+    github_updated_at: Option<i64>,
+) -> Result<()> {
+    let parsed = fields.title.as_deref()
+        .map(|title| title_rules::parse_title(rules, title))
+        .unwrap_or_default();
+    sqlx::query(
+        r#"
+        UPDATE pull_requests SET
+            title = ?, branch = ?, locally_edited = ?, github_updated_at = COALESCE(?, github_updated_at),
+            commit_type = ?, commit_scope = ?, ticket_key = ?, last_updated_at = ?
+        WHERE id = ?
+        "#
+    )
+    .bind(&fields.title)
+    .bind(&fields.branch)
+    .bind(edited_json(&fields.edited))
+    .bind(github_updated_at)
+    .bind(&parsed.commit_type)
+    .bind(&parsed.commit_scope)
+    .bind(&parsed.ticket_key)
+    .bind(chrono::Utc::now().timestamp())
+    .bind(pr_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+impl Database {
+    /// An unreadable value falls back to github-wins everywhere
+    pub async fn get_sync_policies(&self) -> Result<SyncPolicies> {
+        Ok(self.get_setting(SYNC_POLICIES_KEY).await?
+            .and_then(|value| SyncPolicies::parse(&value).ok())
+            .unwrap_or_default())
+    }
+
+    /// A manual edit: sets the value, marks the field as locally edited and drops any
+    /// GitHub value waiting for it, since the person editing has just decided. Logged to
+    /// review_history as field_edited:<field> under the editor's name.
+    pub async fn set_pr_field_locally(&self, ctx: &WriteContext, pr_id: i64, field: SyncField, value: &str) -> Result<()> {
+        let value = value.trim();
+        if field == SyncField::Title && value.is_empty() {
+            return Err(anyhow::anyhow!("Title cannot be empty"));
+        }
+        let rules = self.get_title_rules().await?;
+        let mut tx = self.pool.begin().await?;
+        let mut fields = read_synced_fields(&mut tx, pr_id).await?;
+        fields.set(field, value);
+        fields.edited.insert(field);
+        write_synced_fields(&mut tx, pr_id, &fields, &rules, None).await?;
+        delete_conflict(&mut tx, pr_id, field).await?;
+        sqlx::query("INSERT INTO review_history (pr_id, action, performed_at, actor) VALUES (?, ?, ?, ?)")
+            .bind(pr_id)
+            .bind(format!("field_edited:{}", field.as_str()))
+            .bind(chrono::Utc::now().timestamp())
+            .bind(&ctx.actor)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Apply title and branch from a GitHub refresh, following the sync policies for
+    /// locally edited fields. Returns the fields left waiting in sync_conflicts.
+    pub async fn update_pr_github_fields(&self, pr_id: i64, title: &str, branch: &str, github_updated_at: Option<i64>) -> Result<Vec<SyncField>> {
+        let policies = self.get_sync_policies().await?;
+        let rules = self.get_title_rules().await?;
+        let now = chrono::Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+        let mut fields = read_synced_fields(&mut tx, pr_id).await?;
+        let mut prompted = Vec::new();
+
+        for (field, github_value) in [(SyncField::Title, title), (SyncField::Branch, branch)] {
+            let agrees = fields.get(field) == Some(github_value);
+            let policy = if fields.edited.contains(&field) && !agrees {
+                policies.for_field(field)
+            } else {
+                SyncPolicy::GithubWins
+            };
+            match policy {
+                SyncPolicy::GithubWins => {
+                    fields.set(field, github_value);
+                    fields.edited.remove(&field);
+                    delete_conflict(&mut tx, pr_id, field).await?;
+                }
+                SyncPolicy::LocalWins => delete_conflict(&mut tx, pr_id, field).await?,
+                SyncPolicy::Prompt => {
+                    // detected_at only moves when GitHub reports a different value
+                    sqlx::query(
+                        r#"
+                        INSERT INTO sync_conflicts (pr_id, field, local_value, github_value, detected_at)
+                        VALUES (?, ?, ?, ?, ?)
+                        ON CONFLICT(pr_id, field) DO UPDATE SET
+                            local_value = excluded.local_value,
+                            detected_at = CASE WHEN github_value = excluded.github_value THEN detected_at ELSE excluded.detected_at END,
+                            github_value = excluded.github_value
+                        "#
+                    )
+                    .bind(pr_id)
+                    .bind(field.as_str())
+                    .bind(fields.get(field))
+                    .bind(github_value)
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await?;
+                    prompted.push(field);
+                }
+            }
+        }
+
+        write_synced_fields(&mut tx, pr_id, &fields, &rules, github_updated_at).await?;
+        tx.commit().await?;
+        Ok(prompted)
+    }
+
+    /// GitHub values held back under the prompt policy, oldest first
+    pub async fn get_sync_conflicts(&self) -> Result<Vec<SyncConflict>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT c.id, c.pr_id, c.field, c.local_value, c.github_value, c.detected_at,
+                   pr.repository_owner, pr.repository_name, pr.pr_number
+            FROM sync_conflicts c
+            JOIN pull_requests pr ON pr.id = c.pr_id
+            ORDER BY c.detected_at, c.id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter()
+            .filter_map(|row| {
+                let owner: Option<String> = row.get("repository_owner");
+                let repo: Option<String> = row.get("repository_name");
+                Some(SyncConflict {
+                    id: row.get("id"),
+                    pr_id: row.get("pr_id"),
+                    display_key: display_key(owner.as_deref(), repo.as_deref(), row.get("pr_number"), false),
+                    field: SyncField::parse(row.get::<&str, _>("field"))?,
+                    local_value: row.get("local_value"),
+                    github_value: row.get("github_value"),
+                    detected_at: row.get("detected_at"),
+                })
+            })
+            .collect())
+    }
+
+    /// Settle a conflict. Choosing GitHub writes its value and clears the edit mark, so later
+    /// refreshes update the field again; choosing local keeps the edit and its mark.
+    pub async fn resolve_sync_conflict(&self, id: i64, choose: ConflictChoice) -> Result<()> {
+        let rules = self.get_title_rules().await?;
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query("DELETE FROM sync_conflicts WHERE id = ? RETURNING pr_id, field, github_value")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Sync conflict {} not found", id))?;
+        let pr_id: i64 = row.get("pr_id");
+        let field_name: String = row.get("field");
+        let field = SyncField::parse(&field_name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown sync field '{}'", field_name))?;
+
+        if choose == ConflictChoice::Github {
+            let github_value: String = row.get("github_value");
+            let mut fields = read_synced_fields(&mut tx, pr_id).await?;
+            fields.set(field, &github_value);
+            fields.edited.remove(&field);
+            write_synced_fields(&mut tx, pr_id, &fields, &rules, None).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policies_parse() {
+        assert_eq!(SyncPolicies::parse("{}").unwrap(), SyncPolicies::default());
+        let policies = SyncPolicies::parse(r#"{"title": "prompt", "branch": "local-wins"}"#).unwrap();
+        assert_eq!((policies.title, policies.branch), (SyncPolicy::Prompt, SyncPolicy::LocalWins));
+        assert!(SyncPolicies::parse(r#"{"title": "ask"}"#).is_err());
+        assert!(SyncPolicies::parse(r#"{"body": "prompt"}"#).is_err());
+    }
+
+    #[test]
+    fn test_edited_fields_round_trip() {
+        let fields: BTreeSet<SyncField> = [SyncField::Branch, SyncField::Title].into_iter().collect();
+        assert_eq!(edited_json(&fields).as_deref(), Some(r#"["title","branch"]"#));
+        assert_eq!(edited_fields(edited_json(&fields).as_deref()), fields);
+        assert_eq!(edited_json(&BTreeSet::new()), None);
+        assert!(edited_fields(Some("not json")).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+// When the background scheduler should hold back: on battery, on a metered connection, or
+// more often than every few minutes while on battery. Stored as JSON under sync_constraints;
+// with no power data (services/power.rs) none of this applies and syncs run normally.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+pub const SYNC_CONSTRAINTS_KEY: &str = "sync_constraints";
+
+// A day; longer would leave a laptop unsynced from one charge to the next
+const MAX_BATTERY_INTERVAL_MINUTES: u32 = 1_440;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConstraints {
+    pub pause_on_battery: bool,
+    pub pause_on_metered: bool,
+    pub min_interval_on_battery: Option<u32>, // minutes between runs on battery when not paused
+}
+
+impl SyncConstraints {
+    /// Setting value, e.g. {"pause_on_battery": false, "pause_on_metered": true, "min_interval_on_battery": 30}
+    pub fn parse(value: &str) -> Result<Self> {
+        let constraints: SyncConstraints = serde_json::from_str(value)
+            .map_err(|e| anyhow::anyhow!("Invalid sync constraints: {}", e))?;
+        if constraints.min_interval_on_battery.is_some_and(|minutes| !(1..=MAX_BATTERY_INTERVAL_MINUTES).contains(&minutes)) {
+            anyhow::bail!("Minimum interval on battery must be between 1 and {} minutes", MAX_BATTERY_INTERVAL_MINUTES);
+        }
+        Ok(constraints)
+    }
+
+    /// False when nothing is constrained, so the power state needn't be looked up
+    pub fn is_active(&self) -> bool {
+        self.pause_on_battery || self.pause_on_metered || self.min_interval_on_battery.is_some()
+    }
+}
+
+impl Database {
+    /// An unreadable value falls back to no constraints rather than stopping syncs
+    pub async fn get_sync_constraints(&self) -> Result<SyncConstraints> {
+        Ok(self.get_setting(SYNC_CONSTRAINTS_KEY).await?
+            .and_then(|value| SyncConstraints::parse(&value).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn set_sync_constraints(&self, constraints: &SyncConstraints) -> Result<()> {
+        self.set_setting(SYNC_CONSTRAINTS_KEY, &serde_json::to_string(constraints)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_constraints_parse_and_fall_back() {
+        assert_eq!(SyncConstraints::parse("{}").unwrap(), SyncConstraints::default());
+        let parsed = SyncConstraints::parse(r#"{"pause_on_metered": true, "min_interval_on_battery": 45}"#).unwrap();
+        assert_eq!(parsed, SyncConstraints { pause_on_battery: false, pause_on_metered: true, min_interval_on_battery: Some(45) });
+        assert!(SyncConstraints::parse(r#"{"min_interval_on_battery": 0}"#).is_err());
+        assert!(SyncConstraints::parse("battery").is_err());
+
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_sync_constraints().await.unwrap(), SyncConstraints::default());
+        db.set_sync_constraints(&parsed).await.unwrap();
+        assert_eq!(db.get_sync_constraints().await.unwrap(), parsed);
+        assert!(db.set_setting(SYNC_CONSTRAINTS_KEY, r#"{"min_interval_on_battery": 5000}"#).await.is_err());
+    }
+}
@@ -0,0 +1,157 @@
+// What each background sync run changed, kept so a merge that happened overnight
+// is still visible the next morning. Runs older than SYNC_RUN_RETENTION_DAYS are
+// pruned by maintenance.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+
+use super::Database;
+
+pub const SYNC_RUN_RETENTION_DAYS: i64 = 30;
+
+// Only the first few errors are kept; `failed` still counts all of them
+const MAX_STORED_ERRORS: usize = 20;
+
+/// One PR a sync run changed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncChange {
+    pub pr_id: i64,
+    pub display_key: String, // always "owner/repo#512", runs outlive the PR list
+    pub title: Option<String>,
+    pub merged: bool,        // moved to Merged by this run
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewSyncRun {
+    pub kind: String, // the ApiFeature that ran it: "merge_sync", "scheduled_refresh"
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub refreshed: i64,
+    pub failed: i64,
+    pub errors: Vec<String>,
+    pub changes: Vec<SyncChange>,
+    pub skipped_reason: Option<String>, // set when the run was held back by the sync constraints
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SyncRun {
+    pub id: i64,
+    pub kind: String,
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub refreshed: i64,
+    pub changed: i64,
+    pub merged: i64,
+    pub failed: i64,
+    pub errors: Vec<String>,
+    pub changes: Vec<SyncChange>,
+    pub skipped_reason: Option<String>,
+}
+
+fn sync_run_from_row(row: &SqliteRow) -> SyncRun {
+    let errors: String = row.get("errors");
+    let changes: String = row.get("changes");
+    SyncRun {
+        id: row.get("id"),
+        kind: row.get("kind"),
+        started_at: row.get("started_at"),
+        finished_at: row.get("finished_at"),
+        refreshed: row.get("refreshed"),
+        changed: row.get("changed"),
+        merged: row.get("merged"),
+        failed: row.get("failed"),
+        errors: serde_json::from_str(&errors).unwrap_or_default(),
+        changes: serde_json::from_str(&changes).unwrap_or_default(),
+        skipped_reason: row.get("skipped_reason"),
+    }
+}
+
+impl Database {
+    pub async fn record_sync_run(&self, run: &NewSyncRun) -> Result<i64> {
+        let errors: Vec<&String> = run.errors.iter().take(MAX_STORED_ERRORS).collect();
+        let result = sqlx::query(
+            "INSERT INTO sync_runs (kind, started_at, finished_at, refreshed, changed, merged, failed, errors, changes, skipped_reason)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&run.kind)
+        .bind(run.started_at)
+        .bind(run.finished_at)
+        .bind(run.refreshed)
+        .bind(run.changes.len() as i64)
+        .bind(run.changes.iter().filter(|c| c.merged).count() as i64)
+        .bind(run.failed)
+        .bind(serde_json::to_string(&errors)?)
+        .bind(serde_json::to_string(&run.changes)?)
+        .bind(&run.skipped_reason)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Most recent runs first
+    pub async fn get_recent_sync_runs(&self, limit: i64) -> Result<Vec<SyncRun>> {
+        let rows = sqlx::query("SELECT * FROM sync_runs ORDER BY finished_at DESC, id DESC LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(sync_run_from_row).collect())
+    }
+
+    pub async fn get_last_sync_run(&self) -> Result<Option<SyncRun>> {
+        Ok(self.get_recent_sync_runs(1).await?.pop())
+    }
+
+    pub async fn prune_sync_runs(&self, now: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM sync_runs WHERE finished_at < ?")
+            .bind(now - SYNC_RUN_RETENTION_DAYS * 86_400)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    fn change(pr_id: i64, merged: bool) -> SyncChange {
+        SyncChange { pr_id, display_key: format!("acme/api#{}", pr_id), title: None, merged }
+    }
+
+    #[tokio::test]
+    async fn test_sync_runs_are_kept_for_30_days() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_last_sync_run().await.unwrap(), None);
+
+        let now = 100 * DAY;
+        db.record_sync_run(&NewSyncRun {
+            kind: "merge_sync".to_string(),
+            started_at: now - 31 * DAY,
+            finished_at: now - 31 * DAY,
+            refreshed: 3,
+            ..Default::default()
+        }).await.unwrap();
+        db.record_sync_run(&NewSyncRun {
+            kind: "merge_sync".to_string(),
+            started_at: now - 60,
+            finished_at: now,
+            refreshed: 12,
+            failed: 25,
+            errors: (0..25).map(|i| format!("error {}", i)).collect(),
+            changes: vec![change(1, true), change(2, false), change(3, true)],
+            ..Default::default()
+        }).await.unwrap();
+
+        let last = db.get_last_sync_run().await.unwrap().unwrap();
+        assert_eq!((last.refreshed, last.changed, last.merged, last.failed), (12, 3, 2, 25));
+        assert_eq!(last.errors.len(), MAX_STORED_ERRORS);
+        assert_eq!(last.changes[0], change(1, true));
+        assert_eq!(db.get_recent_sync_runs(10).await.unwrap().len(), 2);
+
+        assert_eq!(db.prune_sync_runs(now).await.unwrap(), 1);
+        assert_eq!(db.get_recent_sync_runs(10).await.unwrap().len(), 1);
+    }
+}
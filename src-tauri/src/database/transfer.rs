@@ -0,0 +1,593 @@
+// JSON export of projects and PRs, and merge-import with field-level conflict detection
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::actor::WriteContext;
+use super::lifecycle::record_status_move;
+use super::status::PrStatus;
+use super::waiting_on::follow_status;
+use super::{check_score, Database, NewPullRequest, PrPriority, PullRequest};
+
+pub const DATA_EXPORT_VERSION: i64 = 1;
+
+// Pending "theirs" values are kept this long before the import session expires
+const IMPORT_SESSION_TTL_SECS: i64 = 24 * 60 * 60;
+
+// User-owned PR fields compared when the same github_id exists on both sides
+const CONFLICT_FIELDS: [&str; 3] = ["status", "score", "priority"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataExport {
+    pub version: i64,
+    pub exported_at: i64,
+    pub projects: Vec<ExportedProject>,
+    pub pull_requests: Vec<ExportedPullRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedProject {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub members: Vec<String>, // usernames on the project's roster
+}
+
+// Ids differ between databases, so authors and projects travel by name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedPullRequest {
+    pub github_id: i64,
+    pub pr_number: i64,
+    #[serde(default)]
+    pub display_key: String, // informational; not read back on import
+    pub title: Option<String>,
+    pub author_username: String,
+    pub project_name: Option<String>,
+    pub status: String,
+    pub branch: Option<String>,
+    pub score: Option<i32>,
+    #[serde(default)]
+    pub exclude_from_scoring: bool,
+    pub priority: PrPriority,
+    pub repository_owner: Option<String>,
+    pub repository_name: Option<String>,
+    pub github_created_at: Option<i64>,
+    pub github_updated_at: Option<i64>,
+    #[serde(default)]
+    pub external_links: Vec<ExportedLink>,
+    #[serde(default)]
+    pub added_by: Option<String>,
+    // Informational, for debugging reports; not read back on import
+    #[serde(default)]
+    pub created_by_version: Option<String>,
+    #[serde(default)]
+    pub updated_by_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedLink {
+    pub label: String,
+    pub url: String,
+}
+
+// Values are rendered as text (None = empty score) so every field fits one shape
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportConflict {
+    pub pr_id: i64,
+    pub github_id: i64,
+    pub pr_number: i64,
+    pub title: Option<String>,
+    pub field: String,
+    pub mine: Option<String>,
+    pub theirs: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataImportResult {
+    pub session_id: Option<String>, // set when conflicts are waiting for apply_import_conflict_resolutions
+    pub expires_at: Option<i64>,
+    pub projects_created: u32,
+    pub prs_imported: u32,
+    pub prs_unchanged: u32,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictChoice {
+    Mine,
+    Theirs,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictResolution {
+    pub pr_id: i64,
+    pub field: String,
+    pub choose: ConflictChoice,
+}
+
+fn field_value(pr: &PullRequest, field: &str) -> Option<String> {
+    match field {
+        "status" => Some(pr.status.to_string()),
+        "score" => pr.score.map(|score| score.to_string()),
+        "priority" => Some(pr.priority.to_string()),
+        _ => None,
+    }
+}
+
+fn exported_field_value(pr: &ExportedPullRequest, field: &str) -> Option<String> {
+    match field {
+        "status" => Some(pr.status.clone()),
+        "score" => pr.score.map(|score| score.to_string()),
+        "priority" => Some(pr.priority.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_priority(value: &str) -> Result<PrPriority> {
+    match value {
+        "Low" => Ok(PrPriority::Low),
+        "Medium" => Ok(PrPriority::Medium),
+        "High" => Ok(PrPriority::High),
+        other => Err(anyhow::anyhow!("Invalid priority: {}", other)),
+    }
+}
+
+impl Database {
+    /// Export projects and PRs as JSON for merging into another database
+    pub async fn export_data_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.build_data_export().await?)?)
+    }
+
+    pub(super) async fn build_data_export(&self) -> Result<DataExport> {
+        let mut projects = Vec::new();
+        for project in self.get_projects().await? {
+            let members = self.get_project_members(project.id).await?
+                .into_iter()
+                .map(|member| member.github_username)
+                .collect();
+            projects.push(ExportedProject { name: project.name, description: project.description, members });
+        }
+        let mut pull_requests = Vec::new();
+        for pr in self.get_pull_requests().await? {
+            let external_links = self.get_pr_external_links(pr.id).await?
+                .into_iter()
+                .map(|link| ExportedLink { label: link.label, url: link.url })
+                .collect();
+            let (created_by_version, updated_by_version) = self.get_pr_row_versions(pr.id).await?;
+            pull_requests.push(ExportedPullRequest {
+                github_id: pr.github_id,
+                pr_number: pr.pr_number,
+                display_key: pr.display_key,
+                title: pr.title,
+                author_username: pr.author_name.unwrap_or_default(),
+                project_name: pr.project_name,
+                status: pr.status.into(),
+                branch: pr.branch,
+                score: pr.score,
+                exclude_from_scoring: pr.exclude_from_scoring,
+                priority: pr.priority,
+                repository_owner: pr.repository_owner,
+                repository_name: pr.repository_name,
+                github_created_at: pr.github_created_at,
+                github_updated_at: pr.github_updated_at,
+                external_links,
+                added_by: pr.added_by,
+                created_by_version,
+                updated_by_version,
+            });
+        }
+
+        Ok(DataExport {
+            version: DATA_EXPORT_VERSION,
+            exported_at: chrono::Utc::now().timestamp(),
+            projects,
+            pull_requests,
+        })
+    }
+
+    /// Merge an export into this database. New PRs and projects are added right away;
+    /// PRs whose status/score/priority differ are returned as conflicts and left untouched.
+    pub async fn import_data_json(&self, json: &str) -> Result<DataImportResult> {
+        let export: DataExport = serde_json::from_str(json)
+            .map_err(|e| anyhow::anyhow!("Invalid data export JSON: {}", e))?;
+        self.import_data_export(export).await
+    }
+
+    pub(super) async fn import_data_export(&self, export: DataExport) -> Result<DataImportResult> {
+        if export.version != DATA_EXPORT_VERSION {
+            return Err(anyhow::anyhow!("Unsupported data export version: {}", export.version));
+        }
+        // A typo'd status would start a column of its own and a score off the 1-10 scale would
+        // skew every average, so the whole file is refused up front
+        for theirs in &export.pull_requests {
+            PrStatus::parse_input(&theirs.status)
+                .and_then(|_| theirs.score.map(check_score).transpose())
+                .map_err(|e| anyhow::anyhow!("PR #{} in the export: {}", theirs.pr_number, e))?;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let importer = self.write_context().await?;
+        let mut result = DataImportResult::default();
+        let mut projects = self.get_projects().await?;
+
+        // Rosters are merged: people only on this side stay on the project
+        for exported in &export.projects {
+            let project_id = match projects.iter().find(|p| p.name == exported.name) {
+                Some(project) => project.id,
+                None => {
+                    let project = self.add_project(exported.name.clone(), exported.description.clone()).await?;
+                    result.projects_created += 1;
+                    let id = project.id;
+                    projects.push(project);
+                    id
+                }
+            };
+            for username in &exported.members {
+                let member = self.get_or_create_team_member(username.clone()).await?;
+                self.add_project_member(project_id, member.id).await?;
+            }
+        }
+
+        for theirs in &export.pull_requests {
+            if let Some(mine) = self.get_pull_request_by_github_id(theirs.github_id).await? {
+                let conflicts: Vec<ImportConflict> = CONFLICT_FIELDS.iter()
+                    .filter(|field| field_value(&mine, field) != exported_field_value(theirs, field))
+                    .map(|field| ImportConflict {
+                        pr_id: mine.id,
+                        github_id: mine.github_id,
+                        pr_number: mine.pr_number,
+                        title: mine.title.clone(),
+                        field: field.to_string(),
+                        mine: field_value(&mine, field),
+                        theirs: exported_field_value(theirs, field),
+                    })
+                    .collect();
+                if conflicts.is_empty() {
+                    result.prs_unchanged += 1;
+                }
+                result.conflicts.extend(conflicts);
+                continue;
+            }
+
+            let author = self.get_or_create_team_member(theirs.author_username.clone()).await?;
+            let project_id = match &theirs.project_name {
+                Some(name) => match projects.iter().find(|p| &p.name == name) {
+                    Some(project) => Some(project.id),
+                    None => {
+                        let project = self.add_project(name.clone(), None).await?;
+                        result.projects_created += 1;
+                        let id = project.id;
+                        projects.push(project);
+                        Some(id)
+                    }
+                },
+                None => None,
+            };
+
+            // Whoever added it on their side stays its adder; otherwise it's whoever imports
+            let ctx = match &theirs.added_by {
+                Some(added_by) => WriteContext::new(added_by.clone()),
+                None => importer.clone(),
+            };
+            let pr = self.add_pull_request(&ctx, NewPullRequest {
+                github_id: theirs.github_id,
+                pr_number: theirs.pr_number,
+                title: theirs.title.clone(),
+                author_id: author.id,
+                project_id,
+                branch: theirs.branch.clone(),
+                status: PrStatus::parse_input(&theirs.status)?,
+                repository_owner: theirs.repository_owner.clone(),
+                repository_name: theirs.repository_name.clone(),
+                github_created_at: theirs.github_created_at,
+                github_updated_at: theirs.github_updated_at,
+                ..Default::default()
+            }).await?;
+            sqlx::query("UPDATE pull_requests SET score = ?, priority = ?, exclude_from_scoring = ? WHERE id = ?")
+                .bind(theirs.score)
+                .bind(theirs.priority.as_i64())
+                .bind(theirs.exclude_from_scoring)
+                .bind(pr.id)
+                .execute(&self.pool)
+                .await?;
+            // Arrived merged and unscored: this database's policy decides, unless the export already excluded it
+            if theirs.status == "Merged" && theirs.score.is_none() && !theirs.exclude_from_scoring {
+                self.apply_merged_import_scoring(pr.id).await?;
+            }
+            for link in &theirs.external_links {
+                self.add_pr_external_link(pr.id, &link.label, &link.url).await?;
+            }
+            result.prs_imported += 1;
+        }
+
+        if !result.conflicts.is_empty() {
+            let (session_id, expires_at) = self.stash_import_conflicts(&result.conflicts, now).await?;
+            result.session_id = Some(session_id);
+            result.expires_at = Some(expires_at);
+        }
+
+        println!(
+            "📥 Imported {} PRs ({} unchanged, {} conflicts pending)",
+            result.prs_imported, result.prs_unchanged, result.conflicts.len()
+        );
+        Ok(result)
+    }
+
+    // Store the "theirs" side of each conflict under a new import session
+    async fn stash_import_conflicts(&self, conflicts: &[ImportConflict], now: i64) -> Result<(String, i64)> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM import_conflicts WHERE expires_at < ?")
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        let session_id: String = sqlx::query_scalar("SELECT lower(hex(randomblob(16)))")
+            .fetch_one(&mut *tx)
+            .await?;
+        let expires_at = now + IMPORT_SESSION_TTL_SECS;
+
+        for conflict in conflicts {
+            sqlx::query(
+                "INSERT INTO import_conflicts (session_id, pr_id, field, theirs, expires_at) VALUES (?, ?, ?, ?, ?)"
+            )
+            .bind(&session_id)
+            .bind(conflict.pr_id)
+            .bind(&conflict.field)
+            .bind(&conflict.theirs)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok((session_id, expires_at))
+    }
+
+    /// Apply the user's choices for an import session's conflicts, all or nothing.
+    /// Returns how many fields were overwritten with "theirs".
+    pub async fn apply_import_conflict_resolutions(&self, session_id: &str, resolutions: &[ConflictResolution]) -> Result<u32> {
+        let now = chrono::Utc::now().timestamp();
+        let importer = self.write_context().await?;
+        let mut tx = self.pool.begin().await?;
+        let mut applied = 0;
+
+        for resolution in resolutions {
+            let row = sqlx::query(
+                r#"
+                DELETE FROM import_conflicts
+                WHERE session_id = ? AND pr_id = ? AND field = ? AND expires_at >= ?
+                RETURNING theirs
+                "#
+            )
+            .bind(session_id)
+            .bind(resolution.pr_id)
+            .bind(&resolution.field)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(
+                "No pending conflict for PR {} field '{}' (expired or already resolved)",
+                resolution.pr_id, resolution.field
+            ))?;
+
+            if resolution.choose == ConflictChoice::Mine {
+                continue;
+            }
+
+            let theirs: Option<String> = row.get("theirs");
+            if resolution.field == "status" {
+                let status = PrStatus::parse_input(&theirs.ok_or_else(|| anyhow::anyhow!("Imported status is empty"))?)?;
+                let current: String = sqlx::query_scalar("SELECT status FROM pull_requests WHERE id = ?")
+                    .bind(resolution.pr_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Pull request {} not found", resolution.pr_id))?;
+                // Already there (moved since the import): nothing to write or log
+                if status == current.as_str() {
+                    continue;
+                }
+                sqlx::query("UPDATE pull_requests SET status = ? WHERE id = ?")
+                    .bind(status.as_str())
+                    .bind(resolution.pr_id)
+                    .execute(&mut *tx)
+                    .await?;
+                record_status_move(&mut tx, resolution.pr_id, &current, status.as_str(), &importer.actor, now).await?;
+                follow_status(&mut tx, resolution.pr_id, "status taken from an import", Some(&importer.actor), now).await?;
+                applied += 1;
+                continue;
+            }
+
+            let query = match resolution.field.as_str() {
+                "score" => {
+                    let score = theirs.map(|s| s.parse::<i32>()).transpose()?;
+                    score.map(check_score).transpose()?;
+                    sqlx::query("UPDATE pull_requests SET score = ? WHERE id = ?").bind(score)
+                }
+                "priority" => {
+                    let priority = parse_priority(theirs.as_deref().unwrap_or_default())?;
+                    sqlx::query("UPDATE pull_requests SET priority = ? WHERE id = ?").bind(priority.as_i64())
+                }
+                other => return Err(anyhow::anyhow!("Unknown conflict field: {}", other)),
+            };
+            query.bind(resolution.pr_id).execute(&mut *tx).await?;
+            applied += 1;
+        }
+
+        tx.commit().await?;
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::LOCAL_IDENTITY_KEY;
+
+    async fn seed(db: &Database) -> PullRequest {
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 100,
+            pr_number: 1,
+            author_id: author.id,
+            project_id: Some(project.id),
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_applies_merged_scoring_policy() {
+        use crate::database::import_defaults::MERGED_IMPORT_SCORING_KEY;
+
+        let theirs_db = Database::new_in_memory().await.unwrap();
+        let author = theirs_db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        for (github_id, status) in [(1, "Merged"), (2, "Merged"), (3, "Waiting")] {
+            theirs_db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id,
+                pr_number: github_id,
+                author_id: author.id,
+                status: status.into(),
+                ..Default::default()
+            }).await.unwrap();
+        }
+        let already_excluded = theirs_db.get_pull_request_by_github_id(2).await.unwrap().unwrap();
+        theirs_db.set_setting(MERGED_IMPORT_SCORING_KEY, "exclude").await.unwrap();
+        theirs_db.apply_merged_import_scoring(already_excluded.id).await.unwrap();
+        let json = theirs_db.export_data_json().await.unwrap();
+
+        // (policy here, score and excluded flag of each imported PR by github_id)
+        let cases = [
+            ("unscored", [(None, false), (None, true), (None, false)]),
+            ("exclude", [(None, true), (None, true), (None, false)]),
+            ("sentinel:5", [(Some(5), false), (None, true), (None, false)]),
+        ];
+        for (policy, expected) in cases {
+            let db = Database::new_in_memory().await.unwrap();
+            db.set_setting(MERGED_IMPORT_SCORING_KEY, policy).await.unwrap();
+            db.import_data_json(&json).await.unwrap();
+            for (github_id, expected) in (1..=3).zip(expected) {
+                let pr = db.get_pull_request_by_github_id(github_id).await.unwrap().unwrap();
+                assert_eq!((pr.score, pr.exclude_from_scoring), expected, "{} #{}", policy, github_id);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_detects_conflicts_and_applies_resolutions() {
+        let theirs_db = Database::new_in_memory().await.unwrap();
+        let shared = seed(&theirs_db).await;
+        theirs_db.update_pr_status(&WriteContext::default(), shared.id, "Approved".to_string()).await.unwrap();
+        theirs_db.update_pr_score(&WriteContext::default(), shared.id, 8).await.unwrap();
+        let author = theirs_db.get_or_create_team_member("hubot".to_string()).await.unwrap();
+        let extra = theirs_db.add_pull_request(&WriteContext::new("ana"), NewPullRequest {
+            github_id: 200,
+            pr_number: 2,
+            author_id: author.id,
+            status: PrStatus::Reviewing,
+            ..Default::default()
+        }).await.unwrap();
+        theirs_db.set_pr_priority(&WriteContext::default(), extra.id, PrPriority::High).await.unwrap();
+        theirs_db.add_pr_external_link(extra.id, "Staging", "https://staging.example.com").await.unwrap();
+        let other = theirs_db.add_project("Other".to_string(), None).await.unwrap();
+        theirs_db.add_project_member(other.id, author.id).await.unwrap();
+        let json = theirs_db.export_data_json().await.unwrap();
+
+        let db = Database::new_in_memory().await.unwrap();
+        db.set_setting(LOCAL_IDENTITY_KEY, "importer").await.unwrap();
+        let mine = seed(&db).await;
+        let result = db.import_data_json(&json).await.unwrap();
+        assert_eq!((result.projects_created, result.prs_imported, result.prs_unchanged), (1, 1, 0));
+
+        // The new PR arrives with its user fields; the shared one is untouched
+        let imported = db.get_pull_request_by_github_id(200).await.unwrap().unwrap();
+        assert_eq!((imported.priority, imported.author_name.as_deref()), (PrPriority::High, Some("hubot")));
+        assert_eq!(imported.added_by.as_deref(), Some("ana"));
+        let links = db.get_pr_detail(imported.id).await.unwrap().unwrap().external_links;
+        assert_eq!((links.len(), links[0].label.as_str()), (1, "Staging"));
+        assert_eq!(db.get_pull_request_by_id(mine.id).await.unwrap().unwrap().status, "Waiting");
+        let other = db.get_projects().await.unwrap().into_iter().find(|p| p.name == "Other").unwrap();
+        let roster = db.get_project_members(other.id).await.unwrap();
+        assert_eq!(roster.iter().map(|m| m.github_username.as_str()).collect::<Vec<_>>(), vec!["hubot"]);
+
+        let fields: Vec<(&str, Option<&str>, Option<&str>)> = result.conflicts.iter()
+            .map(|c| (c.field.as_str(), c.mine.as_deref(), c.theirs.as_deref()))
+            .collect();
+        assert_eq!(fields, vec![("status", Some("Waiting"), Some("Approved")), ("score", None, Some("8"))]);
+
+        let session_id = result.session_id.unwrap();
+        let resolutions = [
+            ConflictResolution { pr_id: mine.id, field: "status".to_string(), choose: ConflictChoice::Theirs },
+            ConflictResolution { pr_id: mine.id, field: "score".to_string(), choose: ConflictChoice::Mine },
+        ];
+
+        // An unknown session rolls back everything
+        assert!(db.apply_import_conflict_resolutions("nope", &resolutions).await.is_err());
+        let applied = db.apply_import_conflict_resolutions(&session_id, &resolutions).await.unwrap();
+        assert_eq!(applied, 1);
+        let pr = db.get_pull_request_by_id(mine.id).await.unwrap().unwrap();
+        assert_eq!((pr.status.as_str(), pr.score), ("Approved", None));
+        let moves: Vec<(String, Option<String>)> = db.get_pr_history(mine.id).await.unwrap().into_iter()
+            .filter(|entry| entry.action.starts_with("status_changed"))
+            .map(|entry| (entry.action, entry.actor))
+            .collect();
+        assert_eq!(moves, vec![("status_changed:Waiting->Approved".to_string(), Some("importer".to_string()))]);
+
+        // Resolutions are single use
+        assert!(db.apply_import_conflict_resolutions(&session_id, &resolutions[..1]).await.is_err());
+
+        // Importing again finds nothing left to do for the shared PR except the score kept as mine
+        let again = db.import_data_json(&json).await.unwrap();
+        assert_eq!((again.prs_imported, again.conflicts.len()), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_status_resolution_skips_a_status_already_reached() {
+        let theirs_db = Database::new_in_memory().await.unwrap();
+        let shared = seed(&theirs_db).await;
+        theirs_db.update_pr_status(&WriteContext::default(), shared.id, "Approved".to_string()).await.unwrap();
+        let json = theirs_db.export_data_json().await.unwrap();
+
+        let db = Database::new_in_memory().await.unwrap();
+        let mine = seed(&db).await;
+        let session_id = db.import_data_json(&json).await.unwrap().session_id.unwrap();
+        db.update_pr_status(&WriteContext::default(), mine.id, "Approved".to_string()).await.unwrap();
+        let logged = db.get_pr_history(mine.id).await.unwrap().len();
+
+        let resolution = ConflictResolution { pr_id: mine.id, field: "status".to_string(), choose: ConflictChoice::Theirs };
+        assert_eq!(db.apply_import_conflict_resolutions(&session_id, &[resolution]).await.unwrap(), 0);
+        assert_eq!(db.get_pr_history(mine.id).await.unwrap().len(), logged);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_scores_off_the_scale() {
+        let theirs_db = Database::new_in_memory().await.unwrap();
+        seed(&theirs_db).await;
+        let mut export: DataExport = serde_json::from_str(&theirs_db.export_data_json().await.unwrap()).unwrap();
+        export.pull_requests[0].score = Some(42);
+
+        let db = Database::new_in_memory().await.unwrap();
+        let err = db.import_data_json(&serde_json::to_string(&export).unwrap()).await.unwrap_err();
+        assert_eq!(err.to_string(), "PR #1 in the export: Score must be between 1 and 10, got 42");
+        assert!(db.get_pull_request_by_github_id(100).await.unwrap().is_none());
+
+        // A pending "theirs" score is checked again when it's chosen
+        let mine = seed(&db).await;
+        export.pull_requests[0].score = Some(8);
+        let result = db.import_data_json(&serde_json::to_string(&export).unwrap()).await.unwrap();
+        let session_id = result.session_id.unwrap();
+        sqlx::query("UPDATE import_conflicts SET theirs = '0'").execute(&db.pool).await.unwrap();
+        let resolution = ConflictResolution { pr_id: mine.id, field: "score".to_string(), choose: ConflictChoice::Theirs };
+        assert!(db.apply_import_conflict_resolutions(&session_id, &[resolution]).await.is_err());
+        assert_eq!(db.get_pull_request_by_id(mine.id).await.unwrap().unwrap().score, None);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_other_versions() {
+        let db = Database::new_in_memory().await.unwrap();
+        let json = r#"{"version": 2, "exported_at": 0, "projects": [], "pull_requests": []}"#;
+        assert!(db.import_data_json(json).await.is_err());
+        assert!(db.import_data_json("not json").await.is_err());
+    }
+}
@@ -0,0 +1,308 @@
+// Optional strict mode for status changes. Teams can store a transition graph (status ->
+// allowed next statuses) and turn on strict_status_transitions; user moves outside the
+// graph are then rejected. Sync-originated moves (mark_pr_merged, recorded with source
+// "github") always go through: GitHub's state wins. Archiving and restoring are
+// housekeeping, not workflow moves, and aren't checked either. Default is permissive.
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::actor::WriteContext;
+use super::lifecycle::record_status_move;
+use super::pr_links::{blocking_links_for_move, BlockedByLinks, BlockingLinksEnforcement, PrExternalLink};
+use super::settings_registry::DEFAULT_STRICT_STATUS_TRANSITIONS;
+use super::status::PrStatus;
+use super::waiting_on::follow_status;
+use super::{Database, BOARD_COLUMNS, CLOSED_STATUSES};
+
+pub const STATUS_TRANSITIONS_KEY: &str = "status_transitions";
+pub const STRICT_STATUS_TRANSITIONS_KEY: &str = "strict_status_transitions";
+
+/// status -> statuses a PR may move to next
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TransitionGraph(pub BTreeMap<String, Vec<String>>);
+
+fn is_known_status(status: &str) -> bool {
+    BOARD_COLUMNS.contains(&status) || CLOSED_STATUSES.contains(&status)
+}
+
+impl TransitionGraph {
+    /// Every status must be known and every board column reachable from the first one
+    pub fn validate(&self) -> Result<()> {
+        let unknown: BTreeSet<&str> = self.0.iter()
+            .flat_map(|(from, to)| std::iter::once(from).chain(to))
+            .map(String::as_str)
+            .filter(|status| !is_known_status(status))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Unknown status(es) in transition graph: {}",
+                unknown.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        let mut reached = BTreeSet::from([BOARD_COLUMNS[0]]);
+        let mut queue = VecDeque::from([BOARD_COLUMNS[0]]);
+        while let Some(status) = queue.pop_front() {
+            for next in self.allowed_from(status) {
+                if reached.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        let unreachable: Vec<&str> = BOARD_COLUMNS.iter().copied().filter(|status| !reached.contains(status)).collect();
+        if !unreachable.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Unreachable from {} in transition graph: {}",
+                BOARD_COLUMNS[0],
+                unreachable.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        let graph: TransitionGraph = serde_json::from_str(value)
+            .map_err(|e| anyhow::anyhow!("Transition graph must be a JSON object of status -> [next statuses]: {}", e))?;
+        graph.validate()?;
+        Ok(graph)
+    }
+
+    pub fn allowed_from(&self, status: &str) -> Vec<&str> {
+        self.0.get(status).map(|next| next.iter().map(String::as_str).collect()).unwrap_or_default()
+    }
+
+    /// Staying put is always allowed
+    pub fn check(&self, from: &str, to: &str) -> std::result::Result<(), TransitionRejected> {
+        let allowed = self.allowed_from(from);
+        if from == to || allowed.contains(&to) {
+            return Ok(());
+        }
+        Err(TransitionRejected {
+            from: from.to_string(),
+            to: to.to_string(),
+            allowed: allowed.into_iter().map(str::to_string).collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransitionRejected {
+    pub from: String,
+    pub to: String,
+    pub allowed: Vec<String>,
+}
+
+impl fmt::Display for TransitionRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let allowed = if self.allowed.is_empty() { "none".to_string() } else { self.allowed.join(", ") };
+        write!(f, "Strict mode doesn't allow moving from '{}' to '{}'; allowed next: {}", self.from, self.to, allowed)
+    }
+}
+
+impl std::error::Error for TransitionRejected {}
+
+pub fn parse_strict_flag(key: &str, value: &str) -> Result<bool> {
+    match value.trim() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => Err(anyhow::anyhow!("{} must be 'true' or 'false', got '{}'", key, other)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StatusTransitions {
+    pub strict: bool,
+    pub graph: Option<TransitionGraph>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct RejectedStatusUpdate {
+    pub pr_id: i64,
+    pub reason: String,
+    pub allowed: Vec<String>, // next statuses strict mode would accept; empty for other reasons
+    pub blocking_links: Vec<PrExternalLink>, // unresolved blocking links holding up a move to Merged
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct BulkStatusUpdateResult {
+    pub updated: Vec<i64>,
+    pub rejected: Vec<RejectedStatusUpdate>,
+    pub warned: Vec<BlockedByLinks>, // moved anyway since blocking links only warn
+}
+
+impl Database {
+    pub async fn get_status_transitions(&self) -> Result<StatusTransitions> {
+        let strict = match self.get_setting(STRICT_STATUS_TRANSITIONS_KEY).await? {
+            Some(value) => parse_strict_flag(STRICT_STATUS_TRANSITIONS_KEY, &value).unwrap_or(DEFAULT_STRICT_STATUS_TRANSITIONS),
+            None => DEFAULT_STRICT_STATUS_TRANSITIONS,
+        };
+        let graph = self.get_setting(STATUS_TRANSITIONS_KEY).await?
+            .and_then(|value| TransitionGraph::parse(&value).ok());
+        Ok(StatusTransitions { strict, graph })
+    }
+
+    /// Validate and store the graph; strict mode is switched separately
+    pub async fn set_status_transitions(&self, graph: &TransitionGraph) -> Result<()> {
+        graph.validate()?;
+        self.set_setting(STATUS_TRANSITIONS_KEY, &serde_json::to_string(graph)?).await
+    }
+
+    /// The graph to enforce on user moves, or None while permissive
+    pub(crate) async fn enforced_transitions(&self) -> Result<Option<TransitionGraph>> {
+        let transitions = self.get_status_transitions().await?;
+        Ok(transitions.graph.filter(|_| transitions.strict))
+    }
+
+    /// Move several PRs at once; in strict mode, disallowed moves are reported per PR and skipped,
+    /// and so are PRs held up by blocking links (or they move and are listed in `warned`).
+    /// PRs already in `status` are left alone; each move is logged like update_pr_status's.
+    pub async fn bulk_update_pr_status(&self, ctx: &WriteContext, pr_ids: &[i64], status: &str) -> Result<BulkStatusUpdateResult> {
+        let status = PrStatus::parse_input(status)?;
+        let status = status.as_str();
+        let graph = self.enforced_transitions().await?;
+        let enforcement = self.get_blocking_links_enforcement().await?;
+        let mut result = BulkStatusUpdateResult::default();
+        let (cause, now) = (format!("status changed to {}", status), chrono::Utc::now().timestamp());
+        let mut tx = self.pool.begin().await?;
+        for &pr_id in pr_ids {
+            let current: Option<String> = sqlx::query_scalar("SELECT status FROM pull_requests WHERE id = ?")
+                .bind(pr_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let Some(current) = current else {
+                result.rejected.push(RejectedStatusUpdate { pr_id, reason: format!("Pull request {} not found", pr_id), ..Default::default() });
+                continue;
+            };
+            if current == status {
+                continue;
+            }
+            if let Err(rejected) = check_status_transition(graph.as_ref(), &current, status) {
+                result.rejected.push(RejectedStatusUpdate { pr_id, reason: rejected.to_string(), allowed: rejected.allowed, ..Default::default() });
+                continue;
+            }
+            let links = blocking_links_for_move(&mut tx, pr_id, status).await?;
+            if !links.is_empty() {
+                let blocked = BlockedByLinks { pr_id, links };
+                if enforcement == BlockingLinksEnforcement::Block {
+                    result.rejected.push(RejectedStatusUpdate { pr_id, reason: blocked.to_string(), blocking_links: blocked.links, ..Default::default() });
+                    continue;
+                }
+                result.warned.push(blocked);
+            }
+            sqlx::query("UPDATE pull_requests SET status = ? WHERE id = ?")
+                .bind(status)
+                .bind(pr_id)
+                .execute(&mut *tx)
+                .await?;
+            record_status_move(&mut tx, pr_id, &current, status, &ctx.actor, now).await?;
+            follow_status(&mut tx, pr_id, &cause, Some(&ctx.actor), now).await?;
+            result.updated.push(pr_id);
+        }
+        tx.commit().await?;
+        Ok(result)
+    }
+}
+
+/// Reject the move when strict mode is on (`graph` from enforced_transitions) and the graph
+/// doesn't allow it. `current` is read in the mover's transaction, so the check and the write
+/// see the same status.
+pub(crate) fn check_status_transition(graph: Option<&TransitionGraph>, current: &str, status: &str) -> std::result::Result<(), TransitionRejected> {
+    match graph {
+        Some(graph) => graph.check(current, status),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    fn graph(edges: &[(&str, &[&str])]) -> TransitionGraph {
+        TransitionGraph(edges.iter()
+            .map(|(from, to)| (from.to_string(), to.iter().map(|s| s.to_string()).collect()))
+            .collect())
+    }
+
+    fn review_flow() -> TransitionGraph {
+        graph(&[
+            ("Waiting", &["Reviewing"]),
+            ("Reviewing", &["Action", "Approved"]),
+            ("Action", &["Reviewing"]),
+            ("Approved", &["Merged"]),
+        ])
+    }
+
+    #[test]
+    fn test_graph_validation() {
+        assert!(review_flow().validate().is_ok());
+
+        let err = graph(&[("Waiting", &["Done"])]).validate().unwrap_err().to_string();
+        assert_eq!(err, "Unknown status(es) in transition graph: Done");
+
+        let err = graph(&[("Waiting", &["Reviewing"]), ("Action", &["Approved"])]).validate().unwrap_err().to_string();
+        assert_eq!(err, "Unreachable from Waiting in transition graph: Action, Approved");
+
+        assert!(TransitionGraph::parse("[\"Waiting\"]").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode_rejects_disallowed_moves() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let mut ids = Vec::new();
+        for (number, status) in [(1, "Waiting"), (2, "Reviewing")] {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                author_id: author.id,
+                status: status.into(),
+                ..Default::default()
+            }).await.unwrap();
+            ids.push(pr.id);
+        }
+
+        // Permissive until strict mode is on, even with a graph stored
+        db.set_status_transitions(&review_flow()).await.unwrap();
+        db.update_pr_status(&WriteContext::default(), ids[0], "Approved".to_string()).await.unwrap();
+        db.update_pr_status(&WriteContext::default(), ids[0], "Waiting".to_string()).await.unwrap();
+
+        db.set_setting(STRICT_STATUS_TRANSITIONS_KEY, "true").await.unwrap();
+        let err = db.update_pr_status(&WriteContext::default(), ids[0], "Approved".to_string()).await.unwrap_err();
+        assert_eq!(err.to_string(), "Strict mode doesn't allow moving from 'Waiting' to 'Approved'; allowed next: Reviewing");
+        db.update_pr_status(&WriteContext::default(), ids[0], "Reviewing".to_string()).await.unwrap();
+
+        let result = db.bulk_update_pr_status(&WriteContext::new("ana"), &[ids[0], ids[1], 999], "Action").await.unwrap();
+        assert_eq!(result.updated, vec![ids[0], ids[1]]);
+        // Already there: not moved again, and no second history row
+        let result = db.bulk_update_pr_status(&WriteContext::default(), &[ids[0]], "Action").await.unwrap();
+        assert!(result.updated.is_empty() && result.rejected.is_empty());
+        let history = db.get_pr_history(ids[0]).await.unwrap();
+        assert_eq!(history.last().unwrap().actor.as_deref(), Some("ana"));
+        let moves: Vec<String> = history.into_iter()
+            .map(|entry| entry.action)
+            .filter(|action| action.starts_with("status_changed:"))
+            .collect();
+        assert_eq!(moves, vec![
+            "status_changed:Waiting->Approved",
+            "status_changed:Approved->Waiting",
+            "status_changed:Waiting->Reviewing",
+            "status_changed:Reviewing->Action",
+        ]);
+        let result = db.bulk_update_pr_status(&WriteContext::default(), &[ids[0], ids[1]], "Approved").await.unwrap();
+        assert!(result.updated.is_empty());
+        assert_eq!(result.rejected[0].allowed, vec!["Reviewing"]);
+
+        // Sync still wins
+        db.mark_pr_merged(ids[1], None).await.unwrap();
+        assert_eq!(db.get_pull_request_by_id(ids[1]).await.unwrap().unwrap().status, "Merged");
+
+        assert!(db.set_setting(STRICT_STATUS_TRANSITIONS_KEY, "yes").await.is_err());
+        assert!(db.set_setting(STATUS_TRANSITIONS_KEY, "{\"Waiting\": []}").await.is_err());
+    }
+}
@@ -0,0 +1,161 @@
+// The handful of numbers a tray icon shows ("3 waiting · 1 overdue"). Kept to one
+// aggregate pass and one LIMIT 1 lookup so it can be polled without the cost of the
+// full statistics queries.
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::Row;
+
+use super::settings_registry::DEFAULT_STALE_THRESHOLD_DAYS;
+use super::{display_key, sql_string_list, Database, CLOSED_STATUSES};
+
+// Open PRs untouched for longer than stale_threshold_days count as overdue, unless the next
+// move is the author's: their wait doesn't count against the reviewers
+pub const STALE_THRESHOLD_KEY: &str = "stale_threshold_days";
+
+// Columns where the next move is the reviewer's
+const REVIEW_COLUMNS: [&str; 2] = ["Waiting", "Reviewing"];
+
+// Most urgent open PR: highest priority, then the longest without activity
+const URGENCY_ORDER: &str = "pr.priority DESC, COALESCE(pr.github_updated_at, pr.last_updated_at) ASC, pr.id";
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct TraySummary {
+    pub waiting: i64,
+    pub overdue: i64,
+    pub needs_my_review: i64,         // in a review column with changes the active profile hasn't seen
+    pub most_urgent: Option<String>,  // display key, e.g. "api#512"; Approved PRs and PRs waiting on their author are not candidates
+}
+
+impl Database {
+    pub async fn get_stale_threshold_days(&self) -> Result<i64> {
+        Ok(self.get_setting(STALE_THRESHOLD_KEY).await?
+            .and_then(|value| value.trim().parse::<i64>().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_STALE_THRESHOLD_DAYS))
+    }
+
+    pub async fn get_tray_summary(&self, now: i64) -> Result<TraySummary> {
+        let cutoff = now - self.get_stale_threshold_days().await? * 86_400;
+        let open = format!("pr.is_issue = 0 AND pr.status NOT IN ({})", sql_string_list(&CLOSED_STATUSES));
+
+        let counts = sqlx::query(&format!(
+            r#"
+            SELECT
+                COALESCE(SUM(pr.status = 'Waiting'), 0) as waiting,
+                COALESCE(SUM(pr.waiting_on != 'author' AND COALESCE(pr.github_updated_at, pr.last_updated_at) < ?), 0) as overdue,
+                COALESCE(SUM(pr.status IN ({}) AND (
+                    ps.last_viewed_at IS NULL
+                    OR COALESCE(
+                        pr.github_updated_at,
+                        (SELECT MAX(rh.performed_at) FROM review_history rh WHERE rh.pr_id = pr.id)
+                    ) > ps.last_viewed_at
+                )), 0) as needs_my_review
+            FROM pull_requests pr
+            LEFT JOIN pr_profile_state ps ON ps.pr_id = pr.id
+                AND ps.profile_id = (SELECT id FROM local_profiles WHERE is_active = 1)
+            WHERE {}
+            "#,
+            sql_string_list(&REVIEW_COLUMNS),
+            open
+        ))
+        .bind(cutoff)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let most_urgent = sqlx::query(&format!(
+            "SELECT pr.repository_owner, pr.repository_name, pr.pr_number FROM pull_requests pr
+             WHERE {} AND pr.status != 'Approved' AND pr.waiting_on != 'author' ORDER BY {} LIMIT 1",
+            open, URGENCY_ORDER
+        ))
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|row| {
+            let owner: Option<String> = row.get("repository_owner");
+            let repo: Option<String> = row.get("repository_name");
+            display_key(owner.as_deref(), repo.as_deref(), row.get("pr_number"), false)
+        });
+
+        Ok(TraySummary {
+            waiting: counts.get("waiting"),
+            overdue: counts.get("overdue"),
+            needs_my_review: counts.get("needs_my_review"),
+            most_urgent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::{NewPullRequest, PrPriority};
+
+    const NOW: i64 = 1_700_000_000;
+
+    // 10k PRs cycling through the columns; every third one untouched for 30 days
+    async fn seed_10k(db: &Database) -> i64 {
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        sqlx::query(
+            r#"
+            WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i < 9999)
+            INSERT INTO pull_requests (github_id, pr_number, author_id, status, waiting_on, last_updated_at, github_updated_at,
+                                       repository_owner, repository_name, priority, is_issue)
+            SELECT 100000 + i, i, ?,
+                   CASE i % 5 WHEN 0 THEN 'Waiting' WHEN 1 THEN 'Reviewing' WHEN 2 THEN 'Action'
+                              WHEN 3 THEN 'Approved' ELSE 'Merged' END,
+                   CASE i % 5 WHEN 2 THEN 'author' WHEN 3 THEN 'nobody' WHEN 4 THEN 'nobody' ELSE 'reviewer' END,
+                   ?, CASE WHEN i % 3 = 0 THEN ? ELSE ? END,
+                   'octo-org', 'widget-service', 2, 0
+            FROM n
+            "#
+        )
+        .bind(author.id)
+        .bind(NOW)
+        .bind(NOW - 30 * 86_400)
+        .bind(NOW - 3_600)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        author.id
+    }
+
+    #[tokio::test]
+    async fn test_tray_summary_on_10k_prs() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_tray_summary(NOW).await.unwrap(), TraySummary::default());
+        let author_id = seed_10k(&db).await;
+
+        let summary = db.get_tray_summary(NOW).await.unwrap();
+        // 2000 per column; 8000 open, a third of them stale, but stale Action PRs are the author's wait;
+        // nothing viewed yet
+        assert_eq!(summary.waiting, 2000);
+        assert_eq!(summary.overdue, (0..10_000).filter(|i| i % 5 != 4 && i % 5 != 2 && i % 3 == 0).count() as i64);
+        assert_eq!(summary.needs_my_review, 4000);
+        // All Medium: the oldest activity wins, skipping Approved (3) and Merged (4)
+        assert_eq!(summary.most_urgent.as_deref(), Some("widget-service#0"));
+
+        // A High priority PR jumps ahead; viewing takes it out of needs_my_review
+        let urgent = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 20_001,
+            author_id,
+            status: PrStatus::Reviewing,
+            repository_name: Some("api".to_string()),
+            github_updated_at: Some(NOW),
+            ..Default::default()
+        }).await.unwrap();
+        db.set_pr_priority(&WriteContext::default(), urgent.id, PrPriority::High).await.unwrap();
+        db.mark_pr_viewed(urgent.id).await.unwrap();
+        let summary = db.get_tray_summary(NOW).await.unwrap();
+        assert_eq!(summary.most_urgent.as_deref(), Some("api#20001"));
+        assert_eq!(summary.needs_my_review, 4000);
+
+        // Changes requested: the author's move now, so it's not the reviewers' most urgent
+        db.update_pr_status(&WriteContext::default(), urgent.id, "Action".to_string()).await.unwrap();
+        assert_eq!(db.get_tray_summary(NOW).await.unwrap().most_urgent.as_deref(), Some("widget-service#0"));
+
+        db.set_setting(STALE_THRESHOLD_KEY, "60").await.unwrap();
+        assert_eq!(db.get_tray_summary(NOW).await.unwrap().overdue, 0);
+    }
+}
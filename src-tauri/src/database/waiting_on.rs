@@ -0,0 +1,222 @@
+// Whose move a PR is waiting on. "Waiting" alone doesn't say whether a reviewer has yet to
+// pick it up or the author has feedback to address, so this is kept next to the status and
+// maintained automatically, never set by hand:
+//   - every status write sets it to what the new status implies: reviewer for Waiting and
+//     Reviewing, author for Action (changes requested), nobody for Approved and the closed
+//     statuses. Statuses outside the known ones leave it alone.
+//   - a refresh that finds a new CHANGES_REQUESTED review of the current head commit while it's
+//     the reviewer's move hands it to the author (database/github_reviews.rs).
+//   - a refresh that finds a new head commit while it's the author's move hands it back to
+//     the reviewer; neither moves the card out of its column.
+// Each flip is a review_history row "waiting_on_changed:<from>-><to>" with the cause in details.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+
+use super::status::PrStatus;
+use super::Database;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WaitingOn {
+    #[default]
+    Reviewer,
+    Author,
+    Nobody,
+}
+
+impl WaitingOn {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WaitingOn::Reviewer => "reviewer",
+            WaitingOn::Author => "author",
+            WaitingOn::Nobody => "nobody",
+        }
+    }
+
+    // Stored values only come from as_str; anything else reads as the default
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "author" => WaitingOn::Author,
+            "nobody" => WaitingOn::Nobody,
+            _ => WaitingOn::Reviewer,
+        }
+    }
+
+    /// What moving a PR into `status` implies, or None for statuses outside the known ones
+    pub fn for_status(status: &PrStatus) -> Option<WaitingOn> {
+        match status {
+            PrStatus::Waiting | PrStatus::Reviewing => Some(WaitingOn::Reviewer),
+            PrStatus::Action => Some(WaitingOn::Author),
+            PrStatus::Approved | PrStatus::Merged | PrStatus::Archived => Some(WaitingOn::Nobody),
+            PrStatus::Unknown(_) => None,
+        }
+    }
+}
+
+async fn record_flip(
+    conn: &mut SqliteConnection,
+    pr_id: i64,
+    from: WaitingOn,
+    to: WaitingOn,
+    cause: &str,
+    actor: Option<&str>,
+    performed_at: i64,
+) -> Result<()> {
+    sqlx::query("UPDATE pull_requests SET waiting_on = ? WHERE id = ?")
+        .bind(to.as_str())
+        .bind(pr_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("INSERT INTO review_history (pr_id, action, performed_at, details, actor) VALUES (?, ?, ?, ?, ?)")
+        .bind(pr_id)
+        .bind(format!("waiting_on_changed:{}->{}", from.as_str(), to.as_str()))
+        .bind(performed_at)
+        .bind(cause)
+        .bind(actor)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Bring waiting_on in line with the PR's status, inside the caller's transaction and after
+/// the status was written. Returns the new value when it changed.
+pub(crate) async fn follow_status(
+    conn: &mut SqliteConnection,
+    pr_id: i64,
+    cause: &str,
+    actor: Option<&str>,
+    performed_at: i64,
+) -> Result<Option<WaitingOn>> {
+    let row: Option<(String, String)> = sqlx::query_as("SELECT status, waiting_on FROM pull_requests WHERE id = ?")
+        .bind(pr_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some((status, current)) = row else { return Ok(None) };
+    let current = WaitingOn::from_db(&current);
+    match WaitingOn::for_status(&PrStatus::from(status)) {
+        Some(next) if next != current => {
+            record_flip(conn, pr_id, current, next, cause, actor, performed_at).await?;
+            Ok(Some(next))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// A reviewer requested changes: the author's move now, unless it already was or the PR is
+/// past review. Runs inside the caller's transaction; returns the new value when it changed.
+pub(crate) async fn changes_requested(
+    conn: &mut SqliteConnection,
+    pr_id: i64,
+    reviewer: &str,
+    performed_at: i64,
+) -> Result<Option<WaitingOn>> {
+    let current: Option<String> = sqlx::query_scalar("SELECT waiting_on FROM pull_requests WHERE id = ?")
+        .bind(pr_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if current.as_deref().map(WaitingOn::from_db) != Some(WaitingOn::Reviewer) {
+        return Ok(None);
+    }
+    let cause = format!("changes requested by {}", reviewer);
+    record_flip(conn, pr_id, WaitingOn::Reviewer, WaitingOn::Author, &cause, None, performed_at).await?;
+    Ok(Some(WaitingOn::Author))
+}
+
+impl Database {
+    /// Remember the head commit a refresh reported. A different commit than last time while
+    /// it's the author's move means they pushed, so it goes back to the reviewer. The first
+    /// commit seen for a PR only sets the baseline.
+    pub async fn record_pr_head_sha(&self, pr_id: i64, head_sha: &str) -> Result<Option<WaitingOn>> {
+        if head_sha.is_empty() {
+            return Ok(None);
+        }
+        let mut tx = self.pool.begin().await?;
+        let row: Option<(Option<String>, String)> = sqlx::query_as("SELECT head_sha, waiting_on FROM pull_requests WHERE id = ?")
+            .bind(pr_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let (previous, current) = row.ok_or_else(|| anyhow::anyhow!("Pull request {} not found", pr_id))?;
+        if previous.as_deref() == Some(head_sha) {
+            return Ok(None);
+        }
+
+        sqlx::query("UPDATE pull_requests SET head_sha = ? WHERE id = ?")
+            .bind(head_sha)
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+        let flipped = if previous.is_some() && WaitingOn::from_db(&current) == WaitingOn::Author {
+            let short: String = head_sha.chars().take(7).collect();
+            let cause = format!("new commits pushed ({})", short);
+            record_flip(&mut tx, pr_id, WaitingOn::Author, WaitingOn::Reviewer, &cause, None, chrono::Utc::now().timestamp()).await?;
+            Some(WaitingOn::Reviewer)
+        } else {
+            None
+        };
+
+        tx.commit().await?;
+        Ok(flipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    fn flips(history: &[crate::database::lifecycle::HistoryEntry]) -> Vec<(&str, Option<&str>)> {
+        history.iter()
+            .filter(|entry| entry.action.starts_with("waiting_on_changed:"))
+            .map(|entry| (entry.action.as_str(), entry.details.as_deref()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_waiting_on_follows_status_and_new_commits() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(pr.waiting_on, WaitingOn::Reviewer);
+
+        // Baseline commit, then changes requested by hand
+        assert_eq!(db.record_pr_head_sha(pr.id, "aaaaaaaaaa").await.unwrap(), None);
+        db.update_pr_status(&WriteContext::new("ana"), pr.id, "Reviewing".to_string()).await.unwrap();
+        db.update_pr_status(&WriteContext::new("ana"), pr.id, "Action".to_string()).await.unwrap();
+        assert_eq!(db.get_pull_request_by_id(pr.id).await.unwrap().unwrap().waiting_on, WaitingOn::Author);
+
+        // The same commit again changes nothing; a new one is the author's answer
+        assert_eq!(db.record_pr_head_sha(pr.id, "aaaaaaaaaa").await.unwrap(), None);
+        assert_eq!(db.record_pr_head_sha(pr.id, "bbbbbbbbbb").await.unwrap(), Some(WaitingOn::Reviewer));
+        let pr_now = db.get_pull_request_by_id(pr.id).await.unwrap().unwrap();
+        assert_eq!((pr_now.status.as_str(), pr_now.waiting_on), ("Action", WaitingOn::Reviewer));
+        // New commits while it's already the reviewer's move aren't a flip
+        assert_eq!(db.record_pr_head_sha(pr.id, "cccccccccc").await.unwrap(), None);
+
+        let on_reviewer = db.get_pull_requests_filtered(None, None, None, Some(WaitingOn::Reviewer)).await.unwrap();
+        assert_eq!(on_reviewer.iter().map(|p| p.id).collect::<Vec<_>>(), vec![pr.id]);
+        assert!(db.get_pull_requests_filtered(None, None, None, Some(WaitingOn::Author)).await.unwrap().is_empty());
+
+        db.bulk_update_pr_status(&WriteContext::default(), &[pr.id], "Reviewing").await.unwrap();
+        db.bulk_update_pr_status(&WriteContext::default(), &[pr.id], "Action").await.unwrap();
+        db.mark_pr_merged(pr.id, None).await.unwrap();
+        assert_eq!(db.get_pull_request_by_id(pr.id).await.unwrap().unwrap().waiting_on, WaitingOn::Nobody);
+
+        let history = db.get_pr_history(pr.id).await.unwrap();
+        assert_eq!(flips(&history), vec![
+            ("waiting_on_changed:reviewer->author", Some("status changed to Action")),
+            ("waiting_on_changed:author->reviewer", Some("new commits pushed (bbbbbbb)")),
+            ("waiting_on_changed:reviewer->author", Some("status changed to Action")),
+            ("waiting_on_changed:author->nobody", Some("merged on GitHub")),
+        ]);
+        let by_hand = history.iter().find(|entry| entry.action == "waiting_on_changed:reviewer->author").unwrap();
+        assert_eq!(by_hand.actor.as_deref(), Some("ana"));
+    }
+}
@@ -0,0 +1,206 @@
+// Throwaway development data. Builds with the dev feature keep everything under
+// <data dir>/PRTracker-dev rather than the installed app's PRTracker, so running the app or
+// the test bins from a checkout never touches real data. reset_dev_environment (command, or
+// `pr-tracker reset-dev-environment`) wipes that directory and refills it: fresh schema,
+// the sample projects and PRs, and a fake local identity.
+//
+// Two guards run before anything is deleted: a build without the feature refuses while its
+// database is the production one, and the directory wiped must be a PRTracker-dev that
+// neither is nor contains the production directory. The wipe keeps the instance lock, so the
+// running app stays primary; the CLI takes that lock first and refuses while an app holds it.
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::database::actor::LOCAL_IDENTITY_KEY;
+use crate::database::{app_dir_in, Database, DATABASE_FILE_NAME, DEV_APP_DIR_NAME};
+use crate::instance_lock::{self, Acquired, LOCK_FILE_NAME};
+
+pub const RESET_DEV_SUBCOMMAND: &str = "reset-dev-environment";
+pub const DEV_IDENTITY: &str = "dev-reviewer";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DevEnvironment {
+    pub app_dir: String,
+    pub database_path: String,
+    pub production_app_dir: String, // left alone; shown so it's clear which is which
+    pub local_identity: String,
+}
+
+// Canonical when the path exists, so symlinks and ".." can't disguise the production directory
+fn resolved(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Refuse to reset while the active database is the production one, unless this is a dev
+/// build (whose active database never is)
+pub fn check_reset_allowed(active_db: &Path, production_db: &Path, dev_build: bool) -> Result<()> {
+    if !dev_build && resolved(active_db) == resolved(production_db) {
+        return Err(anyhow::anyhow!(
+            "Refusing to reset: the active database is the production one ({}); build with --features dev",
+            production_db.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Only a directory named PRTracker-dev may be wiped, and never one that is, contains or sits
+/// inside the production directory
+pub fn check_wipe_target(target: &Path, production_dir: &Path) -> Result<()> {
+    if target.file_name().and_then(|name| name.to_str()) != Some(DEV_APP_DIR_NAME) {
+        return Err(anyhow::anyhow!("Refusing to wipe {}: not a {} directory", target.display(), DEV_APP_DIR_NAME));
+    }
+    let (target, production) = (resolved(target), resolved(production_dir));
+    if production.starts_with(&target) || target.starts_with(&production) {
+        return Err(anyhow::anyhow!("Refusing to wipe {}: it overlaps the production data in {}", target.display(), production.display()));
+    }
+    Ok(())
+}
+
+/// The dev and production app directories for this build, once the reset is known to be allowed
+pub fn reset_target() -> Result<(PathBuf, PathBuf)> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?;
+    let active_dir = app_dir_in(&data_dir, cfg!(feature = "dev"));
+    let production_dir = app_dir_in(&data_dir, false);
+    check_reset_allowed(&active_dir.join(DATABASE_FILE_NAME), &production_dir.join(DATABASE_FILE_NAME), cfg!(feature = "dev"))?;
+    Ok((active_dir, production_dir))
+}
+
+// Delete everything in `app_dir` except the instance lock
+fn wipe(app_dir: &Path) -> Result<()> {
+    if !app_dir.exists() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(app_dir)? {
+        let entry = entry?;
+        if entry.file_name() == LOCK_FILE_NAME {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Wipe `app_dir` and set it up from scratch; the caller closes any database open in it first
+pub async fn reset_in(app_dir: &Path, production_dir: &Path) -> Result<(Database, DevEnvironment)> {
+    check_wipe_target(app_dir, production_dir)?;
+    wipe(app_dir)?;
+
+    let database_path = app_dir.join(DATABASE_FILE_NAME);
+    let db = Database::open(&database_path).await?;
+    db.add_sample_data().await?;
+    db.set_setting(LOCAL_IDENTITY_KEY, DEV_IDENTITY).await?;
+
+    let env = DevEnvironment {
+        app_dir: app_dir.display().to_string(),
+        database_path: database_path.display().to_string(),
+        production_app_dir: production_dir.display().to_string(),
+        local_identity: DEV_IDENTITY.to_string(),
+    };
+    println!("🧪 Reset dev environment in {} (production data in {} untouched)", env.app_dir, env.production_app_dir);
+    Ok((db, env))
+}
+
+/// `pr-tracker reset-dev-environment`; returns the process exit code
+pub fn run_cli() -> i32 {
+    let result = tokio::runtime::Runtime::new()
+        .map_err(anyhow::Error::from)
+        .and_then(|runtime| runtime.block_on(async {
+            let (app_dir, production_dir) = reset_target()?;
+            std::fs::create_dir_all(&app_dir)?;
+            // Held until the reset is done, so an app started meanwhile runs as secondary
+            let _lock = match instance_lock::acquire(&app_dir.join(LOCK_FILE_NAME), std::process::id())? {
+                Acquired::Primary(lock) => lock,
+                Acquired::Secondary { holder_pid } => {
+                    return Err(anyhow::anyhow!(
+                        "Refusing to reset: PR Tracker{} is running on {}; quit it or use Reset dev environment in the app",
+                        holder_pid.map(|pid| format!(" (PID {})", pid)).unwrap_or_default(), app_dir.display()
+                    ));
+                }
+            };
+            let (db, env) = reset_in(&app_dir, &production_dir).await?;
+            db.pool.close().await;
+            Ok(env)
+        }));
+    match result {
+        Ok(env) => {
+            println!("App directory:  {}", env.app_dir);
+            println!("Database:       {}", env.database_path);
+            println!("Local identity: {}", env.local_identity);
+            0
+        }
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_guard() {
+        let data = Path::new("/home/dev/.local/share");
+        let production_db = app_dir_in(data, false).join(DATABASE_FILE_NAME);
+        let dev_db = app_dir_in(data, true).join(DATABASE_FILE_NAME);
+        assert_eq!(dev_db, Path::new("/home/dev/.local/share/PRTracker-dev/database.sqlite"));
+
+        // Without the feature the app runs on production data, so the reset is refused
+        let err = check_reset_allowed(&production_db, &production_db, false).unwrap_err();
+        assert!(err.to_string().contains("--features dev"), "{}", err);
+        check_reset_allowed(&dev_db, &production_db, true).unwrap();
+    }
+
+    #[test]
+    fn test_wipe_target_guard() {
+        let data = Path::new("/home/dev/.local/share");
+        let production_dir = app_dir_in(data, false);
+        check_wipe_target(&app_dir_in(data, true), &production_dir).unwrap();
+
+        for target in [
+            production_dir.clone(),
+            data.to_path_buf(),
+            data.join("PRTracker/../PRTracker"),
+            production_dir.join(DEV_APP_DIR_NAME),
+            Path::new("/").to_path_buf(),
+        ] {
+            assert!(check_wipe_target(&target, &production_dir).is_err(), "{} was allowed", target.display());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reset_wipes_only_the_dev_directory() {
+        let data = std::env::temp_dir().join(format!("pr-tracker-dev-env-{}", std::process::id()));
+        let (dev_dir, production_dir) = (app_dir_in(&data, true), app_dir_in(&data, false));
+        std::fs::create_dir_all(&production_dir).unwrap();
+        std::fs::write(production_dir.join(DATABASE_FILE_NAME), "real data").unwrap();
+        std::fs::create_dir_all(&dev_dir).unwrap();
+        std::fs::write(dev_dir.join("stale.txt"), "old").unwrap();
+        std::fs::create_dir_all(dev_dir.join("avatars")).unwrap();
+        std::fs::write(dev_dir.join(LOCK_FILE_NAME), "4242").unwrap();
+
+        let (db, env) = reset_in(&dev_dir, &production_dir).await.unwrap();
+        assert!(!dev_dir.join("stale.txt").exists());
+        assert!(!dev_dir.join("avatars").exists());
+        // The running instance keeps its lock
+        assert_eq!(std::fs::read_to_string(dev_dir.join(LOCK_FILE_NAME)).unwrap(), "4242");
+        assert_eq!(env.database_path, dev_dir.join(DATABASE_FILE_NAME).display().to_string());
+        assert!(!db.get_projects().await.unwrap().is_empty());
+        assert_eq!(db.write_context().await.unwrap().actor, DEV_IDENTITY);
+        db.pool.close().await;
+
+        // Reset twice in a row, as a developer would
+        let (db, _) = reset_in(&dev_dir, &production_dir).await.unwrap();
+        db.pool.close().await;
+
+        assert!(reset_in(&production_dir, &production_dir).await.is_err());
+        assert_eq!(std::fs::read_to_string(production_dir.join(DATABASE_FILE_NAME)).unwrap(), "real data");
+        std::fs::remove_dir_all(&data).unwrap();
+    }
+}
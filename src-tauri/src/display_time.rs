@@ -0,0 +1,91 @@
+// Human-facing timestamp formatting. Storage stays unix seconds (UTC); anything
+// rendered for people goes through format_timestamp in the display time zone.
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+
+pub const DISPLAY_TIMEZONE_KEY: &str = "display_timezone";
+
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| format!("Unknown time zone '{}'. Use an IANA name such as Asia/Tokyo", name))
+}
+
+/// The system zone, detected once per process; UTC when it can't be determined
+pub fn system_timezone() -> Tz {
+    static SYSTEM_TZ: OnceLock<Tz> = OnceLock::new();
+    *SYSTEM_TZ.get_or_init(|| {
+        iana_time_zone::get_timezone()
+            .ok()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(Tz::UTC)
+    })
+}
+
+/// e.g. "2024-01-01 18:00 +09:00"
+pub fn format_timestamp(timestamp: i64, tz: Tz) -> String {
+    match DateTime::from_timestamp(timestamp, 0) {
+        Some(dt) => dt.with_timezone(&tz).format("%Y-%m-%d %H:%M %:z").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// e.g. "18:00", for times close enough that the date is obvious
+pub fn format_time_of_day(timestamp: i64, tz: Tz) -> String {
+    match DateTime::from_timestamp(timestamp, 0) {
+        Some(dt) => dt.with_timezone(&tz).format("%H:%M").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// Calendar day the timestamp falls on in the display zone
+pub fn local_date(timestamp: i64, tz: Tz) -> NaiveDate {
+    DateTime::from_timestamp(timestamp, 0).unwrap_or_default().with_timezone(&tz).date_naive()
+}
+
+/// Unix time of the local midnight starting `date`; zones that skip midnight for DST start an hour later
+pub fn local_midnight(date: NaiveDate, tz: Tz) -> i64 {
+    let midnight = date.and_time(NaiveTime::MIN);
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(midnight + chrono::Duration::hours(1))).earliest())
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
+
+/// Monday of the week the date falls in
+pub fn week_start_date(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01 09:00:00 UTC and 2024-07-01 09:00:00 UTC
+    const WINTER: i64 = 1_704_099_600;
+    const SUMMER: i64 = 1_719_824_400;
+
+    #[test]
+    fn test_format_across_zones() {
+        let tokyo = parse_timezone("Asia/Tokyo").unwrap();
+        let new_york = parse_timezone("America/New_York").unwrap();
+
+        assert_eq!(format_timestamp(WINTER, tokyo), "2024-01-01 18:00 +09:00");
+        assert_eq!(format_timestamp(WINTER, new_york), "2024-01-01 04:00 -05:00");
+        // Daylight saving time shows in the offset
+        assert_eq!(format_timestamp(SUMMER, tokyo), "2024-07-01 18:00 +09:00");
+        assert_eq!(format_timestamp(SUMMER, new_york), "2024-07-01 05:00 -04:00");
+        assert_eq!(format_timestamp(WINTER, Tz::UTC), "2024-01-01 09:00 +00:00");
+    }
+
+    #[test]
+    fn test_parse_timezone_rejects_unknown_names() {
+        assert!(parse_timezone(" Europe/Berlin ").is_ok());
+        assert!(parse_timezone("JST+9").is_err());
+        assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+        assert!(parse_timezone("").is_err());
+    }
+}
@@ -1,6 +1,31 @@
+pub mod api_usage;
+pub mod keychain_access;
+pub mod models;
+pub mod rate_limit;
+#[cfg(any(test, feature = "github-fixtures"))]
+pub mod replay;
+pub mod token_grants;
+pub mod token_permissions;
+pub mod url;
+
 use anyhow::Result;
+use base64::prelude::{Engine, BASE64_STANDARD};
 use keyring::Entry;
+use models::{
+    GitHubCompare, GitHubDeployment, GitHubDeploymentStatus, GitHubIssue, GitHubIssueData, GitHubPRData, GitHubPRFile, GitHubPRHeadOnly, GitHubPRLocation,
+    GitHubPRSummary, GitHubReactions, GitHubRelease, GitHubReview, GitHubReviewComment, GitHubTimelineEvent, GraphQLNodeResponse,
+};
 use serde::{Deserialize, Serialize};
+use api_usage::ApiFeature;
+use keychain_access::TokenAccessDenied;
+use token_grants::TokenGrant;
+use token_permissions::Permission;
+
+// Error returned when GitHub reports the rate limit is exhausted; callers stop batching on it
+pub const RATE_LIMITED_ERROR: &str = "GitHub API rate limit exceeded";
+
+// Error returned when a command gets no token and none is stored in the keychain
+pub const NO_TOKEN_ERROR: &str = "No GitHub token configured; add one in Settings";
 
 // GitHub API response structures
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +45,35 @@ pub struct GitHubTokenInfo {
     pub scopes: Vec<String>,
     pub rate_limit_remaining: Option<u32>,
     pub rate_limit_total: Option<u32>,
+    pub rate_limit_pause: Option<String>, // filled in by the connection check while calls are held back
+    pub token_expiry_warning: Option<String>, // filled in by the connection check when the token expires soon
+    pub keychain_access_denied: bool, // the keychain refused to hand over the stored token (not the same as no token)
+    pub last_sync: Option<String>, // filled in by the connection check from the latest sync run
+}
+
+// Every GitHub API request goes out through here, so tests can answer it from recorded fixtures
+trait SendGitHub {
+    fn send_github(self) -> impl std::future::Future<Output = reqwest::Result<reqwest::Response>>;
+}
+
+impl SendGitHub for reqwest::RequestBuilder {
+    #[cfg(not(any(test, feature = "github-fixtures")))]
+    async fn send_github(self) -> reqwest::Result<reqwest::Response> {
+        self.send().await
+    }
+
+    #[cfg(any(test, feature = "github-fixtures"))]
+    async fn send_github(self) -> reqwest::Result<reqwest::Response> {
+        let (client, request) = self.build_split();
+        replay::send(&client, request?).await
+    }
+}
+
+// Every GitHub response goes through here so quota and token-expiry headers are never missed
+fn record_response(response: &reqwest::Response, feature: ApiFeature) {
+    api_usage::record(feature, chrono::Utc::now().timestamp());
+    rate_limit::record_response(response);
+    token_grants::record_token_expiry(response.status().as_u16(), response.headers());
 }
 
 const KEYCHAIN_SERVICE: &str = "PRTracker";
@@ -46,6 +100,7 @@ impl GitHubTokenManager {
             .map_err(|e| anyhow::anyhow!("Failed to save token to keychain: {}", e))?;
 
         println!("✅ GitHub token saved to macOS Keychain");
+        keychain_access::clear();
 
         // Immediately test retrieval
         match self.entry.get_password() {
@@ -56,8 +111,13 @@ impl GitHubTokenManager {
         Ok(())
     }
 
-    /// Retrieve GitHub token from macOS Keychain
+    /// Retrieve GitHub token from macOS Keychain; fails with TokenAccessDenied without
+    /// touching the keychain while a denial is backing off
     pub fn get_token(&self) -> Result<Option<String>> {
+        let now = chrono::Utc::now().timestamp();
+        if let Some(denied) = keychain_access::check(now) {
+            return Err(denied.into());
+        }
         println!("🔍 Attempting to retrieve GitHub token from keychain (service: {}, account: {})", KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT);
 
         match self.entry.get_password() {
@@ -69,6 +129,10 @@ impl GitHubTokenManager {
                 println!("ℹ️ No GitHub token found in keychain (service: {}, account: {})", KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT);
                 Ok(None)
             }
+            Err(e) if keychain_access::is_access_denied(&e) => {
+                println!("🚫 Keychain access denied; not asking again for {} minutes: {}", keychain_access::KEYCHAIN_BACKOFF_SECS / 60, e);
+                Err(keychain_access::record_denied(now).into())
+            }
             Err(e) => {
                 println!("❌ Failed to retrieve token from keychain: {} (service: {}, account: {})", e, KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT);
                 Err(anyhow::anyhow!("Failed to retrieve token from keychain: {}", e))
@@ -107,8 +171,9 @@ impl GitHubTokenManager {
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "PRTracker/1.0")
             .header("Accept", "application/vnd.github.v3+json")
-            .send()
+            .send_github()
             .await?;
+        record_response(&response, ApiFeature::TokenCheck);
 
         // Check rate limits from headers
         let rate_limit_remaining = response
@@ -147,6 +212,10 @@ impl GitHubTokenManager {
                 scopes,
                 rate_limit_remaining,
                 rate_limit_total,
+                rate_limit_pause: None,
+                token_expiry_warning: None,
+                keychain_access_denied: false,
+                last_sync: None,
             })
         } else {
             let status = response.status();
@@ -160,30 +229,817 @@ impl GitHubTokenManager {
                 scopes: Vec::new(),
                 rate_limit_remaining,
                 rate_limit_total,
+                rate_limit_pause: None,
+                token_expiry_warning: None,
+                keychain_access_denied: false,
+                last_sync: None,
             })
         }
     }
 
     /// Test connection with current stored token
     pub async fn test_stored_token(&self) -> Result<GitHubTokenInfo> {
-        if let Some(token) = self.get_token()? {
-            self.verify_token(&token).await
-        } else {
-            Ok(GitHubTokenInfo {
-                valid: false,
-                user: None,
-                scopes: Vec::new(),
-                rate_limit_remaining: None,
-                rate_limit_total: None,
-            })
+        let keychain_access_denied = match self.get_token() {
+            Ok(Some(token)) => return self.verify_token(&token).await,
+            Ok(None) => false,
+            Err(e) if e.is::<TokenAccessDenied>() => true,
+            Err(e) => return Err(e),
+        };
+        Ok(GitHubTokenInfo {
+            valid: false,
+            user: None,
+            scopes: Vec::new(),
+            rate_limit_remaining: None,
+            rate_limit_total: None,
+            rate_limit_pause: None,
+            token_expiry_warning: None,
+            keychain_access_denied,
+            last_sync: None,
+        })
+    }
+}
+
+/// The token a command should use: the one passed in, or the stored one when it's omitted or empty
+pub fn token_or_stored(token: Option<String>) -> Result<String, String> {
+    resolve_token(token, || GitHubTokenManager::new()?.get_token())
+}
+
+fn resolve_token(token: Option<String>, stored: impl FnOnce() -> Result<Option<String>>) -> Result<String, String> {
+    if let Some(token) = token.filter(|token| !token.trim().is_empty()) {
+        return Ok(token);
+    }
+    match stored().map_err(|e| e.to_string())? {
+        Some(token) if !token.trim().is_empty() => Ok(token),
+        _ => Err(NO_TOKEN_ERROR.to_string()),
+    }
+}
+
+/// Whether a fetch_github_pr_data error is GitHub's 404 for the PR or its repository: deleted
+/// upstream, or hidden from the token
+pub fn is_pr_not_found_error(error: &str) -> bool {
+    (error.starts_with("PR #") && error.contains(" not found in repository "))
+        || (error.starts_with("Cannot access repository ") && error.contains("Status: 404"))
+}
+
+/// Fetch a single pull request from the GitHub API
+pub async fn fetch_github_pr_data(feature: ApiFeature, token: &str, owner: &str, repo: &str, pr_number: i64) -> Result<GitHubPRData, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = format!("https://api.github.com/repos/{}/{}/pulls/{}", owner, repo, pr_number);
+    println!("📡 Making GitHub API request to: {}", url);
+    println!("🔑 Token format check: first 10 chars = '{}...', last 4 chars = '...{}'",
+        &token.chars().take(10).collect::<String>(),
+        &token.chars().rev().take(4).collect::<String>().chars().rev().collect::<String>()
+    );
+
+    // First, let's test if we can access the repository at all
+    let repo_url = format!("https://api.github.com/repos/{}/{}", owner, repo);
+    println!("🔍 Testing repository access: {}", repo_url);
+
+    let repo_response = client
+        .get(&repo_url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to test repository access: {}", e))?;
+    record_response(&repo_response, feature);
+
+    let repo_status = repo_response.status();
+    println!("🏛️ Repository access status: {}", repo_status);
+    if !repo_status.is_success() {
+        let repo_error = repo_response.text().await.unwrap_or_default();
+        println!("❌ Repository access error: {}", repo_error);
+        return Err(format!("Cannot access repository {}/{}. Status: {} - {}", owner, repo, repo_status, repo_error));
+    } else {
+        println!("✅ Repository access successful");
+    }
+
+    // Now try to access the specific PR
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch PR data: {}", e))?;
+    record_response(&response, feature);
+
+    let status = response.status();
+    println!("📊 GitHub API response status: {}", status);
+
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        println!("❌ GitHub API error response: {}", error_text);
+
+        // Provide more specific error messages based on status code
+        let error_msg = match status.as_u16() {
+            404 => {
+                // More specific 404 debugging
+                format!("PR #{} not found in repository {}/{}. Possible reasons:\n\
+                        • PR number doesn't exist\n\
+                        • PR might be in 'draft' state\n\
+                        • Fine-grained token doesn't have 'Pull requests' read permission\n\
+                        • Token doesn't have access to this specific repository\n\
+                        \nPlease check:\n\
+                        1. The PR URL is correct: https://github.com/{}/{}/pull/{}\n\
+                        2. Your fine-grained token has 'Pull requests' read permission\n\
+                        3. Your token has access to the {} repository",
+                        pr_number, owner, repo, owner, repo, pr_number, repo)
+            },
+            401 => "GitHub token is invalid or expired. Please update your token in settings.".to_string(),
+            403 => {
+                if error_text.contains("forbids access via a personal access token (classic)") {
+                    "Organization requires fine-grained token. This organization blocks classic tokens. \
+                    Please create a fine-grained personal access token at GitHub Settings > Personal Access Tokens > Fine-grained tokens.".to_string()
+                } else {
+                    "Access forbidden. For private repositories, your GitHub token needs the 'repo' scope. \
+                    Please go to GitHub Settings > Personal Access Tokens and create a new token with 'repo' permission.".to_string()
+                }
+            },
+            _ => format!("GitHub API error: {} - {}", status, error_text)
+        };
+
+        return Err(error_msg);
+    }
+
+    let pr_data: GitHubPRData = response.json().await
+        .map_err(|e| format!("Failed to parse GitHub API response: {}", e))?;
+
+    println!("✅ Successfully parsed PR data");
+    Ok(pr_data)
+}
+
+/// Report what the token grants and when it expires, from the headers on `GET /user`
+pub async fn fetch_token_grant(feature: ApiFeature, token: &str) -> Result<TokenGrant, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get("https://api.github.com/user")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to check token: {}", e))?;
+    record_response(&response, feature);
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("GitHub rejected the token: {}", status));
+    }
+
+    let headers = response.headers().clone();
+    let user: GitHubUser = response.json().await.map_err(|e| format!("Failed to parse user: {}", e))?;
+    Ok(token_grants::token_grant(token, &headers, Some(user.login), chrono::Utc::now().timestamp()))
+}
+
+/// Pulls list filtered to one head branch. Each piece is encoded separately, so branch
+/// names like "feature/login" or "fix#12 & more" reach GitHub intact.
+pub fn branch_pulls_url(owner: &str, repo: &str, branch: &str) -> Result<reqwest::Url, String> {
+    let mut url = reqwest::Url::parse("https://api.github.com").map_err(|e| e.to_string())?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid GitHub API base URL".to_string())?
+        .extend(["repos", owner, repo, "pulls"]);
+    url.query_pairs_mut()
+        .append_pair("head", &format!("{}:{}", owner, branch))
+        .append_pair("state", "all")
+        .append_pair("per_page", "100");
+    Ok(url)
+}
+
+/// PRs (open, closed and merged) whose head is `owner:branch`, newest first.
+/// Ok(None) when the token can't read the repository.
+pub async fn fetch_prs_for_branch(feature: ApiFeature, token: &str, owner: &str, repo: &str, branch: &str) -> Result<Option<Vec<GitHubPRSummary>>, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = branch_pulls_url(owner, repo, branch)?;
+
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to look up PRs for branch: {}", e))?;
+    record_response(&response, feature);
+
+    match response.status().as_u16() {
+        200..=299 => {}
+        403 | 404 => return Ok(None),
+        401 => return Err("GitHub token is invalid or expired. Please update your token in settings.".to_string()),
+        _ => return Err(format!("GitHub API error listing PRs for {}/{}: {}", owner, repo, response.status())),
+    }
+
+    let prs = response.json().await
+        .map_err(|e| format!("Failed to parse GitHub pulls response: {}", e))?;
+    Ok(Some(prs))
+}
+
+// Page size for the list endpoints that are paged through
+pub const LIST_PAGE_SIZE: usize = 100;
+
+/// One page of a repository's PRs in `state` ("open" | "closed"), most recently updated first.
+/// Ok(None) when the token can't read the repository.
+pub async fn fetch_pulls_page(feature: ApiFeature, token: &str, owner: &str, repo: &str, state: &str, page: u32) -> Result<Option<Vec<GitHubPRSummary>>, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let mut url = reqwest::Url::parse("https://api.github.com").map_err(|e| e.to_string())?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid GitHub API base URL".to_string())?
+        .extend(["repos", owner, repo, "pulls"]);
+    url.query_pairs_mut()
+        .append_pair("state", state)
+        .append_pair("sort", "updated")
+        .append_pair("direction", "desc")
+        .append_pair("per_page", &LIST_PAGE_SIZE.to_string())
+        .append_pair("page", &page.to_string());
+
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to list PRs: {}", e))?;
+    record_response(&response, feature);
+
+    match response.status().as_u16() {
+        200..=299 => {}
+        403 | 404 => return Ok(None),
+        401 => return Err("GitHub token is invalid or expired. Please update your token in settings.".to_string()),
+        _ => return Err(format!("GitHub API error listing PRs for {}/{}: {}", owner, repo, response.status())),
+    }
+
+    let prs = response.json().await
+        .map_err(|e| format!("Failed to parse GitHub pulls response: {}", e))?;
+    Ok(Some(prs))
+}
+
+/// One page of a repository's PR review comments updated since `since` (RFC 3339), oldest first.
+/// Ok(None) when the token can't read the repository.
+pub async fn fetch_review_comments_page(feature: ApiFeature, token: &str, owner: &str, repo: &str, since: &str, page: u32) -> Result<Option<Vec<GitHubReviewComment>>, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let mut url = reqwest::Url::parse("https://api.github.com").map_err(|e| e.to_string())?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid GitHub API base URL".to_string())?
+        .extend(["repos", owner, repo, "pulls", "comments"]);
+    url.query_pairs_mut()
+        .append_pair("since", since)
+        .append_pair("sort", "created")
+        .append_pair("direction", "asc")
+        .append_pair("per_page", &LIST_PAGE_SIZE.to_string())
+        .append_pair("page", &page.to_string());
+
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch review comments: {}", e))?;
+    record_response(&response, feature);
+
+    match response.status().as_u16() {
+        200..=299 => {}
+        403 | 404 => return Ok(None),
+        401 => return Err("GitHub token is invalid or expired. Please update your token in settings.".to_string()),
+        _ => return Err(format!("GitHub API error listing review comments for {}/{}: {}", owner, repo, response.status())),
+    }
+
+    let comments = response.json().await
+        .map_err(|e| format!("Failed to parse review comments: {}", e))?;
+    Ok(Some(comments))
+}
+
+/// One page of a repository's releases, newest first. Ok(None) when the token can't read the repository.
+pub async fn fetch_releases_page(feature: ApiFeature, token: &str, owner: &str, repo: &str, page: u32) -> Result<Option<Vec<GitHubRelease>>, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let mut url = reqwest::Url::parse("https://api.github.com").map_err(|e| e.to_string())?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid GitHub API base URL".to_string())?
+        .extend(["repos", owner, repo, "releases"]);
+    url.query_pairs_mut()
+        .append_pair("per_page", &LIST_PAGE_SIZE.to_string())
+        .append_pair("page", &page.to_string());
+
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch releases: {}", e))?;
+    record_response(&response, feature);
+
+    match response.status().as_u16() {
+        200..=299 => {}
+        403 | 404 => return Ok(None),
+        401 => return Err("GitHub token is invalid or expired. Please update your token in settings.".to_string()),
+        _ => return Err(format!("GitHub API error listing releases for {}/{}: {}", owner, repo, response.status())),
+    }
+
+    let releases = response.json().await
+        .map_err(|e| format!("Failed to parse releases: {}", e))?;
+    Ok(Some(releases))
+}
+
+/// Compare URL for base...head; each ref is one path segment, so tags like "release/1.2" stay intact
+pub fn compare_url(owner: &str, repo: &str, base: &str, head: &str) -> Result<reqwest::Url, String> {
+    let mut url = reqwest::Url::parse("https://api.github.com").map_err(|e| e.to_string())?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid GitHub API base URL".to_string())?
+        .extend(["repos", owner, repo, "compare", &format!("{}...{}", base, head)]);
+    // Only the status is read; keep the commit list short
+    url.query_pairs_mut().append_pair("per_page", "1");
+    Ok(url)
+}
+
+/// How `head` relates to `base`: "ahead", "behind", "identical" or "diverged"
+pub async fn fetch_compare_status(feature: ApiFeature, token: &str, owner: &str, repo: &str, base: &str, head: &str) -> Result<String, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = compare_url(owner, repo, base, head)?;
+
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to compare commits: {}", e))?;
+    record_response(&response, feature);
+
+    if !response.status().is_success() {
+        return Err(format!("GitHub API error comparing {}...{} in {}/{}: {}", base, head, owner, repo, response.status()));
+    }
+
+    let compare: GitHubCompare = response.json().await
+        .map_err(|e| format!("Failed to parse compare response: {}", e))?;
+    Ok(compare.status)
+}
+
+/// Whether the token can read the repository: Ok(false) on 403/404, Err on rate limiting or other failures
+pub async fn check_repo_access(feature: ApiFeature, token: &str, owner: &str, repo: &str) -> Result<bool, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = format!("https://api.github.com/repos/{}/{}", owner, repo);
+
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to check repository access: {}", e))?;
+    record_response(&response, feature);
+
+    let status = response.status();
+    let rate_limited = response.headers()
+        .get("x-ratelimit-remaining")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == "0");
+
+    if status.as_u16() == 429 || (status.as_u16() == 403 && rate_limited) {
+        return Err(RATE_LIMITED_ERROR.to_string());
+    }
+
+    match status.as_u16() {
+        200..=299 => Ok(true),
+        403 | 404 => Ok(false),
+        _ => Err(format!("GitHub API error checking {}/{}: {}", owner, repo, status)),
+    }
+}
+
+/// Whether the token can read one permission's probe endpoint on owner/repo (one item at most)
+pub async fn probe_repo_permission(feature: ApiFeature, token: &str, owner: &str, repo: &str, permission: Permission) -> Result<bool, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let mut url = reqwest::Url::parse("https://api.github.com").map_err(|e| e.to_string())?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid GitHub API base URL".to_string())?
+        .extend(["repos", owner, repo])
+        .extend(permission.probe_path());
+    if !permission.probe_path().is_empty() {
+        url.query_pairs_mut().append_pair("per_page", "1");
+    }
+
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to check the {} permission: {}", permission.as_str(), e))?;
+    record_response(&response, feature);
+
+    let status = response.status();
+    let rate_limited = response.headers()
+        .get("x-ratelimit-remaining")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == "0");
+
+    if status.as_u16() == 429 || (status.as_u16() == 403 && rate_limited) {
+        return Err(RATE_LIMITED_ERROR.to_string());
+    }
+
+    match status.as_u16() {
+        200..=299 => Ok(true),
+        403 | 404 => Ok(false),
+        401 => Err("GitHub token is invalid or expired. Please update your token in settings.".to_string()),
+        _ => Err(format!("GitHub API error checking {} on {}/{}: {}", permission.as_str(), owner, repo, status)),
+    }
+}
+
+/// Fetch reaction counts from the issues endpoint (the pulls payload doesn't always carry them)
+pub async fn fetch_github_issue_reactions(feature: ApiFeature, token: &str, owner: &str, repo: &str, number: i64) -> Result<Option<GitHubReactions>, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = format!("https://api.github.com/repos/{}/{}/issues/{}", owner, repo, number);
+
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch issue reactions: {}", e))?;
+    record_response(&response, feature);
+
+    if !response.status().is_success() {
+        return Err(format!("GitHub API error fetching reactions: {}", response.status()));
+    }
+
+    let issue: GitHubIssueData = response.json().await
+        .map_err(|e| format!("Failed to parse GitHub issue response: {}", e))?;
+
+    Ok(issue.reactions)
+}
+
+/// Fetch a single issue (the payload carries `pull_request` when the number is actually a PR)
+pub async fn fetch_github_issue(feature: ApiFeature, token: &str, owner: &str, repo: &str, number: i64) -> Result<GitHubIssue, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = format!("https://api.github.com/repos/{}/{}/issues/{}", owner, repo, number);
+
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch issue: {}", e))?;
+    record_response(&response, feature);
+
+    match response.status().as_u16() {
+        200..=299 => {}
+        404 => return Err(format!("Issue #{} not found in {}/{}", number, owner, repo)),
+        _ => return Err(format!("GitHub API error fetching issue: {}", response.status())),
+    }
+
+    response.json().await.map_err(|e| format!("Failed to parse GitHub issue response: {}", e))
+}
+
+/// First page (100 events) of an issue's timeline
+pub async fn fetch_issue_timeline(feature: ApiFeature, token: &str, owner: &str, repo: &str, number: i64) -> Result<Vec<GitHubTimelineEvent>, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = format!("https://api.github.com/repos/{}/{}/issues/{}/timeline?per_page=100", owner, repo, number);
+
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch issue timeline: {}", e))?;
+    record_response(&response, feature);
+
+    if !response.status().is_success() {
+        return Err(format!("GitHub API error fetching issue timeline: {}", response.status()));
+    }
+
+    response.json().await.map_err(|e| format!("Failed to parse GitHub timeline response: {}", e))
+}
+
+/// First page (100 reviews) of a PR's submitted reviews, oldest first
+pub async fn fetch_pr_reviews(feature: ApiFeature, token: &str, owner: &str, repo: &str, number: i64) -> Result<Vec<GitHubReview>, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = format!("https://api.github.com/repos/{}/{}/pulls/{}/reviews?per_page=100", owner, repo, number);
+
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch PR reviews: {}", e))?;
+    record_response(&response, feature);
+
+    if !response.status().is_success() {
+        return Err(format!("GitHub API error fetching PR reviews: {}", response.status()));
+    }
+
+    response.json().await.map_err(|e| format!("Failed to parse GitHub reviews response: {}", e))
+}
+
+/// Current head commit SHA of a PR
+pub async fn fetch_pr_head_sha(feature: ApiFeature, token: &str, owner: &str, repo: &str, number: i64) -> Result<String, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let url = format!("https://api.github.com/repos/{}/{}/pulls/{}", owner, repo, number);
+
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch PR head: {}", e))?;
+    record_response(&response, feature);
+
+    if !response.status().is_success() {
+        return Err(format!("GitHub API error fetching PR head: {}", response.status()));
+    }
+
+    let pr: GitHubPRHeadOnly = response.json().await
+        .map_err(|e| format!("Failed to parse GitHub PR response: {}", e))?;
+
+    Ok(pr.head.sha)
+}
+
+// Deployment lookups: a plain 403 or 404 means the token lacks the deployments permission
+// (Ok(None)), while an exhausted rate limit is still an error
+async fn fetch_deployments_json<T: serde::de::DeserializeOwned>(feature: ApiFeature, token: &str, url: reqwest::Url) -> Result<Option<T>, String> {
+    rate_limit::ensure_not_paused()?;
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to fetch deployments: {}", e))?;
+    record_response(&response, feature);
+
+    let status = response.status();
+    let rate_limited = response.headers()
+        .get("x-ratelimit-remaining")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == "0");
+    if status.as_u16() == 429 || (status.as_u16() == 403 && rate_limited) {
+        return Err(RATE_LIMITED_ERROR.to_string());
+    }
+
+    match status.as_u16() {
+        200..=299 => {}
+        403 | 404 => return Ok(None),
+        401 => return Err("GitHub token is invalid or expired. Please update your token in settings.".to_string()),
+        _ => return Err(format!("GitHub API error fetching deployments: {}", status)),
+    }
+    let body = response.json().await
+        .map_err(|e| format!("Failed to parse GitHub deployments response: {}", e))?;
+    Ok(Some(body))
+}
+
+fn deployments_url(owner: &str, repo: &str, path: &[&str]) -> Result<reqwest::Url, String> {
+    let mut url = reqwest::Url::parse("https://api.github.com").map_err(|e| e.to_string())?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid GitHub API base URL".to_string())?
+        .extend(["repos", owner, repo, "deployments"])
+        .extend(path);
+    Ok(url)
+}
+
+/// Deployments created for a commit, newest first (one page).
+/// Ok(None) when the token can't read deployments.
+pub async fn fetch_deployments_for_sha(feature: ApiFeature, token: &str, owner: &str, repo: &str, sha: &str) -> Result<Option<Vec<GitHubDeployment>>, String> {
+    let mut url = deployments_url(owner, repo, &[])?;
+    url.query_pairs_mut()
+        .append_pair("sha", sha)
+        .append_pair("per_page", &LIST_PAGE_SIZE.to_string());
+    fetch_deployments_json(feature, token, url).await
+}
+
+/// The latest status of one deployment, None while it has none yet.
+/// Ok(None) when the token can't read deployments.
+pub async fn fetch_latest_deployment_status(feature: ApiFeature, token: &str, owner: &str, repo: &str, deployment_id: i64) -> Result<Option<Option<GitHubDeploymentStatus>>, String> {
+    let mut url = deployments_url(owner, repo, &[&deployment_id.to_string(), "statuses"])?;
+    url.query_pairs_mut().append_pair("per_page", "1");
+    let statuses: Option<Vec<GitHubDeploymentStatus>> = fetch_deployments_json(feature, token, url).await?;
+    Ok(statuses.map(|statuses| statuses.into_iter().next()))
+}
+
+/// Changed files of a PR, following pagination up to `max_files`.
+/// Returns the files and whether more were available beyond the cap.
+pub async fn fetch_pr_files(feature: ApiFeature, token: &str, owner: &str, repo: &str, number: i64, max_files: usize) -> Result<(Vec<GitHubPRFile>, bool), String> {
+    const PER_PAGE: usize = 100;
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let mut files: Vec<GitHubPRFile> = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/pulls/{}/files?per_page={}&page={}",
+            owner, repo, number, PER_PAGE, page
+        );
+        let response = client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("User-Agent", "PR-Tracker")
+            .header("Accept", "application/vnd.github.v3+json")
+            .send_github()
+            .await
+            .map_err(|e| format!("Failed to fetch PR files: {}", e))?;
+        record_response(&response, feature);
+
+        if !response.status().is_success() {
+            return Err(format!("GitHub API error fetching PR files: {}", response.status()));
         }
+
+        let has_next = response.headers()
+            .get("link")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|link| link.contains("rel=\"next\""));
+        let batch: Vec<GitHubPRFile> = response.json().await
+            .map_err(|e| format!("Failed to parse GitHub files response: {}", e))?;
+
+        files.extend(batch);
+        if files.len() >= max_files {
+            let truncated = files.len() > max_files || has_next;
+            files.truncate(max_files);
+            return Ok((files, truncated));
+        }
+        if !has_next {
+            return Ok((files, false));
+        }
+        page += 1;
     }
 }
 
+/// Legacy GraphQL node id for a pull request's REST id, e.g. 1 -> base64("011:PullRequest1")
+fn pull_request_node_id(github_id: i64) -> String {
+    let type_name = "PullRequest";
+    BASE64_STANDARD.encode(format!("{:03}:{}{}", type_name.len(), type_name, github_id))
+}
+
+/// Look up a PR's repository and number from its GitHub id alone (via GraphQL)
+pub async fn fetch_pr_location_by_id(feature: ApiFeature, token: &str, github_id: i64) -> Result<Option<GitHubPRLocation>, String> {
+    rate_limit::ensure_not_paused()?;
+    let client = reqwest::Client::new();
+    let query = serde_json::json!({
+        "query": "query($id: ID!) { node(id: $id) { ... on PullRequest { number repository { name owner { login } } } } }",
+        "variables": { "id": pull_request_node_id(github_id) },
+    });
+
+    let response = client
+        .post("https://api.github.com/graphql")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "PR-Tracker")
+        .json(&query)
+        .send_github()
+        .await
+        .map_err(|e| format!("Failed to query GitHub GraphQL API: {}", e))?;
+    record_response(&response, feature);
+
+    if !response.status().is_success() {
+        return Err(format!("GitHub GraphQL API error: {}", response.status()));
+    }
+
+    // Unknown ids come back as a null node (with an error entry), not as a failed request
+    let body: GraphQLNodeResponse = response.json().await
+        .map_err(|e| format!("Failed to parse GitHub GraphQL response: {}", e))?;
+
+    Ok(body.data.and_then(|d| d.node))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolve_token_prefers_passed_token() {
+        let token = resolve_token(Some("ghp_passed".to_string()), || panic!("keychain read")).unwrap();
+        assert_eq!(token, "ghp_passed");
+
+        // Omitted or blank falls back to the keychain
+        assert_eq!(resolve_token(None, || Ok(Some("ghp_stored".to_string()))).unwrap(), "ghp_stored");
+        assert_eq!(resolve_token(Some("  ".to_string()), || Ok(Some("ghp_stored".to_string()))).unwrap(), "ghp_stored");
+    }
+
+    #[test]
+    fn test_resolve_token_without_stored_token() {
+        assert_eq!(resolve_token(None, || Ok(None)).unwrap_err(), NO_TOKEN_ERROR);
+        assert_eq!(resolve_token(Some(String::new()), || Ok(Some(String::new()))).unwrap_err(), NO_TOKEN_ERROR);
+
+        let denied = resolve_token(None, || Err(TokenAccessDenied { retry_at: 0 }.into())).unwrap_err();
+        assert_ne!(denied, NO_TOKEN_ERROR);
+    }
+
+    #[test]
+    fn test_pull_request_node_id() {
+        // Matches node_id in the pulls API fixture for id 1
+        assert_eq!(pull_request_node_id(1), "MDExOlB1bGxSZXF1ZXN0MQ==");
+    }
+
+    #[test]
+    fn test_branch_pulls_url_encodes_branch() {
+        let url = branch_pulls_url("acme", "api", "feature/login fix#12&x=1").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://api.github.com/repos/acme/api/pulls?head=acme%3Afeature%2Flogin+fix%2312%26x%3D1&state=all&per_page=100"
+        );
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(pairs[0], ("head".to_string(), "acme:feature/login fix#12&x=1".to_string()));
+    }
+
+    #[test]
+    fn test_compare_url_keeps_tag_in_one_segment() {
+        let url = compare_url("acme", "api", "release/1.2", "e5bd3914").unwrap();
+        assert_eq!(url.as_str(), "https://api.github.com/repos/acme/api/compare/release%2F1.2...e5bd3914?per_page=1");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pr_data_from_fixtures() {
+        let data = replay::with_fixtures(
+            &["default"],
+            fetch_github_pr_data(ApiFeature::PrRefresh, "test-token", "octo-org", "widget-service", 1347),
+        ).await.unwrap();
+
+        assert_eq!(data.title, "Amazing new feature");
+        assert_eq!(data.user.login, "octocat");
+        assert_eq!(data.head.ref_field, "new-topic");
+        assert!(!data.merged);
+        assert_eq!(data.reactions.unwrap().plus_one, 9);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pr_data_not_found() {
+        let error = replay::with_fixtures(
+            &["pr_not_found", "default"],
+            fetch_github_pr_data(ApiFeature::PrRefresh, "test-token", "octo-org", "widget-service", 1347),
+        ).await.unwrap_err();
+
+        assert!(error.starts_with("PR #1347 not found in repository octo-org/widget-service"), "{}", error);
+        assert!(is_pr_not_found_error(&error));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pr_data_with_expired_token() {
+        let error = replay::with_fixtures(
+            &["token_expired", "default"],
+            fetch_github_pr_data(ApiFeature::PrRefresh, "test-token", "octo-org", "widget-service", 1347),
+        ).await.unwrap_err();
+
+        assert!(error.starts_with("Cannot access repository octo-org/widget-service. Status: 401"), "{}", error);
+        assert!(!is_pr_not_found_error(&error));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token_grant_from_fixtures() {
+        let grant = replay::with_fixtures(&["default"], fetch_token_grant(ApiFeature::TokenCheck, "ghp_test")).await.unwrap();
+        assert_eq!(grant.login.as_deref(), Some("octocat"));
+        assert_eq!(grant.scopes, vec!["repo", "read:org"]);
+
+        let error = replay::with_fixtures(&["token_expired"], fetch_token_grant(ApiFeature::TokenCheck, "ghp_test")).await.unwrap_err();
+        assert_eq!(error, "GitHub rejected the token: 401 Unauthorized");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pulls_from_fixtures() {
+        let branch_prs = replay::with_fixtures(
+            &["default"],
+            fetch_prs_for_branch(ApiFeature::BranchLookup, "test-token", "octo-org", "widget-service", "new-topic"),
+        ).await.unwrap().unwrap();
+        assert_eq!(branch_prs.iter().map(|pr| pr.number).collect::<Vec<_>>(), vec![1347]);
+
+        let page = replay::with_fixtures(
+            &["default"],
+            fetch_pulls_page(ApiFeature::Reimport, "test-token", "octo-org", "widget-service", "open", 1),
+        ).await.unwrap().unwrap();
+        assert_eq!(page.iter().map(|pr| pr.number).collect::<Vec<_>>(), vec![1347, 1340]);
+        assert_eq!(page[1].head.ref_field, "tidy-cache");
+    }
+
     #[test]
     fn test_token_manager_creation() {
         let manager = GitHubTokenManager::new();
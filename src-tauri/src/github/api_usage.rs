@@ -0,0 +1,194 @@
+// GitHub calls counted per feature per hour, so a rate-limit pause can be traced back to
+// whatever spent the quota. Counts live in memory and are flushed to the api_usage table
+// by a background task; the table is optional, and the in-memory counts stand alone
+// while the database is closed.
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+const HOUR_SECS: i64 = 60 * 60;
+
+// In-memory counts older than this are dropped; the table keeps the long history
+const MEMORY_RETENTION_HOURS: i64 = 48;
+
+/// What started a GitHub call; every function in github.rs takes one from its caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiFeature {
+    TokenCheck,       // token verification, connection test, grant report
+    AddPr,            // adding a PR by URL (including quick add)
+    BranchLookup,
+    Issues,           // adding and refreshing tracked issues
+    PrFiles,
+    RepoRepair,       // backfilling missing repository fields
+    MergeSync,        // closing PRs merged on GitHub
+    RepoAudit,
+    RetryQueue,
+    ScheduledRefresh,
+    PrRefresh,        // refreshing PRs on request, one or all
+    ReviewCommentStats,
+    Releases,         // finding the release that shipped a merged PR
+    Reimport,         // rebuilding one repository's tracked PRs
+    Deployments,      // deployment status in the PR detail
+}
+
+impl ApiFeature {
+    pub const ALL: [ApiFeature; 15] = [
+        ApiFeature::TokenCheck,
+        ApiFeature::AddPr,
+        ApiFeature::BranchLookup,
+        ApiFeature::Issues,
+        ApiFeature::PrFiles,
+        ApiFeature::RepoRepair,
+        ApiFeature::MergeSync,
+        ApiFeature::RepoAudit,
+        ApiFeature::RetryQueue,
+        ApiFeature::ScheduledRefresh,
+        ApiFeature::PrRefresh,
+        ApiFeature::ReviewCommentStats,
+        ApiFeature::Releases,
+        ApiFeature::Reimport,
+        ApiFeature::Deployments,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiFeature::TokenCheck => "token_check",
+            ApiFeature::AddPr => "add_pr",
+            ApiFeature::BranchLookup => "branch_lookup",
+            ApiFeature::Issues => "issues",
+            ApiFeature::PrFiles => "pr_files",
+            ApiFeature::RepoRepair => "repo_repair",
+            ApiFeature::MergeSync => "merge_sync",
+            ApiFeature::RepoAudit => "repo_audit",
+            ApiFeature::RetryQueue => "retry_queue",
+            ApiFeature::ScheduledRefresh => "scheduled_refresh",
+            ApiFeature::PrRefresh => "pr_refresh",
+            ApiFeature::ReviewCommentStats => "review_comment_stats",
+            ApiFeature::Releases => "releases",
+            ApiFeature::Reimport => "reimport",
+            ApiFeature::Deployments => "deployments",
+        }
+    }
+}
+
+/// Calls made for one feature in one hour (hour_start is a UTC hour boundary)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiUsageEntry {
+    pub hour_start: i64,
+    pub feature: String,
+    pub calls: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeatureTotal {
+    pub feature: String,
+    pub calls: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApiUsageReport {
+    pub hours: i64,
+    pub since: i64,
+    pub total_calls: i64,
+    pub features: Vec<FeatureTotal>, // busiest first
+    pub hourly: Vec<ApiUsageEntry>,  // oldest first
+}
+
+pub fn hour_start(at: i64) -> i64 {
+    at.div_euclid(HOUR_SECS) * HOUR_SECS
+}
+
+#[derive(Debug, Default)]
+struct UsageCounters {
+    hourly: BTreeMap<(i64, ApiFeature), i64>,  // this session, for pause logging
+    unsaved: BTreeMap<(i64, ApiFeature), i64>, // not yet flushed to api_usage
+}
+
+static COUNTERS: LazyLock<Mutex<UsageCounters>> = LazyLock::new(|| Mutex::new(UsageCounters::default()));
+
+/// Count one call
+pub fn record(feature: ApiFeature, now: i64) {
+    let hour = hour_start(now);
+    let mut counters = COUNTERS.lock().unwrap();
+    *counters.hourly.entry((hour, feature)).or_default() += 1;
+    *counters.unsaved.entry((hour, feature)).or_default() += 1;
+    let cutoff = hour - MEMORY_RETENTION_HOURS * HOUR_SECS;
+    counters.hourly.retain(|(hour, _), _| *hour >= cutoff);
+}
+
+/// Counts this session since `since`, one entry per hour and feature
+pub fn session_usage(since: i64) -> Vec<ApiUsageEntry> {
+    entries(&COUNTERS.lock().unwrap().hourly, hour_start(since))
+}
+
+/// Counts not yet saved; pass them to Database::add_api_usage
+pub fn take_unsaved() -> Vec<ApiUsageEntry> {
+    let unsaved = std::mem::take(&mut COUNTERS.lock().unwrap().unsaved);
+    entries(&unsaved, i64::MIN)
+}
+
+/// Put counts back after a failed save so the next flush retries them
+pub fn restore_unsaved(entries: &[ApiUsageEntry]) {
+    let mut counters = COUNTERS.lock().unwrap();
+    for entry in entries {
+        if let Some(feature) = parse_feature(&entry.feature) {
+            *counters.unsaved.entry((entry.hour_start, feature)).or_default() += entry.calls;
+        }
+    }
+}
+
+/// Feature with the most calls since `since`, for logging pause decisions
+pub fn top_feature_since(since: i64) -> Option<FeatureTotal> {
+    summarize(&session_usage(since)).into_iter().next()
+}
+
+fn parse_feature(name: &str) -> Option<ApiFeature> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+fn entries(counts: &BTreeMap<(i64, ApiFeature), i64>, from_hour: i64) -> Vec<ApiUsageEntry> {
+    counts.iter()
+        .filter(|((hour, _), _)| *hour >= from_hour)
+        .map(|((hour, feature), calls)| ApiUsageEntry { hour_start: *hour, feature: feature.as_str().to_string(), calls: *calls })
+        .collect()
+}
+
+/// Totals per feature, busiest first (ties by name)
+pub fn summarize(entries: &[ApiUsageEntry]) -> Vec<FeatureTotal> {
+    let mut totals: BTreeMap<&str, i64> = BTreeMap::new();
+    for entry in entries {
+        *totals.entry(entry.feature.as_str()).or_default() += entry.calls;
+    }
+    let mut totals: Vec<FeatureTotal> = totals.into_iter()
+        .map(|(feature, calls)| FeatureTotal { feature: feature.to_string(), calls })
+        .collect();
+    totals.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.feature.cmp(&b.feature)));
+    totals
+}
+
+/// Combine saved and unsaved counts into the report for the last `hours` hours
+pub fn usage_report(hours: i64, since: i64, saved: Vec<ApiUsageEntry>, unsaved: Vec<ApiUsageEntry>) -> ApiUsageReport {
+    let mut merged: BTreeMap<(i64, String), i64> = BTreeMap::new();
+    for entry in saved.into_iter().chain(unsaved).filter(|entry| entry.hour_start >= hour_start(since)) {
+        *merged.entry((entry.hour_start, entry.feature)).or_default() += entry.calls;
+    }
+    let hourly: Vec<ApiUsageEntry> = merged.into_iter()
+        .map(|((hour_start, feature), calls)| ApiUsageEntry { hour_start, feature, calls })
+        .collect();
+    let features = summarize(&hourly);
+
+    ApiUsageReport {
+        hours,
+        since,
+        total_calls: features.iter().map(|f| f.calls).sum(),
+        features,
+        hourly,
+    }
+}
+
+/// Counts waiting to be saved, without taking them
+pub fn peek_unsaved() -> Vec<ApiUsageEntry> {
+    entries(&COUNTERS.lock().unwrap().unsaved, i64::MIN)
+}
@@ -0,0 +1,127 @@
+// Backing off from the keychain after the user clicks "Deny" on the access prompt.
+// Every read would otherwise show the prompt again (the retry worker and scheduler read
+// the token on each tick), so after a denial reads fail fast with TokenAccessDenied until
+// the backoff runs out or retry_keychain_access clears it.
+use std::fmt;
+use std::sync::{LazyLock, Mutex};
+
+use serde::Serialize;
+
+pub const KEYCHAIN_BACKOFF_SECS: i64 = 10 * 60;
+
+// macOS Security framework codes for a refused prompt: errSecUserCanceled,
+// errSecAuthFailed, errSecInteractionNotAllowed. keyring reports them as PlatformFailure.
+const DENIED_CODES: [i32; 3] = [-128, -25293, -25308];
+
+/// The keychain refused access to the stored token
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenAccessDenied {
+    pub retry_at: i64, // the keychain is left alone until then
+}
+
+impl fmt::Display for TokenAccessDenied {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Keychain access to the GitHub token was denied. Open Keychain Access, find the \"PRTracker\" item, \
+             allow PR Tracker under Access Control, then choose \"Retry keychain access\""
+        )
+    }
+}
+
+impl std::error::Error for TokenAccessDenied {}
+
+/// Whether a keyring error means the user (or a policy) refused access, as opposed to a missing entry
+pub fn is_access_denied(error: &keyring::Error) -> bool {
+    match error {
+        keyring::Error::NoStorageAccess(_) => true,
+        keyring::Error::PlatformFailure(inner) => {
+            // The platform error is only exposed as a trait object; its Debug output carries the code
+            format!("{:?}", inner)
+                .split("code: ")
+                .skip(1)
+                .filter_map(|rest| rest.split(|c: char| c != '-' && !c.is_ascii_digit()).next()?.parse::<i32>().ok())
+                .any(|code| DENIED_CODES.contains(&code))
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct KeychainBackoff {
+    retry_at: Option<i64>,
+}
+
+impl KeychainBackoff {
+    /// The denial still in force at `now`, if any
+    pub fn check(&self, now: i64) -> Option<TokenAccessDenied> {
+        self.retry_at
+            .filter(|retry_at| now < *retry_at)
+            .map(|retry_at| TokenAccessDenied { retry_at })
+    }
+
+    pub fn record_denied(&mut self, now: i64) -> TokenAccessDenied {
+        let retry_at = now + KEYCHAIN_BACKOFF_SECS;
+        self.retry_at = Some(retry_at);
+        TokenAccessDenied { retry_at }
+    }
+
+    pub fn clear(&mut self) {
+        self.retry_at = None;
+    }
+}
+
+// Process-wide, like the rate-limit state: the background workers read the token too
+static BACKOFF: LazyLock<Mutex<KeychainBackoff>> = LazyLock::new(|| Mutex::new(KeychainBackoff::default()));
+
+pub fn check(now: i64) -> Option<TokenAccessDenied> {
+    BACKOFF.lock().unwrap().check(now)
+}
+
+pub fn record_denied(now: i64) -> TokenAccessDenied {
+    BACKOFF.lock().unwrap().record_denied(now)
+}
+
+/// Let the next read try the keychain again (the user says they've granted access)
+pub fn clear() {
+    BACKOFF.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct SecError {
+        #[allow(dead_code)] // only read through Debug
+        code: i32,
+    }
+
+    impl fmt::Display for SecError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "security error")
+        }
+    }
+
+    impl std::error::Error for SecError {}
+
+    #[test]
+    fn test_denial_detection_and_backoff() {
+        assert!(is_access_denied(&keyring::Error::PlatformFailure(Box::new(SecError { code: -128 }))));
+        assert!(is_access_denied(&keyring::Error::PlatformFailure(Box::new(SecError { code: -25293 }))));
+        assert!(is_access_denied(&keyring::Error::NoStorageAccess(Box::new(SecError { code: -25291 }))));
+        assert!(!is_access_denied(&keyring::Error::PlatformFailure(Box::new(SecError { code: -1280 }))));
+        assert!(!is_access_denied(&keyring::Error::NoEntry));
+
+        let mut backoff = KeychainBackoff::default();
+        assert_eq!(backoff.check(1_000), None);
+        let denied = backoff.record_denied(1_000);
+        assert_eq!(denied.retry_at, 1_000 + KEYCHAIN_BACKOFF_SECS);
+        assert_eq!(backoff.check(1_000 + KEYCHAIN_BACKOFF_SECS - 1), Some(denied));
+        assert_eq!(backoff.check(1_000 + KEYCHAIN_BACKOFF_SECS), None);
+
+        backoff.record_denied(2_000);
+        backoff.clear();
+        assert_eq!(backoff.check(2_001), None);
+    }
+}
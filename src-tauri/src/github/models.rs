@@ -0,0 +1,381 @@
+// GitHub API DTOs for pull request data
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubPRData {
+    pub id: i64,
+    pub title: String,
+    pub user: GitHubPRUser,
+    pub head: GitHubHead,
+    #[serde(default)]
+    pub state: String, // "open" | "closed"; merged PRs are closed too
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub merged: bool,
+    pub merged_at: Option<String>,
+    // Set once merged; before that GitHub reports a test merge commit, which is never stored
+    #[serde(default)]
+    pub merge_commit_sha: Option<String>,
+    // Older GitHub Enterprise versions omit the reactions block entirely
+    #[serde(default)]
+    pub reactions: Option<GitHubReactions>,
+    // Size of the change; only the single-PR endpoint reports these, list responses omit them
+    #[serde(default)]
+    pub additions: Option<i64>,
+    #[serde(default)]
+    pub deletions: Option<i64>,
+    #[serde(default)]
+    pub changed_files: Option<i64>,
+}
+
+// Reaction rollup on a PR/issue description
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct GitHubReactions {
+    #[serde(default)]
+    pub total_count: i64,
+    #[serde(rename = "+1", default)]
+    pub plus_one: i64,
+}
+
+// Issue payload, used only for the reactions the pulls endpoint may not include
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubIssueData {
+    #[serde(default)]
+    pub reactions: Option<GitHubReactions>,
+}
+
+// Full issue payload, for tracking planned work before it becomes a PR
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubIssue {
+    pub id: i64,
+    pub number: i64,
+    pub title: String,
+    pub user: GitHubPRUser,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    // Present when the issue is (or was converted into) a pull request
+    #[serde(default)]
+    pub pull_request: Option<serde_json::Value>,
+}
+
+// Issue timeline entry; only cross-references are of interest
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubTimelineEvent {
+    pub event: String,
+    pub source: Option<GitHubTimelineSource>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubTimelineSource {
+    pub issue: Option<GitHubTimelineIssue>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubTimelineIssue {
+    pub number: i64,
+    #[serde(default)]
+    pub pull_request: Option<serde_json::Value>,
+    pub repository: Option<GitHubRepoRef>,
+}
+
+/// Most recent pull request that cross-references the issue, as (owner, repo, number)
+pub fn linked_pull_request(events: &[GitHubTimelineEvent]) -> Option<(String, String, i64)> {
+    events.iter().rev()
+        .filter(|e| e.event == "cross-referenced")
+        .filter_map(|e| e.source.as_ref()?.issue.as_ref())
+        .filter(|issue| issue.pull_request.is_some())
+        .find_map(|issue| {
+            let repository = issue.repository.as_ref()?;
+            Some((repository.owner.login.clone(), repository.name.clone(), issue.number))
+        })
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GitHubPRUser {
+    pub login: String,
+    pub avatar_url: String,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubHead {
+    #[serde(rename = "ref")]
+    pub ref_field: String,
+    #[serde(default)]
+    pub sha: String,
+}
+
+// One entry from GET /repos/{owner}/{repo}/pulls
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubPRSummary {
+    pub id: i64,
+    pub number: i64,
+    pub title: String,
+    pub state: String, // "open" | "closed"; merged PRs are closed with merged_at set
+    pub merged_at: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub html_url: String,
+    pub user: GitHubPRUser,
+    pub head: GitHubHead,
+    #[serde(default)]
+    pub merge_commit_sha: Option<String>,
+}
+
+impl GitHubPRSummary {
+    pub fn created_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.created_at.as_deref())
+    }
+
+    pub fn updated_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.updated_at.as_deref())
+    }
+
+    pub fn merged_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.merged_at.as_deref())
+    }
+
+    /// The merge commit, only once the PR is actually merged
+    pub fn merged_commit_sha(&self) -> Option<&str> {
+        self.merge_commit_sha.as_deref().filter(|_| self.merged_at.is_some())
+    }
+}
+
+// Just the head commit, for cheap "has this PR changed?" checks
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubPRHeadOnly {
+    pub head: GitHubHead,
+}
+
+// One entry from GET /repos/{owner}/{repo}/pulls/{number}/files
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GitHubPRFile {
+    pub filename: String,
+    pub status: String, // added, removed, modified, renamed, copied, changed, unchanged
+    #[serde(default)]
+    pub additions: i64,
+    #[serde(default)]
+    pub deletions: i64,
+    #[serde(default)]
+    pub changes: i64,
+    pub previous_filename: Option<String>, // set for renamed/copied files
+    pub patch: Option<String>,             // omitted for binary files and very large diffs
+}
+
+impl GitHubPRFile {
+    /// GitHub sends no patch and no line counts for binary files. A pure rename has
+    /// no patch either, so renames only count as binary when GitHub reports changes.
+    pub fn is_binary(&self) -> bool {
+        self.patch.is_none() && self.changes == 0 && self.status != "renamed"
+    }
+}
+
+// One entry from GET /repos/{owner}/{repo}/releases
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GitHubRelease {
+    pub tag_name: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    pub published_at: Option<String>, // None for drafts
+}
+
+impl GitHubRelease {
+    pub fn published_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.published_at.as_deref())
+    }
+}
+
+// One entry from GET /repos/{owner}/{repo}/deployments
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GitHubDeployment {
+    pub id: i64,
+    pub environment: String,
+}
+
+// One entry from GET /repos/{owner}/{repo}/deployments/{id}/statuses, newest first
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GitHubDeploymentStatus {
+    pub state: String, // error, failure, inactive, in_progress, queued, pending, success
+    pub environment_url: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl GitHubDeploymentStatus {
+    pub fn created_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.created_at.as_deref())
+    }
+}
+
+// GET /repos/{owner}/{repo}/compare/{base}...{head}; only the relationship is read
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubCompare {
+    pub status: String, // "ahead" | "behind" | "identical" | "diverged", head relative to base
+}
+
+// GraphQL `node` lookup result for a pull request
+#[derive(Debug, serde::Deserialize)]
+pub struct GraphQLNodeResponse {
+    pub data: Option<GraphQLNodeData>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GraphQLNodeData {
+    pub node: Option<GitHubPRLocation>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubPRLocation {
+    pub number: i64,
+    pub repository: GitHubRepoRef,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubRepoRef {
+    pub name: String,
+    pub owner: GitHubOwnerRef,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubOwnerRef {
+    pub login: String,
+}
+
+// Helper struct for easier access
+#[derive(Debug)]
+pub struct PRAuthor {
+    pub login: String,
+    pub avatar_url: String,
+    pub name: Option<String>,
+}
+
+impl From<GitHubPRUser> for PRAuthor {
+    fn from(user: GitHubPRUser) -> Self {
+        PRAuthor {
+            login: user.login,
+            avatar_url: user.avatar_url,
+            name: user.name,
+        }
+    }
+}
+
+impl GitHubPRData {
+    pub fn author(&self) -> PRAuthor {
+        self.user.clone().into()
+    }
+
+    /// GitHub's created_at as a unix timestamp
+    pub fn created_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.created_at.as_deref())
+    }
+
+    /// GitHub's merged_at as a unix timestamp
+    pub fn merged_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.merged_at.as_deref())
+    }
+
+    /// GitHub's updated_at as a unix timestamp
+    pub fn updated_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.updated_at.as_deref())
+    }
+
+    /// The merge commit, only once the PR is actually merged
+    pub fn merged_commit_sha(&self) -> Option<&str> {
+        self.merge_commit_sha.as_deref().filter(|_| self.merged)
+    }
+}
+
+impl GitHubIssue {
+    pub fn author(&self) -> PRAuthor {
+        self.user.clone().into()
+    }
+
+    pub fn created_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.created_at.as_deref())
+    }
+
+    pub fn updated_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.updated_at.as_deref())
+    }
+}
+
+// One entry from GET /repos/{owner}/{repo}/pulls/comments. Only who and when: comment
+// bodies are never deserialized, so they can't end up stored anywhere.
+#[derive(Debug, serde::Deserialize)]
+pub struct GitHubReviewComment {
+    pub user: Option<GitHubCommentAuthor>, // None for deleted ("ghost") accounts
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GitHubCommentAuthor {
+    pub login: String,
+}
+
+impl GitHubReviewComment {
+    pub fn created_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.created_at.as_deref())
+    }
+}
+
+// One entry from GET /repos/{owner}/{repo}/pulls/{number}/reviews, oldest first
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GitHubReview {
+    pub id: i64,
+    pub user: Option<GitHubCommentAuthor>, // None for deleted ("ghost") accounts
+    pub state: String,                     // APPROVED | CHANGES_REQUESTED | COMMENTED | DISMISSED | PENDING
+    pub submitted_at: Option<String>,      // None while pending
+    #[serde(default)]
+    pub commit_id: Option<String>,         // head commit the review was made against
+}
+
+impl GitHubReview {
+    pub fn submitted_at_timestamp(&self) -> Option<i64> {
+        rfc3339_to_timestamp(self.submitted_at.as_deref())
+    }
+}
+
+fn rfc3339_to_timestamp(value: Option<&str>) -> Option<i64> {
+    value
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_pull_request_with_reactions() {
+        let data: GitHubPRData = serde_json::from_str(include_str!("../../tests/fixtures/github/pull_request.json")).unwrap();
+        assert_eq!(data.id, 1296269);
+        assert_eq!(data.head.ref_field, "new-topic");
+        assert!(!data.merged);
+        assert_eq!(data.merged_at_timestamp(), None);
+        // The fixture's merge_commit_sha is GitHub's test merge; not a merge until merged
+        assert!(data.merge_commit_sha.is_some());
+        assert_eq!(data.merged_commit_sha(), None);
+        let reactions = data.reactions.unwrap();
+        assert_eq!(reactions.total_count, 12);
+        assert_eq!(reactions.plus_one, 9);
+        assert_eq!((data.additions, data.deletions, data.changed_files), (Some(100), Some(3), Some(5)));
+    }
+
+    #[test]
+    fn test_linked_pull_request_from_timeline() {
+        let events: Vec<GitHubTimelineEvent> = serde_json::from_str(include_str!("../../tests/fixtures/github/issue_timeline.json")).unwrap();
+        // The issue cross-reference and the label event are skipped; the newest PR wins
+        assert_eq!(linked_pull_request(&events), Some(("octocat".to_string(), "Hello-World".to_string(), 1350)));
+        assert_eq!(linked_pull_request(&events[..2]), None);
+    }
+
+    #[test]
+    fn test_deserialize_pull_request_without_reactions() {
+        let data: GitHubPRData = serde_json::from_str(include_str!("../../tests/fixtures/github/pull_request_no_reactions.json")).unwrap();
+        assert!(data.reactions.is_none());
+        assert_eq!(data.created_at_timestamp(), Some(1296068472));
+        assert_eq!(data.updated_at_timestamp(), Some(1296069283));
+    }
+}
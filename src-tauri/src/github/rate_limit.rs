@@ -0,0 +1,383 @@
+// Rate-limit state GitHub last reported, shared by every API call so a limited
+// app stops sending requests until the quota resets or the pause ends.
+//
+// Requests are also budgeted per category within each quota window. Background
+// categories stop at their share of the hourly limit; interactive requests may go
+// past theirs until only INTERACTIVE_FLOOR requests remain, so a sync or backfill can
+// never leave the user unable to load a PR.
+use std::future::Future;
+use std::sync::{LazyLock, Mutex};
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use super::{api_usage, RATE_LIMITED_ERROR};
+
+// Settings row holding the last state, so a relaunch doesn't re-hammer a limited API
+pub const RATE_LIMIT_STATE_KEY: &str = "rate_limit_state";
+
+// GitHub asks for at least a minute when a secondary limit comes without Retry-After
+const DEFAULT_SECONDARY_PAUSE_SECS: i64 = 60;
+
+// Settings row holding the per-category budgets as JSON percentages
+pub const RATE_LIMIT_BUDGETS_KEY: &str = "rate_limit_budgets";
+
+// Interactive requests stop once GitHub's remaining quota is down to this
+pub const INTERACTIVE_FLOOR: u32 = 25;
+
+// GitHub's primary quota resets hourly
+const WINDOW_SECS: i64 = 60 * 60;
+
+// Used for budget maths before any response has reported the real limit
+const ASSUMED_HOURLY_LIMIT: u32 = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestCategory {
+    Interactive,
+    BackgroundSync, // retry worker, scheduler, merge sync, repository audits
+    Import,         // backfills and bulk imports
+}
+
+impl RequestCategory {
+    pub const ALL: [RequestCategory; 3] = [RequestCategory::Interactive, RequestCategory::BackgroundSync, RequestCategory::Import];
+
+    fn index(self) -> usize {
+        match self {
+            RequestCategory::Interactive => 0,
+            RequestCategory::BackgroundSync => 1,
+            RequestCategory::Import => 2,
+        }
+    }
+}
+
+/// Share of each quota window per category, in percent (at most 100 in total)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateBudgets {
+    pub interactive: u32,
+    pub background_sync: u32,
+    pub import: u32,
+}
+
+impl Default for RateBudgets {
+    fn default() -> Self {
+        RateBudgets { interactive: 50, background_sync: 30, import: 20 }
+    }
+}
+
+impl RateBudgets {
+    /// Setting value, e.g. {"interactive": 60, "background_sync": 30, "import": 10}
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let budgets: RateBudgets = serde_json::from_str(value).map_err(|e| format!("Invalid rate limit budgets: {}", e))?;
+        if budgets.interactive + budgets.background_sync + budgets.import > 100 {
+            return Err("Rate limit budgets must add up to at most 100%".to_string());
+        }
+        Ok(budgets)
+    }
+
+    pub fn percent(&self, category: RequestCategory) -> u32 {
+        match category {
+            RequestCategory::Interactive => self.interactive,
+            RequestCategory::BackgroundSync => self.background_sync,
+            RequestCategory::Import => self.import,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryUsage {
+    pub category: RequestCategory,
+    pub budget_percent: u32,
+    pub allowance: u32, // requests this window; soft for interactive
+    pub used: u32,
+    pub exhausted: bool,
+}
+
+/// Requests sent per category in the current quota window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BudgetTracker {
+    pub budgets: RateBudgets,
+    window_reset_at: Option<i64>,
+    used: [u32; 3],
+}
+
+impl BudgetTracker {
+    pub fn new(budgets: RateBudgets) -> Self {
+        BudgetTracker { budgets, ..Default::default() }
+    }
+
+    // A new window starts at GitHub's reset time, or when GitHub reports a different one
+    fn roll_window(&mut self, reset_at: Option<i64>, now: i64) {
+        let expired = self.window_reset_at.is_some_and(|at| at <= now);
+        if expired || (reset_at.is_some() && reset_at != self.window_reset_at) {
+            self.used = [0; 3];
+            self.window_reset_at = reset_at.filter(|at| *at > now);
+        }
+    }
+
+    fn allowance(&self, category: RequestCategory, state: &RateLimitState) -> u32 {
+        let limit = state.limit.unwrap_or(ASSUMED_HOURLY_LIMIT);
+        limit * self.budgets.percent(category) / 100
+    }
+
+    fn exhausted(&self, category: RequestCategory, state: &RateLimitState) -> bool {
+        match category {
+            RequestCategory::Interactive => state.remaining.is_some_and(|remaining| remaining <= INTERACTIVE_FLOOR),
+            background => self.used[background.index()] >= self.allowance(background, state),
+        }
+    }
+
+    /// Whether a request in `category` may go out now
+    pub fn check(&mut self, category: RequestCategory, state: &RateLimitState, now: i64) -> Result<(), String> {
+        self.roll_window(state.reset_at, now);
+        if self.exhausted(category, state) {
+            return Err(RATE_LIMITED_ERROR.to_string());
+        }
+        Ok(())
+    }
+
+    /// Count a response against `category`
+    pub fn record(&mut self, category: RequestCategory, state: &RateLimitState, now: i64) {
+        self.roll_window(state.reset_at, now);
+        self.used[category.index()] += 1;
+    }
+
+    pub fn usage(&mut self, state: &RateLimitState, now: i64) -> Vec<CategoryUsage> {
+        self.roll_window(state.reset_at, now);
+        RequestCategory::ALL.iter()
+            .map(|category| CategoryUsage {
+                category: *category,
+                budget_percent: self.budgets.percent(*category),
+                allowance: self.allowance(*category, state),
+                used: self.used[category.index()],
+                exhausted: self.exhausted(*category, state),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitState {
+    pub remaining: Option<u32>,
+    pub limit: Option<u32>,
+    pub reset_at: Option<i64>,     // unix seconds when the primary quota refills
+    pub paused_until: Option<i64>, // Retry-After / secondary limit pause
+    #[serde(default)]
+    pub persisted: bool, // loaded from a previous session, not yet confirmed by a response
+}
+
+impl RateLimitState {
+    /// When requests may resume, if GitHub told us to stop
+    pub fn blocked_until(&self, now: i64) -> Option<i64> {
+        let exhausted = self.reset_at.filter(|_| self.remaining == Some(0));
+        [self.paused_until, exhausted].into_iter().flatten().filter(|t| *t > now).max()
+    }
+
+    fn observe(&mut self, status: u16, headers: &HeaderMap, now: i64) {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+        // Only responses that carry quota headers replace what we knew
+        if let Some(remaining) = header("x-ratelimit-remaining").and_then(|v| v.parse().ok()) {
+            self.remaining = Some(remaining);
+            self.limit = header("x-ratelimit-limit").and_then(|v| v.parse().ok()).or(self.limit);
+            self.reset_at = header("x-ratelimit-reset").and_then(|v| v.parse().ok()).or(self.reset_at);
+            self.persisted = false;
+        }
+
+        // A plain 403 can't be told apart from a permissions error, so only 429 implies a pause
+        if let Some(secs) = header("retry-after").and_then(|v| v.parse::<i64>().ok()) {
+            self.paused_until = Some(now + secs);
+        } else if status == 429 {
+            self.paused_until = Some(now + DEFAULT_SECONDARY_PAUSE_SECS);
+        } else if self.paused_until.is_some_and(|t| t <= now) {
+            self.paused_until = None;
+        }
+    }
+}
+
+static STATE: LazyLock<watch::Sender<RateLimitState>> = LazyLock::new(|| watch::Sender::new(RateLimitState::default()));
+static BUDGETS: LazyLock<Mutex<BudgetTracker>> = LazyLock::new(|| Mutex::new(BudgetTracker::new(RateBudgets::default())));
+
+tokio::task_local! {
+    static CATEGORY: RequestCategory;
+}
+
+/// Run `fut` with its GitHub calls counted against `category` (untagged calls are interactive)
+pub async fn in_category<F: Future>(category: RequestCategory, fut: F) -> F::Output {
+    CATEGORY.scope(category, fut).await
+}
+
+/// The category calls are counted against here, for carrying it into spawned tasks
+pub fn current_category() -> RequestCategory {
+    CATEGORY.try_with(|category| *category).unwrap_or(RequestCategory::Interactive)
+}
+
+/// Apply new budget percentages; usage in the current window is kept
+pub fn set_budgets(budgets: RateBudgets) {
+    BUDGETS.lock().unwrap().budgets = budgets;
+}
+
+/// Per-category consumption in the current window
+pub fn budget_usage() -> Vec<CategoryUsage> {
+    BUDGETS.lock().unwrap().usage(&current(), chrono::Utc::now().timestamp())
+}
+
+pub fn current() -> RateLimitState {
+    STATE.borrow().clone()
+}
+
+/// Receiver notified whenever the state changes (used to persist it)
+pub fn subscribe() -> watch::Receiver<RateLimitState> {
+    STATE.subscribe()
+}
+
+/// Start from the previous session's state; ignored once a live response has been seen
+pub fn seed(state: RateLimitState) {
+    STATE.send_if_modified(|current| {
+        if *current != RateLimitState::default() {
+            return false;
+        }
+        *current = RateLimitState { persisted: true, ..state };
+        true
+    });
+}
+
+/// Forget everything, e.g. when a wrong clock left a bogus pause behind
+pub fn reset() {
+    STATE.send_replace(RateLimitState::default());
+}
+
+/// Fail fast instead of calling GitHub while paused or when the caller's budget is spent
+pub fn ensure_not_paused() -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    let state = current();
+    if state.blocked_until(now).is_some() {
+        return Err(RATE_LIMITED_ERROR.to_string());
+    }
+    BUDGETS.lock().unwrap().check(current_category(), &state, now)
+}
+
+/// Record the quota headers (and any pause) from a GitHub response
+pub fn record_response(response: &reqwest::Response) {
+    let now = chrono::Utc::now().timestamp();
+    let mut paused = None;
+    STATE.send_if_modified(|state| {
+        let before = state.clone();
+        state.observe(response.status().as_u16(), response.headers(), now);
+        if before.blocked_until(now).is_none() {
+            paused = state.blocked_until(now);
+        }
+        *state != before
+    });
+    let state = current();
+    if let Some(until) = paused {
+        log_pause(&state, until, now);
+    }
+    BUDGETS.lock().unwrap().record(current_category(), &state, now);
+}
+
+// Name the feature that spent the most of this quota window when calls get paused
+fn log_pause(state: &RateLimitState, until: i64, now: i64) {
+    let window_start = state.reset_at.map(|reset| reset - WINDOW_SECS).unwrap_or(now - WINDOW_SECS);
+    match api_usage::top_feature_since(window_start) {
+        Some(top) => println!(
+            "⏸️ GitHub calls paused for {}s; top consumer this window: {} ({} calls)",
+            until - now, top.feature, top.calls
+        ),
+        None => println!("⏸️ GitHub calls paused for {}s", until - now),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_observe_quota_and_pauses() {
+        let mut state = RateLimitState { persisted: true, ..Default::default() };
+        state.observe(200, &headers(&[("x-ratelimit-remaining", "10"), ("x-ratelimit-limit", "5000"), ("x-ratelimit-reset", "2000")]), 1000);
+        assert_eq!((state.remaining, state.limit, state.reset_at, state.persisted), (Some(10), Some(5000), Some(2000), false));
+        assert_eq!(state.blocked_until(1000), None);
+
+        // Primary quota exhausted: blocked until the reset
+        state.observe(403, &headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", "2000")]), 1100);
+        assert_eq!(state.blocked_until(1100), Some(2000));
+        assert_eq!(state.blocked_until(2000), None);
+
+        // Secondary limit with Retry-After, and without
+        let mut state = RateLimitState::default();
+        state.observe(403, &headers(&[("retry-after", "30")]), 1000);
+        assert_eq!(state.blocked_until(1000), Some(1030));
+        let mut state = RateLimitState::default();
+        state.observe(429, &HeaderMap::new(), 1000);
+        assert_eq!(state.paused_until, Some(1000 + DEFAULT_SECONDARY_PAUSE_SECS));
+
+        // An expired pause is cleared by the next response
+        state.observe(200, &HeaderMap::new(), 5000);
+        assert_eq!(state.paused_until, None);
+    }
+
+    // Stands in for GitHub: every request uses one unit of a 200-request window
+    struct MockGitHub {
+        state: RateLimitState,
+    }
+
+    impl MockGitHub {
+        fn send(&mut self, tracker: &mut BudgetTracker, category: RequestCategory, now: i64) -> Result<(), String> {
+            tracker.check(category, &self.state, now)?;
+            let remaining = self.state.remaining.unwrap() - 1;
+            self.state.observe(200, &headers(&[
+                ("x-ratelimit-remaining", &remaining.to_string()),
+                ("x-ratelimit-limit", "200"),
+                ("x-ratelimit-reset", "3600"),
+            ]), now);
+            tracker.record(category, &self.state, now);
+            Ok(())
+        }
+
+        fn send_until_refused(&mut self, tracker: &mut BudgetTracker, category: RequestCategory, now: i64) -> u32 {
+            let mut sent = 0;
+            while self.send(tracker, category, now).is_ok() {
+                sent += 1;
+            }
+            sent
+        }
+    }
+
+    #[test]
+    fn test_budgets_and_interactive_floor() {
+        assert!(RateBudgets::parse(r#"{"interactive": 60, "background_sync": 30, "import": 20}"#).is_err());
+        let budgets = RateBudgets::parse(r#"{"interactive": 50, "background_sync": 30, "import": 10}"#).unwrap();
+        let mut tracker = BudgetTracker::new(budgets);
+        let mut github = MockGitHub {
+            state: RateLimitState { remaining: Some(200), limit: Some(200), reset_at: Some(3600), ..Default::default() },
+        };
+
+        // Background categories stop at exactly their share of the window
+        assert_eq!(github.send_until_refused(&mut tracker, RequestCategory::BackgroundSync, 100), 60);
+        assert_eq!(github.send_until_refused(&mut tracker, RequestCategory::Import, 100), 20);
+        assert_eq!(tracker.check(RequestCategory::BackgroundSync, &github.state, 200), Err(RATE_LIMITED_ERROR.to_string()));
+
+        // Interactive keeps going past its 100 until only the floor is left
+        assert_eq!(github.send_until_refused(&mut tracker, RequestCategory::Interactive, 100), 120 - INTERACTIVE_FLOOR);
+        assert_eq!(github.state.remaining, Some(INTERACTIVE_FLOOR));
+
+        let usage = tracker.usage(&github.state, 100);
+        let used: Vec<(u32, u32, bool)> = usage.iter().map(|u| (u.allowance, u.used, u.exhausted)).collect();
+        assert_eq!(used, vec![(100, 95, true), (60, 60, true), (20, 20, true)]);
+
+        // GitHub's reset time starts a fresh window
+        github.state = RateLimitState { remaining: Some(200), limit: Some(200), reset_at: Some(7200), ..Default::default() };
+        assert!(tracker.check(RequestCategory::BackgroundSync, &github.state, 3600).is_ok());
+        assert!(tracker.usage(&github.state, 3600).iter().all(|u| u.used == 0 && !u.exhausted));
+    }
+}
@@ -0,0 +1,197 @@
+// Recorded GitHub responses, so code that calls the API can be tested without a token or network.
+// Inside with_fixtures, requests are answered from JSON files under tests/fixtures/github/replay/<set>/,
+// one per method and path (query included). Tests never reach GitHub: a request outside
+// with_fixtures, or without a fixture, panics.
+//
+// Recording is for developers only: run the tests with PR_TRACKER_RECORD_FIXTURES=1 and GITHUB_TOKEN
+// set, and requests go to GitHub with that token; each response is written to the first fixture set,
+// keeping only the headers in KEPT_HEADERS and with the token scrubbed from the body.
+use reqwest::{Client, Request, Response, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+pub const RECORD_ENV: &str = "PR_TRACKER_RECORD_FIXTURES";
+pub const RECORD_TOKEN_ENV: &str = "GITHUB_TOKEN";
+const SCRUBBED_TOKEN: &str = "[scrubbed]";
+
+// Response headers the code reads; the rest (request ids, cookies, caching) is not recorded
+const KEPT_HEADERS: [&str; 8] = [
+    "content-type",
+    "link",
+    "retry-after",
+    "x-oauth-scopes",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+    "github-authentication-token-expiration",
+];
+
+/// One recorded response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub method: String,
+    pub path: String, // path and query, e.g. /repos/acme/api/pulls?state=open
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: serde_json::Value, // null for an empty body
+}
+
+tokio::task_local! {
+    static FIXTURE_SETS: Vec<String>;
+}
+
+/// Run `fut` with its GitHub requests answered from the named fixture sets; the first set
+/// with a matching file wins, so a scenario set can override a few responses of "default"
+pub async fn with_fixtures<F: Future>(sets: &[&str], fut: F) -> F::Output {
+    FIXTURE_SETS.scope(sets.iter().map(|set| set.to_string()).collect(), fut).await
+}
+
+pub fn fixture_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/github/replay")
+}
+
+/// "GET", "/repos/acme/api/pulls/1" -> "get_repos_acme_api_pulls_1.json"
+pub fn fixture_file_name(method: &str, path: &str) -> String {
+    let key: String = format!("{} {}", method.to_ascii_lowercase(), path)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    format!("{}.json", key.split('_').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("_"))
+}
+
+fn request_path(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+fn load(sets: &[String], method: &str, path: &str) -> Option<Fixture> {
+    let file_name = fixture_file_name(method, path);
+    sets.iter().find_map(|set| {
+        let file = fixture_root().join(set).join(&file_name);
+        let json = std::fs::read_to_string(&file).ok()?;
+        let fixture: Fixture = serde_json::from_str(&json)
+            .unwrap_or_else(|e| panic!("Invalid fixture {}: {}", file.display(), e));
+        // Different paths can share a file name once sanitized
+        (fixture.method == method && fixture.path == path).then_some(fixture)
+    })
+}
+
+impl Fixture {
+    fn into_response(self) -> Response {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        let body = match self.body {
+            serde_json::Value::Null => Vec::new(),
+            body => serde_json::to_vec(&body).expect("fixture body serializes"),
+        };
+        Response::from(builder.body(body).expect("fixture headers are valid"))
+    }
+}
+
+/// Send a GitHub request, or answer it from fixtures inside with_fixtures
+pub(crate) async fn send(client: &Client, request: Request) -> reqwest::Result<Response> {
+    let Ok(sets) = FIXTURE_SETS.try_with(|sets| sets.clone()) else {
+        if cfg!(test) {
+            panic!("GitHub request to {} outside replay::with_fixtures", request.url());
+        }
+        return client.execute(request).await;
+    };
+    let method = request.method().as_str().to_string();
+    let path = request_path(request.url());
+
+    if std::env::var_os(RECORD_ENV).is_some() {
+        let fixture = record(client, request, &sets[0]).await?;
+        return Ok(fixture.into_response());
+    }
+
+    let fixture = load(&sets, &method, &path).unwrap_or_else(|| panic!(
+        "No fixture for {} {} in {:?} (expected {}); record it with {}=1",
+        method, path, sets, fixture_file_name(&method, &path), RECORD_ENV
+    ));
+    Ok(fixture.into_response())
+}
+
+async fn record(client: &Client, mut request: Request, set: &str) -> reqwest::Result<Fixture> {
+    let token = std::env::var(RECORD_TOKEN_ENV)
+        .unwrap_or_else(|_| panic!("{} needs {} set to a real token", RECORD_ENV, RECORD_TOKEN_ENV));
+    let authorization = format!("Bearer {}", token).parse().expect("token is a valid header value");
+    request.headers_mut().insert(reqwest::header::AUTHORIZATION, authorization);
+
+    let method = request.method().as_str().to_string();
+    let path = request_path(request.url());
+    let response = client.execute(request).await?;
+    let status = response.status().as_u16();
+    let headers = response.headers().iter()
+        .filter(|(name, _)| KEPT_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let text = response.text().await?.replace(&token, SCRUBBED_TOKEN);
+    let body = match text.trim() {
+        "" => serde_json::Value::Null,
+        json => serde_json::from_str(json).unwrap_or(serde_json::Value::String(text)),
+    };
+
+    let fixture = Fixture { method, path, status, headers, body };
+    let dir = fixture_root().join(set);
+    let file = dir.join(fixture_file_name(&fixture.method, &fixture.path));
+    std::fs::create_dir_all(&dir).expect("create fixture directory");
+    let json = serde_json::to_string_pretty(&fixture).expect("fixture serializes");
+    std::fs::write(&file, json + "\n").unwrap_or_else(|e| panic!("Could not write {}: {}", file.display(), e));
+    println!("📼 Recorded {} {} to {}", fixture.method, fixture.path, file.display());
+    Ok(fixture)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_file_name() {
+        assert_eq!(fixture_file_name("GET", "/repos/acme/api/pulls/1"), "get_repos_acme_api_pulls_1.json");
+        assert_eq!(
+            fixture_file_name("GET", "/repos/my.org/api/pulls?head=my.org%3Afix&state=all"),
+            "get_repos_my.org_api_pulls_head_my.org_3Afix_state_all.json"
+        );
+        assert_eq!(fixture_file_name("POST", "/graphql"), "post_graphql.json");
+    }
+
+    #[tokio::test]
+    async fn test_replay_serves_status_headers_and_body() {
+        let client = Client::new();
+        let request = client.get("https://api.github.com/rate_limit").build().unwrap();
+        let response = with_fixtures(&["default"], send(&client, request)).await.unwrap();
+
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.headers()["x-ratelimit-limit"], "5000");
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["resources"]["core"]["remaining"], 4999);
+        assert_eq!(body["resources"]["search"]["limit"], 30);
+    }
+
+    #[tokio::test]
+    async fn test_scenario_set_overrides_default() {
+        let client = Client::new();
+        let request = || client.get("https://api.github.com/user").build().unwrap();
+
+        let ok = with_fixtures(&["default"], send(&client, request())).await.unwrap();
+        assert_eq!(ok.status().as_u16(), 200);
+        let expired = with_fixtures(&["token_expired", "default"], send(&client, request())).await.unwrap();
+        assert_eq!(expired.status().as_u16(), 401);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "No fixture for GET /repos/acme/api/pulls/404")]
+    async fn test_missing_fixture_panics() {
+        let client = Client::new();
+        let request = client.get("https://api.github.com/repos/acme/api/pulls/404").build().unwrap();
+        let _ = with_fixtures(&["default"], send(&client, request)).await;
+    }
+}
@@ -0,0 +1,202 @@
+// What a token is allowed to do and when it stops working, read from GitHub's response
+// headers. Notes on classic tokens are no longer exposed by the API, so the report
+// points at the settings page for anything it can't show.
+use std::sync::LazyLock;
+
+use chrono::{DateTime, NaiveDateTime};
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use tokio::sync::watch;
+
+// Warn this long before a token expires
+pub const EXPIRY_WARNING_DAYS: i64 = 7;
+
+// Settings row holding the stored token's expiry, so the startup check works before any call
+pub const TOKEN_EXPIRY_KEY: &str = "github_token_expires_at";
+
+const EXPIRATION_HEADER: &str = "github-authentication-token-expiration";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    ClassicPat,
+    FineGrainedPat,
+    OAuthApp,
+    GitHubAppUser,
+    GitHubAppInstallation,
+    Unknown,
+}
+
+impl TokenKind {
+    /// GitHub prefixes every token with its type
+    pub fn from_token(token: &str) -> Self {
+        match token {
+            t if t.starts_with("github_pat_") => TokenKind::FineGrainedPat,
+            t if t.starts_with("ghp_") => TokenKind::ClassicPat,
+            t if t.starts_with("gho_") => TokenKind::OAuthApp,
+            t if t.starts_with("ghu_") => TokenKind::GitHubAppUser,
+            t if t.starts_with("ghs_") => TokenKind::GitHubAppInstallation,
+            _ => TokenKind::Unknown,
+        }
+    }
+
+    /// Where the user can inspect, rotate or revoke this kind of token
+    pub fn manage_url(&self) -> &'static str {
+        match self {
+            TokenKind::ClassicPat | TokenKind::Unknown => "https://github.com/settings/tokens",
+            TokenKind::FineGrainedPat => "https://github.com/settings/personal-access-tokens",
+            TokenKind::OAuthApp | TokenKind::GitHubAppUser | TokenKind::GitHubAppInstallation => {
+                "https://github.com/settings/applications"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TokenGrant {
+    pub kind: TokenKind,
+    pub login: Option<String>,
+    pub scopes: Vec<String>,        // classic and OAuth tokens only; fine-grained permissions aren't reported
+    pub expires_at: Option<i64>,    // None when the token never expires or GitHub didn't say
+    pub expires_in_days: Option<i64>,
+    // Revoking needs the issuing OAuth app's client secret, which this app doesn't have
+    pub revocable: bool,
+    pub manage_url: String,
+    pub guidance: String,
+}
+
+/// `GitHub-Authentication-Token-Expiration`, e.g. "2024-05-01 12:00:00 UTC" or "... +0000"
+pub fn parse_token_expiration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(parsed) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z") {
+        return Some(parsed.timestamp());
+    }
+    let naive = value.strip_suffix("UTC")?.trim();
+    NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S").ok().map(|dt| dt.and_utc().timestamp())
+}
+
+/// Whole days left before `at`; negative once it has passed
+pub fn days_until(at: i64, now: i64) -> i64 {
+    (at - now).div_euclid(24 * 60 * 60)
+}
+
+fn guidance(kind: TokenKind, expires_in_days: Option<i64>) -> String {
+    let mut guidance = match expires_in_days {
+        Some(days) if days < 0 => "This token has expired; create a new one and save it in Settings.".to_string(),
+        Some(days) if days <= EXPIRY_WARNING_DAYS => {
+            format!("This token expires in {} day(s); regenerate it before then to avoid interruptions.", days)
+        }
+        Some(days) => format!("This token expires in {} days.", days),
+        None => "This token does not report an expiry; consider one with an expiration date.".to_string(),
+    };
+    guidance.push_str(match kind {
+        TokenKind::ClassicPat => " Review its scopes and note, or revoke it, on the classic tokens page.",
+        TokenKind::FineGrainedPat => " Review its repository access and permissions, or revoke it, on the fine-grained tokens page.",
+        TokenKind::OAuthApp | TokenKind::GitHubAppUser | TokenKind::GitHubAppInstallation => {
+            " It was issued by an app; revoke that app's access under Authorized applications."
+        }
+        TokenKind::Unknown => " Revoke it from your GitHub developer settings if it is no longer needed.",
+    });
+    guidance
+}
+
+/// Build the report from a successful `GET /user` response
+pub fn token_grant(token: &str, headers: &HeaderMap, login: Option<String>, now: i64) -> TokenGrant {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let kind = TokenKind::from_token(token);
+    let scopes = header("x-oauth-scopes")
+        .map(|s| s.split(',').map(|scope| scope.trim().to_string()).filter(|scope| !scope.is_empty()).collect())
+        .unwrap_or_default();
+    let expires_at = header(EXPIRATION_HEADER).and_then(parse_token_expiration);
+    let expires_in_days = expires_at.map(|at| days_until(at, now));
+
+    TokenGrant {
+        kind,
+        login,
+        scopes,
+        expires_at,
+        expires_in_days,
+        revocable: false,
+        manage_url: kind.manage_url().to_string(),
+        guidance: guidance(kind, expires_in_days),
+    }
+}
+
+/// Expiry of the token in use, as last reported by GitHub
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TokenExpiry {
+    pub expires_at: Option<i64>, // None when the token doesn't expire (or nothing is known yet)
+    pub observed: bool,          // confirmed by a response this session, not just loaded from settings
+}
+
+static EXPIRY: LazyLock<watch::Sender<TokenExpiry>> = LazyLock::new(|| watch::Sender::new(TokenExpiry::default()));
+
+pub fn current_expiry() -> TokenExpiry {
+    *EXPIRY.borrow()
+}
+
+/// Receiver notified whenever the expiry changes (used to persist it)
+pub fn subscribe_expiry() -> watch::Receiver<TokenExpiry> {
+    EXPIRY.subscribe()
+}
+
+/// Start from the expiry saved last session; ignored once a live response has been seen
+pub fn seed_expiry(expires_at: i64) {
+    EXPIRY.send_if_modified(|current| {
+        if current.observed {
+            return false;
+        }
+        *current = TokenExpiry { expires_at: Some(expires_at), observed: false };
+        true
+    });
+}
+
+/// Forget what we knew, e.g. when the token is replaced or deleted
+pub fn forget_expiry() {
+    EXPIRY.send_replace(TokenExpiry::default());
+}
+
+/// Record the expiry header from a successful response; its absence means the token doesn't expire
+pub(crate) fn record_token_expiry(status: u16, headers: &HeaderMap) {
+    if !(200..300).contains(&status) {
+        return;
+    }
+    let expires_at = headers.get(EXPIRATION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_token_expiration);
+    EXPIRY.send_if_modified(|current| {
+        let next = TokenExpiry { expires_at, observed: true };
+        let changed = *current != next;
+        *current = next;
+        changed
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_grant_from_headers() {
+        assert_eq!(parse_token_expiration("2024-01-08 09:00:00 UTC"), Some(1_704_704_400));
+        assert_eq!(parse_token_expiration("2024-01-08 18:00:00 +0900"), Some(1_704_704_400));
+        assert_eq!(parse_token_expiration("next tuesday"), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-oauth-scopes", "repo, read:org".parse().unwrap());
+        headers.insert("github-authentication-token-expiration", "2024-01-08 09:00:00 UTC".parse().unwrap());
+
+        // 2024-01-03 09:00 UTC: five days left
+        let grant = token_grant("ghp_abc", &headers, Some("octocat".to_string()), 1_704_272_400);
+        assert_eq!(grant.kind, TokenKind::ClassicPat);
+        assert_eq!(grant.scopes, vec!["repo", "read:org"]);
+        assert_eq!((grant.expires_at, grant.expires_in_days), (Some(1_704_704_400), Some(5)));
+        assert!(grant.guidance.starts_with("This token expires in 5 day(s)"));
+        assert_eq!(grant.manage_url, "https://github.com/settings/tokens");
+
+        let grant = token_grant("github_pat_abc", &HeaderMap::new(), None, 0);
+        assert_eq!((grant.kind, grant.expires_in_days), (TokenKind::FineGrainedPat, None));
+        assert!(grant.scopes.is_empty());
+        assert_eq!(token_grant("gho_abc", &headers, None, 1_704_790_800).expires_in_days, Some(-1));
+    }
+}
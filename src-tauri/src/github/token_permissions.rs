@@ -0,0 +1,185 @@
+// Which token permissions each feature needs, kept as one table so a new feature can't
+// ship without saying what it reads. Everything the app does is read-only, so every
+// fine-grained permission is "read"; classic tokens need `repo` for private repositories
+// and no scope at all for public ones. There is no CI-status or org-sync feature, so
+// nothing here asks for checks or members access.
+use serde::Serialize;
+
+use super::api_usage::ApiFeature;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Metadata,
+    PullRequests,
+    Issues,
+    Contents,
+    Deployments,
+}
+
+impl Permission {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::Metadata => "metadata",
+            Permission::PullRequests => "pull_requests",
+            Permission::Issues => "issues",
+            Permission::Contents => "contents",
+            Permission::Deployments => "deployments",
+        }
+    }
+
+    /// Smallest read under /repos/{owner}/{repo} that only succeeds with this permission
+    pub fn probe_path(self) -> &'static [&'static str] {
+        match self {
+            Permission::Metadata => &[],
+            Permission::PullRequests => &["pulls"],
+            Permission::Issues => &["issues"],
+            Permission::Contents => &["releases"],
+            Permission::Deployments => &["deployments"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NeededWhen {
+    Always,
+    TrackingIssues,     // any tracked issue
+    ResolvingReleases,  // any PR already matched to a release
+    Never,              // on-demand enrichment that reports itself unavailable without it
+}
+
+pub struct PermissionRule {
+    pub permission: Permission,
+    pub classic_scope: &'static str,
+    pub needed_when: NeededWhen,
+    pub features: &'static [ApiFeature],
+}
+
+// Verifying a token only calls GET /user, which any token may do
+pub const NO_PERMISSION_FEATURES: [ApiFeature; 1] = [ApiFeature::TokenCheck];
+
+pub const PERMISSION_RULES: [PermissionRule; 5] = [
+    // Fine-grained tokens always carry metadata; listed so the probe checks the repository is visible
+    PermissionRule {
+        permission: Permission::Metadata,
+        classic_scope: "repo",
+        needed_when: NeededWhen::Always,
+        features: &[ApiFeature::RepoAudit, ApiFeature::RepoRepair],
+    },
+    PermissionRule {
+        permission: Permission::PullRequests,
+        classic_scope: "repo",
+        needed_when: NeededWhen::Always,
+        features: &[
+            ApiFeature::AddPr,
+            ApiFeature::BranchLookup,
+            ApiFeature::PrFiles,
+            ApiFeature::MergeSync,
+            ApiFeature::RetryQueue,
+            ApiFeature::ScheduledRefresh,
+            ApiFeature::PrRefresh,
+            ApiFeature::ReviewCommentStats,
+            ApiFeature::Reimport,
+        ],
+    },
+    PermissionRule {
+        permission: Permission::Issues,
+        classic_scope: "repo",
+        needed_when: NeededWhen::TrackingIssues,
+        features: &[ApiFeature::Issues],
+    },
+    // Listing releases and comparing a tag with a merge commit
+    PermissionRule {
+        permission: Permission::Contents,
+        classic_scope: "repo",
+        needed_when: NeededWhen::ResolvingReleases,
+        features: &[ApiFeature::Releases],
+    },
+    // Deployment status in the PR detail; without it the detail just says so
+    PermissionRule {
+        permission: Permission::Deployments,
+        classic_scope: "repo",
+        needed_when: NeededWhen::Never,
+        features: &[ApiFeature::Deployments],
+    },
+];
+
+/// What the user has set up, which decides the conditional rules
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeatureUsage {
+    pub tracks_issues: bool,
+    pub resolves_releases: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RequiredPermission {
+    pub permission: Permission,
+    pub access: &'static str,        // always "read"
+    pub features: Vec<&'static str>, // ApiFeature names that need it
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RequiredTokenPermissions {
+    pub required: Vec<RequiredPermission>,
+    pub optional: Vec<RequiredPermission>, // for features not in use yet
+    pub classic_scopes: Vec<&'static str>,
+    pub guidance: String,
+}
+
+fn required_permission(rule: &PermissionRule) -> RequiredPermission {
+    RequiredPermission {
+        permission: rule.permission,
+        access: "read",
+        features: rule.features.iter().map(|feature| feature.as_str()).collect(),
+    }
+}
+
+pub fn required_permissions(usage: FeatureUsage) -> RequiredTokenPermissions {
+    let (required, optional): (Vec<&PermissionRule>, Vec<&PermissionRule>) = PERMISSION_RULES.iter().partition(|rule| match rule.needed_when {
+        NeededWhen::Always => true,
+        NeededWhen::TrackingIssues => usage.tracks_issues,
+        NeededWhen::ResolvingReleases => usage.resolves_releases,
+        NeededWhen::Never => false,
+    });
+    let mut classic_scopes: Vec<&'static str> = required.iter().map(|rule| rule.classic_scope).collect();
+    classic_scopes.dedup();
+
+    RequiredTokenPermissions {
+        required: required.into_iter().map(required_permission).collect(),
+        optional: optional.into_iter().map(required_permission).collect(),
+        classic_scopes,
+        guidance: "Fine-grained token: grant read-only access to these permissions on the tracked repositories. \
+                   Classic token: `repo` covers private repositories; public ones need no scope."
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_feature_has_permissions() {
+        for feature in ApiFeature::ALL {
+            let rules = PERMISSION_RULES.iter().filter(|rule| rule.features.contains(&feature)).count();
+            let exempt = NO_PERMISSION_FEATURES.contains(&feature);
+            assert!(rules == 1 || exempt, "{} needs exactly one entry in PERMISSION_RULES", feature.as_str());
+            assert!(!(rules > 0 && exempt), "{} is both exempt and listed", feature.as_str());
+        }
+    }
+
+    #[test]
+    fn test_conditional_permissions_follow_usage() {
+        let names = |list: &[RequiredPermission]| list.iter().map(|p| p.permission).collect::<Vec<_>>();
+
+        let fresh = required_permissions(FeatureUsage::default());
+        assert_eq!(names(&fresh.required), vec![Permission::Metadata, Permission::PullRequests]);
+        assert_eq!(names(&fresh.optional), vec![Permission::Issues, Permission::Contents, Permission::Deployments]);
+        assert_eq!(fresh.classic_scopes, vec!["repo"]);
+
+        let all = required_permissions(FeatureUsage { tracks_issues: true, resolves_releases: true });
+        assert_eq!(all.required.len(), PERMISSION_RULES.len() - 1);
+        assert_eq!(names(&all.optional), vec![Permission::Deployments]);
+        assert!(all.required.iter().find(|p| p.permission == Permission::Contents).unwrap().features.contains(&"releases"));
+    }
+}
@@ -0,0 +1,264 @@
+// Parsing of GitHub pull request URLs and the owner/repo/pull/123 and owner/repo#123 shorthands
+use serde::Serialize;
+use std::fmt;
+
+const URL_FORMAT_HINT: &str =
+    "Supported formats: https://github.com/owner/repo/pull/123, owner/repo/pull/123, owner/repo#123, or #123 with a default repository";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GitHubPRUrl {
+    pub owner: String,
+    pub repo: String,
+    pub pr_number: i64,
+}
+
+impl GitHubPRUrl {
+    /// Normalized form used for display and duplicate checks
+    pub fn canonical_url(&self) -> String {
+        format!("https://github.com/{}/{}/pull/{}", self.owner, self.repo, self.pr_number)
+    }
+}
+
+/// Which part of a PR URL is wrong, so the UI can point at the right segment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PrUrlError {
+    Empty,
+    InvalidHost { host: String },
+    MissingOwnerOrRepo,
+    MissingDefaultRepo,
+    MissingPullSegment { found: Option<String> },
+    MissingNumber,
+    InvalidNumber { value: String },
+}
+
+impl fmt::Display for PrUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self {
+            PrUrlError::Empty => "URL is empty".to_string(),
+            PrUrlError::InvalidHost { host } if host.is_empty() => "host is missing".to_string(),
+            PrUrlError::InvalidHost { host } => format!("host must be github.com, got '{}'", host),
+            PrUrlError::MissingOwnerOrRepo => "owner and repository are required".to_string(),
+            PrUrlError::MissingDefaultRepo => "'#number' needs a default repository".to_string(),
+            PrUrlError::MissingPullSegment { found: Some(found) } => {
+                format!("expected '/pull/' after the repository, got '/{}/'", found)
+            }
+            PrUrlError::MissingPullSegment { found: None } => "expected '/pull/' after the repository".to_string(),
+            PrUrlError::MissingNumber => "PR number is missing".to_string(),
+            PrUrlError::InvalidNumber { value } => format!("PR number must be a positive integer, got '{}'", value),
+        };
+        write!(f, "Invalid GitHub PR URL format: {}. {}", problem, URL_FORMAT_HINT)
+    }
+}
+
+impl std::error::Error for PrUrlError {}
+
+/// Result of validating a URL without adding it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidatedPrUrl {
+    pub owner: String,
+    pub repo: String,
+    pub pr_number: i64,
+    pub canonical_url: String,
+}
+
+impl From<GitHubPRUrl> for ValidatedPrUrl {
+    fn from(parsed: GitHubPRUrl) -> Self {
+        ValidatedPrUrl {
+            canonical_url: parsed.canonical_url(),
+            owner: parsed.owner,
+            repo: parsed.repo,
+            pr_number: parsed.pr_number,
+        }
+    }
+}
+
+/// Parse a PR URL or an `owner/repo/pull/123` / `owner/repo#123` shorthand; bare `#123` needs parse_pr_reference
+pub fn parse_github_pr_url(url: &str) -> Result<GitHubPRUrl, PrUrlError> {
+    parse_pr_reference(url, None)
+}
+
+/// Like parse_github_pr_url, with `#123` resolved against `default_repo` ("owner/repo")
+pub fn parse_pr_reference(input: &str, default_repo: Option<&str>) -> Result<GitHubPRUrl, PrUrlError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(PrUrlError::Empty);
+    }
+    if let Some((repo_part, number)) = shorthand_parts(trimmed) {
+        let (owner, repo) = if repo_part.is_empty() {
+            let default_repo = default_repo.ok_or(PrUrlError::MissingDefaultRepo)?;
+            split_repo(default_repo).ok_or(PrUrlError::MissingOwnerOrRepo)?
+        } else {
+            split_repo(repo_part).ok_or(PrUrlError::MissingOwnerOrRepo)?
+        };
+        let pr_number = parse_number(number)?;
+        return Ok(GitHubPRUrl { owner, repo, pr_number });
+    }
+
+    // Query strings and fragments (e.g. #discussion_r123) never carry PR coordinates
+    let without_query = trimmed.split(['?', '#']).next().unwrap_or_default();
+    let without_scheme = without_query
+        .strip_prefix("https://")
+        .or_else(|| without_query.strip_prefix("http://"))
+        .unwrap_or(without_query);
+
+    let segments: Vec<&str> = without_scheme.split('/').collect();
+    let host = segments[0].to_ascii_lowercase();
+    let github_host = host == "github.com" || host == "www.github.com";
+    // owner/repo/pull/123 with the host left off; anything with a scheme must name github.com
+    let hostless = !github_host
+        && without_scheme.len() == without_query.len()
+        && matches!(segments.get(2), Some(&"pull") | Some(&"pulls"));
+    if !github_host && !hostless {
+        return Err(PrUrlError::InvalidHost { host });
+    }
+
+    let path: Vec<&str> = segments.into_iter().skip(usize::from(!hostless)).filter(|s| !s.is_empty()).collect();
+    let (owner, repo) = match (path.first(), path.get(1).map(|repo| strip_git_suffix(repo))) {
+        (Some(owner), Some(repo)) if !repo.is_empty() => (owner.to_string(), repo.to_string()),
+        _ => return Err(PrUrlError::MissingOwnerOrRepo),
+    };
+
+    match path.get(2) {
+        Some(&"pull") | Some(&"pulls") => {}
+        other => return Err(PrUrlError::MissingPullSegment { found: other.map(|s| s.to_string()) }),
+    }
+
+    // Anything after the number (/files, /commits/<sha>) is ignored
+    let number = path.get(3).ok_or(PrUrlError::MissingNumber)?;
+    let pr_number = parse_number(number)?;
+
+    Ok(GitHubPRUrl { owner, repo, pr_number })
+}
+
+// "owner/repo#12" or "#12" split at the '#'; None for anything URL-shaped, where '#' starts a fragment
+fn shorthand_parts(input: &str) -> Option<(&str, &str)> {
+    if input.contains("://") {
+        return None;
+    }
+    let (repo_part, number) = input.split_once('#')?;
+    let repo_part = repo_part.trim_end_matches('/');
+    (repo_part.is_empty() || repo_part.matches('/').count() == 1).then_some((repo_part, number.trim_end_matches('/')))
+}
+
+fn split_repo(repo_part: &str) -> Option<(String, String)> {
+    let (owner, repo) = repo_part.trim().trim_end_matches('/').split_once('/')?;
+    let repo = strip_git_suffix(repo);
+    (!owner.is_empty() && !repo.is_empty() && !repo.contains('/')).then(|| (owner.to_string(), repo.to_string()))
+}
+
+fn strip_git_suffix(repo: &str) -> &str {
+    repo.strip_suffix(".git").unwrap_or(repo)
+}
+
+fn parse_number(number: &str) -> Result<i64, PrUrlError> {
+    if number.is_empty() {
+        return Err(PrUrlError::MissingNumber);
+    }
+    number.parse::<i64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| PrUrlError::InvalidNumber { value: number.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(owner: &str, repo: &str, pr_number: i64) -> Result<GitHubPRUrl, PrUrlError> {
+        Ok(GitHubPRUrl { owner: owner.to_string(), repo: repo.to_string(), pr_number })
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let cases: Vec<(&str, Result<GitHubPRUrl, PrUrlError>)> = vec![
+            ("https://github.com/tauri-apps/tauri/pull/1234", ok("tauri-apps", "tauri", 1234)),
+            ("http://github.com/owner/repo/pull/1", ok("owner", "repo", 1)),
+            ("github.com/owner/repo/pull/7", ok("owner", "repo", 7)),
+            ("https://www.github.com/owner/repo/pull/7", ok("owner", "repo", 7)),
+            ("https://GitHub.com/owner/repo/pull/7", ok("owner", "repo", 7)),
+            ("  https://github.com/owner/repo/pull/7  ", ok("owner", "repo", 7)),
+            ("https://github.com/owner/repo/pull/42/", ok("owner", "repo", 42)),
+            ("https://github.com/owner/repo/pull/42/files", ok("owner", "repo", 42)),
+            ("https://github.com/owner/repo/pull/42/commits/abc123", ok("owner", "repo", 42)),
+            ("https://github.com/owner/repo/pull/42?diff=split", ok("owner", "repo", 42)),
+            ("https://github.com/owner/repo/pull/42#discussion_r1", ok("owner", "repo", 42)),
+            ("https://github.com/owner/repo/pulls/42", ok("owner", "repo", 42)),
+            ("https://github.com/my.org/my-repo.rs/pull/3", ok("my.org", "my-repo.rs", 3)),
+            ("https://github.com/a/b/pull/1", ok("a", "b", 1)),
+            ("https://github.com/a/b.git/pull/1/", ok("a", "b", 1)),
+            ("a/b#1", ok("a", "b", 1)),
+            ("acme/backend/pull/4521", ok("acme", "backend", 4521)),
+            ("acme/backend.git/pulls/4521/files", ok("acme", "backend", 4521)),
+            ("acme/backend/pull/4521#discussion_r1", ok("acme", "backend", 4521)),
+            ("acme/backend/pull/x", Err(PrUrlError::InvalidNumber { value: "x".to_string() })),
+            ("https://acme/backend/pull/4521", Err(PrUrlError::InvalidHost { host: "acme".to_string() })),
+            ("gitlab.com/owner/repo/pull/1", Err(PrUrlError::InvalidHost { host: "gitlab.com".to_string() })),
+            ("  a/b#1/ ", ok("a", "b", 1)),
+            ("a/b/#1", ok("a", "b", 1)),
+            ("a/b.git#1", ok("a", "b", 1)),
+            ("my.org/my-repo.rs#3", ok("my.org", "my-repo.rs", 3)),
+            ("#1", Err(PrUrlError::MissingDefaultRepo)),
+            ("a#1", Err(PrUrlError::InvalidHost { host: "a".to_string() })),
+            ("a/.git#1", Err(PrUrlError::MissingOwnerOrRepo)),
+            ("a/b#", Err(PrUrlError::MissingNumber)),
+            ("a/b#x", Err(PrUrlError::InvalidNumber { value: "x".to_string() })),
+            ("https://github.com/a/.git/pull/1", Err(PrUrlError::MissingOwnerOrRepo)),
+            ("", Err(PrUrlError::Empty)),
+            ("   ", Err(PrUrlError::Empty)),
+            ("https://gitlab.com/owner/repo/pull/1", Err(PrUrlError::InvalidHost { host: "gitlab.com".to_string() })),
+            ("https://example.com/not-a-pr", Err(PrUrlError::InvalidHost { host: "example.com".to_string() })),
+            ("not a url", Err(PrUrlError::InvalidHost { host: "not a url".to_string() })),
+            ("https:///owner/repo/pull/1", Err(PrUrlError::InvalidHost { host: String::new() })),
+            ("https://github.com", Err(PrUrlError::MissingOwnerOrRepo)),
+            ("https://github.com/owner", Err(PrUrlError::MissingOwnerOrRepo)),
+            ("https://github.com/owner/repo", Err(PrUrlError::MissingPullSegment { found: None })),
+            ("https://github.com/owner/repo/issues/42", Err(PrUrlError::MissingPullSegment { found: Some("issues".to_string()) })),
+            ("https://github.com/owner/repo/pull", Err(PrUrlError::MissingNumber)),
+            ("https://github.com/owner/repo/pull/", Err(PrUrlError::MissingNumber)),
+            ("https://github.com/owner/repo/pull/abc", Err(PrUrlError::InvalidNumber { value: "abc".to_string() })),
+            ("https://github.com/owner/repo/pull/0", Err(PrUrlError::InvalidNumber { value: "0".to_string() })),
+            ("https://github.com/owner/repo/pull/-5", Err(PrUrlError::InvalidNumber { value: "-5".to_string() })),
+            ("https://github.com/owner/repo/pull/99999999999999999999", Err(PrUrlError::InvalidNumber { value: "99999999999999999999".to_string() })),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(parse_github_pr_url(input), expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_bare_number_uses_default_repo() {
+        assert_eq!(parse_pr_reference("#12", Some("acme/api")), ok("acme", "api", 12));
+        assert_eq!(parse_pr_reference("#12", Some("acme/api.git/")), ok("acme", "api", 12));
+        assert_eq!(parse_pr_reference("#12", Some("acme")), Err(PrUrlError::MissingOwnerOrRepo));
+        // An explicit repository wins over the default
+        assert_eq!(parse_pr_reference("other/web#3", Some("acme/api")), ok("other", "web", 3));
+        assert_eq!(parse_pr_reference("https://github.com/a/b/pull/1#r2", Some("acme/api")), ok("a", "b", 1));
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        let parsed = parse_github_pr_url("github.com/owner/repo/pull/42/files?w=1").unwrap();
+        assert_eq!(parsed.canonical_url(), "https://github.com/owner/repo/pull/42");
+
+        let validated = ValidatedPrUrl::from(parsed);
+        assert_eq!(validated.canonical_url, "https://github.com/owner/repo/pull/42");
+        assert_eq!(validated.pr_number, 42);
+    }
+
+    #[test]
+    fn test_error_messages_name_the_segment() {
+        let err = parse_github_pr_url("https://github.com/owner/repo/issues/42").unwrap_err();
+        assert!(err.to_string().contains("'/issues/'"));
+        assert!(err.to_string().starts_with("Invalid GitHub PR URL format"));
+        // Every error lists what is accepted
+        let ambiguous = parse_github_pr_url("acme/backend").unwrap_err();
+        assert!(ambiguous.to_string().ends_with(URL_FORMAT_HINT), "{}", ambiguous);
+        assert!(URL_FORMAT_HINT.contains("owner/repo#123") && URL_FORMAT_HINT.contains("owner/repo/pull/123"));
+
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["kind"], "missing_pull_segment");
+        assert_eq!(json["found"], "issues");
+    }
+}
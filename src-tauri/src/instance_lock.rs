@@ -0,0 +1,127 @@
+// Single-writer guard for the data directory. The first instance holds an OS advisory lock
+// (flock / LockFileEx) on a lock file and writes its PID there; a second instance finds the
+// lock taken and runs without background tasks, so sync, history and notifications aren't
+// done twice. The OS releases the lock whenever the holder exits, crashed or not, so a lock
+// file left behind never blocks the next launch, whatever PID it names.
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+pub const LOCK_FILE_NAME: &str = "instance.lock";
+
+// Event emitted to the UI when this instance started as secondary
+pub const SECONDARY_INSTANCE_EVENT: &str = "secondary-instance";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct InstanceStatus {
+    pub secondary: bool,
+    pub holder_pid: Option<u32>, // the primary's PID when this instance is secondary
+}
+
+/// Held by the primary instance; the lock is released when this is dropped or the process ends.
+/// The file itself stays: deleting it would let a later launch lock a fresh file beside ours.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+#[derive(Debug)]
+pub enum Acquired {
+    Primary(InstanceLock),
+    // The PID is unknown while the holder is still writing it, and on Windows, where the
+    // locked file can't be read by others
+    Secondary { holder_pid: Option<u32> },
+}
+
+/// Take the lock at `path` for `pid`, without waiting
+pub fn acquire(path: &Path, pid: u32) -> io::Result<Acquired> {
+    let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let holder_pid = fs::read_to_string(path).ok().and_then(|content| content.trim().parse::<u32>().ok());
+            return Ok(Acquired::Secondary { holder_pid });
+        }
+        Err(TryLockError::Error(e)) => return Err(e),
+    }
+
+    // Whatever an earlier holder left in the file is replaced by our PID
+    file.set_len(0)?;
+    file.write_all(pid.to_string().as_bytes())?;
+    file.sync_data()?;
+    Ok(Acquired::Primary(InstanceLock { _file: file }))
+}
+
+static STATUS: OnceLock<InstanceStatus> = OnceLock::new();
+static LOCK: OnceLock<InstanceLock> = OnceLock::new();
+
+/// Startup: take the lock in the app data directory. If that fails for any reason other
+/// than a live holder, run as primary rather than lose background sync.
+pub fn claim_instance() -> InstanceStatus {
+    let result = crate::database::get_app_dir()
+        .map_err(|e| io::Error::other(e.to_string()))
+        .and_then(|dir| acquire(&dir.join(LOCK_FILE_NAME), std::process::id()));
+    let status = match result {
+        Ok(Acquired::Primary(lock)) => {
+            let _ = LOCK.set(lock);
+            InstanceStatus::default()
+        }
+        Ok(Acquired::Secondary { holder_pid }) => {
+            println!("👥 Another instance (PID {:?}) is running; background tasks are disabled here", holder_pid);
+            InstanceStatus { secondary: true, holder_pid }
+        }
+        Err(e) => {
+            println!("⚠️ Could not take the instance lock: {}", e);
+            InstanceStatus::default()
+        }
+    };
+    *STATUS.get_or_init(|| status)
+}
+
+/// This instance's role; primary until claim_instance says otherwise
+pub fn status() -> InstanceStatus {
+    STATUS.get().copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn lock_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("prtracker-lock-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOCK_FILE_NAME);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_held_lock_makes_second_instance_secondary() {
+        let path = lock_path("held");
+        let Acquired::Primary(lock) = acquire(&path, 100).unwrap() else { panic!("expected primary") };
+        assert!(matches!(acquire(&path, 200).unwrap(), Acquired::Secondary { .. }));
+        #[cfg(unix)]
+        assert!(matches!(acquire(&path, 200).unwrap(), Acquired::Secondary { holder_pid: Some(100) }));
+
+        // Closing the primary frees the lock; the file stays
+        drop(lock);
+        assert!(path.exists());
+        assert!(matches!(acquire(&path, 200).unwrap(), Acquired::Primary(_)));
+    }
+
+    #[test]
+    fn test_leftover_lock_files_dont_block() {
+        // Left by a process that quit or crashed, possibly naming a PID now reused, or half written
+        for (name, content) in [("leftover", "4242"), ("own-pid", "300"), ("garbled", "")] {
+            let path = lock_path(name);
+            fs::write(&path, content).unwrap();
+            let Acquired::Primary(_lock) = acquire(&path, 300).unwrap() else { panic!("expected primary for {}", name) };
+            #[cfg(unix)]
+            assert_eq!(fs::read_to_string(&path).unwrap(), "300");
+        }
+    }
+}
@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `pr-tracker reset-dev-environment` resets the dev data directory and exits
+    if std::env::args().nth(1).as_deref() == Some(pr_tracker_lib::dev_env::RESET_DEV_SUBCOMMAND) {
+        std::process::exit(pr_tracker_lib::dev_env::run_cli());
+    }
     pr_tracker_lib::run()
 }
@@ -0,0 +1,159 @@
+// Cleaning PR titles and notes before they go into Markdown the app generates. Titles come
+// straight from GitHub and can hold control characters, ANSI escapes, markdown or HTML; the
+// PR summary card (services/pr_summary.rs) renders every value through markdown_cell so one
+// stray pipe or escape code can't break the output.
+
+// Longest value put in a Markdown table cell
+pub const REPORT_CELL_MAX_CHARS: usize = 200;
+
+const ELLIPSIS: char = '…';
+
+// Characters with meaning inside a Markdown table row
+const MARKDOWN_METACHARACTERS: [char; 11] = ['\\', '`', '*', '_', '[', ']', '#', '~', '|', '!', '$'];
+
+const ESC: char = '\u{1b}';
+
+/// Drop ANSI escape sequences: CSI (`ESC [ ... final`), OSC (`ESC ] ... BEL` or `ESC \`)
+/// and two-character escapes
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Cut to at most `max_chars` characters, ending with an ellipsis when shortened
+fn clamp_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut clamped: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    clamped.truncate(clamped.trim_end().len());
+    clamped.push(ELLIPSIS);
+    clamped
+}
+
+/// Plain single-line text: no escapes or control characters, whitespace collapsed, clamped
+fn clean_text(text: &str, max_chars: usize) -> String {
+    let stripped = strip_ansi(text);
+    let collapsed = stripped
+        .split(|c: char| c.is_whitespace())
+        .map(|word| word.chars().filter(|c| !c.is_control()).collect::<String>())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    clamp_chars(&collapsed, max_chars)
+}
+
+/// Cleaned text that renders literally inside a Markdown table cell. HTML is entity-escaped
+/// rather than backslash-escaped, since renderers pass raw tags through.
+pub fn markdown_cell(text: &str, max_chars: usize) -> String {
+    let mut escaped = String::new();
+    for c in clean_text(text, max_chars).chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            c if MARKDOWN_METACHARACTERS.contains(&c) => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADVERSARIAL_TITLES: [&str; 7] = [
+        "fix: handle ``nested `backticks` `` in code spans",
+        "feat(api): a | b || c",
+        "<script>alert('x')</script> & <b>bold</b>",
+        "\u{1b}[31mred\u{1b}[0m and \u{1b}]8;;https://evil.example\u{7}link\u{1b}]8;;\u{7}",
+        "line one\nline two\r\n\tindented\u{0}\u{7f}",
+        "**bold** _em_ [link](http://x) ![img](y) # heading ~~gone~~",
+        "日本語のタイトルはとても長いのでここで切り詰められるべきですよね、そう思いませんか？本当に長いですからね",
+    ];
+
+    // Split a rendered row on pipes that aren't backslash-escaped
+    fn cells(line: &str) -> Vec<String> {
+        let mut cells = vec![String::new()];
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let last = cells.last_mut().unwrap();
+                    last.push(c);
+                    last.extend(chars.next());
+                }
+                '|' => cells.push(String::new()),
+                c => cells.last_mut().unwrap().push(c),
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn test_adversarial_titles_keep_the_table_intact() {
+        let table: String = ADVERSARIAL_TITLES.iter()
+            .enumerate()
+            .map(|(i, title)| {
+                let cells = [format!("#{}", i + 1), title.to_string(), "octocat".to_string()];
+                let cells: Vec<String> = cells.iter().map(|cell| markdown_cell(cell, REPORT_CELL_MAX_CHARS)).collect();
+                format!("| {} |\n", cells.join(" | "))
+            })
+            .collect();
+
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), ADVERSARIAL_TITLES.len());
+        for line in &lines {
+            assert!(line.starts_with("| ") && line.ends_with(" |"), "{}", line);
+            // Leading and trailing pipes give an empty cell on each side
+            assert_eq!(cells(line).len(), 5, "{}", line);
+            assert!(!line.chars().any(|c| c.is_control()), "{:?}", line);
+            assert!(!line.contains("<script>") && !line.contains("<b>"));
+        }
+        assert_eq!(cells(lines[1])[2].trim(), "feat(api): a \\| b \\|\\| c");
+        assert_eq!(cells(lines[3])[2].trim(), "red and link");
+        assert_eq!(cells(lines[4])[2].trim(), "line one line two indented");
+        assert_eq!(cells(lines[6])[3].trim(), "octocat");
+    }
+
+    #[test]
+    fn test_clean_and_clamp() {
+        assert_eq!(clean_text("  a\n\n b\t\u{1b}[1mc\u{1b}[0m ", 50), "a b c");
+        assert_eq!(clamp_chars("abcdef", 6), "abcdef");
+        assert_eq!(clamp_chars("abc def", 5), "abc…");
+        // Multi-byte characters are counted, never split
+        assert_eq!(clamp_chars("日本語のタイトル", 4), "日本語…");
+        let long = clean_text(&"word ".repeat(100), 120);
+        assert_eq!(long.chars().count(), 120);
+        assert!(long.ends_with(ELLIPSIS));
+        assert_eq!(markdown_cell("use `x` | <y>", 50), "use \\`x\\` \\| &lt;y&gt;");
+    }
+}
@@ -0,0 +1,55 @@
+// Saving and reporting per-feature GitHub call counts
+use std::time::Duration;
+
+use crate::database::Database;
+use crate::github::api_usage::{self, ApiUsageReport};
+use crate::DbState;
+
+// How often in-memory counts are written to api_usage
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+// get_api_usage covers at most a week
+pub const MAX_USAGE_HOURS: i64 = 24 * 7;
+
+/// Write pending counts; they are kept for the next flush if the write fails
+pub async fn flush_api_usage(db: &Database) -> Result<(), String> {
+    let pending = api_usage::take_unsaved();
+    if pending.is_empty() {
+        return Ok(());
+    }
+    if let Err(e) = db.add_api_usage(&pending).await {
+        api_usage::restore_unsaved(&pending);
+        return Err(e.to_string());
+    }
+    Ok(())
+}
+
+/// Calls per feature over the last `hours` hours; this session only while the database is closed
+pub async fn get_api_usage(db: Option<&Database>, hours: i64, now: i64) -> Result<ApiUsageReport, String> {
+    if !(1..=MAX_USAGE_HOURS).contains(&hours) {
+        return Err(format!("hours must be between 1 and {}", MAX_USAGE_HOURS));
+    }
+    let since = now - hours * 60 * 60;
+    let report = match db {
+        Some(db) => {
+            let saved = db.get_api_usage_since(since).await.map_err(|e| e.to_string())?;
+            api_usage::usage_report(hours, since, saved, api_usage::peek_unsaved())
+        }
+        None => api_usage::usage_report(hours, since, Vec::new(), api_usage::session_usage(since)),
+    };
+    Ok(report)
+}
+
+/// Background loop spawned at startup
+pub async fn run_api_usage_persister(state: DbState) {
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let db_state = state.lock().await;
+        let Some(db) = db_state.as_ref() else { continue };
+        if let Err(e) = flush_api_usage(db).await {
+            println!("⚠️ Could not save API usage: {}", e);
+        }
+    }
+}
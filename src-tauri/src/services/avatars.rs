@@ -0,0 +1,275 @@
+// Downloading team member avatars after an import, so the UI doesn't show dozens of broken
+// images while they trickle in from the CDN. A few downloads run at once, each with a
+// timeout and a size cap, and only PNG/JPEG/GIF/WebP bodies are written to disk. The database
+// is locked to list the members and to record each result, never while a download is out.
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::task::JoinSet;
+
+use crate::database::avatars::AvatarCandidate;
+use crate::database::{get_app_dir, RETRY_MAX_ATTEMPTS};
+use crate::DbState;
+
+pub const AVATAR_PREFETCH_PROGRESS_EVENT: &str = "avatar-prefetch-progress";
+
+pub const MAX_AVATAR_BYTES: usize = 1024 * 1024;
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone)]
+pub struct AvatarImage {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// One avatar download (mocked in tests)
+pub trait AvatarFetcher {
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<AvatarImage, String>> + Send;
+}
+
+#[derive(Clone)]
+pub struct HttpAvatarFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpAvatarFetcher {
+    pub fn new() -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .user_agent("PR-Tracker")
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { client })
+    }
+}
+
+impl AvatarFetcher for HttpAvatarFetcher {
+    // Avatars come from the CDN, not the API, so they don't count against the rate limit
+    async fn fetch(&self, url: &str) -> Result<AvatarImage, String> {
+        let mut response = self.client.get(url).send().await.map_err(|e| format!("Download failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Download failed: {}", response.status()));
+        }
+        let content_type = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        // Checked before the body is read; the size again while it streams in
+        image_extension(&content_type)?;
+        if response.content_length().is_some_and(|length| length as usize > MAX_AVATAR_BYTES) {
+            return Err(too_large());
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| format!("Download failed: {}", e))? {
+            if bytes.len() + chunk.len() > MAX_AVATAR_BYTES {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(AvatarImage { content_type, bytes })
+    }
+}
+
+fn too_large() -> String {
+    format!("Avatar is larger than {} KB", MAX_AVATAR_BYTES / 1024)
+}
+
+/// File extension for a raster image type; anything else (SVG included) is refused
+pub fn image_extension(content_type: &str) -> Result<&'static str, String> {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "image/png" => Ok("png"),
+        "image/jpeg" | "image/jpg" => Ok("jpg"),
+        "image/gif" => Ok("gif"),
+        "image/webp" => Ok("webp"),
+        "" => Err("Response has no content type".to_string()),
+        other => Err(format!("Not an image: {}", other)),
+    }
+}
+
+async fn save_avatar(dir: &Path, member_id: i64, image: AvatarImage) -> Result<PathBuf, String> {
+    let extension = image_extension(&image.content_type)?;
+    if image.bytes.is_empty() {
+        return Err("Avatar is empty".to_string());
+    }
+    if image.bytes.len() > MAX_AVATAR_BYTES {
+        return Err(too_large());
+    }
+    tokio::fs::create_dir_all(dir).await.map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.{}", member_id, extension));
+    tokio::fs::write(&path, &image.bytes).await.map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct AvatarPrefetchProgress {
+    pub total: usize,
+    pub completed: usize,
+    pub downloaded: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct AvatarPrefetchReport {
+    pub total: usize,
+    pub downloaded: usize,
+    pub failed: usize,
+    pub errors: Vec<String>, // "member 12: Not an image: text/html"
+}
+
+/// Download every missing avatar, MAX_CONCURRENT_DOWNLOADS at a time. Members whose
+/// earlier downloads failed are skipped until their backoff passes.
+pub async fn prefetch_missing_avatars<F>(
+    state: &DbState,
+    fetcher: &F,
+    dir: &Path,
+    now: i64,
+    on_progress: impl Fn(&AvatarPrefetchProgress),
+) -> Result<AvatarPrefetchReport, String>
+where
+    F: AvatarFetcher + Clone + Send + Sync + 'static,
+{
+    let mut candidates = {
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        db.get_members_missing_avatars(now).await.map_err(|e| e.to_string())?.into_iter()
+    };
+    let mut progress = AvatarPrefetchProgress { total: candidates.len(), ..Default::default() };
+    let mut report = AvatarPrefetchReport { total: progress.total, ..Default::default() };
+    if progress.total == 0 {
+        return Ok(report);
+    }
+    println!("🖼️ Downloading {} missing avatar(s)", progress.total);
+
+    let mut downloads = JoinSet::new();
+    loop {
+        while downloads.len() < MAX_CONCURRENT_DOWNLOADS {
+            let Some(candidate) = candidates.next() else { break };
+            let (fetcher, dir) = (fetcher.clone(), dir.to_path_buf());
+            downloads.spawn(async move {
+                let saved = match fetcher.fetch(&candidate.avatar_url).await {
+                    Ok(image) => save_avatar(&dir, candidate.member_id, image).await,
+                    Err(e) => Err(e),
+                };
+                (candidate, saved)
+            });
+        }
+        let Some(finished) = downloads.join_next().await else { break };
+        let (AvatarCandidate { member_id, avatar_url }, saved) = finished.map_err(|e| e.to_string())?;
+
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        match saved {
+            Ok(path) => {
+                db.set_avatar_path(member_id, &avatar_url, &path.to_string_lossy()).await.map_err(|e| e.to_string())?;
+                progress.downloaded += 1;
+            }
+            Err(e) => {
+                let failures = db.record_avatar_failure(member_id, now).await.map_err(|e| e.to_string())?;
+                if failures >= RETRY_MAX_ATTEMPTS {
+                    println!("🅿️ Giving up on the avatar of member {} after {} attempts: {}", member_id, failures, e);
+                }
+                report.errors.push(format!("member {}: {}", member_id, e));
+                progress.failed += 1;
+            }
+        }
+        drop(db_state);
+        progress.completed += 1;
+        on_progress(&progress);
+    }
+
+    report.downloaded = progress.downloaded;
+    report.failed = progress.failed;
+    Ok(report)
+}
+
+pub fn avatar_dir() -> Result<PathBuf, String> {
+    get_app_dir().map(|dir| dir.join("avatars")).map_err(|e| e.to_string())
+}
+
+/// Prefetch from the CDN into the app's avatars folder
+pub async fn prefetch_from_cdn(state: &DbState, on_progress: impl Fn(&AvatarPrefetchProgress)) -> Result<AvatarPrefetchReport, String> {
+    let fetcher = HttpAvatarFetcher::new()?;
+    let dir = avatar_dir()?;
+    prefetch_missing_avatars(state, &fetcher, &dir, chrono::Utc::now().timestamp(), on_progress).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    // Downloads also check that the database can be locked while they are out
+    #[derive(Clone)]
+    struct MockFetcher(Arc<HashMap<&'static str, Result<AvatarImage, String>>>, DbState);
+
+    impl AvatarFetcher for MockFetcher {
+        async fn fetch(&self, url: &str) -> Result<AvatarImage, String> {
+            let waited = tokio::time::timeout(Duration::from_secs(1), self.1.lock()).await;
+            assert!(waited.is_ok(), "database held during a download");
+            self.0.get(url).cloned().unwrap_or_else(|| Err("not found".to_string()))
+        }
+    }
+
+    fn image(content_type: &str, size: usize) -> Result<AvatarImage, String> {
+        Ok(AvatarImage { content_type: content_type.to_string(), bytes: vec![7; size] })
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_saves_images_and_backs_off_on_failures() {
+        let db = Database::new_in_memory().await.unwrap();
+        let images = Arc::new(HashMap::from([
+            ("https://a/1", image("image/png", 10)),
+            ("https://a/2", image("image/jpeg; charset=binary", 10)),
+            ("https://a/3", image("text/html", 10)),
+            ("https://a/4", image("image/png", MAX_AVATAR_BYTES + 1)),
+            ("https://a/5", image("image/svg+xml", 10)),
+            ("https://a/6", image("image/gif", 10)),
+        ]));
+        let mut members = Vec::new();
+        for n in 1..=7 {
+            let url = format!("https://a/{}", n);
+            members.push(db.add_team_member(&format!("member{}", n), Some(&url), None).await.unwrap());
+        }
+        db.add_team_member("no-avatar", None, None).await.unwrap();
+
+        let dir = std::env::temp_dir().join(format!("pr-tracker-avatars-{}", std::process::id()));
+        let events = std::sync::Mutex::new(Vec::new());
+        let state: DbState = Arc::new(tokio::sync::Mutex::new(Some(db)));
+        let fetcher = MockFetcher(images, state.clone());
+        let report = prefetch_missing_avatars(&state, &fetcher, &dir, 1_000, |progress| events.lock().unwrap().push(progress.clone()))
+            .await
+            .unwrap();
+        assert_eq!((report.total, report.downloaded, report.failed), (7, 3, 4));
+        let events = events.into_inner().unwrap();
+        assert_eq!(events.len(), 7);
+        assert_eq!(events.last().unwrap().completed, 7);
+
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().unwrap();
+
+        let member = db.get_team_member_by_username("member2").await.unwrap().unwrap();
+        let path = member.avatar_path.unwrap();
+        assert!(path.ends_with(&format!("{}.jpg", members[1].id)));
+        assert_eq!(std::fs::read(&path).unwrap().len(), 10);
+        assert_eq!(db.get_team_member_by_username("member3").await.unwrap().unwrap().avatar_path, None);
+
+        // Failed members wait out their backoff; nothing is left before then
+        assert!(db.get_members_missing_avatars(1_000).await.unwrap().is_empty());
+        assert_eq!(db.get_members_missing_avatars(1_000 + 60).await.unwrap().len(), 4);
+
+        // A new URL starts over
+        db.update_team_member_info(members[2].id, "https://a/6", None).await.unwrap();
+        let due = db.get_members_missing_avatars(1_000).await.unwrap();
+        assert_eq!(due.iter().map(|c| c.member_id).collect::<Vec<_>>(), vec![members[2].id]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,50 @@
+// Reaching the database through the app's DbState from background tasks, which may start
+// before init_database has opened it
+use std::time::Duration;
+
+use chrono_tz::Tz;
+use tokio::sync::{MappedMutexGuard, MutexGuard};
+
+use crate::database::Database;
+use crate::display_time::system_timezone;
+use crate::DbState;
+
+// How often a startup task checks whether the database is open yet
+const DATABASE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Wait until the database is open and return it locked; the lock is released between checks
+pub async fn wait_for_database(state: &DbState) -> MappedMutexGuard<'_, Database> {
+    loop {
+        if let Ok(db) = MutexGuard::try_map(state.lock().await, Option::as_mut) {
+            return db;
+        }
+        tokio::time::sleep(DATABASE_POLL_INTERVAL).await;
+    }
+}
+
+/// The display time zone, or the system zone while the database isn't open (or has no usable setting)
+pub async fn display_tz(db: Option<&Database>) -> Tz {
+    match db {
+        Some(db) => db.get_display_timezone().await.unwrap_or_else(|_| system_timezone()),
+        None => system_timezone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_wait_for_database_returns_once_it_opens() {
+        let state: DbState = Arc::new(tokio::sync::Mutex::new(None));
+        let opener = state.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            *opener.lock().await = Some(Database::new_in_memory().await.unwrap());
+        });
+
+        let db = tokio::time::timeout(Duration::from_secs(5), wait_for_database(&state)).await.unwrap();
+        assert_eq!(display_tz(Some(&db)).await, system_timezone());
+    }
+}
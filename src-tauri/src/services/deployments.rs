@@ -0,0 +1,233 @@
+// Deployment status for the PR detail: the latest deployment per environment for the PR's
+// head commit, with its latest status and environment URL. Fetched when the detail opens
+// and kept only briefly in memory, per head SHA; nothing is stored in SQLite.
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::bounded_cache::{json_size, BoundedCache, CacheName, EstimateSize};
+use crate::database::Database;
+use crate::github::api_usage::ApiFeature;
+use crate::github::models::{GitHubDeployment, GitHubDeploymentStatus};
+use crate::github::{fetch_deployments_for_sha, fetch_latest_deployment_status, fetch_pr_head_sha};
+
+// Statuses move on without new commits, so a cached answer is only reused for a minute
+const CACHE_TTL_SECS: i64 = 60;
+// One status call per environment; more than this on one commit is a misconfiguration
+const MAX_ENVIRONMENTS: usize = 10;
+
+pub const MISSING_PERMISSION: &str = "unavailable: missing permission";
+
+/// Results by "owner/repo@sha"
+pub type DeploymentsCache = Arc<Mutex<BoundedCache<String, PrDeployments>>>;
+
+pub fn new_cache() -> DeploymentsCache {
+    Arc::new(Mutex::new(BoundedCache::new(CacheName::Deployments)))
+}
+
+/// GitHub calls needed for the deployment status (mocked in tests). Ok(None) from the
+/// deployment calls means the token can't read deployments.
+pub trait DeploymentsClient {
+    fn fetch_head_sha(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<String, String>> + Send;
+    fn fetch_deployments(&self, owner: &str, repo: &str, sha: &str) -> impl Future<Output = Result<Option<Vec<GitHubDeployment>>, String>> + Send;
+    fn fetch_latest_status(&self, owner: &str, repo: &str, deployment_id: i64) -> impl Future<Output = Result<Option<Option<GitHubDeploymentStatus>>, String>> + Send;
+}
+
+pub struct GitHubDeploymentsClient {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl DeploymentsClient for GitHubDeploymentsClient {
+    async fn fetch_head_sha(&self, owner: &str, repo: &str, number: i64) -> Result<String, String> {
+        fetch_pr_head_sha(self.feature, &self.token, owner, repo, number).await
+    }
+
+    async fn fetch_deployments(&self, owner: &str, repo: &str, sha: &str) -> Result<Option<Vec<GitHubDeployment>>, String> {
+        fetch_deployments_for_sha(self.feature, &self.token, owner, repo, sha).await
+    }
+
+    async fn fetch_latest_status(&self, owner: &str, repo: &str, deployment_id: i64) -> Result<Option<Option<GitHubDeploymentStatus>>, String> {
+        fetch_latest_deployment_status(self.feature, &self.token, owner, repo, deployment_id).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EnvironmentDeployment {
+    pub environment: String,
+    pub state: Option<String>, // None until the deployment reports a status
+    pub environment_url: Option<String>,
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PrDeployments {
+    pub pr_id: i64,
+    pub head_sha: String,
+    pub environments: Vec<EnvironmentDeployment>, // by environment name; empty when nothing deployed
+    pub unavailable: Option<String>,              // MISSING_PERMISSION when the token can't read deployments
+    pub fetched_at: i64,
+}
+
+impl EstimateSize for PrDeployments {
+    fn estimated_bytes(&self) -> usize {
+        json_size(self)
+    }
+}
+
+/// The newest deployment of each environment, by environment name
+fn latest_per_environment(deployments: Vec<GitHubDeployment>) -> Vec<GitHubDeployment> {
+    let mut latest: BTreeMap<String, GitHubDeployment> = BTreeMap::new();
+    for deployment in deployments {
+        match latest.get(&deployment.environment) {
+            Some(newer) if newer.id > deployment.id => {}
+            _ => {
+                latest.insert(deployment.environment.clone(), deployment);
+            }
+        }
+    }
+    latest.into_values().take(MAX_ENVIRONMENTS).collect()
+}
+
+async fn fetch_environments<C: DeploymentsClient>(client: &C, owner: &str, repo: &str, sha: &str) -> Result<Option<Vec<EnvironmentDeployment>>, String> {
+    let Some(deployments) = client.fetch_deployments(owner, repo, sha).await? else {
+        return Ok(None);
+    };
+    let mut environments = Vec::new();
+    for deployment in latest_per_environment(deployments) {
+        let Some(status) = client.fetch_latest_status(owner, repo, deployment.id).await? else {
+            return Ok(None);
+        };
+        environments.push(EnvironmentDeployment {
+            environment: deployment.environment,
+            updated_at: status.as_ref().and_then(|status| status.created_at_timestamp()),
+            environment_url: status.as_ref().and_then(|status| status.environment_url.clone()).filter(|url| !url.is_empty()),
+            state: status.map(|status| status.state),
+        });
+    }
+    Ok(Some(environments))
+}
+
+/// Deployment status of a PR's head commit, reusing a result from the last minute
+pub async fn get_pr_deployments<C: DeploymentsClient>(db: &Database, client: &C, cache: &DeploymentsCache, pr_id: i64, now: i64) -> Result<PrDeployments, String> {
+    let pr = db.get_pull_request_by_id(pr_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} not found", pr_id))?;
+    if pr.is_issue {
+        return Err(format!("Issue #{} has no deployments until a PR is linked", pr.pr_number));
+    }
+    let (Some(owner), Some(repo)) = (pr.repository_owner.as_deref(), pr.repository_name.as_deref()) else {
+        return Err("Repository fields missing; run repair_missing_repo_fields first".to_string());
+    };
+
+    let head_sha = client.fetch_head_sha(owner, repo, pr.pr_number).await?;
+    let key = format!("{}/{}@{}", owner, repo, head_sha);
+    if let Some(cached) = cache.lock().await.get_if(&key, |d| now - d.fetched_at < CACHE_TTL_SECS) {
+        return Ok(PrDeployments { pr_id, ..cached.clone() });
+    }
+
+    let environments = fetch_environments(client, owner, repo, &head_sha).await?;
+    let deployments = PrDeployments {
+        pr_id,
+        unavailable: environments.is_none().then(|| MISSING_PERMISSION.to_string()),
+        environments: environments.unwrap_or_default(),
+        head_sha,
+        fetched_at: now,
+    };
+    cache.lock().await.insert(key, deployments.clone());
+    Ok(deployments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    struct MockClient {
+        permitted: AtomicBool,
+        deployment_fetches: AtomicU32,
+    }
+
+    fn deployment(id: i64, environment: &str) -> GitHubDeployment {
+        GitHubDeployment { id, environment: environment.to_string() }
+    }
+
+    impl DeploymentsClient for MockClient {
+        async fn fetch_head_sha(&self, _owner: &str, _repo: &str, number: i64) -> Result<String, String> {
+            Ok(format!("sha{}", number))
+        }
+
+        async fn fetch_deployments(&self, _owner: &str, _repo: &str, sha: &str) -> Result<Option<Vec<GitHubDeployment>>, String> {
+            self.deployment_fetches.fetch_add(1, Ordering::SeqCst);
+            if !self.permitted.load(Ordering::SeqCst) {
+                return Ok(None);
+            }
+            Ok(Some(match sha {
+                "sha1" => vec![deployment(30, "preview"), deployment(20, "staging"), deployment(10, "preview")],
+                _ => Vec::new(),
+            }))
+        }
+
+        async fn fetch_latest_status(&self, _owner: &str, _repo: &str, deployment_id: i64) -> Result<Option<Option<GitHubDeploymentStatus>>, String> {
+            Ok(Some(match deployment_id {
+                30 => Some(GitHubDeploymentStatus {
+                    state: "success".to_string(),
+                    environment_url: Some("https://pr-1.preview.example".to_string()),
+                    created_at: Some("1970-01-01T00:01:40Z".to_string()),
+                }),
+                10 => panic!("superseded deployment looked up"),
+                _ => None,
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_latest_status_per_environment() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let mut prs = Vec::new();
+        for number in [1, 2] {
+            prs.push(db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                author_id: author.id,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some("web".to_string()),
+                ..Default::default()
+            }).await.unwrap());
+        }
+        let client = MockClient { permitted: AtomicBool::new(true), deployment_fetches: AtomicU32::new(0) };
+        let cache = new_cache();
+
+        let deployed = get_pr_deployments(&db, &client, &cache, prs[0].id, 1_000).await.unwrap();
+        assert_eq!(deployed.unavailable, None);
+        assert_eq!(deployed.environments, vec![
+            EnvironmentDeployment {
+                environment: "preview".to_string(),
+                state: Some("success".to_string()),
+                environment_url: Some("https://pr-1.preview.example".to_string()),
+                updated_at: Some(100),
+            },
+            EnvironmentDeployment { environment: "staging".to_string(), state: None, environment_url: None, updated_at: None },
+        ]);
+
+        let nothing = get_pr_deployments(&db, &client, &cache, prs[1].id, 1_000).await.unwrap();
+        assert!(nothing.environments.is_empty() && nothing.unavailable.is_none());
+
+        // Reused within the minute, fetched again after it
+        get_pr_deployments(&db, &client, &cache, prs[0].id, 1_030).await.unwrap();
+        assert_eq!(client.deployment_fetches.load(Ordering::SeqCst), 2);
+
+        client.permitted.store(false, Ordering::SeqCst);
+        let denied = get_pr_deployments(&db, &client, &cache, prs[0].id, 1_060).await.unwrap();
+        assert_eq!(denied.unavailable.as_deref(), Some(MISSING_PERMISSION));
+        assert!(denied.environments.is_empty());
+        assert_eq!(client.deployment_fetches.load(Ordering::SeqCst), 3);
+    }
+}
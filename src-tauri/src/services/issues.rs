@@ -0,0 +1,240 @@
+// Tracking planned work from an issue, upgraded in place once GitHub links (or converts it to) a PR
+use std::future::Future;
+
+use serde::Serialize;
+
+use crate::database::status::PrStatus;
+use crate::database::{Database, NewPullRequest, PullRequest};
+use crate::github::api_usage::ApiFeature;
+use crate::github::models::{linked_pull_request, GitHubIssue, GitHubPRData, GitHubTimelineEvent};
+use crate::github::{fetch_github_issue, fetch_github_pr_data, fetch_issue_timeline};
+use crate::services::pull_requests::ensure_team_member_exists;
+
+/// GitHub calls needed to track an issue (mocked in tests)
+pub trait IssueClient {
+    fn fetch_issue(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<GitHubIssue, String>> + Send;
+    fn fetch_timeline(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<Vec<GitHubTimelineEvent>, String>> + Send;
+    fn fetch_pull_request(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<GitHubPRData, String>> + Send;
+}
+
+pub struct GitHubIssueClient {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl IssueClient for GitHubIssueClient {
+    async fn fetch_issue(&self, owner: &str, repo: &str, number: i64) -> Result<GitHubIssue, String> {
+        fetch_github_issue(self.feature, &self.token, owner, repo, number).await
+    }
+
+    async fn fetch_timeline(&self, owner: &str, repo: &str, number: i64) -> Result<Vec<GitHubTimelineEvent>, String> {
+        fetch_issue_timeline(self.feature, &self.token, owner, repo, number).await
+    }
+
+    async fn fetch_pull_request(&self, owner: &str, repo: &str, number: i64) -> Result<GitHubPRData, String> {
+        fetch_github_pr_data(self.feature, &self.token, owner, repo, number).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssueRefreshResult {
+    pub converted: bool,
+    pub pull_request: PullRequest,
+}
+
+// Issue ids and PR ids come from different sequences, so issue rows store the
+// negated issue id to stay clear of the github_id unique index
+fn issue_github_id(issue: &GitHubIssue) -> i64 {
+    -issue.id
+}
+
+/// Track an issue in a project; it shows up as a row with is_issue set and status Waiting
+pub async fn add_issue<C: IssueClient>(
+    db: &Database,
+    client: &C,
+    owner: &str,
+    repo: &str,
+    issue_number: i64,
+    project_id: i64,
+) -> Result<PullRequest, String> {
+    let issue = client.fetch_issue(owner, repo, issue_number).await?;
+    if issue.pull_request.is_some() {
+        return Err(format!("#{} in {}/{} is a pull request; add it by URL instead", issue_number, owner, repo));
+    }
+
+    if let Some(existing) = db.get_pull_request_by_github_id(issue_github_id(&issue)).await.map_err(|e| e.to_string())? {
+        return Err(format!(
+            "This issue is already tracked in {}",
+            existing.project_name.unwrap_or("Unknown Project".to_string())
+        ));
+    }
+
+    let author_id = ensure_team_member_exists(db, &issue.author()).await.map_err(|e| e.to_string())?;
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    let tracked = db.add_pull_request(&ctx, NewPullRequest {
+        github_id: issue_github_id(&issue),
+        pr_number: issue.number,
+        title: Some(issue.title.clone()),
+        author_id,
+        project_id: Some(project_id),
+        status: PrStatus::Waiting,
+        repository_owner: Some(owner.to_string()),
+        repository_name: Some(repo.to_string()),
+        github_created_at: issue.created_at_timestamp(),
+        github_updated_at: issue.updated_at_timestamp(),
+        is_issue: true,
+        ..Default::default()
+    }).await.map_err(|e| e.to_string())?;
+
+    println!("📝 Tracking issue #{} in {}/{} as item {}", issue.number, owner, repo, tracked.id);
+    Ok(tracked)
+}
+
+/// Re-check a tracked issue. When the issue itself became a PR, or a PR cross-references it,
+/// the row is upgraded to that PR and `converted` is true.
+pub async fn refresh_issue<C: IssueClient>(db: &Database, client: &C, pr_id: i64) -> Result<IssueRefreshResult, String> {
+    let tracked = db.get_pull_request_by_id(pr_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} not found", pr_id))?;
+    if !tracked.is_issue {
+        return Ok(IssueRefreshResult { converted: false, pull_request: tracked });
+    }
+    let (Some(owner), Some(repo)) = (tracked.repository_owner.clone(), tracked.repository_name.clone()) else {
+        return Err("Repository fields missing; run repair_missing_repo_fields first".to_string());
+    };
+
+    let issue = client.fetch_issue(&owner, &repo, tracked.pr_number).await?;
+    let linked = if issue.pull_request.is_some() {
+        Some((owner.clone(), repo.clone(), issue.number))
+    } else {
+        linked_pull_request(&client.fetch_timeline(&owner, &repo, tracked.pr_number).await?)
+    };
+
+    let Some((pr_owner, pr_repo, pr_number)) = linked else {
+        return Ok(IssueRefreshResult { converted: false, pull_request: tracked });
+    };
+
+    let pr_data = client.fetch_pull_request(&pr_owner, &pr_repo, pr_number).await?;
+    if let Some(existing) = db.get_pull_request_by_github_id(pr_data.id).await.map_err(|e| e.to_string())? {
+        return Err(format!(
+            "Issue #{} is linked to PR #{}, which is already tracked as item {}",
+            tracked.pr_number, pr_number, existing.id
+        ));
+    }
+
+    let author_id = ensure_team_member_exists(db, &pr_data.author()).await.map_err(|e| e.to_string())?;
+    let converted = db.convert_issue_to_pr(pr_id, &NewPullRequest {
+        github_id: pr_data.id,
+        pr_number,
+        title: Some(pr_data.title.clone()),
+        author_id,
+        branch: Some(pr_data.head.ref_field.clone()),
+        repository_owner: Some(pr_owner),
+        repository_name: Some(pr_repo),
+        github_created_at: pr_data.created_at_timestamp(),
+        github_updated_at: pr_data.updated_at_timestamp(),
+        ..Default::default()
+    }).await.map_err(|e| e.to_string())?;
+
+    println!("🔀 Issue #{} converted to PR #{} (item {})", tracked.pr_number, pr_number, pr_id);
+    Ok(IssueRefreshResult { converted: true, pull_request: converted })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::models::{GitHubHead, GitHubPRUser};
+
+    struct MockClient {
+        issue_is_pr: bool,
+        timeline: &'static str,
+    }
+
+    fn user() -> GitHubPRUser {
+        GitHubPRUser { login: "octocat".to_string(), avatar_url: "https://example.com/a.png".to_string(), name: None }
+    }
+
+    impl IssueClient for MockClient {
+        async fn fetch_issue(&self, _owner: &str, _repo: &str, number: i64) -> Result<GitHubIssue, String> {
+            Ok(GitHubIssue {
+                id: 9000 + number,
+                number,
+                title: "Support dark mode".to_string(),
+                user: user(),
+                created_at: Some("2024-01-01T00:00:00Z".to_string()),
+                updated_at: None,
+                pull_request: self.issue_is_pr.then(|| serde_json::json!({ "url": "https://api.github.com/pulls/1" })),
+            })
+        }
+
+        async fn fetch_timeline(&self, _owner: &str, _repo: &str, _number: i64) -> Result<Vec<GitHubTimelineEvent>, String> {
+            Ok(serde_json::from_str(self.timeline).unwrap())
+        }
+
+        async fn fetch_pull_request(&self, _owner: &str, _repo: &str, number: i64) -> Result<GitHubPRData, String> {
+            Ok(GitHubPRData {
+                id: 500 + number,
+                title: "feat(ui): dark mode".to_string(),
+                user: user(),
+                head: GitHubHead { ref_field: "dark-mode".to_string(), sha: String::new() },
+                state: "open".to_string(),
+                created_at: None,
+                updated_at: None,
+                merged: false,
+                merged_at: None,
+                merge_commit_sha: None,
+                reactions: None,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_issue_converts_when_pr_is_linked() {
+        let db = Database::new_in_memory().await.unwrap();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+
+        let unlinked = MockClient { issue_is_pr: false, timeline: "[]" };
+        let issue = add_issue(&db, &unlinked, "acme", "web", 7, project.id).await.unwrap();
+        assert!(issue.is_issue);
+        assert_eq!((issue.github_id, issue.pr_number), (-9007, 7));
+        assert!(add_issue(&db, &unlinked, "acme", "web", 7, project.id).await.is_err());
+        assert!(db.get_merge_candidates().await.unwrap().is_empty());
+
+        let refreshed = refresh_issue(&db, &unlinked, issue.id).await.unwrap();
+        assert!(!refreshed.converted);
+        assert!(refreshed.pull_request.is_issue);
+
+        let linked = MockClient {
+            issue_is_pr: false,
+            timeline: include_str!("../../tests/fixtures/github/issue_timeline.json"),
+        };
+        let refreshed = refresh_issue(&db, &linked, issue.id).await.unwrap();
+        assert!(refreshed.converted);
+        let pr = refreshed.pull_request;
+        assert!(!pr.is_issue);
+        assert_eq!((pr.id, pr.github_id, pr.pr_number), (issue.id, 1850, 1350));
+        assert_eq!(pr.repository_name.as_deref(), Some("Hello-World"));
+        assert_eq!(pr.commit_type.as_deref(), Some("feat"));
+        assert_eq!(pr.project_id, Some(project.id));
+
+        // Already a PR: nothing more to do
+        assert!(!refresh_issue(&db, &linked, issue.id).await.unwrap().converted);
+    }
+
+    #[tokio::test]
+    async fn test_issue_converted_in_place_and_pr_numbers_rejected() {
+        let db = Database::new_in_memory().await.unwrap();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        let converted_in_place = MockClient { issue_is_pr: true, timeline: "[]" };
+        assert!(add_issue(&db, &converted_in_place, "acme", "web", 3, project.id).await.unwrap_err().contains("is a pull request"));
+
+        let issue = add_issue(&db, &MockClient { issue_is_pr: false, timeline: "[]" }, "acme", "web", 3, project.id).await.unwrap();
+        let refreshed = refresh_issue(&db, &converted_in_place, issue.id).await.unwrap();
+        assert!(refreshed.converted);
+        assert_eq!((refreshed.pull_request.pr_number, refreshed.pull_request.branch.as_deref()), (3, Some("dark-mode")));
+    }
+}
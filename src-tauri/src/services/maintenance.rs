@@ -0,0 +1,32 @@
+// Periodic housekeeping: the once-a-day statistics snapshot and pruning old sync runs
+use std::time::Duration;
+
+use crate::database::Database;
+use crate::display_time::local_date;
+use crate::DbState;
+
+// Hourly is plenty to catch each day once; snapshot_daily_stats ignores repeats
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Take today's statistics snapshot (in the display time zone) if it hasn't been taken yet,
+/// and drop sync runs past their retention
+pub async fn run_daily_tasks(db: &Database, now: i64) -> Result<(), String> {
+    let tz = db.get_display_timezone().await.map_err(|e| e.to_string())?;
+    db.snapshot_daily_stats(local_date(now, tz), now).await.map_err(|e| e.to_string())?;
+    db.prune_sync_runs(now).await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Background loop spawned at startup
+pub async fn run_maintenance(state: DbState) {
+    let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let db_state = state.lock().await;
+        let Some(db) = db_state.as_ref() else { continue };
+        if let Err(e) = run_daily_tasks(db, chrono::Utc::now().timestamp()).await {
+            println!("❌ Maintenance failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+// Business logic shared by commands, kept free of Tauri types
+pub mod api_usage;
+pub mod avatars;
+pub mod db_state;
+pub mod deployments;
+pub mod issues;
+pub mod maintenance;
+pub mod onboarding;
+pub mod power;
+pub mod pr_files;
+pub mod pr_list_cache;
+pub mod pr_summary;
+pub mod privacy;
+pub mod pull_requests;
+pub mod rate_limit;
+pub mod reimport;
+pub mod releases;
+pub mod repositories;
+pub mod retry;
+pub mod review_comments;
+pub mod scheduler;
+pub mod sla;
+pub mod sync_all;
+pub mod sync_runs;
+pub mod token_expiry;
+pub mod token_permissions;
+pub mod tray;
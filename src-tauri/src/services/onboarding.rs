@@ -0,0 +1,65 @@
+// First-run experience: which setup step the user is on, from local state only. Token
+// validity is whatever the last check found; the screen offers "Test connection" to refresh it.
+use serde::Serialize;
+
+use crate::database::onboarding::TokenCheck;
+use crate::database::Database;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    AddToken,
+    CreateProject,
+    AddPullRequests,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OnboardingState {
+    pub has_token: bool,
+    pub keychain_access_denied: bool,    // a token may be stored, but the keychain refused to say
+    pub token_valid_cached: Option<bool>, // None until a token has been checked
+    pub token_checked_at: Option<i64>,
+    pub project_count: i64,
+    pub pr_count: i64,
+    pub tracked_repo_count: i64,
+    pub has_completed_onboarding: bool,
+    pub next_step: OnboardingStep,
+}
+
+/// First step still missing; a denied keychain isn't sent back to "add a token"
+pub fn next_step(has_token: bool, keychain_access_denied: bool, project_count: i64, pr_count: i64, completed: bool) -> OnboardingStep {
+    if completed {
+        OnboardingStep::Done
+    } else if !has_token && !keychain_access_denied {
+        OnboardingStep::AddToken
+    } else if project_count == 0 {
+        OnboardingStep::CreateProject
+    } else if pr_count == 0 {
+        OnboardingStep::AddPullRequests
+    } else {
+        OnboardingStep::Done
+    }
+}
+
+pub async fn get_onboarding_state(db: &Database, has_token: bool, keychain_access_denied: bool) -> Result<OnboardingState, String> {
+    let counts = db.get_onboarding_counts().await.map_err(|e| e.to_string())?;
+    let completed = db.is_onboarding_complete().await.map_err(|e| e.to_string())?;
+    // A check from before the token was removed says nothing about the next one
+    let check: Option<TokenCheck> = match has_token || keychain_access_denied {
+        true => db.get_last_token_check().await.map_err(|e| e.to_string())?,
+        false => None,
+    };
+
+    Ok(OnboardingState {
+        has_token,
+        keychain_access_denied,
+        token_valid_cached: check.as_ref().map(|check| check.valid),
+        token_checked_at: check.as_ref().map(|check| check.checked_at),
+        project_count: counts.project_count,
+        pr_count: counts.pr_count,
+        tracked_repo_count: counts.tracked_repo_count,
+        has_completed_onboarding: completed,
+        next_step: next_step(has_token, keychain_access_denied, counts.project_count, counts.pr_count, completed),
+    })
+}
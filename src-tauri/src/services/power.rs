@@ -0,0 +1,134 @@
+// Whether the machine is on battery or a metered connection, for the sync constraints
+// (database/sync_constraints.rs). The frontend can report both through report_power_state;
+// on macOS the battery state is also read from pmset. Anything not known stays None and
+// the constraints that depend on it don't apply.
+use std::sync::{LazyLock, Mutex};
+
+use serde::Serialize;
+
+use crate::database::sync_constraints::SyncConstraints;
+
+// A report older than this is ignored, so a closed window can't pause syncs for good
+const REPORT_TTL_SECS: i64 = 15 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Reported, // report_power_state from the frontend
+    Detected, // read from the OS
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PowerState {
+    pub on_battery: Option<bool>,
+    pub metered: Option<bool>,
+    pub source: PowerSource,
+}
+
+impl PowerState {
+    pub const UNKNOWN: PowerState = PowerState { on_battery: None, metered: None, source: PowerSource::Unknown };
+}
+
+// Process-wide, like the keychain backoff: the scheduler reads what the window reported
+static REPORTED: LazyLock<Mutex<Option<(PowerState, i64)>>> = LazyLock::new(|| Mutex::new(None));
+
+pub fn report_power_state(on_battery: bool, metered: bool, now: i64) -> PowerState {
+    let state = PowerState { on_battery: Some(on_battery), metered: Some(metered), source: PowerSource::Reported };
+    *REPORTED.lock().unwrap() = Some((state, now));
+    state
+}
+
+/// A fresh frontend report wins; otherwise whatever the OS tells us, which may be nothing
+pub async fn current_power_state(now: i64) -> PowerState {
+    let reported = *REPORTED.lock().unwrap();
+    if let Some((state, reported_at)) = reported {
+        if now - reported_at <= REPORT_TTL_SECS {
+            return state;
+        }
+    }
+    match detect_on_battery().await {
+        Some(on_battery) => PowerState { on_battery: Some(on_battery), metered: None, source: PowerSource::Detected },
+        None => PowerState::UNKNOWN,
+    }
+}
+
+#[cfg(target_os = "macos")]
+async fn detect_on_battery() -> Option<bool> {
+    let output = tokio::process::Command::new("pmset").args(["-g", "batt"]).output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_pmset_source(&String::from_utf8_lossy(&output.stdout))
+}
+
+// No detection elsewhere yet; the frontend hint is all there is
+#[cfg(not(target_os = "macos"))]
+async fn detect_on_battery() -> Option<bool> {
+    None
+}
+
+/// On battery per `pmset -g batt`, whose first line reads "Now drawing from 'Battery Power'"
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset_source(output: &str) -> Option<bool> {
+    let line = output.lines().find(|line| line.contains("drawing from"))?;
+    if line.contains("'AC Power'") {
+        Some(false)
+    } else if line.contains("'Battery Power'") || line.contains("'UPS Power'") {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Why a cycle due at `now` should wait, or None to run it. `last_run_at` is the last
+/// cycle that actually ran; unknown power data never holds a cycle back.
+pub fn skip_reason(constraints: &SyncConstraints, power: &PowerState, last_run_at: Option<i64>, now: i64) -> Option<String> {
+    if constraints.pause_on_metered && power.metered == Some(true) {
+        return Some("Paused on a metered connection".to_string());
+    }
+    if power.on_battery != Some(true) {
+        return None;
+    }
+    if constraints.pause_on_battery {
+        return Some("Paused on battery power".to_string());
+    }
+    let minutes = constraints.min_interval_on_battery?;
+    let next_run_at = last_run_at? + i64::from(minutes) * 60;
+    (now < next_run_at).then(|| format!("On battery power: runs at most every {} minutes", minutes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn power(on_battery: Option<bool>, metered: Option<bool>) -> PowerState {
+        PowerState { on_battery, metered, source: PowerSource::Reported }
+    }
+
+    #[test]
+    fn test_skip_reason_only_applies_with_power_data() {
+        let everything = SyncConstraints { pause_on_battery: true, pause_on_metered: true, min_interval_on_battery: None };
+        assert_eq!(skip_reason(&everything, &PowerState::UNKNOWN, None, 0), None);
+        assert_eq!(skip_reason(&everything, &power(Some(false), Some(false)), None, 0), None);
+        assert_eq!(skip_reason(&everything, &power(None, Some(true)), None, 0).as_deref(), Some("Paused on a metered connection"));
+        assert_eq!(skip_reason(&everything, &power(Some(true), None), None, 0).as_deref(), Some("Paused on battery power"));
+        assert_eq!(skip_reason(&SyncConstraints::default(), &power(Some(true), Some(true)), None, 0), None);
+
+        // On battery with a minimum interval: the first run goes, the next waits 30 minutes
+        let throttled = SyncConstraints { min_interval_on_battery: Some(30), ..Default::default() };
+        let battery = power(Some(true), None);
+        assert_eq!(skip_reason(&throttled, &battery, None, 1_000), None);
+        assert!(skip_reason(&throttled, &battery, Some(1_000), 1_000 + 29 * 60).is_some());
+        assert_eq!(skip_reason(&throttled, &battery, Some(1_000), 1_000 + 30 * 60), None);
+        assert_eq!(skip_reason(&throttled, &power(Some(false), None), Some(1_000), 1_060), None);
+    }
+
+    #[test]
+    fn test_parse_pmset_source() {
+        let battery = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t85%; discharging; 4:12 remaining present: true\n";
+        assert_eq!(parse_pmset_source(battery), Some(true));
+        assert_eq!(parse_pmset_source("Now drawing from 'AC Power'\n"), Some(false));
+        assert_eq!(parse_pmset_source(""), None);
+    }
+}
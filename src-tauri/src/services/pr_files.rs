@@ -0,0 +1,239 @@
+// "Files changed" summary for a PR, cached in memory per head SHA
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::bounded_cache::{json_size, BoundedCache, CacheName, EstimateSize};
+use crate::database::Database;
+use crate::github::api_usage::ApiFeature;
+use crate::github::models::GitHubPRFile;
+use crate::github::{fetch_pr_files, fetch_pr_head_sha};
+
+// GitHub allows up to 3000 files; the summary only needs the first few pages
+pub const MAX_PR_FILES: usize = 300;
+const TOP_DIRECTORIES: usize = 5;
+
+/// Summaries by PR id, reused until the PR's head SHA changes
+pub type PrFilesCache = Arc<Mutex<BoundedCache<i64, PrFilesSummary>>>;
+
+pub fn new_cache() -> PrFilesCache {
+    Arc::new(Mutex::new(BoundedCache::new(CacheName::PrFiles)))
+}
+
+/// GitHub calls needed for the files summary (mocked in tests)
+pub trait PrFilesClient {
+    fn fetch_head_sha(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<String, String>> + Send;
+    fn fetch_files(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<(Vec<GitHubPRFile>, bool), String>> + Send;
+}
+
+pub struct GitHubPrFilesClient {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl PrFilesClient for GitHubPrFilesClient {
+    async fn fetch_head_sha(&self, owner: &str, repo: &str, number: i64) -> Result<String, String> {
+        fetch_pr_head_sha(self.feature, &self.token, owner, repo, number).await
+    }
+
+    async fn fetch_files(&self, owner: &str, repo: &str, number: i64) -> Result<(Vec<GitHubPRFile>, bool), String> {
+        fetch_pr_files(self.feature, &self.token, owner, repo, number, MAX_PR_FILES).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PrFileChange {
+    pub filename: String,
+    pub previous_filename: Option<String>,
+    pub status: String,
+    pub additions: i64,
+    pub deletions: i64,
+    pub binary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DirectoryChurn {
+    pub directory: String, // "." for files at the repository root
+    pub files: u32,
+    pub additions: i64,
+    pub deletions: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrFilesSummary {
+    pub pr_id: i64,
+    pub head_sha: String,
+    pub files: Vec<PrFileChange>,
+    pub truncated: bool, // more than MAX_PR_FILES files changed
+    pub total_additions: i64,
+    pub total_deletions: i64,
+    pub top_directories: Vec<DirectoryChurn>,
+}
+
+impl EstimateSize for PrFilesSummary {
+    fn estimated_bytes(&self) -> usize {
+        json_size(self)
+    }
+}
+
+fn parent_directory(filename: &str) -> &str {
+    filename.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(".")
+}
+
+/// Top directories by additions + deletions; renamed files count under their new path
+fn directory_rollup(files: &[PrFileChange]) -> Vec<DirectoryChurn> {
+    let mut by_directory: BTreeMap<&str, DirectoryChurn> = BTreeMap::new();
+    for file in files {
+        let directory = parent_directory(&file.filename);
+        let entry = by_directory.entry(directory).or_insert_with(|| DirectoryChurn {
+            directory: directory.to_string(),
+            files: 0,
+            additions: 0,
+            deletions: 0,
+        });
+        entry.files += 1;
+        entry.additions += file.additions;
+        entry.deletions += file.deletions;
+    }
+
+    let mut rollup: Vec<DirectoryChurn> = by_directory.into_values().collect();
+    // Stable sort keeps ties in directory-name order
+    rollup.sort_by_key(|d| std::cmp::Reverse(d.additions + d.deletions));
+    rollup.truncate(TOP_DIRECTORIES);
+    rollup
+}
+
+fn summarize(pr_id: i64, head_sha: String, files: Vec<GitHubPRFile>, truncated: bool) -> PrFilesSummary {
+    let files: Vec<PrFileChange> = files.into_iter()
+        .map(|file| PrFileChange {
+            binary: file.is_binary(),
+            filename: file.filename,
+            previous_filename: file.previous_filename,
+            status: file.status,
+            additions: file.additions,
+            deletions: file.deletions,
+        })
+        .collect();
+
+    PrFilesSummary {
+        pr_id,
+        head_sha,
+        truncated,
+        total_additions: files.iter().map(|f| f.additions).sum(),
+        total_deletions: files.iter().map(|f| f.deletions).sum(),
+        top_directories: directory_rollup(&files),
+        files,
+    }
+}
+
+/// Files changed in a PR; only re-fetches the file list when the head SHA moved
+pub async fn get_pr_files<C: PrFilesClient>(db: &Database, client: &C, cache: &PrFilesCache, pr_id: i64) -> Result<PrFilesSummary, String> {
+    let pr = db.get_pull_request_by_id(pr_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} not found", pr_id))?;
+    if pr.is_issue {
+        return Err(format!("Issue #{} has no changed files until a PR is linked", pr.pr_number));
+    }
+    let (Some(owner), Some(repo)) = (pr.repository_owner.as_deref(), pr.repository_name.as_deref()) else {
+        return Err("Repository fields missing; run repair_missing_repo_fields first".to_string());
+    };
+
+    let head_sha = client.fetch_head_sha(owner, repo, pr.pr_number).await?;
+    if let Some(cached) = cache.lock().await.get_if(&pr_id, |s| s.head_sha == head_sha) {
+        return Ok(cached.clone());
+    }
+
+    let (files, truncated) = client.fetch_files(owner, repo, pr.pr_number).await?;
+    println!("📂 Fetched {} changed files for PR #{} at {}", files.len(), pr.pr_number, head_sha);
+    let summary = summarize(pr_id, head_sha, files, truncated);
+    cache.lock().await.insert(pr_id, summary.clone());
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct MockClient {
+        head_sha: std::sync::Mutex<String>,
+        file_fetches: AtomicU32,
+    }
+
+    fn file(filename: &str, status: &str, additions: i64, deletions: i64, patch: bool) -> GitHubPRFile {
+        GitHubPRFile {
+            filename: filename.to_string(),
+            status: status.to_string(),
+            additions,
+            deletions,
+            changes: additions + deletions,
+            previous_filename: (status == "renamed").then(|| "old/name.rs".to_string()),
+            patch: patch.then(|| "@@ -1 +1 @@".to_string()),
+        }
+    }
+
+    impl PrFilesClient for MockClient {
+        async fn fetch_head_sha(&self, _owner: &str, _repo: &str, _number: i64) -> Result<String, String> {
+            Ok(self.head_sha.lock().unwrap().clone())
+        }
+
+        async fn fetch_files(&self, _owner: &str, _repo: &str, _number: i64) -> Result<(Vec<GitHubPRFile>, bool), String> {
+            self.file_fetches.fetch_add(1, Ordering::SeqCst);
+            Ok((vec![
+                file("src/api/handler.rs", "modified", 40, 10, true),
+                file("src/api/routes.rs", "added", 20, 0, true),
+                file("docs/logo.png", "added", 0, 0, false),
+                file("src/lib/name.rs", "renamed", 0, 0, false),
+                file("README.md", "modified", 3, 1, true),
+            ], true))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_files_summary_and_head_sha_cache() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 12,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+
+        let client = MockClient { head_sha: std::sync::Mutex::new("abc".to_string()), file_fetches: AtomicU32::new(0) };
+        let cache = new_cache();
+
+        let summary = get_pr_files(&db, &client, &cache, pr.id).await.unwrap();
+        assert!(summary.truncated);
+        assert_eq!((summary.total_additions, summary.total_deletions), (63, 11));
+        let binary: Vec<&str> = summary.files.iter().filter(|f| f.binary).map(|f| f.filename.as_str()).collect();
+        assert_eq!(binary, vec!["docs/logo.png"]);
+        let renamed = summary.files.iter().find(|f| f.status == "renamed").unwrap();
+        assert_eq!(renamed.previous_filename.as_deref(), Some("old/name.rs"));
+
+        let directories: Vec<(&str, u32, i64)> = summary.top_directories.iter()
+            .map(|d| (d.directory.as_str(), d.files, d.additions + d.deletions))
+            .collect();
+        assert_eq!(directories, vec![("src/api", 2, 70), (".", 1, 4), ("docs", 1, 0), ("src/lib", 1, 0)]);
+
+        // Same head SHA: served from the cache
+        get_pr_files(&db, &client, &cache, pr.id).await.unwrap();
+        assert_eq!(client.file_fetches.load(Ordering::SeqCst), 1);
+
+        // New commits: fetched again
+        *client.head_sha.lock().unwrap() = "def".to_string();
+        let summary = get_pr_files(&db, &client, &cache, pr.id).await.unwrap();
+        assert_eq!(summary.head_sha, "def");
+        assert_eq!(client.file_fetches.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,164 @@
+// Warm copy of the default PR list so the first paint after launch doesn't wait on SQLite
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use crate::bounded_cache::{self, json_size, BoundedCache, CacheName, EstimateSize};
+use crate::database::{Database, PullRequest};
+use crate::DbState;
+
+pub const PR_LIST_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// The last default-sorted PR list, tagged with the data generation it was read at
+pub type PrListCache = Arc<Mutex<BoundedCache<(), CachedPrList>>>;
+
+pub fn new_cache() -> PrListCache {
+    Arc::new(Mutex::new(BoundedCache::new(CacheName::PrList)))
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedPrList {
+    generation: i64,
+    cached_at: Instant,
+    pull_requests: Vec<PullRequest>,
+}
+
+impl CachedPrList {
+    // Any write behind the list bumps the generation, so a match means nothing changed
+    fn is_fresh(&self, generation: i64, now: Instant) -> bool {
+        self.generation == generation && now.duration_since(self.cached_at) < PR_LIST_CACHE_TTL
+    }
+}
+
+impl EstimateSize for CachedPrList {
+    fn estimated_bytes(&self) -> usize {
+        json_size(&self.pull_requests)
+    }
+}
+
+// The generation is read before the query, so a write racing the query leaves the entry stale
+async fn load(db: &Database, cache: &PrListCache) -> Result<Vec<PullRequest>, String> {
+    let generation = db.get_data_generation().await.map_err(|e| e.to_string())?;
+    let pull_requests = db.get_pull_requests_sorted(None).await.map_err(|e| e.to_string())?;
+    cache.lock().await.insert((), CachedPrList { generation, cached_at: Instant::now(), pull_requests: pull_requests.clone() });
+    Ok(pull_requests)
+}
+
+/// PR list for `get_pull_requests`; only the default sort is cached
+pub async fn get_pull_requests(db: &Database, cache: &PrListCache, sort_by: Option<&str>) -> Result<Vec<PullRequest>, String> {
+    if sort_by.is_some() {
+        return db.get_pull_requests_sorted(sort_by).await.map_err(|e| e.to_string());
+    }
+
+    let generation = db.get_data_generation().await.map_err(|e| e.to_string())?;
+    if let Some(cached) = cache.lock().await.get_if(&(), |c| c.is_fresh(generation, Instant::now())) {
+        return Ok(cached.pull_requests.clone());
+    }
+    load(db, cache).await
+}
+
+/// Apply the saved cache_limits; called at startup and whenever settings change
+pub async fn reload_cache_limits(db: &Database) {
+    match db.get_cache_limits().await {
+        Ok(limits) => bounded_cache::set_limits(limits),
+        Err(e) => println!("⚠️ Could not load cache limits: {}", e),
+    }
+}
+
+/// Startup task: open the database if the frontend hasn't yet, then fill the cache
+pub async fn open_and_warm(db_state: DbState, cache: PrListCache) {
+    let mut db_state = db_state.lock().await;
+    if db_state.is_none() {
+        match Database::new().await {
+            Ok(db) => *db_state = Some(db),
+            // init_database will retry and report the error to the UI
+            Err(e) => {
+                println!("⚠️ Could not open database during startup: {}", e);
+                return;
+            }
+        }
+    }
+
+    if let Some(db) = db_state.as_ref() {
+        reload_cache_limits(db).await;
+        match load(db, &cache).await {
+            Ok(pull_requests) => println!("🔥 Warmed PR list cache with {} PRs", pull_requests.len()),
+            Err(e) => println!("⚠️ Could not warm PR list cache: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+
+    async fn titles(db: &Database, cache: &PrListCache) -> Vec<String> {
+        get_pull_requests(db, cache, None).await.unwrap().into_iter().filter_map(|pr| pr.title).collect()
+    }
+
+    #[tokio::test]
+    async fn test_every_mutation_invalidates_the_cache() {
+        let db = Database::new_in_memory().await.unwrap();
+        let cache = new_cache();
+        let project = db.add_project("Board".to_string(), None).await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            title: Some("First".to_string()),
+            author_id: author.id,
+            project_id: Some(project.id),
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap();
+
+        assert_eq!(titles(&db, &cache).await, vec!["First"]);
+        assert_eq!(cache.lock().await.stats().entries, 1);
+
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 2,
+            pr_number: 2,
+            title: Some("Second".to_string()),
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(titles(&db, &cache).await.len(), 2);
+
+        db.update_pr_status(&WriteContext::default(), pr.id, "Approved".to_string()).await.unwrap();
+        let list = get_pull_requests(&db, &cache, None).await.unwrap();
+        assert_eq!(list.iter().find(|p| p.id == pr.id).unwrap().status, "Approved");
+
+        db.update_project(project.id, "Renamed".to_string(), None, None, None).await.unwrap();
+        let list = get_pull_requests(&db, &cache, None).await.unwrap();
+        assert_eq!(list.iter().find(|p| p.id == pr.id).unwrap().project_name.as_deref(), Some("Renamed"));
+
+        db.set_pr_status_pinned(&WriteContext::default(), pr.id, true).await.unwrap();
+        let list = get_pull_requests(&db, &cache, None).await.unwrap();
+        assert!(list.iter().find(|p| p.id == pr.id).unwrap().status_pinned);
+
+        // Switching profile changes the per-profile columns
+        db.switch_local_profile("work").await.unwrap();
+        let list = get_pull_requests(&db, &cache, None).await.unwrap();
+        assert!(!list.iter().find(|p| p.id == pr.id).unwrap().status_pinned);
+
+        // Raw SQL (as clear_all_data uses) is caught too
+        sqlx::query("DELETE FROM pr_profile_state").execute(&db.pool).await.unwrap();
+        sqlx::query("DELETE FROM review_history").execute(&db.pool).await.unwrap();
+        sqlx::query("DELETE FROM pull_requests").execute(&db.pool).await.unwrap();
+        assert!(titles(&db, &cache).await.is_empty());
+    }
+
+    #[test]
+    fn test_cache_expires_after_ttl() {
+        let cached_at = Instant::now();
+        let entry = CachedPrList { generation: 3, cached_at, pull_requests: Vec::new() };
+        assert!(entry.is_fresh(3, cached_at + Duration::from_secs(29)));
+        assert!(!entry.is_fresh(3, cached_at + PR_LIST_CACHE_TTL));
+        assert!(!entry.is_fresh(4, cached_at));
+    }
+}
@@ -0,0 +1,239 @@
+// One-pager about a PR for meeting notes: a short Markdown card built from the same data
+// as get_pr_detail. Every value coming from GitHub or the user goes through the sanitize
+// helpers, and a section with nothing to show is left out rather than rendered empty.
+// Size, diff stats and labels aren't stored locally, so the card has no place for them yet.
+use chrono_tz::Tz;
+
+use crate::database::lifecycle::{HistoryEntry, LifecycleEvent};
+use crate::database::{Database, PrComment, PrDetail, PrReviewer};
+use crate::display_time::format_timestamp;
+use crate::sanitize::{markdown_cell, REPORT_CELL_MAX_CHARS};
+use crate::services::privacy::{redact_display_key, Privacy};
+
+// Notes are longer than table cells but still kept to a paragraph each
+const NOTE_MAX_CHARS: usize = 500;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PrSummaryOptions {
+    pub include_local_notes: bool, // off for cards shared outside the team
+}
+
+/// Everything the card shows, already redacted when privacy mode is on
+pub struct PrSummaryData {
+    pub detail: PrDetail,
+    pub reviewers: Vec<PrReviewer>,
+    pub history: Vec<HistoryEntry>,
+    pub notes: Vec<PrComment>,
+    pub link_to_github: bool, // false while the owner is a placeholder
+}
+
+fn text(value: &str) -> String {
+    markdown_cell(value, REPORT_CELL_MAX_CHARS)
+}
+
+fn person(login: Option<&str>, display_name: Option<&str>) -> Option<String> {
+    match (display_name.filter(|name| !name.is_empty()), login.filter(|login| !login.is_empty())) {
+        (Some(name), Some(login)) if name != login => Some(format!("{} (@{})", text(name), text(login))),
+        (Some(name), _) => Some(text(name)),
+        (None, Some(login)) => Some(format!("@{}", text(login))),
+        (None, None) => None,
+    }
+}
+
+/// "3d 4h", "5h 12m" or "7m"
+fn format_span(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    let (days, hours, minutes) = (minutes / 1_440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+/// When the PR entered its current status, if a lifecycle event or a logged column move
+/// says so. Moves from before status changes were logged have no answer.
+fn status_since(history: &[HistoryEntry], status: &str) -> Option<i64> {
+    let latest = history.iter().rev().find(|entry| {
+        entry.action == LifecycleEvent::Merged.action()
+            || entry.action == LifecycleEvent::Closed.action()
+            || entry.action == LifecycleEvent::Archived.action()
+            || entry.action == LifecycleEvent::Restored.action()
+            || entry.action.starts_with("status_changed:")
+    })?;
+    let entered = match latest.action.as_str() {
+        "merged" => "Merged",
+        "closed" | "archived" => "archived",
+        "restored" => latest.details.as_deref()?,
+        moved => moved.rsplit_once("->")?.1,
+    };
+    (entered == status).then_some(latest.performed_at)
+}
+
+pub fn render_pr_summary(data: &PrSummaryData, options: PrSummaryOptions, tz: Tz, now: i64) -> String {
+    let pr = &data.detail.pull_request;
+    let heading = text(pr.title.as_deref().filter(|title| !title.trim().is_empty()).unwrap_or(&pr.display_key));
+    let heading = match (pr.repository_owner.as_deref(), pr.repository_name.as_deref()) {
+        (Some(owner), Some(repo)) if data.link_to_github && !pr.is_issue => {
+            format!("[{}](https://github.com/{}/{}/pull/{})", heading, owner, repo, pr.pr_number)
+        }
+        _ => heading,
+    };
+    let mut card = format!("### {} · {}\n\n", heading, text(&pr.display_key));
+
+    let mut lines = Vec::new();
+    if let Some(author) = person(pr.author_name.as_deref(), pr.author_display_name.as_deref()) {
+        lines.push(format!("**Author:** {}", author));
+    }
+    let mut state = vec![format!("**Status:** {}", text(pr.status.as_str()))];
+    if let Some(score) = pr.score {
+        state.push(format!("**Score:** {}", score));
+    }
+    state.push(format!("**Priority:** {}", pr.priority));
+    lines.push(state.join(" · "));
+
+    let mut timing = Vec::new();
+    if let Some(created_at) = pr.github_created_at {
+        timing.push(format!("**Age:** {}", format_span(now - created_at)));
+    }
+    if let Some(since) = status_since(&data.history, pr.status.as_str()) {
+        timing.push(format!("**In {} for:** {}", text(pr.status.as_str()), format_span(now - since)));
+    }
+    if !timing.is_empty() {
+        lines.push(timing.join(" · "));
+    }
+
+    let reviewers: Vec<String> = data.reviewers.iter()
+        .filter_map(|reviewer| {
+            let name = person(reviewer.github_username.as_deref(), reviewer.display_name.as_deref())?;
+            Some(format!("{} (reviewed {})", name, format_timestamp(reviewer.reviewed_at, tz)))
+        })
+        .collect();
+    if !reviewers.is_empty() {
+        lines.push(format!("**Reviewers:** {}", reviewers.join(", ")));
+    }
+
+    let issues: Vec<String> = data.history.iter()
+        .filter(|entry| entry.action == LifecycleEvent::ConvertedToPr.action())
+        .filter_map(|entry| entry.details.as_deref())
+        .map(text)
+        .collect();
+    if !issues.is_empty() {
+        lines.push(format!("**Linked issues:** {}", issues.join(", ")));
+    }
+    let links: Vec<String> = data.detail.external_links.iter()
+        .map(|link| format!("[{}](<{}>)", text(&link.label), link.url.replace(['<', '>', ' '], "")))
+        .collect();
+    if !links.is_empty() {
+        lines.push(format!("**Links:** {}", links.join(", ")));
+    }
+    for line in lines {
+        card.push_str(&format!("- {}\n", line));
+    }
+
+    if options.include_local_notes && !data.notes.is_empty() {
+        card.push_str("\n**Notes**\n\n");
+        for note in &data.notes {
+            let author = person(note.author_name.as_deref(), note.author_display_name.as_deref());
+            let when = format_timestamp(note.created_at, tz);
+            let header = match author {
+                Some(author) => format!("{}, {}", when, author),
+                None => when,
+            };
+            card.push_str(&format!("- {}: {}\n", header, markdown_cell(&note.body, NOTE_MAX_CHARS)));
+        }
+    }
+    card
+}
+
+/// Gather the card's data for one PR, applying privacy mode, and render it
+pub async fn generate_pr_summary_markdown(
+    db: &Database,
+    privacy: &Privacy,
+    pr_id: i64,
+    options: PrSummaryOptions,
+    now: i64,
+) -> Result<String, String> {
+    let detail = db.get_pr_detail(pr_id).await.map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} not found", pr_id))?;
+    let reviewers = db.get_pr_reviewers(pr_id).await.map_err(|e| e.to_string())?;
+    let mut history = db.get_pr_history(pr_id).await.map_err(|e| e.to_string())?;
+    let notes = if options.include_local_notes {
+        db.get_pr_comments(pr_id).await.map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+    let tz = db.get_display_timezone().await.map_err(|e| e.to_string())?;
+
+    if privacy.is_enabled() {
+        for entry in history.iter_mut().filter(|entry| entry.action == LifecycleEvent::ConvertedToPr.action()) {
+            if let Some(details) = entry.details.as_mut() {
+                redact_display_key(details);
+            }
+        }
+    }
+    let data = PrSummaryData {
+        detail: privacy.redact(detail),
+        reviewers: privacy.redact(reviewers),
+        history,
+        notes: privacy.redact(notes),
+        link_to_github: !privacy.is_enabled(),
+    };
+    Ok(render_pr_summary(&data, options, tz, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+
+    #[tokio::test]
+    async fn test_summary_card_skips_missing_sections_and_can_omit_notes() {
+        let db = Database::new_in_memory().await.unwrap();
+        db.set_setting(crate::display_time::DISPLAY_TIMEZONE_KEY, "UTC").await.unwrap();
+        let author = db.add_team_member("octocat", None, Some("The Octocat")).await.unwrap();
+        let reviewer = db.add_team_member("hubot", None, None).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 512,
+            pr_number: 512,
+            title: Some("fix: handle | pipes <b>and</b> tags".to_string()),
+            author_id: author.id,
+            status: PrStatus::Reviewing,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            github_created_at: Some(0),
+            ..Default::default()
+        }).await.unwrap();
+        let now = 3 * 86_400 + 2 * 3_600;
+
+        let options = PrSummaryOptions { include_local_notes: true };
+        let bare = generate_pr_summary_markdown(&db, &Privacy::default(), pr.id, options, now).await.unwrap();
+        assert!(bare.starts_with("### [fix: handle \\| pipes &lt;b&gt;and&lt;/b&gt; tags](https://github.com/acme/api/pull/512)"));
+        assert!(bare.contains("- **Author:** The Octocat (@octocat)\n"));
+        assert!(bare.contains("- **Status:** Reviewing · **Priority:** Medium\n"));
+        assert!(bare.contains("- **Age:** 3d 2h\n"));
+        for missing in ["Score", "Reviewers", "Links", "Notes", "null", "None"] {
+            assert!(!bare.contains(missing), "{} in {}", missing, bare);
+        }
+
+        db.add_pr_reviewer(pr.id, reviewer.id, 86_400).await.unwrap();
+        db.add_pr_external_link(pr.id, "Design doc", "https://docs.example/design").await.unwrap();
+        db.add_pr_comment(&WriteContext::default(), pr.id, author.id, "Check the\nmigration first".to_string()).await.unwrap();
+        let full = generate_pr_summary_markdown(&db, &Privacy::default(), pr.id, options, now).await.unwrap();
+        assert!(full.contains("- **Reviewers:** @hubot (reviewed 1970-01-02 00:00 +00:00)\n"));
+        assert!(full.contains("- **Links:** [Design doc](<https://docs.example/design>)\n"));
+        assert!(full.contains("The Octocat (@octocat): Check the migration first\n"));
+
+        let shared = generate_pr_summary_markdown(&db, &Privacy::default(), pr.id, PrSummaryOptions { include_local_notes: false }, now)
+            .await
+            .unwrap();
+        assert!(!shared.contains("Notes") && !shared.contains("migration"));
+
+        let privacy = Privacy::default();
+        privacy.set_enabled(true);
+        let redacted = generate_pr_summary_markdown(&db, &privacy, pr.id, options, now).await.unwrap();
+        assert!(!redacted.contains("octocat") && !redacted.contains("github.com"));
+    }
+}
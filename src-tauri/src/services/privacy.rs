@@ -0,0 +1,516 @@
+// Privacy mode for screenshots: read commands swap author identities for pseudonyms
+// ("Reviewer A"), drop avatars and show repository owners as "org". Only the values
+// leaving a command change; the database is never touched, so switching the mode off
+// shows real data again straight away. Exports refuse to run while it's on.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::database::author_backlog::AuthorBacklogAlert;
+use crate::database::lifecycle::HistoryEntry;
+use crate::database::repo_mappings::ProjectMappingConflict;
+use crate::database::search::SearchHit;
+use crate::database::sync_conflicts::SyncConflict;
+use crate::database::sync_runs::{SyncChange, SyncRun};
+use crate::database::{AuthorScorecard, BoardData, MisfiledPr, PrComment, PrDetail, PrReviewer, PullRequest, PullRequestWithAge, ReviewHistory, TeamMember, TrackedRepository};
+use crate::github::GitHubTokenInfo;
+use crate::services::issues::IssueRefreshResult;
+use crate::services::pull_requests::{AddPrResult, BranchAddResult, BranchPrCandidate, QuickAddResult};
+use crate::services::repositories::RepoAccessReport;
+use crate::services::sla::{ProjectSlaReport, SlaPrEntry};
+
+pub const OWNER_PLACEHOLDER: &str = "org";
+
+#[derive(Debug, Default)]
+pub struct Privacy {
+    enabled: AtomicBool,
+    // lowercased login -> pseudonym, first come first served; kept for the whole session
+    // so a member keeps their letter when the mode is switched off and on again
+    pseudonyms: Mutex<HashMap<String, String>>,
+}
+
+pub type PrivacyState = Arc<Privacy>;
+
+impl Privacy {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        println!("🕶️ Privacy mode {}", if enabled { "on" } else { "off" });
+    }
+
+    /// "Reviewer A", "Reviewer B", ... "Reviewer Z", "Reviewer AA"; the same login always gets the same one
+    pub fn pseudonym(&self, login: &str) -> String {
+        let mut pseudonyms = self.pseudonyms.lock().unwrap();
+        let next = pseudonyms.len();
+        pseudonyms.entry(login.to_lowercase())
+            .or_insert_with(|| format!("Reviewer {}", letters(next)))
+            .clone()
+    }
+
+    /// Pass a command result through; rewritten only while the mode is on
+    pub fn redact<T: Redact>(&self, mut value: T) -> T {
+        if self.is_enabled() {
+            value.redact(self);
+        }
+        value
+    }
+
+    pub fn ensure_export_allowed(&self) -> Result<(), String> {
+        if self.is_enabled() {
+            return Err("Exports are disabled while privacy mode is on".to_string());
+        }
+        Ok(())
+    }
+}
+
+// 0 -> A, 25 -> Z, 26 -> AA, like spreadsheet columns
+fn letters(mut n: usize) -> String {
+    let mut out = Vec::new();
+    loop {
+        out.push(b'A' + (n % 26) as u8);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap()
+}
+
+// "owner/repo#512" -> "org/repo#512"; keys without an owner are left alone
+pub fn redact_display_key(key: &mut String) {
+    if let Some((_, rest)) = key.split_once('/') {
+        *key = format!("{}/{}", OWNER_PLACEHOLDER, rest);
+    }
+}
+
+pub trait Redact {
+    fn redact(&mut self, privacy: &Privacy);
+}
+
+impl<T: Redact> Redact for Vec<T> {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.iter_mut().for_each(|item| item.redact(privacy));
+    }
+}
+
+impl<T: Redact> Redact for Option<T> {
+    fn redact(&mut self, privacy: &Privacy) {
+        if let Some(item) = self {
+            item.redact(privacy);
+        }
+    }
+}
+
+// Name and display name both become the pseudonym; the member id stands in for a missing login
+fn redact_author(
+    privacy: &Privacy,
+    member_id: i64,
+    name: &mut Option<String>,
+    display_name: &mut Option<String>,
+    avatar: &mut Option<String>,
+) {
+    let pseudonym = privacy.pseudonym(name.as_deref().unwrap_or(&format!("member:{}", member_id)));
+    for field in [name, display_name] {
+        if field.is_some() {
+            *field = Some(pseudonym.clone());
+        }
+    }
+    *avatar = None;
+}
+
+impl Redact for PullRequest {
+    fn redact(&mut self, privacy: &Privacy) {
+        redact_author(privacy, self.author_id, &mut self.author_name, &mut self.author_display_name, &mut self.author_avatar);
+        if self.repository_owner.is_some() {
+            self.repository_owner = Some(OWNER_PLACEHOLDER.to_string());
+        }
+        redact_display_key(&mut self.display_key);
+        redact_actor(privacy, &mut self.added_by);
+    }
+}
+
+// Actors are logins or a local identity; both get the member pseudonym
+fn redact_actor(privacy: &Privacy, actor: &mut Option<String>) {
+    if let Some(actor) = actor {
+        *actor = privacy.pseudonym(actor);
+    }
+}
+
+impl Redact for HistoryEntry {
+    fn redact(&mut self, privacy: &Privacy) {
+        redact_actor(privacy, &mut self.actor);
+    }
+}
+
+impl Redact for ReviewHistory {
+    fn redact(&mut self, privacy: &Privacy) {
+        redact_actor(privacy, &mut self.actor);
+    }
+}
+
+impl Redact for PullRequestWithAge {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.pull_request.redact(privacy);
+    }
+}
+
+impl Redact for SearchHit {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.pull_request.redact(privacy);
+    }
+}
+
+impl Redact for PrDetail {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.pull_request.redact(privacy);
+    }
+}
+
+impl Redact for PrComment {
+    fn redact(&mut self, privacy: &Privacy) {
+        redact_author(privacy, self.author_member_id, &mut self.author_name, &mut self.author_display_name, &mut self.author_avatar);
+        redact_actor(privacy, &mut self.added_by);
+    }
+}
+
+impl Redact for PrReviewer {
+    fn redact(&mut self, privacy: &Privacy) {
+        redact_author(privacy, self.team_member_id, &mut self.github_username, &mut self.display_name, &mut None);
+    }
+}
+
+impl Redact for TeamMember {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.github_username = privacy.pseudonym(&self.github_username);
+        if self.display_name.is_some() {
+            self.display_name = Some(self.github_username.clone());
+        }
+        self.avatar_url = None;
+        self.avatar_path = None;
+    }
+}
+
+impl Redact for AuthorScorecard {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.github_username = privacy.pseudonym(&self.github_username);
+    }
+}
+
+impl Redact for AuthorBacklogAlert {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.github_username = privacy.pseudonym(&self.github_username);
+        if self.display_name.is_some() {
+            self.display_name = Some(self.github_username.clone());
+        }
+        self.prs.iter_mut().for_each(|pr| redact_display_key(&mut pr.display_key));
+    }
+}
+
+impl Redact for SyncConflict {
+    fn redact(&mut self, _privacy: &Privacy) {
+        redact_display_key(&mut self.display_key);
+    }
+}
+
+impl Redact for BoardData {
+    fn redact(&mut self, _privacy: &Privacy) {
+        for card in self.columns.iter_mut().flat_map(|column| column.cards.iter_mut()) {
+            card.author_avatar = None;
+        }
+    }
+}
+
+impl Redact for MisfiledPr {
+    fn redact(&mut self, _privacy: &Privacy) {
+        self.repository_owner = OWNER_PLACEHOLDER.to_string();
+    }
+}
+
+impl Redact for SyncChange {
+    fn redact(&mut self, _privacy: &Privacy) {
+        redact_display_key(&mut self.display_key);
+    }
+}
+
+impl Redact for ProjectMappingConflict {
+    fn redact(&mut self, _privacy: &Privacy) {
+        self.message = self.message.replace(&format!(" {}/", self.owner), &format!(" {}/", OWNER_PLACEHOLDER));
+        self.owner = OWNER_PLACEHOLDER.to_string();
+    }
+}
+
+impl Redact for AddPrResult {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.pull_request.redact(privacy);
+        self.mapping_warning.redact(privacy);
+    }
+}
+
+impl Redact for QuickAddResult {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.pull_request.redact(privacy);
+    }
+}
+
+impl Redact for BranchPrCandidate {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.author = privacy.pseudonym(&self.author);
+        // https://github.com/<owner>/<repo>/pull/<number>
+        let mut parts: Vec<&str> = self.url.split('/').collect();
+        if parts.len() > 4 {
+            parts[3] = OWNER_PLACEHOLDER;
+            self.url = parts.join("/");
+        }
+    }
+}
+
+impl Redact for BranchAddResult {
+    fn redact(&mut self, privacy: &Privacy) {
+        match self {
+            BranchAddResult::Added { pull_request, mapping_warning } => {
+                pull_request.redact(privacy);
+                mapping_warning.redact(privacy);
+            }
+            BranchAddResult::Ambiguous { candidates } => candidates.redact(privacy),
+        }
+    }
+}
+
+impl Redact for IssueRefreshResult {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.pull_request.redact(privacy);
+    }
+}
+
+impl Redact for SyncRun {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.changes.redact(privacy);
+        // Errors start with the PR's display key
+        for error in &mut self.errors {
+            if let Some((key, message)) = error.split_once(": ") {
+                let mut key = key.to_string();
+                redact_display_key(&mut key);
+                *error = format!("{}: {}", key, message);
+            }
+        }
+    }
+}
+
+impl Redact for GitHubTokenInfo {
+    fn redact(&mut self, privacy: &Privacy) {
+        if let Some(user) = &mut self.user {
+            user.login = privacy.pseudonym(&user.login);
+            if user.name.is_some() {
+                user.name = Some(user.login.clone());
+            }
+            user.avatar_url = String::new();
+            user.email = None;
+            user.company = None;
+        }
+    }
+}
+
+impl Redact for TrackedRepository {
+    fn redact(&mut self, _privacy: &Privacy) {
+        self.owner = OWNER_PLACEHOLDER.to_string();
+    }
+}
+
+impl Redact for RepoAccessReport {
+    fn redact(&mut self, privacy: &Privacy) {
+        self.newly_inaccessible.redact(privacy);
+        // Errors start with "owner/repo: "
+        self.errors.iter_mut().for_each(redact_display_key);
+    }
+}
+
+impl Redact for SlaPrEntry {
+    fn redact(&mut self, _privacy: &Privacy) {
+        redact_display_key(&mut self.display_key);
+    }
+}
+
+impl Redact for ProjectSlaReport {
+    fn redact(&mut self, privacy: &Privacy) {
+        if let ProjectSlaReport::Configured(report) = self {
+            report.violators.redact(privacy);
+            report.pending.redact(privacy);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::{Database, NewPullRequest};
+    use crate::github::GitHubUser;
+    use crate::services::pull_requests::ProjectSelection;
+    use crate::services::sla::SlaReport;
+
+    #[test]
+    fn test_pseudonyms_are_stable_within_a_session() {
+        let privacy = Privacy::default();
+        assert_eq!(privacy.pseudonym("octocat"), "Reviewer A");
+        assert_eq!(privacy.pseudonym("hubot"), "Reviewer B");
+        assert_eq!(privacy.pseudonym("OctoCat"), "Reviewer A");
+        assert_eq!((letters(25), letters(26), letters(27 + 26 * 26)), ("Z".to_string(), "AA".to_string(), "AAB".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_no_real_identity_leaks() {
+        let db = Database::new_in_memory().await.unwrap();
+        let octocat = db.add_team_member("octocat", Some("https://avatars.example/octocat.png"), Some("Mona Lisa")).await.unwrap();
+        let hubot = db.get_or_create_team_member("hubot".to_string()).await.unwrap();
+        for (number, author) in [(1, &octocat), (2, &hubot), (3, &octocat)] {
+            db.add_pull_request(&WriteContext::new("mihir"), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                title: Some("fix: retry on 502".to_string()),
+                author_id: author.id,
+                repository_owner: Some(format!("secret-org-{}", number)),
+                repository_name: Some("api".to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let privacy = Privacy::default();
+        let prs = db.get_pull_requests_sorted(None).await.unwrap();
+        let real = serde_json::to_string(&prs).unwrap();
+        // Off: untouched
+        assert_eq!(serde_json::to_string(&privacy.redact(prs.clone())).unwrap(), real);
+
+        privacy.set_enabled(true);
+        let redacted = privacy.redact(prs.clone());
+        let json = serde_json::to_string(&redacted).unwrap();
+        for secret in ["octocat", "hubot", "mihir", "Mona Lisa", "avatars.example", "secret-org"] {
+            assert!(!json.contains(secret), "{} leaked: {}", secret, json);
+        }
+        for pr in &redacted {
+            assert_eq!(pr.repository_owner.as_deref(), Some(OWNER_PLACEHOLDER));
+            assert!(pr.display_key.starts_with("org/api#") || !pr.display_key.contains('/'));
+            assert_eq!(pr.author_avatar, None);
+        }
+
+        // Same member, same pseudonym, across PRs and calls
+        let name_of = |number: i64, prs: &[PullRequest]| prs.iter().find(|pr| pr.pr_number == number).unwrap().author_name.clone();
+        assert_eq!(name_of(1, &redacted), name_of(3, &redacted));
+        assert_ne!(name_of(1, &redacted), name_of(2, &redacted));
+        let again = privacy.redact(db.get_pull_requests_sorted(None).await.unwrap());
+        assert_eq!(name_of(1, &again), name_of(1, &redacted));
+        assert_eq!(privacy.redact(octocat.clone()).github_username, name_of(1, &redacted).unwrap());
+
+        // Commands that hand back a single row: restore_archived_pr, add_pr_comment, the add
+        // commands (add_pr_from_github_url, quick_add_from_url, add_pr_for_branch, add_issue),
+        // find_pr_for_branch and refresh_issue
+        let ctx = WriteContext::new("mihir");
+        db.update_pr_status(&ctx, prs[0].id, "archived".to_string()).await.unwrap();
+        let restored = db.restore_archived_pr(&ctx, prs[0].id).await.unwrap();
+        let comment = db.add_pr_comment(&ctx, prs[0].id, octocat.id, "LGTM".to_string()).await.unwrap();
+        let warning = ProjectMappingConflict {
+            owner: "secret-org-1".to_string(),
+            repo: "api".to_string(),
+            mapped_project_id: 1,
+            mapped_project_name: "Payments".to_string(),
+            requested_project_id: 2,
+            message: "repo secret-org-1/api is mapped to project 'Payments'".to_string(),
+        };
+        let candidate = BranchPrCandidate {
+            pr_number: 1,
+            title: "fix: retry on 502".to_string(),
+            state: "open".to_string(),
+            author: "octocat".to_string(),
+            url: "https://github.com/secret-org-1/api/pull/1".to_string(),
+            github_updated_at: None,
+        };
+        let results = [
+            serde_json::to_value(privacy.redact(restored.clone())).unwrap(),
+            serde_json::to_value(privacy.redact(comment)).unwrap(),
+            serde_json::to_value(privacy.redact(AddPrResult { pull_request: restored.clone(), mapping_warning: Some(warning.clone()) })).unwrap(),
+            serde_json::to_value(privacy.redact(QuickAddResult { pull_request: restored.clone(), project_selection: ProjectSelection::DefaultProject })).unwrap(),
+            serde_json::to_value(privacy.redact(BranchAddResult::Added { pull_request: Box::new(restored.clone()), mapping_warning: Some(warning) })).unwrap(),
+            serde_json::to_value(privacy.redact(BranchAddResult::Ambiguous { candidates: vec![candidate.clone()] })).unwrap(),
+            serde_json::to_value(privacy.redact(vec![candidate])).unwrap(),
+            serde_json::to_value(privacy.redact(IssueRefreshResult { converted: true, pull_request: restored })).unwrap(),
+        ];
+
+        // Settings and report commands: verify_github_token and test_github_connection,
+        // audit_repo_access, get_project_sla_report
+        let token_info = GitHubTokenInfo {
+            valid: true,
+            user: Some(GitHubUser {
+                login: "octocat".to_string(),
+                id: 1,
+                avatar_url: "https://avatars.example/octocat.png".to_string(),
+                name: Some("Mona Lisa".to_string()),
+                email: Some("mona@secret-org.example".to_string()),
+                company: Some("secret-org".to_string()),
+            }),
+            scopes: vec!["repo".to_string()],
+            rate_limit_remaining: None,
+            rate_limit_total: None,
+            rate_limit_pause: None,
+            token_expiry_warning: None,
+            keychain_access_denied: false,
+            last_sync: None,
+        };
+        let access = RepoAccessReport {
+            checked: 2,
+            inaccessible_count: 1,
+            newly_inaccessible: vec![TrackedRepository {
+                id: 1,
+                owner: "secret-org-1".to_string(),
+                name: "api".to_string(),
+                accessible: Some(false),
+                last_checked_at: Some(0),
+                project_id: None,
+            }],
+            errors: vec!["secret-org-2/api: Status: 500".to_string()],
+            rate_limited: false,
+        };
+        let entry = SlaPrEntry {
+            pr_id: 1,
+            pr_number: 1,
+            display_key: "secret-org-1/api#1".to_string(),
+            title: None,
+            started_at: 0,
+            reviewed_at: None,
+            business_hours_elapsed: 30.0,
+            met_sla: false,
+        };
+        let sla = ProjectSlaReport::Configured(SlaReport {
+            project_id: 1,
+            sla_hours: 24,
+            target_score: None,
+            window_days: 30,
+            resolved_count: 1,
+            met_count: 0,
+            met_percentage: Some(0.0),
+            current_streak: 0,
+            violators: vec![entry.clone()],
+            pending: vec![entry],
+        });
+        let redacted_access = privacy.redact(access);
+        assert_eq!(redacted_access.errors, vec!["org/api: Status: 500".to_string()]);
+        let results = results.into_iter().chain([
+            serde_json::to_value(privacy.redact(token_info)).unwrap(),
+            serde_json::to_value(redacted_access).unwrap(),
+            serde_json::to_value(privacy.redact(sla)).unwrap(),
+        ]);
+        for result in results {
+            let json = result.to_string();
+            for secret in ["octocat", "hubot", "mihir", "Mona Lisa", "avatars.example", "secret-org"] {
+                assert!(!json.contains(secret), "{} leaked: {}", secret, json);
+            }
+        }
+
+        assert!(privacy.ensure_export_allowed().is_err());
+        privacy.set_enabled(false);
+        assert_eq!(serde_json::to_string(&privacy.redact(prs)).unwrap(), real);
+        assert!(privacy.ensure_export_allowed().is_ok());
+    }
+}
@@ -0,0 +1,948 @@
+use std::collections::HashSet;
+use std::future::Future;
+
+use serde::Serialize;
+
+use crate::database::repo_mappings::ProjectMappingConflict;
+use crate::database::github_reviews::SubmittedReview;
+use crate::database::status::PrStatus;
+use crate::database::{Database, NewPullRequest, PullRequest, RepoRepairSummary};
+use crate::github::api_usage::ApiFeature;
+use crate::github::{fetch_github_issue_reactions, fetch_github_pr_data, fetch_pr_location_by_id, fetch_pr_reviews, fetch_prs_for_branch, is_pr_not_found_error};
+use crate::github::models::{GitHubPRData, GitHubPRSummary, GitHubReview, PRAuthor};
+use crate::github::url::{parse_github_pr_url, GitHubPRUrl, PrUrlError};
+use crate::services::retry::{queue_refresh_retry, OP_REACTIONS};
+use crate::services::sync_runs::SyncRunLog;
+use crate::DbState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AddPrResult {
+    pub pull_request: PullRequest,
+    // Added to a project other than the one its repository is mapped to
+    pub mapping_warning: Option<ProjectMappingConflict>,
+}
+
+/// Add PR from GitHub URL - fetches data and correlates with database
+pub async fn add_pr_from_github_url(
+    db: &Database,
+    pr_url: &str,
+    project_id: i64,
+    token: &str
+) -> Result<AddPrResult, String> {
+    // Use the passed token directly instead of retrieving from keychain
+    println!("🔑 Using provided GitHub token (length: {} chars)", token.len());
+
+    // Parse GitHub URL to extract owner, repo, and PR number
+    println!("🔗 Parsing GitHub URL...");
+    let url_parts = parse_github_pr_url(pr_url).map_err(|e| e.to_string())?;
+    println!("📊 Parsed URL - Owner: {}, Repo: {}, PR: {}", url_parts.owner, url_parts.repo, url_parts.pr_number);
+
+    // Filing a mapped repository's PR elsewhere is usually a slip; strict mode refuses it
+    let mapping_warning = db.project_mapping_conflict(&url_parts.owner, &url_parts.repo, project_id).await.map_err(|e| e.to_string())?;
+    if let Some(conflict) = &mapping_warning {
+        if db.get_strict_repo_mapping().await.map_err(|e| e.to_string())? {
+            return Err(format!("Not added: {} (strict repository mapping is on)", conflict));
+        }
+        println!("⚠️ Adding to project {} although {}", project_id, conflict);
+    }
+
+    // Fetch PR data from GitHub API
+    println!("🌐 Fetching PR data from GitHub API...");
+    let pr_data = fetch_github_pr_data(ApiFeature::AddPr, token, &url_parts.owner, &url_parts.repo, url_parts.pr_number).await?;
+    println!("📋 PR Data fetched - Title: {}, Author: {}", pr_data.title, pr_data.user.login);
+
+    // Check if this PR already exists in the database
+    println!("🔍 Checking for existing PR with GitHub ID: {}", pr_data.id);
+    if let Some(existing_pr) = db.get_pull_request_by_github_id(pr_data.id).await.map_err(|e| e.to_string())? {
+        println!("⚠️ PR already exists in database with ID: {}", existing_pr.id);
+        let project_name = existing_pr.project_name.unwrap_or("Unknown Project".to_string());
+        return Err(format!(
+            "This PR is already added to the system!\n\nPR: {} ({})\nProject: {}\nStatus: {}",
+            existing_pr.title.unwrap_or("Untitled".to_string()),
+            url_parts.pr_number,
+            project_name,
+            existing_pr.status
+        ));
+    }
+
+    // Check if team member exists, create or update if needed
+    println!("👥 Ensuring team member exists for author: {}", pr_data.user.login);
+    let author_id = ensure_team_member_exists(db, &pr_data.author()).await.map_err(|e| e.to_string())?;
+    println!("✅ Team member handled - author_id: {}", author_id);
+
+    // Reactions are a nice-to-have signal, so a failed lookup never blocks the add
+    let mut reactions_error = None;
+    let reactions = match pr_data.reactions.clone() {
+        Some(reactions) => Some(reactions),
+        None => match fetch_github_issue_reactions(ApiFeature::AddPr, token, &url_parts.owner, &url_parts.repo, url_parts.pr_number).await {
+            Ok(reactions) => reactions,
+            Err(e) => {
+                println!("⚠️ Could not fetch reactions: {}", e);
+                reactions_error = Some(e);
+                None
+            }
+        },
+    };
+
+    // Open PRs start in the configured column; ones already merged go straight to Merged
+    let status = if pr_data.merged {
+        PrStatus::Merged
+    } else {
+        db.get_import_open_status().await.map_err(|e| e.to_string())?
+    };
+    println!("💾 Adding PR to database...");
+    let ctx = db.write_context().await.map_err(|e| e.to_string())?;
+    let note = mapping_warning.as_ref().map(|conflict| format!("project override: {}", conflict));
+    let new_pr = db.add_pull_request_with_note(&ctx, NewPullRequest {
+        github_id: pr_data.id,
+        pr_number: url_parts.pr_number,
+        title: Some(pr_data.title.clone()),
+        author_id,
+        project_id: Some(project_id),
+        branch: Some(pr_data.head.ref_field.clone()),
+        status,
+        repository_owner: Some(url_parts.owner.clone()),
+        repository_name: Some(url_parts.repo.clone()),
+        github_created_at: pr_data.created_at_timestamp(),
+        github_updated_at: pr_data.updated_at_timestamp(),
+        reactions_total: reactions.as_ref().map(|r| r.total_count),
+        reactions_plus_one: reactions.as_ref().map(|r| r.plus_one),
+        additions: pr_data.additions,
+        deletions: pr_data.deletions,
+        changed_files: pr_data.changed_files,
+        is_issue: false,
+    }, note.as_deref()).await.map_err(|e| e.to_string())?;
+
+    if pr_data.merged {
+        let policy = db.apply_merged_import_scoring(new_pr.id).await.map_err(|e| e.to_string())?;
+        println!("📦 PR was already merged; applied '{}' scoring", policy);
+    }
+    if let Some(sha) = pr_data.merged_commit_sha() {
+        db.set_merge_commit_sha(new_pr.id, sha).await.map_err(|e| e.to_string())?;
+    }
+
+    // Reviews feed pr_reviewers; like reactions, a failed lookup doesn't undo the add.
+    // The head commit goes first so a change request against it is recognized.
+    db.record_pr_head_sha(new_pr.id, &pr_data.head.sha).await.map_err(|e| e.to_string())?;
+    match fetch_pr_reviews(ApiFeature::AddPr, token, &url_parts.owner, &url_parts.repo, url_parts.pr_number).await {
+        Ok(reviews) => {
+            db.record_github_reviews(new_pr.id, &submitted_reviews(&reviews)).await.map_err(|e| e.to_string())?;
+        }
+        Err(e) => println!("⚠️ Could not fetch reviews: {}", e),
+    }
+
+    // Let the retry worker fill in reactions later
+    if let Some(error) = reactions_error {
+        if let Err(e) = db.record_retry_failure(new_pr.id, OP_REACTIONS, &error, chrono::Utc::now().timestamp()).await {
+            println!("⚠️ Could not queue reactions retry: {}", e);
+        }
+    }
+
+    println!("🎉 PR successfully added to database with ID: {}", new_pr.id);
+    Ok(AddPrResult { pull_request: new_pr, mapping_warning })
+}
+
+/// How quick add picked the project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSelection {
+    RepositoryMatch,
+    DefaultProject,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickAddResult {
+    pub pull_request: PullRequest,
+    pub project_selection: ProjectSelection,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QuickAddError {
+    InvalidUrl { error: PrUrlError, message: String },
+    // No project could be inferred or defaulted; the UI should ask for one
+    NeedsProject { owner: String, repo: String, pr_number: i64 },
+    Failed { message: String },
+}
+
+impl From<String> for QuickAddError {
+    fn from(message: String) -> Self {
+        QuickAddError::Failed { message }
+    }
+}
+
+/// Pick a project for a PR: the repository's mapped or usual project, else the default project
+pub async fn resolve_quick_add_project(db: &Database, url: &GitHubPRUrl) -> Result<(i64, ProjectSelection), QuickAddError> {
+    if let Some(project) = db.get_repository_project(&url.owner, &url.repo).await.map_err(|e| e.to_string())? {
+        return Ok((project.id, ProjectSelection::RepositoryMatch));
+    }
+    if let Some(project) = db.suggest_project_for_repo(&url.owner, &url.repo).await.map_err(|e| e.to_string())? {
+        return Ok((project.id, ProjectSelection::RepositoryMatch));
+    }
+    if let Some(project) = db.get_default_project().await.map_err(|e| e.to_string())? {
+        return Ok((project.id, ProjectSelection::DefaultProject));
+    }
+
+    Err(QuickAddError::NeedsProject {
+        owner: url.owner.clone(),
+        repo: url.repo.clone(),
+        pr_number: url.pr_number,
+    })
+}
+
+/// Add a PR from a pasted URL without asking for a project
+pub async fn quick_add_from_url(db: &Database, url: &str, token: &str) -> Result<QuickAddResult, QuickAddError> {
+    let parsed = parse_github_pr_url(url).map_err(|error| QuickAddError::InvalidUrl {
+        message: error.to_string(),
+        error,
+    })?;
+    let (project_id, project_selection) = resolve_quick_add_project(db, &parsed).await?;
+    println!("⚡ Quick add {} into project {} ({:?})", parsed.canonical_url(), project_id, project_selection);
+
+    let added = add_pr_from_github_url(db, &parsed.canonical_url(), project_id, token).await?;
+    Ok(QuickAddResult { pull_request: added.pull_request, project_selection })
+}
+
+// A PR found for a branch; state is "open", "closed" or "merged"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BranchPrCandidate {
+    pub pr_number: i64,
+    pub title: String,
+    pub state: String,
+    pub author: String,
+    pub url: String,
+    pub github_updated_at: Option<i64>,
+}
+
+impl From<GitHubPRSummary> for BranchPrCandidate {
+    fn from(pr: GitHubPRSummary) -> Self {
+        let state = if pr.merged_at.is_some() { "merged".to_string() } else { pr.state.clone() };
+        BranchPrCandidate {
+            pr_number: pr.number,
+            github_updated_at: pr.updated_at_timestamp(),
+            title: pr.title,
+            state,
+            author: pr.user.login,
+            url: pr.html_url,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BranchLookupError {
+    // The repository is readable but nothing has been opened from that branch yet
+    NoPullRequest { owner: String, repo: String, branch: String, message: String },
+    // 403/404: the repository doesn't exist or the token can't see it
+    RepoAccess { owner: String, repo: String, message: String },
+    Failed { message: String },
+}
+
+impl From<String> for BranchLookupError {
+    fn from(message: String) -> Self {
+        BranchLookupError::Failed { message }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum BranchAddResult {
+    Added { pull_request: Box<PullRequest>, mapping_warning: Option<ProjectMappingConflict> },
+    // Several PRs were opened from the branch and none stands out; let the user choose
+    Ambiguous { candidates: Vec<BranchPrCandidate> },
+}
+
+/// The PR a branch most likely refers to: its only open PR, or its only PR at all
+pub fn pick_branch_pr(candidates: &[BranchPrCandidate]) -> Option<&BranchPrCandidate> {
+    let mut open = candidates.iter().filter(|pr| pr.state == "open");
+    match (open.next(), open.next()) {
+        (Some(pr), None) => Some(pr),
+        (None, None) if candidates.len() == 1 => candidates.first(),
+        _ => None,
+    }
+}
+
+/// PRs opened from `branch` in owner/repo, newest first
+pub async fn find_pr_for_branch(token: &str, owner: &str, repo: &str, branch: &str) -> Result<Vec<BranchPrCandidate>, BranchLookupError> {
+    let branch = branch.trim();
+    if branch.is_empty() {
+        return Err(BranchLookupError::from("Branch name is required".to_string()));
+    }
+
+    let Some(prs) = fetch_prs_for_branch(ApiFeature::BranchLookup, token, owner, repo, branch).await? else {
+        return Err(BranchLookupError::RepoAccess {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            message: format!("Cannot access repository {}/{}. Check the name and that your token can read it.", owner, repo),
+        });
+    };
+    if prs.is_empty() {
+        return Err(BranchLookupError::NoPullRequest {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            branch: branch.to_string(),
+            message: format!("No pull request exists for branch '{}' in {}/{} yet", branch, owner, repo),
+        });
+    }
+
+    Ok(prs.into_iter().map(BranchPrCandidate::from).collect())
+}
+
+/// Add the PR for a branch, or return the candidates when more than one could be meant
+pub async fn add_pr_for_branch(
+    db: &Database,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+    project_id: i64,
+    token: &str
+) -> Result<BranchAddResult, BranchLookupError> {
+    let candidates = find_pr_for_branch(token, owner, repo, branch).await?;
+    let Some(pr) = pick_branch_pr(&candidates) else {
+        return Ok(BranchAddResult::Ambiguous { candidates });
+    };
+
+    let url = format!("https://github.com/{}/{}/pull/{}", owner, repo, pr.pr_number);
+    println!("🌿 Branch '{}' resolved to {}", branch.trim(), url);
+    let added = add_pr_from_github_url(db, &url, project_id, token).await?;
+    Ok(BranchAddResult::Added { pull_request: Box::new(added.pull_request), mapping_warning: added.mapping_warning })
+}
+
+/// Backfill repository_owner/repository_name for legacy PRs by looking up their GitHub id
+pub async fn repair_missing_repo_fields(db: &Database, token: &str) -> Result<RepoRepairSummary, String> {
+    let prs = db.get_prs_missing_repo_fields().await.map_err(|e| e.to_string())?;
+    println!("🔧 Found {} PRs missing repository fields", prs.len());
+
+    let mut summary = RepoRepairSummary::default();
+    for pr in prs {
+        match fetch_pr_location_by_id(ApiFeature::RepoRepair, token, pr.github_id).await {
+            Ok(Some(location)) if location.number == pr.pr_number => {
+                db.update_pr_repo_fields(pr.id, &location.repository.owner.login, &location.repository.name)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                summary.repaired += 1;
+            }
+            Ok(Some(location)) => {
+                summary.unrepairable += 1;
+                summary.errors.push(format!(
+                    "PR #{} (id {}): GitHub reports number #{} for this id, leaving it untouched",
+                    pr.pr_number, pr.id, location.number
+                ));
+            }
+            Ok(None) => {
+                summary.unrepairable += 1;
+                summary.errors.push(format!("PR #{} (id {}): not found on GitHub", pr.pr_number, pr.id));
+            }
+            Err(e) => {
+                summary.unrepairable += 1;
+                summary.errors.push(format!("PR #{} (id {}): {}", pr.pr_number, pr.id, e));
+            }
+        }
+    }
+
+    println!("✅ Repaired {} PRs, {} could not be repaired", summary.repaired, summary.unrepairable);
+    Ok(summary)
+}
+
+/// Move every unpinned PR that GitHub reports as merged to the Merged column.
+/// Only the status changes; returns the PR numbers that were closed.
+pub async fn close_merged_prs(db: &Database, token: &str) -> Result<Vec<i64>, String> {
+    let candidates = db.get_merge_candidates().await.map_err(|e| e.to_string())?;
+    println!("🔄 Checking merge state of {} PRs", candidates.len());
+
+    // Repos the token can no longer read would fail for every PR; skip them once instead
+    let inaccessible: HashSet<(String, String)> = db.get_inaccessible_repositories()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|r| (r.owner, r.name))
+        .collect();
+    for (owner, name) in &inaccessible {
+        println!("⚠️ Skipping {}/{}: token cannot access it (see audit_repo_access)", owner, name);
+    }
+
+    let mut closed = Vec::new();
+    let mut log = SyncRunLog::start(ApiFeature::MergeSync, chrono::Utc::now().timestamp());
+    for pr in candidates {
+        let (Some(owner), Some(repo)) = (pr.repository_owner.as_deref(), pr.repository_name.as_deref()) else {
+            continue;
+        };
+        if inaccessible.contains(&(owner.to_string(), repo.to_string())) {
+            continue;
+        }
+        match fetch_github_pr_data(ApiFeature::MergeSync, token, owner, repo, pr.pr_number).await {
+            Ok(data) if data.merged => {
+                db.mark_pr_merged(pr.id, data.merged_at_timestamp()).await.map_err(|e| e.to_string())?;
+                if let Some(sha) = data.merged_commit_sha() {
+                    db.set_merge_commit_sha(pr.id, sha).await.map_err(|e| e.to_string())?;
+                }
+                closed.push(pr.pr_number);
+                log.merged(&pr);
+            }
+            Ok(_) => log.unchanged(),
+            Err(e) => {
+                println!("⚠️ Could not check PR #{} in {}/{}: {}", pr.pr_number, owner, repo, e);
+                log.failed(&pr, &e);
+            }
+        }
+    }
+
+    println!("✅ Closed {} merged PRs", closed.len());
+    log.finish(db, chrono::Utc::now().timestamp()).await;
+    Ok(closed)
+}
+
+/// GitHub calls needed to refresh a tracked PR (mocked in tests)
+pub trait PrDataClient {
+    fn fetch_pr(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<GitHubPRData, String>> + Send;
+    fn fetch_reviews(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<Vec<GitHubReview>, String>> + Send;
+}
+
+#[derive(Clone)]
+pub struct GitHubPrDataClient {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl PrDataClient for GitHubPrDataClient {
+    async fn fetch_pr(&self, owner: &str, repo: &str, number: i64) -> Result<GitHubPRData, String> {
+        fetch_github_pr_data(self.feature, &self.token, owner, repo, number).await
+    }
+
+    async fn fetch_reviews(&self, owner: &str, repo: &str, number: i64) -> Result<Vec<GitHubReview>, String> {
+        fetch_pr_reviews(self.feature, &self.token, owner, repo, number).await
+    }
+}
+
+/// Reviews that count as a review (pending, dismissed and ghost-account ones don't)
+pub(crate) fn submitted_reviews(reviews: &[GitHubReview]) -> Vec<SubmittedReview> {
+    reviews.iter()
+        .filter(|review| matches!(review.state.as_str(), "APPROVED" | "CHANGES_REQUESTED" | "COMMENTED"))
+        .filter_map(|review| Some(SubmittedReview {
+            review_id: review.id,
+            reviewer: review.user.as_ref()?.login.clone(),
+            state: review.state.clone(),
+            submitted_at: review.submitted_at_timestamp()?,
+            commit_id: review.commit_id.clone(),
+        }))
+        .collect()
+}
+
+/// Re-fetch a tracked PR and update what GitHub owns (title, branch, updated time, reactions, reviewers).
+/// Locally edited titles and branches follow the sync conflict policies. Unless its status is
+/// pinned, a PR GitHub reports merged moves to Merged and one closed unmerged is archived.
+/// The database is locked to read the PR and to write the result, never while a request is out.
+pub async fn refresh_pr<C: PrDataClient>(state: &DbState, client: &C, pr_id: i64) -> Result<PullRequest, String> {
+    let pr = {
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        db.get_pull_request_by_id(pr_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Pull request {} not found", pr_id))?
+    };
+    let (data, reviews) = match fetch_pr_update(client, &pr).await {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            if let Some(db) = state.lock().await.as_ref() {
+                queue_refresh_retry(db, &pr, &e, chrono::Utc::now().timestamp()).await;
+            }
+            return Err(e);
+        }
+    };
+    apply_fetched_pr(state, pr.id, &data, &reviews).await
+}
+
+// How fetch_pr_update words a 404
+const PR_GONE_ERROR: &str = "is no longer on GitHub";
+
+/// Whether a failed fetch_pr_update may pass on a later try: not for issues, PRs missing
+/// repository fields, or a PR GitHub answers 404 for
+pub(crate) fn is_retryable_fetch_error(pr: &PullRequest, error: &str) -> bool {
+    !pr.is_issue && pr.repository_owner.is_some() && pr.repository_name.is_some() && !error.contains(PR_GONE_ERROR)
+}
+
+/// The GitHub half of refresh_pr: the PR and its reviews, with no database access
+pub(crate) async fn fetch_pr_update<C: PrDataClient>(client: &C, pr: &PullRequest) -> Result<(GitHubPRData, Vec<GitHubReview>), String> {
+    if pr.is_issue {
+        return Err(format!("Item {} is a tracked issue; use refresh_issue", pr.id));
+    }
+    let (Some(owner), Some(repo)) = (pr.repository_owner.as_deref(), pr.repository_name.as_deref()) else {
+        return Err("Repository fields missing; run repair_missing_repo_fields first".to_string());
+    };
+
+    let data = client.fetch_pr(owner, repo, pr.pr_number).await.map_err(|e| {
+        if is_pr_not_found_error(&e) {
+            format!(
+                "PR #{} {} in {}/{}, or the token can't see it; the tracked copy was left as is (delete_pull_request removes it)",
+                pr.pr_number, PR_GONE_ERROR, owner, repo
+            )
+        } else {
+            e
+        }
+    })?;
+    let reviews = client.fetch_reviews(owner, repo, pr.pr_number).await?;
+    Ok((data, reviews))
+}
+
+/// Apply a fetch made without the lock held: the PR is read again under it, since it may
+/// have been edited or deleted while the request was out
+pub(crate) async fn apply_fetched_pr(state: &DbState, pr_id: i64, data: &GitHubPRData, reviews: &[GitHubReview]) -> Result<PullRequest, String> {
+    let db_state = state.lock().await;
+    let db = db_state.as_ref().ok_or("Database not initialized")?;
+    let pr = db.get_pull_request_by_id(pr_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} was deleted during the refresh", pr_id))?;
+    apply_pr_data(db, &pr, data, reviews).await
+}
+
+/// Write what GitHub reported for a tracked PR (refresh_pr after the fetch); returns the updated row
+pub(crate) async fn apply_pr_data(db: &Database, pr: &PullRequest, data: &GitHubPRData, reviews: &[GitHubReview]) -> Result<PullRequest, String> {
+    let owner = pr.repository_owner.as_deref().unwrap_or_default();
+    let repo = pr.repository_name.as_deref().unwrap_or_default();
+    let held = db.update_pr_github_fields(pr.id, &data.title, &data.head.ref_field, data.updated_at_timestamp())
+        .await
+        .map_err(|e| e.to_string())?;
+    if !held.is_empty() {
+        let fields: Vec<&str> = held.iter().map(|field| field.as_str()).collect();
+        println!("⚖️ PR #{} in {}/{}: GitHub's {} held back for review", pr.pr_number, owner, repo, fields.join(", "));
+    }
+    if let Some(reactions) = &data.reactions {
+        db.update_pr_reactions(pr.id, Some(reactions.total_count), Some(reactions.plus_one))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    db.update_pr_size(pr.id, data.additions, data.deletions, data.changed_files)
+        .await
+        .map_err(|e| e.to_string())?;
+    if data.merged && pr.status != "Merged" && !pr.status_pinned {
+        db.mark_pr_merged(pr.id, data.merged_at_timestamp()).await.map_err(|e| e.to_string())?;
+    } else if !data.merged && data.state == "closed" && !matches!(pr.status.as_str(), "Merged" | "archived") && !pr.status_pinned {
+        // The pulls payload has no closed_at; updated_at is the close unless it was edited since
+        db.mark_pr_closed(pr.id, data.updated_at_timestamp()).await.map_err(|e| e.to_string())?;
+    }
+    if let Some(sha) = data.merged_commit_sha().filter(|sha| pr.merge_commit_sha.as_deref() != Some(*sha)) {
+        db.set_merge_commit_sha(pr.id, sha).await.map_err(|e| e.to_string())?;
+    }
+    if db.record_pr_head_sha(pr.id, &data.head.sha).await.map_err(|e| e.to_string())?.is_some() {
+        println!("🔁 PR #{} in {}/{}: new commits, back with the reviewer", pr.pr_number, owner, repo);
+    }
+    let recorded = db.record_github_reviews(pr.id, &submitted_reviews(reviews)).await.map_err(|e| e.to_string())?;
+    if recorded.waiting_on.is_some() {
+        println!("✋ PR #{} in {}/{}: changes requested, the author's move", pr.pr_number, owner, repo);
+    }
+
+    println!("🔄 Refreshed PR #{} in {}/{}", pr.pr_number, owner, repo);
+    db.get_pull_request_by_id(pr.id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} not found", pr.id))
+}
+
+/// The author's team member id; GitHub's avatar and name replace stored ones (a missing name
+/// keeps the stored one). Safe to call concurrently for the same login.
+pub async fn ensure_team_member_exists(db: &Database, author: &PRAuthor) -> Result<i64, anyhow::Error> {
+    // Fast path: already known and nothing new to write
+    if let Some(existing) = db.get_team_member_by_username(&author.login).await? {
+        let unchanged = existing.avatar_url.as_ref() == Some(&author.avatar_url)
+            && (author.name.is_none() || existing.display_name == author.name);
+        if unchanged {
+            return Ok(existing.id);
+        }
+    }
+
+    let member = db.add_team_member(&author.login, Some(&author.avatar_url), author.name.as_deref()).await?;
+    Ok(member.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::repo_mappings::STRICT_REPO_MAPPING_KEY;
+    use crate::github::replay;
+
+    const WIDGET_PR_URL: &str = "https://github.com/octo-org/widget-service/pull/1347";
+
+    async fn add_widget_pr(db: &Database, project_id: i64) -> Result<AddPrResult, String> {
+        replay::with_fixtures(&["default"], add_pr_from_github_url(db, WIDGET_PR_URL, project_id, "test-token")).await
+    }
+
+    async fn created_note(db: &Database, pr_id: i64) -> Option<String> {
+        let history = db.get_pr_history(pr_id).await.unwrap();
+        history.into_iter().find(|entry| entry.action == "created").unwrap().details
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_ensures_create_one_member() {
+        let db = std::sync::Arc::new(Database::new_in_memory().await.unwrap());
+        let author = |avatar: &str, name: Option<&str>| PRAuthor {
+            login: "octocat".to_string(),
+            avatar_url: avatar.to_string(),
+            name: name.map(str::to_string),
+        };
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for n in 0..20 {
+            let (db, author) = (db.clone(), author(&format!("https://avatars.example/{}.png", n % 2), None));
+            tasks.spawn(async move { ensure_team_member_exists(&db, &author).await });
+        }
+        let mut ids = HashSet::new();
+        while let Some(result) = tasks.join_next().await {
+            ids.insert(result.unwrap().unwrap());
+        }
+        assert_eq!(ids.len(), 1);
+        let members = db.get_team_members().await.unwrap();
+        assert_eq!(members.len(), 1);
+
+        // GitHub's avatar and name win; a payload without a name keeps the stored one
+        let id = ensure_team_member_exists(&db, &author("https://avatars.example/new.png", Some("The Octocat"))).await.unwrap();
+        ensure_team_member_exists(&db, &author("https://avatars.example/new.png", None)).await.unwrap();
+        let member = db.get_team_member_by_username("octocat").await.unwrap().unwrap();
+        assert_eq!(member.id, id);
+        assert_eq!((member.avatar_url.as_deref(), member.display_name.as_deref()), (Some("https://avatars.example/new.png"), Some("The Octocat")));
+    }
+
+    #[tokio::test]
+    async fn test_add_pr_without_repo_mapping() {
+        let db = Database::new_in_memory().await.unwrap();
+        let mobile = db.add_project("Mobile".to_string(), None).await.unwrap();
+        db.set_setting(STRICT_REPO_MAPPING_KEY, "true").await.unwrap();
+
+        let added = add_widget_pr(&db, mobile.id).await.unwrap();
+        assert_eq!(added.pull_request.project_id, Some(mobile.id));
+        assert_eq!(added.pull_request.title.as_deref(), Some("Amazing new feature"));
+        let pr = &added.pull_request;
+        assert_eq!((pr.additions, pr.deletions, pr.changed_files), (Some(100), Some(3), Some(5)));
+        assert!(added.mapping_warning.is_none());
+        assert_eq!(created_note(&db, added.pull_request.id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_add_pr_records_reviewers() {
+        let db = Database::new_in_memory().await.unwrap();
+        let widgets = db.add_project("Widgets".to_string(), None).await.unwrap();
+        let hubot = db.get_or_create_team_member("hubot".to_string()).await.unwrap();
+
+        let added = add_widget_pr(&db, widgets.id).await.unwrap();
+        let reviewers = db.get_pr_reviewers(added.pull_request.id).await.unwrap();
+        assert_eq!(reviewers.iter().map(|r| (r.team_member_id, r.reviewed_at)).collect::<Vec<_>>(), vec![(hubot.id, 1_296_069_002)]);
+    }
+
+    #[tokio::test]
+    async fn test_add_pr_against_repo_mapping() {
+        let db = Database::new_in_memory().await.unwrap();
+        let widgets = db.add_project("Widgets".to_string(), None).await.unwrap();
+        let mobile = db.add_project("Mobile".to_string(), None).await.unwrap();
+        db.set_repository_project("octo-org", "widget-service", Some(widgets.id)).await.unwrap();
+
+        // Strict: refused before anything is stored
+        db.set_setting(STRICT_REPO_MAPPING_KEY, "true").await.unwrap();
+        let error = add_widget_pr(&db, mobile.id).await.unwrap_err();
+        assert_eq!(error, "Not added: repo octo-org/widget-service is mapped to project 'Widgets' (strict repository mapping is on)");
+        assert!(db.get_pull_request_by_github_id(1296269).await.unwrap().is_none());
+
+        // Warn: added where asked, with the warning and a note in the history
+        db.set_setting(STRICT_REPO_MAPPING_KEY, "false").await.unwrap();
+        let added = add_widget_pr(&db, mobile.id).await.unwrap();
+        assert_eq!(added.pull_request.project_id, Some(mobile.id));
+        let warning = added.mapping_warning.unwrap();
+        assert_eq!(warning.mapped_project_id, widgets.id);
+        assert_eq!(warning.requested_project_id, mobile.id);
+        assert_eq!(
+            created_note(&db, added.pull_request.id).await.as_deref(),
+            Some("project override: repo octo-org/widget-service is mapped to project 'Widgets'")
+        );
+
+        // Quick add follows the mapping
+        let parsed = parse_github_pr_url("octo-org/widget-service#2").unwrap();
+        assert_eq!(resolve_quick_add_project(&db, &parsed).await.unwrap(), (widgets.id, ProjectSelection::RepositoryMatch));
+    }
+
+    fn candidate(pr_number: i64, state: &str) -> BranchPrCandidate {
+        BranchPrCandidate {
+            pr_number,
+            title: format!("PR {}", pr_number),
+            state: state.to_string(),
+            author: "octocat".to_string(),
+            url: String::new(),
+            github_updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_pick_branch_pr() {
+        let pick = |candidates: &[BranchPrCandidate]| pick_branch_pr(candidates).map(|pr| pr.pr_number);
+        // The open PR wins over earlier closed attempts from the same branch
+        assert_eq!(pick(&[candidate(9, "open"), candidate(4, "closed"), candidate(2, "merged")]), Some(9));
+        assert_eq!(pick(&[candidate(4, "merged")]), Some(4));
+        assert_eq!(pick(&[candidate(9, "open"), candidate(8, "open")]), None);
+        assert_eq!(pick(&[candidate(4, "closed"), candidate(2, "merged")]), None);
+        assert_eq!(pick(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_quick_add_project() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let api = db.add_project("API".to_string(), None).await.unwrap();
+        let inbox = db.add_project("Inbox".to_string(), None).await.unwrap();
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            project_id: Some(api.id),
+            status: PrStatus::Waiting,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+
+        let known = parse_github_pr_url("https://github.com/acme/api/pull/2").unwrap();
+        let unknown = parse_github_pr_url("https://github.com/acme/docs/pull/9").unwrap();
+
+        let resolved = resolve_quick_add_project(&db, &known).await.unwrap();
+        assert_eq!(resolved, (api.id, ProjectSelection::RepositoryMatch));
+
+        let err = resolve_quick_add_project(&db, &unknown).await.unwrap_err();
+        assert_eq!(err, QuickAddError::NeedsProject { owner: "acme".to_string(), repo: "docs".to_string(), pr_number: 9 });
+        assert_eq!(serde_json::to_value(&err).unwrap()["kind"], "needs_project");
+
+        db.set_setting("default_project_id", &inbox.id.to_string()).await.unwrap();
+        let resolved = resolve_quick_add_project(&db, &unknown).await.unwrap();
+        assert_eq!(resolved, (inbox.id, ProjectSelection::DefaultProject));
+    }
+
+    #[tokio::test]
+    async fn test_quick_add_rejects_bad_url_before_network() {
+        let db = Database::new_in_memory().await.unwrap();
+        let err = quick_add_from_url(&db, "https://github.com/acme/api/issues/3", "token").await.unwrap_err();
+        assert!(matches!(err, QuickAddError::InvalidUrl { error: PrUrlError::MissingPullSegment { .. }, .. }));
+    }
+
+    struct RenamedOnGitHub;
+
+    impl PrDataClient for RenamedOnGitHub {
+        async fn fetch_pr(&self, _owner: &str, _repo: &str, number: i64) -> Result<GitHubPRData, String> {
+            Ok(GitHubPRData {
+                id: number,
+                title: "fix(api): handle emtpy body".to_string(),
+                user: crate::github::models::GitHubPRUser { login: "octocat".to_string(), avatar_url: String::new(), name: None },
+                head: crate::github::models::GitHubHead { ref_field: "empty-body".to_string(), sha: String::new() },
+                state: "open".to_string(),
+                created_at: None,
+                updated_at: None,
+                merged: false,
+                merged_at: None,
+                merge_commit_sha: None,
+                reactions: None,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+            })
+        }
+
+        // alice approved, the author answered, a draft review is still pending
+        async fn fetch_reviews(&self, _owner: &str, _repo: &str, _number: i64) -> Result<Vec<GitHubReview>, String> {
+            let review = |id: i64, login: &str, state: &str, submitted_at: Option<&str>| GitHubReview {
+                id,
+                user: Some(crate::github::models::GitHubCommentAuthor { login: login.to_string() }),
+                state: state.to_string(),
+                submitted_at: submitted_at.map(str::to_string),
+                commit_id: None,
+            };
+            Ok(vec![
+                review(1, "alice", "APPROVED", Some("1970-01-01T00:01:40Z")),
+                review(2, "octocat", "COMMENTED", Some("1970-01-01T00:02:00Z")),
+                review(3, "bob", "PENDING", None),
+            ])
+        }
+    }
+
+    // refresh_pr works on the app's DbState; the test lends its database for the call
+    async fn refresh<C: PrDataClient>(db: Database, client: &C, pr_id: i64) -> (Database, Result<PullRequest, String>) {
+        let state: DbState = std::sync::Arc::new(tokio::sync::Mutex::new(Some(db)));
+        let result = refresh_pr(&state, client, pr_id).await;
+        let db = state.lock().await.take().unwrap();
+        (db, result)
+    }
+
+    #[tokio::test]
+    async fn test_refresh_follows_the_title_sync_policy() {
+        use crate::database::sync_conflicts::{ConflictChoice, SyncField, SYNC_POLICIES_KEY};
+
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let mut ids = Vec::new();
+        for number in 1..=4 {
+            ids.push(db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                title: Some("fix(api): handle emtpy body".to_string()),
+                author_id: author.id,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some("api".to_string()),
+                ..Default::default()
+            }).await.unwrap().id);
+        }
+        let title = |pr: PullRequest| pr.title.unwrap();
+        let fixed = "fix(api): handle empty body";
+
+        // Never edited: GitHub's value always lands
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, ids[0]).await;
+        assert_eq!(title(refreshed.unwrap()), "fix(api): handle emtpy body");
+
+        // github-wins (the default) overwrites the edit and drops the mark
+        db.set_pr_field_locally(&WriteContext::default(), ids[1], SyncField::Title, fixed).await.unwrap();
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, ids[1]).await;
+        assert_eq!(title(refreshed.unwrap()), "fix(api): handle emtpy body");
+
+        // local-wins keeps it, also on later refreshes
+        db.set_setting(SYNC_POLICIES_KEY, r#"{"title": "local-wins"}"#).await.unwrap();
+        db.set_pr_field_locally(&WriteContext::default(), ids[2], SyncField::Title, fixed).await.unwrap();
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, ids[2]).await;
+        refreshed.unwrap();
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, ids[2]).await;
+        let kept = refreshed.unwrap();
+        assert_eq!((kept.title.as_deref(), kept.commit_type.as_deref()), (Some(fixed), Some("fix")));
+        assert_eq!(kept.branch.as_deref(), Some("empty-body"));
+        assert!(db.get_sync_conflicts().await.unwrap().is_empty());
+
+        // prompt keeps it and queues GitHub's value once, however often the refresh runs
+        db.set_setting(SYNC_POLICIES_KEY, r#"{"title": "prompt"}"#).await.unwrap();
+        db.set_pr_field_locally(&WriteContext::default(), ids[3], SyncField::Title, fixed).await.unwrap();
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, ids[3]).await;
+        refreshed.unwrap();
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, ids[3]).await;
+        assert_eq!(title(refreshed.unwrap()), fixed);
+        let conflicts = db.get_sync_conflicts().await.unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].pr_id, conflicts[0].field, conflicts[0].display_key.as_str()), (ids[3], SyncField::Title, "api#4"));
+        assert_eq!((conflicts[0].local_value.as_deref(), conflicts[0].github_value.as_str()), (Some(fixed), "fix(api): handle emtpy body"));
+
+        // Choosing GitHub applies its value and lets later refreshes through again
+        db.resolve_sync_conflict(conflicts[0].id, ConflictChoice::Github).await.unwrap();
+        assert_eq!(title(db.get_pull_request_by_id(ids[3]).await.unwrap().unwrap()), "fix(api): handle emtpy body");
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, ids[3]).await;
+        refreshed.unwrap();
+        assert!(db.get_sync_conflicts().await.unwrap().is_empty());
+
+        // Choosing local keeps the edit
+        db.set_pr_field_locally(&WriteContext::default(), ids[3], SyncField::Title, fixed).await.unwrap();
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, ids[3]).await;
+        refreshed.unwrap();
+        let conflict = db.get_sync_conflicts().await.unwrap().remove(0);
+        db.resolve_sync_conflict(conflict.id, ConflictChoice::Local).await.unwrap();
+        assert_eq!(title(db.get_pull_request_by_id(ids[3]).await.unwrap().unwrap()), fixed);
+        assert!(db.resolve_sync_conflict(conflict.id, ConflictChoice::Local).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_records_team_reviewers() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let alice = db.get_or_create_team_member("alice".to_string()).await.unwrap();
+        db.get_or_create_team_member("bob".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+
+        // The author's reply and bob's pending review don't count
+        let (db, refreshed) = refresh(db, &RenamedOnGitHub, pr.id).await;
+        let refreshed = refreshed.unwrap();
+        assert_eq!(refreshed.reviewer_count, 1);
+        let reviewers = db.get_pr_reviewers(pr.id).await.unwrap();
+        assert_eq!(reviewers.iter().map(|r| (r.team_member_id, r.reviewed_at)).collect::<Vec<_>>(), vec![(alice.id, 100)]);
+    }
+
+    // Closed on GitHub: PR 1 was merged, PR 5 deleted, every other number closed without merging
+    struct ClosedOnGitHub;
+
+    impl PrDataClient for ClosedOnGitHub {
+        async fn fetch_pr(&self, owner: &str, repo: &str, number: i64) -> Result<GitHubPRData, String> {
+            if number == 5 {
+                return Err(format!("PR #{} not found in repository {}/{}. Possible reasons: ...", number, owner, repo));
+            }
+            Ok(GitHubPRData {
+                id: number,
+                title: format!("PR {}", number),
+                user: crate::github::models::GitHubPRUser { login: "octocat".to_string(), avatar_url: String::new(), name: None },
+                head: crate::github::models::GitHubHead { ref_field: format!("branch-{}", number), sha: String::new() },
+                state: "closed".to_string(),
+                created_at: None,
+                updated_at: Some("1970-01-01T00:16:40Z".to_string()),
+                merged: number == 1,
+                merged_at: (number == 1).then(|| "1970-01-01T00:08:20Z".to_string()),
+                merge_commit_sha: None,
+                reactions: None,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+            })
+        }
+
+        async fn fetch_reviews(&self, _owner: &str, _repo: &str, _number: i64) -> Result<Vec<GitHubReview>, String> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_moves_prs_closed_on_github() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let mut ids = Vec::new();
+        for number in 1..=5 {
+            ids.push(db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                title: Some("Old title".to_string()),
+                author_id: author.id,
+                status: PrStatus::Reviewing,
+                repository_owner: (number != 4).then(|| "acme".to_string()),
+                repository_name: (number != 4).then(|| "api".to_string()),
+                ..Default::default()
+            }).await.unwrap().id);
+        }
+        db.set_pr_status_pinned(&WriteContext::default(), ids[2], true).await.unwrap();
+
+        let (db, refreshed) = refresh(db, &ClosedOnGitHub, ids[0]).await;
+        let merged = refreshed.unwrap();
+        assert_eq!((merged.title.as_deref(), merged.branch.as_deref(), merged.status.as_str()), (Some("PR 1"), Some("branch-1"), "Merged"));
+
+        let (db, refreshed) = refresh(db, &ClosedOnGitHub, ids[1]).await;
+        let closed = refreshed.unwrap();
+        assert_eq!(closed.status, "archived");
+        let history = db.get_pr_history(ids[1]).await.unwrap();
+        let close = history.iter().find(|entry| entry.action == "closed").unwrap();
+        assert_eq!((close.source.as_deref(), close.details.as_deref(), close.performed_at), (Some("github"), Some("Reviewing"), 1_000));
+        // Refreshing again doesn't log a second close, and restoring undoes it
+        let (db, refreshed) = refresh(db, &ClosedOnGitHub, ids[1]).await;
+        refreshed.unwrap();
+        assert_eq!(db.get_pr_history(ids[1]).await.unwrap().iter().filter(|entry| entry.action == "closed").count(), 1);
+        assert_eq!(db.restore_archived_pr(&WriteContext::default(), ids[1]).await.unwrap().status, "Reviewing");
+
+        // Pinned statuses stay put; the title still updates
+        let (db, refreshed) = refresh(db, &ClosedOnGitHub, ids[2]).await;
+        let pinned = refreshed.unwrap();
+        assert_eq!((pinned.title.as_deref(), pinned.status.as_str()), (Some("PR 3"), "Reviewing"));
+
+        let (db, refreshed) = refresh(db, &ClosedOnGitHub, ids[3]).await;
+        let err = refreshed.unwrap_err();
+        assert!(err.contains("repair_missing_repo_fields"), "{}", err);
+        let (db, refreshed) = refresh(db, &ClosedOnGitHub, 9999).await;
+        assert!(refreshed.is_err());
+
+        // Deleted upstream: a short error, and the tracked row is untouched
+        let (db, refreshed) = refresh(db, &ClosedOnGitHub, ids[4]).await;
+        let err = refreshed.unwrap_err();
+        assert!(err.starts_with("PR #5 is no longer on GitHub in acme/api"), "{}", err);
+        let kept = db.get_pull_request_by_id(ids[4]).await.unwrap().unwrap();
+        assert_eq!((kept.title.as_deref(), kept.status.as_str()), (Some("Old title"), "Reviewing"));
+    }
+}
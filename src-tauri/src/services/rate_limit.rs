@@ -0,0 +1,118 @@
+// Persisting GitHub rate-limit state across restarts and reporting it to the UI
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::database::Database;
+use crate::display_time::format_time_of_day;
+use crate::github::rate_limit::{self, CategoryUsage, RateLimitState};
+use crate::services::db_state::{display_tz, wait_for_database};
+use crate::DbState;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RateLimitStatus {
+    pub remaining: Option<u32>,
+    pub limit: Option<u32>,
+    pub reset_at: Option<i64>,
+    pub paused_until: Option<i64>, // set while calls are held back (quota exhausted or Retry-After)
+    pub persisted: bool,
+    pub message: Option<String>,   // e.g. "paused until 14:05 (persisted from previous session)"
+    pub categories: Vec<CategoryUsage>, // consumption per request category this window
+}
+
+pub fn rate_limit_status(state: &RateLimitState, categories: Vec<CategoryUsage>, now: i64, tz: Tz) -> RateLimitStatus {
+    let paused_until = state.blocked_until(now);
+    let message = paused_until.map(|until| {
+        let mut message = format!("paused until {}", format_time_of_day(until, tz));
+        if state.persisted {
+            message.push_str(" (persisted from previous session)");
+        }
+        message
+    });
+
+    RateLimitStatus {
+        remaining: state.remaining,
+        limit: state.limit,
+        reset_at: state.reset_at,
+        paused_until,
+        persisted: state.persisted,
+        message,
+        categories,
+    }
+}
+
+/// Current status, with times in the display zone when the database is open
+pub async fn current_status(db: Option<&Database>) -> RateLimitStatus {
+    let tz = display_tz(db).await;
+    rate_limit_status(&rate_limit::current(), rate_limit::budget_usage(), chrono::Utc::now().timestamp(), tz)
+}
+
+/// Clear the in-memory and saved state, for when a wrong clock left a bogus pause
+pub async fn reset_rate_limit_state(db: &Database) -> Result<(), String> {
+    rate_limit::reset();
+    db.clear_rate_limit_state().await.map_err(|e| e.to_string())
+}
+
+/// Apply the saved budgets; called at startup and whenever settings change
+pub async fn reload_budgets(db: &Database) {
+    match db.get_rate_budgets().await {
+        Ok(budgets) => rate_limit::set_budgets(budgets),
+        Err(e) => println!("⚠️ Could not load rate-limit budgets: {}", e),
+    }
+}
+
+/// Startup task: seed the throttling state from the previous session once the
+/// database is open, then save every change GitHub reports.
+pub async fn run_rate_limit_persister(state: DbState) {
+    let mut changes = rate_limit::subscribe();
+
+    {
+        let db = wait_for_database(&state).await;
+        match db.get_rate_limit_state().await {
+            Ok(Some(saved)) => {
+                println!("⏳ Restored GitHub rate-limit state from previous session");
+                rate_limit::seed(saved);
+            }
+            Ok(None) => {}
+            Err(e) => println!("⚠️ Could not load rate-limit state: {}", e),
+        }
+        reload_budgets(&db).await;
+    }
+
+    while changes.changed().await.is_ok() {
+        let snapshot = changes.borrow_and_update().clone();
+        let db_state = state.lock().await;
+        let Some(db) = db_state.as_ref() else { continue };
+        if let Err(e) = db.save_rate_limit_state(&snapshot).await {
+            println!("⚠️ Could not save rate-limit state: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_persisted_pause_is_reported() {
+        let db = Database::new_in_memory().await.unwrap();
+        assert_eq!(db.get_rate_limit_state().await.unwrap(), None);
+
+        // 2024-01-01 09:00 UTC, paused for 30 minutes
+        let now = 1_704_099_600;
+        let saved = RateLimitState { remaining: Some(0), limit: Some(5000), reset_at: Some(now + 1800), ..Default::default() };
+        db.save_rate_limit_state(&saved).await.unwrap();
+
+        let loaded = RateLimitState { persisted: true, ..db.get_rate_limit_state().await.unwrap().unwrap() };
+        let status = rate_limit_status(&loaded, Vec::new(), now, "Asia/Tokyo".parse().unwrap());
+        assert_eq!(status.paused_until, Some(now + 1800));
+        assert_eq!(status.message.as_deref(), Some("paused until 18:30 (persisted from previous session)"));
+
+        // Live state, and after the reset time
+        let live = RateLimitState { persisted: false, ..loaded.clone() };
+        assert_eq!(rate_limit_status(&live, Vec::new(), now, Tz::UTC).message.as_deref(), Some("paused until 09:30"));
+        assert_eq!(rate_limit_status(&loaded, Vec::new(), now + 1800, Tz::UTC).message, None);
+
+        db.clear_rate_limit_state().await.unwrap();
+        assert_eq!(db.get_rate_limit_state().await.unwrap(), None);
+    }
+}
@@ -0,0 +1,174 @@
+// Rebuilding one repository from GitHub: list its open PRs and the recently closed ones,
+// then swap them in for the tracked rows (database/reimport.rs carries local fields over)
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use crate::database::reimport::{ReimportSummary, UpstreamPr};
+use crate::database::status::PrStatus;
+use crate::database::{Database, NewPullRequest};
+use crate::github::api_usage::ApiFeature;
+use crate::github::models::GitHubPRSummary;
+use crate::github::{fetch_pulls_page, LIST_PAGE_SIZE};
+use crate::services::pull_requests::ensure_team_member_exists;
+
+// Closed PRs last updated longer ago than this aren't reimported
+pub const RECENTLY_CLOSED_DAYS: i64 = 30;
+const MAX_PAGES_PER_STATE: u32 = 10;
+
+/// GitHub call needed to list a repository's PRs (mocked in tests)
+pub trait RepoPullsClient {
+    /// One page, most recently updated first; None when the token can't read the repository
+    fn fetch_pulls_page(&self, owner: &str, repo: &str, state: &str, page: u32) -> impl Future<Output = Result<Option<Vec<GitHubPRSummary>>, String>> + Send;
+}
+
+pub struct GitHubRepoPullsClient {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl RepoPullsClient for GitHubRepoPullsClient {
+    async fn fetch_pulls_page(&self, owner: &str, repo: &str, state: &str, page: u32) -> Result<Option<Vec<GitHubPRSummary>>, String> {
+        fetch_pulls_page(self.feature, &self.token, owner, repo, state, page).await
+    }
+}
+
+/// Open PRs plus those closed and last updated within RECENTLY_CLOSED_DAYS, by number
+async fn list_upstream<C: RepoPullsClient>(client: &C, owner: &str, repo: &str, now: i64) -> Result<Vec<GitHubPRSummary>, String> {
+    let cutoff = now - RECENTLY_CLOSED_DAYS * 86_400;
+    let mut by_number = BTreeMap::new();
+    for state in ["open", "closed"] {
+        for page in 1..=MAX_PAGES_PER_STATE {
+            let batch = client.fetch_pulls_page(owner, repo, state, page)
+                .await?
+                .ok_or_else(|| format!("Cannot list PRs of {}/{}; check the token's access to the repository", owner, repo))?;
+            let last_page = batch.len() < LIST_PAGE_SIZE;
+            let mut past_window = false;
+            for pr in batch {
+                if state == "closed" && pr.updated_at_timestamp().is_some_and(|updated_at| updated_at < cutoff) {
+                    past_window = true;
+                    break;
+                }
+                // A PR closed between the two listings shows up in both; the later listing wins
+                by_number.insert(pr.number, pr);
+            }
+            if last_page || past_window {
+                break;
+            }
+        }
+    }
+    Ok(by_number.into_values().collect())
+}
+
+/// Wipe and reimport owner/repo's tracked PRs. GitHub is listed before anything is touched,
+/// so a failed listing leaves the repository as it was.
+pub async fn reimport_repository<C: RepoPullsClient>(
+    db: &Database,
+    client: &C,
+    owner: &str,
+    repo: &str,
+    preserve_local_fields: bool,
+    confirm_nonce: &str,
+    now: i64,
+) -> Result<ReimportSummary, String> {
+    let (owner, repo) = (owner.trim(), repo.trim());
+    if owner.is_empty() || repo.is_empty() {
+        return Err("Owner and repository are required".to_string());
+    }
+    let pulls = list_upstream(client, owner, repo, now).await?;
+    println!("♻️ {}/{} lists {} open or recently closed PRs", owner, repo, pulls.len());
+
+    // New rows go where the repository's PRs usually live
+    let project_id = match db.suggest_project_for_repo(owner, repo).await.map_err(|e| e.to_string())? {
+        Some(project) => Some(project.id),
+        None => db.get_default_project().await.map_err(|e| e.to_string())?.map(|project| project.id),
+    };
+    let open_status = db.get_import_open_status().await.map_err(|e| e.to_string())?;
+
+    let mut upstream = Vec::with_capacity(pulls.len());
+    for pr in pulls {
+        let author_id = ensure_team_member_exists(db, &pr.user.clone().into()).await.map_err(|e| e.to_string())?;
+        let status = match (pr.merged_at.is_some(), pr.state.as_str()) {
+            (true, _) => PrStatus::Merged,
+            (false, "closed") => PrStatus::Archived,
+            _ => open_status.clone(),
+        };
+        upstream.push(UpstreamPr {
+            pr: NewPullRequest {
+                github_id: pr.id,
+                pr_number: pr.number,
+                title: Some(pr.title.clone()),
+                author_id,
+                project_id,
+                branch: Some(pr.head.ref_field.clone()),
+                status,
+                repository_owner: Some(owner.to_string()),
+                repository_name: Some(repo.to_string()),
+                github_created_at: pr.created_at_timestamp(),
+                github_updated_at: pr.updated_at_timestamp(),
+                ..Default::default()
+            },
+            merged_at: pr.merged_at_timestamp(),
+            merge_commit_sha: pr.merged_commit_sha().map(str::to_string),
+        });
+    }
+
+    db.replace_repository_prs(owner, repo, &upstream, preserve_local_fields, confirm_nonce)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::CONFIRM_REIMPORT_REPOSITORY;
+
+    const DAY: i64 = 86_400;
+    const NOW: i64 = 1_717_200_000; // 2024-06-01
+
+    struct MockClient;
+
+    fn pull(number: i64, state: &str, merged: bool, updated_days_ago: i64) -> serde_json::Value {
+        let updated_at = chrono::DateTime::from_timestamp(NOW - updated_days_ago * DAY, 0).unwrap().to_rfc3339();
+        serde_json::json!({
+            "id": 1000 + number,
+            "number": number,
+            "title": format!("PR {}", number),
+            "state": state,
+            "merged_at": merged.then(|| updated_at.clone()),
+            "created_at": updated_at,
+            "updated_at": updated_at,
+            "html_url": format!("https://github.com/acme/api/pull/{}", number),
+            "user": { "login": "octocat", "avatar_url": "" },
+            "head": { "ref": format!("branch-{}", number), "sha": "abc" },
+            "merge_commit_sha": format!("sha{}", number),
+        })
+    }
+
+    impl RepoPullsClient for MockClient {
+        async fn fetch_pulls_page(&self, _owner: &str, _repo: &str, state: &str, _page: u32) -> Result<Option<Vec<GitHubPRSummary>>, String> {
+            let pulls = match state {
+                "open" => vec![pull(4, "open", false, 1), pull(1, "open", false, 3)],
+                // #4 closed between the two listings; #2 is older than the window
+                _ => vec![pull(4, "closed", true, 0), pull(3, "closed", true, 5), pull(5, "closed", false, 10), pull(2, "closed", true, 40)],
+            };
+            Ok(Some(serde_json::from_value(serde_json::Value::Array(pulls)).unwrap()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reimport_lists_open_and_recently_closed() {
+        let db = Database::new_in_memory().await.unwrap();
+        let nonce = db.issue_confirmation_nonce(CONFIRM_REIMPORT_REPOSITORY).await.unwrap();
+
+        let summary = reimport_repository(&db, &MockClient, "acme", "api", true, &nonce, NOW).await.unwrap();
+        assert_eq!(summary.added, vec![1, 3, 4, 5]);
+
+        let prs = db.get_pull_requests().await.unwrap();
+        let status_of = |number: i64| prs.iter().find(|pr| pr.pr_number == number).map(|pr| (pr.status.as_str(), pr.merge_commit_sha.as_deref()));
+        assert_eq!(status_of(1), Some(("Waiting", None)));
+        assert_eq!(status_of(3), Some(("Merged", Some("sha3"))));
+        assert_eq!(status_of(4), Some(("Merged", Some("sha4"))));
+        assert_eq!(status_of(5), Some(("archived", None)));
+        assert_eq!(status_of(2), None);
+    }
+}
@@ -0,0 +1,315 @@
+// Finding the release that shipped a merged PR: the earliest published release whose tag
+// contains the merge commit, checked with the compare API. Each check is a GitHub call, so
+// lookups only run on demand, for one PR or a bounded batch per repository. A release once
+// found is stored on the PR and never looked up again; "not released yet" is not stored.
+use std::future::Future;
+
+use serde::Serialize;
+
+use crate::database::{Database, PullRequest};
+use crate::github::api_usage::ApiFeature;
+use crate::github::models::GitHubRelease;
+use crate::github::{fetch_compare_status, fetch_github_pr_data, fetch_releases_page, LIST_PAGE_SIZE, RATE_LIMITED_ERROR};
+
+// Older releases than these aren't listed; they predate anything still waiting for a release
+const MAX_RELEASE_PAGES: u32 = 10;
+// Compare calls per PR; a PR not in the first few releases after its merge reads as not released
+pub const MAX_COMPARES_PER_PR: usize = 20;
+pub const MAX_RELEASE_BATCH: i64 = 50;
+
+/// GitHub calls needed to place a PR in a release (mocked in tests)
+pub trait ReleaseClient {
+    /// None when GitHub doesn't report the PR merged
+    fn fetch_merge_commit_sha(&self, owner: &str, repo: &str, number: i64) -> impl Future<Output = Result<Option<String>, String>> + Send;
+    /// None when the token can't read the repository
+    fn fetch_releases(&self, owner: &str, repo: &str) -> impl Future<Output = Result<Option<Vec<GitHubRelease>>, String>> + Send;
+    fn tag_contains(&self, owner: &str, repo: &str, tag: &str, sha: &str) -> impl Future<Output = Result<bool, String>> + Send;
+}
+
+pub struct GitHubReleaseClient {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl ReleaseClient for GitHubReleaseClient {
+    async fn fetch_merge_commit_sha(&self, owner: &str, repo: &str, number: i64) -> Result<Option<String>, String> {
+        let data = fetch_github_pr_data(self.feature, &self.token, owner, repo, number).await?;
+        Ok(data.merged_commit_sha().map(str::to_string))
+    }
+
+    async fn fetch_releases(&self, owner: &str, repo: &str) -> Result<Option<Vec<GitHubRelease>>, String> {
+        let mut releases = Vec::new();
+        for page in 1..=MAX_RELEASE_PAGES {
+            let Some(batch) = fetch_releases_page(self.feature, &self.token, owner, repo, page).await? else {
+                return Ok(None);
+            };
+            let last_page = batch.len() < LIST_PAGE_SIZE;
+            releases.extend(batch);
+            if last_page {
+                break;
+            }
+        }
+        Ok(Some(releases))
+    }
+
+    async fn tag_contains(&self, owner: &str, repo: &str, tag: &str, sha: &str) -> Result<bool, String> {
+        // The commit is behind (or at) the tag when the tag contains it
+        let status = fetch_compare_status(self.feature, &self.token, owner, repo, tag, sha).await?;
+        Ok(status == "behind" || status == "identical")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ShippedRelease {
+    Shipped { pr_id: i64, release: String, cached: bool },
+    NotYetReleased { pr_id: i64, releases_checked: usize },
+    // The repository has no published releases to look in
+    NoReleases { pr_id: i64, message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReleaseLookupFailure {
+    pub pr_id: i64,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReleaseBatchResult {
+    pub results: Vec<ShippedRelease>,
+    pub failed: Vec<ReleaseLookupFailure>,
+    pub no_releases: bool,  // nothing was checked: the repository has no published releases
+    pub rate_limited: bool, // stopped early; the rest are picked up by the next batch
+}
+
+/// Published releases oldest first. Drafts are skipped, and so is anything published
+/// before the merge, which can't contain the merge commit.
+fn candidate_releases(releases: &[GitHubRelease], merged_at: Option<i64>) -> Vec<&GitHubRelease> {
+    let mut candidates: Vec<(i64, &GitHubRelease)> = releases.iter()
+        .filter(|release| !release.draft)
+        .filter_map(|release| Some((release.published_at_timestamp()?, release)))
+        .filter(|(published_at, _)| merged_at.is_none_or(|merged_at| *published_at >= merged_at))
+        .collect();
+    candidates.sort_by_key(|(published_at, _)| *published_at);
+    candidates.into_iter().map(|(_, release)| release).collect()
+}
+
+fn no_releases_message(owner: &str, repo: &str) -> String {
+    format!("{}/{} has no published releases", owner, repo)
+}
+
+fn repository(pr: &PullRequest) -> Result<(&str, &str), String> {
+    match (pr.repository_owner.as_deref(), pr.repository_name.as_deref()) {
+        (Some(owner), Some(repo)) => Ok((owner, repo)),
+        _ => Err("Repository fields missing; run repair_missing_repo_fields first".to_string()),
+    }
+}
+
+/// The stored merge commit, fetched (and stored) for PRs merged before it was recorded
+async fn merge_commit_sha<C: ReleaseClient>(db: &Database, client: &C, pr: &PullRequest) -> Result<String, String> {
+    if let Some(sha) = &pr.merge_commit_sha {
+        return Ok(sha.clone());
+    }
+    let (owner, repo) = repository(pr)?;
+    let sha = client.fetch_merge_commit_sha(owner, repo, pr.pr_number)
+        .await?
+        .ok_or_else(|| format!("PR #{} isn't merged on GitHub", pr.pr_number))?;
+    db.set_merge_commit_sha(pr.id, &sha).await.map_err(|e| e.to_string())?;
+    Ok(sha)
+}
+
+/// Check candidate releases oldest first and store the first whose tag contains the PR
+async fn find_release<C: ReleaseClient>(
+    db: &Database,
+    client: &C,
+    pr: &PullRequest,
+    sha: &str,
+    releases: &[GitHubRelease],
+) -> Result<ShippedRelease, String> {
+    let (owner, repo) = repository(pr)?;
+    let merged_at = db.get_pr_merged_at(pr.id).await.map_err(|e| e.to_string())?;
+    let mut releases_checked = 0;
+    for release in candidate_releases(releases, merged_at).into_iter().take(MAX_COMPARES_PER_PR) {
+        releases_checked += 1;
+        if client.tag_contains(owner, repo, &release.tag_name, sha).await? {
+            db.set_shipped_in_release(pr.id, &release.tag_name).await.map_err(|e| e.to_string())?;
+            println!("🏷️ PR #{} in {}/{} shipped in {}", pr.pr_number, owner, repo, release.tag_name);
+            return Ok(ShippedRelease::Shipped { pr_id: pr.id, release: release.tag_name.clone(), cached: false });
+        }
+    }
+    Ok(ShippedRelease::NotYetReleased { pr_id: pr.id, releases_checked })
+}
+
+async fn fetch_published_releases<C: ReleaseClient>(client: &C, owner: &str, repo: &str) -> Result<Vec<GitHubRelease>, String> {
+    let releases = client.fetch_releases(owner, repo)
+        .await?
+        .ok_or_else(|| format!("Cannot read releases of {}/{}; check the token's access to the repository", owner, repo))?;
+    Ok(releases.into_iter().filter(|release| !release.draft).collect())
+}
+
+/// Which release shipped one merged PR; answered from the database once known
+pub async fn resolve_shipped_release<C: ReleaseClient>(db: &Database, client: &C, pr_id: i64) -> Result<ShippedRelease, String> {
+    let pr = db.get_pull_request_by_id(pr_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} not found", pr_id))?;
+    if let Some(release) = &pr.shipped_in_release {
+        return Ok(ShippedRelease::Shipped { pr_id, release: release.clone(), cached: true });
+    }
+    if pr.is_issue {
+        return Err(format!("Item {} is a tracked issue, not a merged PR", pr_id));
+    }
+    if pr.merge_commit_sha.is_none() && pr.status != "Merged" {
+        return Err(format!("PR #{} isn't merged yet", pr.pr_number));
+    }
+    let (owner, repo) = repository(&pr)?;
+
+    let sha = merge_commit_sha(db, client, &pr).await?;
+    let releases = fetch_published_releases(client, owner, repo).await?;
+    if releases.is_empty() {
+        return Ok(ShippedRelease::NoReleases { pr_id, message: no_releases_message(owner, repo) });
+    }
+    find_release(db, client, &pr, &sha, &releases).await
+}
+
+/// Resolve up to `limit` merged PRs of one repository that have no release yet, oldest
+/// first. The release list is fetched once for the whole batch.
+pub async fn resolve_shipped_releases_for_repo<C: ReleaseClient>(
+    db: &Database,
+    client: &C,
+    owner: &str,
+    repo: &str,
+    limit: i64,
+) -> Result<ReleaseBatchResult, String> {
+    if !(1..=MAX_RELEASE_BATCH).contains(&limit) {
+        return Err(format!("limit must be between 1 and {}", MAX_RELEASE_BATCH));
+    }
+    let mut result = ReleaseBatchResult::default();
+    let prs = db.get_unreleased_merged_prs(owner, repo, limit).await.map_err(|e| e.to_string())?;
+    if prs.is_empty() {
+        return Ok(result);
+    }
+
+    let releases = fetch_published_releases(client, owner, repo).await?;
+    if releases.is_empty() {
+        println!("🏷️ {}", no_releases_message(owner, repo));
+        result.no_releases = true;
+        return Ok(result);
+    }
+
+    for pr in prs {
+        let outcome = match merge_commit_sha(db, client, &pr).await {
+            Ok(sha) => find_release(db, client, &pr, &sha, &releases).await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(shipped) => result.results.push(shipped),
+            Err(e) if e == RATE_LIMITED_ERROR => {
+                println!("⏳ Rate limited, stopping release lookup early");
+                result.rate_limited = true;
+                break;
+            }
+            Err(error) => result.failed.push(ReleaseLookupFailure { pr_id: pr.id, error }),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    // Tags in publish order; a tag contains a commit when the commit is listed under it
+    struct MockClient {
+        releases: Vec<(&'static str, &'static str, &'static [&'static str])>,
+        compares: AtomicU32,
+    }
+
+    impl ReleaseClient for MockClient {
+        async fn fetch_merge_commit_sha(&self, _owner: &str, _repo: &str, number: i64) -> Result<Option<String>, String> {
+            Ok(Some(format!("sha{}", number)))
+        }
+
+        async fn fetch_releases(&self, _owner: &str, _repo: &str) -> Result<Option<Vec<GitHubRelease>>, String> {
+            // Newest first, like GitHub, plus a draft that must be ignored
+            let mut releases: Vec<GitHubRelease> = self.releases.iter().rev()
+                .map(|(tag, published_at, _)| GitHubRelease {
+                    tag_name: tag.to_string(),
+                    name: None,
+                    draft: false,
+                    prerelease: false,
+                    published_at: Some(published_at.to_string()),
+                })
+                .collect();
+            releases.push(GitHubRelease { tag_name: "v9.9.9".to_string(), name: None, draft: true, prerelease: false, published_at: None });
+            Ok(Some(releases))
+        }
+
+        async fn tag_contains(&self, _owner: &str, _repo: &str, tag: &str, sha: &str) -> Result<bool, String> {
+            self.compares.fetch_add(1, Ordering::SeqCst);
+            let (_, _, commits) = self.releases.iter().find(|(t, _, _)| *t == tag).unwrap();
+            Ok(commits.contains(&sha))
+        }
+    }
+
+    async fn merged_pr(db: &Database, number: i64, merged_at: i64) -> i64 {
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: number,
+            pr_number: number,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        db.mark_pr_merged(pr.id, Some(merged_at)).await.unwrap();
+        pr.id
+    }
+
+    #[tokio::test]
+    async fn test_earliest_release_containing_the_merge_is_stored() {
+        let db = Database::new_in_memory().await.unwrap();
+        // 2024-01-10 and 2024-02-10 merges
+        let early = merged_pr(&db, 1, 1_704_844_800).await;
+        let late = merged_pr(&db, 2, 1_707_523_200).await;
+        let unreleased = merged_pr(&db, 3, 1_707_523_200).await;
+
+        let client = MockClient {
+            releases: vec![
+                ("v1.0.0", "2024-01-01T00:00:00Z", &[]),
+                ("v1.1.0", "2024-01-20T00:00:00Z", &["sha1"]),
+                ("v1.2.0", "2024-02-20T00:00:00Z", &["sha1", "sha2"]),
+            ],
+            compares: AtomicU32::new(0),
+        };
+
+        let shipped = resolve_shipped_release(&db, &client, early).await.unwrap();
+        assert_eq!(shipped, ShippedRelease::Shipped { pr_id: early, release: "v1.1.0".to_string(), cached: false });
+        let pr = db.get_pull_request_by_id(early).await.unwrap().unwrap();
+        assert_eq!((pr.merge_commit_sha.as_deref(), pr.shipped_in_release.as_deref()), (Some("sha1"), Some("v1.1.0")));
+        // v1.0.0 predates the merge and is never compared
+        assert_eq!(client.compares.load(Ordering::SeqCst), 1);
+
+        // Cached from now on
+        let cached = resolve_shipped_release(&db, &client, early).await.unwrap();
+        assert_eq!(cached, ShippedRelease::Shipped { pr_id: early, release: "v1.1.0".to_string(), cached: true });
+        assert_eq!(client.compares.load(Ordering::SeqCst), 1);
+
+        let batch = resolve_shipped_releases_for_repo(&db, &client, "acme", "api", 10).await.unwrap();
+        assert_eq!(batch.results, vec![
+            ShippedRelease::Shipped { pr_id: late, release: "v1.2.0".to_string(), cached: false },
+            ShippedRelease::NotYetReleased { pr_id: unreleased, releases_checked: 1 },
+        ]);
+        assert!(batch.failed.is_empty());
+
+        let nothing = MockClient { releases: vec![], compares: AtomicU32::new(0) };
+        let result = resolve_shipped_release(&db, &nothing, unreleased).await.unwrap();
+        assert_eq!(result, ShippedRelease::NoReleases { pr_id: unreleased, message: "acme/api has no published releases".to_string() });
+        assert!(resolve_shipped_releases_for_repo(&db, &nothing, "acme", "api", 10).await.unwrap().no_releases);
+    }
+}
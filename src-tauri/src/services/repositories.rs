@@ -0,0 +1,144 @@
+// Repository access auditing
+use std::future::Future;
+
+use serde::Serialize;
+
+use crate::database::{Database, TrackedRepository};
+use crate::github::api_usage::ApiFeature;
+use crate::github::{check_repo_access, RATE_LIMITED_ERROR};
+
+/// Per-repository access probe (mocked in tests)
+pub trait RepoAccessChecker {
+    fn check_repo_access(&self, owner: &str, repo: &str) -> impl Future<Output = Result<bool, String>> + Send;
+}
+
+pub struct GitHubRepoAccessChecker {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl RepoAccessChecker for GitHubRepoAccessChecker {
+    async fn check_repo_access(&self, owner: &str, repo: &str) -> Result<bool, String> {
+        check_repo_access(self.feature, &self.token, owner, repo).await
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepoAccessReport {
+    pub checked: u32,
+    pub inaccessible_count: u32,
+    pub newly_inaccessible: Vec<TrackedRepository>,
+    pub errors: Vec<String>,
+    pub rate_limited: bool, // audit stopped early; unchecked repos keep their previous state
+}
+
+/// Probe every tracked repository once and record whether it is readable
+pub async fn audit_repo_access<C: RepoAccessChecker>(db: &Database, checker: &C, now: i64) -> Result<RepoAccessReport, String> {
+    let repositories = db.get_tracked_repositories().await.map_err(|e| e.to_string())?;
+    let mut report = RepoAccessReport::default();
+
+    for repository in repositories {
+        match checker.check_repo_access(&repository.owner, &repository.name).await {
+            Ok(accessible) => {
+                db.set_repository_access(repository.id, accessible, now).await.map_err(|e| e.to_string())?;
+                report.checked += 1;
+                if !accessible && repository.accessible != Some(false) {
+                    println!("🚫 Lost access to {}/{}", repository.owner, repository.name);
+                    report.newly_inaccessible.push(TrackedRepository {
+                        accessible: Some(false),
+                        last_checked_at: Some(now),
+                        ..repository
+                    });
+                }
+            }
+            Err(e) if e == RATE_LIMITED_ERROR => {
+                println!("⏳ Rate limited, stopping repository audit early");
+                report.rate_limited = true;
+                break;
+            }
+            Err(e) => report.errors.push(format!("{}/{}: {}", repository.owner, repository.name, e)),
+        }
+    }
+
+    report.inaccessible_count = db.get_inaccessible_repositories().await.map_err(|e| e.to_string())?.len() as u32;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::NewPullRequest;
+    use std::collections::HashMap;
+
+    struct MockChecker(HashMap<&'static str, Result<bool, String>>);
+
+    impl RepoAccessChecker for MockChecker {
+        async fn check_repo_access(&self, _owner: &str, repo: &str) -> Result<bool, String> {
+            self.0.get(repo).cloned().unwrap_or(Ok(true))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_reports_newly_inaccessible_repos() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        for (i, repo) in ["api", "web", "web", "infra"].iter().enumerate() {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64,
+                author_id: author.id,
+                status: PrStatus::Waiting,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some(repo.to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let report = audit_repo_access(&db, &MockChecker(HashMap::new()), 100).await.unwrap();
+        assert_eq!(report.checked, 3);
+        assert!(report.newly_inaccessible.is_empty());
+
+        let checker = MockChecker(HashMap::from([
+            ("web", Ok(false)),
+            ("infra", Err("GitHub API error checking acme/infra: 502".to_string())),
+        ]));
+        let report = audit_repo_access(&db, &checker, 200).await.unwrap();
+        assert_eq!(report.newly_inaccessible.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), vec!["web"]);
+        assert_eq!(report.inaccessible_count, 1);
+        assert_eq!(report.errors.len(), 1);
+
+        // Already known to be inaccessible, so not reported again
+        let report = audit_repo_access(&db, &checker, 300).await.unwrap();
+        assert!(report.newly_inaccessible.is_empty());
+        assert_eq!(report.inaccessible_count, 1);
+
+        let repos = db.get_tracked_repositories().await.unwrap();
+        let infra = repos.iter().find(|r| r.name == "infra").unwrap();
+        assert_eq!((infra.accessible, infra.last_checked_at), (Some(true), Some(100)));
+    }
+
+    #[tokio::test]
+    async fn test_audit_stops_when_rate_limited() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        for (i, repo) in ["api", "web"].iter().enumerate() {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64,
+                pr_number: i as i64,
+                author_id: author.id,
+                status: PrStatus::Waiting,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some(repo.to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let checker = MockChecker(HashMap::from([("api", Err(RATE_LIMITED_ERROR.to_string()))]));
+        let report = audit_repo_access(&db, &checker, 100).await.unwrap();
+        assert!(report.rate_limited);
+        assert_eq!(report.checked, 0);
+        assert!(db.get_tracked_repositories().await.unwrap().iter().all(|r| r.accessible.is_none()));
+    }
+}
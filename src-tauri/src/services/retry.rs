@@ -0,0 +1,403 @@
+// Retry queue for background GitHub operations that failed transiently
+use std::future::Future;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::database::{Database, PullRequest, RetryEntry};
+use crate::github::api_usage::ApiFeature;
+use crate::github::{fetch_github_issue_reactions, fetch_github_pr_data, fetch_pr_reviews};
+use crate::github::models::{GitHubPRData, GitHubReactions, GitHubReview};
+use crate::github::GitHubTokenManager;
+use crate::services::pull_requests::{apply_pr_data, fetch_pr_update, is_retryable_fetch_error, PrDataClient};
+use crate::DbState;
+
+// Operation names stored in retry_queue.operation
+pub const OP_REACTIONS: &str = "reactions";
+pub const OP_REFRESH: &str = "refresh";
+
+// How often the background worker drains due entries
+const RETRY_WORKER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// GitHub calls needed to replay queued operations (mocked in tests); refreshes use the PR calls
+pub trait EnrichmentClient: PrDataClient {
+    fn fetch_reactions(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: i64,
+    ) -> impl Future<Output = Result<Option<GitHubReactions>, String>> + Send;
+}
+
+pub struct GitHubEnrichmentClient {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl EnrichmentClient for GitHubEnrichmentClient {
+    async fn fetch_reactions(&self, owner: &str, repo: &str, number: i64) -> Result<Option<GitHubReactions>, String> {
+        fetch_github_issue_reactions(self.feature, &self.token, owner, repo, number).await
+    }
+}
+
+impl PrDataClient for GitHubEnrichmentClient {
+    async fn fetch_pr(&self, owner: &str, repo: &str, number: i64) -> Result<GitHubPRData, String> {
+        fetch_github_pr_data(self.feature, &self.token, owner, repo, number).await
+    }
+
+    async fn fetch_reviews(&self, owner: &str, repo: &str, number: i64) -> Result<Vec<GitHubReview>, String> {
+        fetch_pr_reviews(self.feature, &self.token, owner, repo, number).await
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetryDrainSummary {
+    pub succeeded: u32,
+    pub rescheduled: u32,
+    pub parked: u32,
+}
+
+// Where an entry's GitHub call goes, read from the PR under the lock
+struct Target {
+    pr: PullRequest,
+    owner: String,
+    repo: String,
+}
+
+// What an operation fetched, written back by finish
+enum Fetched {
+    Reactions(Option<GitHubReactions>),
+    Pr(Box<GitHubPRData>, Vec<GitHubReview>),
+}
+
+async fn target(db: &Database, pr_id: i64) -> Result<Target, String> {
+    let pr = db.get_pull_request_by_id(pr_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} not found", pr_id))?;
+    match (pr.repository_owner.clone(), pr.repository_name.clone()) {
+        (Some(owner), Some(repo)) => Ok(Target { pr, owner, repo }),
+        _ => Err("Repository fields missing; run repair_missing_repo_fields first".to_string()),
+    }
+}
+
+// The GitHub half of an operation; needs no database
+async fn fetch<C: EnrichmentClient>(client: &C, operation: &str, target: &Target) -> Result<Fetched, String> {
+    match operation {
+        OP_REACTIONS => client.fetch_reactions(&target.owner, &target.repo, target.pr.pr_number).await.map(Fetched::Reactions),
+        OP_REFRESH => fetch_pr_update(client, &target.pr).await.map(|(data, reviews)| Fetched::Pr(Box::new(data), reviews)),
+        other => Err(format!("Unknown retry operation '{}'", other)),
+    }
+}
+
+// A refresh is written over the PR as it is now, since it may have been edited meanwhile
+async fn apply_refresh(db: &Database, pr_id: i64, data: &GitHubPRData, reviews: &[GitHubReview]) -> Result<(), String> {
+    let pr = db.get_pull_request_by_id(pr_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Pull request {} not found", pr_id))?;
+    apply_pr_data(db, &pr, data, reviews).await.map(|_| ())
+}
+
+// Write the fetch, removing the entry on success and rescheduling/parking it on failure.
+// Returns the updated entry when it failed again.
+async fn finish(db: &Database, entry: &RetryEntry, fetched: Result<Fetched, String>, now: i64) -> Result<Option<RetryEntry>, String> {
+    let outcome = match fetched {
+        Ok(Fetched::Reactions(reactions)) => db.update_pr_reactions(
+            entry.pr_id,
+            reactions.as_ref().map(|r| r.total_count),
+            reactions.as_ref().map(|r| r.plus_one),
+        ).await.map_err(|e| e.to_string()),
+        Ok(Fetched::Pr(data, reviews)) => apply_refresh(db, entry.pr_id, &data, &reviews).await,
+        Err(e) => Err(e),
+    };
+    match outcome {
+        Ok(()) => {
+            db.complete_retry(entry.pr_id, &entry.operation).await.map_err(|e| e.to_string())?;
+            Ok(None)
+        }
+        Err(error) => {
+            let updated = db.record_retry_failure(entry.pr_id, &entry.operation, &error, now)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(Some(updated))
+        }
+    }
+}
+
+/// Queue a refresh whose fetch failed so the worker replays it with backoff. Failures a retry
+/// can't fix (issues, missing repository fields, a 404) aren't queued.
+pub async fn queue_refresh_retry(db: &Database, pr: &PullRequest, error: &str, now: i64) {
+    if !is_retryable_fetch_error(pr, error) {
+        return;
+    }
+    if let Err(e) = db.record_retry_failure(pr.id, OP_REFRESH, error, now).await {
+        println!("⚠️ Could not queue refresh retry: {}", e);
+    }
+}
+
+/// Replay every due entry once. The database is locked to read each entry's PR and to write
+/// the outcome, never while a request is out.
+pub async fn drain_retry_queue<C: EnrichmentClient>(state: &DbState, client: &C, now: i64) -> Result<RetryDrainSummary, String> {
+    let due = {
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        db.get_due_retries(now).await.map_err(|e| e.to_string())?
+    };
+    drain_entries(state, client, &due, now).await
+}
+
+async fn drain_entries<C: EnrichmentClient>(state: &DbState, client: &C, due: &[RetryEntry], now: i64) -> Result<RetryDrainSummary, String> {
+    let mut summary = RetryDrainSummary::default();
+    for entry in due {
+        let target = {
+            let db_state = state.lock().await;
+            let db = db_state.as_ref().ok_or("Database not initialized")?;
+            target(db, entry.pr_id).await
+        };
+        let fetched = match target {
+            Ok(target) => fetch(client, &entry.operation, &target).await,
+            Err(e) => Err(e),
+        };
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        match finish(db, entry, fetched, now).await? {
+            None => summary.succeeded += 1,
+            Some(updated) if updated.parked => summary.parked += 1,
+            Some(_) => summary.rescheduled += 1,
+        }
+    }
+
+    if !due.is_empty() {
+        println!(
+            "🔁 Retry queue: {} succeeded, {} rescheduled, {} parked",
+            summary.succeeded, summary.rescheduled, summary.parked
+        );
+    }
+    Ok(summary)
+}
+
+/// Unpark an entry and run it immediately. Returns the entry if it failed again.
+pub async fn retry_now<C: EnrichmentClient>(db: &Database, client: &C, id: i64, now: i64) -> Result<Option<RetryEntry>, String> {
+    db.reschedule_retry_now(id, now).await.map_err(|e| e.to_string())?;
+    let entry = db.get_retry_entry(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Retry entry {} not found", id))?;
+
+    let fetched = match target(db, entry.pr_id).await {
+        Ok(target) => fetch(client, &entry.operation, &target).await,
+        Err(e) => Err(e),
+    };
+    finish(db, &entry, fetched, now).await
+}
+
+/// Background loop draining the queue with the stored token; skips cycles until the
+/// database is initialized. The keychain is read once per cycle, and only when something
+/// is due, so an idle queue never touches it.
+pub async fn run_retry_worker(state: DbState) {
+    let mut interval = tokio::time::interval(RETRY_WORKER_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let now = chrono::Utc::now().timestamp();
+        let due = {
+            let db_state = state.lock().await;
+            let Some(db) = db_state.as_ref() else { continue };
+            match db.get_due_retries(now).await {
+                Ok(due) => due,
+                Err(e) => {
+                    println!("❌ Retry worker failed: {}", e);
+                    continue;
+                }
+            }
+        };
+        if due.is_empty() {
+            continue;
+        }
+
+        let token = match GitHubTokenManager::new().and_then(|m| m.get_token()) {
+            Ok(Some(token)) => token,
+            Ok(None) => continue,
+            Err(e) => {
+                println!("⚠️ Retry worker could not read GitHub token: {}", e);
+                continue;
+            }
+        };
+
+        let client = GitHubEnrichmentClient { token, feature: ApiFeature::RetryQueue };
+        if let Err(e) = drain_entries(&state, &client, &due, now).await {
+            println!("❌ Retry worker failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::{NewPullRequest, RETRY_MAX_ATTEMPTS, retry_backoff_secs};
+    use crate::github::models::{GitHubHead, GitHubPRUser};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    // Fails the first `failures` calls, then returns fixed reactions or a renamed PR
+    struct MockClient {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl MockClient {
+        fn failing(failures: u32) -> Self {
+            MockClient { failures, calls: AtomicU32::new(0) }
+        }
+    }
+
+    impl EnrichmentClient for MockClient {
+        async fn fetch_reactions(&self, _owner: &str, _repo: &str, _number: i64) -> Result<Option<GitHubReactions>, String> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                Err("API rate limit exceeded".to_string())
+            } else {
+                Ok(Some(GitHubReactions { total_count: 4, plus_one: 3 }))
+            }
+        }
+    }
+
+    impl PrDataClient for MockClient {
+        async fn fetch_pr(&self, _owner: &str, _repo: &str, number: i64) -> Result<GitHubPRData, String> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                return Err("API rate limit exceeded".to_string());
+            }
+            Ok(GitHubPRData {
+                id: number,
+                title: "Renamed".to_string(),
+                user: GitHubPRUser { login: "octocat".to_string(), avatar_url: String::new(), name: None },
+                head: GitHubHead { ref_field: "main".to_string(), sha: "abc".to_string() },
+                state: "open".to_string(),
+                created_at: None,
+                updated_at: None,
+                merged: false,
+                merged_at: None,
+                merge_commit_sha: None,
+                reactions: None,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+            })
+        }
+
+        async fn fetch_reviews(&self, _owner: &str, _repo: &str, _number: i64) -> Result<Vec<GitHubReview>, String> {
+            Ok(Vec::new())
+        }
+    }
+
+    async fn seed_pr(db: &Database) -> i64 {
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 7,
+            title: Some("Flaky".to_string()),
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            repository_owner: Some("owner".to_string()),
+            repository_name: Some("repo".to_string()),
+            ..Default::default()
+        }).await.unwrap().id
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        assert_eq!(retry_backoff_secs(1), 60);
+        assert_eq!(retry_backoff_secs(2), 120);
+        assert_eq!(retry_backoff_secs(4), 480);
+        assert_eq!(retry_backoff_secs(50), 6 * 60 * 60);
+    }
+
+    #[tokio::test]
+    async fn test_failures_back_off_then_park() {
+        let db = Database::new_in_memory().await.unwrap();
+        let pr_id = seed_pr(&db).await;
+        let client = MockClient::failing(u32::MAX);
+
+        let entry = db.record_retry_failure(pr_id, OP_REACTIONS, "timeout", 1_000).await.unwrap();
+        assert_eq!(entry.attempts, 1);
+        assert_eq!(entry.next_attempt_at, 1_060);
+        let state: DbState = std::sync::Arc::new(tokio::sync::Mutex::new(Some(db)));
+
+        // Not due yet
+        let summary = drain_retry_queue(&state, &client, 1_059).await.unwrap();
+        assert_eq!(summary.rescheduled + summary.succeeded + summary.parked, 0);
+
+        let mut now = 1_060;
+        for attempts in 2..RETRY_MAX_ATTEMPTS {
+            let summary = drain_retry_queue(&state, &client, now).await.unwrap();
+            assert_eq!(summary.rescheduled, 1);
+            let entry = state.lock().await.as_ref().unwrap().get_due_retries(i64::MAX).await.unwrap().remove(0);
+            assert_eq!(entry.attempts, attempts);
+            assert_eq!(entry.next_attempt_at, now + retry_backoff_secs(attempts));
+            assert_eq!(entry.last_error.as_deref(), Some("API rate limit exceeded"));
+            now = entry.next_attempt_at;
+        }
+
+        let summary = drain_retry_queue(&state, &client, now).await.unwrap();
+        assert_eq!(summary.parked, 1);
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().unwrap();
+        assert!(db.get_due_retries(i64::MAX).await.unwrap().is_empty());
+
+        let failed = db.get_failed_operations().await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].attempts, RETRY_MAX_ATTEMPTS);
+        assert_eq!(client.calls.load(Ordering::SeqCst), (RETRY_MAX_ATTEMPTS - 1) as u32);
+    }
+
+    #[tokio::test]
+    async fn test_retry_now_completes_parked_entry() {
+        let db = Database::new_in_memory().await.unwrap();
+        let pr_id = seed_pr(&db).await;
+        for _ in 0..RETRY_MAX_ATTEMPTS {
+            db.record_retry_failure(pr_id, OP_REACTIONS, "timeout", 0).await.unwrap();
+        }
+        let parked = db.get_failed_operations().await.unwrap().remove(0);
+
+        // A parked entry that fails again goes straight back to parked
+        let again = retry_now(&db, &MockClient::failing(1), parked.id, 10).await.unwrap();
+        assert!(again.unwrap().parked);
+
+        let result = retry_now(&db, &MockClient::failing(0), parked.id, 20).await.unwrap();
+        assert!(result.is_none());
+        assert!(db.get_failed_operations().await.unwrap().is_empty());
+
+        let pr = db.get_pull_request_by_id(pr_id).await.unwrap().unwrap();
+        assert_eq!((pr.reactions_total, pr.reactions_plus_one), (Some(4), Some(3)));
+
+        assert!(retry_now(&db, &MockClient::failing(0), parked.id, 30).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_is_queued_and_replayed() {
+        let db = Database::new_in_memory().await.unwrap();
+        let pr_id = seed_pr(&db).await;
+        let pr = db.get_pull_request_by_id(pr_id).await.unwrap().unwrap();
+
+        // A 404 won't pass on a retry, so it isn't queued
+        queue_refresh_retry(&db, &pr, "PR #7 is no longer on GitHub in owner/repo, or the token can't see it", 1_000).await;
+        assert!(db.get_due_retries(i64::MAX).await.unwrap().is_empty());
+
+        queue_refresh_retry(&db, &pr, "timeout", 1_000).await;
+        let entry = db.get_due_retries(i64::MAX).await.unwrap().remove(0);
+        assert_eq!((entry.operation.as_str(), entry.attempts), (OP_REFRESH, 1));
+
+        let state: DbState = std::sync::Arc::new(tokio::sync::Mutex::new(Some(db)));
+        let summary = drain_retry_queue(&state, &MockClient::failing(0), entry.next_attempt_at).await.unwrap();
+        assert_eq!(summary.succeeded, 1);
+
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().unwrap();
+        assert!(db.get_due_retries(i64::MAX).await.unwrap().is_empty());
+        let pr = db.get_pull_request_by_id(pr_id).await.unwrap().unwrap();
+        assert_eq!(pr.title.as_deref(), Some("Renamed"));
+    }
+}
@@ -0,0 +1,193 @@
+// Opt-in sync of review comment counts per team member from GitHub, for contribution
+// stats. Only authors and timestamps are read; see database/review_comments.rs for why
+// nothing else is kept.
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+
+use serde::Serialize;
+
+use crate::database::review_comments::week_start;
+use crate::database::Database;
+use crate::github::api_usage::ApiFeature;
+use crate::github::models::GitHubReviewComment;
+use crate::github::{fetch_review_comments_page, LIST_PAGE_SIZE, RATE_LIMITED_ERROR};
+
+pub const MAX_WINDOW_DAYS: i64 = 365;
+
+// Stop paging a repository after this many pages (10k comments) and report it as truncated
+const MAX_PAGES_PER_REPO: u32 = 100;
+
+pub trait ReviewCommentClient {
+    /// One page of comments updated since `since`; Ok(None) when the repository can't be read
+    fn fetch_page(&self, owner: &str, repo: &str, since: &str, page: u32)
+        -> impl Future<Output = Result<Option<Vec<GitHubReviewComment>>, String>> + Send;
+}
+
+pub struct GitHubReviewCommentClient {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl ReviewCommentClient for GitHubReviewCommentClient {
+    async fn fetch_page(&self, owner: &str, repo: &str, since: &str, page: u32) -> Result<Option<Vec<GitHubReviewComment>>, String> {
+        fetch_review_comments_page(self.feature, &self.token, owner, repo, since, page).await
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CommentStatsSyncResult {
+    pub window_start: i64, // Monday 00:00 UTC; whole weeks are recounted
+    pub repositories_synced: u32,
+    pub comments_counted: i64,
+    pub skipped_authors: Vec<String>, // commenters who aren't team members, lowercased
+    pub inaccessible: Vec<String>,    // "owner/repo"
+    pub truncated: Vec<String>,       // hit MAX_PAGES_PER_REPO; counts are a lower bound
+    pub rate_limited: bool,           // stopped early; unsynced repositories keep their old counts
+}
+
+/// Recount review comments on every tracked repository for the weeks covering the last
+/// `window_days` days. Each repository's counts are replaced only once all its pages are read.
+pub async fn sync_review_comment_stats<C: ReviewCommentClient>(db: &Database, client: &C, window_days: i64, now: i64) -> Result<CommentStatsSyncResult, String> {
+    if !(1..=MAX_WINDOW_DAYS).contains(&window_days) {
+        return Err(format!("window_days must be between 1 and {}", MAX_WINDOW_DAYS));
+    }
+    let window_start = week_start(now - window_days * 86_400);
+    let since = chrono::DateTime::from_timestamp(window_start, 0)
+        .ok_or("Invalid window start")?
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let members = db.team_member_ids_by_login().await.map_err(|e| e.to_string())?;
+    let repositories = db.get_tracked_repositories().await.map_err(|e| e.to_string())?;
+
+    let mut result = CommentStatsSyncResult { window_start, ..Default::default() };
+    let mut skipped = BTreeSet::new();
+    'repos: for repository in repositories.iter().filter(|r| r.accessible != Some(false)) {
+        let name = format!("{}/{}", repository.owner, repository.name);
+        let mut counts: BTreeMap<(i64, i64), i64> = BTreeMap::new();
+        let mut page = 1;
+        loop {
+            let comments = match client.fetch_page(&repository.owner, &repository.name, &since, page).await {
+                Ok(Some(comments)) => comments,
+                Ok(None) => {
+                    result.inaccessible.push(name);
+                    continue 'repos;
+                }
+                Err(e) if e == RATE_LIMITED_ERROR => {
+                    result.rate_limited = true;
+                    break 'repos;
+                }
+                Err(e) => return Err(format!("{}: {}", name, e)),
+            };
+
+            // `since` filters on update time, so older comments edited recently come back too
+            for comment in &comments {
+                let (Some(user), Some(created_at)) = (&comment.user, comment.created_at_timestamp()) else { continue };
+                if created_at < window_start {
+                    continue;
+                }
+                let login = user.login.to_lowercase();
+                match members.get(&login) {
+                    Some(member_id) => *counts.entry((*member_id, week_start(created_at))).or_default() += 1,
+                    None => {
+                        skipped.insert(login);
+                    }
+                }
+            }
+
+            if comments.len() < LIST_PAGE_SIZE {
+                break;
+            }
+            if page == MAX_PAGES_PER_REPO {
+                result.truncated.push(name.clone());
+                break;
+            }
+            page += 1;
+        }
+
+        db.replace_review_comment_stats(&repository.owner, &repository.name, window_start, &counts, now)
+            .await
+            .map_err(|e| e.to_string())?;
+        result.repositories_synced += 1;
+        result.comments_counted += counts.values().sum::<i64>();
+    }
+    result.skipped_authors = skipped.into_iter().collect();
+
+    println!(
+        "💬 Synced review comment stats for {} repositories ({} comments){}",
+        result.repositories_synced,
+        result.comments_counted,
+        if result.rate_limited { ", stopped by the rate limit" } else { "" }
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::github::models::GitHubCommentAuthor;
+    use crate::database::NewPullRequest;
+
+    // 2024-03-06 (Wednesday) 12:00 UTC
+    const NOW: i64 = 1_709_726_400;
+    const WEEK: i64 = 7 * 86_400;
+
+    fn comment(login: &str, created_at: i64) -> GitHubReviewComment {
+        GitHubReviewComment {
+            user: Some(GitHubCommentAuthor { login: login.to_string() }),
+            created_at: chrono::DateTime::from_timestamp(created_at, 0).map(|dt| dt.to_rfc3339()),
+        }
+    }
+
+    // api: one full page then a partial one; web: unreadable; ops: rate limited
+    struct MockComments;
+
+    impl ReviewCommentClient for MockComments {
+        async fn fetch_page(&self, _owner: &str, repo: &str, since: &str, page: u32) -> Result<Option<Vec<GitHubReviewComment>>, String> {
+            assert_eq!(since, "2024-02-26T00:00:00Z");
+            match (repo, page) {
+                ("api", 1) => Ok(Some((0..LIST_PAGE_SIZE).map(|_| comment("Alice", NOW - 3600)).collect())),
+                ("api", 2) => Ok(Some(vec![
+                    comment("bob", NOW - WEEK),
+                    comment("mallory", NOW - 3600),
+                    comment("alice", NOW - 10 * WEEK), // edited recently, written long ago
+                ])),
+                ("web", _) => Ok(None),
+                ("ops", _) => Err(RATE_LIMITED_ERROR.to_string()),
+                _ => panic!("unexpected page {} of {}", page, repo),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_counts_members_per_week() {
+        let db = Database::new_in_memory().await.unwrap();
+        let alice = db.get_or_create_team_member("alice".to_string()).await.unwrap();
+        let bob = db.get_or_create_team_member("bob".to_string()).await.unwrap();
+        for (github_id, repo) in [(1, "api"), (2, "ops"), (3, "web")] {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id,
+                pr_number: github_id,
+                author_id: alice.id,
+                repository_owner: Some("acme".to_string()),
+                repository_name: Some(repo.to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+        // ops keeps what an earlier sync stored when this one is cut short
+        db.replace_review_comment_stats("acme", "ops", 0, &BTreeMap::from([((bob.id, week_start(NOW)), 4)]), 0).await.unwrap();
+
+        let result = sync_review_comment_stats(&db, &MockComments, 7, NOW).await.unwrap();
+        assert_eq!(result.window_start, week_start(NOW) - WEEK);
+        assert_eq!((result.repositories_synced, result.comments_counted), (1, 101));
+        assert_eq!(result.skipped_authors, vec!["mallory"]);
+        assert!(result.rate_limited);
+        // Repositories run in name order, so the rate limit stopped the sync before web
+        assert!(result.inaccessible.is_empty());
+
+        assert_eq!(db.get_review_comment_count(alice.id, NOW - WEEK, NOW).await.unwrap(), 100);
+        assert_eq!(db.get_review_comment_count(bob.id, NOW - WEEK, NOW).await.unwrap(), 5);
+        assert_eq!(db.get_review_comment_count(bob.id, week_start(NOW), NOW).await.unwrap(), 4);
+
+        assert!(sync_review_comment_stats(&db, &MockComments, 0, NOW).await.is_err());
+    }
+}
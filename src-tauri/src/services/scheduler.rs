@@ -0,0 +1,276 @@
+// Background runner for scheduled_tasks (one-off PR refreshes at a chosen time)
+use std::time::Duration;
+
+use crate::database::scheduled_tasks::TASK_REFRESH_PR;
+use crate::github::api_usage::ApiFeature;
+use crate::github::{GitHubTokenManager, RATE_LIMITED_ERROR};
+use crate::services::power::{self, PowerState};
+use crate::services::pull_requests::{apply_fetched_pr, fetch_pr_update, GitHubPrDataClient, PrDataClient};
+use crate::services::retry::queue_refresh_retry;
+use crate::services::sync_runs::{record_skipped_run, SyncRunLog};
+use crate::DbState;
+
+// How often the scheduler looks for due tasks
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Run every task due at `now` once; returns how many ran. Tasks stay pending
+/// while GitHub is rate limited so they go out once the pause ends.
+/// Ticks that refreshed anything are saved as a scheduled_refresh sync run.
+/// The database is locked around each read and write, never while a request is out.
+pub async fn run_due_tasks<C: PrDataClient>(state: &DbState, client: &C, now: i64) -> Result<u32, String> {
+    let due = {
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        db.get_due_scheduled_tasks(now).await.map_err(|e| e.to_string())?
+    };
+    let mut ran = 0;
+    let mut log = SyncRunLog::start(ApiFeature::ScheduledRefresh, now);
+
+    for task in due {
+        let before = {
+            let db_state = state.lock().await;
+            let db = db_state.as_ref().ok_or("Database not initialized")?;
+            db.get_pull_request_by_id(task.pr_id).await.map_err(|e| e.to_string())?
+        };
+        let result = match (task.task_type.as_str(), &before) {
+            (TASK_REFRESH_PR, Some(pr)) => match fetch_pr_update(client, pr).await {
+                Ok((data, reviews)) => apply_fetched_pr(state, pr.id, &data, &reviews).await,
+                Err(e) => {
+                    // Rate-limited tasks stay pending instead
+                    if e != RATE_LIMITED_ERROR {
+                        if let Some(db) = state.lock().await.as_ref() {
+                            queue_refresh_retry(db, pr, &e, now).await;
+                        }
+                    }
+                    Err(e)
+                }
+            },
+            (TASK_REFRESH_PR, None) => Err(format!("Pull request {} not found", task.pr_id)),
+            (other, _) => Err(format!("Unknown scheduled task type '{}'", other)),
+        };
+        if result.as_ref().is_err_and(|e| e == RATE_LIMITED_ERROR) {
+            if !log.is_empty() {
+                log.note("Stopped by the GitHub rate limit; remaining tasks stay pending");
+            }
+            break;
+        }
+
+        if let Err(e) = &result {
+            println!("❌ Scheduled {} for PR {} failed: {}", task.task_type, task.pr_id, e);
+        }
+        if let Some(before) = &before {
+            match &result {
+                Ok(after) => log.refreshed(before, after),
+                Err(e) => log.failed(before, e),
+            }
+        }
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        db.complete_scheduled_task(task.id, result.err().as_deref(), now)
+            .await
+            .map_err(|e| e.to_string())?;
+        ran += 1;
+    }
+
+    if !log.is_empty() {
+        if let Some(db) = state.lock().await.as_ref() {
+            log.finish(db, now).await;
+        }
+    }
+    Ok(ran)
+}
+
+/// What the loop remembers between ticks for the sync constraints
+#[derive(Debug, Clone, Default)]
+pub struct SchedulerCycle {
+    last_run_at: Option<i64>,  // last tick that ran its due tasks
+    last_skip: Option<String>, // reason already saved for the current stretch of skips
+}
+
+/// One tick: run the due tasks unless the sync constraints hold them back. Only ticks with
+/// work due are considered, and a skip is saved once per reason rather than every 30s.
+/// The power state is only looked up while some constraint is set.
+pub async fn run_cycle<C: PrDataClient>(state: &DbState, client: &C, cycle: &mut SchedulerCycle, now: i64) -> Result<u32, String> {
+    let constraints = {
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        if db.get_due_scheduled_tasks(now).await.map_err(|e| e.to_string())?.is_empty() {
+            return Ok(0);
+        }
+        db.get_sync_constraints().await.map_err(|e| e.to_string())?
+    };
+
+    let power = if constraints.is_active() { power::current_power_state(now).await } else { PowerState::UNKNOWN };
+    if let Some(reason) = power::skip_reason(&constraints, &power, cycle.last_run_at, now) {
+        if cycle.last_skip.as_deref() != Some(reason.as_str()) {
+            println!("🔋 Scheduled refresh skipped: {}", reason);
+            if let Some(db) = state.lock().await.as_ref() {
+                record_skipped_run(db, ApiFeature::ScheduledRefresh, &reason, now).await;
+            }
+            cycle.last_skip = Some(reason);
+        }
+        return Ok(0);
+    }
+
+    cycle.last_skip = None;
+    cycle.last_run_at = Some(now);
+    run_due_tasks(state, client, now).await
+}
+
+/// Background loop spawned at startup; tasks wait until a token is configured. The keychain
+/// is only read on ticks with tasks due.
+pub async fn run_scheduler(state: DbState) {
+    let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+    let mut cycle = SchedulerCycle::default();
+    loop {
+        interval.tick().await;
+
+        let now = chrono::Utc::now().timestamp();
+        let has_due = match state.lock().await.as_ref() {
+            Some(db) => db.get_due_scheduled_tasks(now).await.is_ok_and(|due| !due.is_empty()),
+            None => false,
+        };
+        if !has_due {
+            continue;
+        }
+
+        let token = match GitHubTokenManager::new().and_then(|m| m.get_token()) {
+            Ok(Some(token)) => token,
+            Ok(None) => continue,
+            Err(e) => {
+                println!("⚠️ Scheduler could not read GitHub token: {}", e);
+                continue;
+            }
+        };
+
+        let client = GitHubPrDataClient { token, feature: ApiFeature::ScheduledRefresh };
+        if let Err(e) = run_cycle(&state, &client, &mut cycle, now).await {
+            println!("❌ Scheduler failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::{Database, NewPullRequest};
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+    use crate::github::models::{GitHubHead, GitHubPRData, GitHubPRUser, GitHubReview};
+
+    struct MockClient {
+        rate_limited: bool,
+    }
+
+    impl PrDataClient for MockClient {
+        async fn fetch_pr(&self, _owner: &str, _repo: &str, number: i64) -> Result<GitHubPRData, String> {
+            if self.rate_limited {
+                return Err(RATE_LIMITED_ERROR.to_string());
+            }
+            Ok(GitHubPRData {
+                id: 100 + number,
+                title: "fix(api): handle empty body".to_string(),
+                user: GitHubPRUser { login: "octocat".to_string(), avatar_url: String::new(), name: None },
+                head: GitHubHead { ref_field: "empty-body".to_string(), sha: String::new() },
+                state: "closed".to_string(),
+                created_at: None,
+                updated_at: Some("2024-01-01T00:00:00Z".to_string()),
+                merged: true,
+                merged_at: Some("2024-01-01T00:00:00Z".to_string()),
+                merge_commit_sha: Some("c0ffee".to_string()),
+                reactions: None,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+            })
+        }
+
+        async fn fetch_reviews(&self, _owner: &str, _repo: &str, _number: i64) -> Result<Vec<GitHubReview>, String> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_due_refresh_runs_once() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 105,
+            pr_number: 5,
+            title: Some("WIP".to_string()),
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+
+        let task = db.schedule_task(pr.id, TASK_REFRESH_PR, 1_060, 1_000).await.unwrap();
+        let state: DbState = Arc::new(Mutex::new(Some(db)));
+        let client = MockClient { rate_limited: false };
+        assert_eq!(run_due_tasks(&state, &client, 1_059).await.unwrap(), 0);
+
+        // Rate limited: left pending for the next tick
+        assert_eq!(run_due_tasks(&state, &MockClient { rate_limited: true }, 1_060).await.unwrap(), 0);
+        assert_eq!(state.lock().await.as_ref().unwrap().get_scheduled_task(task).await.unwrap().unwrap().status, "pending");
+
+        assert_eq!(run_due_tasks(&state, &client, 1_060).await.unwrap(), 1);
+        // Only the tick that refreshed something was saved as a sync run
+        assert_eq!(run_due_tasks(&state, &client, 2_000).await.unwrap(), 0);
+
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().unwrap();
+        assert_eq!(db.get_scheduled_task(task).await.unwrap().unwrap().status, "done");
+        let refreshed = db.get_pull_request_by_id(pr.id).await.unwrap().unwrap();
+        assert_eq!(refreshed.title.as_deref(), Some("fix(api): handle empty body"));
+        assert_eq!(refreshed.commit_type.as_deref(), Some("fix"));
+        assert_eq!(refreshed.status, "Merged");
+        let runs = db.get_recent_sync_runs(10).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!((runs[0].kind.as_str(), runs[0].refreshed, runs[0].merged), ("scheduled_refresh", 1, 1));
+        assert_eq!(runs[0].changes[0].display_key, "acme/api#5");
+    }
+
+    #[tokio::test]
+    async fn test_constrained_cycles_are_skipped_once_per_reason() {
+        use crate::database::sync_constraints::SyncConstraints;
+
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 106,
+            pr_number: 6,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        let task = db.schedule_task(pr.id, TASK_REFRESH_PR, 1_000, 900).await.unwrap();
+        db.set_sync_constraints(&SyncConstraints { pause_on_metered: true, ..Default::default() }).await.unwrap();
+        let state: DbState = Arc::new(Mutex::new(Some(db)));
+        let client = MockClient { rate_limited: false };
+        let mut cycle = SchedulerCycle::default();
+
+        power::report_power_state(false, true, 1_000);
+        assert_eq!(run_cycle(&state, &client, &mut cycle, 1_000).await.unwrap(), 0);
+        assert_eq!(run_cycle(&state, &client, &mut cycle, 1_030).await.unwrap(), 0);
+        {
+            let db_state = state.lock().await;
+            let db = db_state.as_ref().unwrap();
+            assert_eq!(db.get_scheduled_task(task).await.unwrap().unwrap().status, "pending");
+            let runs = db.get_recent_sync_runs(10).await.unwrap();
+            assert_eq!(runs.len(), 1);
+            assert_eq!(runs[0].skipped_reason.as_deref(), Some("Paused on a metered connection"));
+        }
+
+        // Once the report goes stale nothing says the connection is metered, so the task runs
+        assert_eq!(run_cycle(&state, &client, &mut cycle, 1_000 + 3_600).await.unwrap(), 1);
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().unwrap();
+        assert_eq!(db.get_scheduled_task(task).await.unwrap().unwrap().status, "done");
+        assert_eq!(db.get_last_sync_run().await.unwrap().unwrap().skipped_reason, None);
+    }
+}
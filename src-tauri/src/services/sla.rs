@@ -0,0 +1,214 @@
+// Project review SLA reporting
+use chrono::{DateTime, Datelike, NaiveTime, Weekday};
+use serde::Serialize;
+
+use crate::database::waiting_on::WaitingOn;
+use crate::database::{Database, PrReviewTiming};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlaPrEntry {
+    pub pr_id: i64,
+    pub pr_number: i64,
+    pub display_key: String,
+    pub title: Option<String>,
+    pub started_at: i64,
+    pub reviewed_at: Option<i64>,
+    pub business_hours_elapsed: f64,
+    pub met_sla: bool, // for pending PRs: still within the SLA so far
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlaReport {
+    pub project_id: i64,
+    pub sla_hours: i64,
+    pub target_score: Option<i32>,
+    pub window_days: i64,
+    pub resolved_count: usize,
+    pub met_count: usize,
+    pub met_percentage: Option<f64>, // None when nothing was resolved in the window
+    pub current_streak: usize,       // most recent resolved PRs in a row that met the SLA
+    pub violators: Vec<SlaPrEntry>,  // resolved PRs reviewed after the SLA
+    pub pending: Vec<SlaPrEntry>,    // not reviewed yet and not waiting on the author; met_sla = false means already overdue
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProjectSlaReport {
+    NotConfigured { project_id: i64 },
+    Configured(SlaReport),
+}
+
+/// Seconds between two timestamps that fall on Monday-Friday (UTC)
+pub fn business_seconds_between(start: i64, end: i64) -> i64 {
+    if end <= start {
+        return 0;
+    }
+    let (Some(start_dt), Some(end_dt)) = (DateTime::from_timestamp(start, 0), DateTime::from_timestamp(end, 0)) else {
+        return 0;
+    };
+
+    let mut total = 0;
+    let mut day = start_dt.date_naive();
+    while day <= end_dt.date_naive() {
+        if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            let day_start = day.and_time(NaiveTime::MIN).and_utc().timestamp();
+            let day_end = day_start + SECONDS_PER_DAY;
+            total += end.min(day_end) - start.max(day_start);
+        }
+        match day.succ_opt() {
+            Some(next) => day = next,
+            None => break,
+        }
+    }
+    total
+}
+
+fn sla_entry(timing: &PrReviewTiming, sla_seconds: i64, now: i64) -> SlaPrEntry {
+    let elapsed = business_seconds_between(timing.started_at, timing.first_reviewed_at.unwrap_or(now));
+    SlaPrEntry {
+        pr_id: timing.pr_id,
+        pr_number: timing.pr_number,
+        display_key: timing.display_key.clone(),
+        title: timing.title.clone(),
+        started_at: timing.started_at,
+        reviewed_at: timing.first_reviewed_at,
+        business_hours_elapsed: elapsed as f64 / 3600.0,
+        met_sla: elapsed <= sla_seconds,
+    }
+}
+
+/// SLA compliance for PRs started in the last `window_days`, measured in business hours until first review
+pub async fn get_project_sla_report(db: &Database, project_id: i64, window_days: i64, now: i64) -> Result<ProjectSlaReport, String> {
+    if window_days <= 0 {
+        return Err("Window must be at least one day".to_string());
+    }
+
+    let project = db.get_project_by_id(project_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Project {} not found", project_id))?;
+    let Some(sla_hours) = project.sla_hours else {
+        return Ok(ProjectSlaReport::NotConfigured { project_id });
+    };
+
+    let timings = db.get_pr_review_timings(project_id, now - window_days * SECONDS_PER_DAY)
+        .await
+        .map_err(|e| e.to_string())?;
+    let sla_seconds = sla_hours * 3600;
+
+    // An unreviewed PR waiting on its author (changes requested) isn't the reviewers' delay
+    let (mut resolved, pending): (Vec<SlaPrEntry>, Vec<SlaPrEntry>) = timings.iter()
+        .filter(|timing| timing.first_reviewed_at.is_some() || timing.waiting_on != WaitingOn::Author)
+        .map(|timing| sla_entry(timing, sla_seconds, now))
+        .partition(|entry| entry.reviewed_at.is_some());
+    resolved.sort_by_key(|entry| std::cmp::Reverse(entry.reviewed_at));
+
+    let met_count = resolved.iter().filter(|entry| entry.met_sla).count();
+    let current_streak = resolved.iter().take_while(|entry| entry.met_sla).count();
+    let met_percentage = (!resolved.is_empty()).then(|| met_count as f64 * 100.0 / resolved.len() as f64);
+
+    Ok(ProjectSlaReport::Configured(SlaReport {
+        project_id,
+        sla_hours,
+        target_score: project.target_score,
+        window_days,
+        resolved_count: resolved.len(),
+        met_count,
+        met_percentage,
+        current_streak,
+        violators: resolved.into_iter().filter(|entry| !entry.met_sla).collect(),
+        pending,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::github_reviews::SubmittedReview;
+    use crate::database::NewPullRequest;
+
+    // Monday 2024-01-01 00:00:00 UTC
+    const MONDAY: i64 = 1_704_067_200;
+    const HOUR: i64 = 3600;
+
+    #[test]
+    fn test_business_seconds_skip_weekends() {
+        assert_eq!(business_seconds_between(MONDAY, MONDAY + 5 * HOUR), 5 * HOUR);
+        // Friday noon -> Monday noon is 24 business hours
+        let friday_noon = MONDAY + 4 * SECONDS_PER_DAY + 12 * HOUR;
+        let monday_noon = MONDAY + 7 * SECONDS_PER_DAY + 12 * HOUR;
+        assert_eq!(business_seconds_between(friday_noon, monday_noon), 24 * HOUR);
+        // Entirely inside a weekend
+        let saturday = MONDAY + 5 * SECONDS_PER_DAY;
+        assert_eq!(business_seconds_between(saturday, saturday + 30 * HOUR), 0);
+        assert_eq!(business_seconds_between(MONDAY + HOUR, MONDAY), 0);
+    }
+
+    #[tokio::test]
+    async fn test_project_sla_report() {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        let project = db.add_project("SLA".to_string(), None).await.unwrap();
+
+        let report = get_project_sla_report(&db, project.id, 30, MONDAY).await.unwrap();
+        assert!(matches!(report, ProjectSlaReport::NotConfigured { .. }));
+
+        assert!(db.update_project(project.id, "SLA".to_string(), None, Some(48), Some(101)).await.is_err());
+        assert!(db.update_project(project.id, "SLA".to_string(), None, Some(48), Some(-1)).await.is_err());
+        db.update_project(project.id, "SLA".to_string(), None, Some(48), Some(80)).await.unwrap();
+
+        // (created, first review): met, violated, met (most recent), pending overdue, pending on
+        // time, and one sent back to its author without a review, which isn't pending
+        let now = MONDAY + 14 * SECONDS_PER_DAY;
+        let cases = [
+            (MONDAY, Some(MONDAY + 10 * HOUR)),
+            (MONDAY + 2 * SECONDS_PER_DAY, Some(MONDAY + 7 * SECONDS_PER_DAY)),
+            (MONDAY + 4 * SECONDS_PER_DAY, Some(MONDAY + 8 * SECONDS_PER_DAY)),
+            (MONDAY + 9 * SECONDS_PER_DAY, None),
+            (now - HOUR, None),
+            (MONDAY + 9 * SECONDS_PER_DAY, None),
+        ];
+        for (i, (created, reviewed)) in cases.iter().enumerate() {
+            let pr = db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: i as i64 + 1,
+                pr_number: i as i64 + 1,
+                author_id: author.id,
+                project_id: Some(project.id),
+                status: if i == 5 { PrStatus::Action } else { PrStatus::Waiting },
+                github_created_at: Some(*created),
+                ..Default::default()
+            }).await.unwrap();
+            if let Some(reviewed) = reviewed {
+                // The author's own comment earlier on doesn't count as the first review
+                db.record_github_reviews(pr.id, &[
+                    SubmittedReview { review_id: 10 * i as i64 + 1, reviewer: "octocat".to_string(), state: "COMMENTED".to_string(), submitted_at: *created, commit_id: None },
+                    SubmittedReview { review_id: 10 * i as i64 + 2, reviewer: "alice".to_string(), state: "APPROVED".to_string(), submitted_at: *reviewed, commit_id: None },
+                ]).await.unwrap();
+            }
+        }
+
+        let ProjectSlaReport::Configured(report) = get_project_sla_report(&db, project.id, 30, now).await.unwrap() else {
+            panic!("expected a configured report");
+        };
+        assert_eq!(report.target_score, Some(80));
+        assert_eq!(report.resolved_count, 3);
+        assert_eq!(report.met_count, 2);
+        assert!((report.met_percentage.unwrap() - 66.666).abs() < 0.01);
+        assert_eq!(report.current_streak, 1);
+        assert_eq!(report.violators.iter().map(|e| e.pr_number).collect::<Vec<_>>(), vec![2]);
+        let pending: Vec<(i64, bool)> = report.pending.iter().map(|e| (e.pr_number, e.met_sla)).collect();
+        assert_eq!(pending, vec![(4, false), (5, true)]);
+
+        // A narrower window leaves only the recent PRs
+        let ProjectSlaReport::Configured(recent) = get_project_sla_report(&db, project.id, 6, now).await.unwrap() else {
+            panic!("expected a configured report");
+        };
+        assert_eq!(recent.resolved_count, 0);
+        assert_eq!(recent.met_percentage, None);
+        assert_eq!(recent.pending.len(), 2);
+    }
+}
@@ -0,0 +1,265 @@
+// "Sync All", the one refresh-every-PR path: fetch every tracked PR that has repository
+// fields, a few at a time, and apply what GitHub reports the way refresh_pr_from_github does.
+// The database is locked to read the list and to write each result, never while a request is
+// out, so the app stays usable during a long run. The caller gets progress after each PR (the
+// command forwards it as sync-progress events). Once GitHub (or the caller's budget) says
+// stop, no new requests go out and the run ends early with the reset time, rather than
+// failing the rest one by one.
+use serde::Serialize;
+use tokio::task::JoinSet;
+
+use crate::database::PullRequest;
+use crate::display_time::format_time_of_day;
+use crate::github::{rate_limit, RATE_LIMITED_ERROR};
+use crate::services::pull_requests::{apply_fetched_pr, fetch_pr_update, PrDataClient};
+use crate::services::db_state::display_tz;
+use crate::services::retry::queue_refresh_retry;
+use crate::DbState;
+
+pub const SYNC_PROGRESS_EVENT: &str = "sync-progress";
+
+const MAX_CONCURRENT_SYNCS: usize = 4;
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SyncProgress {
+    pub done: usize,
+    pub total: usize,
+    pub current_pr: i64, // id of the PR that just finished
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SyncAllSummary {
+    pub updated: u32,   // title, branch or status changed
+    pub unchanged: u32,
+    pub failed: u32,
+    pub errors: Vec<String>,
+    pub rate_limited: bool,     // stopped early; PRs not reached are in none of the counts
+    pub resets_at: Option<i64>, // when calls may go out again, if GitHub said
+}
+
+// What refresh_all_prs reports: Sync All without the unchanged/updated split or progress
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct RefreshAllSummary {
+    pub updated: u32, // refreshed, whether or not anything changed
+    pub failed: u32,
+    pub errors: Vec<String>,
+}
+
+impl From<SyncAllSummary> for RefreshAllSummary {
+    fn from(summary: SyncAllSummary) -> Self {
+        RefreshAllSummary {
+            updated: summary.updated + summary.unchanged,
+            failed: summary.failed,
+            errors: summary.errors,
+        }
+    }
+}
+
+fn changed(before: &PullRequest, after: &PullRequest) -> bool {
+    before.title != after.title || before.branch != after.branch || before.status != after.status
+}
+
+pub async fn sync_all_prs<C>(state: &DbState, client: &C, on_progress: impl Fn(&SyncProgress)) -> Result<SyncAllSummary, String>
+where
+    C: PrDataClient + Clone + Send + Sync + 'static,
+{
+    let prs: Vec<PullRequest> = {
+        let db_state = state.lock().await;
+        let db = db_state.as_ref().ok_or("Database not initialized")?;
+        db.get_pull_requests()
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|pr| !pr.is_issue && pr.repository_owner.is_some() && pr.repository_name.is_some())
+            .collect()
+    };
+    let mut progress = SyncProgress { total: prs.len(), ..Default::default() };
+    let mut summary = SyncAllSummary::default();
+    println!("🔄 Syncing {} PRs with GitHub", progress.total);
+
+    // Spawned tasks don't inherit the caller's request category, so it's passed along
+    let category = rate_limit::current_category();
+    let mut pending = prs.into_iter();
+    let mut fetches = JoinSet::new();
+    loop {
+        while !summary.rate_limited && fetches.len() < MAX_CONCURRENT_SYNCS {
+            let Some(pr) = pending.next() else { break };
+            let client = client.clone();
+            fetches.spawn(rate_limit::in_category(category, async move {
+                let fetched = fetch_pr_update(&client, &pr).await;
+                (pr, fetched)
+            }));
+        }
+        let Some(finished) = fetches.join_next().await else { break };
+        let (pr, fetched) = finished.map_err(|e| e.to_string())?;
+
+        let outcome = match fetched {
+            Ok((data, reviews)) => apply_fetched_pr(state, pr.id, &data, &reviews).await,
+            Err(e) => {
+                // Rate-limited PRs are left for the next run rather than queued
+                if e != RATE_LIMITED_ERROR {
+                    if let Some(db) = state.lock().await.as_ref() {
+                        queue_refresh_retry(db, &pr, &e, chrono::Utc::now().timestamp()).await;
+                    }
+                }
+                Err(e)
+            }
+        };
+        match outcome {
+            Ok(after) if changed(&pr, &after) => summary.updated += 1,
+            Ok(_) => summary.unchanged += 1,
+            Err(e) if e == RATE_LIMITED_ERROR => {
+                if !summary.rate_limited {
+                    println!("⏳ Rate limited, stopping sync early");
+                    summary.rate_limited = true;
+                }
+                continue;
+            }
+            Err(e) => {
+                summary.failed += 1;
+                summary.errors.push(format!("PR #{} (id {}): {}", pr.pr_number, pr.id, e));
+            }
+        }
+        progress.done += 1;
+        progress.current_pr = pr.id;
+        on_progress(&progress);
+    }
+
+    if summary.rate_limited {
+        let limits = rate_limit::current();
+        summary.resets_at = limits.blocked_until(chrono::Utc::now().timestamp()).or(limits.reset_at);
+        let tz = display_tz(state.lock().await.as_ref()).await;
+        let resets = summary.resets_at.map(|at| format!(", resets at {}", format_time_of_day(at, tz))).unwrap_or_default();
+        summary.errors.push(format!("Stopped after {} of {} PRs: {}{}", progress.done, progress.total, RATE_LIMITED_ERROR, resets));
+    }
+    println!("✅ Synced {} PRs: {} updated, {} unchanged, {} failed", progress.done, summary.updated, summary.unchanged, summary.failed);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::status::PrStatus;
+    use crate::database::{Database, NewPullRequest};
+    use crate::github::models::{GitHubHead, GitHubPRData, GitHubPRUser, GitHubReview};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    // PR 2 is unchanged upstream, PR 3 is gone, PR 4 merged; the rest were renamed.
+    // With `limited`, every call reports the rate limit instead. Given the app's state, each
+    // call checks that the database can still be locked while it is out.
+    #[derive(Clone, Default)]
+    struct MockClient {
+        limited: bool,
+        state: Option<DbState>,
+        calls: Arc<AtomicUsize>,
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    impl PrDataClient for MockClient {
+        async fn fetch_pr(&self, owner: &str, repo: &str, number: i64) -> Result<GitHubPRData, String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            if let Some(state) = &self.state {
+                // Other commands get the database while requests are out (writes take turns)
+                let waited = tokio::time::timeout(Duration::from_secs(1), state.lock()).await;
+                assert!(waited.is_ok(), "database held during a fetch");
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            if self.limited {
+                return Err(RATE_LIMITED_ERROR.to_string());
+            }
+            if number == 3 {
+                return Err(format!("PR #{} not found in repository {}/{}", number, owner, repo));
+            }
+            Ok(GitHubPRData {
+                id: number,
+                title: if number == 2 { "PR".to_string() } else { format!("PR {}", number) },
+                user: GitHubPRUser { login: "octocat".to_string(), avatar_url: String::new(), name: None },
+                head: GitHubHead { ref_field: "main".to_string(), sha: String::new() },
+                state: if number == 4 { "closed" } else { "open" }.to_string(),
+                created_at: None,
+                updated_at: None,
+                merged: number == 4,
+                merged_at: None,
+                merge_commit_sha: None,
+                reactions: None,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+            })
+        }
+
+        async fn fetch_reviews(&self, _owner: &str, _repo: &str, _number: i64) -> Result<Vec<GitHubReview>, String> {
+            Ok(Vec::new())
+        }
+    }
+
+    async fn seed() -> DbState {
+        let db = Database::new_in_memory().await.unwrap();
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        for number in 1..=10 {
+            db.add_pull_request(&WriteContext::default(), NewPullRequest {
+                github_id: number,
+                pr_number: number,
+                title: Some("PR".to_string()),
+                branch: Some("main".to_string()),
+                author_id: author.id,
+                status: PrStatus::Reviewing,
+                repository_owner: (number != 10).then(|| "acme".to_string()),
+                repository_name: (number != 10).then(|| "api".to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+        Arc::new(tokio::sync::Mutex::new(Some(db)))
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_counts_outcomes_and_reports_progress() {
+        let state = seed().await;
+        let client = MockClient { state: Some(state.clone()), ..Default::default() };
+        let events = Mutex::new(Vec::new());
+
+        let summary = sync_all_prs(&state, &client, |progress| events.lock().unwrap().push(progress.clone())).await.unwrap();
+        assert_eq!((summary.updated, summary.unchanged, summary.failed), (7, 1, 1));
+        assert!(summary.errors[0].starts_with("PR #3 "), "{:?}", summary.errors);
+        assert!(!summary.rate_limited);
+
+        // PR 10 has no repository fields and isn't part of the run
+        let events = events.into_inner().unwrap();
+        assert_eq!(events.iter().map(|p| (p.done, p.total)).collect::<Vec<_>>(), (1..=9).map(|done| (done, 9)).collect::<Vec<_>>());
+        let max = client.max_in_flight.load(Ordering::SeqCst);
+        assert!((2..=MAX_CONCURRENT_SYNCS).contains(&max), "{}", max);
+
+        let db_state = state.lock().await;
+        let merged = db_state.as_ref().unwrap().get_pull_requests().await.unwrap().into_iter().find(|pr| pr.pr_number == 4).unwrap();
+        assert_eq!(merged.status, "Merged");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_all_summary_counts_every_refreshed_pr() {
+        let state = seed().await;
+        let summary = RefreshAllSummary::from(sync_all_prs(&state, &MockClient::default(), |_| {}).await.unwrap());
+        assert_eq!((summary.updated, summary.failed), (8, 1));
+        assert!(summary.errors[0].starts_with("PR #3 "), "{:?}", summary.errors);
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_stops_when_rate_limited() {
+        let state = seed().await;
+        let client = MockClient { limited: true, ..Default::default() };
+
+        let summary = sync_all_prs(&state, &client, |_| {}).await.unwrap();
+        assert!(summary.rate_limited);
+        assert_eq!((summary.updated, summary.unchanged, summary.failed), (0, 0, 0));
+        assert!(summary.errors[0].starts_with("Stopped after 0 of 9 PRs"), "{:?}", summary.errors);
+        // Only the first batch went out
+        assert!(client.calls.load(Ordering::SeqCst) <= MAX_CONCURRENT_SYNCS);
+    }
+}
@@ -0,0 +1,112 @@
+// Collecting what a background sync run did and saving it to sync_runs
+use crate::database::display_key::display_key;
+use crate::database::sync_runs::{NewSyncRun, SyncChange, SyncRun};
+use crate::database::{Database, PullRequest};
+use crate::display_time::format_timestamp;
+use crate::github::api_usage::ApiFeature;
+
+pub const MAX_RECENT_SYNC_RUNS: i64 = 100;
+
+/// Tally for one run; call finish() once the run is over
+pub struct SyncRunLog {
+    run: NewSyncRun,
+}
+
+impl SyncRunLog {
+    pub fn start(feature: ApiFeature, now: i64) -> Self {
+        SyncRunLog {
+            run: NewSyncRun { kind: feature.as_str().to_string(), started_at: now, ..Default::default() },
+        }
+    }
+
+    /// A PR re-read from GitHub; recorded as a change when anything GitHub owns moved
+    pub fn refreshed(&mut self, before: &PullRequest, after: &PullRequest) {
+        self.run.refreshed += 1;
+        let merged = after.status == "Merged" && before.status != "Merged";
+        if merged || before.title != after.title || before.branch != after.branch
+            || before.github_updated_at != after.github_updated_at
+        {
+            self.push_change(after, merged);
+        }
+    }
+
+    /// A PR checked and moved to Merged
+    pub fn merged(&mut self, pr: &PullRequest) {
+        self.run.refreshed += 1;
+        self.push_change(pr, true);
+    }
+
+    /// A PR checked with nothing to change
+    pub fn unchanged(&mut self) {
+        self.run.refreshed += 1;
+    }
+
+    pub fn failed(&mut self, pr: &PullRequest, error: &str) {
+        self.run.failed += 1;
+        self.run.errors.push(format!("{}: {}", change_key(pr), error));
+    }
+
+    /// Stopped early; not counted as a failure, the work is picked up next time
+    pub fn note(&mut self, message: &str) {
+        self.run.errors.push(message.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.run.refreshed == 0 && self.run.failed == 0 && self.run.errors.is_empty()
+    }
+
+    /// Save the run; a failed save is logged, never fatal to the sync itself
+    pub async fn finish(mut self, db: &Database, now: i64) {
+        self.run.finished_at = now;
+        if let Err(e) = db.record_sync_run(&self.run).await {
+            println!("⚠️ Could not save {} sync run: {}", self.run.kind, e);
+        }
+    }
+
+    fn push_change(&mut self, pr: &PullRequest, merged: bool) {
+        self.run.changes.push(SyncChange { pr_id: pr.id, display_key: change_key(pr), title: pr.title.clone(), merged });
+    }
+}
+
+/// A cycle held back by the sync constraints, saved so the gap in syncs can be explained
+pub async fn record_skipped_run(db: &Database, feature: ApiFeature, reason: &str, now: i64) {
+    let run = NewSyncRun {
+        kind: feature.as_str().to_string(),
+        started_at: now,
+        finished_at: now,
+        skipped_reason: Some(reason.to_string()),
+        ..Default::default()
+    };
+    if let Err(e) = db.record_sync_run(&run).await {
+        println!("⚠️ Could not save skipped {} sync run: {}", run.kind, e);
+    }
+}
+
+fn change_key(pr: &PullRequest) -> String {
+    display_key(pr.repository_owner.as_deref(), pr.repository_name.as_deref(), pr.pr_number, true)
+}
+
+pub async fn get_recent_sync_runs(db: &Database, limit: i64) -> Result<Vec<SyncRun>, String> {
+    if !(1..=MAX_RECENT_SYNC_RUNS).contains(&limit) {
+        return Err(format!("limit must be between 1 and {}", MAX_RECENT_SYNC_RUNS));
+    }
+    db.get_recent_sync_runs(limit).await.map_err(|e| e.to_string())
+}
+
+/// One line for the connection check, e.g. "Last sync 2024-01-01 07:30 +09:00 (merge_sync): 12 refreshed, 4 changed (2 merged), 1 failed"
+pub async fn last_sync_message(db: Option<&Database>) -> Option<String> {
+    let run = db?.get_last_sync_run().await.ok()??;
+    let tz = db?.get_display_timezone().await.ok()?;
+    if let Some(reason) = &run.skipped_reason {
+        return Some(format!("Last sync {} ({}) skipped: {}", format_timestamp(run.finished_at, tz), run.kind, reason));
+    }
+    Some(format!(
+        "Last sync {} ({}): {} refreshed, {} changed ({} merged), {} failed",
+        format_timestamp(run.finished_at, tz),
+        run.kind,
+        run.refreshed,
+        run.changed,
+        run.merged,
+        run.failed
+    ))
+}
@@ -0,0 +1,94 @@
+// Warning users before the stored token expires, so sync doesn't silently stop
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::database::Database;
+use crate::display_time::format_timestamp;
+use crate::github::token_grants::{self, days_until, EXPIRY_WARNING_DAYS};
+use crate::services::db_state::{display_tz, wait_for_database};
+use crate::DbState;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TokenExpiryStatus {
+    pub expires_at: Option<i64>, // None when the token doesn't expire or no call has reported it yet
+    pub days_remaining: Option<i64>,
+    pub expiring_soon: bool,     // within EXPIRY_WARNING_DAYS, or already expired
+    pub message: Option<String>, // set when expiring_soon
+}
+
+pub fn token_expiry_status(expires_at: Option<i64>, now: i64, tz: Tz) -> TokenExpiryStatus {
+    let days_remaining = expires_at.map(|at| days_until(at, now));
+    let message = match (expires_at, days_remaining) {
+        (Some(at), _) if at <= now => Some(format!(
+            "GitHub token expired on {}; create a new one and save it in Settings",
+            format_timestamp(at, tz)
+        )),
+        (Some(at), Some(days)) if days <= EXPIRY_WARNING_DAYS => Some(format!(
+            "GitHub token expires in {} day(s) ({}); rotate it before sync stops working",
+            days,
+            format_timestamp(at, tz)
+        )),
+        _ => None,
+    };
+
+    TokenExpiryStatus { expires_at, days_remaining, expiring_soon: message.is_some(), message }
+}
+
+/// Current status, with times in the display zone when the database is open
+pub async fn current_status(db: Option<&Database>) -> TokenExpiryStatus {
+    let tz = display_tz(db).await;
+    token_expiry_status(token_grants::current_expiry().expires_at, chrono::Utc::now().timestamp(), tz)
+}
+
+/// Startup task: restore the saved expiry and warn if it's close, then save every change GitHub reports
+pub async fn run_token_expiry_watcher(state: DbState) {
+    let mut changes = token_grants::subscribe_expiry();
+
+    {
+        let db = wait_for_database(&state).await;
+        match db.get_saved_token_expiry().await {
+            Ok(Some(expires_at)) => token_grants::seed_expiry(expires_at),
+            Ok(None) => {}
+            Err(e) => println!("⚠️ Could not load token expiry: {}", e),
+        }
+        if let Some(message) = current_status(Some(&db)).await.message {
+            println!("⚠️ {}", message);
+        }
+    }
+
+    while changes.changed().await.is_ok() {
+        let expiry = *changes.borrow_and_update();
+        let db_state = state.lock().await;
+        let Some(db) = db_state.as_ref() else { continue };
+        if let Err(e) = db.save_token_expiry(expiry.expires_at).await {
+            println!("⚠️ Could not save token expiry: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expiry_warning_window() {
+        // 2024-01-01 09:00 UTC
+        let now = 1_704_099_600;
+        let day = 86_400;
+
+        let soon = token_expiry_status(Some(now + 3 * day), now, Tz::UTC);
+        assert_eq!((soon.days_remaining, soon.expiring_soon), (Some(3), true));
+        assert_eq!(soon.message.as_deref(), Some("GitHub token expires in 3 day(s) (2024-01-04 09:00 +00:00); rotate it before sync stops working"));
+
+        assert!(!token_expiry_status(Some(now + 30 * day), now, Tz::UTC).expiring_soon);
+        assert!(!token_expiry_status(None, now, Tz::UTC).expiring_soon);
+        let expired = token_expiry_status(Some(now - 60), now, Tz::UTC);
+        assert!(expired.message.unwrap().starts_with("GitHub token expired on 2024-01-01 08:59"));
+
+        let db = Database::new_in_memory().await.unwrap();
+        db.save_token_expiry(Some(now)).await.unwrap();
+        assert_eq!(db.get_saved_token_expiry().await.unwrap(), Some(now));
+        db.save_token_expiry(None).await.unwrap();
+        assert_eq!(db.get_saved_token_expiry().await.unwrap(), None);
+    }
+}
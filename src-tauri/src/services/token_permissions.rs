@@ -0,0 +1,162 @@
+// Token permission report and diagnosis: what the enabled features need (github/token_permissions.rs)
+// and, per permission, whether the token actually has it on a tracked repository.
+use std::future::Future;
+
+use serde::Serialize;
+
+use crate::database::Database;
+use crate::github::api_usage::ApiFeature;
+use crate::github::token_permissions::{required_permissions, Permission, RequiredTokenPermissions};
+use crate::github::{probe_repo_permission, RATE_LIMITED_ERROR};
+
+/// One permission probe against a repository (mocked in tests)
+pub trait PermissionProbe {
+    fn probe(&self, owner: &str, repo: &str, permission: Permission) -> impl Future<Output = Result<bool, String>> + Send;
+}
+
+pub struct GitHubPermissionProbe {
+    pub token: String,
+    pub feature: ApiFeature,
+}
+
+impl PermissionProbe for GitHubPermissionProbe {
+    async fn probe(&self, owner: &str, repo: &str, permission: Permission) -> Result<bool, String> {
+        probe_repo_permission(self.feature, &self.token, owner, repo, permission).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PermissionCheck {
+    pub permission: Permission,
+    pub required: bool,         // false for permissions only unused features need
+    pub granted: Option<bool>,  // None when it couldn't be tested
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TokenDiagnosis {
+    pub probe_repository: Option<String>, // "owner/repo" the checks ran against
+    pub checks: Vec<PermissionCheck>,
+    pub missing: Vec<Permission>,         // required and not granted
+    pub rate_limited: bool,
+    pub message: String,
+}
+
+pub async fn get_required_token_permissions(db: &Database) -> Result<RequiredTokenPermissions, String> {
+    let usage = db.get_feature_usage().await.map_err(|e| e.to_string())?;
+    Ok(required_permissions(usage))
+}
+
+/// Test each permission on the first tracked repository not known to be inaccessible.
+/// Metadata goes first: when the repository isn't visible at all the rest can't be told apart.
+pub async fn diagnose_token_access<P: PermissionProbe>(db: &Database, probe: &P) -> Result<TokenDiagnosis, String> {
+    let report = get_required_token_permissions(db).await?;
+    let mut checks: Vec<PermissionCheck> = report.required.iter().map(|p| (p.permission, true))
+        .chain(report.optional.iter().map(|p| (p.permission, false)))
+        .map(|(permission, required)| PermissionCheck { permission, required, granted: None, error: None })
+        .collect();
+
+    let repositories = db.get_tracked_repositories().await.map_err(|e| e.to_string())?;
+    let Some(repository) = repositories.iter().find(|repository| repository.accessible != Some(false)) else {
+        return Ok(TokenDiagnosis {
+            probe_repository: None,
+            checks,
+            missing: Vec::new(),
+            rate_limited: false,
+            message: "Track a repository the token should read to test its permissions".to_string(),
+        });
+    };
+
+    let mut rate_limited = false;
+    let mut repository_visible = true;
+    for check in checks.iter_mut() {
+        if !repository_visible {
+            check.error = Some("Not tested: the token can't see the repository".to_string());
+            continue;
+        }
+        match probe.probe(&repository.owner, &repository.name, check.permission).await {
+            Ok(granted) => {
+                check.granted = Some(granted);
+                repository_visible = granted || check.permission != Permission::Metadata;
+            }
+            Err(e) if e == RATE_LIMITED_ERROR => {
+                rate_limited = true;
+                break;
+            }
+            Err(e) => check.error = Some(e),
+        }
+    }
+
+    let missing: Vec<Permission> = checks.iter()
+        .filter(|check| check.required && check.granted == Some(false))
+        .map(|check| check.permission)
+        .collect();
+    let message = match (missing.is_empty(), rate_limited) {
+        (_, true) => "Rate limited; some permissions weren't tested".to_string(),
+        (true, false) => "The token has every permission the enabled features need".to_string(),
+        (false, false) => format!(
+            "Missing read access to: {}",
+            missing.iter().map(|permission| permission.as_str()).collect::<Vec<_>>().join(", ")
+        ),
+    };
+
+    Ok(TokenDiagnosis {
+        probe_repository: Some(format!("{}/{}", repository.owner, repository.name)),
+        checks,
+        missing,
+        rate_limited,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::actor::WriteContext;
+    use crate::database::NewPullRequest;
+
+    // A fine-grained token granted pull requests but not issues
+    struct MockProbe;
+
+    impl PermissionProbe for MockProbe {
+        async fn probe(&self, _owner: &str, _repo: &str, permission: Permission) -> Result<bool, String> {
+            Ok(matches!(permission, Permission::Metadata | Permission::PullRequests))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_diagnosis_reports_missing_required_permissions() {
+        let db = Database::new_in_memory().await.unwrap();
+        let diagnosis = diagnose_token_access(&db, &MockProbe).await.unwrap();
+        assert_eq!(diagnosis.probe_repository, None);
+
+        let author = db.get_or_create_team_member("octocat".to_string()).await.unwrap();
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 1,
+            pr_number: 1,
+            author_id: author.id,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+
+        // Issues and contents are only optional until issues or releases are in use; deployments always are
+        let diagnosis = diagnose_token_access(&db, &MockProbe).await.unwrap();
+        assert_eq!(diagnosis.probe_repository.as_deref(), Some("acme/api"));
+        assert!(diagnosis.missing.is_empty());
+        assert_eq!(diagnosis.checks.iter().filter(|check| check.granted == Some(false)).count(), 3);
+
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 2,
+            pr_number: 2,
+            author_id: author.id,
+            repository_owner: Some("acme".to_string()),
+            repository_name: Some("api".to_string()),
+            is_issue: true,
+            ..Default::default()
+        }).await.unwrap();
+        let diagnosis = diagnose_token_access(&db, &MockProbe).await.unwrap();
+        assert_eq!(diagnosis.missing, vec![Permission::Issues]);
+        assert_eq!(diagnosis.message, "Missing read access to: issues");
+    }
+}
@@ -0,0 +1,60 @@
+// Pushing the tray summary to the UI when it changes
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::database::tray::TraySummary;
+use crate::DbState;
+
+pub const TRAY_SUMMARY_CHANGED_EVENT: &str = "tray-summary-changed";
+
+// Checking once a second is also the debounce: bursts of writes produce one event
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Overdue counts move with the clock, not just with writes
+const FORCED_REFRESH_TICKS: u32 = 60;
+
+/// Background loop: recompute after any write (tracked by the data generation) or once
+/// a minute, and emit tray-summary-changed only when a number actually changed
+pub async fn run_tray_summary_watcher<R: Runtime>(app: AppHandle<R>, state: DbState) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut last_generation = None;
+    let mut last_summary: Option<TraySummary> = None;
+    let mut ticks_since_refresh = 0;
+    loop {
+        interval.tick().await;
+        ticks_since_refresh += 1;
+
+        let db_state = state.lock().await;
+        let Some(db) = db_state.as_ref() else { continue };
+        let generation = match db.get_data_generation().await {
+            Ok(generation) => Some(generation),
+            Err(e) => {
+                println!("⚠️ Could not read data generation: {}", e);
+                continue;
+            }
+        };
+        if generation == last_generation && ticks_since_refresh < FORCED_REFRESH_TICKS {
+            continue;
+        }
+
+        let summary = match db.get_tray_summary(chrono::Utc::now().timestamp()).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                println!("⚠️ Could not compute tray summary: {}", e);
+                continue;
+            }
+        };
+        drop(db_state);
+        last_generation = generation;
+        ticks_since_refresh = 0;
+
+        if last_summary.as_ref() != Some(&summary) {
+            // The first summary is a baseline, not a change
+            if last_summary.is_some() {
+                let _ = app.emit(TRAY_SUMMARY_CHANGED_EVENT, summary.clone());
+            }
+            last_summary = Some(summary);
+        }
+    }
+}
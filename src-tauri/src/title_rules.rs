@@ -0,0 +1,108 @@
+// Regex rules that pull structured parts (commit type, scope, ticket key) out of PR titles.
+// The raw title is always kept; parsed parts go into their own columns.
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+pub const TITLE_RULES_KEY: &str = "title_rules";
+
+// Named capture groups a rule may use
+const RULE_GROUPS: [&str; 3] = ["type", "scope", "ticket"];
+
+// Used while the title_rules setting is unset: conventional commits and JIRA-style keys
+const DEFAULT_RULES: [&str; 2] = [
+    r"^(?P<type>[A-Za-z]+)(?:\((?P<scope>[^)]+)\))?!?:",
+    r"\b(?P<ticket>[A-Z][A-Z0-9]+-\d+)\b",
+];
+
+/// One rule in the title_rules setting, e.g. `{"pattern": "^\\[(?P<ticket>[A-Z]+-\\d+)\\]"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleRule {
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedTitle {
+    pub commit_type: Option<String>, // lowercased
+    pub commit_scope: Option<String>,
+    pub ticket_key: Option<String>,
+}
+
+/// Parse and compile the title_rules setting (a JSON array of rules)
+pub fn parse_rules(json: &str) -> Result<Vec<Regex>, String> {
+    let rules: Vec<TitleRule> = serde_json::from_str(json)
+        .map_err(|e| format!("Title rules must be a JSON array of {{\"pattern\": ...}}: {}", e))?;
+
+    rules.iter()
+        .map(|rule| {
+            let regex = Regex::new(&rule.pattern)
+                .map_err(|e| format!("Invalid title rule '{}': {}", rule.pattern, e))?;
+            if !regex.capture_names().flatten().any(|name| RULE_GROUPS.contains(&name)) {
+                return Err(format!(
+                    "Title rule '{}' needs a named group: (?P<type>...), (?P<scope>...) or (?P<ticket>...)",
+                    rule.pattern
+                ));
+            }
+            Ok(regex)
+        })
+        .collect()
+}
+
+/// The built-in rules as a title_rules value
+pub fn default_rules_json() -> String {
+    let rules: Vec<TitleRule> = DEFAULT_RULES.iter().map(|pattern| TitleRule { pattern: pattern.to_string() }).collect();
+    serde_json::to_string(&rules).expect("default title rules serialize")
+}
+
+pub fn default_rules() -> Vec<Regex> {
+    DEFAULT_RULES.iter().map(|pattern| Regex::new(pattern).expect("default title rule")).collect()
+}
+
+/// Apply rules in order; each component comes from the first rule that captures it
+pub fn parse_title(rules: &[Regex], title: &str) -> ParsedTitle {
+    let mut parsed = ParsedTitle::default();
+    for rule in rules {
+        let Some(captures) = rule.captures(title) else { continue };
+        let group = |name: &str| captures.name(name).map(|m| m.as_str().trim().to_string()).filter(|s| !s.is_empty());
+
+        if parsed.commit_type.is_none() {
+            parsed.commit_type = group("type").map(|t| t.to_lowercase());
+        }
+        if parsed.commit_scope.is_none() {
+            parsed.commit_scope = group("scope");
+        }
+        if parsed.ticket_key.is_none() {
+            parsed.ticket_key = group("ticket");
+        }
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules() {
+        let rules = default_rules();
+        assert_eq!(parse_title(&rules, "feat(api)!: PROJ-42 add pagination"), ParsedTitle {
+            commit_type: Some("feat".to_string()),
+            commit_scope: Some("api".to_string()),
+            ticket_key: Some("PROJ-42".to_string()),
+        });
+        assert_eq!(parse_title(&rules, "Fix: typo").commit_type.as_deref(), Some("fix"));
+        assert_eq!(parse_title(&rules, "Update README"), ParsedTitle::default());
+    }
+
+    #[test]
+    fn test_custom_rules_and_validation() {
+        let rules = parse_rules(r#"[{"pattern": "^\\[(?P<ticket>[A-Z]+-\\d+)\\]"}, {"pattern": "(?P<type>chore|docs)"}]"#).unwrap();
+        let parsed = parse_title(&rules, "[OPS-7] docs: runbook");
+        assert_eq!(parsed.ticket_key.as_deref(), Some("OPS-7"));
+        assert_eq!(parsed.commit_type.as_deref(), Some("docs"));
+
+        assert!(parse_rules(r#"[{"pattern": "(?P<type>feat"}]"#).is_err());
+        assert!(parse_rules(r#"[{"pattern": "^feat"}]"#).is_err());
+        assert!(parse_rules(r#"{"pattern": "(?P<type>feat)"}"#).is_err());
+        assert!(parse_rules("[]").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+// Pins the IPC surface after the command module split: one representative
+// command from each commands:: module is invoked through the mock runtime.
+use pr_tracker_lib::database::actor::WriteContext;
+use pr_tracker_lib::database::status::PrStatus;
+use pr_tracker_lib::database::{Database, NewPullRequest};
+use pr_tracker_lib::{app_builder, DbState};
+use serde_json::{json, Value};
+use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, INVOKE_KEY};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::webview::InvokeRequest;
+use tauri::{Manager, WebviewWindow, WebviewWindowBuilder};
+
+fn invoke(webview: &WebviewWindow<tauri::test::MockRuntime>, cmd: &str, args: Value) -> Result<Value, Value> {
+    get_ipc_response(
+        webview,
+        InvokeRequest {
+            cmd: cmd.into(),
+            callback: CallbackFn(0),
+            error: CallbackFn(1),
+            url: "http://tauri.localhost".parse().unwrap(),
+            body: InvokeBody::Json(args),
+            headers: Default::default(),
+            invoke_key: INVOKE_KEY.to_string(),
+        },
+    )
+    .map(|body| body.deserialize::<Value>().unwrap())
+}
+
+#[test]
+fn representative_commands_from_each_module() {
+    let app = app_builder(mock_builder())
+        .build(mock_context(noop_assets()))
+        .expect("failed to build app");
+
+    // Swap in an in-memory database instead of the user's real one
+    let db = tauri::async_runtime::block_on(Database::new_in_memory()).unwrap();
+    let state = app.state::<DbState>();
+    *tauri::async_runtime::block_on(state.lock()) = Some(db);
+
+    let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+        .build()
+        .unwrap();
+
+    // commands::app
+    assert_eq!(
+        invoke(&webview, "greet", json!({ "name": "Tauri" })),
+        Ok(json!("Hello, Tauri! You've been greeted from Rust!"))
+    );
+
+    // commands::projects
+    let project = invoke(&webview, "add_project", json!({ "name": "Core", "description": null })).unwrap();
+    assert_eq!(project["name"], "Core");
+    let projects = invoke(&webview, "get_projects", json!({})).unwrap();
+    assert_eq!(projects.as_array().unwrap().len(), 1);
+
+    // commands::pull_requests
+    assert_eq!(invoke(&webview, "get_pull_requests", json!({})), Ok(json!([])));
+    let board = invoke(&webview, "get_board_data", json!({ "projectId": project["id"] })).unwrap();
+    assert_eq!(board["total"], 0);
+
+    // commands::settings
+    invoke(&webview, "set_setting", json!({ "key": "sync_interval_minutes", "value": "10" })).unwrap();
+    assert_eq!(invoke(&webview, "get_setting", json!({ "key": "sync_interval_minutes" })), Ok(json!("10")));
+
+    // commands::team
+    assert_eq!(invoke(&webview, "get_team_members", json!({})), Ok(json!([])));
+
+    // commands::github - a malformed URL fails in the parser, before any network access
+    let error = invoke(
+        &webview,
+        "add_pr_from_github_url",
+        json!({ "prUrl": "https://example.com/not-a-pr", "projectId": project["id"], "token": "unused" }),
+    )
+    .unwrap_err();
+    assert!(error.as_str().unwrap().contains("Invalid GitHub PR URL format"));
+}
+
+#[test]
+fn delete_pull_request_removes_the_pr_and_its_history() {
+    let app = app_builder(mock_builder())
+        .build(mock_context(noop_assets()))
+        .expect("failed to build app");
+
+    let db = tauri::async_runtime::block_on(Database::new_in_memory()).unwrap();
+    let pr = tauri::async_runtime::block_on(async {
+        let author = db.add_team_member("octocat", None, None).await.unwrap();
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 7,
+            pr_number: 7,
+            author_id: author.id,
+            status: PrStatus::Reviewing,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    });
+    let state = app.state::<DbState>();
+    *tauri::async_runtime::block_on(state.lock()) = Some(db);
+
+    let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+        .build()
+        .unwrap();
+
+    let deleted = invoke(&webview, "delete_pull_request", json!({ "prId": pr.id })).unwrap();
+    assert!(deleted.as_u64().is_some());
+    assert_eq!(invoke(&webview, "get_pull_requests", json!({})), Ok(json!([])));
+
+    let error = invoke(&webview, "delete_pull_request", json!({ "prId": pr.id })).unwrap_err();
+    assert!(error.as_str().unwrap().contains("not found"));
+}
+
+#[test]
+fn update_pr_status_rejects_unknown_statuses() {
+    let app = app_builder(mock_builder())
+        .build(mock_context(noop_assets()))
+        .expect("failed to build app");
+
+    let db = tauri::async_runtime::block_on(Database::new_in_memory()).unwrap();
+    let pr = tauri::async_runtime::block_on(async {
+        let author = db.add_team_member("octocat", None, None).await.unwrap();
+        db.add_pull_request(&WriteContext::default(), NewPullRequest {
+            github_id: 8,
+            pr_number: 8,
+            author_id: author.id,
+            status: PrStatus::Waiting,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    });
+    let state = app.state::<DbState>();
+    *tauri::async_runtime::block_on(state.lock()) = Some(db);
+
+    let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+        .build()
+        .unwrap();
+
+    let error = invoke(&webview, "update_pr_status", json!({ "prId": pr.id, "status": "Reviwing" })).unwrap_err();
+    assert_eq!(
+        error,
+        json!("Unknown status 'Reviwing'. Valid statuses: Waiting, Reviewing, Action, Approved, Merged, archived")
+    );
+
+    invoke(&webview, "update_pr_status", json!({ "prId": pr.id, "status": "reviewing" })).unwrap();
+    let prs = invoke(&webview, "get_pull_requests", json!({})).unwrap();
+    assert_eq!(prs[0]["status"], "Reviewing");
+}
@@ -41,6 +41,8 @@ interface Project {
   name: string;
   description: string | null;
   created_at: number;
+  sla_hours: number | null;
+  target_score: number | null;
 }
 
 interface PullRequest {
@@ -56,10 +58,32 @@ interface PullRequest {
   author_display_name: string | null;
   project_name: string | null;
   status: string;
+  waiting_on: 'reviewer' | 'author' | 'nobody';
   branch: string | null;
   score: number | null;
+  exclude_from_scoring: boolean;
   repository_owner: string | null;
   repository_name: string | null;
+  github_created_at: number | null;
+  github_updated_at: number | null;
+  last_viewed_at: number | null;
+  has_unread_changes: boolean;
+  reactions_total: number | null;
+  reactions_plus_one: number | null;
+  priority: "High" | "Medium" | "Low";
+  status_pinned: boolean;
+  commit_type: string | null;
+  commit_scope: string | null;
+  ticket_key: string | null;
+  is_issue: boolean;
+  added_by: string | null;
+  reviewer_count: number;
+  additions: number | null;
+  deletions: number | null;
+  changed_files: number | null;
+  project_missing: boolean;
+  author_missing: boolean;
+  display_key: string;
 }
 
 // 初始化模擬數據
@@ -428,7 +452,8 @@ const App = () => {
       setPrs(prs.map(p => p.id === id ? {
         ...p,
         project_name: newProjectName,
-        project_id: project.id
+        project_id: project.id,
+        project_missing: false
       } : p));
     } catch (error) {
       console.error('Failed to update PR project:', error);
@@ -436,6 +461,16 @@ const App = () => {
     }
   };
 
+  const openPrDetail = async (id: number) => {
+    setSelectedPrId(id);
+    try {
+      await invoke('mark_pr_viewed', { prId: id });
+      setPrs(prs.map(p => p.id === id ? { ...p, has_unread_changes: false } : p));
+    } catch (error) {
+      console.error('Failed to mark PR as viewed:', error);
+    }
+  };
+
   const handleSyncBranch = () => {
     setIsSyncing(true);
     setTimeout(() => setIsSyncing(false), 1500);
@@ -931,7 +966,7 @@ const App = () => {
                       ))
                     ) : filteredPrs.length > 0 ? (
                       filteredPrs.map((pr) => (
-                        <tr key={pr.id} className={`group ${isDarkMode ? 'hover:bg-slate-700/50' : 'hover:bg-slate-50/80'} transition-colors duration-150 cursor-pointer`} onClick={() => setSelectedPrId(pr.id)}>
+                        <tr key={pr.id} className={`group ${isDarkMode ? 'hover:bg-slate-700/50' : 'hover:bg-slate-50/80'} transition-colors duration-150 cursor-pointer`} onClick={() => openPrDetail(pr.id)}>
                           <td className="px-6 py-4">
                             <div className="flex flex-col gap-1.5">
                               <div className="flex items-center gap-2">
@@ -939,7 +974,7 @@ const App = () => {
                                 <span className={`text-sm font-semibold ${themeClasses.text} group-hover:text-indigo-600 transition-colors truncate`}>{pr.title}</span>
                               </div>
                               <div className="flex items-center gap-2 text-[11px]">
-                                <span className={`font-medium px-2 py-0.5 rounded ${isDarkMode ? 'bg-indigo-900/30 text-indigo-300' : 'bg-indigo-50 text-indigo-600'}`}>{pr.project_name}</span>
+                                <span className={`font-medium px-2 py-0.5 rounded ${isDarkMode ? 'bg-indigo-900/30 text-indigo-300' : 'bg-indigo-50 text-indigo-600'}`}>{pr.project_missing ? 'Missing project' : pr.project_name}</span>
                                 {pr.score && (
                                   <span className={`flex items-center gap-1 ml-2 font-black px-2 py-0.5 rounded shadow-sm ${isDarkMode ? 'bg-emerald-900/30 text-emerald-300' : 'bg-emerald-50 text-emerald-600'}`}>
                                     <Trophy size={10} /> {pr.score}/10